@@ -52,6 +52,8 @@ pub enum QueryError {
     #[error("Function call returned an error: {error_message}")]
     ContractExecutionError {
         error_message: String,
+        /// Wasm frames that led to the error, innermost first.
+        backtrace: Option<Vec<near_primitives::views::WasmFrameView>>,
         block_height: near_primitives::types::BlockHeight,
         block_hash: near_primitives::hash::CryptoHash,
     },
@@ -8,10 +8,11 @@ use near_primitives::sharding::{ChunkHash, ShardChunkHeader};
 use near_primitives::stateless_validation::ChunkProductionKey;
 use near_primitives::types::{BlockHeight, ShardId};
 use near_primitives::views::{
-    BlockProcessingInfo, BlockProcessingStatus, ChainProcessingInfo, ChunkProcessingInfo,
-    ChunkProcessingStatus, DroppedReason,
+    BlockProcessingInfo, BlockProcessingStatus, BlockTimelineStage, BlockTimelineStageView,
+    BlockTimelineView, ChainProcessingInfo, ChunkProcessingInfo, ChunkProcessingStatus,
+    DroppedReason,
 };
-use std::collections::{BTreeMap, HashMap, hash_map::Entry};
+use std::collections::{BTreeMap, HashMap, HashSet, hash_map::Entry};
 use std::mem;
 use std::num::NonZeroUsize;
 use time::ext::InstantExt as _;
@@ -50,6 +51,10 @@ pub struct BlockTrackingStats {
     /// Timestamp when block was received or self-produced.
     pub received_timestamp: Instant,
     pub received_utc_timestamp: Utc,
+    /// Timestamp set by the block producer in the header.
+    pub block_timestamp: Utc,
+    /// Last final block according to the header, used to track when blocks become final.
+    pub last_final_block: CryptoHash,
     /// Timestamp when block was put to the orphan pool, if it ever was
     pub orphaned_timestamp: Option<Instant>,
     /// Timestamp when block was put to the missing chunks pool
@@ -62,8 +67,15 @@ pub struct BlockTrackingStats {
     pub removed_from_missing_chunks_timestamp: Option<Instant>,
     /// Timestamp when block was moved out of the pending execution pool
     pub removed_from_pending_timestamp: Option<Instant>,
+    /// Timestamps of applying the chunks of the block
+    pub apply_chunks: Option<ApplyChunksTimestamps>,
     /// Timestamp when block was done processing
     pub processed_timestamp: Option<Instant>,
+    /// Timestamp when approvals for the block with enough stake were received, only known to
+    /// the block producer of the next height
+    pub approval_quorum_timestamp: Option<Instant>,
+    /// Timestamp when a block making this block final was done processing
+    pub final_timestamp: Option<Instant>,
     /// Whether the block is not processed because of different reasons
     pub dropped: Option<DroppedReason>,
     /// Stores the error message encountered during the processing of this block
@@ -73,6 +85,25 @@ pub struct BlockTrackingStats {
     pub chunks: Vec<Option<ChunkHash>>,
 }
 
+/// Timestamps of applying the chunks of a block.
+#[derive(Debug, Clone)]
+pub struct ApplyChunksTimestamps {
+    pub started: Instant,
+    pub finished: Instant,
+    /// Start and end of applying the chunk of each shard.
+    pub shards: Vec<(ShardId, Instant, Instant)>,
+}
+
+/// Timestamps of receiving the endorsements of a chunk, recorded by the chunk endorsement
+/// tracker of the client.
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkEndorsementTimestamps {
+    /// Timestamp when the first endorsement of the chunk was received.
+    pub first_received: Utc,
+    /// Timestamp when endorsements of the chunk with enough stake were received.
+    pub quorum_reached: Option<Utc>,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct OptimisticBlockStats {
     /// Timestamp when the optimistic block became ready for processing.
@@ -220,13 +251,18 @@ impl BlocksDelayTracker {
         entry.insert(BlockTrackingStats {
             received_timestamp: self.clock.now(),
             received_utc_timestamp: self.clock.now_utc(),
+            block_timestamp: block.header().timestamp(),
+            last_final_block: *block.header().last_final_block(),
             orphaned_timestamp: None,
             missing_chunks_timestamp: None,
             pending_execution_timestamp: None,
             removed_from_orphan_timestamp: None,
             removed_from_missing_chunks_timestamp: None,
             removed_from_pending_timestamp: None,
+            apply_chunks: None,
             processed_timestamp: None,
+            approval_quorum_timestamp: None,
+            final_timestamp: None,
             dropped: None,
             error: None,
             chunks,
@@ -304,6 +340,26 @@ impl BlocksDelayTracker {
         }
     }
 
+    pub fn mark_block_chunks_applied(
+        &mut self,
+        block_hash: &CryptoHash,
+        apply_chunks: ApplyChunksTimestamps,
+    ) {
+        if let Some(block_entry) = self.blocks.get_mut(block_hash) {
+            block_entry.apply_chunks = Some(apply_chunks);
+        } else {
+            tracing::error!(target: "blocks_delay_tracker", ?block_hash, "block chunks were applied but block was not marked received");
+        }
+    }
+
+    /// Records when approvals for the block passed the doomslug threshold. Approvals may arrive
+    /// before the block, in which case they are not recorded.
+    pub fn mark_block_approval_quorum(&mut self, block_hash: &CryptoHash, timestamp: Instant) {
+        if let Some(block_entry) = self.blocks.get_mut(block_hash) {
+            block_entry.approval_quorum_timestamp.get_or_insert(timestamp);
+        }
+    }
+
     pub fn record_optimistic_block_ready(&mut self, block_height: BlockHeight) {
         let entry = self
             .optimistic_blocks
@@ -384,8 +440,13 @@ impl BlocksDelayTracker {
         block_hash: &CryptoHash,
         new_head: Option<Tip>,
     ) {
+        let now = self.clock.now();
         if let Some(processed_block) = self.blocks.get_mut(&block_hash) {
-            processed_block.processed_timestamp = Some(self.clock.now());
+            processed_block.processed_timestamp = Some(now);
+            let last_final_block = processed_block.last_final_block;
+            if let Some(final_block) = self.blocks.get_mut(&last_final_block) {
+                final_block.final_timestamp.get_or_insert(now);
+            }
         }
         // To get around the rust reference scope check
         if let Some(processed_block) = self.blocks.get(&block_hash) {
@@ -513,6 +574,171 @@ impl BlocksDelayTracker {
             }
         })
     }
+
+    fn get_block_timeline(
+        &self,
+        block_hash: &CryptoHash,
+        chain: &Chain,
+        endorsement_timestamps: &dyn Fn(&ChunkHash) -> Option<ChunkEndorsementTimestamps>,
+    ) -> Option<BlockTimelineView> {
+        let block_stats = self.blocks.get(block_hash)?;
+        let height = self
+            .blocks_height_map
+            .iter()
+            .find(|(_, hashes)| hashes.contains(block_hash))
+            .map(|(height, _)| *height)?;
+        let received = block_stats.received_timestamp;
+        let received_utc = block_stats.received_utc_timestamp;
+        let ms = |timestamp: Instant| {
+            timestamp.signed_duration_since(received).whole_milliseconds() as i64
+        };
+        let utc_ms = |timestamp: Utc| (timestamp - received_utc).whole_milliseconds() as i64;
+        let stage = |stage, shard_id, start_ms: i64, end_ms: Option<i64>| BlockTimelineStageView {
+            stage,
+            shard_id,
+            // Timestamps recorded by different components may slightly disagree.
+            start_ms: end_ms.map_or(start_ms, |end_ms| start_ms.min(end_ms)),
+            end_ms,
+        };
+
+        let mut stages = vec![stage(
+            BlockTimelineStage::Propagation,
+            None,
+            utc_ms(block_stats.block_timestamp),
+            Some(0),
+        )];
+        for chunk_hash in block_stats.chunks.iter().flatten() {
+            let Some(chunk_stats) = self.chunks.get(chunk_hash) else {
+                continue;
+            };
+            let shard_id = Some(chunk_stats.shard_id);
+            let completed = chunk_stats.completed_timestamp.map(utc_ms);
+            if let Some(start) = chunk_stats.requested_timestamp.map(utc_ms).or(completed) {
+                stages.push(stage(
+                    BlockTimelineStage::ChunkPartsCollection,
+                    shard_id,
+                    start,
+                    completed,
+                ));
+            }
+            let Some(endorsements) = endorsement_timestamps(chunk_hash) else {
+                continue;
+            };
+            let first_endorsement = utc_ms(endorsements.first_received);
+            if let Some(completed) = completed {
+                stages.push(stage(
+                    BlockTimelineStage::WitnessValidation,
+                    shard_id,
+                    completed,
+                    Some(first_endorsement),
+                ));
+            }
+            stages.push(stage(
+                BlockTimelineStage::EndorsementCollection,
+                shard_id,
+                first_endorsement,
+                endorsements.quorum_reached.map(utc_ms),
+            ));
+        }
+
+        let waits = [
+            (
+                BlockTimelineStage::Orphaned,
+                block_stats.orphaned_timestamp,
+                block_stats.removed_from_orphan_timestamp,
+            ),
+            (
+                BlockTimelineStage::WaitingForChunks,
+                block_stats.missing_chunks_timestamp,
+                block_stats.removed_from_missing_chunks_timestamp,
+            ),
+            (
+                BlockTimelineStage::PendingExecution,
+                block_stats.pending_execution_timestamp,
+                block_stats.removed_from_pending_timestamp,
+            ),
+        ];
+        let mut preprocessing_start = 0;
+        for (wait_stage, start, end) in waits {
+            if let Some(start) = start {
+                stages.push(stage(wait_stage, None, ms(start), end.map(ms)));
+            }
+            if let Some(end) = end {
+                preprocessing_start = preprocessing_start.max(ms(end));
+            }
+        }
+
+        let processed = block_stats.processed_timestamp.map(ms);
+        let apply_chunks = block_stats.apply_chunks.as_ref();
+        stages.push(stage(
+            BlockTimelineStage::Preprocessing,
+            None,
+            preprocessing_start,
+            apply_chunks.map(|apply_chunks| ms(apply_chunks.started)).or(processed),
+        ));
+        if let Some(apply_chunks) = apply_chunks {
+            for &(shard_id, start, end) in &apply_chunks.shards {
+                stages.push(stage(
+                    BlockTimelineStage::ApplyChunk,
+                    Some(shard_id),
+                    ms(start),
+                    Some(ms(end)),
+                ));
+            }
+            stages.push(stage(
+                BlockTimelineStage::Postprocessing,
+                None,
+                ms(apply_chunks.finished),
+                processed,
+            ));
+        }
+
+        if let Some(processed) = processed {
+            let approval_quorum = block_stats.approval_quorum_timestamp.map(ms);
+            if let Some(approval_quorum) = approval_quorum {
+                stages.push(stage(
+                    BlockTimelineStage::ApprovalQuorum,
+                    None,
+                    processed,
+                    Some(approval_quorum),
+                ));
+            }
+            if let Some(finalized) = block_stats.final_timestamp {
+                let start = approval_quorum.map_or(processed, |quorum| quorum.max(processed));
+                stages.push(stage(BlockTimelineStage::Finality, None, start, Some(ms(finalized))));
+            }
+        }
+
+        stages.sort_by_key(|stage| stage.start_ms);
+        let critical_path = critical_path(&stages);
+        Some(BlockTimelineView {
+            hash: *block_hash,
+            height,
+            received_timestamp: received_utc,
+            block_status: chain.get_block_status(block_hash, block_stats),
+            stages,
+            critical_path,
+        })
+    }
+}
+
+/// Walks back from the stage ending last, each time picking the stage which ended last before
+/// the current one started. Stages which are still in progress are treated as ending last.
+fn critical_path(stages: &[BlockTimelineStageView]) -> Vec<BlockTimelineStageView> {
+    let end = |stage: &BlockTimelineStageView| stage.end_ms.unwrap_or(i64::MAX);
+    let mut visited = HashSet::new();
+    let mut path = vec![];
+    let mut current = (0..stages.len()).max_by_key(|&index| end(&stages[index]));
+    while let Some(index) = current {
+        visited.insert(index);
+        let start_ms = stages[index].start_ms;
+        path.push(stages[index].clone());
+        current = (0..stages.len())
+            .filter(|index| !visited.contains(index) && end(&stages[*index]) <= start_ms)
+            .max_by_key(|&index| end(&stages[index]));
+    }
+    path.reverse();
+    path
 }
 
 impl Chain {
@@ -542,6 +768,17 @@ impl Chain {
         return BlockProcessingStatus::Unknown;
     }
 
+    /// Returns the timestamps of the stages of processing a block which is still tracked, i.e.
+    /// one of the recent blocks. `endorsement_timestamps` provides the timestamps of the
+    /// endorsements of the chunks, which are collected outside of the chain.
+    pub fn get_block_timeline(
+        &self,
+        block_hash: &CryptoHash,
+        endorsement_timestamps: &dyn Fn(&ChunkHash) -> Option<ChunkEndorsementTimestamps>,
+    ) -> Option<BlockTimelineView> {
+        self.blocks_delay_tracker.get_block_timeline(block_hash, self, endorsement_timestamps)
+    }
+
     pub fn get_chain_processing_info(&self) -> ChainProcessingInfo {
         let blocks_info: Vec<_> = self
             .blocks_delay_tracker
@@ -587,3 +824,41 @@ impl Chain {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::critical_path;
+    use near_primitives::types::ShardId;
+    use near_primitives::views::{BlockTimelineStage, BlockTimelineStageView};
+
+    fn stage(
+        stage: BlockTimelineStage,
+        shard_id: Option<u64>,
+        start_ms: i64,
+        end_ms: Option<i64>,
+    ) -> BlockTimelineStageView {
+        BlockTimelineStageView { stage, shard_id: shard_id.map(ShardId::new), start_ms, end_ms }
+    }
+
+    #[test]
+    fn test_critical_path() {
+        let stages = vec![
+            stage(BlockTimelineStage::Propagation, None, -300, Some(0)),
+            stage(BlockTimelineStage::Preprocessing, None, 0, Some(20)),
+            stage(BlockTimelineStage::ApplyChunk, Some(0), 20, Some(100)),
+            stage(BlockTimelineStage::ApplyChunk, Some(1), 20, Some(400)),
+            stage(BlockTimelineStage::Postprocessing, None, 400, Some(450)),
+            stage(BlockTimelineStage::ApprovalQuorum, None, 450, Some(900)),
+        ];
+        let path = critical_path(&stages);
+        let expected = [0, 1, 3, 4, 5].map(|index| stages[index].clone());
+        assert_eq!(path, expected);
+
+        // A stage in progress ends the critical path.
+        let mut stages = stages;
+        stages.push(stage(BlockTimelineStage::Finality, None, 900, None));
+        let path = critical_path(&stages);
+        assert_eq!(path.last(), stages.last());
+        assert_eq!(path.len(), 6);
+    }
+}
@@ -3,7 +3,7 @@ use crate::block_processing_utils::{
     ApplyChunksDoneWaiter, ApplyChunksStillApplying, BlockPreprocessInfo, BlockProcessingArtifact,
     BlocksInProcessing, OptimisticBlockInfo,
 };
-use crate::blocks_delay_tracker::BlocksDelayTracker;
+use crate::blocks_delay_tracker::{ApplyChunksTimestamps, BlocksDelayTracker};
 use crate::chain_update::ChainUpdate;
 use crate::crypto_hash_timer::CryptoHashTimer;
 use crate::lightclient::get_epoch_block_producers_view;
@@ -243,8 +243,11 @@ impl ApplyChunksResultCache {
     }
 }
 
-type BlockApplyChunksResult =
-    (BlockToApply, Vec<(ShardId, CachedShardUpdateKey, Result<ShardUpdateResult, Error>)>);
+type BlockApplyChunksResult = (
+    BlockToApply,
+    Vec<(ShardId, CachedShardUpdateKey, Result<ShardUpdateResult, Error>)>,
+    ApplyChunksTimestamps,
+);
 
 /// Parallel or sequential applying of chunks for tracked shards.
 /// Default behavior uses Rayon, but can be configured to use sequential processing for testing.
@@ -1414,9 +1417,13 @@ impl Chain {
     ) -> (Vec<AcceptedBlock>, HashMap<CryptoHash, Error>) {
         let mut accepted_blocks = vec![];
         let mut errors = HashMap::new();
-        while let Ok((block, apply_result)) = self.apply_chunks_receiver.try_recv() {
+        while let Ok((block, apply_result, apply_timestamps)) =
+            self.apply_chunks_receiver.try_recv()
+        {
             match block {
                 BlockToApply::Normal(block_hash) => {
+                    self.blocks_delay_tracker
+                        .mark_block_chunks_applied(&block_hash, apply_timestamps);
                     let apply_result = apply_result.into_iter().map(|res| (res.0, res.2)).collect();
                     match self.postprocess_ready_block(
                         block_hash,
@@ -1766,14 +1773,38 @@ impl Chain {
         let iteration_mode = self.apply_chunks_iteration_mode;
         self.apply_chunks_spawner.spawn("apply_chunks", move || {
             let apply_all_chunks_start_time = clock.now();
+            // Record when each shard was applied, for the block timeline.
+            let shard_timestamps =
+                Arc::new(parking_lot::Mutex::new(Vec::with_capacity(work.len())));
+            let work = work
+                .into_iter()
+                .map(|(shard_id, cached_shard_update_key, task)| -> UpdateShardJob {
+                    let clock = clock.clone();
+                    let shard_timestamps = shard_timestamps.clone();
+                    let task = Box::new(move |parent_span: &Span| {
+                        let start = clock.now();
+                        let result = task(parent_span);
+                        shard_timestamps.lock().push((shard_id, start, clock.now()));
+                        result
+                    });
+                    (shard_id, cached_shard_update_key, task)
+                })
+                .collect();
             // do_apply_chunks runs `work` in parallel, but still waits for all of them to finish
             let res = do_apply_chunks(iteration_mode, block.clone(), block_height, work);
+            let apply_all_chunks_end_time = clock.now();
             // If we encounter an error here, that means the receiver is deallocated and the client
             // thread is already shut down. The node is already crashed, so we can unwrap here
             metrics::APPLY_ALL_CHUNKS_TIME.with_label_values(&[block.as_ref()]).observe(
-                (clock.now().signed_duration_since(apply_all_chunks_start_time)).as_seconds_f64(),
+                (apply_all_chunks_end_time.signed_duration_since(apply_all_chunks_start_time))
+                    .as_seconds_f64(),
             );
-            sc.send((block, res)).unwrap();
+            let apply_timestamps = ApplyChunksTimestamps {
+                started: apply_all_chunks_start_time,
+                finished: apply_all_chunks_end_time,
+                shards: std::mem::take(&mut *shard_timestamps.lock()),
+            };
+            sc.send((block, res, apply_timestamps)).unwrap();
             drop(apply_chunks_still_applying);
             if let Some(sender) = apply_chunks_done_sender {
                 sender.send(ApplyChunksDoneMessage {}.span_wrap());
@@ -1912,6 +1943,16 @@ impl Chain {
         if self.epoch_manager.is_next_block_epoch_start(block.header().prev_hash())? {
             // Keep in memory only these tries that we care about this or next epoch.
             self.runtime_adapter.get_tries().retain_memtries(&shards_cares_this_or_next_epoch);
+            if new_head.is_some() {
+                let epoch_info = self.epoch_manager.get_epoch_info(block.header().epoch_id())?;
+                let prev_header = self.get_block_header(block.header().prev_hash())?;
+                near_o11y::event_log::emit(near_o11y::event_log::NodeEvent::EpochTransition {
+                    height: block.header().height(),
+                    epoch_id: block.header().epoch_id().0,
+                    epoch_height: epoch_info.epoch_height(),
+                    prev_epoch_id: prev_header.epoch_id().0,
+                });
+            }
         }
 
         self.pending_state_patch.clear();
@@ -3911,6 +3952,92 @@ impl Chain {
     pub fn patch_state_in_progress(&self) -> bool {
         !self.pending_state_patch.is_empty()
     }
+
+    /// Rolls the chain back to `tip`, a block on the canonical chain, abandoning the blocks built
+    /// on top of it. Flat storage and memtries of the shards with flat storage are rebuilt from
+    /// the state as of `tip`.
+    ///
+    /// Heights are not reused, the next block is produced on top of `tip` at a height above all
+    /// the blocks produced so far.
+    #[cfg(feature = "sandbox")]
+    pub fn sandbox_revert_to(&mut self, tip: &Tip) -> Result<(), Error> {
+        if self.tail()? > tip.height {
+            return Err(Error::Other(format!(
+                "block {} at height {} is already garbage collected",
+                tip.last_block_hash, tip.height
+            )));
+        }
+        if self.get_block_hash_by_height(tip.height)? != tip.last_block_hash {
+            return Err(Error::Other(format!(
+                "block {} at height {} is not on the canonical chain",
+                tip.last_block_hash, tip.height
+            )));
+        }
+        self.pending_state_patch.clear();
+
+        let header = self.get_block_header(&tip.last_block_hash)?;
+        let flat_head = near_store::flat::BlockInfo {
+            hash: tip.last_block_hash,
+            height: tip.height,
+            prev_hash: tip.prev_block_hash,
+        };
+        let flat_storage_manager = self.runtime_adapter.get_flat_storage_manager();
+        let tries = self.runtime_adapter.get_tries();
+        let store = self.chain_store.store();
+        for shard_uid in self.epoch_manager.get_shard_layout(&tip.epoch_id)?.shard_uids() {
+            if flat_storage_manager.get_flat_storage_for_shard(shard_uid).is_none() {
+                continue;
+            }
+            let state_root = *self.get_chunk_extra(&tip.last_block_hash, &shard_uid)?.state_root();
+            tracing::info!(target: "chain", %shard_uid, ?state_root, "reverting flat storage");
+
+            let mut store_update = store.flat_store().store_update();
+            flat_storage_manager.remove_flat_storage_for_shard(shard_uid, &mut store_update)?;
+            store_update.commit()?;
+
+            let mut store_update = store.flat_store().store_update();
+            let trie = tries.get_view_trie_for_shard(shard_uid, state_root);
+            for item in trie.disk_iter()? {
+                let (key, value) = item?;
+                store_update.set(
+                    shard_uid,
+                    key,
+                    Some(near_primitives::state::FlatStateValue::on_disk(&value)),
+                );
+            }
+            store_update.set_flat_storage_status(
+                shard_uid,
+                near_store::flat::FlatStorageStatus::Ready(
+                    near_store::flat::FlatStorageReadyStatus { flat_head },
+                ),
+            );
+            store_update.commit()?;
+            flat_storage_manager.create_flat_storage_for_shard(shard_uid)?;
+
+            if tries.get_memtries(shard_uid).is_some() {
+                tries.load_memtrie(&shard_uid, Some(state_root), false)?;
+            }
+        }
+
+        let last_final_hash = *header.last_final_block();
+        let final_head = if last_final_hash == CryptoHash::default() {
+            Tip::from_header(self.genesis())
+        } else {
+            Tip::from_header(&*self.get_block_header(&last_final_hash)?)
+        };
+        // The abandoned blocks stay in the store, but they must not be found by height, otherwise
+        // the new blocks produced at the same heights are not considered canonical.
+        let abandoned_height = self.head()?.height.max(self.header_head()?.height);
+        let mut chain_store_update = self.mut_chain_store().store_update();
+        for height in tip.height + 1..=abandoned_height {
+            chain_store_update.chain_store_cache_update.height_to_hashes.insert(height, None);
+        }
+        chain_store_update.save_body_head(tip)?;
+        chain_store_update.save_header_head(tip)?;
+        chain_store_update.save_final_head(&final_head)?;
+        chain_store_update.commit()?;
+        Ok(())
+    }
 }
 
 #[instrument(
@@ -514,6 +514,7 @@ impl<'a> ChainUpdate<'a> {
                 last_validator_proposals: chunk_header.prev_validator_proposals(),
                 is_new_chunk: true,
                 on_post_state_ready: None,
+                execution_trace_collector: None,
             },
             ApplyChunkBlockContext {
                 block_type: BlockType::Normal,
@@ -633,6 +634,7 @@ impl<'a> ChainUpdate<'a> {
                 gas_limit: chunk_extra.gas_limit(),
                 is_new_chunk: false,
                 on_post_state_ready: None,
+                execution_trace_collector: None,
             },
             ApplyChunkBlockContext::from_header(
                 &block_header,
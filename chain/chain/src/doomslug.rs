@@ -1,5 +1,6 @@
 use crate::doomslug::trackable::TrackableBlockHeightValue;
 use crate::metrics;
+use crate::signing_history::{SigningHistory, SigningKind};
 use near_async::time::{Clock, Duration, Instant, Utc};
 use near_client_primitives::debug::{ApprovalAtHeightStatus, ApprovalHistoryEntry};
 use near_crypto::Signature;
@@ -158,6 +159,9 @@ pub struct Doomslug {
     endorsement_pending: bool,
     /// Information to track the timer (see `start_timer` routine in the paper)
     timer: DoomslugTimer,
+    /// Consulted before signing an approval so that we never approve two different things for
+    /// the same target height, even across restarts.
+    signing_history: SigningHistory,
 
     /// Approvals that were created by this doomslug instance (for debugging only).
     /// Keeps up to MAX_HISTORY_SIZE entries.
@@ -388,6 +392,7 @@ impl Doomslug {
         max_delay: Duration,
         chunk_wait_mult: Rational32,
         threshold_mode: DoomslugThresholdMode,
+        signing_history: SigningHistory,
     ) -> Self {
         Doomslug {
             clock: clock.clone(),
@@ -418,6 +423,7 @@ impl Doomslug {
                 max_delay,
                 chunk_wait_mult,
             },
+            signing_history,
             history: VecDeque::new(),
         }
     }
@@ -567,9 +573,23 @@ impl Doomslug {
         target_height: BlockHeight,
         signer: &Option<Arc<ValidatorSigner>>,
     ) -> Option<Approval> {
-        signer.as_ref().map(|signer| {
-            Approval::new(self.tip.block_hash, self.tip.height, target_height, &*signer)
-        })
+        let signer = signer.as_ref()?;
+        let inner = ApprovalInner::new(&self.tip.block_hash, self.tip.height, target_height);
+        self.signing_history
+            .check_and_record(
+                target_height,
+                SigningKind::Approval,
+                None,
+                CryptoHash::hash_borsh(&inner),
+            )
+            .ok()?;
+        match Approval::try_new(self.tip.block_hash, self.tip.height, target_height, &*signer) {
+            Ok(approval) => Some(approval),
+            Err(err) => {
+                tracing::error!(target: "doomslug", target_height, ?err, "failed to sign the approval, skipping it");
+                None
+            }
+        }
     }
 
     /// Determines whether a block has enough approvals to be produced.
@@ -697,6 +717,20 @@ impl Doomslug {
         let _ = self.on_approval_message_internal(approval, stakes);
     }
 
+    /// Returns when the approvals `approval_inner` for `target_height` passed the threshold, if
+    /// they did and are still in memory.
+    pub fn approvals_threshold_time(
+        &self,
+        target_height: BlockHeight,
+        approval_inner: &ApprovalInner,
+    ) -> Option<Instant> {
+        self.approval_trackers
+            .get(&target_height)?
+            .approval_trackers
+            .get(approval_inner)?
+            .time_passed_threshold
+    }
+
     /// Gets the current status of approvals for a given height.
     /// It will only work for heights that we have in memory, that is that are not older than MAX_HEIGHTS_BEFORE_TO_STORE_APPROVALS
     /// blocks from the head.
@@ -803,6 +837,7 @@ mod tests {
     use crate::doomslug::{
         DoomslugApprovalsTrackersAtHeight, DoomslugBlockProductionReadiness, DoomslugThresholdMode,
     };
+    use crate::signing_history::SigningHistory;
     use near_async::time::{Duration, FakeClock, Utc};
     use near_crypto::{KeyType, SecretKey};
     use near_primitives::block::{Approval, ApprovalInner};
@@ -826,6 +861,7 @@ mod tests {
             Duration::milliseconds(3000),
             Rational32::new(1, 3),
             DoomslugThresholdMode::TwoThirds,
+            SigningHistory::disabled(),
         );
 
         // Set a new tip, must produce an endorsement
@@ -985,6 +1021,7 @@ mod tests {
             Duration::milliseconds(3000),
             Rational32::new(1, 3),
             DoomslugThresholdMode::TwoThirds,
+            SigningHistory::disabled(),
         );
 
         // In the comments below the format is
@@ -2,18 +2,24 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::{fmt, io};
 
+use borsh::{BorshDeserialize, BorshSerialize};
 use itertools::Itertools;
-use near_chain_configs::GCConfig;
+use near_chain_configs::{GCConfig, RetainedAccountsConfig};
 use near_chain_primitives::Error;
 use near_epoch_manager::EpochManagerAdapter;
 use near_epoch_manager::shard_assignment::shard_id_to_uid;
 use near_epoch_manager::shard_tracker::ShardTracker;
 use near_primitives::block::Block;
 use near_primitives::hash::CryptoHash;
+use near_primitives::receipt::Receipt;
 use near_primitives::shard_layout::{ShardLayout, get_block_shard_uid};
 use near_primitives::state_sync::{StateHeaderKey, StatePartKey};
 use near_primitives::stateless_validation::spice_chunk_endorsement::SpiceStoredVerifiedEndorsement;
-use near_primitives::types::{BlockHeight, BlockHeightDelta, EpochId, NumBlocks, ShardId};
+use near_primitives::transaction::SignedTransaction;
+use near_primitives::trie_key::trie_key_parsers::parse_account_id_from_raw_key;
+use near_primitives::types::{
+    AccountId, BlockHeight, BlockHeightDelta, EpochId, NumBlocks, ShardId,
+};
 use near_primitives::utils::{
     get_block_shard_id, get_block_shard_id_rev, get_endorsements_key_prefix,
     get_execution_results_key, get_outcome_id_block_hash, get_receipt_proof_key,
@@ -29,20 +35,132 @@ use crate::{Chain, ChainStore, ChainStoreAccess, ChainStoreUpdate, metrics};
 #[derive(Clone)]
 pub enum GCMode {
     Fork(ShardTries),
-    Canonical(ShardTries),
-    StateSync { clear_block_info: bool },
+    /// The data touching the retained accounts, if given, is kept.
+    Canonical(ShardTries, Option<Arc<RetainedAccounts>>),
+    StateSync {
+        clear_block_info: bool,
+    },
 }
 
 impl fmt::Debug for GCMode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             GCMode::Fork(_) => write!(f, "GCMode::Fork"),
-            GCMode::Canonical(_) => write!(f, "GCMode::Canonical"),
+            GCMode::Canonical(..) => write!(f, "GCMode::Canonical"),
             GCMode::StateSync { .. } => write!(f, "GCMode::StateSync"),
         }
     }
 }
 
+/// Accounts whose data is kept beyond the regular garbage collection, see
+/// `GCConfig::retained_accounts`.
+#[derive(Debug)]
+pub struct RetainedAccounts {
+    accounts: HashSet<AccountId>,
+}
+
+impl RetainedAccounts {
+    pub fn new(config: &RetainedAccountsConfig) -> Self {
+        Self { accounts: config.accounts.iter().cloned().collect() }
+    }
+
+    fn contains(&self, account_id: &AccountId) -> bool {
+        self.accounts.contains(account_id)
+    }
+
+    fn touches_transaction(&self, transaction: &SignedTransaction) -> bool {
+        self.contains(transaction.transaction.signer_id())
+            || self.contains(transaction.transaction.receiver_id())
+    }
+
+    fn touches_receipt(&self, receipt: &Receipt) -> bool {
+        self.contains(receipt.predecessor_id()) || self.contains(receipt.receiver_id())
+    }
+
+    /// Rows of `DBCol::StateChanges` are keyed by the block hash followed by
+    /// the trie key.
+    fn touches_state_change(&self, key: &[u8]) -> bool {
+        key.get(CryptoHash::LENGTH..)
+            .and_then(|trie_key| parse_account_id_from_raw_key(trie_key).ok().flatten())
+            .is_some_and(|account_id| self.contains(&account_id))
+    }
+}
+
+/// The data of a garbage collected block kept because it touches the retained
+/// accounts. Stored in `DBCol::RetainedAccountData` so that it can be garbage
+/// collected once its retention expires.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default)]
+struct RetainedBlockData {
+    transactions: Vec<CryptoHash>,
+    receipts: Vec<CryptoHash>,
+    /// Ids of the outcomes in the block the row is keyed by.
+    outcome_ids: Vec<CryptoHash>,
+    /// Keys of the rows in `DBCol::StateChanges`.
+    state_changes: Vec<Vec<u8>>,
+}
+
+impl RetainedBlockData {
+    fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+            && self.receipts.is_empty()
+            && self.outcome_ids.is_empty()
+            && self.state_changes.is_empty()
+    }
+}
+
+/// Collects the data kept while garbage collecting a block on the canonical
+/// chain.
+struct AccountDataRetention {
+    accounts: Arc<RetainedAccounts>,
+    data: RetainedBlockData,
+    /// Receipts caused by the kept outcomes of the block.
+    caused_receipt_ids: HashSet<CryptoHash>,
+}
+
+impl AccountDataRetention {
+    fn new(accounts: Arc<RetainedAccounts>) -> Self {
+        Self { accounts, data: RetainedBlockData::default(), caused_receipt_ids: HashSet::new() }
+    }
+
+    fn retain_transaction(&mut self, transaction: &SignedTransaction) -> bool {
+        let retained = self.accounts.touches_transaction(transaction);
+        if retained {
+            self.data.transactions.push(transaction.get_hash());
+        }
+        retained
+    }
+
+    fn retain_receipt(&mut self, receipt: &Receipt) -> bool {
+        let retained = self.accounts.touches_receipt(receipt);
+        if retained {
+            self.data.receipts.push(*receipt.receipt_id());
+        }
+        retained
+    }
+
+    fn retain_state_change(&mut self, key: &[u8]) -> bool {
+        let retained = self.accounts.touches_state_change(key);
+        if retained {
+            self.data.state_changes.push(key.to_vec());
+        }
+        retained
+    }
+}
+
+/// The height is big-endian so that the rows are ordered by height.
+fn get_retained_account_data_key(height: BlockHeight, block_hash: &CryptoHash) -> Vec<u8> {
+    [&height.to_be_bytes()[..], block_hash.as_ref()].concat()
+}
+
+fn get_retained_account_data_key_rev(key: &[u8]) -> Result<(BlockHeight, CryptoHash), Error> {
+    let (height, block_hash) = key
+        .split_first_chunk::<8>()
+        .ok_or_else(|| Error::Other("invalid retained account data key".into()))?;
+    let block_hash = CryptoHash::try_from(block_hash)
+        .map_err(|_| Error::Other("invalid retained account data key".into()))?;
+    Ok((BlockHeight::from_be_bytes(*height), block_hash))
+}
+
 /// Both functions here are only used for testing as they create convenient
 /// wrappers that allow us to do correctness integration testing without having
 /// to fully spin up GCActor
@@ -226,6 +344,10 @@ impl ChainStore {
         )
         .entered();
 
+        let retained_accounts = gc_config
+            .retained_accounts
+            .as_ref()
+            .map(|config| Arc::new(RetainedAccounts::new(config)));
         let gc_fork_clean_step = gc_config.gc_fork_clean_step;
         let stop_height = tail.max(fork_tail.saturating_sub(gc_fork_clean_step));
         tracing::debug!(
@@ -290,7 +412,7 @@ impl ChainStore {
                 chain_store_update.clear_block_data(
                     epoch_manager.as_ref(),
                     *block_hash,
-                    GCMode::Canonical(tries.clone()),
+                    GCMode::Canonical(tries.clone(), retained_accounts.clone()),
                 )?;
                 gc_parent_shard_after_resharding(
                     &mut chain_store_update,
@@ -310,6 +432,50 @@ impl ChainStore {
             chain_store_update.commit()?;
         }
 
+        self.clear_expired_retained_account_data(
+            gc_config,
+            gc_stop_height,
+            epoch_manager.as_ref(),
+            &mut gc_blocks_remaining,
+        )
+    }
+
+    /// Garbage collects the data kept for the retained accounts once it falls
+    /// `num_extra_epochs_to_keep` epochs behind the regular garbage collection.
+    /// If the retention is not configured anymore, all of that data is collected.
+    fn clear_expired_retained_account_data(
+        &mut self,
+        gc_config: &GCConfig,
+        gc_stop_height: BlockHeight,
+        epoch_manager: &dyn EpochManagerAdapter,
+        gc_blocks_remaining: &mut NumBlocks,
+    ) -> Result<(), Error> {
+        let stop_height = match &gc_config.retained_accounts {
+            None => gc_stop_height,
+            Some(config) => {
+                let epoch_length =
+                    epoch_manager.get_epoch_config(&self.head()?.epoch_id)?.epoch_length;
+                match config.stop_height(gc_stop_height, epoch_length) {
+                    Some(stop_height) => stop_height,
+                    None => return Ok(()),
+                }
+            }
+        };
+        let upper_bound = stop_height.to_be_bytes();
+        let expired = self
+            .store()
+            .iter_range(DBCol::RetainedAccountData, None, Some(&upper_bound))
+            .take(*gc_blocks_remaining as usize)
+            .map(|item| {
+                item.and_then(|(key, value)| Ok((key, RetainedBlockData::try_from_slice(&value)?)))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        for (key, data) in expired {
+            let mut chain_store_update = self.store_update();
+            chain_store_update.clear_retained_account_data(&key, &data)?;
+            chain_store_update.commit()?;
+            *gc_blocks_remaining -= 1;
+        }
         Ok(())
     }
 
@@ -534,7 +700,7 @@ impl ChainStore {
         let mut chain_store_update = self.store_update();
         // The largest height of chunk we have in storage is head.height + 1
         let chunk_height = std::cmp::min(head.height + 2, sync_height);
-        chain_store_update.clear_chunk_data_and_headers(chunk_height)?;
+        chain_store_update.clear_chunk_data_and_headers(chunk_height, None)?;
         chain_store_update.commit()?;
 
         // clear all trie data
@@ -572,7 +738,11 @@ impl<'a> ChainStoreUpdate<'a> {
         Ok(())
     }
 
-    fn clear_chunk_data_and_headers(&mut self, min_chunk_height: BlockHeight) -> Result<(), Error> {
+    fn clear_chunk_data_and_headers(
+        &mut self,
+        min_chunk_height: BlockHeight,
+        mut retention: Option<&mut AccountDataRetention>,
+    ) -> Result<(), Error> {
         let chunk_tail = self.chunk_tail()?;
         for height in chunk_tail..min_chunk_height {
             let chunk_hashes = self.store().chunk_store().get_all_chunk_hashes_by_height(height)?;
@@ -581,6 +751,12 @@ impl<'a> ChainStoreUpdate<'a> {
                 let chunk = self.get_chunk(&chunk_hash)?;
                 debug_assert_eq!(chunk.height_created(), height);
                 for transaction in chunk.to_transactions() {
+                    if retention
+                        .as_deref_mut()
+                        .is_some_and(|retention| retention.retain_transaction(transaction))
+                    {
+                        continue;
+                    }
                     self.gc_col(DBCol::Transactions, transaction.get_hash().as_bytes());
                 }
 
@@ -588,6 +764,12 @@ impl<'a> ChainStoreUpdate<'a> {
                 if let Ok(partial_chunk) = partial_chunk {
                     for receipts in partial_chunk.prev_outgoing_receipts() {
                         for receipt in &receipts.0 {
+                            if retention
+                                .as_deref_mut()
+                                .is_some_and(|retention| retention.retain_receipt(receipt))
+                            {
+                                continue;
+                            }
                             self.gc_col(DBCol::Receipts, receipt.receipt_id().as_bytes());
                         }
                     }
@@ -696,7 +878,7 @@ impl<'a> ChainStoreUpdate<'a> {
         // 1. Garbage collect TrieChanges.
         self.gc_trie_changes(epoch_manager, block_hash, &gc_mode, &mut store_update)?;
 
-        if matches!(gc_mode, GCMode::Canonical(_)) {
+        if matches!(gc_mode, GCMode::Canonical(..)) {
             // If you know why do we do this in case of canonical chain please add a comment here.
             block_hash = *self.get_block_header(&block_hash)?.prev_hash();
         }
@@ -706,6 +888,12 @@ impl<'a> ChainStoreUpdate<'a> {
         let height = block.header().height();
         let epoch_id = block.header().epoch_id();
         let shard_layout = epoch_manager.get_shard_layout(epoch_id).expect("epoch id must exist");
+        let mut retention = match &gc_mode {
+            GCMode::Canonical(_, Some(accounts)) => {
+                Some(AccountDataRetention::new(accounts.clone()))
+            }
+            _ => None,
+        };
 
         // 2. Delete shard_id-indexed data (Receipts, State Headers and Parts, etc.)
         for shard_id in shard_layout.shard_ids() {
@@ -762,10 +950,13 @@ impl<'a> ChainStoreUpdate<'a> {
             .map(|item| item.map(|(key, _)| key))
             .collect::<io::Result<Vec<_>>>()?;
         for key in stored_state_changes {
+            if retention.as_mut().is_some_and(|retention| retention.retain_state_change(&key)) {
+                continue;
+            }
             self.gc_col(DBCol::StateChanges, &key);
         }
         self.gc_col(DBCol::BlockRefCount, block_hash.as_bytes());
-        self.gc_outcomes(&block)?;
+        self.gc_outcomes(&block, retention.as_mut())?;
         match gc_mode {
             GCMode::StateSync { clear_block_info: false } => {}
             _ => self.gc_col(DBCol::BlockInfo, block_hash.as_bytes()),
@@ -782,7 +973,7 @@ impl<'a> ChainStoreUpdate<'a> {
                 // 5. Forks only clearing
                 self.dec_block_refcount(block.header().prev_hash())?;
             }
-            GCMode::Canonical(_) => {
+            GCMode::Canonical(..) => {
                 // 6. Canonical Chain only clearing
                 // Delete chunks, chunk-indexed data and block headers
                 let mut min_chunk_height = self.tail()?;
@@ -791,7 +982,7 @@ impl<'a> ChainStoreUpdate<'a> {
                         min_chunk_height = chunk_header.height_created();
                     }
                 }
-                self.clear_chunk_data_and_headers(min_chunk_height)?;
+                self.clear_chunk_data_and_headers(min_chunk_height, retention.as_mut())?;
             }
             GCMode::StateSync { .. } => {
                 // 7. State Sync clearing
@@ -799,6 +990,19 @@ impl<'a> ChainStoreUpdate<'a> {
             }
         };
         self.merge(store_update.into());
+
+        // 8. Record the retained data so that it can be collected once its retention expires
+        if let Some(retention) = retention {
+            if !retention.data.is_empty() {
+                let mut store_update = self.store().store_update();
+                store_update.set_ser(
+                    DBCol::RetainedAccountData,
+                    &get_retained_account_data_key(height, &block_hash),
+                    &retention.data,
+                )?;
+                self.merge(store_update);
+            }
+        }
         Ok(())
     }
 
@@ -864,7 +1068,7 @@ impl<'a> ChainStoreUpdate<'a> {
                     // If the block is on a fork, we delete the state that's the result of applying this block
                     tries.revert_insertions(&trie_changes, shard_uid, store_update);
                 }
-                GCMode::Canonical(tries) => {
+                GCMode::Canonical(tries, _) => {
                     // If the block is on canonical chain, we delete the state that's before applying this block
                     tries.apply_deletions(&trie_changes, shard_uid, store_update);
                 }
@@ -943,7 +1147,7 @@ impl<'a> ChainStoreUpdate<'a> {
             self.gc_col(DBCol::StateChanges, &key);
         }
         self.gc_col(DBCol::BlockRefCount, block_hash.as_bytes());
-        self.gc_outcomes(&block)?;
+        self.gc_outcomes(&block, None)?;
         self.gc_col(DBCol::BlockInfo, block_hash.as_bytes());
         self.gc_col(DBCol::StateDlInfos, block_hash.as_bytes());
         self.gc_col(DBCol::StateSyncNewChunks, block_hash.as_bytes());
@@ -1045,7 +1249,11 @@ impl<'a> ChainStoreUpdate<'a> {
         self.merge(store_update);
     }
 
-    fn gc_outcomes(&mut self, block: &Block) -> Result<(), Error> {
+    fn gc_outcomes(
+        &mut self,
+        block: &Block,
+        mut retention: Option<&mut AccountDataRetention>,
+    ) -> Result<(), Error> {
         let block_hash = block.hash();
         let store_update = self.store().store_update();
         for chunk_header in block.chunks().iter_new() {
@@ -1055,6 +1263,11 @@ impl<'a> ChainStoreUpdate<'a> {
             let outcome_ids =
                 self.chain_store().get_outcomes_by_block_hash_and_shard_id(block_hash, shard_id)?;
             for outcome_id in outcome_ids {
+                if let Some(retention) = retention.as_deref_mut() {
+                    if self.retain_outcome(retention, &outcome_id, block_hash)? {
+                        continue;
+                    }
+                }
                 self.gc_col(
                     DBCol::TransactionResultForBlock,
                     &get_outcome_id_block_hash(&outcome_id, block_hash),
@@ -1066,6 +1279,76 @@ impl<'a> ChainStoreUpdate<'a> {
         Ok(())
     }
 
+    /// Returns whether the outcome is kept because it belongs to a transaction
+    /// or receipt touching the retained accounts or to a receipt caused by one.
+    /// The receipts caused by a kept outcome are marked so that their outcomes
+    /// are kept as well.
+    fn retain_outcome(
+        &mut self,
+        retention: &mut AccountDataRetention,
+        outcome_id: &CryptoHash,
+        block_hash: &CryptoHash,
+    ) -> Result<bool, Error> {
+        let Some(outcome) =
+            self.chain_store().get_outcome_by_id_and_block_hash(outcome_id, block_hash)?
+        else {
+            return Ok(false);
+        };
+        // The marker of a receipt caused by an outcome in the same block is
+        // not committed yet, hence the in-memory set.
+        let caused_by_retained = retention.caused_receipt_ids.remove(outcome_id)
+            || self.store().get(DBCol::RetainedReceiptIds, outcome_id.as_ref())?.is_some();
+        if caused_by_retained {
+            self.gc_col(DBCol::RetainedReceiptIds, outcome_id.as_ref());
+        }
+        let retained = caused_by_retained
+            || retention.accounts.contains(&outcome.outcome.executor_id)
+            || self
+                .get_transaction(outcome_id)?
+                .is_some_and(|transaction| retention.accounts.touches_transaction(&transaction))
+            || self
+                .get_receipt(outcome_id)?
+                .is_some_and(|receipt| retention.accounts.touches_receipt(&receipt));
+        if !retained {
+            return Ok(false);
+        }
+        let mut store_update = self.store().store_update();
+        for receipt_id in &outcome.outcome.receipt_ids {
+            retention.caused_receipt_ids.insert(*receipt_id);
+            store_update.set(DBCol::RetainedReceiptIds, receipt_id.as_ref(), &[]);
+        }
+        self.merge(store_update);
+        retention.data.outcome_ids.push(*outcome_id);
+        Ok(true)
+    }
+
+    /// Garbage collects the data of a block which was kept because it touches
+    /// the retained accounts.
+    fn clear_retained_account_data(
+        &mut self,
+        key: &[u8],
+        data: &RetainedBlockData,
+    ) -> Result<(), Error> {
+        let (_, block_hash) = get_retained_account_data_key_rev(key)?;
+        for transaction_hash in &data.transactions {
+            self.gc_col(DBCol::Transactions, transaction_hash.as_bytes());
+        }
+        for receipt_id in &data.receipts {
+            self.gc_col(DBCol::Receipts, receipt_id.as_bytes());
+        }
+        for outcome_id in &data.outcome_ids {
+            self.gc_col(
+                DBCol::TransactionResultForBlock,
+                &get_outcome_id_block_hash(outcome_id, &block_hash),
+            );
+        }
+        for state_change_key in &data.state_changes {
+            self.gc_col(DBCol::StateChanges, state_change_key);
+        }
+        self.gc_col(DBCol::RetainedAccountData, key);
+        Ok(())
+    }
+
     fn gc_col(&mut self, col: DBCol, key: &[u8]) {
         let mut store_update = self.store().store_update();
         match col {
@@ -1204,6 +1487,12 @@ impl<'a> ChainStoreUpdate<'a> {
             DBCol::UncertifiedChunks => {
                 store_update.delete(col, key);
             }
+            DBCol::RetainedAccountData => {
+                store_update.delete(col, key);
+            }
+            DBCol::RetainedReceiptIds => {
+                store_update.delete(col, key);
+            }
             DBCol::DbVersion
             | DBCol::BlockMisc
             | DBCol::_BlockExtra
@@ -42,6 +42,7 @@ pub mod resharding;
 pub mod runtime;
 pub mod sharding;
 pub mod signature_verification;
+pub mod signing_history;
 mod soft_realtime_thread_pool;
 pub mod spice_chunk_application;
 pub mod spice_core;
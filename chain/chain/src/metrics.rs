@@ -372,3 +372,12 @@ pub(crate) static THREAD_POOL_MAX_NUM_THREADS: LazyLock<IntGaugeVec> = LazyLock:
     )
     .unwrap()
 });
+
+pub(crate) static SIGNING_HISTORY_REFUSED_TOTAL: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    try_create_int_counter_vec(
+        "near_signing_history_refused_total",
+        "Number of signatures refused because they conflict with the local signing history",
+        &["kind"],
+    )
+    .unwrap()
+});
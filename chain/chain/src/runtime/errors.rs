@@ -17,8 +17,11 @@ impl QueryError {
             node_runtime::state_viewer::errors::CallFunctionError::InternalError {
                 error_message,
             } => Self::InternalError { error_message, block_height, block_hash },
-            node_runtime::state_viewer::errors::CallFunctionError::VMError { error_message } => {
-                Self::ContractExecutionError { error_message, block_height, block_hash }
+            node_runtime::state_viewer::errors::CallFunctionError::VMError {
+                error_message,
+                backtrace,
+            } => {
+                Self::ContractExecutionError { error_message, backtrace, block_height, block_hash }
             }
         }
     }
@@ -16,6 +16,8 @@ use near_pool::types::TransactionGroupIterator;
 use near_primitives::account::{AccessKey, Account};
 use near_primitives::action::GlobalContractIdentifier;
 use near_primitives::apply::ApplyChunkReason;
+use near_primitives::bandwidth_scheduler::BlockBandwidthRequests;
+use near_primitives::block::BlockHeader;
 use near_primitives::congestion_info::{
     CongestionControl, ExtendedCongestionInfo, RejectTransactionReason, ShardAcceptsTransactions,
 };
@@ -49,12 +51,16 @@ use near_vm_runner::ContractCode;
 use near_vm_runner::{ContractRuntimeCache, precompile_contract};
 use node_runtime::adapter::ViewRuntimeAdapter;
 use node_runtime::config::tx_cost;
+use node_runtime::execution_trace::ExecutionTraceCollector;
+use node_runtime::simulation::{
+    SimulationError, SimulationLimits, SimulationResult, SimulationShard,
+};
 use node_runtime::state_viewer::{TrieViewer, ViewApplyState};
 use node_runtime::{
     ApplyState, Runtime, SignedValidPeriodTransactions, ValidatorAccountsUpdate,
     get_signer_and_access_key, validate_transaction, verify_and_charge_tx_ephemeral,
 };
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -68,6 +74,12 @@ pub mod test_utils;
 mod tests;
 mod trie_update_wrapper;
 
+/// Bounds on the work done by a single transaction simulation, which runs on
+/// the view client thread. Every round corresponds to one block of receipt
+/// delivery, and the gas bound is the gas limit of a single chunk.
+const SIMULATION_LIMITS: SimulationLimits =
+    SimulationLimits { max_rounds: 64, max_gas_burnt: Gas::from_teragas(1000) };
+
 /// Defines Nightshade state transition and validator rotation.
 /// TODO: this possibly should be merged with the runtime cargo or at least reconciled on the interfaces.
 pub struct NightshadeRuntime {
@@ -188,6 +200,7 @@ impl NightshadeRuntime {
             gas_limit,
             is_new_chunk,
             on_post_state_ready,
+            execution_trace_collector,
         } = chunk;
         let epoch_id = self.epoch_manager.get_epoch_id_from_prev_block(prev_block_hash)?;
         let validator_accounts_update = {
@@ -251,6 +264,12 @@ impl NightshadeRuntime {
             is_first_block_of_version
         );
 
+        let mut config = self.runtime_config_store.get_config(current_protocol_version).clone();
+        if execution_trace_collector.is_some() {
+            // The trace collector is only set by debugging tools, never when applying chunks for
+            // the consensus, so the failed calls can have their wasm backtraces captured.
+            config = Arc::new(config.with_wasm_backtraces());
+        }
         let apply_state = ApplyState {
             apply_reason,
             block_height,
@@ -263,13 +282,14 @@ impl NightshadeRuntime {
             gas_limit: Some(gas_limit),
             random_seed,
             current_protocol_version,
-            config: self.runtime_config_store.get_config(current_protocol_version).clone(),
+            config,
             cache: Some(self.compiled_contract_cache.handle()),
             is_new_chunk,
             congestion_info,
             bandwidth_requests,
             trie_access_tracker_state: Default::default(),
             on_post_state_ready,
+            execution_trace_collector,
         };
 
         let instant = Instant::now();
@@ -533,6 +553,51 @@ impl NightshadeRuntime {
         }
         Ok(())
     }
+
+    /// Compiles up to `limit` of the most frequently executed contracts, as tracked by the
+    /// compiled contract cache, that are missing from the cache for the current head's protocol
+    /// version.
+    ///
+    /// This is meant to be run in the background after a restart, so that a cache invalidated by
+    /// a VM configuration change (or pruned by its size limit) does not slow down chunk
+    /// application. Returns the number of contracts submitted for compilation.
+    pub fn warmup_compiled_contract_cache(&self, limit: usize) -> Result<usize, Error> {
+        let code_hashes = self.compiled_contract_cache.hottest_contracts(limit);
+        if code_hashes.is_empty() {
+            return Ok(0);
+        }
+        let head = self.store.chain_store().head()?;
+        let protocol_version = self.epoch_manager.get_epoch_protocol_version(&head.epoch_id)?;
+        let runtime_config = self.runtime_config_store.get_config(protocol_version);
+        let shard_layout = self.epoch_manager.get_shard_layout(&head.epoch_id)?;
+        let trie_store = self.tries.store();
+        let mut contract_codes = Vec::new();
+        for code_hash in code_hashes {
+            let cached = near_vm_runner::contract_cached(
+                Arc::clone(&runtime_config.wasm_config),
+                self.compiled_contract_cache.as_ref(),
+                code_hash,
+            );
+            if matches!(cached, Ok(true)) {
+                continue;
+            }
+            // The shard the contract is deployed to is not known here, so look in all of them.
+            let code = shard_layout
+                .shard_uids()
+                .find_map(|shard_uid| trie_store.get(shard_uid, &code_hash).ok());
+            match code {
+                Some(code) => {
+                    contract_codes.push(ContractCode::new(code.to_vec(), Some(code_hash)))
+                }
+                None => {
+                    tracing::debug!(target: "runtime", %code_hash, "contract code for cache warmup not found")
+                }
+            }
+        }
+        let num_contracts = contract_codes.len();
+        self.precompile_contracts(&head.epoch_id, contract_codes)?;
+        Ok(num_contracts)
+    }
 }
 
 fn get_epoch_start_height_from_archival_head(
@@ -1093,8 +1158,10 @@ impl RuntimeAdapter for NightshadeRuntime {
                     block_hash: *block_hash,
                 })
             }
-            QueryRequest::CallFunction { account_id, method_name, args } => {
+            QueryRequest::CallFunction { account_id, method_name, args, trace } => {
                 let mut logs = vec![];
+                let execution_trace_collector =
+                    trace.then(|| Arc::new(ExecutionTraceCollector::default()));
                 let (epoch_height, current_protocol_version) = {
                     let epoch_manager = self.epoch_manager.read();
                     let epoch_info = epoch_manager.get_epoch_info(epoch_id).map_err(|err| {
@@ -1122,6 +1189,7 @@ impl RuntimeAdapter for NightshadeRuntime {
                         &mut logs,
                         self.epoch_manager.as_ref(),
                         current_protocol_version,
+                        execution_trace_collector.clone(),
                     )
                     .map_err(|err| {
                         crate::near_chain_primitives::error::QueryError::from_call_function_error(
@@ -1134,6 +1202,8 @@ impl RuntimeAdapter for NightshadeRuntime {
                     kind: QueryResponseKind::CallResult(CallResult {
                         result: call_function_result,
                         logs,
+                        trace: execution_trace_collector
+                            .and_then(|collector| collector.take().pop()),
                     }),
                     block_height,
                     block_hash: *block_hash,
@@ -1250,6 +1320,65 @@ impl RuntimeAdapter for NightshadeRuntime {
         }
     }
 
+    fn simulate_transaction(
+        &self,
+        prev_block: &BlockHeader,
+        state_roots: &HashMap<ShardId, StateRoot>,
+        transaction: &SignedTransaction,
+        verify_signature: bool,
+    ) -> Result<SimulationResult, Error> {
+        let prev_block_hash = prev_block.hash();
+        let epoch_id = self.epoch_manager.get_epoch_id_from_prev_block(prev_block_hash)?;
+        let epoch_height = self.epoch_manager.get_epoch_height_from_prev_block(prev_block_hash)?;
+        let current_protocol_version = self.epoch_manager.get_epoch_protocol_version(&epoch_id)?;
+        let config = self.runtime_config_store.get_config(current_protocol_version);
+
+        let mut shards = BTreeMap::new();
+        for (&shard_id, state_root) in state_roots {
+            let trie = self.get_view_trie_for_shard(shard_id, prev_block_hash, *state_root)?;
+            let apply_state = ApplyState {
+                apply_reason: ApplyChunkReason::ViewTrackedShard,
+                block_height: prev_block.height() + 1,
+                prev_block_hash: *prev_block_hash,
+                shard_id,
+                epoch_id,
+                epoch_height,
+                gas_price: prev_block.next_gas_price(),
+                block_timestamp: prev_block.raw_timestamp(),
+                gas_limit: None,
+                random_seed: *prev_block.random_value(),
+                current_protocol_version,
+                config: config.clone(),
+                cache: Some(self.compiled_contract_cache.handle()),
+                is_new_chunk: true,
+                congestion_info: Default::default(),
+                bandwidth_requests: BlockBandwidthRequests::empty(),
+                trie_access_tracker_state: Default::default(),
+                on_post_state_ready: None,
+                execution_trace_collector: None,
+            };
+            let state_update = TrieUpdate::new(trie);
+            shards.insert(shard_id, SimulationShard { apply_state, state_update });
+        }
+
+        self.runtime
+            .simulate_transaction(
+                &epoch_id,
+                shards,
+                transaction,
+                verify_signature,
+                SIMULATION_LIMITS,
+                self.epoch_manager.as_ref(),
+            )
+            .map_err(|err| match err {
+                SimulationError::Runtime(RuntimeError::StorageError(err)) => {
+                    Error::StorageError(err)
+                }
+                SimulationError::Runtime(RuntimeError::ValidatorError(err)) => err.into(),
+                err => Error::Other(err.to_string()),
+            })
+    }
+
     // Wrapper to get the metrics.
     fn obtain_state_part(
         &self,
@@ -1548,6 +1677,7 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
         logs: &mut Vec<String>,
         epoch_info_provider: &dyn EpochInfoProvider,
         current_protocol_version: ProtocolVersion,
+        execution_trace_collector: Option<Arc<ExecutionTraceCollector>>,
     ) -> Result<Vec<u8>, node_runtime::state_viewer::errors::CallFunctionError> {
         let state_update = self.tries.new_trie_update_view(*shard_uid, state_root);
         let view_state = ViewApplyState {
@@ -1559,6 +1689,7 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
             block_timestamp,
             current_protocol_version,
             cache: Some(self.compiled_contract_cache.handle()),
+            execution_trace_collector,
         };
         self.trie_viewer.call_function(
             state_update,
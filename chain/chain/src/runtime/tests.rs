@@ -246,6 +246,7 @@ impl TestEnv {
                     gas_limit,
                     is_new_chunk: true,
                     on_post_state_ready: None,
+                    execution_trace_collector: None,
                 },
                 ApplyChunkBlockContext {
                     block_type: BlockType::Normal,
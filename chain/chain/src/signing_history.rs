@@ -0,0 +1,465 @@
+//! Local record of everything this validator has signed, used to avoid double signing.
+//!
+//! The history lives in its own file next to the node config rather than in the chain
+//! database. Restoring the database from a backup or moving it to a standby machine therefore
+//! does not bring back a stale view of what has already been signed. The file is an append-only
+//! log of JSON lines which is fsynced before a signature is released, and is periodically
+//! compacted to keep only the most recent heights.
+//!
+//! Signatures are keyed by height, kind and shard. Signing the same message again is allowed,
+//! signing a different message for an existing key is refused. Heights below the pruning
+//! watermark are refused as well, since the history can no longer tell whether they conflict.
+//!
+//! The history is opened by a single process at a time: an exclusive lock is held on a file next
+//! to it for as long as it is open, so e.g. an import can't race with a running node.
+
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{BlockHeight, BlockHeightDelta, ShardId};
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// How many heights below the highest signed height are kept in the history.
+const RETAINED_HEIGHTS: BlockHeightDelta = 10_000;
+
+/// Number of appended records after which the file is rewritten without pruned entries.
+const COMPACTION_INTERVAL: usize = 100_000;
+
+#[derive(
+    serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SigningKind {
+    Block,
+    Approval,
+    ChunkEndorsement,
+}
+
+impl SigningKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SigningKind::Block => "block",
+            SigningKind::Approval => "approval",
+            SigningKind::ChunkEndorsement => "chunk_endorsement",
+        }
+    }
+}
+
+/// A single signed message. `hash` identifies the content that was signed, e.g. the block hash
+/// for blocks or the chunk hash for chunk endorsements.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SigningRecord {
+    pub height: BlockHeight,
+    pub kind: SigningKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shard_id: Option<ShardId>,
+    pub hash: CryptoHash,
+}
+
+/// Portable form of the history, as produced by `neard signing-history export`.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct SigningHistoryExport {
+    /// Nothing may be signed below this height.
+    pub pruned_below: BlockHeight,
+    pub records: Vec<SigningRecord>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SigningHistoryError {
+    #[error(
+        "refusing to sign {kind:?} at height {height} for shard {shard_id:?}: already signed {existing}, asked to sign {requested}"
+    )]
+    Conflict {
+        height: BlockHeight,
+        kind: SigningKind,
+        shard_id: Option<ShardId>,
+        existing: CryptoHash,
+        requested: CryptoHash,
+    },
+    #[error(
+        "refusing to sign {kind:?} at height {height}: signing history only covers heights from {pruned_below}"
+    )]
+    BelowPrunedHeight { height: BlockHeight, kind: SigningKind, pruned_below: BlockHeight },
+    #[error("signing history at {path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+    #[error("signing history at {path} is corrupted at line {line}: {source}")]
+    Corrupted { path: PathBuf, line: usize, source: serde_json::Error },
+    #[error("signing history at {path} is in use by another process, e.g. a running node")]
+    Locked { path: PathBuf },
+}
+
+/// Line format of the history file.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Entry {
+    Record(SigningRecord),
+    PrunedBelow(BlockHeight),
+}
+
+type RecordKey = (BlockHeight, SigningKind, Option<ShardId>);
+
+struct SigningHistoryInner {
+    path: PathBuf,
+    /// Holds the exclusive lock until the history is closed.
+    _lock: File,
+    file: File,
+    records: BTreeMap<RecordKey, CryptoHash>,
+    pruned_below: BlockHeight,
+    appended_since_compaction: usize,
+}
+
+/// Handle to the signing history, cheap to clone and shared by everything that signs on behalf
+/// of the validator. A disabled handle accepts everything and records nothing.
+#[derive(Clone, Default)]
+pub struct SigningHistory(Option<Arc<Mutex<SigningHistoryInner>>>);
+
+impl SigningHistory {
+    pub fn disabled() -> Self {
+        Self(None)
+    }
+
+    /// Opens the history at `path`, creating an empty one if the file does not exist yet.
+    ///
+    /// Fails with [`SigningHistoryError::Locked`] if another process has it open.
+    pub fn open(path: &Path) -> Result<Self, SigningHistoryError> {
+        let io_err = |source| SigningHistoryError::Io { path: path.to_path_buf(), source };
+        // The history file itself is replaced when compacted, so the lock is taken on a separate
+        // file which stays in place.
+        let mut lock_path = path.as_os_str().to_owned();
+        lock_path.push(".lock");
+        let lock = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .map_err(io_err)?;
+        match rustix::fs::flock(&lock, rustix::fs::FlockOperation::NonBlockingLockExclusive) {
+            Ok(()) => {}
+            Err(rustix::io::Errno::WOULDBLOCK) => {
+                return Err(SigningHistoryError::Locked { path: path.to_path_buf() });
+            }
+            Err(err) => return Err(io_err(err.into())),
+        }
+        let mut records = BTreeMap::new();
+        let mut pruned_below = 0;
+        match File::open(path) {
+            Ok(file) => {
+                let lines = BufReader::new(file).lines().collect::<Result<Vec<_>, _>>();
+                let lines = lines.map_err(io_err)?;
+                let num_lines = lines.len();
+                for (idx, line) in lines.into_iter().enumerate() {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let entry = match serde_json::from_str(&line) {
+                        Ok(entry) => entry,
+                        // The last line may be torn if the node crashed while appending. Since
+                        // the record was not synced, the signature was never released either.
+                        Err(err) if idx + 1 == num_lines => {
+                            tracing::warn!(target: "chain", ?path, ?err, "ignoring torn last line of signing history");
+                            continue;
+                        }
+                        Err(source) => {
+                            return Err(SigningHistoryError::Corrupted {
+                                path: path.to_path_buf(),
+                                line: idx + 1,
+                                source,
+                            });
+                        }
+                    };
+                    match entry {
+                        Entry::Record(record) => {
+                            records
+                                .entry((record.height, record.kind, record.shard_id))
+                                .or_insert(record.hash);
+                        }
+                        Entry::PrunedBelow(height) => pruned_below = pruned_below.max(height),
+                    }
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(io_err(err)),
+        }
+        records.retain(|(height, _, _), _| *height >= pruned_below);
+
+        // Rewriting on open drops torn lines and makes sure the file is writable.
+        let file = rewrite(path, pruned_below, &records)?;
+        let inner = SigningHistoryInner {
+            path: path.to_path_buf(),
+            _lock: lock,
+            file,
+            records,
+            pruned_below,
+            appended_since_compaction: 0,
+        };
+        Ok(Self(Some(Arc::new(Mutex::new(inner)))))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Checks that signing `hash` for the given key does not conflict with anything signed
+    /// before, and durably records it. The signature must not be released if this fails.
+    pub fn check_and_record(
+        &self,
+        height: BlockHeight,
+        kind: SigningKind,
+        shard_id: Option<ShardId>,
+        hash: CryptoHash,
+    ) -> Result<(), SigningHistoryError> {
+        let Some(inner) = &self.0 else {
+            return Ok(());
+        };
+        let result = inner.lock().check_and_record(SigningRecord { height, kind, shard_id, hash });
+        if let Err(err) = &result {
+            crate::metrics::SIGNING_HISTORY_REFUSED_TOTAL.with_label_values(&[kind.as_str()]).inc();
+            tracing::error!(target: "chain", %err, "refused to sign conflicting message");
+        }
+        result
+    }
+
+    /// Highest height at which anything was signed, if any.
+    pub fn highest_signed_height(&self) -> Option<BlockHeight> {
+        let inner = self.0.as_ref()?.lock();
+        inner.records.last_key_value().map(|((height, _, _), _)| *height)
+    }
+
+    /// Refuses signing anything below `height` from now on, e.g. because another node may have
+    /// signed up to that height with the same key.
+    pub fn forbid_below(&self, height: BlockHeight) -> Result<(), SigningHistoryError> {
+        let Some(inner) = &self.0 else {
+            return Ok(());
+        };
+        let mut inner = inner.lock();
+        if height <= inner.pruned_below {
+            return Ok(());
+        }
+        inner.append(&Entry::PrunedBelow(height))?;
+        inner.pruned_below = height;
+        inner.records.retain(|(record_height, _, _), _| *record_height >= height);
+        Ok(())
+    }
+
+    pub fn export(&self) -> SigningHistoryExport {
+        let Some(inner) = &self.0 else {
+            return SigningHistoryExport::default();
+        };
+        let inner = inner.lock();
+        let records = inner
+            .records
+            .iter()
+            .map(|(&(height, kind, shard_id), &hash)| SigningRecord {
+                height,
+                kind,
+                shard_id,
+                hash,
+            })
+            .collect();
+        SigningHistoryExport { pruned_below: inner.pruned_below, records }
+    }
+
+    /// Merges an exported history into this one. Fails without changing anything if any of the
+    /// imported records conflicts with an existing one.
+    pub fn import(&self, export: SigningHistoryExport) -> Result<(), SigningHistoryError> {
+        let Some(inner) = &self.0 else {
+            return Ok(());
+        };
+        let mut inner = inner.lock();
+        for record in &export.records {
+            if let Some(&existing) =
+                inner.records.get(&(record.height, record.kind, record.shard_id))
+            {
+                if existing != record.hash {
+                    return Err(SigningHistoryError::Conflict {
+                        height: record.height,
+                        kind: record.kind,
+                        shard_id: record.shard_id,
+                        existing,
+                        requested: record.hash,
+                    });
+                }
+            }
+        }
+        for record in export.records {
+            inner.records.insert((record.height, record.kind, record.shard_id), record.hash);
+        }
+        inner.pruned_below = inner.pruned_below.max(export.pruned_below);
+        let pruned_below = inner.pruned_below;
+        inner.records.retain(|(height, _, _), _| *height >= pruned_below);
+        inner.compact()
+    }
+}
+
+impl SigningHistoryInner {
+    fn check_and_record(&mut self, record: SigningRecord) -> Result<(), SigningHistoryError> {
+        if record.height < self.pruned_below {
+            return Err(SigningHistoryError::BelowPrunedHeight {
+                height: record.height,
+                kind: record.kind,
+                pruned_below: self.pruned_below,
+            });
+        }
+        let key = (record.height, record.kind, record.shard_id);
+        if let Some(&existing) = self.records.get(&key) {
+            if existing == record.hash {
+                return Ok(());
+            }
+            return Err(SigningHistoryError::Conflict {
+                height: record.height,
+                kind: record.kind,
+                shard_id: record.shard_id,
+                existing,
+                requested: record.hash,
+            });
+        }
+
+        self.append(&Entry::Record(record.clone()))?;
+        self.records.insert(key, record.hash);
+        self.appended_since_compaction += 1;
+
+        let max_height = self.records.last_key_value().map_or(0, |((height, _, _), _)| *height);
+        let pruned_below = max_height.saturating_sub(RETAINED_HEIGHTS);
+        if self.appended_since_compaction >= COMPACTION_INTERVAL && pruned_below > self.pruned_below
+        {
+            self.pruned_below = pruned_below;
+            self.records.retain(|(height, _, _), _| *height >= pruned_below);
+            self.compact()?;
+        }
+        Ok(())
+    }
+
+    fn append(&mut self, entry: &Entry) -> Result<(), SigningHistoryError> {
+        let mut line = serde_json::to_vec(entry).expect("signing history entry is serializable");
+        line.push(b'\n');
+        self.file
+            .write_all(&line)
+            .and_then(|()| self.file.sync_data())
+            .map_err(|source| SigningHistoryError::Io { path: self.path.clone(), source })
+    }
+
+    fn compact(&mut self) -> Result<(), SigningHistoryError> {
+        self.file = rewrite(&self.path, self.pruned_below, &self.records)?;
+        self.appended_since_compaction = 0;
+        Ok(())
+    }
+}
+
+/// Atomically replaces the file at `path` with the given watermark and records and returns it
+/// opened for appending.
+fn rewrite(
+    path: &Path,
+    pruned_below: BlockHeight,
+    records: &BTreeMap<RecordKey, CryptoHash>,
+) -> Result<File, SigningHistoryError> {
+    let io_err = |source| SigningHistoryError::Io { path: path.to_path_buf(), source };
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+    let mut tmp = tempfile::NamedTempFile::new_in(dir.unwrap_or(Path::new("."))).map_err(io_err)?;
+    let mut writer = io::BufWriter::new(tmp.as_file_mut());
+    let mut write_entry = |entry: &Entry| -> io::Result<()> {
+        serde_json::to_writer(&mut writer, entry)?;
+        writer.write_all(b"\n")
+    };
+    write_entry(&Entry::PrunedBelow(pruned_below)).map_err(io_err)?;
+    for (&(height, kind, shard_id), &hash) in records {
+        write_entry(&Entry::Record(SigningRecord { height, kind, shard_id, hash }))
+            .map_err(io_err)?;
+    }
+    writer.flush().map_err(io_err)?;
+    drop(writer);
+    tmp.as_file().sync_all().map_err(io_err)?;
+    tmp.persist(path).map_err(|err| io_err(err.error))?;
+    std::fs::OpenOptions::new().append(true).open(path).map_err(io_err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_primitives::hash::hash;
+
+    #[test]
+    fn test_refuses_conflicting_signatures() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("signing_history");
+        let history = SigningHistory::open(&path).unwrap();
+        let shard = Some(ShardId::new(0));
+
+        history.check_and_record(10, SigningKind::Block, None, hash(&[1])).unwrap();
+        history.check_and_record(10, SigningKind::Block, None, hash(&[1])).unwrap();
+        history.check_and_record(10, SigningKind::ChunkEndorsement, shard, hash(&[2])).unwrap();
+        assert!(matches!(
+            history.check_and_record(10, SigningKind::Block, None, hash(&[3])),
+            Err(SigningHistoryError::Conflict { .. })
+        ));
+
+        // The history survives a restart.
+        drop(history);
+        let history = SigningHistory::open(&path).unwrap();
+        assert!(matches!(
+            history.check_and_record(10, SigningKind::ChunkEndorsement, shard, hash(&[3])),
+            Err(SigningHistoryError::Conflict { .. })
+        ));
+        history
+            .check_and_record(10, SigningKind::ChunkEndorsement, Some(ShardId::new(1)), hash(&[3]))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_export_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = SigningHistory::open(&dir.path().join("source")).unwrap();
+        source.check_and_record(5, SigningKind::Approval, None, hash(&[1])).unwrap();
+        source.check_and_record(6, SigningKind::Block, None, hash(&[2])).unwrap();
+        let export = source.export();
+
+        let target = SigningHistory::open(&dir.path().join("target")).unwrap();
+        target.check_and_record(7, SigningKind::Block, None, hash(&[3])).unwrap();
+        target.import(export.clone()).unwrap();
+        assert!(target.check_and_record(6, SigningKind::Block, None, hash(&[4])).is_err());
+        assert_eq!(target.export().records.len(), 3);
+
+        let conflicting = SigningHistory::open(&dir.path().join("conflicting")).unwrap();
+        conflicting.check_and_record(5, SigningKind::Approval, None, hash(&[9])).unwrap();
+        assert!(matches!(conflicting.import(export), Err(SigningHistoryError::Conflict { .. })));
+        assert_eq!(conflicting.export().records.len(), 1);
+    }
+
+    #[test]
+    fn test_open_is_exclusive() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("signing_history");
+        let history = SigningHistory::open(&path).unwrap();
+        history.check_and_record(5, SigningKind::Block, None, hash(&[1])).unwrap();
+        assert!(matches!(SigningHistory::open(&path), Err(SigningHistoryError::Locked { .. })));
+
+        // The lock is held by all the handles and released with the last one.
+        let handle = history.clone();
+        drop(history);
+        assert!(matches!(SigningHistory::open(&path), Err(SigningHistoryError::Locked { .. })));
+        drop(handle);
+        let history = SigningHistory::open(&path).unwrap();
+        assert_eq!(history.highest_signed_height(), Some(5));
+    }
+
+    #[test]
+    fn test_forbid_below() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("signing_history");
+        let history = SigningHistory::open(&path).unwrap();
+        history.check_and_record(5, SigningKind::Block, None, hash(&[1])).unwrap();
+        history.forbid_below(8).unwrap();
+        assert_eq!(history.highest_signed_height(), None);
+
+        drop(history);
+        let history = SigningHistory::open(&path).unwrap();
+        assert!(matches!(
+            history.check_and_record(7, SigningKind::Approval, None, hash(&[2])),
+            Err(SigningHistoryError::BelowPrunedHeight { .. })
+        ));
+        history.check_and_record(8, SigningKind::Approval, None, hash(&[2])).unwrap();
+        assert_eq!(history.highest_signed_height(), Some(8));
+    }
+}
@@ -781,16 +781,19 @@ mod tests {
         signer: &ValidatorSigner,
         tx_root: CryptoHash,
     ) -> ShardChunkHeader {
-        ShardChunkHeader::V3(ShardChunkHeaderV3::new_for_spice(
-            prev_block_hash,
-            Default::default(),
-            Default::default(),
-            height,
-            shard_id,
-            Default::default(),
-            tx_root,
-            signer,
-        ))
+        ShardChunkHeader::V3(
+            ShardChunkHeaderV3::new_for_spice(
+                prev_block_hash,
+                Default::default(),
+                Default::default(),
+                height,
+                shard_id,
+                Default::default(),
+                tx_root,
+                signer,
+            )
+            .unwrap(),
+        )
     }
 
     fn test_transactions_from_prev_block_hash(
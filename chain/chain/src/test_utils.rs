@@ -284,16 +284,19 @@ pub fn get_fake_next_block_spice_chunk_headers(
         prev_block_hash: CryptoHash,
         signer: &ValidatorSigner,
     ) -> ShardChunkHeader {
-        ShardChunkHeader::V3(ShardChunkHeaderV3::new_for_spice(
-            prev_block_hash,
-            Default::default(),
-            Default::default(),
-            height,
-            shard_id,
-            Default::default(),
-            Default::default(),
-            signer,
-        ))
+        ShardChunkHeader::V3(
+            ShardChunkHeaderV3::new_for_spice(
+                prev_block_hash,
+                Default::default(),
+                Default::default(),
+                height,
+                shard_id,
+                Default::default(),
+                Default::default(),
+                signer,
+            )
+            .unwrap(),
+        )
     }
 
     let mut chunks = Vec::new();
@@ -1,4 +1,5 @@
 use crate::doomslug::ChunksReadiness;
+use crate::signing_history::SigningHistory;
 use crate::{Doomslug, DoomslugThresholdMode};
 use near_async::time::{Duration, FakeClock, Instant, Utc};
 use near_crypto::{KeyType, SecretKey};
@@ -61,6 +62,7 @@ fn one_iter(
             delta * 20, // some arbitrary number larger than delta * 6
             Rational32::new(1, 3),
             DoomslugThresholdMode::TwoThirds,
+            SigningHistory::disabled(),
         )
     })
     .take(signers.len())
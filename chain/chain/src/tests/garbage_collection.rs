@@ -12,17 +12,20 @@ use crate::test_utils::{
 };
 use crate::types::Tip;
 
-use near_chain_configs::{DEFAULT_GC_NUM_EPOCHS_TO_KEEP, GCConfig};
+use near_chain_configs::{DEFAULT_GC_NUM_EPOCHS_TO_KEEP, GCConfig, RetainedAccountsConfig};
 use near_epoch_manager::EpochManagerAdapter;
 use near_primitives::block::Block;
 use near_primitives::epoch_block_info::BlockInfo;
+use near_primitives::hash::{CryptoHash, hash};
 use near_primitives::merkle::PartialMerkleTree;
 use near_primitives::shard_layout::ShardUId;
 use near_primitives::test_utils::{TestBlockBuilder, create_test_signer};
-use near_primitives::types::{BlockHeight, StateRoot};
+use near_primitives::transaction::{ExecutionOutcome, ExecutionOutcomeWithId};
+use near_primitives::trie_key::TrieKey;
+use near_primitives::types::{AccountId, BlockHeight, StateRoot};
 use near_primitives::validator_signer::ValidatorSigner;
 use near_store::test_utils::gen_changes;
-use near_store::{ShardTries, Trie, WrappedTrieChanges};
+use near_store::{DBCol, KeyForStateChanges, ShardTries, Trie, WrappedTrieChanges};
 
 // Build a chain of num_blocks on top of prev_block
 fn do_fork(
@@ -726,24 +729,47 @@ fn add_block(
     blocks: &mut Vec<Arc<Block>>,
     signer: Arc<ValidatorSigner>,
     height: u64,
+) {
+    add_block_with_outcomes(chain, epoch_manager, prev_block, blocks, signer, height, None);
+}
+
+// Adds block to the chain at given height after prev_block. If outcomes are
+// given, the chunks of the block are new and the outcomes are saved for them.
+fn add_block_with_outcomes(
+    chain: &mut Chain,
+    epoch_manager: &dyn EpochManagerAdapter,
+    prev_block: &mut Arc<Block>,
+    blocks: &mut Vec<Arc<Block>>,
+    signer: Arc<ValidatorSigner>,
+    height: u64,
+    outcomes: Option<Vec<ExecutionOutcomeWithId>>,
 ) {
     let next_epoch_id = epoch_manager
         .get_next_epoch_id_from_prev_block(prev_block.hash())
         .expect("block must exist");
     let mut store_update = chain.mut_chain_store().store_update();
 
-    let block = if next_epoch_id == *prev_block.header().next_epoch_id() {
-        TestBlockBuilder::from_prev_block(Clock::real(), &prev_block, signer).height(height).build()
-    } else {
+    let mut builder =
+        TestBlockBuilder::from_prev_block(Clock::real(), &prev_block, signer).height(height);
+    if next_epoch_id != *prev_block.header().next_epoch_id() {
         let epoch_id = *prev_block.header().next_epoch_id();
         let next_bp_hash = Chain::compute_bp_hash(epoch_manager, next_epoch_id).unwrap();
-        TestBlockBuilder::from_prev_block(Clock::real(), &prev_block, signer)
-            .height(height)
-            .epoch_id(epoch_id)
-            .next_epoch_id(next_epoch_id)
-            .next_bp_hash(next_bp_hash)
-            .build()
-    };
+        builder =
+            builder.epoch_id(epoch_id).next_epoch_id(next_epoch_id).next_bp_hash(next_bp_hash);
+    }
+    if outcomes.is_some() {
+        let mut chunks: Vec<_> = prev_block.chunks().iter_raw().cloned().collect();
+        for chunk in &mut chunks {
+            *chunk.height_included_mut() = height;
+        }
+        builder = builder.chunks(chunks);
+    }
+    let block = builder.build();
+    if let Some(outcomes) = outcomes {
+        let shard_id = block.chunks().iter_raw().next().unwrap().shard_id();
+        let proofs = vec![vec![]; outcomes.len()];
+        store_update.save_outcomes_with_proofs(block.hash(), shard_id, outcomes, proofs);
+    }
     blocks.push(block.clone());
     store_update.save_block(block.clone());
     store_update.inc_block_refcount(block.header().prev_hash()).unwrap();
@@ -804,7 +830,11 @@ fn test_clear_old_data_fixed_height() {
     let mut store_update = chain.mut_chain_store().store_update();
     assert!(
         store_update
-            .clear_block_data(epoch_manager.as_ref(), *blocks[5].hash(), GCMode::Canonical(trie))
+            .clear_block_data(
+                epoch_manager.as_ref(),
+                *blocks[5].hash(),
+                GCMode::Canonical(trie, None)
+            )
             .is_ok()
     );
     store_update.commit().unwrap();
@@ -874,3 +904,183 @@ fn test_fork_chunk_tail_updates() {
         assert_eq!(store_update.chunk_tail().unwrap(), 0);
     }
 }
+
+/// Ids of the outcomes saved in a block by `build_chain_with_retained_accounts`.
+struct BlockOutcomeIds {
+    /// Outcome executed on the retained account.
+    retained: CryptoHash,
+    /// Outcome of the receipt caused by the retained outcome of the previous block.
+    caused: CryptoHash,
+    /// Outcome not touching the retained account.
+    other: CryptoHash,
+}
+
+fn outcome_ids(height: BlockHeight) -> BlockOutcomeIds {
+    BlockOutcomeIds {
+        retained: hash(format!("retained {height}").as_bytes()),
+        caused: hash(format!("caused {height}").as_bytes()),
+        other: hash(format!("other {height}").as_bytes()),
+    }
+}
+
+fn state_changes_key(block: &Block, account_id: &AccountId) -> KeyForStateChanges {
+    KeyForStateChanges::from_trie_key(
+        block.hash(),
+        &TrieKey::Account { account_id: account_id.clone() },
+    )
+}
+
+/// Builds a chain with epochs of one block in which every block has outcomes
+/// and state changes of both the retained and another account.
+fn build_chain_with_retained_accounts(
+    max_height: BlockHeight,
+    retained: &AccountId,
+    other: &AccountId,
+) -> (Chain, Vec<Arc<Block>>) {
+    let mut chain = get_chain_with_epoch_length(Clock::real(), 1);
+    let epoch_manager = chain.epoch_manager.clone();
+    let genesis = chain.get_block_by_height(0).unwrap();
+    let signer = Arc::new(create_test_signer("test1"));
+    let mut prev_block = genesis;
+    let mut blocks = vec![prev_block.clone()];
+    for height in 1..=max_height {
+        let ids = outcome_ids(height);
+        let outcome = |id, executor_id: &AccountId, receipt_ids| ExecutionOutcomeWithId {
+            id,
+            outcome: ExecutionOutcome {
+                executor_id: executor_id.clone(),
+                receipt_ids,
+                ..Default::default()
+            },
+        };
+        let mut outcomes = vec![
+            outcome(ids.retained, retained, vec![outcome_ids(height + 1).caused]),
+            outcome(ids.other, other, vec![]),
+        ];
+        if height > 1 {
+            outcomes.push(outcome(ids.caused, other, vec![]));
+        }
+        add_block_with_outcomes(
+            &mut chain,
+            epoch_manager.as_ref(),
+            &mut prev_block,
+            &mut blocks,
+            signer.clone(),
+            height,
+            Some(outcomes),
+        );
+        let mut store_update = chain.chain_store().store().store_update();
+        for account_id in [retained, other] {
+            store_update.set(
+                DBCol::StateChanges,
+                state_changes_key(&prev_block, account_id).as_ref(),
+                &[],
+            );
+        }
+        store_update.commit().unwrap();
+    }
+    (chain, blocks)
+}
+
+fn has_outcome(chain: &Chain, id: &CryptoHash, block: &Block) -> bool {
+    chain.chain_store().get_outcome_by_id_and_block_hash(id, block.hash()).unwrap().is_some()
+}
+
+fn has_state_changes(chain: &Chain, block: &Block, account_id: &AccountId) -> bool {
+    let key = state_changes_key(block, account_id);
+    chain.chain_store().store().get(DBCol::StateChanges, key.as_ref()).unwrap().is_some()
+}
+
+/// Test that the outcomes and state changes of the retained accounts, and the
+/// outcomes of the receipts they cause, survive the regular garbage collection
+/// and are collected once they fall `num_extra_epochs_to_keep` epochs behind.
+#[test]
+fn test_retained_account_data_survives_gc() {
+    let max_height = 14;
+    let retained: AccountId = "retained.near".parse().unwrap();
+    let other: AccountId = "other.near".parse().unwrap();
+    let (mut chain, blocks) = build_chain_with_retained_accounts(max_height, &retained, &other);
+
+    let num_extra_epochs_to_keep = 2;
+    chain
+        .clear_data(&GCConfig {
+            gc_blocks_limit: 100,
+            retained_accounts: Some(RetainedAccountsConfig {
+                accounts: vec![retained.clone()],
+                num_extra_epochs_to_keep: Some(num_extra_epochs_to_keep),
+            }),
+            ..GCConfig::default()
+        })
+        .unwrap();
+
+    let gc_stop_height = max_height - DEFAULT_GC_NUM_EPOCHS_TO_KEEP;
+    let retention_stop_height = gc_stop_height - num_extra_epochs_to_keep;
+    for height in 1..=max_height {
+        let block = &blocks[height as usize];
+        let collected = height < gc_stop_height;
+        let expired = height < retention_stop_height;
+        let ids = outcome_ids(height);
+        assert_eq!(chain.get_block(block.hash()).is_err(), collected, "height {height}");
+        assert_eq!(has_outcome(&chain, &ids.retained, block), !expired, "height {height}");
+        if height > 1 {
+            assert_eq!(has_outcome(&chain, &ids.caused, block), !expired, "height {height}");
+        }
+        assert_eq!(has_outcome(&chain, &ids.other, block), !collected, "height {height}");
+        assert_eq!(has_state_changes(&chain, block, &retained), !expired, "height {height}");
+        assert_eq!(has_state_changes(&chain, block, &other), !collected, "height {height}");
+        let retained_data = chain
+            .chain_store()
+            .store()
+            .iter_prefix(DBCol::RetainedAccountData, &height.to_be_bytes())
+            .count();
+        assert_eq!(retained_data, (collected && !expired) as usize, "height {height}");
+    }
+    // The receipt caused in the last collected block is still tracked, so that
+    // its outcome is retained once its own block is collected.
+    let store = chain.chain_store().store();
+    let pending = outcome_ids(gc_stop_height).caused;
+    assert!(store.get(DBCol::RetainedReceiptIds, pending.as_ref()).unwrap().is_some());
+    let consumed = outcome_ids(gc_stop_height - 1).caused;
+    assert!(store.get(DBCol::RetainedReceiptIds, consumed.as_ref()).unwrap().is_none());
+}
+
+/// Test that the retained data is kept indefinitely without
+/// `num_extra_epochs_to_keep` and collected once the retention is removed from
+/// the config.
+#[test]
+fn test_retained_account_data_cleared_without_retention() {
+    let max_height = 14;
+    let retained: AccountId = "retained.near".parse().unwrap();
+    let other: AccountId = "other.near".parse().unwrap();
+    let (mut chain, blocks) = build_chain_with_retained_accounts(max_height, &retained, &other);
+
+    chain
+        .clear_data(&GCConfig {
+            gc_blocks_limit: 100,
+            retained_accounts: Some(RetainedAccountsConfig {
+                accounts: vec![retained.clone()],
+                num_extra_epochs_to_keep: None,
+            }),
+            ..GCConfig::default()
+        })
+        .unwrap();
+    let gc_stop_height = max_height - DEFAULT_GC_NUM_EPOCHS_TO_KEEP;
+    for height in 1..gc_stop_height {
+        let block = &blocks[height as usize];
+        assert!(chain.get_block(block.hash()).is_err(), "height {height}");
+        assert!(has_outcome(&chain, &outcome_ids(height).retained, block), "height {height}");
+        assert!(has_state_changes(&chain, block, &retained), "height {height}");
+    }
+
+    chain.clear_data(&GCConfig { gc_blocks_limit: 100, ..GCConfig::default() }).unwrap();
+    for height in 1..gc_stop_height {
+        let block = &blocks[height as usize];
+        assert!(!has_outcome(&chain, &outcome_ids(height).retained, block), "height {height}");
+        if height > 1 {
+            assert!(!has_outcome(&chain, &outcome_ids(height).caused, block), "height {height}");
+        }
+        assert!(!has_state_changes(&chain, block, &retained), "height {height}");
+    }
+    let store = chain.chain_store().store();
+    assert_eq!(store.iter(DBCol::RetainedAccountData).count(), 0);
+}
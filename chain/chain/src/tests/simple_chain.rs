@@ -78,29 +78,32 @@ fn build_chain_with_orphans() {
         blocks.push(block);
     }
     let last_block = &blocks[blocks.len() - 1];
-    let block = Arc::new(Block::produce(
-        PROTOCOL_VERSION,
-        last_block.header(),
-        10,
-        last_block.header().block_ordinal() + 1,
-        last_block.chunks().iter_raw().cloned().collect(),
-        vec![vec![]; last_block.chunks().len()],
-        *last_block.header().epoch_id(),
-        *last_block.header().next_epoch_id(),
-        None,
-        vec![],
-        Ratio::from_integer(0),
-        Balance::ZERO,
-        Balance::from_yoctonear(100),
-        Some(Balance::ZERO),
-        &*signer,
-        *last_block.header().next_bp_hash(),
-        CryptoHash::default(),
-        clock,
-        None,
-        None,
-        None,
-    ));
+    let block = Arc::new(
+        Block::produce(
+            PROTOCOL_VERSION,
+            last_block.header(),
+            10,
+            last_block.header().block_ordinal() + 1,
+            last_block.chunks().iter_raw().cloned().collect(),
+            vec![vec![]; last_block.chunks().len()],
+            *last_block.header().epoch_id(),
+            *last_block.header().next_epoch_id(),
+            None,
+            vec![],
+            Ratio::from_integer(0),
+            Balance::ZERO,
+            Balance::from_yoctonear(100),
+            Some(Balance::ZERO),
+            &*signer,
+            *last_block.header().next_bp_hash(),
+            CryptoHash::default(),
+            clock,
+            None,
+            None,
+            None,
+        )
+        .unwrap(),
+    );
     assert_matches!(chain.process_block_test(block).unwrap_err(), Error::Orphan);
     assert_matches!(chain.process_block_test(blocks.pop().unwrap()).unwrap_err(), Error::Orphan);
     assert_matches!(chain.process_block_test(blocks.pop().unwrap()).unwrap_err(), Error::Orphan);
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 
@@ -50,6 +50,8 @@ use near_vm_runner::ContractCode;
 use near_vm_runner::ContractRuntimeCache;
 use node_runtime::PostStateReadyCallback;
 use node_runtime::SignedValidPeriodTransactions;
+use node_runtime::execution_trace::ExecutionTraceCollector;
+use node_runtime::simulation::SimulationResult;
 use num_rational::Rational32;
 use tracing::instrument;
 
@@ -368,6 +370,8 @@ pub struct ApplyChunkShardContext<'a> {
     pub gas_limit: Gas,
     pub is_new_chunk: bool,
     pub on_post_state_ready: Option<PostStateReadyCallback>,
+    /// Records host calls of all function calls executed in the chunk.
+    pub execution_trace_collector: Option<Arc<ExecutionTraceCollector>>,
 }
 
 /// Contains transactions that were fetched from the transaction pool
@@ -563,6 +567,18 @@ pub trait RuntimeAdapter: Send + Sync {
         request: &QueryRequest,
     ) -> Result<QueryResponse, near_chain_primitives::error::QueryError>;
 
+    /// Simulates `transaction` and all receipts it produces on top of the
+    /// post-state of `prev_block`, as if the transaction was included in the
+    /// next block. `state_roots` contains post-state roots of `prev_block` for
+    /// the shards the simulation is allowed to touch. Nothing is persisted.
+    fn simulate_transaction(
+        &self,
+        prev_block: &BlockHeader,
+        state_roots: &HashMap<ShardId, StateRoot>,
+        transaction: &SignedTransaction,
+        verify_signature: bool,
+    ) -> Result<SimulationResult, Error>;
+
     /// Get part of the state corresponding to the given state root.
     /// `prev_hash` is a block whose post state root is `state_root`.
     /// Returns error when storage is inconsistent.
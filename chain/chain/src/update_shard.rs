@@ -166,6 +166,7 @@ pub fn apply_new_chunk(
             gas_limit,
             is_new_chunk: true,
             on_post_state_ready,
+            execution_trace_collector: None,
         },
         block,
         &receipts,
@@ -216,6 +217,7 @@ pub fn apply_old_chunk(
             gas_limit: prev_chunk_extra.gas_limit(),
             is_new_chunk: false,
             on_post_state_ready: None,
+            execution_trace_collector: None,
         },
         block,
         &[],
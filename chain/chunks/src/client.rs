@@ -70,6 +70,13 @@ impl ShardedTransactionPool {
         }
     }
 
+    /// Removes all transactions from the pools of all shards.
+    pub fn clear(&mut self) {
+        for pool in self.tx_pools.values_mut() {
+            pool.clear();
+        }
+    }
+
     /// Computes a deterministic random seed for given `shard_id`.
     /// This seed is used to randomize the transaction pool.
     /// For better security we want the seed to different in each shard.
@@ -161,6 +161,7 @@ impl ChunkTestFixture {
                 &signer,
                 &rs,
             )
+            .unwrap()
         } else {
             ShardChunkWithEncoding::new(
                 mock_parent_hash,
@@ -181,6 +182,7 @@ impl ChunkTestFixture {
                 &signer,
                 &rs,
             )
+            .unwrap()
         };
 
         let mock_encoded_chunk = mock_chunk.into_parts().1;
@@ -3,8 +3,8 @@
 use near_primitives::congestion_info::CongestionInfo;
 use near_primitives::types::{EpochId, ShardId};
 use near_primitives::views::{
-    CatchupStatusView, ChainProcessingInfo, EpochValidatorInfo, RequestedStatePartsView,
-    SyncStatusView,
+    BlockTimelineView, CatchupStatusView, ChainProcessingInfo, ConfigReloadView,
+    EpochValidatorInfo, RequestedStatePartsView, SyncStatusView,
 };
 use near_primitives::{
     block_header::ApprovalInner,
@@ -266,6 +266,10 @@ pub enum DebugStatus {
     ChainProcessingStatus,
     // The state parts already requested.
     RequestedStateParts,
+    // Recent reloads of the dynamically updatable configs.
+    ConfigReloads,
+    // Timestamps of the stages of processing a recent block.
+    BlockTimeline(CryptoHash),
 }
 
 #[derive(serde::Serialize, Debug)]
@@ -283,4 +287,8 @@ pub enum DebugStatusResponse {
     ChainProcessingStatus(ChainProcessingInfo),
     // The state parts already requested.
     RequestedStateParts(Vec<RequestedStatePartsView>),
+    // Recent reloads of the dynamically updatable configs, newest first.
+    ConfigReloads(Vec<ConfigReloadView>),
+    // None if the block is not among the recent blocks tracked by the node.
+    BlockTimeline(Option<BlockTimelineView>),
 }
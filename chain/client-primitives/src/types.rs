@@ -7,8 +7,8 @@ use near_primitives::types::{
     TransactionOrReceiptId,
 };
 use near_primitives::views::{
-    ExecutionOutcomeWithIdView, LightClientBlockLiteView, QueryRequest, StateChangesRequestView,
-    StateSyncStatusView, SyncStatusView,
+    ExecutionOutcomeWithIdView, LightClientBlockLiteView, QueryRequest, ShardSyncProgressView,
+    StateChangesRequestView, StateSyncStatusView, SyncStatusView,
 };
 pub use near_primitives::views::{StatusResponse, StatusSyncInfo};
 use near_time::Duration;
@@ -61,6 +61,18 @@ impl ShardSyncStatus {
             ShardSyncStatus::StateSyncDone => 5,
         }
     }
+
+    pub fn from_repr(repr: u8) -> Option<Self> {
+        match repr {
+            0 => Some(ShardSyncStatus::StateDownloadHeader),
+            1 => Some(ShardSyncStatus::StateDownloadParts),
+            2 => Some(ShardSyncStatus::StateApplyScheduling),
+            3 => Some(ShardSyncStatus::StateApplyInProgress),
+            4 => Some(ShardSyncStatus::StateApplyFinalizing),
+            5 => Some(ShardSyncStatus::StateSyncDone),
+            _ => None,
+        }
+    }
 }
 
 /// Manually implement compare for ShardSyncStatus to compare only based on variant name
@@ -85,10 +97,24 @@ impl ToString for ShardSyncStatus {
     }
 }
 
+/// Number of state parts of a shard which are done, and the estimated time until the
+/// current phase of the shard completes.
+#[derive(Clone, Debug, Default)]
+pub struct ShardSyncProgress {
+    pub num_parts: u64,
+    pub parts_downloaded: u64,
+    pub parts_applied: u64,
+    /// Parts which were downloaded before the node restarted and didn't need to be
+    /// downloaded again.
+    pub parts_resumed: u64,
+    pub eta: Option<Duration>,
+}
+
 #[derive(Clone, Debug)]
 pub struct StateSyncStatus {
     pub sync_hash: CryptoHash,
     pub sync_status: HashMap<ShardId, ShardSyncStatus>,
+    pub shard_progress: HashMap<ShardId, ShardSyncProgress>,
     pub download_tasks: Vec<String>,
     pub computation_tasks: Vec<String>,
 }
@@ -98,6 +124,7 @@ impl StateSyncStatus {
         Self {
             sync_hash,
             sync_status: HashMap::new(),
+            shard_progress: HashMap::new(),
             download_tasks: Vec::new(),
             computation_tasks: Vec::new(),
         }
@@ -219,6 +246,24 @@ impl From<SyncStatus> for SyncStatusView {
                             (*shard_id, shard_sync_status.to_string())
                         })
                         .collect(),
+                    shard_progress: state_sync_status
+                        .shard_progress
+                        .into_iter()
+                        .map(|(shard_id, progress)| {
+                            (
+                                shard_id,
+                                ShardSyncProgressView {
+                                    num_parts: progress.num_parts,
+                                    parts_downloaded: progress.parts_downloaded,
+                                    parts_applied: progress.parts_applied,
+                                    parts_resumed: progress.parts_resumed,
+                                    eta_secs: progress
+                                        .eta
+                                        .map(|eta| eta.whole_seconds().max(0) as u64),
+                                },
+                            )
+                        })
+                        .collect(),
                     download_tasks: state_sync_status.download_tasks,
                     computation_tasks: state_sync_status.computation_tasks,
                 })
@@ -275,6 +320,16 @@ impl GetBlock {
     }
 }
 
+/// Actor message requesting the header of the block in which the state changes
+/// of `account_ids` are queried. Like `GetBlock` it fails for garbage collected
+/// blocks, unless the changes of all these accounts are still retained, see
+/// `GCConfig::retained_accounts`.
+#[derive(Clone, Debug)]
+pub struct GetStateChangesBlockHeader {
+    pub block_reference: BlockReference,
+    pub account_ids: Vec<AccountId>,
+}
+
 /// Get block with the block merkle tree. Used for testing
 #[derive(Debug)]
 pub struct GetBlockWithMerkleTree(pub BlockReference);
@@ -409,6 +464,8 @@ pub enum QueryError {
     #[error("Function call returned an error: {vm_error}")]
     ContractExecutionError {
         vm_error: String,
+        /// Wasm frames that led to the error, innermost first.
+        backtrace: Option<Vec<near_primitives::views::WasmFrameView>>,
         block_height: near_primitives::types::BlockHeight,
         block_hash: near_primitives::hash::CryptoHash,
     },
@@ -657,6 +714,12 @@ pub struct GetValidatorOrdered {
     pub block_id: MaybeBlockId,
 }
 
+/// Mid-epoch forecast of validator performance as of the given block.
+#[derive(Debug)]
+pub struct GetValidatorForecast {
+    pub block_id: MaybeBlockId,
+}
+
 #[derive(Debug)]
 pub struct GetStateChanges {
     pub block_hash: CryptoHash,
@@ -861,6 +924,79 @@ impl From<near_chain_primitives::Error> for GetReceiptError {
     }
 }
 
+/// Dry-runs a transaction on top of the latest final block without
+/// submitting it to the network.
+#[derive(Debug)]
+pub struct SimulateTransaction {
+    pub signed_transaction: near_primitives::transaction::SignedTransaction,
+    /// If false, the transaction signature is not checked, which allows
+    /// simulating transactions that were not signed.
+    pub verify_signature: bool,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SimulateTransactionError {
+    #[error("IO Error: {0}")]
+    IOError(String),
+    #[error("There are no fully synchronized blocks on the node yet")]
+    NoSyncedBlocks,
+    #[error("The node does not track the shard ID {requested_shard_id} of the signer")]
+    UnavailableShard { requested_shard_id: ShardId },
+    // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
+    // expected cases, we cannot statically guarantee that no other errors will be returned
+    // in the future.
+    // TODO #3851: Remove this variant once we can exhaustively match all the underlying errors
+    #[error(
+        "It is a bug if you receive this error type, please, report this incident: https://github.com/near/nearcore/issues/new/choose. Details: {0}"
+    )]
+    Unreachable(String),
+}
+
+impl From<near_chain_primitives::Error> for SimulateTransactionError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        match error {
+            near_chain_primitives::Error::IOErr(error) => Self::IOError(error.to_string()),
+            _ => Self::Unreachable(error.to_string()),
+        }
+    }
+}
+
+/// Re-executes the chunk in which the receipt was executed and returns the
+/// host calls made by the receipt's function calls.
+#[derive(Debug)]
+pub struct TraceReceipt {
+    pub receipt_id: CryptoHash,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum TraceReceiptError {
+    #[error("IO Error: {0}")]
+    IOError(String),
+    #[error("Receipt with id {receipt_id} has never been observed on this node")]
+    UnknownReceipt { receipt_id: CryptoHash },
+    #[error(
+        "The node does not have the state of shard {requested_shard_id} required to re-execute the receipt"
+    )]
+    UnavailableShard { requested_shard_id: ShardId },
+    // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
+    // expected cases, we cannot statically guarantee that no other errors will be returned
+    // in the future.
+    // TODO #3851: Remove this variant once we can exhaustively match all the underlying errors
+    #[error(
+        "It is a bug if you receive this error type, please, report this incident: https://github.com/near/nearcore/issues/new/choose. Details: {0}"
+    )]
+    Unreachable(String),
+}
+
+impl From<near_chain_primitives::Error> for TraceReceiptError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        match error {
+            near_chain_primitives::Error::IOErr(error) => Self::IOError(error.to_string()),
+            _ => Self::Unreachable(error.to_string()),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct GetProtocolConfig(pub BlockReference);
 
@@ -979,6 +1115,9 @@ pub enum SandboxMessage {
     SandboxPatchStateStatus,
     SandboxFastForward(near_primitives::types::BlockHeightDelta),
     SandboxFastForwardStatus,
+    SandboxSnapshot,
+    SandboxRevert(u64),
+    SandboxSetBlockTimestamp(u64),
 }
 
 #[cfg(feature = "sandbox")]
@@ -987,5 +1126,9 @@ pub enum SandboxResponse {
     SandboxPatchStateFinished(bool),
     SandboxFastForwardFinished(bool),
     SandboxFastForwardFailed(String),
+    SandboxSnapshotCreated(u64),
+    SandboxSnapshotFailed(String),
+    SandboxRevertFailed(String),
+    SandboxSetBlockTimestampFailed(String),
     SandboxNoResponse,
 }
@@ -420,7 +420,7 @@ mod tests {
             congestion_info: CongestionInfo::default(),
             bandwidth_requests: BandwidthRequests::empty(),
         });
-        let header = ShardChunkHeaderV3::from_inner(header_inner, &signer);
+        let header = ShardChunkHeaderV3::from_inner(header_inner, &signer).unwrap();
         PartialEncodedChunk::V2(PartialEncodedChunkV2 {
             header: ShardChunkHeader::V3(header),
             parts: Vec::new(),
@@ -639,18 +639,21 @@ impl ChunkExecutorActor {
             .contains(my_signer.validator_id())
         {
             // If we're validator we can send endorsement without witness validation.
-            let endorsement = SpiceChunkEndorsement::new(
-                SpiceChunkId { block_hash: *block.hash(), shard_id },
-                execution_result,
-                &my_signer,
-            );
-            send_spice_chunk_endorsement(
-                endorsement.clone(),
-                self.epoch_manager.as_ref(),
-                &self.network_adapter.clone().into_sender(),
-                &my_signer,
-            );
-            self.core_writer_sender.send(SpiceChunkEndorsementMessage(endorsement));
+            let chunk_id = SpiceChunkId { block_hash: *block.hash(), shard_id };
+            match SpiceChunkEndorsement::try_new(chunk_id, execution_result, &my_signer) {
+                Ok(endorsement) => {
+                    send_spice_chunk_endorsement(
+                        endorsement.clone(),
+                        self.epoch_manager.as_ref(),
+                        &self.network_adapter.clone().into_sender(),
+                        &my_signer,
+                    );
+                    self.core_writer_sender.send(SpiceChunkEndorsementMessage(endorsement));
+                }
+                Err(err) => {
+                    tracing::error!(target: "chunk_executor", ?err, ?shard_id, "failed to sign chunk endorsement");
+                }
+            }
         }
 
         let state_witness =
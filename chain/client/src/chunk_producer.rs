@@ -17,6 +17,7 @@ use near_client_primitives::debug::ChunkProduction;
 use near_client_primitives::types::Error;
 use near_epoch_manager::EpochManagerAdapter;
 use near_epoch_manager::shard_assignment::shard_id_to_uid;
+use near_o11y::event_log::{self, NodeEvent};
 use near_pool::types::TransactionGroupIterator;
 use near_primitives::bandwidth_scheduler::BandwidthRequests;
 use near_primitives::epoch_info::RngSeed;
@@ -352,7 +353,7 @@ impl ChunkProducer {
         );
 
         let protocol_version = self.epoch_manager.get_epoch_protocol_version(epoch_id)?;
-        let (chunk, merkle_paths) = if ProtocolFeature::Spice.enabled(protocol_version) {
+        let chunk_with_encoding = if ProtocolFeature::Spice.enabled(protocol_version) {
             ShardChunkWithEncoding::new_for_spice(
                 prev_block_hash,
                 next_height,
@@ -385,6 +386,11 @@ impl ChunkProducer {
                 &mut self.reed_solomon_encoder,
             )
         };
+        let (chunk, merkle_paths) = chunk_with_encoding.map_err(|err| {
+            Error::ChunkProducer(format!(
+                "failed to sign the chunk, skipping its production: {err}"
+            ))
+        })?;
 
         let encoded_chunk = chunk.to_encoded_shard_chunk();
         span.record("chunk_hash", tracing::field::debug(encoded_chunk.chunk_hash()));
@@ -397,6 +403,11 @@ impl ChunkProducer {
         );
 
         metrics::CHUNK_PRODUCED_TOTAL.inc();
+        event_log::emit(NodeEvent::ChunkProduced {
+            height: next_height,
+            shard_id,
+            chunk_hash: encoded_chunk.chunk_hash().0,
+        });
 
         metrics::CHUNK_TRANSACTIONS_TOTAL
             .with_label_values(&[&shard_id.to_string()])
@@ -15,6 +15,7 @@ use crate::stateless_validation::chunk_validation_actor::{
 };
 use crate::stateless_validation::partial_witness::partial_witness_actor::PartialWitnessSenderForClient;
 use crate::sync::block::BlockSync;
+use crate::sync::cloud_archive::CloudArchiveSync;
 use crate::sync::epoch::EpochSync;
 use crate::sync::handler::SyncHandler;
 use crate::sync::header::HeaderSync;
@@ -33,22 +34,28 @@ use near_chain::chain::{
 use near_chain::orphan::OrphanMissingChunks;
 use near_chain::rayon_spawner::RayonAsyncComputationSpawner;
 use near_chain::resharding::types::ReshardingSender;
+use near_chain::signing_history::{SigningHistory, SigningKind};
 use near_chain::state_snapshot_actor::SnapshotCallbacks;
 use near_chain::test_utils::format_hash;
 use near_chain::types::{ChainConfig, LatestKnown, RuntimeAdapter};
+use near_chain::validate::validate_chunk_proofs;
 use near_chain::{
     ApplyChunksIterationMode, ApplyChunksSpawner, BlockProcessingArtifact, BlockStatus, Chain,
     ChainGenesis, ChainStoreAccess, ChunksReadiness, Doomslug, DoomslugThresholdMode, Provenance,
 };
 use near_chain_configs::{ClientConfig, MutableValidatorSigner, UpdatableClientConfig};
 use near_chunks::adapter::ShardsManagerRequestFromClient;
-use near_chunks::logic::{create_partial_chunk, persist_chunk};
+use near_chunks::logic::{
+    create_partial_chunk, make_outgoing_receipts_proofs,
+    make_partial_encoded_chunk_from_owned_parts_and_needed_receipts, persist_chunk,
+};
 use near_client_primitives::types::{Error, StateSyncStatus, SyncStatus};
 use near_epoch_manager::EpochManagerAdapter;
 use near_epoch_manager::shard_assignment::shard_id_to_uid;
 use near_epoch_manager::shard_tracker::ShardTracker;
 use near_network::types::{AccountKeys, ChainInfo, PeerManagerMessageRequest, SetChainInfo};
 use near_network::types::{NetworkRequests, PeerManagerAdapter, ReasonForBan};
+use near_o11y::event_log::{self, NodeEvent};
 use near_primitives::block::{
     Approval, ApprovalInner, ApprovalMessage, Block, BlockHeader, SpiceNewBlockProductionInfo, Tip,
 };
@@ -97,6 +104,15 @@ pub enum AdvProduceBlocksMode {
     OnlyValid,
 }
 
+/// Chain head and block time captured by `Client::sandbox_snapshot`.
+#[cfg(feature = "sandbox")]
+#[derive(Clone)]
+pub(crate) struct SandboxSnapshot {
+    head: Tip,
+    accrued_fastforward_delta: near_primitives::types::BlockHeightDelta,
+    time_offset: Duration,
+}
+
 /// The state associated with downloading state for a shard this node will track in the
 /// future but does not currently.
 pub struct CatchupState {
@@ -117,6 +133,12 @@ pub struct Client {
     /// Fast Forward accrued delta height used to calculate fast forwarded timestamps for each block.
     #[cfg(feature = "sandbox")]
     pub(crate) accrued_fastforward_delta: near_primitives::types::BlockHeightDelta,
+    /// Offset of block timestamps set with `sandbox_set_block_timestamp`.
+    #[cfg(feature = "sandbox")]
+    sandbox_time_offset: Duration,
+    /// Snapshots that the sandbox can be reverted to, indexed by the snapshot id.
+    #[cfg(feature = "sandbox")]
+    sandbox_snapshots: Vec<SandboxSnapshot>,
 
     pub clock: Clock,
     pub config: ClientConfig,
@@ -132,6 +154,9 @@ pub struct Client {
     /// Lock the value of mutable validator signer for the duration of a request to ensure consistency.
     /// Please note that the locked value should not be stored anywhere or passed through the thread boundary.
     pub validator_signer: MutableValidatorSigner,
+    /// Record of what the validator has signed, consulted before releasing blocks, approvals and
+    /// chunk endorsements to refuse double signing.
+    pub signing_history: SigningHistory,
     /// Approvals for which we do not have the block yet
     pub pending_approvals:
         lru::LruCache<ApprovalInner, HashMap<AccountId, (Approval, ApprovalType)>>,
@@ -172,6 +197,8 @@ pub struct Client {
     pub partial_witness_adapter: PartialWitnessSenderForClient,
     // Optional value used for the Chunk Distribution Network Feature.
     chunk_distribution_network: Option<ChunkDistributionNetwork>,
+    /// Cloud archive that block sync falls back to, shared with `BlockSync`.
+    cloud_archive_sync: Option<Arc<CloudArchiveSync>>,
     /// Upgrade schedule which determines when the client starts voting for new protocol versions.
     upgrade_schedule: ProtocolUpgradeVotingSchedule,
     /// Produced optimistic block.
@@ -197,6 +224,10 @@ impl Client {
             .config
             .produce_chunk_add_transactions_time_limit
             .update(update_client_config.produce_chunk_add_transactions_time_limit);
+        is_updated |= self
+            .config
+            .tx_routing_height_horizon
+            .update(update_client_config.tx_routing_height_horizon);
         is_updated
     }
 
@@ -277,6 +308,7 @@ impl Client {
         myself_sender: ClientSenderForClient,
         chunk_validation_sender: ChunkValidationSender,
         upgrade_schedule: ProtocolUpgradeVotingSchedule,
+        signing_history: SigningHistory,
     ) -> Result<Self, Error> {
         let doomslug_threshold_mode = if enable_doomslug {
             DoomslugThresholdMode::TwoThirds
@@ -324,12 +356,25 @@ impl Client {
             config.header_sync_expected_height_per_second,
             config.expected_shutdown.clone(),
         );
+        let cloud_archive_sync = config
+            .block_sync_cloud_archive
+            .as_ref()
+            .map(|cloud_archive_config| {
+                CloudArchiveSync::from_config(
+                    cloud_archive_config,
+                    state_sync_future_spawner.clone(),
+                )
+                .map(Arc::new)
+            })
+            .transpose()
+            .map_err(|err| Error::Other(format!("failed to open the cloud archive: {err}")))?;
         let block_sync = BlockSync::new(
             clock.clone(),
             network_adapter.clone(),
             config.block_fetch_horizon,
             config.archive,
             config.state_sync_enabled,
+            cloud_archive_sync.clone(),
         );
 
         let state_sync = StateSync::new(
@@ -359,8 +404,10 @@ impl Client {
             config.max_block_wait_delay,
             config.chunk_wait_mult,
             doomslug_threshold_mode,
+            signing_history.clone(),
         );
         let chunk_endorsement_tracker = Arc::new(ChunkEndorsementTracker::new(
+            clock.clone(),
             epoch_manager.clone(),
             chain.chain_store().store(),
         ));
@@ -381,6 +428,10 @@ impl Client {
             adv_produce_blocks: None,
             #[cfg(feature = "sandbox")]
             accrued_fastforward_delta: 0,
+            #[cfg(feature = "sandbox")]
+            sandbox_time_offset: Duration::ZERO,
+            #[cfg(feature = "sandbox")]
+            sandbox_snapshots: vec![],
             clock: clock.clone(),
             config: config.clone(),
             chain,
@@ -391,6 +442,7 @@ impl Client {
             shards_manager_adapter: shards_manager_sender,
             network_adapter,
             validator_signer,
+            signing_history,
             pending_approvals: lru::LruCache::new(
                 NonZeroUsize::new(num_block_producer_seats).unwrap(),
             ),
@@ -419,6 +471,7 @@ impl Client {
             chunk_endorsement_tracker,
             partial_witness_adapter,
             chunk_distribution_network,
+            cloud_archive_sync,
             upgrade_schedule,
             last_optimistic_block_produced: None,
             chunk_producer_accounts_cache: None,
@@ -679,7 +732,10 @@ impl Client {
             &*validator_signer,
             self.clock.now_utc().unix_timestamp_nanos() as u64,
             sandbox_delta_time,
-        );
+        )
+        .map_err(|err| {
+            Error::BlockProducer(format!("failed to sign the optimistic block: {err}"))
+        })?;
 
         metrics::OPTIMISTIC_BLOCK_PRODUCED_TOTAL.inc();
 
@@ -947,30 +1003,41 @@ impl Client {
             None
         };
 
-        let block = Arc::new(Block::produce(
-            self.upgrade_schedule
-                .protocol_version_to_vote_for(self.clock.now_utc(), next_epoch_protocol_version),
-            prev_header,
-            height,
-            block_ordinal,
-            chunk_headers,
-            chunk_endorsements,
-            epoch_id,
-            next_epoch_id,
-            epoch_sync_data_hash,
-            approvals,
-            gas_price_adjustment_rate,
-            min_gas_price,
-            max_gas_price,
-            minted_amount,
-            &*validator_signer,
-            next_bp_hash,
-            block_merkle_root,
-            self.clock.clone(),
-            sandbox_delta_time,
-            optimistic_block,
-            spice_info,
-        ));
+        let block = Arc::new(
+            Block::produce(
+                self.upgrade_schedule.protocol_version_to_vote_for(
+                    self.clock.now_utc(),
+                    next_epoch_protocol_version,
+                ),
+                prev_header,
+                height,
+                block_ordinal,
+                chunk_headers,
+                chunk_endorsements,
+                epoch_id,
+                next_epoch_id,
+                epoch_sync_data_hash,
+                approvals,
+                gas_price_adjustment_rate,
+                min_gas_price,
+                max_gas_price,
+                minted_amount,
+                &*validator_signer,
+                next_bp_hash,
+                block_merkle_root,
+                self.clock.clone(),
+                sandbox_delta_time,
+                optimistic_block,
+                spice_info,
+            )
+            .map_err(|err| Error::BlockProducer(format!("failed to sign the block: {err}")))?,
+        );
+
+        // The block does not leave the client until it is durably recorded, so a conflicting
+        // block produced after a restore or failover is dropped here instead of being broadcast.
+        self.signing_history
+            .check_and_record(height, SigningKind::Block, None, *block.hash())
+            .map_err(|err| Error::Other(err.to_string()))?;
 
         // Update latest known even before returning block out, to prevent race conditions.
         self.chain
@@ -978,6 +1045,11 @@ impl Client {
             .save_latest_known(LatestKnown { height, seen: block.header().raw_timestamp() })?;
 
         metrics::BLOCK_PRODUCED_TOTAL.inc();
+        event_log::emit(NodeEvent::BlockProduced {
+            height,
+            hash: *block.hash(),
+            prev_hash: *block.header().prev_hash(),
+        });
 
         Ok(Some(block))
     }
@@ -1089,6 +1161,111 @@ impl Client {
         res
     }
 
+    /// Processes the blocks and chunks block sync downloaded from the cloud
+    /// archive. They are verified the same way as the ones received from peers.
+    pub fn process_cloud_archive_downloads(
+        &mut self,
+        apply_chunks_done_sender: Option<ApplyChunksDoneSender>,
+    ) {
+        let Some(cloud_archive) = self.cloud_archive_sync.clone() else {
+            return;
+        };
+        for block in cloud_archive.take_blocks() {
+            let hash = *block.hash();
+            if let Err(err) =
+                self.receive_cloud_archive_block(block, apply_chunks_done_sender.clone())
+            {
+                tracing::debug!(target: "client", ?hash, ?err, "failed to process a block from the cloud archive");
+                self.chain.blocks_delay_tracker.mark_block_errored(&hash, err.to_string());
+            }
+        }
+        for (header, chunk) in cloud_archive.take_chunks() {
+            let prev_hash = *header.prev_block_hash();
+            if let Err(err) = self.receive_cloud_archive_chunk(
+                header.clone(),
+                chunk,
+                apply_chunks_done_sender.clone(),
+            ) {
+                tracing::warn!(target: "client", chunk_hash = ?header.chunk_hash(), ?err, "invalid chunk from the cloud archive, requesting it from peers");
+                self.shards_manager_adapter.send(ShardsManagerRequestFromClient::RequestChunks {
+                    chunks_to_request: vec![header],
+                    prev_hash,
+                });
+            }
+        }
+    }
+
+    /// Same as `receive_block_impl`, except that there is no peer to ban or to
+    /// request the previous block from.
+    fn receive_cloud_archive_block(
+        &mut self,
+        block: Arc<Block>,
+        apply_chunks_done_sender: Option<ApplyChunksDoneSender>,
+    ) -> Result<(), near_chain::Error> {
+        self.chain.blocks_delay_tracker.mark_block_received(&block);
+        if !self.should_process_block(&block, true)? {
+            self.chain
+                .blocks_delay_tracker
+                .mark_block_dropped(block.hash(), DroppedReason::HeightProcessed);
+            return Ok(());
+        }
+        if self.chain.verify_block_hash_and_signature(&block)?
+            == VerifyBlockHashAndSignatureResult::Incorrect
+        {
+            return Err(near_chain::Error::InvalidSignature);
+        }
+        let block: MaybeValidated<Arc<Block>> = block.into();
+        match self
+            .chain
+            .process_block_header(block.header())
+            .and_then(|_| self.chain.validate_block(&block))
+        {
+            Err(err) if err.is_bad_data() => return Err(err),
+            // Other errors, e.g. a missing previous block, are handled when
+            // processing the block.
+            _ => {}
+        }
+        self.start_process_block(block, Provenance::SYNC, apply_chunks_done_sender)
+    }
+
+    /// Checks that the chunk matches the header it was requested for and
+    /// stores it together with a partial chunk holding the outgoing receipts.
+    fn receive_cloud_archive_chunk(
+        &mut self,
+        header: ShardChunkHeader,
+        chunk: ShardChunk,
+        apply_chunks_done_sender: Option<ApplyChunksDoneSender>,
+    ) -> Result<(), Error> {
+        if chunk.chunk_hash() != header.chunk_hash()
+            || !validate_chunk_proofs(&chunk, self.epoch_manager.as_ref())?
+        {
+            return Err(near_chain::Error::InvalidChunk(
+                "chunk from the cloud archive doesn't match its header".to_string(),
+            )
+            .into());
+        }
+        let prev_outgoing_receipts = make_outgoing_receipts_proofs(
+            &header,
+            chunk.prev_outgoing_receipts().to_vec(),
+            self.epoch_manager.as_ref(),
+        )?;
+        let me = self.validator_signer.get().map(|signer| signer.validator_id().clone());
+        let cares_about_shard = self
+            .shard_tracker
+            .cares_about_shard_this_or_next_epoch(header.prev_block_hash(), header.shard_id());
+        let partial_chunk = make_partial_encoded_chunk_from_owned_parts_and_needed_receipts(
+            header,
+            std::iter::empty(),
+            prev_outgoing_receipts.into_iter(),
+            me.as_ref(),
+            self.epoch_manager.as_ref(),
+            &self.shard_tracker,
+        );
+        let shard_chunk = cares_about_shard.then_some(chunk);
+        self.on_chunk_completed(partial_chunk, shard_chunk, apply_chunks_done_sender);
+        Ok(())
+    }
+
     /// Check optimistic block and start processing if is valid.
     #[instrument(
         level = "debug",
@@ -1467,6 +1644,7 @@ impl Client {
     }
 
     /// Gets the advanced timestamp delta in nanoseconds for sandbox once it has been fast-forwarded
+    /// or its block timestamp has been set.
     #[cfg(feature = "sandbox")]
     pub fn sandbox_delta_time(&self) -> Duration {
         let avg_block_prod_time = (self.config.min_block_production_delay.whole_nanoseconds()
@@ -1482,7 +1660,58 @@ impl Client {
                 )
             });
 
-        Duration::nanoseconds(ns)
+        Duration::nanoseconds(ns) + self.sandbox_time_offset
+    }
+
+    /// Makes the timestamp of the next produced block `timestamp` nanoseconds. The timestamps of
+    /// the following blocks advance from there.
+    #[cfg(feature = "sandbox")]
+    pub fn sandbox_set_block_timestamp(&mut self, timestamp: u64) -> Result<(), Error> {
+        let head_timestamp = self.chain.head_header()?.raw_timestamp();
+        if timestamp <= head_timestamp {
+            return Err(Error::Other(format!(
+                "timestamp {timestamp} is not after the head block timestamp {head_timestamp}"
+            )));
+        }
+        let timestamp = near_async::time::Utc::from_unix_timestamp_nanos(timestamp.into())
+            .map_err(|err| Error::Other(err.to_string()))?;
+        self.sandbox_time_offset = Duration::ZERO;
+        let next_timestamp = self.clock.now_utc() + self.sandbox_delta_time();
+        self.sandbox_time_offset = timestamp - next_timestamp;
+        Ok(())
+    }
+
+    /// Captures the chain head and block time, returning the id to pass to `sandbox_revert`.
+    #[cfg(feature = "sandbox")]
+    pub fn sandbox_snapshot(&mut self) -> Result<u64, Error> {
+        let head = self.chain.head()?;
+        self.sandbox_snapshots.push(SandboxSnapshot {
+            head: Tip::clone(&head),
+            accrued_fastforward_delta: self.accrued_fastforward_delta,
+            time_offset: self.sandbox_time_offset,
+        });
+        Ok(self.sandbox_snapshots.len() as u64 - 1)
+    }
+
+    /// Reverts the chain and its state to the snapshot with the given id and drops the pending
+    /// transactions. The snapshots taken after it are discarded.
+    #[cfg(feature = "sandbox")]
+    pub fn sandbox_revert(&mut self, snapshot_id: u64) -> Result<(), Error> {
+        let Some(snapshot) = usize::try_from(snapshot_id)
+            .ok()
+            .and_then(|index| self.sandbox_snapshots.get(index))
+            .cloned()
+        else {
+            return Err(Error::Other(format!("unknown sandbox snapshot {snapshot_id}")));
+        };
+        self.chain.sandbox_revert_to(&snapshot.head)?;
+        self.sandbox_snapshots.truncate(snapshot_id as usize + 1);
+        self.accrued_fastforward_delta = snapshot.accrued_fastforward_delta;
+        self.sandbox_time_offset = snapshot.time_offset;
+        self.chunk_producer.sharded_tx_pool.lock().clear();
+        // Blocks are never produced again at the heights of the abandoned ones.
+        let latest_known = self.chain.mut_chain_store().get_latest_known()?;
+        self.sandbox_update_tip(latest_known.height)
     }
 
     fn send_block_approval_to_account(
@@ -1542,6 +1771,10 @@ impl Client {
 
         let next_block_producer =
             self.epoch_manager.get_block_producer(&next_epoch_id, approval.target_height)?;
+        event_log::emit(NodeEvent::ApprovalSent {
+            target_height: approval.target_height,
+            account_id: approval.account_id.clone(),
+        });
         self.send_block_approval_to_account(approval, next_block_producer);
 
         Ok(())
@@ -1615,6 +1848,14 @@ impl Client {
                 tracing::error!(target: "client", ?err, "failed to update network chain info");
             }
 
+            // While syncing the node couldn't have produced anything, so don't report the
+            // heights it was assigned to as missed.
+            if provenance != Provenance::SYNC && !self.sync_handler.sync_status.is_syncing() {
+                if let Err(err) = self.emit_missed_production_events(&block) {
+                    tracing::debug!(target: "client", ?err, "failed to check for missed blocks and chunks");
+                }
+            }
+
             // If the next block is the first of the next epoch and the shard
             // layout is changing we need to reshard the transaction pool.
             // TODO make sure transactions don't get added for the old shard
@@ -1674,6 +1915,46 @@ impl Client {
         self.chunk_validation_sender.block_notification.send(block_notification);
     }
 
+    /// Records in the event log the blocks skipped between the parent of the new head and the
+    /// new head, and the chunks missing from the new head, which this node was meant to produce.
+    fn emit_missed_production_events(&self, block: &Block) -> Result<(), Error> {
+        let Some(signer) = self.validator_signer.get() else {
+            return Ok(());
+        };
+        let me = signer.validator_id();
+        let prev_header = self.chain.get_block_header(block.header().prev_hash())?;
+        let epoch_id = self.epoch_manager.get_epoch_id_from_prev_block(prev_header.hash())?;
+        for height in prev_header.height() + 1..block.header().height() {
+            let block_producer = self.epoch_manager.get_block_producer(&epoch_id, height)?;
+            if &block_producer == me {
+                event_log::emit(NodeEvent::BlockMissed { height, block_producer });
+            }
+        }
+        let epoch_id = block.header().epoch_id();
+        for chunk_header in block.chunks().iter_raw() {
+            if chunk_header.is_new_chunk(block.header().height()) {
+                continue;
+            }
+            let shard_id = chunk_header.shard_id();
+            let chunk_producer = self
+                .epoch_manager
+                .get_chunk_producer_info(&ChunkProductionKey {
+                    epoch_id: *epoch_id,
+                    height_created: block.header().height(),
+                    shard_id,
+                })?
+                .take_account_id();
+            if &chunk_producer == me {
+                event_log::emit(NodeEvent::ChunkMissed {
+                    height: block.header().height(),
+                    shard_id,
+                    chunk_producer,
+                });
+            }
+        }
+        Ok(())
+    }
+
     /// Reconcile the transaction pool after processing a block.
     /// returns true if it's ok to proceed to produce chunks
     /// returns false when handling a fork and there is no need to produce chunks
@@ -1903,9 +2184,31 @@ impl Client {
     )]
     pub fn request_missing_chunks(
         &mut self,
-        blocks_missing_chunks: Vec<BlockMissingChunks>,
+        mut blocks_missing_chunks: Vec<BlockMissingChunks>,
         orphans_missing_chunks: Vec<OrphanMissingChunks>,
     ) {
+        if let Some(cloud_archive) = &self.cloud_archive_sync {
+            // Peers which couldn't provide a block are unlikely to have its
+            // chunks, so those are downloaded from the cloud archive as well.
+            blocks_missing_chunks.retain(|BlockMissingChunks { prev_hash, missing_chunks }| {
+                let Some(height) = missing_chunks.first().map(|chunk| chunk.height_created())
+                else {
+                    return true;
+                };
+                if !cloud_archive.has_downloaded_block(height) {
+                    return true;
+                }
+                for chunk in missing_chunks {
+                    self.chain.blocks_delay_tracker.mark_chunk_requested(chunk);
+                }
+                cloud_archive.request_chunks(
+                    missing_chunks.clone(),
+                    *prev_hash,
+                    &self.shards_manager_adapter,
+                );
+                false
+            });
+        }
         if let Some(chunk_distribution) = &self.chunk_distribution_network {
             if chunk_distribution.enabled() {
                 return crate::chunk_distribution_network::request_missing_chunks(
@@ -2136,6 +2439,15 @@ impl Client {
                 }
             };
         self.doomslug.on_approval_message(approval, &block_producer_stakes);
+        if let ApprovalInner::Endorsement(endorsed_hash) = &approval.inner {
+            if let Some(threshold_time) =
+                self.doomslug.approvals_threshold_time(approval.target_height, &approval.inner)
+            {
+                self.chain
+                    .blocks_delay_tracker
+                    .mark_block_approval_quorum(endorsed_hash, threshold_time);
+            }
+        }
     }
 
     /// Find the sync hash. Most of the time it will already be set in `state_sync_info`. If not, try to find it,
@@ -2215,12 +2527,7 @@ impl Client {
                             self.state_sync_future_spawner.clone(),
                             true,
                         ),
-                        sync_status: StateSyncStatus {
-                            sync_hash,
-                            sync_status: HashMap::new(),
-                            download_tasks: Vec::new(),
-                            computation_tasks: Vec::new(),
-                        },
+                        sync_status: StateSyncStatus::new(sync_hash),
                         catchup: BlocksCatchUpState::new(sync_hash, *epoch_id),
                     }
                 });
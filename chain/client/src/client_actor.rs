@@ -39,6 +39,7 @@ use near_chain::chain::{
     ApplyChunksDoneMessage, BlockCatchUpRequest, BlockCatchUpResponse, PostStateReadyMessage,
 };
 use near_chain::resharding::types::ReshardingSender;
+use near_chain::signing_history::SigningHistory;
 use near_chain::spice_core_writer_actor::ProcessedBlock;
 use near_chain::state_snapshot_actor::SnapshotCallbacks;
 use near_chain::test_utils::format_hash;
@@ -158,6 +159,7 @@ pub fn start_client(
     seed: Option<RngSeed>,
     resharding_sender: ReshardingSender,
     spice_client_config: SpiceClientConfig,
+    signing_history: SigningHistory,
 ) -> StartClientResult {
     wait_until_genesis(&chain_genesis.time);
 
@@ -191,6 +193,7 @@ pub fn start_client(
         client_sender_for_client.as_multi_sender(),
         chunk_validation_adapter.as_multi_sender(),
         protocol_upgrade_schedule,
+        signing_history,
     )
     .unwrap();
 
@@ -213,6 +216,7 @@ pub fn start_client(
         runtime.clone(),
         network_adapter.clone().into_sender(),
         client.validator_signer.clone(),
+        client.signing_history.clone(),
         client.config.save_latest_witnesses,
         client.config.save_invalid_witnesses,
         {
@@ -309,7 +313,7 @@ pub struct ClientActor {
     shutdown_signal: Option<broadcast::Sender<()>>,
 
     /// Manages updating the config.
-    config_updater: Option<ConfigUpdater>,
+    pub(crate) config_updater: Option<ConfigUpdater>,
 
     /// With spice chunk executor executes chunks asynchronously.
     /// Should be noop sender otherwise.
@@ -732,6 +736,43 @@ impl
                     self.fastforward_delta == 0,
                 )
             }
+            near_client_primitives::types::SandboxMessage::SandboxSnapshot => {
+                match self.client.sandbox_snapshot() {
+                    Ok(snapshot_id) => {
+                        near_client_primitives::types::SandboxResponse::SandboxSnapshotCreated(
+                            snapshot_id,
+                        )
+                    }
+                    Err(err) => {
+                        near_client_primitives::types::SandboxResponse::SandboxSnapshotFailed(
+                            err.to_string(),
+                        )
+                    }
+                }
+            }
+            near_client_primitives::types::SandboxMessage::SandboxRevert(snapshot_id) => {
+                if self.fastforward_delta > 0 {
+                    return near_client_primitives::types::SandboxResponse::SandboxRevertFailed(
+                        "Cannot revert while a fast_forward request is going on.".to_string(),
+                    );
+                }
+                match self.client.sandbox_revert(snapshot_id) {
+                    Ok(()) => near_client_primitives::types::SandboxResponse::SandboxNoResponse,
+                    Err(err) => near_client_primitives::types::SandboxResponse::SandboxRevertFailed(
+                        err.to_string(),
+                    ),
+                }
+            }
+            near_client_primitives::types::SandboxMessage::SandboxSetBlockTimestamp(timestamp) => {
+                match self.client.sandbox_set_block_timestamp(timestamp) {
+                    Ok(()) => near_client_primitives::types::SandboxResponse::SandboxNoResponse,
+                    Err(err) => {
+                        near_client_primitives::types::SandboxResponse::SandboxSetBlockTimestampFailed(
+                            err.to_string(),
+                        )
+                    }
+                }
+            }
         }
     }
 }
@@ -1014,10 +1055,19 @@ impl ClientActor {
         // Check client is part of the futures validators
         if self.client.is_validator(&next_epoch_id) {
             tracing::debug!(target: "client", validator_id = %signer.validator_id(), "sending announce account for validator");
+            let announce_account = match AnnounceAccount::new(
+                signer.as_ref(),
+                self.node_id.clone(),
+                next_epoch_id,
+            ) {
+                Ok(announce_account) => announce_account,
+                Err(err) => {
+                    // Not updating the announce time, so that we try again on the next check.
+                    tracing::error!(target: "client", ?err, "failed to sign announce account, skipping it");
+                    return;
+                }
+            };
             self.last_validator_announce_time = Some(now);
-
-            let announce_account =
-                AnnounceAccount::new(signer.as_ref(), self.node_id.clone(), next_epoch_id);
             self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
                 NetworkRequests::AnnounceAccount(announce_account),
             ));
@@ -1237,6 +1287,10 @@ impl ClientActor {
                     self.client.update_client_config(updatable_client_config)
                 },
                 &|validator_signer| self.client.update_validator_signer(validator_signer),
+                &|network_config| {
+                    self.network_adapter
+                        .send(PeerManagerMessageRequest::UpdateNetworkConfig(network_config))
+                },
             );
 
             if update_result.validator_signer_updated {
@@ -1263,6 +1317,9 @@ impl ClientActor {
             }
         }
 
+        self.client.process_cloud_archive_downloads(Some(
+            self.client.myself_sender.apply_chunks_done.clone(),
+        ));
         self.try_process_unfinished_blocks();
 
         let mut delay = near_async::time::Duration::seconds(1);
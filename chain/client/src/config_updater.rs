@@ -1,16 +1,38 @@
-use near_chain_configs::UpdatableClientConfig;
-use near_dyn_configs::{UpdatableConfigLoaderError, UpdatableConfigs, UpdatableValidatorSigner};
+use near_async::time::Clock;
+use near_chain_configs::{
+    MutableConfigValue, UpdatableClientConfig, UpdatableNetworkConfig, UpdatableRpcConfig,
+};
+use near_dyn_configs::{
+    UpdatableConfigLoaderError, UpdatableConfigs, UpdatableValidatorSigner, diff_updatable_configs,
+};
 use near_primitives::validator_signer::ValidatorSigner;
+use near_primitives::views::ConfigReloadView;
+use std::collections::VecDeque;
 use std::sync::Arc;
 use tokio::sync::broadcast::Receiver;
 
+/// Number of the most recent reloads reported on the debug page.
+const MAX_RECENT_RELOADS: usize = 16;
+
 /// Manages updating the config encapsulating.
 pub struct ConfigUpdater {
+    clock: Clock,
+
     /// Receives config updates while the node is running.
     rx_config_update: Receiver<Result<UpdatableConfigs, Arc<UpdatableConfigLoaderError>>>,
 
     /// Represents the latest Error of reading the dynamically reload able configs.
     updatable_configs_error: Option<Arc<UpdatableConfigLoaderError>>,
+
+    /// Updatable part of the JSON-RPC config, shared with the JSON-RPC server.
+    rpc_config: Option<MutableConfigValue<UpdatableRpcConfig>>,
+
+    /// Configs received with the last successful reload, the next reload is reported as a
+    /// diff against them.
+    last_configs: Option<UpdatableConfigs>,
+
+    /// Most recent reloads which changed at least one field, oldest first.
+    recent_reloads: VecDeque<ConfigReloadView>,
 }
 
 /// Return type of `ConfigUpdater::try_update()`.
@@ -23,9 +45,23 @@ pub struct ConfigUpdaterResult {
 
 impl ConfigUpdater {
     pub fn new(
+        clock: Clock,
         rx_config_update: Receiver<Result<UpdatableConfigs, Arc<UpdatableConfigLoaderError>>>,
     ) -> Self {
-        Self { rx_config_update, updatable_configs_error: None }
+        Self {
+            clock,
+            rx_config_update,
+            updatable_configs_error: None,
+            rpc_config: None,
+            last_configs: None,
+            recent_reloads: VecDeque::new(),
+        }
+    }
+
+    /// Makes the updater propagate reloads of the JSON-RPC config to `rpc_config`.
+    pub fn with_rpc_config(mut self, rpc_config: MutableConfigValue<UpdatableRpcConfig>) -> Self {
+        self.rpc_config = Some(rpc_config);
+        self
     }
 
     /// Check if any of the configs were updated.
@@ -34,23 +70,36 @@ impl ConfigUpdater {
         &mut self,
         update_client_config_fn: &dyn Fn(UpdatableClientConfig) -> bool,
         update_validator_signer_fn: &dyn Fn(Option<Arc<ValidatorSigner>>) -> bool,
+        update_network_config_fn: &dyn Fn(UpdatableNetworkConfig),
     ) -> ConfigUpdaterResult {
         let mut update_result = ConfigUpdaterResult::default();
         while let Ok(maybe_updatable_configs) = self.rx_config_update.try_recv() {
             match maybe_updatable_configs {
                 Ok(updatable_configs) => {
-                    if let Some(client_config) = updatable_configs.client_config {
+                    self.report_reload(&updatable_configs);
+                    if let Some(client_config) = updatable_configs.client_config.clone() {
                         update_result.client_config_updated |=
                             update_client_config_fn(client_config);
                         tracing::info!(target: "config", "updated client config");
                     }
+                    if let Some(network_config) = updatable_configs.network_config.clone() {
+                        update_network_config_fn(network_config);
+                        tracing::info!(target: "config", "updated network config");
+                    }
+                    if let (Some(rpc_config), Some(new_rpc_config)) =
+                        (&self.rpc_config, updatable_configs.rpc_config.clone())
+                    {
+                        rpc_config.update(new_rpc_config);
+                        tracing::info!(target: "config", "updated rpc config");
+                    }
                     if let UpdatableValidatorSigner::MaybeKey(validator_signer) =
-                        updatable_configs.validator_signer
+                        updatable_configs.validator_signer.clone()
                     {
                         update_result.validator_signer_updated |=
                             update_validator_signer_fn(validator_signer);
                         tracing::info!(target: "config", "updated validator key");
                     }
+                    self.last_configs = Some(updatable_configs);
                     self.updatable_configs_error = None;
                 }
                 Err(err) => {
@@ -61,6 +110,42 @@ impl ConfigUpdater {
         update_result
     }
 
+    /// Logs the fields changed by the reload and records them for the debug page.
+    /// The first configs received are the ones the node started with, so there is nothing
+    /// to compare them to.
+    fn report_reload(&mut self, updatable_configs: &UpdatableConfigs) {
+        let Some(last_configs) = &self.last_configs else {
+            return;
+        };
+        let changes = diff_updatable_configs(last_configs, updatable_configs);
+        if changes.is_empty() {
+            tracing::info!(target: "config", "reloaded configs, no fields changed");
+            return;
+        }
+        for change in &changes {
+            tracing::info!(
+                target: "config",
+                field = %change.field,
+                old_value = change.old_value.as_deref().unwrap_or("<absent>"),
+                new_value = change.new_value.as_deref().unwrap_or("<absent>"),
+                "config field changed by reload"
+            );
+        }
+        near_o11y::event_log::emit(near_o11y::event_log::NodeEvent::ConfigReloaded {
+            fields: changes.iter().map(|change| change.field.clone()).collect(),
+        });
+        if self.recent_reloads.len() == MAX_RECENT_RELOADS {
+            self.recent_reloads.pop_front();
+        }
+        self.recent_reloads
+            .push_back(ConfigReloadView { reload_time: self.clock.now_utc(), changes });
+    }
+
+    /// Returns the most recent reloads which changed at least one field, newest first.
+    pub fn recent_reloads(&self) -> Vec<ConfigReloadView> {
+        self.recent_reloads.iter().rev().cloned().collect()
+    }
+
     /// Prints an error if it's present.
     pub fn report_status(&self) {
         if let Some(updatable_configs_error) = &self.updatable_configs_error {
@@ -197,6 +197,20 @@ impl Handler<DebugStatus, Result<DebugStatusResponse, StatusError>> for ClientAc
             DebugStatus::ChainProcessingStatus => Ok(DebugStatusResponse::ChainProcessingStatus(
                 self.client.chain.get_chain_processing_info(),
             )),
+            DebugStatus::ConfigReloads => Ok(DebugStatusResponse::ConfigReloads(
+                self.config_updater
+                    .as_ref()
+                    .map(|config_updater| config_updater.recent_reloads())
+                    .unwrap_or_default(),
+            )),
+            DebugStatus::BlockTimeline(block_hash) => {
+                let endorsement_tracker = &self.client.chunk_endorsement_tracker;
+                Ok(DebugStatusResponse::BlockTimeline(
+                    self.client.chain.get_block_timeline(&block_hash, &|chunk_hash| {
+                        endorsement_tracker.get_endorsement_timestamps(chunk_hash)
+                    }),
+                ))
+            }
         }
     }
 }
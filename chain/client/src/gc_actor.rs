@@ -8,6 +8,8 @@ use near_chain::{ChainStore, ChainStoreAccess, types::RuntimeAdapter};
 use near_chain_configs::GCConfig;
 use near_epoch_manager::EpochManagerAdapter;
 use near_epoch_manager::shard_tracker::ShardTracker;
+use near_o11y::event_log::{self, NodeEvent};
+use near_primitives::types::BlockHeight;
 use near_store::Store;
 use near_store::db::metadata::DbKind;
 use std::sync::Arc;
@@ -25,6 +27,8 @@ pub struct GCActor {
     is_local_archive: bool,
     /// In some tests we may want to temporarily disable GC
     no_gc: bool,
+    /// Tail and GC stop height after the last GC run, to report only runs which made progress.
+    last_gc_progress: Option<(Option<BlockHeight>, Option<BlockHeight>)>,
 }
 
 impl GCActor {
@@ -45,6 +49,7 @@ impl GCActor {
             shard_tracker,
             is_local_archive,
             no_gc: false,
+            last_gc_progress: None,
         }
     }
 
@@ -94,7 +99,16 @@ impl GCActor {
             tracing::error!(target: "garbage collection", ?e, "error in gc");
             debug_assert!(false, "Error in GCActor");
         }
-        timer.observe_duration();
+        let duration = timer.stop_and_record();
+        let progress = (self.store.tail().ok(), self.store.gc_stop_height().ok());
+        if self.last_gc_progress.replace(progress) != Some(progress) {
+            let (tail, gc_stop_height) = progress;
+            event_log::emit(NodeEvent::GcRun {
+                tail,
+                gc_stop_height,
+                duration_ms: (duration * 1000.0) as u64,
+            });
+        }
     }
 
     fn gc_loop(&mut self, ctx: &mut dyn DelayedActionRunner<Self>) {
@@ -610,7 +610,10 @@ impl InfoHelper {
         // Sign telemetry if there is a signer present.
         if let Some(signer) = signer {
             let content = serde_json::to_string(&json).expect("Telemetry must serialize to JSON");
-            json["signature"] = signer.sign_bytes(content.as_bytes()).to_string().into();
+            match signer.try_sign_bytes(content.as_bytes()) {
+                Ok(signature) => json["signature"] = signature.to_string().into(),
+                Err(err) => tracing::warn!(target: "stats", ?err, "failed to sign telemetry"),
+            }
         }
         json
     }
@@ -740,6 +743,7 @@ pub fn display_sync_status(sync_status: &SyncStatus, head: &Tip) -> String {
             sync_status: shard_statuses,
             download_tasks,
             computation_tasks,
+            ..
         }) => {
             let mut res = format!("State {:?}", sync_hash);
             let mut shard_statuses: Vec<_> = shard_statuses.iter().collect();
@@ -3,9 +3,11 @@ pub use near_client_primitives::types::{
     GetClientConfig, GetExecutionOutcome, GetExecutionOutcomeResponse,
     GetExecutionOutcomesForBlock, GetGasPrice, GetMaintenanceWindows, GetNetworkInfo,
     GetNextLightClientBlock, GetProtocolConfig, GetReceipt, GetShardChunk, GetSplitStorageInfo,
-    GetStateChanges, GetStateChangesInBlock, GetStateChangesWithCauseInBlock,
-    GetStateChangesWithCauseInBlockForTrackedShards, GetValidatorInfo, GetValidatorOrdered, Query,
-    QueryError, Status, StatusResponse, SyncStatus, TxStatus, TxStatusError,
+    GetStateChanges, GetStateChangesBlockHeader, GetStateChangesInBlock,
+    GetStateChangesWithCauseInBlock, GetStateChangesWithCauseInBlockForTrackedShards,
+    GetValidatorForecast, GetValidatorInfo, GetValidatorOrdered, Query, QueryError,
+    SimulateTransaction, SimulateTransactionError, Status, StatusResponse, SyncStatus,
+    TraceReceipt, TraceReceiptError, TxStatus, TxStatusError,
 };
 
 pub use crate::chunk_endorsement_handler::{
@@ -4,6 +4,7 @@ use near_async::{ActorSystem, messaging};
 use near_chain::check_transaction_validity_period;
 use near_chain::types::RuntimeAdapter;
 use near_chain::types::Tip;
+use near_chain_configs::MutableConfigValue;
 use near_chain_configs::MutableValidatorSigner;
 use near_chunks::client::ShardedTransactionPool;
 use near_epoch_manager::EpochManagerAdapter;
@@ -71,7 +72,7 @@ pub fn spawn_rpc_handler_actor(
 #[derive(Clone)]
 pub struct RpcHandlerConfig {
     pub handler_threads: usize,
-    pub tx_routing_height_horizon: u64,
+    pub tx_routing_height_horizon: MutableConfigValue<BlockHeightDelta>,
     pub disable_tx_routing: bool,
     pub epoch_length: u64,
     pub transaction_validity_period: BlockHeightDelta,
@@ -301,8 +302,9 @@ impl RpcHandlerActor {
         let maybe_next_epoch_id = self.get_next_epoch_id_if_at_boundary(&head)?;
 
         let mut validators = HashSet::new();
-        for horizon in (2..=self.config.tx_routing_height_horizon)
-            .chain(vec![self.config.tx_routing_height_horizon * 2].into_iter())
+        let tx_routing_height_horizon = self.config.tx_routing_height_horizon.get();
+        for horizon in
+            (2..=tx_routing_height_horizon).chain(vec![tx_routing_height_horizon * 2].into_iter())
         {
             let target_height = head.height + horizon - 1;
             let validator = self
@@ -364,7 +366,7 @@ impl RpcHandlerActor {
             return Ok(false);
         };
 
-        for i in 1..=self.config.tx_routing_height_horizon {
+        for i in 1..=self.config.tx_routing_height_horizon.get() {
             let chunk_producer = self
                 .epoch_manager
                 .get_chunk_producer_info(&ChunkProductionKey {
@@ -409,8 +411,8 @@ impl RpcHandlerActor {
             self.epoch_manager.get_epoch_start_height(&head.last_block_hash)?
                 + self.config.epoch_length;
 
-        let epoch_boundary_possible =
-            head.height + self.config.tx_routing_height_horizon >= next_epoch_estimated_height;
+        let epoch_boundary_possible = head.height + self.config.tx_routing_height_horizon.get()
+            >= next_epoch_estimated_height;
         if epoch_boundary_possible {
             Ok(Some(self.epoch_manager.get_next_epoch_id_from_prev_block(&head.last_block_hash)?))
         } else {
@@ -300,11 +300,23 @@ impl SpiceChunkValidatorActor {
                 }
             };
 
-            let endorsement = SpiceChunkEndorsement::new(
-                chunk_id,
+            let endorsement = match SpiceChunkEndorsement::try_new(
+                chunk_id.clone(),
                 chunk_execution_result,
                 &signer,
-            );
+            ) {
+                Ok(endorsement) => endorsement,
+                Err(err) => {
+                    tracing::error!(
+                        target: "spice_chunk_validator",
+                        ?err,
+                        ?chunk_id,
+                        ?block_height,
+                        "failed to sign chunk endorsement"
+                    );
+                    return;
+                }
+            };
             send_spice_chunk_endorsement(
                 endorsement.clone(),
                 epoch_manager.as_ref(),
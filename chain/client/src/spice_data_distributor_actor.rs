@@ -338,16 +338,20 @@ impl SpiceDataDistributorActor {
 
         let my_part = distribution_data.parts.swap_remove(me_ord);
 
+        let partial_data = match SpicePartialData::new(
+            data_id,
+            distribution_data.commitment,
+            vec![my_part],
+            &signer,
+        ) {
+            Ok(partial_data) => partial_data,
+            Err(err) => {
+                tracing::error!(target: "spice_data_distribution", ?err, "failed to sign partial data, skipping it");
+                return Ok(());
+            }
+        };
         self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
-            NetworkRequests::SpicePartialData {
-                partial_data: SpicePartialData::new(
-                    data_id,
-                    distribution_data.commitment,
-                    vec![my_part],
-                    &signer,
-                ),
-                recipients,
-            },
+            NetworkRequests::SpicePartialData { partial_data, recipients },
         ));
         Ok(())
     }
@@ -975,12 +979,21 @@ impl SpiceDataDistributorActor {
         // lower-priority way for other nodes that aren't validators (e.g. rpc nodes) to get
         // data they require.
 
+        let partial_data = match SpicePartialData::new(
+            data_id,
+            data.commitment,
+            data.parts,
+            &signer,
+        ) {
+            Ok(partial_data) => partial_data,
+            Err(err) => {
+                tracing::error!(target: "spice_data_distribution", ?requester, ?err, "failed to sign requested partial data, skipping it");
+                return Ok(());
+            }
+        };
         let recipients = HashSet::from([requester]);
         self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
-            NetworkRequests::SpicePartialData {
-                partial_data: SpicePartialData::new(data_id, data.commitment, data.parts, &signer),
-                recipients,
-            },
+            NetworkRequests::SpicePartialData { partial_data, recipients },
         ));
         Ok(())
     }
@@ -1,6 +1,8 @@
 use super::validate::{ChunkRelevance, validate_chunk_endorsement};
 use crate::metrics;
+use near_async::time::{Clock, Utc};
 use near_cache::SyncLruCache;
+use near_chain::blocks_delay_tracker::ChunkEndorsementTimestamps;
 use near_chain_primitives::Error;
 use near_crypto::Signature;
 use near_epoch_manager::EpochManagerAdapter;
@@ -18,24 +20,39 @@ use std::sync::Arc;
 // Ideally, we should not be processing more than num_shards chunks at a time.
 const NUM_CHUNKS_IN_CHUNK_ENDORSEMENTS_CACHE: usize = 100;
 
+/// Endorsements of a recent chunk in the order they were received, reported on the block
+/// timeline.
+#[derive(Clone)]
+struct ReceivedEndorsements {
+    key: ChunkProductionKey,
+    received: Vec<(AccountId, Signature, Utc)>,
+}
+
 /// Module to track chunk endorsements received from chunk validators.
 pub struct ChunkEndorsementTracker {
+    clock: Clock,
     epoch_manager: Arc<dyn EpochManagerAdapter>,
     /// Used to find the chain HEAD when validating partial witnesses.
     store: Store,
     /// We store the validated chunk endorsements received from chunk validators.
     chunk_endorsements:
         SyncLruCache<ChunkProductionKey, HashMap<AccountId, (ChunkHash, Signature)>>,
+    /// Endorsements received for each chunk, reported on the block timeline.
+    received_endorsements: SyncLruCache<ChunkHash, ReceivedEndorsements>,
 }
 
 impl ChunkEndorsementTracker {
-    pub fn new(epoch_manager: Arc<dyn EpochManagerAdapter>, store: Store) -> Self {
+    pub fn new(clock: Clock, epoch_manager: Arc<dyn EpochManagerAdapter>, store: Store) -> Self {
         Self {
+            clock,
             epoch_manager,
             store,
             chunk_endorsements: SyncLruCache::new(
                 NonZeroUsize::new(NUM_CHUNKS_IN_CHUNK_ENDORSEMENTS_CACHE).unwrap().into(),
             ),
+            received_endorsements: SyncLruCache::new(
+                NonZeroUsize::new(NUM_CHUNKS_IN_CHUNK_ENDORSEMENTS_CACHE).unwrap().into(),
+            ),
         }
     }
 
@@ -56,11 +73,15 @@ impl ChunkEndorsementTracker {
         // Validate the chunk endorsement and store it in the cache.
         match validate_chunk_endorsement(self.epoch_manager.as_ref(), &endorsement, &self.store)? {
             ChunkRelevance::Relevant => {
-                let mut cache = self.chunk_endorsements.lock();
-                cache.get_or_insert_mut(key, || HashMap::new()).insert(
-                    account_id.clone(),
-                    (endorsement.chunk_hash(), endorsement.signature()),
-                );
+                {
+                    let mut cache = self.chunk_endorsements.lock();
+                    let entry = cache.get_or_insert_mut(key.clone(), || HashMap::new());
+                    entry.insert(
+                        account_id.clone(),
+                        (endorsement.chunk_hash(), endorsement.signature()),
+                    );
+                }
+                self.record_received_endorsement(key, &endorsement);
                 let shard_id = endorsement.shard_id();
                 metrics::CHUNK_ENDORSEMENTS_ACCEPTED
                     .with_label_values(&[&shard_id.to_string()])
@@ -75,6 +96,48 @@ impl ChunkEndorsementTracker {
         Ok(())
     }
 
+    /// Records when the endorsement was received. Whether the chunk reached enough stake is
+    /// only computed when the timestamps are requested.
+    fn record_received_endorsement(&self, key: ChunkProductionKey, endorsement: &ChunkEndorsement) {
+        let now = self.clock.now_utc();
+        let mut received_endorsements = self.received_endorsements.lock();
+        let entry = received_endorsements.get_or_insert_mut(endorsement.chunk_hash(), || {
+            ReceivedEndorsements { key, received: vec![] }
+        });
+        entry.received.push((endorsement.account_id().clone(), endorsement.signature(), now));
+    }
+
+    /// Returns the timestamps of receiving the endorsements of a recent chunk. Only meant for
+    /// debugging, as it recomputes the endorsement state for every received endorsement.
+    pub fn get_endorsement_timestamps(
+        &self,
+        chunk_hash: &ChunkHash,
+    ) -> Option<ChunkEndorsementTimestamps> {
+        let ReceivedEndorsements { key, received } = self.received_endorsements.get(chunk_hash)?;
+        let first_received = received.first()?.2;
+        let quorum_reached = match self.epoch_manager.get_chunk_validator_assignments(
+            &key.epoch_id,
+            key.shard_id,
+            key.height_created,
+        ) {
+            Ok(chunk_validator_assignments) => (1..=received.len()).find_map(|count| {
+                let validator_signatures = received[..count]
+                    .iter()
+                    .map(|(account_id, signature, _)| (account_id, signature.clone()))
+                    .collect();
+                chunk_validator_assignments
+                    .compute_endorsement_state(validator_signatures)
+                    .is_endorsed
+                    .then_some(received[count - 1].2)
+            }),
+            Err(err) => {
+                tracing::debug!(target: "client", ?err, ?chunk_hash, "failed to get chunk validator assignments");
+                None
+            }
+        };
+        Some(ChunkEndorsementTimestamps { first_received, quorum_reached })
+    }
+
     /// This function is called by block producer potentially multiple times if there's not enough stake.
     pub fn collect_chunk_endorsements(
         &self,
@@ -14,6 +14,7 @@ use near_async::messaging::{Actor, Handler, Sender};
 use near_async::multithread::MultithreadRuntimeHandle;
 use near_async::{ActorSystem, MultiSend, MultiSenderFrom};
 use near_chain::chain::ChunkStateWitnessMessage;
+use near_chain::signing_history::SigningHistory;
 use near_chain::stateless_validation::chunk_validation::{self, MainStateTransitionCache};
 use near_chain::stateless_validation::metrics::CHUNK_WITNESS_VALIDATION_FAILED_TOTAL;
 use near_chain::stateless_validation::processing_tracker::ProcessingDoneTracker;
@@ -78,6 +79,7 @@ pub struct ChunkValidationActor {
     runtime_adapter: Arc<dyn RuntimeAdapter>,
     network_adapter: Sender<PeerManagerMessageRequest>,
     validator_signer: MutableValidatorSigner,
+    signing_history: SigningHistory,
     save_latest_witnesses: bool,
     save_invalid_witnesses: bool,
     validation_spawner: Arc<dyn AsyncComputationSpawner>,
@@ -97,6 +99,7 @@ impl ChunkValidationActor {
         runtime_adapter: Arc<dyn RuntimeAdapter>,
         network_adapter: Sender<PeerManagerMessageRequest>,
         validator_signer: MutableValidatorSigner,
+        signing_history: SigningHistory,
         save_latest_witnesses: bool,
         save_invalid_witnesses: bool,
         validation_spawner: Arc<dyn AsyncComputationSpawner>,
@@ -113,6 +116,7 @@ impl ChunkValidationActor {
             runtime_adapter,
             network_adapter,
             validator_signer,
+            signing_history,
             save_latest_witnesses,
             save_invalid_witnesses,
             validation_spawner,
@@ -133,6 +137,7 @@ impl ChunkValidationActor {
         runtime_adapter: Arc<dyn RuntimeAdapter>,
         network_adapter: Sender<PeerManagerMessageRequest>,
         validator_signer: MutableValidatorSigner,
+        signing_history: SigningHistory,
         save_latest_witnesses: bool,
         save_invalid_witnesses: bool,
         validation_spawner: Arc<dyn AsyncComputationSpawner>,
@@ -149,6 +154,7 @@ impl ChunkValidationActor {
             runtime_adapter,
             network_adapter,
             validator_signer,
+            signing_history,
             save_latest_witnesses,
             save_invalid_witnesses,
             validation_spawner,
@@ -168,6 +174,7 @@ impl ChunkValidationActor {
         runtime_adapter: Arc<dyn RuntimeAdapter>,
         network_adapter: Sender<PeerManagerMessageRequest>,
         validator_signer: MutableValidatorSigner,
+        signing_history: SigningHistory,
         save_latest_witnesses: bool,
         save_invalid_witnesses: bool,
         validation_spawner: Arc<dyn AsyncComputationSpawner>,
@@ -187,6 +194,7 @@ impl ChunkValidationActor {
                 runtime_adapter.clone(),
                 network_adapter.clone(),
                 validator_signer.clone(),
+                signing_history.clone(),
                 save_latest_witnesses,
                 save_invalid_witnesses,
                 validation_spawner.clone(),
@@ -444,6 +452,7 @@ impl ChunkValidationActor {
                         &chunk_header,
                         self.epoch_manager.as_ref(),
                         signer.as_ref(),
+                        &self.signing_history,
                         &self.network_adapter,
                     );
                     return Ok(());
@@ -470,6 +479,7 @@ impl ChunkValidationActor {
         let store = self.chain_store.store();
         let network_adapter = self.network_adapter.clone();
         let signer = signer.clone();
+        let signing_history = self.signing_history.clone();
         let rs = self.rs.clone();
 
         self.validation_spawner.spawn("stateless_validation", move || {
@@ -491,6 +501,7 @@ impl ChunkValidationActor {
                         &chunk_header,
                         epoch_manager.as_ref(),
                         signer.as_ref(),
+                        &signing_history,
                         &network_adapter,
                     );
                 }
@@ -2,8 +2,10 @@ pub mod orphan_witness_pool;
 
 use itertools::Itertools;
 use near_async::messaging::Sender;
+use near_chain::signing_history::{SigningHistory, SigningKind};
 use near_epoch_manager::EpochManagerAdapter;
 use near_network::types::{NetworkRequests, PeerManagerMessageRequest};
+use near_o11y::event_log::{self, NodeEvent};
 use near_primitives::sharding::ShardChunkHeader;
 use near_primitives::stateless_validation::chunk_endorsement::ChunkEndorsement;
 use near_primitives::validator_signer::ValidatorSigner;
@@ -19,10 +21,12 @@ const NUM_NEXT_BLOCK_PRODUCERS_TO_SEND_CHUNK_ENDORSEMENT: u64 = 5;
 /// `NUM_NEXT_BLOCK_PRODUCERS_TO_SEND_CHUNK_ENDORSEMENT` block producers.
 /// Additionally returns chunk endorsement if the signer is one of these block
 /// producers, to be able to process it immediately.
+/// Nothing is signed or sent if the endorsement conflicts with the signing history.
 pub(crate) fn send_chunk_endorsement_to_block_producers(
     chunk_header: &ShardChunkHeader,
     epoch_manager: &dyn EpochManagerAdapter,
     signer: &ValidatorSigner,
+    signing_history: &SigningHistory,
     network_sender: &Sender<PeerManagerMessageRequest>,
 ) -> Option<ChunkEndorsement> {
     let _span = tracing::debug_span!(
@@ -59,7 +63,26 @@ pub(crate) fn send_chunk_endorsement_to_block_producers(
         "send_chunk_endorsement",
     );
 
-    let endorsement = ChunkEndorsement::new(epoch_id, chunk_header, signer);
+    signing_history
+        .check_and_record(
+            chunk_header.height_created(),
+            SigningKind::ChunkEndorsement,
+            Some(chunk_header.shard_id()),
+            chunk_hash.0,
+        )
+        .ok()?;
+    let endorsement = match ChunkEndorsement::try_new(epoch_id, chunk_header, signer) {
+        Ok(endorsement) => endorsement,
+        Err(err) => {
+            tracing::error!(target: "client", ?chunk_hash, ?err, "failed to sign the chunk endorsement, skipping it");
+            return None;
+        }
+    };
+    event_log::emit(NodeEvent::EndorsementSent {
+        height: chunk_header.height_created(),
+        shard_id: chunk_header.shard_id(),
+        chunk_hash: chunk_hash.0,
+    });
     let mut send_to_itself = None;
     for block_producer in block_producers {
         if &block_producer == signer.validator_id() {
@@ -318,7 +318,7 @@ impl PartialWitnessActor {
             .into_par_iter()
             .zip_eq(parts)
             .enumerate()
-            .map(|(part_ord, (validator, part))| {
+            .filter_map(|(part_ord, (validator, part))| {
                 let partial_deploys = PartialEncodedContractDeploys::new(
                     key.clone(),
                     PartialEncodedContractDeploysPart {
@@ -328,7 +328,14 @@ impl PartialWitnessActor {
                     },
                     &signer,
                 );
-                (validator, partial_deploys)
+                // The validators can still decode the deploys from the other parts.
+                match partial_deploys {
+                    Ok(partial_deploys) => Some((validator, partial_deploys)),
+                    Err(err) => {
+                        tracing::error!(target: "client", part_ord, ?err, "failed to sign contract deploys part, skipping it");
+                        None
+                    }
+                }
             })
             .collect())
     }
@@ -685,12 +692,18 @@ impl PartialWitnessActor {
                 .get_epoch_chunk_producers_for_shard(&key.epoch_id, key.shard_id)?;
             chunk_producers.swap_remove(rand::thread_rng().gen_range(0..chunk_producers.len()))
         };
-        let request = ContractCodeRequest::new(
+        let request = match ContractCodeRequest::new(
             key.clone(),
             missing_contract_hashes,
             accesses.main_transition().clone(),
             &signer,
-        );
+        ) {
+            Ok(request) => request,
+            Err(err) => {
+                tracing::error!(target: "client", ?key, ?err, "failed to sign contract code request, skipping it");
+                return Ok(());
+            }
+        };
         self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
             NetworkRequests::ContractCodeRequest(random_chunk_producer, request),
         ));
@@ -721,11 +734,20 @@ impl PartialWitnessActor {
             .filter(|validator| !chunk_producers.contains(*validator))
             .cloned()
             .collect();
+        let accesses = match ChunkContractAccesses::new(
+            key,
+            contract_accesses,
+            main_transition,
+            my_signer,
+        ) {
+            Ok(accesses) => accesses,
+            Err(err) => {
+                tracing::error!(target: "client", ?err, "failed to sign chunk contract accesses, skipping them");
+                return;
+            }
+        };
         self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
-            NetworkRequests::ChunkContractAccesses(
-                target_chunk_validators,
-                ChunkContractAccesses::new(key, contract_accesses, main_transition, my_signer),
-            ),
+            NetworkRequests::ChunkContractAccesses(target_chunk_validators, accesses),
         ));
     }
 
@@ -912,10 +934,10 @@ pub fn generate_state_witness_parts(
         .par_iter()
         .zip_eq(parts)
         .enumerate()
-        .map(|(part_ord, (chunk_validator, part))| {
+        .filter_map(|(part_ord, (chunk_validator, part))| {
             // It's fine to unwrap part here as we just constructed the parts above and we expect
             // all of them to be present.
-            let partial_witness = PartialEncodedStateWitness::new(
+            let partial_witness = PartialEncodedStateWitness::try_new(
                 epoch_id,
                 chunk_header.clone(),
                 part_ord,
@@ -923,7 +945,14 @@ pub fn generate_state_witness_parts(
                 encoded_length,
                 signer,
             );
-            (chunk_validator.clone(), partial_witness)
+            // The validators can still reconstruct the witness from the other parts.
+            match partial_witness {
+                Ok(partial_witness) => Some((chunk_validator.clone(), partial_witness)),
+                Err(err) => {
+                    tracing::error!(target: "client", part_ord, ?err, "failed to sign state witness part, skipping it");
+                    None
+                }
+            }
         })
         .collect()
 }
@@ -59,6 +59,7 @@ impl Client {
                 &chunk_header,
                 self.epoch_manager.as_ref(),
                 my_signer.as_ref(),
+                &self.signing_history,
                 &self.network_adapter.clone().into_sender(),
             ) {
                 self.chunk_endorsement_tracker.process_chunk_endorsement(endorsement)?;
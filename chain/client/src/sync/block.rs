@@ -1,3 +1,4 @@
+use crate::sync::cloud_archive::CloudArchiveSync;
 use near_async::messaging::CanSend;
 use near_async::time::{Clock, Duration, Utc};
 use near_chain::Chain;
@@ -11,6 +12,8 @@ use near_primitives::block::Tip;
 use near_primitives::hash::CryptoHash;
 use near_primitives::types::{BlockHeight, BlockHeightDelta};
 use rand::seq::IteratorRandom;
+use std::collections::HashMap;
+use std::sync::Arc;
 use tracing::instrument;
 
 /// Expect to receive the requested block in this time.
@@ -41,6 +44,14 @@ pub struct BlockSync {
 
     /// Whether State Sync should be enabled when a node falls far enough behind.
     state_sync_enabled: bool,
+
+    /// Cloud archive to fetch the blocks from when peers can't provide them.
+    cloud_archive: Option<Arc<CloudArchiveSync>>,
+
+    /// When the blocks were first requested from peers. A block that is still
+    /// missing after `BLOCK_REQUEST_TIMEOUT_MS` is fetched from the cloud
+    /// archive instead. Only tracked if the cloud archive is configured.
+    requested_from_peers: HashMap<CryptoHash, Utc>,
 }
 
 impl BlockSync {
@@ -50,6 +61,7 @@ impl BlockSync {
         block_fetch_horizon: BlockHeightDelta,
         archive: bool,
         state_sync_enabled: bool,
+        cloud_archive: Option<Arc<CloudArchiveSync>>,
     ) -> Self {
         BlockSync {
             clock,
@@ -58,6 +70,8 @@ impl BlockSync {
             block_fetch_horizon,
             archive,
             state_sync_enabled,
+            cloud_archive,
+            requested_from_peers: HashMap::new(),
         }
     }
 
@@ -235,6 +249,17 @@ impl BlockSync {
             }
 
             let next_height = chain.get_block_header(&next_hash)?.height();
+            if let Some(cloud_archive) = &self.cloud_archive {
+                let timed_out = self.requested_from_peers.get(&next_hash).is_some_and(|when| {
+                    self.clock.now_utc() - *when > Duration::milliseconds(BLOCK_REQUEST_TIMEOUT_MS)
+                });
+                if timed_out {
+                    // Peers didn't provide the block in time, fall back to the cloud archive.
+                    cloud_archive.request_block(next_height, next_hash);
+                    num_requests += 1;
+                    continue;
+                }
+            }
             let request_from_archival = self.archive && next_height < gc_stop_height;
             // Assume that heads of `highest_height_peers` are ahead of the blocks we're requesting.
             let peer = if request_from_archival {
@@ -263,6 +288,18 @@ impl BlockSync {
                         peer_id: peer.peer_info.id.clone(),
                     },
                 ));
+                if self.cloud_archive.is_some() {
+                    self.requested_from_peers.entry(next_hash).or_insert(self.clock.now_utc());
+                }
+                num_requests += 1;
+            } else if let Some(cloud_archive) = &self.cloud_archive {
+                tracing::debug!(
+                    target: "sync",
+                    block_hash = ?next_hash,
+                    block_height = next_height,
+                    request_from_archival,
+                    "no available peers to request a block from, falling back to the cloud archive");
+                cloud_archive.request_block(next_height, next_hash);
                 num_requests += 1;
             } else {
                 tracing::warn!(
@@ -274,6 +311,11 @@ impl BlockSync {
             }
         }
         span.record("num_requests", num_requests);
+        if let Some(cloud_archive) = &self.cloud_archive {
+            cloud_archive.prune(chain_head.height);
+            self.requested_from_peers
+                .retain(|hash, _| matches!(chain.block_exists(hash), Ok(false)));
+        }
         Ok(())
     }
 
@@ -0,0 +1,147 @@
+//! Fallback of block sync to a cloud archive.
+//!
+//! When peers can't provide the blocks a node needs to catch up, e.g. because
+//! archival peers are scarce or slow, blocks and their chunks are downloaded
+//! from a cloud archive written by a cloud archival writer instead. Downloads
+//! run in the background; the client picks up the results with
+//! [`CloudArchiveSync::take_blocks`] and [`CloudArchiveSync::take_chunks`] and
+//! verifies them the same way as the blocks and chunks received from peers.
+
+use near_async::futures::{FutureSpawner, FutureSpawnerExt};
+use near_async::messaging::Sender;
+use near_chain_configs::BlockSyncCloudArchiveConfig;
+use near_chunks::adapter::ShardsManagerRequestFromClient;
+use near_primitives::block::Block;
+use near_primitives::hash::CryptoHash;
+use near_primitives::sharding::{ChunkHash, ShardChunk, ShardChunkHeader};
+use near_primitives::types::BlockHeight;
+use near_store::archive::cloud_storage::CloudStorage;
+use near_store::archive::cloud_storage::config::{CloudArchivalConfig, CloudStorageConfig};
+use near_store::archive::cloud_storage::opener::CloudStorageOpener;
+use parking_lot::Mutex;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Downloads blocks and chunks from the cloud archive for block sync.
+pub struct CloudArchiveSync {
+    cloud_storage: Arc<CloudStorage>,
+    future_spawner: Arc<dyn FutureSpawner>,
+    downloads: Arc<Mutex<Downloads>>,
+}
+
+#[derive(Default)]
+struct Downloads {
+    /// Heights of the blocks being downloaded.
+    pending_blocks: HashSet<BlockHeight>,
+    /// Chunks being downloaded.
+    pending_chunks: HashSet<ChunkHash>,
+    /// Downloaded blocks which are not yet taken by the client.
+    blocks: Vec<Arc<Block>>,
+    /// Downloaded chunks, together with the header they were requested for,
+    /// which are not yet taken by the client.
+    chunks: Vec<(ShardChunkHeader, ShardChunk)>,
+    /// Heights of the downloaded blocks. Peers which couldn't provide these
+    /// blocks are unlikely to have their chunks either, so the chunks are
+    /// downloaded from the cloud archive as well.
+    block_heights: HashSet<BlockHeight>,
+}
+
+impl CloudArchiveSync {
+    pub fn from_config(
+        config: &BlockSyncCloudArchiveConfig,
+        future_spawner: Arc<dyn FutureSpawner>,
+    ) -> std::io::Result<Self> {
+        let cloud_storage = CloudStorageConfig {
+            location: config.location.clone(),
+            credentials_file: config.credentials_file.clone(),
+        };
+        let cloud_storage =
+            CloudStorageOpener::new(CloudArchivalConfig { cloud_storage }).open()?;
+        Ok(Self { cloud_storage, future_spawner, downloads: Default::default() })
+    }
+
+    /// Starts downloading the block at the given height, unless it is already
+    /// being downloaded. The hash is only used to check the downloaded block.
+    pub fn request_block(&self, block_height: BlockHeight, block_hash: CryptoHash) {
+        if !self.downloads.lock().pending_blocks.insert(block_height) {
+            return;
+        }
+        tracing::debug!(target: "sync", block_height, ?block_hash, "requesting block from the cloud archive");
+        let cloud_storage = self.cloud_storage.clone();
+        let downloads = self.downloads.clone();
+        self.future_spawner.spawn("block sync cloud archive block", async move {
+            let result = cloud_storage.retrieve_block(block_height).await;
+            let mut downloads = downloads.lock();
+            downloads.pending_blocks.remove(&block_height);
+            match result {
+                Ok(block) if block.hash() == &block_hash => {
+                    downloads.block_heights.insert(block_height);
+                    downloads.blocks.push(Arc::new(block));
+                }
+                Ok(block) => {
+                    tracing::warn!(target: "sync", block_height, ?block_hash, got_block_hash = ?block.hash(), "cloud archive has a different block at the height");
+                }
+                Err(err) => {
+                    tracing::warn!(target: "sync", block_height, ?err, "failed to download block from the cloud archive");
+                }
+            }
+        });
+    }
+
+    /// Whether the block at the given height was downloaded from the cloud
+    /// archive, in which case its chunks should be requested from it as well.
+    pub fn has_downloaded_block(&self, block_height: BlockHeight) -> bool {
+        self.downloads.lock().block_heights.contains(&block_height)
+    }
+
+    /// Starts downloading the given chunks. The chunks which can't be
+    /// downloaded are requested from peers through the shards manager.
+    pub fn request_chunks(
+        &self,
+        chunks: Vec<ShardChunkHeader>,
+        prev_hash: CryptoHash,
+        shards_manager_adapter: &Sender<ShardsManagerRequestFromClient>,
+    ) {
+        for header in chunks {
+            if !self.downloads.lock().pending_chunks.insert(header.chunk_hash().clone()) {
+                continue;
+            }
+            let block_height = header.height_created();
+            let shard_id = header.shard_id();
+            tracing::debug!(target: "sync", block_height, %shard_id, chunk_hash = ?header.chunk_hash(), "requesting chunk from the cloud archive");
+            let cloud_storage = self.cloud_storage.clone();
+            let downloads = self.downloads.clone();
+            let adapter = shards_manager_adapter.clone();
+            self.future_spawner.spawn("block sync cloud archive chunk", async move {
+                let result = cloud_storage.retrieve_chunk(block_height, shard_id).await;
+                let mut downloads = downloads.lock();
+                downloads.pending_chunks.remove(header.chunk_hash());
+                match result {
+                    Ok(chunk) => downloads.chunks.push((header, chunk)),
+                    Err(err) => {
+                        tracing::debug!(target: "sync", block_height, %shard_id, ?err, "failed to download chunk from the cloud archive, requesting it from peers");
+                        adapter.send(ShardsManagerRequestFromClient::RequestChunks {
+                            chunks_to_request: vec![header],
+                            prev_hash,
+                        });
+                    }
+                }
+            });
+        }
+    }
+
+    /// Returns the downloaded blocks which are not taken yet.
+    pub fn take_blocks(&self) -> Vec<Arc<Block>> {
+        std::mem::take(&mut self.downloads.lock().blocks)
+    }
+
+    /// Returns the downloaded chunks which are not taken yet.
+    pub fn take_chunks(&self) -> Vec<(ShardChunkHeader, ShardChunk)> {
+        std::mem::take(&mut self.downloads.lock().chunks)
+    }
+
+    /// Forgets about the downloaded blocks below the given height.
+    pub fn prune(&self, min_block_height: BlockHeight) {
+        self.downloads.lock().block_heights.retain(|height| *height >= min_block_height);
+    }
+}
@@ -31,6 +31,7 @@ use near_store::Store;
 use near_store::adapter::{StoreAdapter, StoreUpdateAdapter};
 use parking_lot::Mutex;
 use rand::seq::SliceRandom;
+use std::path::Path;
 use std::sync::Arc;
 use tracing::instrument;
 
@@ -45,6 +46,9 @@ pub struct EpochSync {
     last_epoch_sync_response_cache: Arc<Mutex<Option<(EpochId, CompressedEpochSyncProof)>>>,
     // See `my_own_epoch_sync_boundary_block_header()`.
     my_own_epoch_sync_boundary_block_header: Option<Arc<BlockHeader>>,
+    /// The proof read from `EpochSyncConfig::proof_file` in the background, once the read
+    /// is started. The inner value is set when the read finishes.
+    proof_from_file: Option<Arc<Mutex<Option<Result<EpochSyncProof, Error>>>>>,
 }
 
 impl EpochSync {
@@ -70,6 +74,7 @@ impl EpochSync {
             config,
             last_epoch_sync_response_cache: Arc::new(Mutex::new(None)),
             my_own_epoch_sync_boundary_block_header,
+            proof_from_file: None,
         }
     }
 
@@ -185,7 +190,7 @@ impl EpochSync {
         highest_height: BlockHeight,
         highest_height_peers: &[HighestHeightPeerInfo],
     ) -> Result<(), Error> {
-        if self.config.disable_epoch_sync_for_bootstrapping {
+        if self.config.disable_epoch_sync_for_bootstrapping || self.config.proof_file.is_some() {
             return Ok(());
         }
         let tip_height = chain.chain_store().header_head()?.height;
@@ -275,7 +280,80 @@ impl EpochSync {
             return Ok(());
         }
 
-        self.verify_proof(&proof, epoch_manager)?;
+        let epoch_id = *proof.current_epoch.first_block_header_in_epoch.epoch_id();
+        self.save_proof(chain, proof, epoch_manager)?;
+        *status = SyncStatus::EpochSyncDone;
+        tracing::info!(?epoch_id, "bootstrapped from epoch sync");
+        Ok(())
+    }
+
+    /// Bootstraps the node from the epoch sync proof in `EpochSyncConfig::proof_file`, if set,
+    /// instead of requesting a proof from peers. The file is read and decoded on the async
+    /// computation spawner; the proof is verified and applied by a later call once it is read.
+    /// Returns true while the proof is being read, in which case no other sync should run, as
+    /// epoch sync only applies at genesis. Does nothing unless the node is at genesis.
+    pub fn run_from_proof_file(
+        &mut self,
+        status: &mut SyncStatus,
+        chain: &mut Chain,
+    ) -> Result<bool, Error> {
+        let Some(proof_file) = self.config.proof_file.clone() else {
+            return Ok(false);
+        };
+        if self.config.disable_epoch_sync_for_bootstrapping
+            || chain.chain_store().header_head()?.height != chain.genesis().height()
+        {
+            return Ok(false);
+        }
+        let Some(proof_from_file) = &self.proof_from_file else {
+            tracing::info!(proof_file = %proof_file.display(), "bootstrapping node via epoch sync proof file");
+            let proof_from_file = Arc::new(Mutex::new(None));
+            self.proof_from_file = Some(proof_from_file.clone());
+            self.async_computation_spawner.spawn("read epoch sync proof file", move || {
+                *proof_from_file.lock() = Some(read_epoch_sync_proof_file(&proof_file));
+            });
+            return Ok(true);
+        };
+        let Some(proof) = proof_from_file.lock().take() else {
+            return Ok(true);
+        };
+        // Read the file again on the next call if the proof can't be applied.
+        self.proof_from_file = None;
+        let proof = proof?.into_v1();
+        let first_block_header_in_epoch = proof.current_epoch.first_block_header_in_epoch.clone();
+        let epoch_manager = chain.epoch_manager.clone();
+        self.save_proof(chain, proof, epoch_manager.as_ref())?;
+        *status = SyncStatus::EpochSyncDone;
+        tracing::info!(proof_file = %proof_file.display(), epoch_id = ?first_block_header_in_epoch.epoch_id(), "bootstrapped from epoch sync proof file");
+        self.my_own_epoch_sync_boundary_block_header = Some(Arc::new(first_block_header_in_epoch));
+        Ok(false)
+    }
+
+    /// Derives an epoch sync proof from the local database and verifies it, so that it can be
+    /// written to a file with `write_epoch_sync_proof_file` and used to bootstrap another node.
+    pub fn export_proof(
+        store: Store,
+        genesis: &BlockHeader,
+        transaction_validity_period: BlockHeightDelta,
+        epoch_manager: &dyn EpochManagerAdapter,
+    ) -> Result<CompressedEpochSyncProof, Error> {
+        let compressed_proof =
+            Self::derive_epoch_sync_proof(store, transaction_validity_period, Default::default())?;
+        let (proof, _) = compressed_proof.decode().map_err(|err| {
+            Error::Other(format!("Failed to uncompress epoch sync proof: {:?}", err))
+        })?;
+        Self::verify_proof(genesis, &proof.into_v1(), epoch_manager)?;
+        Ok(compressed_proof)
+    }
+
+    /// Verifies the proof and initializes the chain and the epoch manager with it.
+    fn save_proof(
+        &self,
+        chain: &mut Chain,
+        proof: EpochSyncProofV1,
+        epoch_manager: &dyn EpochManagerAdapter,
+    ) -> Result<(), Error> {
+        Self::verify_proof(&self.genesis, &proof, epoch_manager)?;
 
         let store = chain.chain_store.store();
         let mut store_update = store.store_update();
@@ -347,15 +425,13 @@ impl EpochSync {
         );
 
         store_update.commit()?;
-
-        *status = SyncStatus::EpochSyncDone;
-        tracing::info!(epoch_id=?last_header.epoch_id(), "bootstrapped from epoch sync");
-
         Ok(())
     }
 
-    fn verify_proof(
-        &self,
+    /// Verifies the proof against the genesis block. Besides the genesis, only the epoch info
+    /// of the second epoch after genesis is needed from the epoch manager.
+    pub fn verify_proof(
+        genesis: &BlockHeader,
         proof: &EpochSyncProofV1,
         epoch_manager: &dyn EpochManagerAdapter,
     ) -> Result<(), Error> {
@@ -367,7 +443,7 @@ impl EpochSync {
         }
 
         // Verify block producer handoff to the second epoch after genesis.
-        let second_next_epoch_id_after_genesis = EpochId(*self.genesis.hash());
+        let second_next_epoch_id_after_genesis = EpochId(*genesis.hash());
         let second_next_epoch_info_after_genesis =
             epoch_manager.get_epoch_info(&second_next_epoch_id_after_genesis)?;
         if all_epochs[0].block_producers
@@ -570,6 +646,31 @@ impl EpochSync {
     }
 }
 
+/// Writes the proof to a file, in the same encoding as it is sent over the network.
+pub fn write_epoch_sync_proof_file(
+    path: &Path,
+    proof: &CompressedEpochSyncProof,
+) -> std::io::Result<()> {
+    std::fs::write(path, proof.as_ref())
+}
+
+/// Reads a proof written by `write_epoch_sync_proof_file`. The proof is not verified.
+pub fn read_epoch_sync_proof_file(path: &Path) -> Result<EpochSyncProof, Error> {
+    let bytes = std::fs::read(path).map_err(|err| {
+        Error::Other(format!("Failed to read epoch sync proof from {}: {}", path.display(), err))
+    })?;
+    let (proof, _) = CompressedEpochSyncProof::from_boxed_slice(bytes.into_boxed_slice())
+        .decode()
+        .map_err(|err| {
+            Error::InvalidEpochSyncProof(format!(
+                "failed to uncompress epoch sync proof from {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+    Ok(proof)
+}
+
 impl Handler<EpochSyncRequestMessage> for ClientActor {
     fn handle(&mut self, msg: EpochSyncRequestMessage) {
         if self.client.sync_handler.epoch_sync.config.ignore_epoch_sync_network_requests {
@@ -90,6 +90,10 @@ impl SyncHandler {
         apply_chunks_done_sender: Option<ApplyChunksDoneSender>,
     ) -> Option<SyncHandlerRequest> {
         // Run epoch sync first; if this is applicable then nothing else is.
+        let proof_file_result = self.epoch_sync.run_from_proof_file(&mut self.sync_status, chain);
+        if unwrap_and_report_state_sync_result!(proof_file_result) {
+            return None;
+        }
         let epoch_sync_result = self.epoch_sync.run(
             &mut self.sync_status,
             &chain,
@@ -166,6 +170,8 @@ impl SyncHandler {
             apply_chunks_done_sender,
         );
         unwrap_and_report_state_sync_result!(reset_heads_result);
+        let clear_progress_result = self.state_sync.clear_progress();
+        unwrap_and_report_state_sync_result!(clear_progress_result);
         self.sync_status.update(SyncStatus::StateSyncDone);
 
         Some(SyncHandlerRequest::NeedProcessBlockArtifact(block_processing_artifacts))
@@ -182,6 +188,13 @@ impl SyncHandler {
         } else {
             return Ok(());
         };
+        // If the node restarted during state sync to the same hash, the data was already reset
+        // and resetting it again would lose the applied parts.
+        if let Some(state_sync_status) = self.state_sync.resume(sync_hash)? {
+            self.sync_status.update(SyncStatus::StateSync(state_sync_status));
+            self.last_time_sync_block_requested.clear();
+            return Ok(());
+        }
         if !self.config.archive {
             let runtime_adapter = chain.runtime_adapter.clone();
             let epoch_manager = chain.epoch_manager.clone();
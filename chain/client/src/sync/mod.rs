@@ -1,4 +1,5 @@
 pub mod block;
+pub mod cloud_archive;
 pub mod epoch;
 pub mod external;
 pub mod handler;
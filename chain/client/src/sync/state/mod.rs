@@ -2,6 +2,7 @@ pub mod chain_requests;
 mod downloader;
 mod external;
 mod network;
+mod progress;
 mod shard;
 mod task_tracker;
 mod util;
@@ -18,7 +19,7 @@ use near_async::time::{Clock, Duration};
 use near_chain::Chain;
 use near_chain::types::RuntimeAdapter;
 use near_chain_configs::{ExternalStorageConfig, StateSyncConfig, SyncConcurrency, SyncConfig};
-use near_client_primitives::types::{ShardSyncStatus, StateSyncStatus};
+use near_client_primitives::types::{ShardSyncProgress, ShardSyncStatus, StateSyncStatus};
 use near_epoch_manager::EpochManagerAdapter;
 use near_external_storage::S3AccessConfig;
 use near_network::client::StateResponse;
@@ -31,7 +32,8 @@ use near_primitives::types::ShardId;
 use near_store::Store;
 use network::{StateSyncDownloadSourcePeer, StateSyncDownloadSourcePeerSharedState};
 use parking_lot::Mutex;
-use shard::{StateSyncShardHandle, run_state_sync_for_shard};
+use progress::{StateSyncProgress, clear_progress, load_progress, save_progress};
+use shard::{StateSyncShardHandle, load_memtrie_for_synced_shard, run_state_sync_for_shard};
 use std::collections::HashMap;
 use std::collections::hash_map::Entry;
 use std::sync::Arc;
@@ -77,6 +79,12 @@ pub struct StateSync {
     /// failure there indicates the file is yet to be uploaded, in which
     /// case we want to avoid spamming requests aggressively.
     min_delay_before_reattempt: Duration,
+
+    /// Whether the progress is persisted so that state sync resumes after a restart. Catchup
+    /// doesn't need it, because it starts over from the same block after a restart.
+    persist_progress: bool,
+    /// The progress as last persisted, to avoid writing it again if nothing changed.
+    persisted_progress: Option<StateSyncProgress>,
 }
 
 impl StateSync {
@@ -185,7 +193,48 @@ impl StateSync {
             shard_syncs: HashMap::new(),
             concurrency_config: sync_config.concurrency,
             min_delay_before_reattempt,
+            persist_progress: !catchup,
+            persisted_progress: None,
+        }
+    }
+
+    /// Returns the status of the state sync to `sync_hash` which was in progress when the node
+    /// stopped, if any. The shards which were done have their memtries loaded again; the other
+    /// shards continue from the phase they were in, reusing the parts already downloaded or
+    /// applied. The progress of a state sync to a different hash is discarded.
+    pub fn resume(
+        &mut self,
+        sync_hash: CryptoHash,
+    ) -> Result<Option<StateSyncStatus>, near_chain::Error> {
+        let Some(progress) = load_progress(&self.store)? else {
+            return Ok(None);
+        };
+        if progress.sync_hash != sync_hash {
+            tracing::info!(target: "sync", old_sync_hash = %progress.sync_hash, %sync_hash, "discarding progress of state sync to a different sync hash");
+            clear_progress(&self.store)?;
+            return Ok(None);
+        }
+        let status = progress.to_status();
+        for (shard_id, shard_status) in &status.sync_status {
+            if *shard_status == ShardSyncStatus::StateSyncDone {
+                load_memtrie_for_synced_shard(
+                    &self.store,
+                    *shard_id,
+                    sync_hash,
+                    self.runtime.as_ref(),
+                    self.epoch_manager.as_ref(),
+                )?;
+            }
         }
+        tracing::info!(target: "sync", %sync_hash, shard_sync_status = ?status.sync_status, "resuming state sync");
+        self.persisted_progress = Some(progress);
+        Ok(Some(status))
+    }
+
+    /// Forgets the persisted progress once the state sync is completed.
+    pub fn clear_progress(&mut self) -> Result<(), near_chain::Error> {
+        self.persisted_progress = None;
+        clear_progress(&self.store)
     }
 
     /// Apply a state sync message received from a peer.
@@ -213,31 +262,34 @@ impl StateSync {
         for shard_id in tracking_shards {
             let key = (sync_hash, *shard_id);
             let status = match self.shard_syncs.entry(key) {
-                Entry::Occupied(mut entry) => match entry.get_mut().result.try_recv() {
-                    Ok(result) => {
-                        entry.remove();
-                        if let Err(err) = result {
-                            tracing::error!(%shard_id, ?err, "state sync failed for shard");
-                            return Err(err);
+                Entry::Occupied(mut entry) => {
+                    sync_status.shard_progress.insert(*shard_id, entry.get().progress());
+                    match entry.get_mut().result.try_recv() {
+                        Ok(result) => {
+                            entry.remove();
+                            if let Err(err) = result {
+                                tracing::error!(%shard_id, ?err, "state sync failed for shard");
+                                return Err(err);
+                            }
+                            ShardSyncStatus::StateSyncDone
                         }
-                        ShardSyncStatus::StateSyncDone
-                    }
-                    Err(TryRecvError::Closed) => {
-                        return Err(near_chain::Error::Other(
-                            "Shard result channel somehow closed".to_owned(),
-                        ));
+                        Err(TryRecvError::Closed) => {
+                            return Err(near_chain::Error::Other(
+                                "Shard result channel somehow closed".to_owned(),
+                            ));
+                        }
+                        Err(TryRecvError::Empty) => entry.get().status(),
                     }
-                    Err(TryRecvError::Empty) => entry.get().status(),
-                },
+                }
                 Entry::Vacant(entry) => {
-                    if sync_status
-                        .sync_status
-                        .get(&shard_id)
-                        .is_some_and(|status| *status == ShardSyncStatus::StateSyncDone)
-                    {
+                    // Set if the shard was synced before, either before the node restarted or
+                    // before the shard sync failed.
+                    let resume_from = sync_status.sync_status.get(&shard_id).copied();
+                    if resume_from == Some(ShardSyncStatus::StateSyncDone) {
                         continue;
                     }
                     let status = Arc::new(Mutex::new(ShardSyncStatus::StateDownloadHeader));
+                    let progress = Arc::new(Mutex::new(ShardSyncProgress::default()));
                     let cancel = CancellationToken::new();
                     let shard_sync = run_state_sync_for_shard(
                         self.store.clone(),
@@ -248,6 +300,8 @@ impl StateSync {
                         self.epoch_manager.clone(),
                         self.computation_task_tracker.clone(),
                         status.clone(),
+                        progress.clone(),
+                        resume_from,
                         self.chain_requests_sender.clone().into_async_sender(),
                         cancel.clone(),
                         self.future_spawner.clone(),
@@ -259,7 +313,8 @@ impl StateSync {
                     self.future_spawner.spawn("shard sync", async move {
                         sender.send(shard_sync.await).ok();
                     });
-                    let handle = StateSyncShardHandle { status, result: receiver, cancel };
+                    let handle =
+                        StateSyncShardHandle { status, progress, result: receiver, cancel };
                     let ret = handle.status();
                     entry.insert(handle);
                     ret
@@ -282,6 +337,13 @@ impl StateSync {
 
         sync_status.download_tasks = self.downloading_task_tracker.statuses();
         sync_status.computation_tasks = self.computation_task_tracker.statuses();
+        if self.persist_progress {
+            let progress = StateSyncProgress::from_status(sync_status);
+            if self.persisted_progress.as_ref() != Some(&progress) {
+                save_progress(&self.store, &progress)?;
+                self.persisted_progress = Some(progress);
+            }
+        }
         Ok(if all_done { StateSyncResult::Completed } else { StateSyncResult::InProgress })
     }
 }
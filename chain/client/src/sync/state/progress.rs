@@ -0,0 +1,89 @@
+//! Persisted progress of state sync, so that a node restarted in the middle of state sync
+//! resumes where it stopped instead of starting over.
+//!
+//! Only the sync hash and the phase of each shard are recorded here. The downloaded and
+//! applied parts are already recorded in `DBCol::StateParts` and `DBCol::StatePartsApplied`.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_client_primitives::types::{ShardSyncStatus, StateSyncStatus};
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::ShardId;
+use near_store::db::STATE_SYNC_PROGRESS_KEY;
+use near_store::{DBCol, Store};
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Eq)]
+pub(super) struct StateSyncProgress {
+    pub sync_hash: CryptoHash,
+    /// Phase of each shard, as given by `ShardSyncStatus::repr`.
+    pub shard_phases: Vec<(ShardId, u8)>,
+}
+
+impl StateSyncProgress {
+    pub fn from_status(status: &StateSyncStatus) -> Self {
+        let mut shard_phases: Vec<_> = status
+            .sync_status
+            .iter()
+            .map(|(shard_id, shard_status)| (*shard_id, shard_status.repr()))
+            .collect();
+        shard_phases.sort();
+        Self { sync_hash: status.sync_hash, shard_phases }
+    }
+
+    pub fn to_status(&self) -> StateSyncStatus {
+        let mut status = StateSyncStatus::new(self.sync_hash);
+        for (shard_id, phase) in &self.shard_phases {
+            if let Some(shard_status) = ShardSyncStatus::from_repr(*phase) {
+                status.sync_status.insert(*shard_id, shard_status);
+            }
+        }
+        status
+    }
+}
+
+pub(super) fn load_progress(store: &Store) -> Result<Option<StateSyncProgress>, near_chain::Error> {
+    Ok(store.get_ser(DBCol::Misc, STATE_SYNC_PROGRESS_KEY)?)
+}
+
+pub(super) fn save_progress(
+    store: &Store,
+    progress: &StateSyncProgress,
+) -> Result<(), near_chain::Error> {
+    let mut store_update = store.store_update();
+    store_update.set_ser(DBCol::Misc, STATE_SYNC_PROGRESS_KEY, progress)?;
+    store_update.commit()?;
+    Ok(())
+}
+
+pub(super) fn clear_progress(store: &Store) -> Result<(), near_chain::Error> {
+    let mut store_update = store.store_update();
+    store_update.delete(DBCol::Misc, STATE_SYNC_PROGRESS_KEY);
+    store_update.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_primitives::shard_layout::ShardLayout;
+    use near_store::test_utils::create_test_store;
+
+    #[test]
+    fn test_save_and_load_progress() {
+        let store = create_test_store();
+        assert_eq!(load_progress(&store).unwrap(), None);
+
+        let shard_layout = ShardLayout::multi_shard(2, 0);
+        let shard_ids: Vec<_> = shard_layout.shard_ids().collect();
+        let mut status = StateSyncStatus::new(CryptoHash::hash_bytes(b"sync"));
+        status.sync_status.insert(shard_ids[0], ShardSyncStatus::StateSyncDone);
+        status.sync_status.insert(shard_ids[1], ShardSyncStatus::StateApplyInProgress);
+
+        save_progress(&store, &StateSyncProgress::from_status(&status)).unwrap();
+        let resumed = load_progress(&store).unwrap().unwrap().to_status();
+        assert_eq!(resumed.sync_hash, status.sync_hash);
+        assert_eq!(resumed.sync_status, status.sync_status);
+
+        clear_progress(&store).unwrap();
+        assert_eq!(load_progress(&store).unwrap(), None);
+    }
+}
@@ -1,15 +1,15 @@
 use super::downloader::StateSyncDownloader;
 use super::task_tracker::TaskTracker;
+use super::util::get_state_header_if_exists_in_storage;
 use crate::metrics;
 use crate::sync::state::chain_requests::ChainFinalizationRequest;
 use futures::{StreamExt, TryStreamExt};
-use itertools::any;
 use near_async::futures::{FutureSpawner, respawn_for_parallelism};
 use near_async::messaging::AsyncSender;
-use near_async::time::Duration;
+use near_async::time::{Clock, Duration, Instant};
 use near_chain::BlockHeader;
-use near_chain::types::RuntimeAdapter;
-use near_client_primitives::types::ShardSyncStatus;
+use near_chain::types::{RuntimeAdapter, StatePartValidationResult};
+use near_client_primitives::types::{ShardSyncProgress, ShardSyncStatus};
 use near_epoch_manager::EpochManagerAdapter;
 use near_epoch_manager::shard_assignment::shard_id_to_uid;
 use near_o11y::span_wrapped_msg::{SpanWrapped, SpanWrappedMessageExt};
@@ -26,11 +26,13 @@ use parking_lot::Mutex;
 use rand::prelude::SliceRandom;
 use rand::thread_rng;
 use std::sync::Arc;
+use time::ext::InstantExt as _;
 use tokio::sync::oneshot;
 use tokio_util::sync::CancellationToken;
 
 pub(super) struct StateSyncShardHandle {
     pub status: Arc<Mutex<ShardSyncStatus>>,
+    pub progress: Arc<Mutex<ShardSyncProgress>>,
     pub result: oneshot::Receiver<Result<(), near_chain::Error>>,
     pub cancel: CancellationToken,
 }
@@ -39,6 +41,10 @@ impl StateSyncShardHandle {
     pub fn status(&self) -> ShardSyncStatus {
         *self.status.lock()
     }
+
+    pub fn progress(&self) -> ShardSyncProgress {
+        self.progress.lock().clone()
+    }
 }
 
 impl Drop for StateSyncShardHandle {
@@ -55,6 +61,124 @@ macro_rules! return_if_cancelled {
     };
 }
 
+/// Updates the status of a shard being synced and records the new phase in the event log.
+fn set_status(
+    status: &Mutex<ShardSyncStatus>,
+    new_status: ShardSyncStatus,
+    shard_id: ShardId,
+    sync_hash: CryptoHash,
+) {
+    *status.lock() = new_status;
+    near_o11y::event_log::emit(near_o11y::event_log::NodeEvent::StateSyncPhase {
+        sync_hash,
+        shard_id,
+        phase: new_status.to_string(),
+    });
+}
+
+/// Estimates when a phase completes from the rate at which it progressed so far.
+#[derive(Clone)]
+struct EtaEstimator {
+    clock: Clock,
+    start: Instant,
+    done_at_start: u64,
+}
+
+impl EtaEstimator {
+    fn new(clock: Clock, done_at_start: u64) -> Self {
+        Self { start: clock.now(), clock, done_at_start }
+    }
+
+    fn eta(&self, done: u64, total: u64) -> Option<Duration> {
+        let done_since_start = done.saturating_sub(self.done_at_start);
+        if done_since_start == 0 {
+            return None;
+        }
+        let elapsed = self.clock.now().signed_duration_since(self.start);
+        Some(elapsed * (total.saturating_sub(done) as f64 / done_since_start as f64))
+    }
+}
+
+/// Checks a part of a shard which was stored before the node restarted. Returns false if the
+/// part is stored but invalid.
+async fn validate_stored_part(
+    store: Store,
+    runtime: Arc<dyn RuntimeAdapter>,
+    computation_task_tracker: TaskTracker,
+    cancel: CancellationToken,
+    shard_id: ShardId,
+    sync_hash: CryptoHash,
+    part_id: u64,
+    num_parts: u64,
+    state_root: CryptoHash,
+    protocol_version: ProtocolVersion,
+) -> Result<bool, near_chain::Error> {
+    let key_bytes = part_key_bytes(sync_hash, shard_id, part_id);
+    if !store.exists(DBCol::StateParts, &key_bytes)? {
+        return Ok(true);
+    }
+    return_if_cancelled!(cancel);
+    let handle =
+        computation_task_tracker.get_handle(&format!("shard {} part {}", shard_id, part_id)).await;
+    return_if_cancelled!(cancel);
+    handle.set_status("Validating stored part");
+    let Some(bytes) = store.get(DBCol::StateParts, &key_bytes)? else {
+        return Ok(true);
+    };
+    let valid = StatePart::from_bytes(bytes.to_vec(), protocol_version).is_ok_and(|part| {
+        runtime.validate_state_part(
+            shard_id,
+            &state_root,
+            PartId { idx: part_id, total: num_parts },
+            &part,
+        ) == StatePartValidationResult::Valid
+    });
+    if !valid {
+        tracing::warn!(target: "sync", %shard_id, ?sync_hash, part_id, "stored state part is invalid, downloading it again");
+    }
+    Ok(valid)
+}
+
+/// Loads the memtrie of a shard whose state sync completed before the node restarted, unless
+/// it was already loaded when the node started.
+pub(super) fn load_memtrie_for_synced_shard(
+    store: &Store,
+    shard_id: ShardId,
+    sync_hash: CryptoHash,
+    runtime: &dyn RuntimeAdapter,
+    epoch_manager: &dyn EpochManagerAdapter,
+) -> Result<(), near_chain::Error> {
+    let header =
+        get_state_header_if_exists_in_storage(store, sync_hash, shard_id)?.ok_or_else(|| {
+            near_chain::Error::DBNotFoundErr(format!("No state header for shard {}", shard_id))
+        })?;
+    let block_header =
+        store.get_ser::<BlockHeader>(DBCol::BlockHeader, sync_hash.as_bytes())?.ok_or_else(
+            || near_chain::Error::DBNotFoundErr(format!("No block header {}", sync_hash)),
+        )?;
+    let epoch_id = *block_header.epoch_id();
+    let shard_uid = shard_id_to_uid(epoch_manager, shard_id, &epoch_id)?;
+    if runtime.get_tries().get_memtries(shard_uid).is_some() {
+        return Ok(());
+    }
+    let protocol_version = epoch_manager.get_epoch_protocol_version(&epoch_id)?;
+    let shard_uids_pending_resharding =
+        epoch_manager.get_shard_uids_pending_resharding(protocol_version, PROTOCOL_VERSION)?;
+    runtime.get_tries().load_memtrie_on_catchup(
+        &shard_uid,
+        &header.chunk_prev_state_root(),
+        &shard_uids_pending_resharding,
+    )?;
+    Ok(())
+}
+
+fn part_key_bytes(sync_hash: CryptoHash, shard_id: ShardId, part_id: u64) -> Vec<u8> {
+    borsh::to_vec(&StatePartKey(sync_hash, shard_id, part_id)).unwrap()
+}
+
+/// Runs state sync for a shard. If `resume_from` is set, the shard was synced before, so the
+/// stored parts are checked first; the parts which are stored or applied are not downloaded
+/// or applied again in any case.
 pub(super) async fn run_state_sync_for_shard(
     store: Store,
     shard_id: ShardId,
@@ -64,6 +188,8 @@ pub(super) async fn run_state_sync_for_shard(
     epoch_manager: Arc<dyn EpochManagerAdapter>,
     computation_task_tracker: TaskTracker,
     status: Arc<Mutex<ShardSyncStatus>>,
+    progress: Arc<Mutex<ShardSyncProgress>>,
+    resume_from: Option<ShardSyncStatus>,
     chain_finalization_sender: AsyncSender<
         SpanWrapped<ChainFinalizationRequest>,
         Result<(), near_chain::Error>,
@@ -74,7 +200,7 @@ pub(super) async fn run_state_sync_for_shard(
     min_delay_before_reattempt: Duration,
 ) -> Result<(), near_chain::Error> {
     tracing::info!(%shard_id, "running state sync");
-    *status.lock() = ShardSyncStatus::StateDownloadHeader;
+    set_status(&status, ShardSyncStatus::StateDownloadHeader, shard_id, sync_hash);
     let header = downloader.ensure_shard_header(shard_id, sync_hash, cancel.clone()).await?;
     let state_root = header.chunk_prev_state_root();
     let num_parts = header.num_state_parts();
@@ -89,9 +215,71 @@ pub(super) async fn run_state_sync_for_shard(
         .with_label_values(&[&shard_id.to_string()])
         .set(num_parts as i64);
 
+    if resume_from.is_some() {
+        return_if_cancelled!(cancel);
+        let invalid_parts = tokio_stream::iter(0..num_parts)
+            .map(|part_id| {
+                let future = validate_stored_part(
+                    store.clone(),
+                    runtime.clone(),
+                    computation_task_tracker.clone(),
+                    cancel.clone(),
+                    shard_id,
+                    sync_hash,
+                    part_id,
+                    num_parts,
+                    state_root,
+                    protocol_version,
+                );
+                let future = async move { future.await.map(|valid| (part_id, valid)) };
+                respawn_for_parallelism(&*future_spawner, "state sync validate part", future)
+            })
+            .buffer_unordered(concurrency_limit.into())
+            .try_filter_map(|(part_id, valid)| async move { Ok((!valid).then_some(part_id)) })
+            .try_collect::<Vec<_>>()
+            .await?;
+        // Invalid parts are deleted so that they are downloaded again. If an invalid part was
+        // already applied, all parts of the shard are applied again on top of cleared flat
+        // storage.
+        let mut store_update = store.store_update();
+        let mut reapply_parts = false;
+        for part_id in invalid_parts {
+            let key_bytes = part_key_bytes(sync_hash, shard_id, part_id);
+            store_update.delete(DBCol::StateParts, &key_bytes);
+            reapply_parts |= store.exists(DBCol::StatePartsApplied, &key_bytes)?;
+        }
+        if reapply_parts {
+            tracing::warn!(target: "sync", %shard_id, ?sync_hash, "an invalid state part was applied, applying all parts again");
+            for part_id in 0..num_parts {
+                store_update.delete(
+                    DBCol::StatePartsApplied,
+                    &part_key_bytes(sync_hash, shard_id, part_id),
+                );
+            }
+        }
+        store_update.commit()?;
+    }
+
     return_if_cancelled!(cancel);
-    *status.lock() = ShardSyncStatus::StateDownloadParts;
-    let mut parts_to_download: Vec<u64> = (0..num_parts).collect();
+    set_status(&status, ShardSyncStatus::StateDownloadParts, shard_id, sync_hash);
+    let mut parts_to_download = Vec::new();
+    for part_id in 0..num_parts {
+        if !store.exists(DBCol::StateParts, &part_key_bytes(sync_hash, shard_id, part_id))? {
+            parts_to_download.push(part_id);
+        }
+    }
+    let parts_resumed = num_parts - parts_to_download.len() as u64;
+    if parts_resumed > 0 {
+        tracing::info!(target: "sync", %shard_id, ?sync_hash, parts_resumed, num_parts, "resuming download of state parts");
+    }
+    *progress.lock() = ShardSyncProgress {
+        num_parts,
+        parts_downloaded: parts_resumed,
+        parts_applied: 0,
+        parts_resumed,
+        eta: None,
+    };
+    let download_eta = EtaEstimator::new(downloader.clock.clone(), parts_resumed);
     {
         // Peer selection is designed such that different nodes downloading the same part will tend
         // to send the requests to the same host. It allows the host to benefit from caching the part.
@@ -117,6 +305,17 @@ pub(super) async fn run_state_sync_for_shard(
                     cancel.clone(),
                     protocol_version,
                 );
+                let progress = progress.clone();
+                let download_eta = download_eta.clone();
+                let future = async move {
+                    let result = future.await;
+                    if result.is_ok() {
+                        let mut progress = progress.lock();
+                        progress.parts_downloaded += 1;
+                        progress.eta = download_eta.eta(progress.parts_downloaded, num_parts);
+                    }
+                    result
+                };
                 respawn_for_parallelism(&*future_spawner, "state sync download part", future)
             })
             .buffered(concurrency_limit.into())
@@ -142,17 +341,24 @@ pub(super) async fn run_state_sync_for_shard(
     }
 
     return_if_cancelled!(cancel);
-    *status.lock() = ShardSyncStatus::StateApplyInProgress;
+    set_status(&status, ShardSyncStatus::StateApplyInProgress, shard_id, sync_hash);
     runtime.get_tries().unload_memtrie(&shard_uid);
 
     // Clear flat storage, but only if we haven't started applying parts yet.
     // (Otherwise we will delete the parts we already applied)
-    let apply_parts_started = any(0..num_parts, |part_id| {
-        let key = StatePartKey(sync_hash, shard_id, part_id);
-        let key_bytes = borsh::to_vec(&key).unwrap();
-        store.exists(DBCol::StatePartsApplied, &key_bytes).unwrap_or(false)
-    });
-    if apply_parts_started {
+    let mut parts_applied = 0;
+    for part_id in 0..num_parts {
+        if store.exists(DBCol::StatePartsApplied, &part_key_bytes(sync_hash, shard_id, part_id))? {
+            parts_applied += 1;
+        }
+    }
+    {
+        let mut progress = progress.lock();
+        progress.parts_applied = parts_applied;
+        progress.eta = None;
+    }
+    let apply_eta = EtaEstimator::new(downloader.clock.clone(), parts_applied);
+    if parts_applied > 0 {
         tracing::debug!(target: "sync", ?shard_id, ?sync_hash, "not clearing flat storage before applying state parts because some parts were already applied");
     } else {
         tracing::debug!(target: "sync", ?shard_id, ?sync_hash, "clearing flat storage before applying state parts");
@@ -183,6 +389,17 @@ pub(super) async fn run_state_sync_for_shard(
                 epoch_id,
                 protocol_version,
             );
+            let progress = progress.clone();
+            let apply_eta = apply_eta.clone();
+            let future = async move {
+                let result = future.await;
+                if matches!(result, Ok(StatePartApplyResult::Applied)) {
+                    let mut progress = progress.lock();
+                    progress.parts_applied += 1;
+                    progress.eta = apply_eta.eta(progress.parts_applied, num_parts);
+                }
+                result
+            };
             respawn_for_parallelism(&*future_spawner, "state sync apply part", future)
         })
         .buffer_unordered(concurrency_limit.into())
@@ -224,7 +441,8 @@ pub(super) async fn run_state_sync_for_shard(
     return_if_cancelled!(cancel);
 
     // Finalize; this needs to be done by the Chain.
-    *status.lock() = ShardSyncStatus::StateApplyFinalizing;
+    progress.lock().eta = None;
+    set_status(&status, ShardSyncStatus::StateApplyFinalizing, shard_id, sync_hash);
     chain_finalization_sender
         .send_async(ChainFinalizationRequest { shard_id, sync_hash }.span_wrap())
         .await
@@ -232,7 +450,7 @@ pub(super) async fn run_state_sync_for_shard(
             near_chain::Error::Other("Chain finalization request could not be handled".to_owned())
         })??;
 
-    *status.lock() = ShardSyncStatus::StateSyncDone;
+    set_status(&status, ShardSyncStatus::StateSyncDone, shard_id, sync_hash);
 
     Ok(())
 }
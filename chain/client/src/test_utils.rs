@@ -203,6 +203,7 @@ pub fn create_chunk(
                 &*signer,
                 &rs,
             )
+            .unwrap()
         } else {
             ShardChunkWithEncoding::new(
                 *header.prev_block_hash(),
@@ -223,6 +224,7 @@ pub fn create_chunk(
                 &*signer,
                 &rs,
             )
+            .unwrap()
         };
         let mut new_encoded_chunk = new_chunk.into_parts().1;
         swap(&mut encoded_chunk, &mut new_encoded_chunk);
@@ -244,29 +246,32 @@ pub fn create_chunk(
     let endorsement =
         ChunkEndorsement::new(EpochId::default(), &encoded_chunk.cloned_header(), signer.as_ref());
     block_merkle_tree.insert(*last_block.hash());
-    let block = Arc::new(Block::produce(
-        PROTOCOL_VERSION,
-        last_block.header(),
-        next_height,
-        last_block.header().block_ordinal() + 1,
-        vec![encoded_chunk.cloned_header()],
-        vec![vec![Some(Box::new(endorsement.signature()))]],
-        *last_block.header().epoch_id(),
-        *last_block.header().next_epoch_id(),
-        None,
-        vec![],
-        Ratio::new(0, 1),
-        Balance::ZERO,
-        Balance::from_yoctonear(100),
-        None,
-        &*client.validator_signer.get().unwrap(),
-        *last_block.header().next_bp_hash(),
-        block_merkle_tree.root(),
-        client.clock.clone(),
-        None,
-        None,
-        None,
-    ));
+    let block = Arc::new(
+        Block::produce(
+            PROTOCOL_VERSION,
+            last_block.header(),
+            next_height,
+            last_block.header().block_ordinal() + 1,
+            vec![encoded_chunk.cloned_header()],
+            vec![vec![Some(Box::new(endorsement.signature()))]],
+            *last_block.header().epoch_id(),
+            *last_block.header().next_epoch_id(),
+            None,
+            vec![],
+            Ratio::new(0, 1),
+            Balance::ZERO,
+            Balance::from_yoctonear(100),
+            None,
+            &*client.validator_signer.get().unwrap(),
+            *last_block.header().next_bp_hash(),
+            block_merkle_tree.root(),
+            client.clock.clone(),
+            None,
+            None,
+            None,
+        )
+        .unwrap(),
+    );
     let chunk = ShardChunkWithEncoding::from_encoded_shard_chunk(encoded_chunk).unwrap();
     (ProduceChunkResult { chunk, encoded_chunk_parts_paths: merkle_paths, receipts }, block)
 }
@@ -488,6 +488,7 @@ fn simulate_chunk_application(
                 gas_limit: GAS_LIMIT,
                 is_new_chunk: true,
                 on_post_state_ready: None,
+                execution_trace_collector: None,
             },
             {
                 let is_new_chunk = true;
@@ -412,6 +412,7 @@ impl SpicePartialDataBuilder {
             self.parts,
             &create_test_signer(self.sender.as_str()),
         )
+        .unwrap()
     }
 
     fn build_with_signature(self, signature: Signature) -> SpicePartialData {
@@ -3,14 +3,18 @@
 
 use crate::{
     GetChunk, GetExecutionOutcomeResponse, GetNextLightClientBlock, GetShardChunk, GetStateChanges,
-    GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered, metrics, sync,
+    GetStateChangesInBlock, GetValidatorForecast, GetValidatorInfo, GetValidatorOrdered, metrics,
+    sync,
 };
 use near_async::messaging::{Actor, CanSend, Handler};
 use near_async::time::{Clock, Duration, Instant};
-use near_chain::types::{RuntimeAdapter, Tip};
+use near_chain::chain::collect_receipts_from_response;
+use near_chain::types::{
+    ApplyChunkBlockContext, ApplyChunkShardContext, RuntimeAdapter, RuntimeStorageConfig, Tip,
+};
 use near_chain::{
-    Chain, ChainGenesis, ChainStoreAccess, DoomslugThresholdMode, get_epoch_block_producers_view,
-    retrieve_headers,
+    Chain, ChainGenesis, ChainStoreAccess, DoomslugThresholdMode, ReceiptFilter,
+    get_epoch_block_producers_view, get_incoming_receipts_for_shard, retrieve_headers,
 };
 
 use near_chain_configs::{ClientConfig, MutableValidatorSigner, ProtocolConfigView};
@@ -21,9 +25,10 @@ use near_client_primitives::types::{
     GetExecutionOutcomesForBlock, GetGasPrice, GetGasPriceError, GetMaintenanceWindows,
     GetMaintenanceWindowsError, GetNextLightClientBlockError, GetProtocolConfig,
     GetProtocolConfigError, GetReceipt, GetReceiptError, GetSplitStorageInfo,
-    GetSplitStorageInfoError, GetStateChangesError, GetStateChangesWithCauseInBlock,
-    GetStateChangesWithCauseInBlockForTrackedShards, GetValidatorInfoError, Query, QueryError,
-    TxStatus, TxStatusError,
+    GetSplitStorageInfoError, GetStateChangesBlockHeader, GetStateChangesError,
+    GetStateChangesWithCauseInBlock, GetStateChangesWithCauseInBlockForTrackedShards,
+    GetValidatorInfoError, Query, QueryError, SimulateTransaction, SimulateTransactionError,
+    TraceReceipt, TraceReceiptError, TxStatus, TxStatusError,
 };
 use near_epoch_manager::EpochManagerAdapter;
 use near_epoch_manager::shard_assignment::{account_id_to_shard_id, shard_id_to_uid};
@@ -35,6 +40,7 @@ use near_network::client::{
 use near_network::types::{
     NetworkRequests, PeerManagerAdapter, PeerManagerMessageRequest, ReasonForBan,
 };
+use near_primitives::apply::ApplyChunkReason;
 use near_primitives::block::{Block, BlockHeader};
 use near_primitives::epoch_info::EpochInfo;
 use near_primitives::errors::EpochError;
@@ -46,19 +52,22 @@ use near_primitives::sharding::ShardChunk;
 use near_primitives::stateless_validation::ChunkProductionKey;
 use near_primitives::types::{
     AccountId, BlockHeight, BlockId, BlockReference, EpochHeight, EpochId, EpochReference,
-    Finality, MaybeBlockId, ShardId, SyncCheckpoint, TransactionOrReceiptId,
-    ValidatorInfoIdentifier,
+    Finality, MaybeBlockId, ShardId, StateChanges, StateChangesExt, SyncCheckpoint,
+    TransactionOrReceiptId, ValidatorInfoIdentifier,
 };
 use near_primitives::views::validator_stake_view::ValidatorStakeView;
 use near_primitives::views::{
-    BlockView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView, ExecutionStatusView,
-    FinalExecutionOutcomeView, FinalExecutionOutcomeViewEnum, FinalExecutionStatus, GasPriceView,
-    LightClientBlockView, MaintenanceWindowsView, QueryRequest, QueryResponse, ReceiptView,
-    SignedTransactionView, SplitStorageInfoView, StateChangesKindsView, StateChangesView,
-    TxExecutionStatus, TxStatusView,
+    BlockHeaderView, BlockView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
+    ExecutionStatusView, FinalExecutionOutcomeView, FinalExecutionOutcomeViewEnum,
+    FinalExecutionStatus, GasPriceView, LightClientBlockView, MaintenanceWindowsView, QueryRequest,
+    QueryResponse, ReceiptTraceView, ReceiptView, SignedTransactionView, SimulatedOutcomeView,
+    SimulatedTransactionView, SplitStorageInfoView, StateChangesKindsView, StateChangesView,
+    TxExecutionStatus, TxStatusView, ValidatorForecastView,
 };
 use near_store::merkle_proof::MerkleProofAccess;
 use near_store::{COLD_HEAD_KEY, DBCol, FINAL_HEAD_KEY, HEAD_KEY};
+use node_runtime::SignedValidPeriodTransactions;
+use node_runtime::execution_trace::ExecutionTraceCollector;
 use parking_lot::RwLock;
 use std::cmp::Ordering;
 use std::collections::{BTreeSet, HashMap, HashSet};
@@ -428,10 +437,12 @@ impl ViewClientActor {
                 } => QueryError::UnknownGasKey { public_key, block_height, block_hash },
                 near_chain::near_chain_primitives::error::QueryError::ContractExecutionError {
                     error_message,
+                    backtrace,
                     block_hash,
                     block_height,
                 } => QueryError::ContractExecutionError {
                     vm_error: error_message,
+                    backtrace,
                     block_height,
                     block_hash,
                 },
@@ -684,7 +695,8 @@ impl ViewClientActor {
                     .epoch_manager
                     .get_chunk_producer_info(&ChunkProductionKey {
                         epoch_id: head.epoch_id,
-                        height_created: head.height + self.config.tx_routing_height_horizon - 1,
+                        height_created: head.height + self.config.tx_routing_height_horizon.get()
+                            - 1,
                         shard_id: target_shard_id,
                     })
                     .map(|info| info.take_account_id())
@@ -705,6 +717,29 @@ impl ViewClientActor {
         retrieve_headers(self.chain.chain_store(), hashes, sync::header::MAX_BLOCK_HEADERS)
     }
 
+    /// Whether the state changes of all the given accounts in the block are
+    /// kept beyond the regular garbage collection, see
+    /// `GCConfig::retained_accounts`.
+    fn are_state_changes_retained(
+        &self,
+        header: &BlockHeader,
+        account_ids: &[AccountId],
+    ) -> Result<bool, near_chain::Error> {
+        let Some(config) = &self.config.gc.retained_accounts else {
+            return Ok(false);
+        };
+        if account_ids.is_empty() || !config.covers(account_ids.iter()) {
+            return Ok(false);
+        }
+        let tip = self.chain.head()?;
+        let gc_stop_height = self.runtime.get_gc_stop_height(&tip.last_block_hash);
+        let epoch_length =
+            self.epoch_manager.get_epoch_config(&tip.epoch_id).into_chain_error()?.epoch_length;
+        Ok(config
+            .stop_height(gc_stop_height, epoch_length)
+            .is_none_or(|stop_height| header.height() >= stop_height))
+    }
+
     fn check_signature_account_announce(
         &self,
         announce_account: &AnnounceAccount,
@@ -744,6 +779,29 @@ impl Handler<GetBlock, Result<BlockView, GetBlockError>> for ViewClientActor {
     }
 }
 
+/// Handles retrieving the header of the block whose state changes are queried.
+impl Handler<GetStateChangesBlockHeader, Result<BlockHeaderView, GetBlockError>>
+    for ViewClientActor
+{
+    fn handle(
+        &mut self,
+        msg: GetStateChangesBlockHeader,
+    ) -> Result<BlockHeaderView, GetBlockError> {
+        tracing::debug!(target: "client", ?msg);
+        let _timer = metrics::VIEW_CLIENT_MESSAGE_TIME
+            .with_label_values(&["GetStateChangesBlockHeader"])
+            .start_timer();
+        let header = self
+            .get_block_header_by_reference(&msg.block_reference)?
+            .ok_or(GetBlockError::NotSyncedYet)?;
+        if !self.are_state_changes_retained(&header, &msg.account_ids)? {
+            // Fail the same way as `GetBlock` once the block is garbage collected.
+            self.chain.get_block(header.hash())?;
+        }
+        Ok(BlockHeaderView::from(header.as_ref()))
+    }
+}
+
 impl Handler<GetBlockWithMerkleTree, Result<(BlockView, Arc<PartialMerkleTree>), GetBlockError>>
     for ViewClientActor
 {
@@ -922,6 +980,22 @@ impl Handler<GetValidatorOrdered, Result<Vec<ValidatorStakeView>, GetValidatorIn
         })?)
     }
 }
+
+impl Handler<GetValidatorForecast, Result<ValidatorForecastView, GetValidatorInfoError>>
+    for ViewClientActor
+{
+    fn handle(
+        &mut self,
+        msg: GetValidatorForecast,
+    ) -> Result<ValidatorForecastView, GetValidatorInfoError> {
+        tracing::debug!(target: "client", ?msg);
+        let _timer = metrics::VIEW_CLIENT_MESSAGE_TIME
+            .with_label_values(&["GetValidatorForecast"])
+            .start_timer();
+        let header = self.maybe_block_id_to_block_header(msg.block_id)?;
+        Ok(self.epoch_manager.get_validator_forecast(header.hash()).into_chain_error()?)
+    }
+}
 /// Returns a list of change kinds per account in a store for a given block.
 impl Handler<GetStateChangesInBlock, Result<StateChangesKindsView, GetStateChangesError>>
     for ViewClientActor
@@ -1203,6 +1277,169 @@ impl Handler<GetReceipt, Result<Option<ReceiptView>, GetReceiptError>> for ViewC
     }
 }
 
+impl Handler<SimulateTransaction, Result<SimulatedTransactionView, SimulateTransactionError>>
+    for ViewClientActor
+{
+    fn handle(
+        &mut self,
+        msg: SimulateTransaction,
+    ) -> Result<SimulatedTransactionView, SimulateTransactionError> {
+        tracing::debug!(target: "client", ?msg);
+        let _timer = metrics::VIEW_CLIENT_MESSAGE_TIME
+            .with_label_values(&["SimulateTransaction"])
+            .start_timer();
+        let header = self
+            .get_block_header_by_reference(&BlockReference::Finality(Finality::Final))?
+            .ok_or(SimulateTransactionError::NoSyncedBlocks)?;
+        let shard_layout =
+            self.epoch_manager.get_shard_layout(header.epoch_id()).into_chain_error()?;
+
+        // Only the shards tracked by this node can take part in the simulation.
+        let mut state_roots = HashMap::new();
+        for shard_uid in shard_layout.shard_uids() {
+            match self.chain.get_chunk_extra(header.hash(), &shard_uid) {
+                Ok(chunk_extra) => {
+                    state_roots.insert(shard_uid.shard_id(), *chunk_extra.state_root());
+                }
+                Err(near_chain::near_chain_primitives::Error::DBNotFoundErr(_)) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        let signer_id = msg.signed_transaction.transaction.signer_id();
+        let signer_shard_id = shard_layout.account_id_to_shard_id(signer_id);
+        if !state_roots.contains_key(&signer_shard_id) {
+            return Err(SimulateTransactionError::UnavailableShard {
+                requested_shard_id: signer_shard_id,
+            });
+        }
+
+        let result = self.runtime.simulate_transaction(
+            &header,
+            &state_roots,
+            &msg.signed_transaction,
+            msg.verify_signature,
+        )?;
+        let mut outcomes = result.outcomes.into_iter().map(|simulated| SimulatedOutcomeView {
+            shard_id: simulated.shard_id,
+            round: simulated.round as u64,
+            id: simulated.outcome.id,
+            outcome: simulated.outcome.outcome.into(),
+        });
+        let transaction_outcome = outcomes.next().ok_or_else(|| {
+            SimulateTransactionError::Unreachable("simulation produced no outcomes".to_string())
+        })?;
+        let state_changes = StateChanges::from_changes(
+            result.state_changes.into_iter().map(|simulated| Ok(simulated.changes)),
+        )
+        .map_err(|err| SimulateTransactionError::IOError(err.to_string()))?;
+        Ok(SimulatedTransactionView {
+            block_hash: *header.hash(),
+            block_height: header.height(),
+            transaction_outcome,
+            receipts_outcome: outcomes.collect(),
+            state_changes: state_changes.into_iter().map(Into::into).collect(),
+            unprocessed_receipts: result.unprocessed_receipts.into_iter().map(Into::into).collect(),
+        })
+    }
+}
+
+impl Handler<TraceReceipt, Result<ReceiptTraceView, TraceReceiptError>> for ViewClientActor {
+    fn handle(&mut self, msg: TraceReceipt) -> Result<ReceiptTraceView, TraceReceiptError> {
+        tracing::debug!(target: "client", ?msg);
+        let _timer =
+            metrics::VIEW_CLIENT_MESSAGE_TIME.with_label_values(&["TraceReceipt"]).start_timer();
+        let receipt_id = msg.receipt_id;
+        let outcome = match self.chain.get_execution_outcome(&receipt_id) {
+            Ok(outcome) => outcome,
+            Err(near_chain::near_chain_primitives::Error::DBNotFoundErr(_)) => {
+                return Err(TraceReceiptError::UnknownReceipt { receipt_id });
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let block_hash = outcome.block_hash;
+        let block = self.chain.get_block(&block_hash)?;
+        let epoch_id = block.header().epoch_id();
+        let shard_layout = self.epoch_manager.get_shard_layout(epoch_id).into_chain_error()?;
+        let shard_id =
+            shard_layout.account_id_to_shard_id(&outcome.outcome_with_id.outcome.executor_id);
+        let shard_index =
+            shard_layout.get_shard_index(shard_id).map_err(near_chain::Error::from)?;
+        let chunk_header = block
+            .chunks()
+            .get(shard_index)
+            .ok_or(near_chain::Error::InvalidShardId(shard_id))?
+            .clone();
+        if chunk_header.height_included() != block.header().height() {
+            return Err(TraceReceiptError::Unreachable(format!(
+                "receipt {} was executed in block {} without a new chunk for shard {}",
+                receipt_id, block_hash, shard_id
+            )));
+        }
+        let chunk = match self.chain.get_chunk(chunk_header.chunk_hash()) {
+            Ok(chunk) => chunk,
+            Err(near_chain::near_chain_primitives::Error::ChunkMissing(_)) => {
+                return Err(TraceReceiptError::UnavailableShard { requested_shard_id: shard_id });
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let prev_block = self.chain.get_block(block.header().prev_hash())?;
+        let prev_shard_layout = self
+            .epoch_manager
+            .get_shard_layout(prev_block.header().epoch_id())
+            .into_chain_error()?;
+        let prev_shard_index =
+            prev_shard_layout.get_shard_index(shard_id).map_err(near_chain::Error::from)?;
+        let receipt_proof_response = get_incoming_receipts_for_shard(
+            self.chain.chain_store(),
+            self.epoch_manager.as_ref(),
+            shard_id,
+            &shard_layout,
+            block_hash,
+            prev_block.chunks()[prev_shard_index].height_included(),
+            ReceiptFilter::TargetShard,
+        )?;
+        let receipts = collect_receipts_from_response(&receipt_proof_response);
+        let transactions = chunk.to_transactions().to_vec();
+        let valid_txs =
+            self.chain.chain_store().compute_transaction_validity(prev_block.header(), &chunk);
+
+        let collector = Arc::new(ExecutionTraceCollector::default());
+        self.runtime
+            .apply_chunk(
+                RuntimeStorageConfig::new_with_db_trie_only(chunk.prev_state_root()),
+                ApplyChunkReason::ViewTrackedShard,
+                ApplyChunkShardContext {
+                    shard_id,
+                    last_validator_proposals: chunk_header.prev_validator_proposals(),
+                    gas_limit: chunk_header.gas_limit(),
+                    is_new_chunk: true,
+                    on_post_state_ready: None,
+                    execution_trace_collector: Some(collector.clone()),
+                },
+                ApplyChunkBlockContext::from_header(
+                    block.header(),
+                    prev_block.header().next_gas_price(),
+                    block.block_congestion_info(),
+                    block.block_bandwidth_requests(),
+                ),
+                &receipts,
+                SignedValidPeriodTransactions::new(transactions, valid_txs),
+            )
+            .map_err(|err| match err {
+                near_chain::near_chain_primitives::Error::StorageError(
+                    near_store::StorageError::MissingTrieValue(..),
+                ) => TraceReceiptError::UnavailableShard { requested_shard_id: shard_id },
+                err => err.into(),
+            })?;
+        let function_calls = collector
+            .take()
+            .into_iter()
+            .filter(|trace| trace.receipt_id == Some(receipt_id))
+            .collect();
+        Ok(ReceiptTraceView { receipt_id, block_hash, shard_id, function_calls })
+    }
+}
+
 impl Handler<GetBlockProof, Result<GetBlockProofResponse, GetBlockProofError>> for ViewClientActor {
     fn handle(&mut self, msg: GetBlockProof) -> Result<GetBlockProofResponse, GetBlockProofError> {
         tracing::debug!(target: "client", ?msg);
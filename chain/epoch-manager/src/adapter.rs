@@ -17,7 +17,7 @@ use near_primitives::types::{
     ValidatorInfoIdentifier,
 };
 use near_primitives::version::{ProtocolFeature, ProtocolVersion};
-use near_primitives::views::EpochValidatorInfo;
+use near_primitives::views::{EpochValidatorInfo, ValidatorForecastView};
 use near_store::ShardUId;
 use near_store::adapter::epoch_store::EpochStoreUpdateAdapter;
 use std::cmp::Ordering;
@@ -476,6 +476,16 @@ pub trait EpochManagerAdapter: Send + Sync {
         epoch_identifier: ValidatorInfoIdentifier,
     ) -> Result<EpochValidatorInfo, EpochError>;
 
+    /// Projects the online ratio, reward and kickout of the validators of the
+    /// epoch of `block_hash` at the end of that epoch.
+    ///
+    /// WARNING: this call may be expensive. It is intended for diagnostic use
+    /// in rpc and tools.
+    fn get_validator_forecast(
+        &self,
+        block_hash: &CryptoHash,
+    ) -> Result<ValidatorForecastView, EpochError>;
+
     fn add_validator_proposals(
         &self,
         block_info: BlockInfo,
@@ -888,6 +898,15 @@ impl EpochManagerAdapter for EpochManagerHandle {
         epoch_manager.get_validator_info(epoch_id)
     }
 
+    fn get_validator_forecast(
+        &self,
+        block_hash: &CryptoHash,
+    ) -> Result<ValidatorForecastView, EpochError> {
+        // Only collecting the inputs needs the lock, the epoch is walked without it.
+        let inputs = self.read().get_validator_forecast_inputs(block_hash)?;
+        inputs.compute()
+    }
+
     fn add_validator_proposals(
         &self,
         block_info: BlockInfo,
@@ -50,6 +50,7 @@ pub mod test_utils;
 #[cfg(test)]
 mod tests;
 pub mod validate;
+mod validator_forecast;
 mod validator_selection;
 mod validator_stats;
 
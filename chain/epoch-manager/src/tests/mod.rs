@@ -365,6 +365,64 @@ fn test_validator_kickout() {
     );
 }
 
+#[test]
+fn test_validator_forecast() {
+    let amount_staked = Balance::from_yoctonear(1_000_000);
+    let validators =
+        vec![("test1".parse().unwrap(), amount_staked), ("test2".parse().unwrap(), amount_staked)];
+    let epoch_length = 10;
+    let epoch_manager =
+        setup_default_epoch_manager(validators, epoch_length, 1, 2, 90, 60).into_handle();
+    let h = hash_range(epoch_length as usize);
+
+    record_block(&mut epoch_manager.write(), CryptoHash::default(), h[0], 0, vec![]);
+    let epoch_id = epoch_manager.get_epoch_id_from_prev_block(&h[0]).unwrap();
+    // test2 is offline: record test1's blocks until one follows a block test2 missed, so that
+    // the miss is visible in the chain.
+    let mut prev_block = h[0];
+    let mut test2_missed_blocks = vec![];
+    for height in 1..epoch_length - 1 {
+        let block_producer = epoch_manager.get_block_producer_info(&epoch_id, height).unwrap();
+        if block_producer.account_id() == "test2" {
+            test2_missed_blocks.push(height);
+            continue;
+        }
+        let cur_block = h[height as usize];
+        record_block(&mut epoch_manager.write(), prev_block, cur_block, height, vec![]);
+        prev_block = cur_block;
+        if !test2_missed_blocks.is_empty() {
+            break;
+        }
+    }
+    assert!(!test2_missed_blocks.is_empty(), "test2 has no block to miss early in the epoch");
+
+    let forecast = epoch_manager.get_validator_forecast(&prev_block).unwrap();
+    assert_eq!(forecast.epoch_id, epoch_id);
+    assert_eq!(forecast.expected_epoch_end_height, forecast.epoch_start_height + epoch_length - 1);
+    let test1 = forecast.validators.iter().find(|v| v.account_id == "test1").unwrap();
+    assert!(test1.missed_blocks.is_empty());
+    assert_eq!(test1.projected_kickout, None);
+    assert!(!test1.kickout_unavoidable);
+    assert_eq!(test1.online_ratio, 1.0);
+
+    let test2 = forecast.validators.iter().find(|v| v.account_id == "test2").unwrap();
+    assert_eq!(test2.missed_blocks, test2_missed_blocks);
+    assert_eq!(test2.num_produced_blocks, 0);
+    assert_eq!(
+        test2.projected_kickout,
+        Some(NotEnoughBlocks { produced: 0, expected: test2_missed_blocks.len() as u64 })
+    );
+    assert_eq!(test2.projected_reward, Balance::ZERO);
+    // The remaining blocks are the ones test2 is assigned to in the rest of the epoch.
+    let test2_remaining_blocks = (forecast.block_height + 1..=forecast.expected_epoch_end_height)
+        .filter(|height| {
+            epoch_manager.get_block_producer_info(&epoch_id, *height).unwrap().account_id()
+                == "test2"
+        })
+        .count();
+    assert_eq!(test2.num_remaining_blocks, test2_remaining_blocks as u64);
+}
+
 #[test]
 fn test_validator_unstake() {
     let store = create_test_store().epoch_store();
@@ -3149,35 +3207,41 @@ fn test_block_and_chunk_producer_not_kicked_out_for_low_endorsements() {
 
 fn test_chunk_header(h: &[CryptoHash], signer: &ValidatorSigner) -> ShardChunkHeader {
     if ProtocolFeature::Spice.enabled(PROTOCOL_VERSION) {
-        ShardChunkHeader::V3(ShardChunkHeaderV3::new_for_spice(
-            h[0],
-            h[2],
-            0,
-            1,
-            ShardId::new(0),
-            h[2],
-            h[2],
-            signer,
-        ))
+        ShardChunkHeader::V3(
+            ShardChunkHeaderV3::new_for_spice(
+                h[0],
+                h[2],
+                0,
+                1,
+                ShardId::new(0),
+                h[2],
+                h[2],
+                signer,
+            )
+            .unwrap(),
+        )
     } else {
-        ShardChunkHeader::V3(ShardChunkHeaderV3::new(
-            h[0],
-            h[2],
-            h[2],
-            h[2],
-            0,
-            1,
-            ShardId::new(0),
-            Gas::ZERO,
-            Gas::ZERO,
-            Balance::ZERO,
-            h[2],
-            h[2],
-            vec![],
-            Default::default(),
-            BandwidthRequests::empty(),
-            signer,
-        ))
+        ShardChunkHeader::V3(
+            ShardChunkHeaderV3::new(
+                h[0],
+                h[2],
+                h[2],
+                h[2],
+                0,
+                1,
+                ShardId::new(0),
+                Gas::ZERO,
+                Gas::ZERO,
+                Balance::ZERO,
+                h[2],
+                h[2],
+                vec![],
+                Default::default(),
+                BandwidthRequests::empty(),
+                signer,
+            )
+            .unwrap(),
+        )
     }
 }
 
@@ -0,0 +1,347 @@
+//! Mid-epoch forecast of validator online ratios, rewards and kickouts.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use near_primitives::epoch_block_info::BlockInfo;
+use near_primitives::epoch_info::EpochInfo;
+use near_primitives::epoch_manager::EpochConfig;
+use near_primitives::errors::EpochError;
+use near_primitives::hash::CryptoHash;
+use near_primitives::shard_layout::ShardLayout;
+use near_primitives::types::{AccountId, BlockHeight, ValidatorId, ValidatorKickoutReason};
+use near_primitives::version::ProtocolFeature;
+use near_primitives::views::{MissedChunkView, ValidatorForecastItemView, ValidatorForecastView};
+use near_store::adapter::epoch_store::EpochStoreAdapter;
+
+use crate::EpochManager;
+use crate::epoch_info_aggregator::EpochInfoAggregator;
+use crate::reward_calculator::{RewardCalculator, ValidatorOnlineThresholds};
+use crate::validator_stats::{get_validator_online_ratio, online_ratio_to_f64};
+
+/// Duties a validator failed to fulfill so far in the epoch.
+#[derive(Default)]
+struct MissedDuties {
+    blocks: Vec<BlockHeight>,
+    chunks: Vec<MissedChunkView>,
+    endorsements: Vec<MissedChunkView>,
+}
+
+/// Everything the forecast needs from the epoch manager. Collected under the epoch manager
+/// lock, while walking the epoch and computing the forecast happens without it.
+pub(crate) struct ValidatorForecastInputs {
+    store: EpochStoreAdapter,
+    config: EpochConfig,
+    reward_calculator: RewardCalculator,
+    block_info: Arc<BlockInfo>,
+    epoch_info: Arc<EpochInfo>,
+    shard_layout: ShardLayout,
+    epoch_start_height: BlockHeight,
+    aggregator: EpochInfoAggregator,
+    prev_validator_kickout: HashMap<AccountId, ValidatorKickoutReason>,
+    prev_epoch_last_block: Arc<BlockInfo>,
+}
+
+impl EpochManager {
+    /// Collects the inputs of [`ValidatorForecastInputs::compute`] for the epoch `block_hash`
+    /// belongs to.
+    ///
+    /// WARNING: this function calls EpochManager::get_epoch_info_aggregator_upto_last, which
+    /// can be very expensive.
+    pub(crate) fn get_validator_forecast_inputs(
+        &self,
+        block_hash: &CryptoHash,
+    ) -> Result<ValidatorForecastInputs, EpochError> {
+        let block_info = self.get_block_info(block_hash)?;
+        let epoch_id = *block_info.epoch_id();
+        let epoch_info = self.get_epoch_info(&epoch_id)?;
+        let next_epoch_info = self.get_epoch_info(&self.get_next_epoch_id(block_hash)?)?;
+        let prev_epoch_last_block =
+            self.get_block_info(self.get_block_info(block_info.epoch_first_block())?.prev_hash())?;
+        Ok(ValidatorForecastInputs {
+            store: self.store.clone(),
+            config: self.config.for_protocol_version(epoch_info.protocol_version()),
+            reward_calculator: self.reward_calculator.clone(),
+            shard_layout: self.get_shard_layout(&epoch_id)?,
+            epoch_start_height: self.get_epoch_start_from_epoch_id(&epoch_id)?,
+            aggregator: self.get_epoch_info_aggregator_upto_last(block_hash)?,
+            prev_validator_kickout: next_epoch_info.validator_kickout().clone(),
+            prev_epoch_last_block,
+            block_info,
+            epoch_info,
+        })
+    }
+}
+
+impl ValidatorForecastInputs {
+    /// Projects, for each validator of the epoch, the online ratio, reward and kickout at the
+    /// end of the epoch, assuming that validators keep their current online ratios. Also
+    /// reports whether a kickout can still be avoided by fulfilling all remaining duties of
+    /// the epoch.
+    ///
+    /// WARNING: this function walks all blocks of the epoch, which can be expensive.
+    pub(crate) fn compute(self) -> Result<ValidatorForecastView, EpochError> {
+        let Self {
+            store: _,
+            config,
+            reward_calculator,
+            block_info,
+            epoch_info,
+            shard_layout,
+            epoch_start_height,
+            aggregator,
+            prev_validator_kickout,
+            prev_epoch_last_block,
+        } = &self;
+        let epoch_id = *block_info.epoch_id();
+        let protocol_version = epoch_info.protocol_version();
+        let epoch_start_height = *epoch_start_height;
+        let block_height = block_info.height();
+        let expected_epoch_end_height =
+            (epoch_start_height + config.epoch_length).saturating_sub(1).max(block_height);
+
+        let mut missed_duties = self.collect_missed_duties()?;
+
+        let mut best_case = aggregator.clone();
+        best_case.merge(Self::remaining_duties(
+            aggregator,
+            epoch_info,
+            shard_layout,
+            block_height + 1..=expected_epoch_end_height,
+        ));
+
+        let (validator_block_chunk_stats, projected_kickout) =
+            EpochManager::compute_validators_to_reward_and_kickout(
+                config,
+                epoch_info,
+                &aggregator.block_tracker,
+                &aggregator.shard_tracker,
+                prev_validator_kickout,
+            );
+        let (best_case_block_chunk_stats, best_case_kickout) =
+            EpochManager::compute_validators_to_reward_and_kickout(
+                config,
+                epoch_info,
+                &best_case.block_tracker,
+                &best_case.shard_tracker,
+                prev_validator_kickout,
+            );
+
+        // Online ratios are invariant to stretching the epoch so far over the whole epoch,
+        // only the duration of the epoch has to be extrapolated.
+        let elapsed_nanos = block_info
+            .timestamp_nanosec()
+            .saturating_sub(*prev_epoch_last_block.timestamp_nanosec());
+        let elapsed_blocks = block_height.saturating_sub(prev_epoch_last_block.height()).max(1);
+        let epoch_blocks = expected_epoch_end_height - prev_epoch_last_block.height();
+        let epoch_duration =
+            u64::try_from(elapsed_nanos as u128 * epoch_blocks as u128 / elapsed_blocks as u128)
+                .unwrap_or(u64::MAX);
+
+        let online_thresholds = ValidatorOnlineThresholds {
+            online_min_threshold: config.online_min_threshold,
+            online_max_threshold: config.online_max_threshold,
+            endorsement_cutoff_threshold: Some(config.chunk_validator_only_kickout_threshold),
+        };
+        let mut reward_stats = validator_block_chunk_stats.clone();
+        for account_id in projected_kickout.keys() {
+            reward_stats.remove(account_id);
+        }
+        let validator_stake =
+            epoch_info.validators_iter().map(|r| r.account_and_stake()).collect::<HashMap<_, _>>();
+        let (validator_reward, _) = reward_calculator.calculate_reward(
+            reward_stats,
+            &validator_stake,
+            *block_info.total_supply(),
+            protocol_version,
+            epoch_duration,
+            online_thresholds.clone(),
+            config.max_inflation_rate,
+        );
+
+        let validators = epoch_info
+            .validators_iter()
+            .enumerate()
+            .map(|(validator_id, info)| {
+                let (account_id, _, stake) = info.destructure();
+                let stats = &validator_block_chunk_stats[&account_id];
+                let best_case_stats = &best_case_block_chunk_stats[&account_id];
+                let missed = missed_duties.remove(&(validator_id as u64)).unwrap_or_default();
+                ValidatorForecastItemView {
+                    stake,
+                    num_produced_blocks: stats.block_stats.produced,
+                    num_expected_blocks: stats.block_stats.expected,
+                    num_remaining_blocks: best_case_stats.block_stats.expected
+                        - stats.block_stats.expected,
+                    num_produced_chunks: stats.chunk_stats.produced(),
+                    num_expected_chunks: stats.chunk_stats.expected(),
+                    num_remaining_chunks: best_case_stats.chunk_stats.expected()
+                        - stats.chunk_stats.expected(),
+                    num_produced_endorsements: stats.chunk_stats.endorsement_stats().produced,
+                    num_expected_endorsements: stats.chunk_stats.endorsement_stats().expected,
+                    num_remaining_endorsements: best_case_stats
+                        .chunk_stats
+                        .endorsement_stats()
+                        .expected
+                        - stats.chunk_stats.endorsement_stats().expected,
+                    online_ratio: online_ratio_to_f64(&get_validator_online_ratio(
+                        stats,
+                        online_thresholds.endorsement_cutoff_threshold,
+                    )),
+                    best_case_online_ratio: online_ratio_to_f64(&get_validator_online_ratio(
+                        best_case_stats,
+                        online_thresholds.endorsement_cutoff_threshold,
+                    )),
+                    projected_reward: validator_reward
+                        .get(&account_id)
+                        .copied()
+                        .unwrap_or_default(),
+                    projected_kickout: projected_kickout.get(&account_id).cloned(),
+                    kickout_unavoidable: best_case_kickout.contains_key(&account_id),
+                    missed_blocks: missed.blocks,
+                    missed_chunks: missed.chunks,
+                    missed_endorsements: missed.endorsements,
+                    account_id,
+                }
+            })
+            .collect();
+
+        Ok(ValidatorForecastView {
+            epoch_id,
+            epoch_height: epoch_info.epoch_height(),
+            epoch_start_height,
+            block_height,
+            expected_epoch_end_height,
+            validators,
+        })
+    }
+
+    /// Walks the epoch back from the forecast block and records the blocks, chunks and
+    /// endorsements each validator missed, following the accounting of
+    /// [`EpochInfoAggregator::update_tail`].
+    fn collect_missed_duties(&self) -> Result<HashMap<ValidatorId, MissedDuties>, EpochError> {
+        let epoch_info = &self.epoch_info;
+        let shard_layout = &self.shard_layout;
+        let epoch_id = *self.block_info.epoch_id();
+        let skip_endorsements = ProtocolFeature::Spice.enabled(epoch_info.protocol_version());
+        let mut missed_duties: HashMap<ValidatorId, MissedDuties> = HashMap::new();
+        let mut block_info = self.block_info.clone();
+        loop {
+            if block_info.epoch_id() != &epoch_id || block_info.is_genesis() {
+                break;
+            }
+            let prev_block_info = match self.store.get_block_info(block_info.prev_hash()) {
+                Ok(prev_block_info) => Arc::new(prev_block_info),
+                // After epoch sync the blocks preceding the sync point are not available.
+                Err(EpochError::MissingBlock(_)) => break,
+                Err(err) => return Err(err),
+            };
+            let prev_height = prev_block_info.height();
+
+            for height in (prev_height + 1..block_info.height()).rev() {
+                let block_producer_id = epoch_info.sample_block_producer(height);
+                missed_duties.entry(block_producer_id).or_default().blocks.push(height);
+            }
+
+            let height = prev_height + 1;
+            let chunk_validator_assignment = epoch_info.sample_chunk_validators(height);
+            for shard_info in shard_layout.shard_infos() {
+                let shard_index = shard_info.shard_index();
+                let shard_id = shard_info.shard_id();
+                let Some(&mask) = block_info.chunk_mask().get(shard_index) else {
+                    continue;
+                };
+                if !mask {
+                    if let Some(chunk_producer_id) =
+                        epoch_info.sample_chunk_producer(shard_layout, shard_id, height)
+                    {
+                        missed_duties
+                            .entry(chunk_producer_id)
+                            .or_default()
+                            .chunks
+                            .push(MissedChunkView { height, shard_id });
+                    }
+                }
+                if skip_endorsements {
+                    continue;
+                }
+                // A missing chunk cannot be endorsed, and without endorsement bitmaps in the
+                // block a produced chunk counts as endorsed by all its validators.
+                let endorsements: Box<dyn Iterator<Item = bool>> =
+                    match block_info.chunk_endorsements() {
+                        Some(chunk_endorsements) if mask => chunk_endorsements.iter(shard_index),
+                        _ => Box::new(std::iter::repeat(mask)),
+                    };
+                let chunk_validators =
+                    chunk_validator_assignment.get(shard_index).map_or(&[][..], Vec::as_slice);
+                for ((chunk_validator_id, _), endorsed) in chunk_validators.iter().zip(endorsements)
+                {
+                    if !endorsed {
+                        missed_duties
+                            .entry(*chunk_validator_id)
+                            .or_default()
+                            .endorsements
+                            .push(MissedChunkView { height, shard_id });
+                    }
+                }
+            }
+
+            block_info = prev_block_info;
+        }
+
+        // The walk goes backwards, report the misses in increasing height.
+        for missed in missed_duties.values_mut() {
+            missed.blocks.reverse();
+            missed.chunks.reverse();
+            missed.endorsements.reverse();
+        }
+        Ok(missed_duties)
+    }
+
+    /// Returns an aggregator following `aggregator` in which every block, chunk and endorsement
+    /// expected at `heights` has been produced.
+    fn remaining_duties(
+        aggregator: &EpochInfoAggregator,
+        epoch_info: &EpochInfo,
+        shard_layout: &ShardLayout,
+        heights: impl Iterator<Item = BlockHeight>,
+    ) -> EpochInfoAggregator {
+        let skip_endorsements = ProtocolFeature::Spice.enabled(epoch_info.protocol_version());
+        let mut remaining =
+            EpochInfoAggregator::new(aggregator.epoch_id, aggregator.last_block_hash);
+        for height in heights {
+            let block_stats = remaining
+                .block_tracker
+                .entry(epoch_info.sample_block_producer(height))
+                .or_default();
+            block_stats.produced += 1;
+            block_stats.expected += 1;
+
+            let chunk_validator_assignment = epoch_info.sample_chunk_validators(height);
+            for shard_info in shard_layout.shard_infos() {
+                let shard_id = shard_info.shard_id();
+                let tracker = remaining.shard_tracker.entry(shard_id).or_default();
+                if let Some(chunk_producer_id) =
+                    epoch_info.sample_chunk_producer(shard_layout, shard_id, height)
+                {
+                    let chunk_stats = tracker.entry(chunk_producer_id).or_default();
+                    *chunk_stats.produced_mut() += 1;
+                    *chunk_stats.expected_mut() += 1;
+                }
+                if skip_endorsements {
+                    continue;
+                }
+                let chunk_validators = chunk_validator_assignment
+                    .get(shard_info.shard_index())
+                    .map_or(&[][..], Vec::as_slice);
+                for (chunk_validator_id, _) in chunk_validators {
+                    let endorsement_stats =
+                        tracker.entry(*chunk_validator_id).or_default().endorsement_stats_mut();
+                    endorsement_stats.produced += 1;
+                    endorsement_stats.expected += 1;
+                }
+            }
+        }
+        remaining
+    }
+}
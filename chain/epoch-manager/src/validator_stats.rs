@@ -117,6 +117,14 @@ pub(crate) fn get_sortable_validator_online_ratio(stats: &BlockChunkValidatorSta
     BigRational::new(bignumer.try_into().unwrap(), bigdenom.try_into().unwrap())
 }
 
+/// Converts an online ratio to a floating point number for display purposes.
+pub(crate) fn online_ratio_to_f64(ratio: &Ratio<U256>) -> f64 {
+    let to_f64 = |value: &U256| {
+        value.0.iter().rev().fold(0.0, |acc, limb| acc * 2f64.powi(64) + *limb as f64)
+    };
+    to_f64(ratio.numer()) / to_f64(ratio.denom())
+}
+
 /// Applies the `cutoff_threshold` to the endorsement ratio encoded in the `stats`.
 /// If `cutoff_threshold` is not provided, returns the same ratio from the `stats`.
 /// If `cutoff_threshold` is provided, compares the endorsement ratio from the `stats` with the threshold.
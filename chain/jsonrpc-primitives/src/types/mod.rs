@@ -12,7 +12,9 @@ pub mod network_info;
 pub mod query;
 pub mod receipts;
 pub mod sandbox;
+pub mod simulation;
 pub mod split_storage;
 pub mod status;
+pub mod trace;
 pub mod transactions;
 pub mod validator;
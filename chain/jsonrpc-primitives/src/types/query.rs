@@ -67,6 +67,9 @@ pub enum RpcQueryError {
     #[error("Function call returned an error: {vm_error}")]
     ContractExecutionError {
         vm_error: String,
+        /// Wasm frames that led to the error, innermost first.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        backtrace: Option<Vec<near_primitives::views::WasmFrameView>>,
         block_height: near_primitives::types::BlockHeight,
         block_hash: near_primitives::hash::CryptoHash,
     },
@@ -66,3 +66,109 @@ impl From<RpcSandboxFastForwardError> for crate::errors::RpcError {
         Self::new_internal_or_handler_error(Some(error_data.clone()), error_data)
     }
 }
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RpcSandboxSnapshotRequest {}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RpcSandboxSnapshotResponse {
+    pub snapshot_id: u64,
+}
+
+#[derive(thiserror::Error, Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcSandboxSnapshotError {
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<RpcSandboxSnapshotError> for crate::errors::RpcError {
+    fn from(error: RpcSandboxSnapshotError) -> Self {
+        let error_data = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcSandboxSnapshotError: {:?}", err),
+                );
+            }
+        };
+        Self::new_internal_or_handler_error(Some(error_data.clone()), error_data)
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RpcSandboxRevertRequest {
+    pub snapshot_id: u64,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RpcSandboxRevertResponse {}
+
+#[derive(thiserror::Error, Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcSandboxRevertError {
+    #[error("Failed to revert to the snapshot: {error_message}")]
+    RevertFailed { error_message: String },
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<RpcSandboxRevertError> for crate::errors::RpcError {
+    fn from(error: RpcSandboxRevertError) -> Self {
+        let error_data = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcSandboxRevertError: {:?}", err),
+                );
+            }
+        };
+        Self::new_internal_or_handler_error(Some(error_data.clone()), error_data)
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RpcSandboxSetBlockTimestampRequest {
+    /// Timestamp of the next block in nanoseconds since the Unix epoch.
+    #[serde(with = "near_primitives::serialize::dec_format")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    pub timestamp_nanosec: u64,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RpcSandboxSetBlockTimestampResponse {}
+
+#[derive(thiserror::Error, Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcSandboxSetBlockTimestampError {
+    #[error("Invalid block timestamp: {error_message}")]
+    InvalidTimestamp { error_message: String },
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<RpcSandboxSetBlockTimestampError> for crate::errors::RpcError {
+    fn from(error: RpcSandboxSetBlockTimestampError) -> Self {
+        let error_data = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcSandboxSetBlockTimestampError: {:?}", err),
+                );
+            }
+        };
+        Self::new_internal_or_handler_error(Some(error_data.clone()), error_data)
+    }
+}
@@ -0,0 +1,86 @@
+use near_primitives::transaction::{SignedTransaction, Transaction};
+use serde_json::Value;
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RpcSimulateTransactionRequest {
+    #[serde(flatten)]
+    pub transaction: SimulatedTransactionInfo,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
+pub enum SimulatedTransactionInfo {
+    /// Signed transaction. Its signature is verified like for a real submission.
+    SignedTransaction {
+        #[serde(rename = "signed_tx_base64")]
+        signed_tx: SignedTransaction,
+    },
+    /// Unsigned transaction, encoded as base64 borsh. Signature verification
+    /// is skipped, everything else (nonce, access key permissions, balance)
+    /// is still checked.
+    Transaction {
+        #[serde(rename = "tx_base64", with = "base64_borsh_transaction")]
+        #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+        tx: Transaction,
+    },
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RpcSimulateTransactionResponse {
+    #[serde(flatten)]
+    pub simulated_transaction: near_primitives::views::SimulatedTransactionView,
+}
+
+#[derive(thiserror::Error, Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcSimulateTransactionError {
+    #[error("There are no fully synchronized blocks on the node yet")]
+    NoSyncedBlocks,
+    #[error("Node doesn't track the signer shard {requested_shard_id}")]
+    UnavailableShard { requested_shard_id: near_primitives::types::ShardId },
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+mod base64_borsh_transaction {
+    use near_primitives::borsh::{self, BorshDeserialize};
+    use near_primitives::serialize::{from_base64, to_base64};
+    use near_primitives::transaction::Transaction;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(tx: &Transaction, serializer: S) -> Result<S::Ok, S::Error> {
+        let bytes = borsh::to_vec(tx).map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&to_base64(&bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Transaction, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        let bytes = from_base64(&encoded).map_err(serde::de::Error::custom)?;
+        Transaction::try_from_slice(&bytes).map_err(serde::de::Error::custom)
+    }
+}
+
+impl From<RpcSimulateTransactionError> for crate::errors::RpcError {
+    fn from(error: RpcSimulateTransactionError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcSimulateTransactionError: {:?}", err),
+                );
+            }
+        };
+
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
@@ -4,9 +4,9 @@ use near_client_primitives::debug::{
 };
 #[cfg(feature = "debug_types")]
 use near_primitives::views::{
-    CatchupStatusView, ChainProcessingInfo, NetworkGraphView, NetworkRoutesView, PeerStoreView,
-    RecentOutboundConnectionsView, RequestedStatePartsView, SnapshotHostsView,
-    SplitStorageInfoView, SyncStatusView,
+    BlockTimelineView, CatchupStatusView, ChainProcessingInfo, ConfigReloadView, NetworkGraphView,
+    NetworkRoutesView, PeerStoreView, RecentOutboundConnectionsView, RequestedStatePartsView,
+    SnapshotHostsView, SplitStorageInfoView, SyncStatusView,
 };
 
 #[cfg(feature = "schemars")]
@@ -40,6 +40,10 @@ pub enum DebugStatusResponse {
     Routes(NetworkRoutesView),
     SnapshotHosts(SnapshotHostsView),
     SplitStoreStatus(SplitStorageInfoView),
+    // Recent reloads of the dynamically updatable configs, newest first.
+    ConfigReloads(Vec<ConfigReloadView>),
+    // None if the block is not among the recent blocks tracked by the node.
+    BlockTimeline(Option<BlockTimelineView>),
     InstrumentedThreads(serde_json::Value), // Directly use the serialized form here to avoid dependency on near-async.
 }
 
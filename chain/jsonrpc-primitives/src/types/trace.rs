@@ -0,0 +1,47 @@
+use serde_json::Value;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RpcTraceReceiptRequest {
+    #[serde(flatten)]
+    pub receipt_reference: crate::types::receipts::ReceiptReference,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RpcTraceReceiptResponse {
+    #[serde(flatten)]
+    pub receipt_trace: near_primitives::views::ReceiptTraceView,
+}
+
+#[derive(thiserror::Error, Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcTraceReceiptError {
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+    #[error("Receipt with id {receipt_id} has never been observed on this node")]
+    UnknownReceipt { receipt_id: near_primitives::hash::CryptoHash },
+    #[error(
+        "The node does not have the state of shard {requested_shard_id} required to re-execute the receipt"
+    )]
+    UnavailableShard { requested_shard_id: near_primitives::types::ShardId },
+}
+
+impl From<RpcTraceReceiptError> for crate::errors::RpcError {
+    fn from(error: RpcTraceReceiptError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcTraceReceiptError: {:?}", err),
+                );
+            }
+        };
+
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
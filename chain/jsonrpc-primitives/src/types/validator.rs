@@ -28,6 +28,19 @@ pub struct RpcValidatorsOrderedRequest {
     pub block_id: near_primitives::types::MaybeBlockId,
 }
 
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RpcValidatorForecastRequest {
+    pub block_id: near_primitives::types::MaybeBlockId,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RpcValidatorForecastResponse {
+    #[serde(flatten)]
+    pub forecast: near_primitives::views::ValidatorForecastView,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct RpcValidatorResponse {
@@ -210,6 +210,14 @@ impl JsonRpcClient {
         call_method(&self.client, &self.server_addr, "EXPERIMENTAL_validators_ordered", request)
     }
 
+    #[allow(non_snake_case)]
+    pub fn EXPERIMENTAL_validator_forecast(
+        &self,
+        request: near_jsonrpc_primitives::types::validator::RpcValidatorForecastRequest,
+    ) -> RpcRequest<near_jsonrpc_primitives::types::validator::RpcValidatorForecastResponse> {
+        call_method(&self.client, &self.server_addr, "EXPERIMENTAL_validator_forecast", request)
+    }
+
     #[allow(non_snake_case)]
     pub fn EXPERIMENTAL_receipt(
         &self,
@@ -235,6 +243,23 @@ impl JsonRpcClient {
         call_method(&self.client, &self.server_addr, "EXPERIMENTAL_split_storage_info", request)
     }
 
+    #[allow(non_snake_case)]
+    pub fn EXPERIMENTAL_simulate_tx(
+        &self,
+        request: near_jsonrpc_primitives::types::simulation::RpcSimulateTransactionRequest,
+    ) -> RpcRequest<near_jsonrpc_primitives::types::simulation::RpcSimulateTransactionResponse>
+    {
+        call_method(&self.client, &self.server_addr, "EXPERIMENTAL_simulate_tx", request)
+    }
+
+    #[allow(non_snake_case)]
+    pub fn EXPERIMENTAL_trace_receipt(
+        &self,
+        request: near_jsonrpc_primitives::types::trace::RpcTraceReceiptRequest,
+    ) -> RpcRequest<near_jsonrpc_primitives::types::trace::RpcTraceReceiptResponse> {
+        call_method(&self.client, &self.server_addr, "EXPERIMENTAL_trace_receipt", request)
+    }
+
     pub fn validators(
         &self,
         epoch_id_or_block_id: Option<EpochReference>,
@@ -5,6 +5,7 @@ use axum_test::TestServer;
 use near_async::ActorSystem;
 use near_async::messaging::{IntoMultiSender, IntoSender, noop};
 use near_chain::ChainGenesis;
+use near_chain::signing_history::SigningHistory;
 use near_chain_configs::test_utils::TestClientConfigParams;
 use near_chain_configs::{ClientConfig, Genesis, MutableConfigValue, TrackedShardsConfig};
 use near_client::adversarial::Controls;
@@ -164,12 +165,13 @@ pub fn create_test_setup_with_accounts_and_validity(
             spice_data_distributor_sender: noop().into_sender(),
             spice_core_writer_sender: noop().into_sender(),
         },
+        SigningHistory::disabled(),
     );
 
     // 8. Create RpcHandlerActor
     let rpc_handler_config = RpcHandlerConfig {
         handler_threads: client_config.transaction_request_handler_threads,
-        tx_routing_height_horizon: client_config.tx_routing_height_horizon,
+        tx_routing_height_horizon: client_config.tx_routing_height_horizon.clone(),
         epoch_length: client_config.epoch_length,
         transaction_validity_period,
         disable_tx_routing: client_config.disable_tx_routing,
@@ -194,11 +196,15 @@ pub fn create_test_setup_with_accounts_and_validity(
         polling_config: Default::default(),
         limits_config: Default::default(),
         enable_debug_rpc: false,
+        enable_simulate_tx: true,
         experimental_debug_pages_src_path: None,
     };
 
+    let updatable_rpc_config =
+        MutableConfigValue::new(rpc_config.updatable_config(), "rpc_updatable_config");
     let app = create_jsonrpc_app(
         rpc_config,
+        updatable_rpc_config,
         genesis.config,
         client_result.client_actor.into_multi_sender(),
         view_client_actor.into_multi_sender(),
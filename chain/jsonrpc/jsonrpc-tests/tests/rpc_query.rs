@@ -365,6 +365,7 @@ async fn test_query_call_function() {
                 account_id: "test1".parse().unwrap(),
                 method_name: "run_test".to_string(),
                 args: vec![].into(),
+                trace: false,
             },
         })
         .await
@@ -2,13 +2,16 @@ use std::ops::ControlFlow;
 
 use near_crypto::InMemorySigner;
 use near_jsonrpc::client::new_client;
+use near_jsonrpc_primitives::types::simulation::{
+    RpcSimulateTransactionRequest, SimulatedTransactionInfo,
+};
 use near_jsonrpc_primitives::types::transactions::{RpcTransactionStatusRequest, TransactionInfo};
 use near_network::test_utils::wait_or_timeout;
 use near_primitives::hash::{CryptoHash, hash};
 use near_primitives::serialize::to_base64;
 use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::{Balance, BlockReference};
-use near_primitives::views::{FinalExecutionStatus, TxExecutionStatus};
+use near_primitives::views::{ExecutionStatusView, FinalExecutionStatus, TxExecutionStatus};
 
 use near_jsonrpc_tests::{
     NodeType, create_test_setup_with_accounts_and_validity, create_test_setup_with_node_type,
@@ -222,3 +225,46 @@ async fn test_check_invalid_tx() {
         Ok(_) => panic!("transaction should not succeed"),
     }
 }
+
+/// Test that a simulated transaction is executed with all its receipts, but nothing is persisted.
+#[tokio::test]
+// TODO(spice): Assess if this test is relevant for spice and if yes fix it.
+#[cfg_attr(feature = "protocol_feature_spice", ignore)]
+async fn test_simulate_tx() {
+    let setup = create_test_setup_with_node_type(NodeType::Validator);
+    let client = new_client(&setup.server_addr);
+
+    let block_hash = client.block(BlockReference::latest()).await.unwrap().header.hash;
+    let signer = InMemorySigner::test_signer(&"test1".parse().unwrap());
+    let tx = SignedTransaction::send_money(
+        1,
+        "test1".parse().unwrap(),
+        "test2".parse().unwrap(),
+        &signer,
+        Balance::from_yoctonear(100),
+        block_hash,
+    );
+    let simulated = client
+        .EXPERIMENTAL_simulate_tx(RpcSimulateTransactionRequest {
+            transaction: SimulatedTransactionInfo::SignedTransaction { signed_tx: tx.clone() },
+        })
+        .await
+        .unwrap()
+        .simulated_transaction;
+    assert_eq!(simulated.transaction_outcome.id, tx.get_hash());
+    assert_eq!(simulated.transaction_outcome.round, 0);
+    let receipt_outcome = &simulated.receipts_outcome[0];
+    assert_eq!(receipt_outcome.round, 1);
+    assert_eq!(receipt_outcome.outcome.executor_id.as_str(), "test2");
+    assert_eq!(receipt_outcome.outcome.status, ExecutionStatusView::SuccessValue(vec![]));
+    assert!(!simulated.state_changes.is_empty());
+    assert!(simulated.unprocessed_receipts.is_empty());
+
+    // The simulation did not bump the nonce, so the same transaction can still be submitted.
+    let bytes = borsh::to_vec(&tx).unwrap();
+    let result = client.broadcast_tx_commit(to_base64(&bytes)).await.unwrap();
+    assert_eq!(
+        result.final_execution_outcome.unwrap().into_outcome().status,
+        FinalExecutionStatus::SuccessValue(Vec::new())
+    );
+}
@@ -26,16 +26,21 @@ use near_jsonrpc_primitives::types::{
     network_info::{RpcNetworkInfoError, RpcNetworkInfoResponse},
     query::{RpcQueryError, RpcQueryRequest, RpcQueryResponse},
     receipts::{RpcReceiptError, RpcReceiptRequest, RpcReceiptResponse},
+    simulation::{
+        RpcSimulateTransactionError, RpcSimulateTransactionRequest, RpcSimulateTransactionResponse,
+    },
     split_storage::{
         RpcSplitStorageInfoError, RpcSplitStorageInfoRequest, RpcSplitStorageInfoResponse,
     },
     status::{RpcHealthResponse, RpcStatusError, RpcStatusResponse},
+    trace::{RpcTraceReceiptError, RpcTraceReceiptRequest, RpcTraceReceiptResponse},
     transactions::{
         RpcSendTransactionRequest, RpcTransactionError, RpcTransactionResponse,
         RpcTransactionStatusRequest,
     },
     validator::{
-        RpcValidatorError, RpcValidatorRequest, RpcValidatorResponse, RpcValidatorsOrderedRequest,
+        RpcValidatorError, RpcValidatorForecastRequest, RpcValidatorForecastResponse,
+        RpcValidatorRequest, RpcValidatorResponse, RpcValidatorsOrderedRequest,
         RpcValidatorsOrderedResponse,
     },
 };
@@ -845,6 +850,12 @@ fn main() {
         "EXPERIMENTAL_validators_ordered".to_string(),
         "Returns the current epoch validators ordered in the block producer order with repetition. This endpoint is solely used for bridge currently and is not intended for other external use cases.".to_string(),
     );
+    add_spec_for_path::<RpcValidatorForecastRequest, RpcValidatorForecastResponse, RpcValidatorError>(
+        &mut all_schemas,
+        &mut all_paths,
+        "EXPERIMENTAL_validator_forecast".to_string(),
+        "Projects the online ratio, reward and kickout of each current epoch validator at the end of the epoch, and lists the blocks, chunks and endorsements it missed so far.".to_string(),
+    );
     add_spec_for_path::<RpcMaintenanceWindowsRequest, RpcMaintenanceWindowsResponse, RpcMaintenanceWindowsError>(
         &mut all_schemas,
         &mut all_paths,
@@ -857,6 +868,18 @@ fn main() {
         "EXPERIMENTAL_split_storage_info".to_string(),
         "Contains the split storage information. More info on split storage [here](https://near-nodes.io/archival/split-storage-archival)".to_string(),
     );
+    add_spec_for_path::<RpcSimulateTransactionRequest, RpcSimulateTransactionResponse, RpcSimulateTransactionError>(
+        &mut all_schemas,
+        &mut all_paths,
+        "EXPERIMENTAL_simulate_tx".to_string(),
+        "Executes a signed or unsigned transaction and all receipts it produces on top of the latest final state without submitting it. Returns all execution outcomes and the resulting state changes.".to_string(),
+    );
+    add_spec_for_path::<RpcTraceReceiptRequest, RpcTraceReceiptResponse, RpcTraceReceiptError>(
+        &mut all_schemas,
+        &mut all_paths,
+        "EXPERIMENTAL_trace_receipt".to_string(),
+        "Re-executes the receipt and returns every host function call made by its function call actions, including storage accesses, logs, created promises, gas and register contents. Tracing older receipts requires an archival node.".to_string(),
+    );
     add_spec_for_path::<RpcQueryRequest, RpcQueryResponse, RpcQueryError>(
         &mut all_schemas,
         &mut all_paths,
@@ -17,8 +17,10 @@ mod network_info;
 mod query;
 mod receipts;
 mod sandbox;
+mod simulation;
 mod split_storage;
 mod status;
+mod trace;
 mod transactions;
 mod validator;
 
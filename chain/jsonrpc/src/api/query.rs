@@ -79,6 +79,7 @@ fn parse_path_data(path: String, data: String) -> Result<RpcQueryRequest, RpcPar
                 account_id,
                 method_name: method_name.to_string(),
                 args: parse_data()?.into(),
+                trace: false,
             },
             None => return Err(RpcParseError("Method name is missing".to_string())),
         },
@@ -121,9 +122,12 @@ impl RpcFrom<QueryError> for RpcQueryError {
             QueryError::UnknownGasKey { public_key, block_height, block_hash } => {
                 Self::UnknownGasKey { public_key, block_height, block_hash }
             }
-            QueryError::ContractExecutionError { vm_error, block_height, block_hash } => {
-                Self::ContractExecutionError { vm_error, block_height, block_hash }
-            }
+            QueryError::ContractExecutionError {
+                vm_error,
+                backtrace,
+                block_height,
+                block_hash,
+            } => Self::ContractExecutionError { vm_error, backtrace, block_height, block_hash },
             QueryError::Unreachable { ref error_message } => {
                 tracing::warn!(target: "jsonrpc", %error_message, "unreachable error occurred");
                 crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
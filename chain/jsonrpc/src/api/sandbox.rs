@@ -4,7 +4,9 @@ use serde_json::Value;
 use near_jsonrpc_primitives::errors::RpcParseError;
 use near_jsonrpc_primitives::types::sandbox::{
     RpcSandboxFastForwardError, RpcSandboxFastForwardRequest, RpcSandboxPatchStateError,
-    RpcSandboxPatchStateRequest,
+    RpcSandboxPatchStateRequest, RpcSandboxRevertError, RpcSandboxRevertRequest,
+    RpcSandboxSetBlockTimestampError, RpcSandboxSetBlockTimestampRequest, RpcSandboxSnapshotError,
+    RpcSandboxSnapshotRequest,
 };
 
 use super::{Params, RpcFrom, RpcRequest};
@@ -21,6 +23,24 @@ impl RpcRequest for RpcSandboxFastForwardRequest {
     }
 }
 
+impl RpcRequest for RpcSandboxSnapshotRequest {
+    fn parse(value: Value) -> Result<Self, RpcParseError> {
+        Params::parse(value)
+    }
+}
+
+impl RpcRequest for RpcSandboxRevertRequest {
+    fn parse(value: Value) -> Result<Self, RpcParseError> {
+        Params::parse(value)
+    }
+}
+
+impl RpcRequest for RpcSandboxSetBlockTimestampRequest {
+    fn parse(value: Value) -> Result<Self, RpcParseError> {
+        Params::parse(value)
+    }
+}
+
 impl RpcFrom<AsyncSendError> for RpcSandboxPatchStateError {
     fn rpc_from(error: AsyncSendError) -> Self {
         Self::InternalError { error_message: error.to_string() }
@@ -32,3 +52,21 @@ impl RpcFrom<AsyncSendError> for RpcSandboxFastForwardError {
         Self::InternalError { error_message: error.to_string() }
     }
 }
+
+impl RpcFrom<AsyncSendError> for RpcSandboxSnapshotError {
+    fn rpc_from(error: AsyncSendError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl RpcFrom<AsyncSendError> for RpcSandboxRevertError {
+    fn rpc_from(error: AsyncSendError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl RpcFrom<AsyncSendError> for RpcSandboxSetBlockTimestampError {
+    fn rpc_from(error: AsyncSendError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
@@ -0,0 +1,78 @@
+use near_async::messaging::AsyncSendError;
+use serde_json::Value;
+
+use near_client_primitives::types::SimulateTransactionError;
+use near_jsonrpc_primitives::errors::RpcParseError;
+use near_jsonrpc_primitives::types::simulation::{
+    RpcSimulateTransactionError, RpcSimulateTransactionRequest,
+};
+
+use super::{Params, RpcFrom, RpcRequest};
+
+impl RpcRequest for RpcSimulateTransactionRequest {
+    fn parse(value: Value) -> Result<Self, RpcParseError> {
+        Params::parse(value)
+    }
+}
+
+impl RpcFrom<AsyncSendError> for RpcSimulateTransactionError {
+    fn rpc_from(error: AsyncSendError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl RpcFrom<SimulateTransactionError> for RpcSimulateTransactionError {
+    fn rpc_from(error: SimulateTransactionError) -> Self {
+        match error {
+            SimulateTransactionError::IOError(error_message) => {
+                Self::InternalError { error_message }
+            }
+            SimulateTransactionError::NoSyncedBlocks => Self::NoSyncedBlocks,
+            SimulateTransactionError::UnavailableShard { requested_shard_id } => {
+                Self::UnavailableShard { requested_shard_id }
+            }
+            SimulateTransactionError::Unreachable(ref error_message) => {
+                tracing::warn!(target: "jsonrpc", %error_message, "unreachable error occurred");
+                crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
+                    .with_label_values(&["RpcSimulateTransactionError"])
+                    .inc();
+                Self::InternalError { error_message: error.to_string() }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::RpcRequest;
+    use near_jsonrpc_primitives::types::simulation::{
+        RpcSimulateTransactionRequest, SimulatedTransactionInfo,
+    };
+    use near_primitives::borsh;
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::serialize::to_base64;
+    use near_primitives::transaction::SignedTransaction;
+
+    #[test]
+    fn test_parse_simulate_tx_signed() {
+        let tx = SignedTransaction::empty(CryptoHash::new());
+        let params =
+            serde_json::json!({"signed_tx_base64": to_base64(&borsh::to_vec(&tx).unwrap())});
+        let request = RpcSimulateTransactionRequest::parse(params).unwrap();
+        assert!(matches!(request.transaction, SimulatedTransactionInfo::SignedTransaction { .. }));
+    }
+
+    #[test]
+    fn test_parse_simulate_tx_unsigned() {
+        let tx = SignedTransaction::empty(CryptoHash::new()).transaction;
+        let params = serde_json::json!({"tx_base64": to_base64(&borsh::to_vec(&tx).unwrap())});
+        let request = RpcSimulateTransactionRequest::parse(params).unwrap();
+        assert!(matches!(request.transaction, SimulatedTransactionInfo::Transaction { .. }));
+    }
+
+    #[test]
+    fn test_parse_simulate_tx_invalid() {
+        let params = serde_json::json!({"tx_base64": "not a transaction"});
+        assert!(RpcSimulateTransactionRequest::parse(params).is_err());
+    }
+}
@@ -50,6 +50,12 @@ impl RpcFrom<near_client_primitives::debug::DebugStatusResponse>
                     x,
                 )
             }
+            near_client_primitives::debug::DebugStatusResponse::ConfigReloads(x) => {
+                near_jsonrpc_primitives::types::status::DebugStatusResponse::ConfigReloads(x)
+            }
+            near_client_primitives::debug::DebugStatusResponse::BlockTimeline(x) => {
+                near_jsonrpc_primitives::types::status::DebugStatusResponse::BlockTimeline(x)
+            }
         }
     }
 }
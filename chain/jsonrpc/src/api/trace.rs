@@ -0,0 +1,39 @@
+use near_async::messaging::AsyncSendError;
+use serde_json::Value;
+
+use near_client_primitives::types::TraceReceiptError;
+use near_jsonrpc_primitives::errors::RpcParseError;
+use near_jsonrpc_primitives::types::trace::{RpcTraceReceiptError, RpcTraceReceiptRequest};
+
+use super::{Params, RpcFrom, RpcRequest};
+
+impl RpcRequest for RpcTraceReceiptRequest {
+    fn parse(value: Value) -> Result<Self, RpcParseError> {
+        Params::parse(value)
+    }
+}
+
+impl RpcFrom<AsyncSendError> for RpcTraceReceiptError {
+    fn rpc_from(error: AsyncSendError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl RpcFrom<TraceReceiptError> for RpcTraceReceiptError {
+    fn rpc_from(error: TraceReceiptError) -> Self {
+        match error {
+            TraceReceiptError::IOError(error_message) => Self::InternalError { error_message },
+            TraceReceiptError::UnknownReceipt { receipt_id } => Self::UnknownReceipt { receipt_id },
+            TraceReceiptError::UnavailableShard { requested_shard_id } => {
+                Self::UnavailableShard { requested_shard_id }
+            }
+            TraceReceiptError::Unreachable(ref error_message) => {
+                tracing::warn!(target: "jsonrpc", %error_message, "unreachable error occurred");
+                crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
+                    .with_label_values(&["RpcTraceReceiptError"])
+                    .inc();
+                Self::InternalError { error_message: error.to_string() }
+            }
+        }
+    }
+}
@@ -2,24 +2,31 @@
 
 pub use api::{RpcFrom, RpcInto, RpcRequest};
 use axum::Router;
-use axum::extract::{Json, Path, Query as AxumQuery, State};
+use axum::body::Body;
+use axum::extract::{
+    DefaultBodyLimit, Json, Path, Query as AxumQuery, Request as HttpRequest, State,
+};
 use axum::http::HeaderValue;
 use axum::http::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE};
 use axum::http::{Method, StatusCode};
+use axum::middleware::{self, Next};
 use axum::response::{Html, IntoResponse, Response};
 use axum::routing::{get, post};
+use http_body_util::LengthLimitError;
 use near_async::futures::{FutureSpawner, FutureSpawnerExt};
 use near_async::instrumentation::all_actor_instrumentations_view;
 use near_async::messaging::{AsyncSendError, AsyncSender, CanSend, CanSendAsync, Sender};
 use near_async::time::Clock;
-use near_chain_configs::{ClientConfig, GenesisConfig, ProtocolConfigView};
+use near_chain_configs::{
+    ClientConfig, GenesisConfig, MutableConfigValue, ProtocolConfigView, UpdatableRpcConfig,
+};
 use near_client::{
     DebugStatus, GetBlock, GetBlockProof, GetBlockProofResponse, GetChunk, GetClientConfig,
     GetExecutionOutcome, GetExecutionOutcomeResponse, GetGasPrice, GetMaintenanceWindows,
     GetNetworkInfo, GetNextLightClientBlock, GetProtocolConfig, GetReceipt, GetStateChanges,
-    GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered, ProcessTxRequest,
-    ProcessTxResponse, Query as ClientQuery, QueryError, Status, StatusResponse, TxStatus,
-    TxStatusError,
+    GetStateChangesBlockHeader, GetStateChangesInBlock, GetValidatorForecast, GetValidatorInfo,
+    GetValidatorOrdered, ProcessTxRequest, ProcessTxResponse, Query as ClientQuery, QueryError,
+    SimulateTransaction, Status, StatusResponse, TraceReceipt, TxStatus, TxStatusError,
 };
 use near_client_primitives::debug::{
     DebugBlockStatusQuery, DebugBlocksStartingMode, DebugStatusResponse,
@@ -29,7 +36,7 @@ use near_client_primitives::types::{
     GetExecutionOutcomeError, GetGasPriceError, GetMaintenanceWindowsError,
     GetNextLightClientBlockError, GetProtocolConfigError, GetReceiptError, GetSplitStorageInfo,
     GetSplitStorageInfoError, GetStateChangesError, GetValidatorInfoError, NetworkInfoResponse,
-    StatusError,
+    SimulateTransactionError, StatusError, TraceReceiptError,
 };
 pub use near_jsonrpc_client_internal as client;
 pub use near_jsonrpc_primitives as primitives;
@@ -54,9 +61,10 @@ use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::{AccountId, BlockId, BlockReference};
 use near_primitives::views::validator_stake_view::ValidatorStakeView;
 use near_primitives::views::{
-    BlockView, ChunkView, EpochValidatorInfo, GasPriceView, LightClientBlockView,
-    MaintenanceWindowsView, QueryRequest, QueryResponse, ReceiptView, SplitStorageInfoView,
-    StateChangesKindsView, StateChangesView, TxExecutionStatus, TxStatusView,
+    BlockHeaderView, BlockView, ChunkView, EpochValidatorInfo, GasPriceView, LightClientBlockView,
+    MaintenanceWindowsView, QueryRequest, QueryResponse, ReceiptTraceView, ReceiptView,
+    SimulatedTransactionView, SplitStorageInfoView, StateChangesKindsView, StateChangesView,
+    TxExecutionStatus, TxStatusView, ValidatorForecastView,
 };
 use serde_json::{Value, json};
 use std::future::Future;
@@ -66,8 +74,7 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::time::{sleep, timeout};
-use tower_http::cors::CorsLayer;
-use tower_http::limit::RequestBodyLimitLayer;
+use tower_http::cors::{AllowOrigin, CorsLayer};
 
 mod api;
 mod metrics;
@@ -116,6 +123,10 @@ pub struct RpcConfig {
     // We disable it by default, as some of those endpoints might be quite CPU heavy.
     #[serde(default = "default_enable_debug_rpc")]
     pub enable_debug_rpc: bool,
+    // If true, enable the EXPERIMENTAL_simulate_tx endpoint. Disabled by default, as every
+    // simulation executes a transaction and its receipts on the view client thread.
+    #[serde(default)]
+    pub enable_simulate_tx: bool,
     // For node developers only: if specified, the HTML files used to serve the debug pages will
     // be read from this directory, instead of the contents compiled into the binary. This allows
     // for quick iterative development.
@@ -131,6 +142,7 @@ impl Default for RpcConfig {
             polling_config: Default::default(),
             limits_config: Default::default(),
             enable_debug_rpc: false,
+            enable_simulate_tx: false,
             experimental_debug_pages_src_path: None,
         }
     }
@@ -140,6 +152,14 @@ impl RpcConfig {
     pub fn new(addr: tcp::ListenerAddr) -> Self {
         RpcConfig { addr, ..Default::default() }
     }
+
+    /// Returns the part of the config that can be updated while the node is running.
+    pub fn updatable_config(&self) -> UpdatableRpcConfig {
+        UpdatableRpcConfig {
+            cors_allowed_origins: self.cors_allowed_origins.clone(),
+            json_payload_max_size: self.limits_config.json_payload_max_size,
+        }
+    }
 }
 
 /// Serializes response of a query into JSON to be sent to the client.
@@ -199,14 +219,21 @@ fn process_query_response(
         Err(err) => match err {
             near_jsonrpc_primitives::types::query::RpcQueryError::ContractExecutionError {
                 vm_error,
+                backtrace,
                 block_height,
                 block_hash,
-            } => Ok(json!({
-                "error": vm_error,
-                "logs": json!([]),
-                "block_height": block_height,
-                "block_hash": block_hash,
-            })),
+            } => {
+                let mut response = json!({
+                    "error": vm_error,
+                    "logs": json!([]),
+                    "block_height": block_height,
+                    "block_hash": block_hash,
+                });
+                if let Some(backtrace) = backtrace {
+                    response["backtrace"] = json!(backtrace);
+                }
+                Ok(response)
+            }
             near_jsonrpc_primitives::types::query::RpcQueryError::UnknownAccessKey {
                 public_key,
                 block_height,
@@ -270,6 +297,7 @@ pub struct ClientSenderForRpc(
 #[derive(Clone, near_async::MultiSend, near_async::MultiSenderFrom)]
 pub struct ViewClientSenderForRpc(
     AsyncSender<GetBlock, Result<BlockView, GetBlockError>>,
+    AsyncSender<GetStateChangesBlockHeader, Result<BlockHeaderView, GetBlockError>>,
     AsyncSender<GetBlockProof, Result<GetBlockProofResponse, GetBlockProofError>>,
     AsyncSender<GetChunk, Result<ChunkView, GetChunkError>>,
     AsyncSender<GetExecutionOutcome, Result<GetExecutionOutcomeResponse, GetExecutionOutcomeError>>,
@@ -286,7 +314,10 @@ pub struct ViewClientSenderForRpc(
     AsyncSender<GetStateChangesInBlock, Result<StateChangesKindsView, GetStateChangesError>>,
     AsyncSender<GetValidatorInfo, Result<EpochValidatorInfo, GetValidatorInfoError>>,
     AsyncSender<GetValidatorOrdered, Result<Vec<ValidatorStakeView>, GetValidatorInfoError>>,
+    AsyncSender<GetValidatorForecast, Result<ValidatorForecastView, GetValidatorInfoError>>,
     AsyncSender<ClientQuery, Result<QueryResponse, QueryError>>,
+    AsyncSender<SimulateTransaction, Result<SimulatedTransactionView, SimulateTransactionError>>,
+    AsyncSender<TraceReceipt, Result<ReceiptTraceView, TraceReceiptError>>,
     AsyncSender<TxStatus, Result<TxStatusView, TxStatusError>>,
     #[cfg(feature = "test_features")] Sender<near_client::NetworkAdversarialMessage>,
 );
@@ -308,6 +339,7 @@ struct JsonRpcHandler {
     polling_config: RpcPollingConfig,
     genesis_config: GenesisConfig,
     enable_debug_rpc: bool,
+    enable_simulate_tx: bool,
     debug_pages_src_path: Option<PathBuf>,
     entity_debug_handler: Arc<dyn EntityDebugHandler>,
 }
@@ -476,9 +508,18 @@ impl JsonRpcHandler {
             "EXPERIMENTAL_validators_ordered" => {
                 process_method_call(request, |params| self.validators_ordered(params)).await
             }
+            "EXPERIMENTAL_validator_forecast" => {
+                process_method_call(request, |params| self.validator_forecast(params)).await
+            }
             "EXPERIMENTAL_split_storage_info" => {
                 process_method_call(request, |params| self.split_storage_info(params)).await
             }
+            "EXPERIMENTAL_simulate_tx" if self.enable_simulate_tx => {
+                process_method_call(request, |params| self.simulate_tx(params)).await
+            }
+            "EXPERIMENTAL_trace_receipt" => {
+                process_method_call(request, |params| self.trace_receipt(params)).await
+            }
             #[cfg(feature = "sandbox")]
             "sandbox_patch_state" => {
                 process_method_call(request, |params| self.sandbox_patch_state(params)).await
@@ -487,6 +528,19 @@ impl JsonRpcHandler {
             "sandbox_fast_forward" => {
                 process_method_call(request, |params| self.sandbox_fast_forward(params)).await
             }
+            #[cfg(feature = "sandbox")]
+            "sandbox_snapshot" => {
+                process_method_call(request, |params| self.sandbox_snapshot(params)).await
+            }
+            #[cfg(feature = "sandbox")]
+            "sandbox_revert" => {
+                process_method_call(request, |params| self.sandbox_revert(params)).await
+            }
+            #[cfg(feature = "sandbox")]
+            "sandbox_set_block_timestamp" => {
+                process_method_call(request, |params| self.sandbox_set_block_timestamp(params))
+                    .await
+            }
             _ => return Err(request),
         })
     }
@@ -838,6 +892,9 @@ impl JsonRpcHandler {
                     "/debug/api/requested_state_parts" => {
                         self.client_send(DebugStatus::RequestedStateParts).await?.rpc_into()
                     }
+                    "/debug/api/config_reloads" => {
+                        self.client_send(DebugStatus::ConfigReloads).await?.rpc_into()
+                    }
                     "/debug/api/peer_store" => self
                         .peer_manager_send(near_network::debug::GetDebugStatus::PeerStore)
                         .await?
@@ -912,6 +969,24 @@ impl JsonRpcHandler {
         }
     }
 
+    pub async fn debug_block_timeline(
+        &self,
+        block_hash: CryptoHash,
+    ) -> Result<
+        Option<near_jsonrpc_primitives::types::status::RpcDebugStatusResponse>,
+        near_jsonrpc_primitives::types::status::RpcStatusError,
+    > {
+        if self.enable_debug_rpc {
+            let debug_status =
+                self.client_send(DebugStatus::BlockTimeline(block_hash)).await?.rpc_into();
+            Ok(Some(near_jsonrpc_primitives::types::status::RpcDebugStatusResponse {
+                status_response: debug_status,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
     pub fn instrumented_threads(
         &self,
     ) -> Result<
@@ -1082,10 +1157,14 @@ impl JsonRpcHandler {
         near_jsonrpc_primitives::types::changes::RpcStateChangesInBlockResponse,
         near_jsonrpc_primitives::types::changes::RpcStateChangesError,
     > {
-        let block: near_primitives::views::BlockView =
-            self.view_client_send(GetBlock(request.block_reference)).await?;
+        let header = self
+            .view_client_send(GetStateChangesBlockHeader {
+                block_reference: request.block_reference,
+                account_ids: request.state_changes_request.account_ids(),
+            })
+            .await?;
 
-        let block_hash = block.header.hash;
+        let block_hash = header.hash;
         let changes = self
             .view_client_send(GetStateChanges {
                 block_hash,
@@ -1094,7 +1173,7 @@ impl JsonRpcHandler {
             .await?;
 
         Ok(near_jsonrpc_primitives::types::changes::RpcStateChangesInBlockResponse {
-            block_hash: block.header.hash,
+            block_hash,
             changes,
         })
     }
@@ -1215,6 +1294,21 @@ impl JsonRpcHandler {
         Ok(validators)
     }
 
+    /// Projects the online ratio, reward and kickout of each current epoch validator at the end
+    /// of the epoch, listing the blocks, chunks and endorsements it missed so far.
+    async fn validator_forecast(
+        &self,
+        request: near_jsonrpc_primitives::types::validator::RpcValidatorForecastRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::validator::RpcValidatorForecastResponse,
+        near_jsonrpc_primitives::types::validator::RpcValidatorError,
+    > {
+        let near_jsonrpc_primitives::types::validator::RpcValidatorForecastRequest { block_id } =
+            request;
+        let forecast = self.view_client_send(GetValidatorForecast { block_id }).await?;
+        Ok(near_jsonrpc_primitives::types::validator::RpcValidatorForecastResponse { forecast })
+    }
+
     /// If experimental_debug_pages_src_path config is set, reads the html file from that
     /// directory. Otherwise, returns None.
     fn read_html_file_override(&self, html_file: &'static str) -> Option<String> {
@@ -1243,6 +1337,52 @@ impl JsonRpcHandler {
         Ok(windows)
     }
 
+    /// Executes the transaction and all receipts it produces on top of the
+    /// latest final state without submitting anything to the chain.
+    async fn simulate_tx(
+        &self,
+        request: near_jsonrpc_primitives::types::simulation::RpcSimulateTransactionRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::simulation::RpcSimulateTransactionResponse,
+        near_jsonrpc_primitives::types::simulation::RpcSimulateTransactionError,
+    > {
+        use near_jsonrpc_primitives::types::simulation::SimulatedTransactionInfo;
+
+        let (signed_transaction, verify_signature) = match request.transaction {
+            SimulatedTransactionInfo::SignedTransaction { signed_tx } => (signed_tx, true),
+            SimulatedTransactionInfo::Transaction { tx } => (
+                SignedTransaction::new(
+                    near_crypto::Signature::empty(near_crypto::KeyType::ED25519),
+                    tx,
+                ),
+                false,
+            ),
+        };
+        let simulated_transaction = self
+            .view_client_send(SimulateTransaction { signed_transaction, verify_signature })
+            .await?;
+        Ok(near_jsonrpc_primitives::types::simulation::RpcSimulateTransactionResponse {
+            simulated_transaction,
+        })
+    }
+
+    /// Re-executes the chunk in which the receipt was executed and returns the
+    /// host calls made by the receipt. Requires the state from before the
+    /// receipt was executed, so older receipts can only be traced on archival
+    /// nodes.
+    async fn trace_receipt(
+        &self,
+        request: near_jsonrpc_primitives::types::trace::RpcTraceReceiptRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::trace::RpcTraceReceiptResponse,
+        near_jsonrpc_primitives::types::trace::RpcTraceReceiptError,
+    > {
+        let near_jsonrpc_primitives::types::receipts::ReceiptReference { receipt_id } =
+            request.receipt_reference;
+        let receipt_trace = self.view_client_send(TraceReceipt { receipt_id }).await?;
+        Ok(near_jsonrpc_primitives::types::trace::RpcTraceReceiptResponse { receipt_trace })
+    }
+
     async fn client_config(
         &self,
     ) -> Result<
@@ -1356,6 +1496,86 @@ impl JsonRpcHandler {
 
         Ok(near_jsonrpc_primitives::types::sandbox::RpcSandboxFastForwardResponse {})
     }
+
+    async fn sandbox_snapshot(
+        &self,
+        _request: near_jsonrpc_primitives::types::sandbox::RpcSandboxSnapshotRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::sandbox::RpcSandboxSnapshotResponse,
+        near_jsonrpc_primitives::types::sandbox::RpcSandboxSnapshotError,
+    > {
+        use near_client_primitives::types::SandboxResponse;
+        use near_jsonrpc_primitives::types::sandbox::RpcSandboxSnapshotError;
+
+        let response = self
+            .client_sender
+            .send_async(near_client_primitives::types::SandboxMessage::SandboxSnapshot)
+            .await
+            .map_err(RpcFrom::rpc_from)?;
+        match response {
+            SandboxResponse::SandboxSnapshotCreated(snapshot_id) => {
+                Ok(near_jsonrpc_primitives::types::sandbox::RpcSandboxSnapshotResponse {
+                    snapshot_id,
+                })
+            }
+            SandboxResponse::SandboxSnapshotFailed(error_message) => {
+                Err(RpcSandboxSnapshotError::InternalError { error_message })
+            }
+            response => Err(RpcSandboxSnapshotError::InternalError {
+                error_message: format!("unexpected response: {:?}", response),
+            }),
+        }
+    }
+
+    async fn sandbox_revert(
+        &self,
+        revert_request: near_jsonrpc_primitives::types::sandbox::RpcSandboxRevertRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::sandbox::RpcSandboxRevertResponse,
+        near_jsonrpc_primitives::types::sandbox::RpcSandboxRevertError,
+    > {
+        use near_client_primitives::types::SandboxResponse;
+
+        let response = self
+            .client_sender
+            .send_async(near_client_primitives::types::SandboxMessage::SandboxRevert(
+                revert_request.snapshot_id,
+            ))
+            .await
+            .map_err(RpcFrom::rpc_from)?;
+        if let SandboxResponse::SandboxRevertFailed(error_message) = response {
+            return Err(
+                near_jsonrpc_primitives::types::sandbox::RpcSandboxRevertError::RevertFailed {
+                    error_message,
+                },
+            );
+        }
+        Ok(near_jsonrpc_primitives::types::sandbox::RpcSandboxRevertResponse {})
+    }
+
+    async fn sandbox_set_block_timestamp(
+        &self,
+        set_block_timestamp_request: near_jsonrpc_primitives::types::sandbox::RpcSandboxSetBlockTimestampRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::sandbox::RpcSandboxSetBlockTimestampResponse,
+        near_jsonrpc_primitives::types::sandbox::RpcSandboxSetBlockTimestampError,
+    > {
+        use near_client_primitives::types::SandboxResponse;
+
+        let response = self
+            .client_sender
+            .send_async(near_client_primitives::types::SandboxMessage::SandboxSetBlockTimestamp(
+                set_block_timestamp_request.timestamp_nanosec,
+            ))
+            .await
+            .map_err(RpcFrom::rpc_from)?;
+        if let SandboxResponse::SandboxSetBlockTimestampFailed(error_message) = response {
+            return Err(near_jsonrpc_primitives::types::sandbox::RpcSandboxSetBlockTimestampError::InvalidTimestamp {
+                error_message,
+            });
+        }
+        Ok(near_jsonrpc_primitives::types::sandbox::RpcSandboxSetBlockTimestampResponse {})
+    }
 }
 
 #[cfg(feature = "test_features")]
@@ -1607,6 +1827,20 @@ async fn debug_epoch_info_handler(
     }
 }
 
+async fn debug_block_timeline_handler(
+    State(handler): State<Arc<JsonRpcHandler>>,
+    Path(block_hash): Path<String>,
+) -> Response {
+    let Ok(block_hash) = block_hash.parse::<CryptoHash>() else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    match handler.debug_block_timeline(block_hash).await {
+        Ok(Some(value)) => (StatusCode::OK, Json(value)).into_response(),
+        Ok(None) => StatusCode::METHOD_NOT_ALLOWED.into_response(),
+        Err(_) => StatusCode::SERVICE_UNAVAILABLE.into_response(),
+    }
+}
+
 async fn health_handler(State(handler): State<Arc<JsonRpcHandler>>) -> Response {
     match handler.health().await {
         Ok(value) => (StatusCode::OK, Json(value)).into_response(),
@@ -1664,6 +1898,38 @@ fn get_cors(cors_allowed_origins: &[String]) -> CorsLayer {
         .max_age(std::time::Duration::from_secs(3600))
 }
 
+/// Like [get_cors], but the allowed origins follow the updates of `updatable_config`.
+/// Allowed methods and headers are determined by the config the server started with.
+fn get_updatable_cors(updatable_config: MutableConfigValue<UpdatableRpcConfig>) -> CorsLayer {
+    get_cors(&updatable_config.get().cors_allowed_origins).allow_origin(AllowOrigin::predicate(
+        move |origin: &HeaderValue, _| {
+            let cors_allowed_origins = updatable_config.get().cors_allowed_origins;
+            cors_allowed_origins == ["*".to_string()]
+                || cors_allowed_origins
+                    .iter()
+                    .any(|allowed| origin.as_bytes() == allowed.as_bytes())
+        },
+    ))
+}
+
+/// Rejects requests whose body is larger than the configured `json_payload_max_size` with
+/// 413, and requests whose body cannot be read with 400.
+async fn limit_request_body(
+    State(updatable_config): State<MutableConfigValue<UpdatableRpcConfig>>,
+    request: HttpRequest,
+    next: Next,
+) -> Response {
+    let json_payload_max_size = updatable_config.get().json_payload_max_size;
+    let (parts, body) = request.into_parts();
+    match axum::body::to_bytes(body, json_payload_max_size).await {
+        Ok(body) => next.run(HttpRequest::from_parts(parts, Body::from(body))).await,
+        Err(err) if err.into_inner().is::<LengthLimitError>() => {
+            StatusCode::PAYLOAD_TOO_LARGE.into_response()
+        }
+        Err(_) => StatusCode::BAD_REQUEST.into_response(),
+    }
+}
+
 macro_rules! debug_page_string {
     ($html_file: literal, $handler: expr) => {
         $handler
@@ -1697,6 +1963,8 @@ async fn display_debug_html(
         "validator" => Some(debug_page_string!("validator.html", handler)),
         "validator.css" => Some(debug_page_string!("validator.css", handler)),
         "split_store" => Some(debug_page_string!("split_store.html", handler)),
+        "config_reloads" => Some(debug_page_string!("config_reloads.html", handler)),
+        "block_timeline" => Some(debug_page_string!("block_timeline.html", handler)),
         "congestion_control" => Some(debug_page_string!("congestion_control.html", handler)),
         "congestion_control.css" => Some(debug_page_string!("congestion_control.css", handler)),
         "congestion_control.js" => Some(debug_page_string!("congestion_control.js", handler)),
@@ -1716,6 +1984,7 @@ async fn display_debug_html(
 /// or when you need the Router for custom server setup.
 pub fn create_jsonrpc_app(
     config: RpcConfig,
+    updatable_config: MutableConfigValue<UpdatableRpcConfig>,
     genesis_config: GenesisConfig,
     client_sender: ClientSenderForRpc,
     view_client_sender: ViewClientSenderForRpc,
@@ -1725,10 +1994,9 @@ pub fn create_jsonrpc_app(
     entity_debug_handler: Arc<dyn EntityDebugHandler>,
 ) -> Router {
     let RpcConfig {
-        cors_allowed_origins,
         polling_config,
-        limits_config,
         enable_debug_rpc,
+        enable_simulate_tx,
         experimental_debug_pages_src_path: debug_pages_src_path,
         ..
     } = config;
@@ -1742,6 +2010,7 @@ pub fn create_jsonrpc_app(
         polling_config,
         genesis_config,
         enable_debug_rpc,
+        enable_simulate_tx,
         debug_pages_src_path: debug_pages_src_path.map(Into::into),
         entity_debug_handler,
         #[cfg(feature = "test_features")]
@@ -1767,6 +2036,7 @@ pub fn create_jsonrpc_app(
             )
             .route("/debug/api/block_status", get(debug_block_status_handler))
             .route("/debug/api/epoch_info/{epoch_id}", get(debug_epoch_info_handler))
+            .route("/debug/api/block_timeline/{block_hash}", get(debug_block_timeline_handler))
             .route("/debug/api/instrumented_threads", get(debug_instrumented_threads_handler))
             .route("/debug/api/{*api_path}", get(debug_handler))
             .route("/debug/client_config", get(client_config_handler))
@@ -1774,8 +2044,9 @@ pub fn create_jsonrpc_app(
             .route("/debug/pages/{page}", get(display_debug_html));
     }
 
-    app.layer(get_cors(&cors_allowed_origins))
-        .layer(RequestBodyLimitLayer::new(limits_config.json_payload_max_size))
+    app.layer(get_updatable_cors(updatable_config.clone()))
+        .layer(middleware::from_fn_with_state(updatable_config, limit_request_body))
+        .layer(DefaultBodyLimit::disable())
         .with_state(handler)
 }
 
@@ -1789,6 +2060,7 @@ pub fn create_jsonrpc_app(
 /// Starts HTTP server(s) listening for RPC requests using the provided future spawner.
 pub async fn start_http(
     config: RpcConfig,
+    updatable_config: MutableConfigValue<UpdatableRpcConfig>,
     genesis_config: GenesisConfig,
     client_sender: ClientSenderForRpc,
     view_client_sender: ViewClientSenderForRpc,
@@ -1806,6 +2078,7 @@ pub async fn start_http(
     // Create the axum app using the extracted function
     let app = create_jsonrpc_app(
         config,
+        updatable_config,
         genesis_config,
         client_sender,
         view_client_sender,
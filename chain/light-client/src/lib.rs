@@ -0,0 +1,291 @@
+//! Light client that verifies the data served by the light client RPC methods
+//! (`next_light_client_block`, `EXPERIMENTAL_light_client_proof` and
+//! `EXPERIMENTAL_light_client_block_proof`).
+//!
+//! The client starts from a trusted head together with the block producers of the head's epoch
+//! and of the next one. Every `LightClientBlockView` it is given must be approved by more than
+//! 2/3 of the stake of its epoch's block producers, and the block producers it announces for the
+//! next epoch must match its `next_bp_hash`. Once the head is verified, execution outcomes and
+//! blocks before the head can be checked with the merkle proofs returned by the RPC.
+//!
+//! See <https://nomicon.io/ChainSpec/LightClient> for the description of the protocol.
+
+use near_crypto::Signature;
+use near_primitives::block::{Approval, ApprovalInner, compute_bp_hash_from_validator_stakes};
+use near_primitives::hash::CryptoHash;
+use near_primitives::merkle::{
+    MerklePath, combine_hash, compute_root_from_path_and_item, verify_hash, verify_path,
+};
+use near_primitives::types::validator_stake::ValidatorStake;
+use near_primitives::types::{AccountId, Balance, BlockHeight};
+use near_primitives::views::validator_stake_view::ValidatorStakeView;
+use near_primitives::views::{
+    ExecutionOutcomeWithIdView, LightClientBlockLiteView, LightClientBlockView,
+};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(thiserror::Error, Debug)]
+pub enum LightClientError {
+    #[error("block at height {height} is not after the head at height {head_height}")]
+    NotAfterHead { height: BlockHeight, head_height: BlockHeight },
+    #[error("block is in epoch {epoch_id}, which is neither the head's epoch nor the next one")]
+    UnknownEpoch { epoch_id: CryptoHash },
+    #[error("block starts a new epoch but doesn't contain the next epoch's block producers")]
+    MissingNextBlockProducers,
+    #[error("invalid approval signature from {account_id}")]
+    InvalidSignature { account_id: AccountId },
+    #[error("block is approved by {approved_stake} out of {total_stake}, more than 2/3 required")]
+    NotEnoughApprovals { approved_stake: Balance, total_stake: Balance },
+    #[error("next epoch's block producers don't match next_bp_hash")]
+    InvalidNextBlockProducers,
+    #[error("execution outcome is not included in the outcome root of its block")]
+    InvalidOutcomeProof,
+    #[error(
+        "execution outcome proof is for block {proof_block_hash} but the header is of {block_hash}"
+    )]
+    OutcomeBlockMismatch { proof_block_hash: CryptoHash, block_hash: CryptoHash },
+    #[error("block {block_hash} is not included in the block merkle root of the head")]
+    InvalidBlockProof { block_hash: CryptoHash },
+    #[error("I/O error on {path}: {source}")]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error("corrupted light client state in {path}: {source}")]
+    Corrupted { path: PathBuf, source: serde_json::Error },
+}
+
+/// Everything the light client needs to know to verify the next block. This is what gets
+/// persisted, and what a client is bootstrapped from.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct LightClientState {
+    pub head: LightClientBlockLiteView,
+    /// Ordered block producers by epoch id. Only the head's epoch and the next one are kept.
+    pub epoch_block_producers: BTreeMap<CryptoHash, Vec<ValidatorStakeView>>,
+}
+
+impl LightClientState {
+    /// Builds the initial state from a light client block obtained from a trusted source, e.g.
+    /// hardcoded in the application, and the block producers of its epoch. The block's
+    /// `next_bps` are checked against its `next_bp_hash`, but nothing else is verified.
+    pub fn from_trusted_block(
+        block: &LightClientBlockView,
+        block_producers: Vec<ValidatorStakeView>,
+    ) -> Result<Self, LightClientError> {
+        let next_bps = block.next_bps.clone().ok_or(LightClientError::MissingNextBlockProducers)?;
+        verify_next_bp_hash(&next_bps, &block.inner_lite.next_bp_hash)?;
+        let head = LightClientBlockLiteView {
+            prev_block_hash: block.prev_block_hash,
+            inner_rest_hash: block.inner_rest_hash,
+            inner_lite: block.inner_lite.clone(),
+        };
+        let epoch_block_producers = BTreeMap::from([
+            (block.inner_lite.epoch_id, block_producers),
+            (block.inner_lite.next_epoch_id, next_bps),
+        ]);
+        Ok(Self { head, epoch_block_producers })
+    }
+}
+
+pub struct LightClient {
+    state: LightClientState,
+    /// Where the state is saved after every head update, if anywhere.
+    path: Option<PathBuf>,
+}
+
+impl LightClient {
+    /// Creates a light client that keeps its state only in memory.
+    pub fn new(state: LightClientState) -> Self {
+        Self { state, path: None }
+    }
+
+    /// Opens a light client persisted at `path`, or starts a new one from `initial_state` if
+    /// there is nothing there yet. The head is saved to `path` every time it moves.
+    pub fn open(path: &Path, initial_state: LightClientState) -> Result<Self, LightClientError> {
+        let state = match std::fs::read(path) {
+            Ok(data) => serde_json::from_slice(&data).map_err(|source| {
+                LightClientError::Corrupted { path: path.to_path_buf(), source }
+            })?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => initial_state,
+            Err(source) => return Err(LightClientError::Io { path: path.to_path_buf(), source }),
+        };
+        let client = Self { state, path: Some(path.to_path_buf()) };
+        client.save()?;
+        Ok(client)
+    }
+
+    pub fn state(&self) -> &LightClientState {
+        &self.state
+    }
+
+    pub fn head(&self) -> &LightClientBlockLiteView {
+        &self.state.head
+    }
+
+    /// Hash of the head block. This is what `light_client_head` should be set to in proof
+    /// requests.
+    pub fn head_hash(&self) -> CryptoHash {
+        self.state.head.hash()
+    }
+
+    /// Verifies a block returned by `next_light_client_block` and makes it the new head.
+    /// On error the head is left unchanged.
+    pub fn validate_and_update_head(
+        &mut self,
+        block: &LightClientBlockView,
+    ) -> Result<(), LightClientError> {
+        let head = &self.state.head.inner_lite;
+        let inner_lite = &block.inner_lite;
+        if inner_lite.height <= head.height {
+            return Err(LightClientError::NotAfterHead {
+                height: inner_lite.height,
+                head_height: head.height,
+            });
+        }
+        if inner_lite.epoch_id != head.epoch_id && inner_lite.epoch_id != head.next_epoch_id {
+            return Err(LightClientError::UnknownEpoch { epoch_id: inner_lite.epoch_id });
+        }
+        if inner_lite.epoch_id == head.next_epoch_id && block.next_bps.is_none() {
+            return Err(LightClientError::MissingNextBlockProducers);
+        }
+        let block_producers = self
+            .state
+            .epoch_block_producers
+            .get(&inner_lite.epoch_id)
+            .ok_or(LightClientError::UnknownEpoch { epoch_id: inner_lite.epoch_id })?;
+
+        // The approvals are those of the block two heights after this one, endorsing the block
+        // right after this one.
+        let new_head = LightClientBlockLiteView {
+            prev_block_hash: block.prev_block_hash,
+            inner_rest_hash: block.inner_rest_hash,
+            inner_lite: inner_lite.clone(),
+        };
+        let next_block_hash = combine_hash(&block.next_block_inner_hash, &new_head.hash());
+        let approval_message = Approval::get_data_for_sig(
+            &ApprovalInner::Endorsement(next_block_hash),
+            inner_lite.height + 2,
+        );
+        verify_approvals(block_producers, &block.approvals_after_next, &approval_message)?;
+
+        if let Some(next_bps) = &block.next_bps {
+            verify_next_bp_hash(next_bps, &inner_lite.next_bp_hash)?;
+        }
+
+        let mut epoch_block_producers = self.state.epoch_block_producers.clone();
+        if let Some(next_bps) = &block.next_bps {
+            epoch_block_producers.insert(inner_lite.next_epoch_id, next_bps.clone());
+        }
+        epoch_block_producers.retain(|epoch_id, _| {
+            *epoch_id == inner_lite.epoch_id || *epoch_id == inner_lite.next_epoch_id
+        });
+        let previous = std::mem::replace(
+            &mut self.state,
+            LightClientState { head: new_head, epoch_block_producers },
+        );
+        if let Err(err) = self.save() {
+            self.state = previous;
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Verifies that a block before the head is part of the chain, given the header and proof
+    /// returned by `EXPERIMENTAL_light_client_block_proof` for the current head. Returns the hash
+    /// of the block.
+    pub fn verify_block_proof(
+        &self,
+        block_header_lite: &LightClientBlockLiteView,
+        block_proof: &MerklePath,
+    ) -> Result<CryptoHash, LightClientError> {
+        let block_hash = block_header_lite.hash();
+        if !verify_hash(self.state.head.inner_lite.block_merkle_root, block_proof, block_hash) {
+            return Err(LightClientError::InvalidBlockProof { block_hash });
+        }
+        Ok(block_hash)
+    }
+
+    /// Verifies an execution outcome with the proofs returned by `EXPERIMENTAL_light_client_proof`
+    /// for the current head.
+    pub fn verify_execution_outcome_proof(
+        &self,
+        outcome_proof: &ExecutionOutcomeWithIdView,
+        outcome_root_proof: &MerklePath,
+        block_header_lite: &LightClientBlockLiteView,
+        block_proof: &MerklePath,
+    ) -> Result<(), LightClientError> {
+        let shard_outcome_root =
+            compute_root_from_path_and_item(&outcome_proof.proof, outcome_proof.to_hashes());
+        if !verify_path(
+            block_header_lite.inner_lite.outcome_root,
+            outcome_root_proof,
+            shard_outcome_root,
+        ) {
+            return Err(LightClientError::InvalidOutcomeProof);
+        }
+        let block_hash = self.verify_block_proof(block_header_lite, block_proof)?;
+        if block_hash != outcome_proof.block_hash {
+            return Err(LightClientError::OutcomeBlockMismatch {
+                proof_block_hash: outcome_proof.block_hash,
+                block_hash,
+            });
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> Result<(), LightClientError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let io_err = |source| LightClientError::Io { path: path.clone(), source };
+        // Write to a temporary file first so that a crash never leaves a torn state behind.
+        let tmp_path = path.with_extension("tmp");
+        let mut file = std::fs::File::create(&tmp_path).map_err(io_err)?;
+        let data =
+            serde_json::to_vec_pretty(&self.state).expect("serializing to memory can't fail");
+        file.write_all(&data).map_err(io_err)?;
+        file.sync_all().map_err(io_err)?;
+        std::fs::rename(&tmp_path, path).map_err(io_err)
+    }
+}
+
+/// Checks that more than 2/3 of the stake of `block_producers` signed `message`. Approvals are
+/// ordered like the block producers; extra approvals from the next epoch's block producers at
+/// an epoch boundary are ignored.
+fn verify_approvals(
+    block_producers: &[ValidatorStakeView],
+    approvals: &[Option<Box<Signature>>],
+    message: &[u8],
+) -> Result<(), LightClientError> {
+    let mut total_stake = Balance::ZERO;
+    let mut approved_stake = Balance::ZERO;
+    for (i, block_producer) in block_producers.iter().enumerate() {
+        let block_producer = ValidatorStake::from(block_producer.clone());
+        total_stake = total_stake.checked_add(block_producer.stake()).unwrap();
+        let Some(Some(signature)) = approvals.get(i) else {
+            continue;
+        };
+        if !signature.verify(message, block_producer.public_key()) {
+            return Err(LightClientError::InvalidSignature {
+                account_id: block_producer.take_account_id(),
+            });
+        }
+        approved_stake = approved_stake.checked_add(block_producer.stake()).unwrap();
+    }
+
+    let threshold = total_stake.checked_mul(2).unwrap().checked_div(3).unwrap();
+    if approved_stake <= threshold {
+        return Err(LightClientError::NotEnoughApprovals { approved_stake, total_stake });
+    }
+    Ok(())
+}
+
+fn verify_next_bp_hash(
+    next_bps: &[ValidatorStakeView],
+    next_bp_hash: &CryptoHash,
+) -> Result<(), LightClientError> {
+    let next_bps: Vec<ValidatorStake> = next_bps.iter().cloned().map(Into::into).collect();
+    if compute_bp_hash_from_validator_stakes(&next_bps, true) != *next_bp_hash {
+        return Err(LightClientError::InvalidNextBlockProducers);
+    }
+    Ok(())
+}
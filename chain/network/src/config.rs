@@ -13,6 +13,7 @@ use anyhow::Context;
 use near_async::time;
 use near_chain_configs::MutableConfigValue;
 use near_chain_configs::MutableValidatorSigner;
+use near_chain_configs::UpdatableNetworkConfig;
 use near_crypto::{KeyType, SecretKey};
 use near_primitives::network::PeerId;
 use near_primitives::test_utils::create_test_signer;
@@ -490,30 +491,41 @@ impl NetworkConfig {
         }
     }
 
-    pub fn verify(self) -> anyhow::Result<VerifiedConfig> {
-        if !(self.ideal_connections_lo <= self.ideal_connections_hi) {
-            anyhow::bail!(
-                "Invalid ideal_connections values. lo({}) > hi({}).",
-                self.ideal_connections_lo,
-                self.ideal_connections_hi
-            );
+    /// Returns the part of the config that can be updated while the node is running.
+    pub fn updatable_config(&self) -> UpdatableNetworkConfig {
+        UpdatableNetworkConfig {
+            max_num_peers: self.max_num_peers,
+            minimum_outbound_peers: self.minimum_outbound_peers,
+            ideal_connections_lo: self.ideal_connections_lo,
+            ideal_connections_hi: self.ideal_connections_hi,
+            received_messages_rate_limits: self.received_messages_rate_limits.to_updatable(),
         }
+    }
 
-        if !(self.ideal_connections_hi <= self.max_num_peers) {
-            anyhow::bail!(
-                "max_num_peers({}) < ideal_connections_hi({}) which may lead to connection saturation and declining new connections.",
-                self.max_num_peers,
-                self.ideal_connections_hi
-            );
+    /// Extracts the part of the JSON config that can be updated while the node is running.
+    /// The result is validated the same way as by [NetworkConfig::verify].
+    pub fn updatable_config_from_json(
+        cfg: &crate::config_json::Config,
+    ) -> anyhow::Result<UpdatableNetworkConfig> {
+        let mut received_messages_rate_limits = messages_limits::Config::standard_preset();
+        if let Some(rate_limits) =
+            &cfg.experimental.network_config_overrides.received_messages_rate_limits
+        {
+            received_messages_rate_limits.apply_overrides(rate_limits.clone());
         }
+        let config = UpdatableNetworkConfig {
+            max_num_peers: cfg.max_num_peers,
+            minimum_outbound_peers: cfg.minimum_outbound_peers,
+            ideal_connections_lo: cfg.ideal_connections_lo,
+            ideal_connections_hi: cfg.ideal_connections_hi,
+            received_messages_rate_limits: received_messages_rate_limits.to_updatable(),
+        };
+        verify_updatable_config(&config, cfg.safe_set_size)?;
+        Ok(config)
+    }
 
-        if !(self.safe_set_size > self.minimum_outbound_peers) {
-            anyhow::bail!(
-                "safe_set_size({}) must be larger than minimum_outbound_peers({}).",
-                self.safe_set_size,
-                self.minimum_outbound_peers
-            );
-        }
+    pub fn verify(self) -> anyhow::Result<VerifiedConfig> {
+        verify_updatable_config(&self.updatable_config(), self.safe_set_size)?;
 
         if UPDATE_INTERVAL_LAST_TIME_RECEIVED_MESSAGE * 2 > self.peer_recent_time_window {
             anyhow::bail!(
@@ -538,14 +550,47 @@ impl NetworkConfig {
             .validate()
             .context("routing_table_update_rate_limit")?;
 
-        if let Err(err) = self.received_messages_rate_limits.validate() {
-            anyhow::bail!("One or more invalid rate limits: {err:?}");
-        }
-
         Ok(VerifiedConfig { node_id: self.node_id(), inner: self })
     }
 }
 
+/// Validates the part of the config that can be updated while the node is running.
+fn verify_updatable_config(
+    config: &UpdatableNetworkConfig,
+    safe_set_size: u32,
+) -> anyhow::Result<()> {
+    if !(config.ideal_connections_lo <= config.ideal_connections_hi) {
+        anyhow::bail!(
+            "Invalid ideal_connections values. lo({}) > hi({}).",
+            config.ideal_connections_lo,
+            config.ideal_connections_hi
+        );
+    }
+
+    if !(config.ideal_connections_hi <= config.max_num_peers) {
+        anyhow::bail!(
+            "max_num_peers({}) < ideal_connections_hi({}) which may lead to connection saturation and declining new connections.",
+            config.max_num_peers,
+            config.ideal_connections_hi
+        );
+    }
+
+    if !(safe_set_size > config.minimum_outbound_peers) {
+        anyhow::bail!(
+            "safe_set_size({}) must be larger than minimum_outbound_peers({}).",
+            safe_set_size,
+            config.minimum_outbound_peers
+        );
+    }
+
+    let received_messages_rate_limits =
+        messages_limits::Config::from_updatable(&config.received_messages_rate_limits);
+    if let Err(err) = received_messages_rate_limits.validate() {
+        anyhow::bail!("One or more invalid rate limits: {err:?}");
+    }
+    Ok(())
+}
+
 /// On every message from peer don't update `last_time_received_message`
 /// but wait some "small" timeout between updates to avoid a lot of messages between
 /// Peer and PeerManager.
@@ -736,4 +781,31 @@ mod test {
             .insert(BlockHeaders, SingleMessageConfig::new(1, 4.0, None));
         assert!(nc.verify().is_ok());
     }
+
+    #[test]
+    fn updatable_config_from_json() {
+        let cfg = crate::config_json::Config::default();
+        let updatable = config::NetworkConfig::updatable_config_from_json(&cfg).unwrap();
+        assert_eq!(updatable.max_num_peers, cfg.max_num_peers);
+        assert_eq!(updatable.ideal_connections_hi, cfg.ideal_connections_hi);
+        assert!(updatable.received_messages_rate_limits.contains_key("EpochSyncRequest"));
+
+        let mut cfg = crate::config_json::Config::default();
+        cfg.experimental.network_config_overrides.received_messages_rate_limits = Some(
+            serde_json::from_value(serde_json::json!({"rate_limits": {
+                "EpochSyncRequest": null,
+                "BlockHeaders": {"maximum_size": 1, "refill_rate": 4.0},
+            }}))
+            .unwrap(),
+        );
+        let updatable = config::NetworkConfig::updatable_config_from_json(&cfg).unwrap();
+        assert_eq!(
+            updatable.received_messages_rate_limits.keys().collect::<Vec<_>>(),
+            vec!["BlockHeaders"]
+        );
+
+        let mut cfg = crate::config_json::Config::default();
+        cfg.ideal_connections_hi = cfg.max_num_peers + 1;
+        assert!(config::NetworkConfig::updatable_config_from_json(&cfg).is_err());
+    }
 }
@@ -200,7 +200,7 @@ impl VersionedAccountData {
                 MAX_ACCOUNT_DATA_SIZE_BYTES
             );
         }
-        let signature = signer.sign_bytes(&payload);
+        let signature = signer.try_sign_bytes(&payload)?;
         Ok(SignedAccountData {
             account_data: self,
             payload: AccountKeySignedPayload { payload, signature },
@@ -271,19 +271,19 @@ pub struct OwnedAccount {
 impl OwnedAccount {
     /// Serializes OwnedAccount to proto and signs it using `signer`.
     /// Panics if OwnedAccount.account_key doesn't match signer.public_key(),
-    /// as this would likely be a bug.
-    pub fn sign(self, signer: &ValidatorSigner) -> SignedOwnedAccount {
+    /// as this would likely be a bug. Fails if a remote signer fails to sign.
+    pub fn sign(self, signer: &ValidatorSigner) -> std::io::Result<SignedOwnedAccount> {
         assert_eq!(
             self.account_key,
             signer.public_key(),
             "OwnedAccount.account_key doesn't match the signer's account_key"
         );
         let payload = proto::AccountKeyPayload::from(&self).write_to_bytes().unwrap();
-        let signature = signer.sign_bytes(&payload);
-        SignedOwnedAccount {
+        let signature = signer.try_sign_bytes(&payload)?;
+        Ok(SignedOwnedAccount {
             owned_account: self,
             payload: AccountKeySignedPayload { payload, signature },
-        }
+        })
     }
 }
 
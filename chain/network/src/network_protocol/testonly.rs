@@ -44,29 +44,32 @@ pub fn make_block(
     prev: &Block,
     chunks: Vec<ShardChunk>,
 ) -> Arc<Block> {
-    Arc::new(Block::produce(
-        version::PROTOCOL_VERSION,
-        prev.header(),
-        prev.header().height() + 5,
-        prev.header().block_ordinal() + 1,
-        chunks.iter().cloned().map(|c| c.take_header()).collect(),
-        vec![vec![]; chunks.len()],
-        EpochId::default(),
-        EpochId::default(),
-        None,
-        vec![],
-        Ratio::from_integer(0),
-        Balance::ZERO,
-        Balance::ZERO,
-        Some(Balance::ZERO),
-        signer,
-        CryptoHash::default(),
-        CryptoHash::default(),
-        clock,
-        None,
-        None,
-        None,
-    ))
+    Arc::new(
+        Block::produce(
+            version::PROTOCOL_VERSION,
+            prev.header(),
+            prev.header().height() + 5,
+            prev.header().block_ordinal() + 1,
+            chunks.iter().cloned().map(|c| c.take_header()).collect(),
+            vec![vec![]; chunks.len()],
+            EpochId::default(),
+            EpochId::default(),
+            None,
+            vec![],
+            Ratio::from_integer(0),
+            Balance::ZERO,
+            Balance::ZERO,
+            Some(Balance::ZERO),
+            signer,
+            CryptoHash::default(),
+            CryptoHash::default(),
+            clock,
+            None,
+            None,
+            None,
+        )
+        .unwrap(),
+    )
 }
 
 pub fn make_account_id<R: Rng>(rng: &mut R) -> AccountId {
@@ -103,7 +106,7 @@ pub fn make_peer_info<R: Rng>(rng: &mut R) -> PeerInfo {
 pub fn make_announce_account<R: Rng>(rng: &mut R) -> AnnounceAccount {
     let peer_id = make_peer_id(rng);
     let validator_signer = make_validator_signer(rng);
-    AnnounceAccount::new(&validator_signer, peer_id, EpochId::default())
+    AnnounceAccount::new(&validator_signer, peer_id, EpochId::default()).unwrap()
 }
 
 pub fn make_partial_edge<R: Rng>(rng: &mut R) -> PartialEdgeInfo {
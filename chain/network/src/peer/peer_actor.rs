@@ -328,7 +328,9 @@ impl PeerActor {
             account_id: network_state.config.validator.account_id(),
         };
         let received_messages_rate_limits = messages_limits::RateLimits::from_config(
-            &network_state.config.received_messages_rate_limits,
+            &messages_limits::Config::from_updatable(
+                &network_state.updatable_config.get().received_messages_rate_limits,
+            ),
             clock.now(),
         );
         // recv is the HandshakeSignal returned by this spawn_inner() call.
@@ -455,13 +457,19 @@ impl PeerActor {
                 archival: self.network_state.config.archive,
             },
             partial_edge_info: spec.partial_edge_info,
-            owned_account: self.network_state.config.validator.signer.get().map(|signer| {
+            // Without the proof of owning the account key the peer just won't treat us as a
+            // validator, e.g. won't accept a TIER1 connection, so we can still connect.
+            owned_account: self.network_state.config.validator.signer.get().and_then(|signer| {
                 OwnedAccount {
                     account_key: signer.public_key(),
                     peer_id: self.network_state.config.node_id(),
                     timestamp: self.clock.now_utc(),
                 }
                 .sign(&signer)
+                .inspect_err(|err| {
+                    tracing::error!(target: "network", ?err, "failed to sign owned account, sending handshake without it");
+                })
+                .ok()
             }),
         };
         let msg = match spec.tier {
@@ -44,6 +44,7 @@ use arc_swap::ArcSwap;
 use near_async::futures::{FutureSpawner, FutureSpawnerExt};
 use near_async::messaging::{CanSend, CanSendAsync, Sender};
 use near_async::{ActorSystem, new_owned_future_spawner, time};
+use near_chain_configs::{MutableConfigValue, UpdatableNetworkConfig};
 use near_o11y::span_wrapped_msg::SpanWrappedMessageExt;
 use near_primitives::genesis::GenesisId;
 use near_primitives::hash::CryptoHash;
@@ -101,6 +102,10 @@ pub(crate) struct NetworkState {
     ops_spawner: Box<dyn FutureSpawner>,
     /// PeerManager config.
     pub config: config::VerifiedConfig,
+    /// Part of the config which can be updated while the node is running.
+    /// It takes precedence over the corresponding fields of `config`. Kept behind an `Arc` so
+    /// that reading it on hot paths doesn't copy the rate limits.
+    pub updatable_config: MutableConfigValue<Arc<UpdatableNetworkConfig>>,
     /// When network state has been constructed.
     pub created_at: time::Instant,
     /// GenesisId of the chain.
@@ -243,6 +248,10 @@ impl NetworkState {
             #[cfg(feature = "distance_vector_routing")]
             update_routes_demux: demux::Demux::new(config.routing_table_update_rate_limit),
             set_chain_info_mutex: Mutex::new(()),
+            updatable_config: MutableConfigValue::new(
+                Arc::new(config.updatable_config()),
+                "network_updatable_config",
+            ),
             config,
             created_at: clock.now(),
             tier1_advertise_proxies_mutex: tokio::sync::Mutex::new(()),
@@ -280,6 +289,10 @@ impl NetworkState {
         peer_id: &PeerId,
         ban_reason: ReasonForBan,
     ) {
+        near_o11y::event_log::emit(near_o11y::event_log::NodeEvent::PeerBanned {
+            peer_id: peer_id.to_string(),
+            reason: format!("{ban_reason:?}"),
+        });
         let tier2 = self.tier2.load();
         if let Some(peer) = tier2.ready.get(peer_id) {
             peer.stop(Some(ban_reason));
@@ -305,7 +318,8 @@ impl NetworkState {
     fn is_inbound_allowed(&self, peer_info: &PeerInfo) -> bool {
         // Check if we have spare inbound connections capacity.
         let tier2 = self.tier2.load();
-        if tier2.ready.len() + tier2.outbound_handshakes.len() < self.config.max_num_peers as usize
+        if tier2.ready.len() + tier2.outbound_handshakes.len()
+            < self.updatable_config.get().max_num_peers as usize
             && !self.config.inbound_disabled
         {
             return true;
@@ -369,7 +383,7 @@ impl NetworkState {
                             let tier2 = this.tier2.load();
                             tracing::debug!(target: "network",
                                 tier2 = tier2.ready.len(), outgoing_peers = tier2.outbound_handshakes.len(),
-                                max_num_peers = this.config.max_num_peers,
+                                max_num_peers = this.updatable_config.get().max_num_peers,
                                 "dropping handshake (network at max capacity)"
                             );
                             return Err(RegisterPeerError::ConnectionLimitExceeded);
@@ -429,10 +429,11 @@ impl PeerManagerActor {
             tier2.ready.values().filter(|peer| peer.peer_type == PeerType::Outbound).count()
                 + tier2.outbound_handshakes.len();
 
-        (total_connections < self.state.config.ideal_connections_lo as usize
-            || (total_connections < self.state.config.max_num_peers as usize
+        let updatable_config = self.state.updatable_config.get();
+        (total_connections < updatable_config.ideal_connections_lo as usize
+            || (total_connections < updatable_config.max_num_peers as usize
                 && potential_outbound_connections
-                    < self.state.config.minimum_outbound_peers as usize))
+                    < updatable_config.minimum_outbound_peers as usize))
             && !self.state.config.outbound_disabled
     }
 
@@ -515,6 +516,8 @@ impl PeerManagerActor {
                 .collect()
         };
 
+        let updatable_config = self.state.updatable_config.get();
+
         // Build safe set
         let mut safe_set = HashSet::new();
 
@@ -523,14 +526,14 @@ impl PeerManagerActor {
         safe_set.extend(whitelisted_peers);
 
         // If there is not enough non-whitelisted peers, return without disconnecting anyone.
-        if tier2.ready.len() - safe_set.len() <= self.state.config.ideal_connections_hi as usize {
+        if tier2.ready.len() - safe_set.len() <= updatable_config.ideal_connections_hi as usize {
             return;
         }
 
         // If there is not enough outbound peers, add them to the safe set.
         let outbound_peers = filter_peers(&|p| p.peer_type == PeerType::Outbound);
         if outbound_peers.len() + tier2.outbound_handshakes.len()
-            <= self.state.config.minimum_outbound_peers as usize
+            <= updatable_config.minimum_outbound_peers as usize
         {
             safe_set.extend(outbound_peers);
         }
@@ -573,7 +576,7 @@ impl PeerManagerActor {
         if let Some(p) = candidates.choose(&mut rand::thread_rng()) {
             tracing::debug!(target: "network", id = ?p.peer_info.id,
                 tier2_len = tier2.ready.len(),
-                ideal_connections_hi = self.state.config.ideal_connections_hi,
+                ideal_connections_hi = updatable_config.ideal_connections_hi,
                 "stopping active connection"
             );
             p.stop(None);
@@ -732,7 +735,7 @@ impl PeerManagerActor {
             connected_peers: tier2.ready.values().map(connected_peer).collect(),
             tier1_connections: tier1.ready.values().map(connected_peer).collect(),
             num_connected_peers: tier2.ready.len(),
-            peer_max_count: self.state.config.max_num_peers,
+            peer_max_count: self.state.updatable_config.get().max_num_peers,
             highest_height_peers: self.highest_height_peers(),
             sent_bytes_per_sec: tier2
                 .ready
@@ -1330,6 +1333,10 @@ impl PeerManagerActor {
                 });
                 PeerManagerMessageResponse::AdvertiseTier1Proxies
             }
+            PeerManagerMessageRequest::UpdateNetworkConfig(config) => {
+                self.state.updatable_config.update(Arc::new(config));
+                PeerManagerMessageResponse::UpdateNetworkConfig
+            }
             PeerManagerMessageRequest::OutboundTcpConnect(stream) => {
                 let peer_addr = stream.peer_addr;
                 if let Err(err) = PeerActor::spawn(
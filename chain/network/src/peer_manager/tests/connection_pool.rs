@@ -172,7 +172,8 @@ async fn owned_account_mismatch() {
                     peer_id: data::make_peer_id(rng),
                     timestamp: clock.now_utc(),
                 }
-                .sign(&signer),
+                .sign(&signer)
+                .unwrap(),
             ),
         }))
         .await;
@@ -292,7 +293,8 @@ async fn invalid_edge() {
                         peer_id: cfg.node_id(),
                         timestamp: clock.now_utc(),
                     }
-                    .sign(&signer),
+                    .sign(&signer)
+                    .unwrap(),
                 ),
             };
             let handshake = match tier {
@@ -5,7 +5,9 @@ use super::token_bucket::{TokenBucket, TokenBucketError};
 use crate::network_protocol::{PeerMessage, T1MessageBody, T2MessageBody, TieredMessageBody};
 use enum_map::{EnumMap, enum_map};
 use near_async::time::Instant;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+pub use near_chain_configs::SingleMessageConfig;
 
 /// Object responsible to manage the rate limits of all network messages
 /// for a single connection/peer.
@@ -56,24 +58,8 @@ impl RateLimits {
     }
 }
 
-/// Rate limit configuration for a single network message.
-#[derive(Clone, serde::Serialize, serde::Deserialize, Debug)]
-#[cfg_attr(test, derive(PartialEq))]
-pub struct SingleMessageConfig {
-    pub maximum_size: u32,
-    pub refill_rate: f32,
-    /// Optional initial size. Defaults to `maximum_size` if absent.
-    pub initial_size: Option<u32>,
-}
-
-impl SingleMessageConfig {
-    pub fn new(maximum_size: u32, refill_rate: f32, initial_size: Option<u32>) -> Self {
-        Self { maximum_size, refill_rate, initial_size }
-    }
-}
-
 /// Network messages rate limits configuration.
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Debug, PartialEq)]
 pub struct Config {
     pub rate_limits: HashMap<RateLimitedPeerMessageKey, SingleMessageConfig>,
 }
@@ -117,6 +103,31 @@ impl Config {
         config
     }
 
+    /// Builds the configuration from its updatable representation, see
+    /// [near_chain_configs::UpdatableNetworkConfig]. Unknown message types are ignored.
+    pub fn from_updatable(rate_limits: &BTreeMap<String, SingleMessageConfig>) -> Self {
+        let mut config = Self::default();
+        for (key, message_config) in rate_limits {
+            match key.parse::<RateLimitedPeerMessageKey>() {
+                Ok(key) => {
+                    config.rate_limits.insert(key, message_config.clone());
+                }
+                Err(err) => {
+                    tracing::warn!(target: "network", %key, ?err, "ignoring rate limit of unknown message type")
+                }
+            }
+        }
+        config
+    }
+
+    /// Returns the updatable representation of this configuration, keyed by message type name.
+    pub fn to_updatable(&self) -> BTreeMap<String, SingleMessageConfig> {
+        self.rate_limits
+            .iter()
+            .map(|(key, message_config)| (key.to_string(), message_config.clone()))
+            .collect()
+    }
+
     /// Applies rate limits configuration overrides to `self`. In practice, merges the two configurations
     /// giving preference to the values defined by the `overrides` parameter.
     pub fn apply_overrides(&mut self, overrides: OverrideConfig) {
@@ -136,6 +147,7 @@ impl Config {
     Copy,
     enum_map::Enum,
     strum::Display,
+    strum::EnumString,
     Debug,
     PartialEq,
     Eq,
@@ -418,6 +430,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn updatable_representation() {
+        use RateLimitedPeerMessageKey::*;
+
+        let mut config = Config::standard_preset();
+        config.rate_limits.insert(Block, SingleMessageConfig::new(4, 1.0, Some(2)));
+        config.rate_limits.insert(BlockHeaders, SingleMessageConfig::new(1, 0.5, None));
+
+        let updatable = config.to_updatable();
+        assert_eq!(updatable.len(), 3);
+        assert_eq!(updatable.get("Block"), Some(&SingleMessageConfig::new(4, 1.0, Some(2))));
+        assert_eq!(Config::from_updatable(&updatable), config);
+
+        // Unknown message types are ignored.
+        let mut updatable = updatable;
+        updatable.insert("NotAMessage".to_string(), SingleMessageConfig::new(1, 1.0, None));
+        assert_eq!(Config::from_updatable(&updatable), config);
+    }
+
     #[test]
     fn override_config_deserialization() {
         use RateLimitedPeerMessageKey::*;
@@ -6,7 +6,7 @@ use crate::network_protocol::{
 use crate::tcp;
 use crate::types::{
     Edge, PartialEncodedChunkRequestMsg, PartialEncodedChunkResponseMsg, PeerInfo,
-    StateResponseInfo,
+    StateHeaderRequest, StatePartRequest, StateResponseInfo,
 };
 use bytes::BytesMut;
 use bytes::buf::{Buf, BufMut};
@@ -49,6 +49,8 @@ pub enum RoutedMessage {
     Pong { nonce: u64, source: PeerId },
     PartialEncodedChunkRequest(PartialEncodedChunkRequestMsg),
     PartialEncodedChunkResponse(PartialEncodedChunkResponseMsg),
+    StateHeaderRequest(StateHeaderRequest),
+    StatePartRequest(StatePartRequest),
 }
 
 impl fmt::Display for RoutedMessage {
@@ -70,6 +72,12 @@ impl fmt::Debug for RoutedMessage {
                 r.parts.iter().map(|p| p.part_ord).collect::<Vec<_>>(),
                 r.receipts.len()
             ),
+            Self::StateHeaderRequest(r) => {
+                write!(f, "StateHeaderRequest({}, {})", r.shard_id, r.sync_hash)
+            }
+            Self::StatePartRequest(r) => {
+                write!(f, "StatePartRequest({}, {}, {})", r.shard_id, r.sync_hash, r.part_id)
+            }
         }
     }
 }
@@ -419,6 +427,10 @@ impl Connection {
             RoutedMessage::PartialEncodedChunkResponse(response) => {
                 T2MessageBody::PartialEncodedChunkResponse(response)
             }
+            RoutedMessage::StateHeaderRequest(request) => {
+                T2MessageBody::StateHeaderRequest(request)
+            }
+            RoutedMessage::StatePartRequest(request) => T2MessageBody::StatePartRequest(request),
         }
         .into();
         let msg = RawRoutedMessage { target: PeerIdOrHash::PeerId(target), body }.sign(
@@ -464,6 +476,12 @@ impl Connection {
                 T2MessageBody::PartialEncodedChunkResponse(response) => {
                     Some(RoutedMessage::PartialEncodedChunkResponse(response.clone()))
                 }
+                T2MessageBody::StateHeaderRequest(request) => {
+                    Some(RoutedMessage::StateHeaderRequest(request.clone()))
+                }
+                T2MessageBody::StatePartRequest(request) => {
+                    Some(RoutedMessage::StatePartRequest(request.clone()))
+                }
                 _ => None,
             },
         }
@@ -510,6 +528,20 @@ impl Connection {
                 PeerMessage::BlockHeaders(headers) => {
                     return Ok((Message::Direct(DirectMessage::BlockHeaders(headers)), timestamp));
                 }
+                PeerMessage::StateRequestHeader(shard_id, sync_hash) => {
+                    return Ok((
+                        Message::Direct(DirectMessage::StateRequestHeader(shard_id, sync_hash)),
+                        timestamp,
+                    ));
+                }
+                PeerMessage::StateRequestPart(shard_id, sync_hash, part_id) => {
+                    return Ok((
+                        Message::Direct(DirectMessage::StateRequestPart(
+                            shard_id, sync_hash, part_id,
+                        )),
+                        timestamp,
+                    ));
+                }
                 PeerMessage::VersionedStateResponse(state_response) => {
                     return Ok((
                         Message::Direct(DirectMessage::VersionedStateResponse(Box::new(
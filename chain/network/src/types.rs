@@ -7,8 +7,9 @@ pub use crate::network_protocol::{
 /// Exported types, which are part of network protocol.
 pub use crate::network_protocol::{
     Edge, PartialEdgeInfo, PartialEncodedChunkForwardMsg, PartialEncodedChunkRequestMsg,
-    PartialEncodedChunkResponseMsg, PeerChainInfoV2, PeerInfo, SnapshotHostInfo, StateResponseInfo,
-    StateResponseInfoV1, StateResponseInfoV2,
+    PartialEncodedChunkResponseMsg, PeerChainInfoV2, PeerInfo, SnapshotHostInfo,
+    StateHeaderRequest, StatePartRequest, StateResponseInfo, StateResponseInfoV1,
+    StateResponseInfoV2,
 };
 use crate::routing::routing_table_view::RoutingTableInfo;
 use crate::spice_data_distribution::SpicePartialDataRequest;
@@ -184,6 +185,9 @@ pub enum PeerManagerMessageRequest {
     /// The effect would be accounts data known by this node broadcasted to other tier1 nodes.
     /// That includes info about validator signer of this node.
     AdvertiseTier1Proxies,
+    /// Request PeerManager to apply the part of the network config which was updated
+    /// while the node is running.
+    UpdateNetworkConfig(near_chain_configs::UpdatableNetworkConfig),
     /// Request PeerManager to connect to the given peer.
     /// Used in tests and internally by PeerManager.
     /// TODO: replace it with AsyncContext::spawn/run_later for internal use.
@@ -216,6 +220,7 @@ impl PeerManagerMessageRequest {
 pub enum PeerManagerMessageResponse {
     NetworkResponses(NetworkResponses),
     AdvertiseTier1Proxies,
+    UpdateNetworkConfig,
     /// TEST-ONLY
     OutboundTcpConnect,
     FetchRoutingTable(RoutingTableInfo),
@@ -169,6 +169,15 @@ impl TransactionPool {
         self.transaction_pool_size_metric.set(self.total_transaction_size as i64);
     }
 
+    /// Removes all transactions from the pool.
+    pub fn clear(&mut self) {
+        self.transactions.clear();
+        self.unique_transactions.clear();
+        self.total_transaction_size = 0;
+        self.transaction_pool_count_metric.set(0);
+        self.transaction_pool_size_metric.set(0);
+    }
+
     /// Returns the number of unique transactions in the pool.
     pub fn len(&self) -> usize {
         self.unique_transactions.len()
@@ -460,6 +469,21 @@ mod tests {
         assert_eq!(nonces, vec![28, 29, 30, 31]);
     }
 
+    #[test]
+    fn test_clear() {
+        let transactions = generate_transactions("alice.near", "alice.near", 1, 10);
+        let (_, mut pool) = process_txs_to_nonces(transactions.clone(), 0);
+        pool.clear();
+        assert_eq!(pool.len(), 0);
+        assert_eq!(pool.transaction_size(), 0);
+        assert!(prepare_transactions(&mut pool, 10).is_empty());
+        // Cleared transactions can be inserted again.
+        for validated_tx in transactions {
+            assert_eq!(pool.insert_transaction(validated_tx), InsertTransactionResult::Success);
+        }
+        assert_eq!(pool.len(), 10);
+    }
+
     #[test]
     fn test_remove_transactions() {
         let n = 100;
@@ -150,6 +150,47 @@ pub struct GCConfig {
     #[serde(with = "near_time::serde_duration_as_std")]
     #[cfg_attr(feature = "schemars", schemars(with = "DurationAsStdSchemaProvider"))]
     pub gc_step_period: Duration,
+
+    /// Accounts whose transactions, receipts, execution outcomes and state
+    /// changes are kept longer than the rest of the data.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retained_accounts: Option<RetainedAccountsConfig>,
+}
+
+/// Configuration for keeping the data touching some accounts beyond
+/// `gc_num_epochs_to_keep`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RetainedAccountsConfig {
+    /// Transactions signed by or sent to these accounts, receipts sent by or
+    /// to them, the execution outcomes of these transactions and receipts and
+    /// of all the receipts they cause, and the state changes of these accounts
+    /// are retained.
+    pub accounts: Vec<AccountId>,
+    /// Number of epochs for which the data is kept in addition to
+    /// `gc_num_epochs_to_keep`. The data is kept indefinitely if not set.
+    #[serde(default)]
+    pub num_extra_epochs_to_keep: Option<u64>,
+}
+
+impl RetainedAccountsConfig {
+    /// Whether the data of all the given accounts is retained.
+    pub fn covers<'a>(&self, mut account_ids: impl Iterator<Item = &'a AccountId>) -> bool {
+        account_ids.all(|account_id| self.accounts.contains(account_id))
+    }
+
+    /// Height below which the retained data is garbage collected as well, given
+    /// the stop height of the regular garbage collection. `None` if the data is
+    /// kept indefinitely.
+    pub fn stop_height(
+        &self,
+        gc_stop_height: BlockHeight,
+        epoch_length: BlockHeightDelta,
+    ) -> Option<BlockHeight> {
+        self.num_extra_epochs_to_keep.map(|num_epochs| {
+            gc_stop_height.saturating_sub(num_epochs.saturating_mul(epoch_length))
+        })
+    }
 }
 
 impl Default for GCConfig {
@@ -162,6 +203,7 @@ impl Default for GCConfig {
             gc_fork_clean_step: 100,
             gc_num_epochs_to_keep: DEFAULT_GC_NUM_EPOCHS_TO_KEEP,
             gc_step_period: Duration::milliseconds(500),
+            retained_accounts: None,
         }
     }
 }
@@ -264,6 +306,19 @@ impl Default for CloudArchivalWriterConfig {
     }
 }
 
+/// Configures the cloud archive that block sync falls back to when no peer can
+/// provide the blocks it needs, e.g. because archival peers are scarce.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct BlockSyncCloudArchiveConfig {
+    /// The storage location of the cloud archive, as written by a cloud
+    /// archival writer.
+    pub location: ExternalStorageLocation,
+    /// Location of a json file with credentials allowing access to the bucket.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credentials_file: Option<PathBuf>,
+}
+
 /// A handle that allows the main process to interrupt other.
 #[derive(Clone, Debug)]
 pub struct InterruptHandle {
@@ -416,6 +471,20 @@ impl StateSyncConfig {
             ..Default::default()
         }
     }
+
+    /// Reads state parts only from the given directory, without asking peers first.
+    pub fn filesystem(root_dir: PathBuf) -> Self {
+        Self {
+            sync: SyncConfig::ExternalStorage(ExternalStorageConfig {
+                location: ExternalStorageLocation::Filesystem { root_dir },
+                num_concurrent_requests: DEFAULT_STATE_SYNC_NUM_CONCURRENT_REQUESTS_EXTERNAL,
+                num_concurrent_requests_during_catchup:
+                    DEFAULT_STATE_SYNC_NUM_CONCURRENT_REQUESTS_ON_CATCHUP_EXTERNAL,
+                external_storage_fallback_threshold: 0,
+            }),
+            ..Default::default()
+        }
+    }
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
@@ -444,6 +513,11 @@ pub struct EpochSyncConfig {
     #[serde(with = "near_time::serde_duration_as_std")]
     #[cfg_attr(feature = "schemars", schemars(with = "DurationAsStdSchemaProvider"))]
     pub timeout_for_epoch_sync: Duration,
+    /// If set, a node started from genesis bootstraps from the epoch sync proof in this file,
+    /// as written by `neard epoch-sync export`, instead of requesting a proof from peers.
+    /// Headers, state and blocks after the proof are still fetched from peers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof_file: Option<PathBuf>,
 }
 
 impl Default for EpochSyncConfig {
@@ -456,6 +530,7 @@ impl Default for EpochSyncConfig {
             // (Epoch sync should not be picking a target epoch more than 2 epochs old.)
             epoch_sync_horizon: 216000,
             timeout_for_epoch_sync: Duration::seconds(60),
+            proof_file: None,
         }
     }
 }
@@ -851,7 +926,7 @@ pub struct ClientConfig {
     pub resharding_config: MutableConfigValue<ReshardingConfig>,
     /// If the node is not a chunk producer within that many blocks, then route
     /// to upcoming chunk producers.
-    pub tx_routing_height_horizon: BlockHeightDelta,
+    pub tx_routing_height_horizon: MutableConfigValue<BlockHeightDelta>,
     /// If true, the node won't forward transactions to next the chunk producers.
     pub disable_tx_routing: bool,
     /// Limit the time of adding transactions to a chunk.
@@ -865,6 +940,9 @@ pub struct ClientConfig {
     /// Nodes not participating will still function fine, but possibly with higher
     /// latency due to the need of requesting chunks over the peer-to-peer network.
     pub chunk_distribution_network: Option<ChunkDistributionNetworkConfig>,
+    /// If set, block sync fetches blocks and their chunks from this cloud archive
+    /// when peers can't provide them. Headers are still only fetched from peers.
+    pub block_sync_cloud_archive: Option<BlockSyncCloudArchiveConfig>,
     /// OrphanStateWitnessPool keeps instances of ChunkStateWitness which can't be processed
     /// because the previous block isn't available. The witnesses wait in the pool until the
     /// required block appears. This variable controls how many witnesses can be stored in the pool.
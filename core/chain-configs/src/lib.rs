@@ -10,11 +10,12 @@ pub mod test_utils;
 mod updatable_config;
 
 pub use client_config::{
-    ChunkDistributionNetworkConfig, ChunkDistributionUris, ClientConfig, CloudArchivalWriterConfig,
-    DEFAULT_GC_NUM_EPOCHS_TO_KEEP, DEFAULT_STATE_PARTS_COMPRESSION_LEVEL, DumpConfig,
-    EpochSyncConfig, ExternalStorageConfig, ExternalStorageLocation, GCConfig, InterruptHandle,
-    LogSummaryStyle, MIN_GC_NUM_EPOCHS_TO_KEEP, ProtocolVersionCheckConfig, ReshardingConfig,
-    ReshardingHandle, StateSyncConfig, SyncConcurrency, SyncConfig, TrackedShardsConfig,
+    BlockSyncCloudArchiveConfig, ChunkDistributionNetworkConfig, ChunkDistributionUris,
+    ClientConfig, CloudArchivalWriterConfig, DEFAULT_GC_NUM_EPOCHS_TO_KEEP,
+    DEFAULT_STATE_PARTS_COMPRESSION_LEVEL, DumpConfig, EpochSyncConfig, ExternalStorageConfig,
+    ExternalStorageLocation, GCConfig, InterruptHandle, LogSummaryStyle, MIN_GC_NUM_EPOCHS_TO_KEEP,
+    ProtocolVersionCheckConfig, ReshardingConfig, ReshardingHandle, RetainedAccountsConfig,
+    StateSyncConfig, SyncConcurrency, SyncConfig, TrackedShardsConfig,
     default_archival_writer_polling_interval, default_chunk_validation_threads,
     default_chunk_wait_mult, default_chunks_cache_height_horizon,
     default_enable_early_prepare_transactions, default_enable_multiline_logging,
@@ -38,7 +39,10 @@ pub use genesis_config::{
 };
 use near_primitives::types::{Balance, BlockHeightDelta, Gas, NumBlocks, NumSeats};
 use num_rational::Rational32;
-pub use updatable_config::{MutableConfigValue, MutableValidatorSigner, UpdatableClientConfig};
+pub use updatable_config::{
+    MutableConfigValue, MutableValidatorSigner, SingleMessageConfig, UpdatableClientConfig,
+    UpdatableNetworkConfig, UpdatableRpcConfig,
+};
 
 pub const GENESIS_CONFIG_FILENAME: &str = "genesis.json";
 
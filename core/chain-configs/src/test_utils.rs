@@ -332,12 +332,13 @@ impl ClientConfig {
                 ReshardingConfig::default(),
                 "resharding_config",
             ),
-            tx_routing_height_horizon: 4,
+            tx_routing_height_horizon: MutableConfigValue::new(4, "tx_routing_height_horizon"),
             produce_chunk_add_transactions_time_limit: MutableConfigValue::new(
                 default_produce_chunk_add_transactions_time_limit(),
                 "produce_chunk_add_transactions_time_limit",
             ),
             chunk_distribution_network: None,
+            block_sync_cloud_archive: None,
             orphan_state_witness_pool_size: default_orphan_state_witness_pool_size(),
             orphan_state_witness_max_size: default_orphan_state_witness_max_size(),
             save_latest_witnesses: false,
@@ -1,9 +1,10 @@
-use near_primitives::types::BlockHeight;
+use near_primitives::types::{BlockHeight, BlockHeightDelta};
 use near_primitives::validator_signer::ValidatorSigner;
 #[cfg(feature = "metrics")]
 use near_time::Clock;
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize, Serializer};
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::sync::Arc;
 use time::Duration;
@@ -11,6 +12,7 @@ use time::Duration;
 use time::OffsetDateTime as Utc;
 
 use crate::ReshardingConfig;
+use crate::client_config::default_tx_routing_height_horizon;
 
 /// A wrapper for a config value that can be updated while the node is running.
 /// When initializing sub-objects (e.g. `ShardsManager`), please make sure to
@@ -120,6 +122,51 @@ pub struct UpdatableClientConfig {
     #[serde(default)]
     #[serde(with = "near_time::serde_opt_duration_as_std")]
     pub produce_chunk_add_transactions_time_limit: Option<Duration>,
+
+    /// If the node is not a chunk producer within that many blocks, then route
+    /// to upcoming chunk producers.
+    #[serde(default = "default_tx_routing_height_horizon")]
+    pub tx_routing_height_horizon: BlockHeightDelta,
+}
+
+/// Rate limit configuration for a single type of network message.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct SingleMessageConfig {
+    pub maximum_size: u32,
+    pub refill_rate: f32,
+    /// Optional initial size. Defaults to `maximum_size` if absent.
+    pub initial_size: Option<u32>,
+}
+
+impl SingleMessageConfig {
+    pub fn new(maximum_size: u32, refill_rate: f32, initial_size: Option<u32>) -> Self {
+        Self { maximum_size, refill_rate, initial_size }
+    }
+}
+
+#[derive(Default, Clone, Serialize, Deserialize, Debug, PartialEq)]
+/// A subset of the network config that can be updated while the node is running.
+pub struct UpdatableNetworkConfig {
+    /// Maximum number of active peers. Hard limit.
+    pub max_num_peers: u32,
+    /// Minimum outbound connections a peer should have to avoid eclipse attacks.
+    pub minimum_outbound_peers: u32,
+    /// Lower bound of the ideal number of connections.
+    pub ideal_connections_lo: u32,
+    /// Upper bound of the ideal number of connections.
+    pub ideal_connections_hi: u32,
+    /// Rate limits of the messages received from peers, keyed by the message type.
+    /// Only connections established after the update are affected.
+    pub received_messages_rate_limits: BTreeMap<String, SingleMessageConfig>,
+}
+
+#[derive(Default, Clone, Serialize, Deserialize, Debug, PartialEq)]
+/// A subset of the JSON-RPC config that can be updated while the node is running.
+pub struct UpdatableRpcConfig {
+    /// Origins allowed to make cross-origin requests, `["*"]` allows any origin.
+    pub cors_allowed_origins: Vec<String>,
+    /// Maximum byte size of the json payload.
+    pub json_payload_max_size: usize,
 }
 
 pub type MutableValidatorSigner = MutableConfigValue<Option<Arc<ValidatorSigner>>>;
@@ -2,7 +2,7 @@ use crate::{PublicKey, SecretKey};
 use near_account_id::AccountId;
 use std::fs::File;
 use std::io;
-use std::io::{Read, Write};
+use std::io::Write;
 use std::path::Path;
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -17,8 +17,24 @@ pub struct KeyFile {
 }
 
 impl KeyFile {
+    /// Writes the key file, encrypted if a key file passphrase is set for this process.
     pub fn write_to_file(&self, path: &Path) -> io::Result<()> {
-        let data = serde_json::to_string_pretty(self)?;
+        let data = match crate::key_file_encryption::key_file_passphrase() {
+            #[cfg(feature = "rand")]
+            Some(passphrase) => {
+                let mut key_file = serde_json::to_value(self)?;
+                crate::key_file_encryption::encrypt(&mut key_file, passphrase)?;
+                serde_json::to_string_pretty(&key_file)?
+            }
+            #[cfg(not(feature = "rand"))]
+            Some(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "key file passphrase is set, but encryption requires the `rand` feature",
+                ));
+            }
+            None => serde_json::to_string_pretty(self)?,
+        };
         let mut file = Self::create(path)?;
         file.write_all(data.as_bytes())
     }
@@ -34,14 +50,9 @@ impl KeyFile {
         std::fs::File::create(path)
     }
 
+    /// Reads the key file, decrypting it if it is encrypted.
     pub fn from_file(path: &Path) -> io::Result<Self> {
-        let mut file = File::open(path)?;
-        let mut json_config_str = String::new();
-        file.read_to_string(&mut json_config_str)?;
-        let json_str_without_comments: String =
-            near_config_utils::strip_comments_from_json_str(&json_config_str)?;
-
-        Ok(serde_json::from_str(&json_str_without_comments)?)
+        Ok(serde_json::from_value(crate::key_file_encryption::read_key_file(path)?)?)
     }
 }
 
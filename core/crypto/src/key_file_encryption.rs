@@ -0,0 +1,275 @@
+//! Passphrase-based encryption of key files.
+//!
+//! An encrypted key file keeps `account_id` and `public_key` in the clear so that it can still
+//! be identified, and replaces `secret_key` with an `encrypted_secret_key` object. The
+//! encryption key is derived from the passphrase with scrypt, and the secret key is sealed with
+//! XChaCha20-Poly1305 using the public key as associated data, so that the two cannot be
+//! mixed up between files.
+//!
+//! A process that wants to read or write encrypted key files sets the passphrase once with
+//! [`set_key_file_passphrase`]. From then on [`crate::KeyFile`] transparently decrypts
+//! encrypted files when reading and encrypts new files when writing.
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use std::io;
+use std::path::Path;
+use std::sync::OnceLock;
+use zeroize::Zeroizing;
+
+const ENCRYPTED_SECRET_KEY_FIELD: &str = "encrypted_secret_key";
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// scrypt parameters for newly encrypted files: 64 MiB of memory, well under a second.
+const SCRYPT_LOG_N: u8 = 16;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+/// Upper bounds on the scrypt parameters accepted from a key file, so that a crafted file
+/// cannot make the node allocate or compute without limit: at most 1 GiB of memory.
+const MAX_SCRYPT_LOG_N: u8 = 20;
+const MAX_SCRYPT_R: u32 = 16;
+const MAX_SCRYPT_P: u32 = 16;
+const MAX_SCRYPT_MEMORY: u64 = 1 << 30;
+
+static KEY_FILE_PASSPHRASE: OnceLock<Passphrase> = OnceLock::new();
+
+/// Passphrase protecting key files. The memory is wiped on drop.
+#[derive(Clone)]
+pub struct Passphrase(Zeroizing<String>);
+
+impl Passphrase {
+    pub fn new(passphrase: String) -> Self {
+        Self(Zeroizing::new(passphrase))
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl std::fmt::Debug for Passphrase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Passphrase(..)")
+    }
+}
+
+/// Sets the passphrase used for key files read or written by this process. Can only be set
+/// once; returns the passphrase back if it was already set.
+pub fn set_key_file_passphrase(passphrase: Passphrase) -> Result<(), Passphrase> {
+    KEY_FILE_PASSPHRASE.set(passphrase)
+}
+
+pub fn key_file_passphrase() -> Option<&'static Passphrase> {
+    KEY_FILE_PASSPHRASE.get()
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(tag = "name", rename_all = "lowercase")]
+enum Kdf {
+    Scrypt { log_n: u8, r: u32, p: u32, salt: String },
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+enum Cipher {
+    #[serde(rename = "xchacha20poly1305")]
+    XChaCha20Poly1305,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct EncryptedSecretKey {
+    kdf: Kdf,
+    cipher: Cipher,
+    nonce: String,
+    ciphertext: String,
+}
+
+fn invalid_data(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+fn derive_key(passphrase: &Passphrase, kdf: &Kdf) -> io::Result<Zeroizing<[u8; KEY_LEN]>> {
+    let Kdf::Scrypt { log_n, r, p, salt } = kdf;
+    let salt = hex::decode(salt).map_err(|err| invalid_data(err.to_string()))?;
+    // scrypt needs 128 * r * N bytes of memory.
+    let memory = 128u64.saturating_mul(u64::from(*r)).checked_shl(u32::from(*log_n));
+    if *log_n > MAX_SCRYPT_LOG_N
+        || *r > MAX_SCRYPT_R
+        || *p > MAX_SCRYPT_P
+        || memory.is_none_or(|memory| memory > MAX_SCRYPT_MEMORY)
+    {
+        return Err(invalid_data(format!(
+            "scrypt parameters log_n={log_n}, r={r}, p={p} exceed the supported limits"
+        )));
+    }
+    let params = scrypt::Params::new(*log_n, *r, *p, KEY_LEN)
+        .map_err(|err| invalid_data(format!("invalid scrypt parameters: {err}")))?;
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    scrypt::scrypt(passphrase.as_bytes(), &salt, &params, &mut *key)
+        .map_err(|err| invalid_data(err.to_string()))?;
+    Ok(key)
+}
+
+/// Associated data binding the ciphertext to the public key stored next to it.
+fn associated_data(key_file: &serde_json::Map<String, serde_json::Value>) -> io::Result<Vec<u8>> {
+    match key_file.get("public_key") {
+        Some(serde_json::Value::String(public_key)) => Ok(public_key.as_bytes().to_vec()),
+        _ => Err(invalid_data("key file has no public_key")),
+    }
+}
+
+fn as_object(
+    key_file: &mut serde_json::Value,
+) -> io::Result<&mut serde_json::Map<String, serde_json::Value>> {
+    key_file.as_object_mut().ok_or_else(|| invalid_data("key file is not a JSON object"))
+}
+
+pub fn is_encrypted(key_file: &serde_json::Value) -> bool {
+    key_file.get(ENCRYPTED_SECRET_KEY_FIELD).is_some()
+}
+
+/// Replaces the secret key of a parsed key file with its encrypted form.
+#[cfg(feature = "rand")]
+pub fn encrypt(key_file: &mut serde_json::Value, passphrase: &Passphrase) -> io::Result<()> {
+    use rand::RngCore;
+
+    let key_file = as_object(key_file)?;
+    if key_file.contains_key(ENCRYPTED_SECRET_KEY_FIELD) {
+        return Err(invalid_data("key file is already encrypted"));
+    }
+    let secret_key = match key_file.remove("secret_key").or_else(|| key_file.remove("private_key"))
+    {
+        Some(serde_json::Value::String(secret_key)) => Zeroizing::new(secret_key),
+        _ => return Err(invalid_data("key file has no secret_key")),
+    };
+
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut salt);
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+    let kdf =
+        Kdf::Scrypt { log_n: SCRYPT_LOG_N, r: SCRYPT_R, p: SCRYPT_P, salt: hex::encode(salt) };
+    let key = derive_key(passphrase, &kdf)?;
+    let aad = associated_data(key_file)?;
+    let ciphertext = XChaCha20Poly1305::new(key.as_ref().into())
+        .encrypt(XNonce::from_slice(&nonce), Payload { msg: secret_key.as_bytes(), aad: &aad })
+        .map_err(|_| invalid_data("failed to encrypt the secret key"))?;
+
+    let encrypted = EncryptedSecretKey {
+        kdf,
+        cipher: Cipher::XChaCha20Poly1305,
+        nonce: hex::encode(nonce),
+        ciphertext: hex::encode(ciphertext),
+    };
+    key_file.insert(ENCRYPTED_SECRET_KEY_FIELD.to_string(), serde_json::to_value(encrypted)?);
+    Ok(())
+}
+
+/// Replaces the encrypted secret key of a parsed key file with the plaintext one.
+pub fn decrypt(key_file: &mut serde_json::Value, passphrase: &Passphrase) -> io::Result<()> {
+    let key_file = as_object(key_file)?;
+    let encrypted: EncryptedSecretKey = match key_file.get(ENCRYPTED_SECRET_KEY_FIELD) {
+        Some(encrypted) => serde_json::from_value(encrypted.clone())?,
+        None => return Err(invalid_data("key file is not encrypted")),
+    };
+    let Cipher::XChaCha20Poly1305 = encrypted.cipher;
+    let nonce = hex::decode(&encrypted.nonce).map_err(|err| invalid_data(err.to_string()))?;
+    if nonce.len() != NONCE_LEN {
+        return Err(invalid_data("invalid nonce length"));
+    }
+    let ciphertext =
+        hex::decode(&encrypted.ciphertext).map_err(|err| invalid_data(err.to_string()))?;
+    let key = derive_key(passphrase, &encrypted.kdf)?;
+    let aad = associated_data(key_file)?;
+    let secret_key = XChaCha20Poly1305::new(key.as_ref().into())
+        .decrypt(XNonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: &aad })
+        .map(Zeroizing::new)
+        .map_err(|_| invalid_data("wrong passphrase or corrupted key file"))?;
+    let secret_key = String::from_utf8(secret_key.to_vec())
+        .map_err(|_| invalid_data("decrypted secret key is not valid UTF-8"))?;
+
+    key_file.remove(ENCRYPTED_SECRET_KEY_FIELD);
+    key_file.insert("secret_key".to_string(), serde_json::Value::String(secret_key));
+    Ok(())
+}
+
+/// Reads a key file, which can be JSON with comments, decrypting it with the passphrase set
+/// by [`set_key_file_passphrase`] if it is encrypted.
+pub fn read_key_file(path: &Path) -> io::Result<serde_json::Value> {
+    let mut key_file = read_key_file_raw(path)?;
+    if is_encrypted(&key_file) {
+        let passphrase = key_file_passphrase().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{} is encrypted but no key file passphrase was provided", path.display()),
+            )
+        })?;
+        decrypt(&mut key_file, passphrase)?;
+    }
+    Ok(key_file)
+}
+
+/// Reads a key file as is, without decrypting it.
+pub fn read_key_file_raw(path: &Path) -> io::Result<serde_json::Value> {
+    let json_str = std::fs::read_to_string(path)?;
+    let json_str_without_comments = near_config_utils::strip_comments_from_json_str(&json_str)?;
+    Ok(serde_json::from_str(&json_str_without_comments)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_FILE: &str = r#"{
+        "account_id": "example",
+        "public_key": "ed25519:6DSjZ8mvsRZDvFqFxo8tCKePG96omXW7eVYVSySmDk8e",
+        "secret_key": "ed25519:3D4YudUahN1nawWogh8pAKSj92sUNMdbZGjn7kERKzYoTy8tnFQuwoGUC51DowKqorvkr2pytJSnwuSbsNVfqygr"
+    }"#;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let original: serde_json::Value = serde_json::from_str(KEY_FILE).unwrap();
+        let passphrase = Passphrase::new("correct horse battery staple".to_string());
+
+        let mut key_file = original.clone();
+        encrypt(&mut key_file, &passphrase).unwrap();
+        assert!(is_encrypted(&key_file));
+        assert!(key_file.get("secret_key").is_none());
+        assert_eq!(key_file["public_key"], original["public_key"]);
+
+        let err = decrypt(&mut key_file.clone(), &Passphrase::new("wrong".to_string()));
+        assert_eq!(err.unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        // The ciphertext is bound to the public key.
+        let mut swapped = key_file.clone();
+        swapped["public_key"] = "ed25519:11111111111111111111111111111111".into();
+        assert!(decrypt(&mut swapped, &passphrase).is_err());
+
+        decrypt(&mut key_file, &passphrase).unwrap();
+        assert_eq!(key_file, original);
+    }
+
+    #[test]
+    fn test_reject_expensive_kdf() {
+        let passphrase = Passphrase::new("correct horse battery staple".to_string());
+        let mut key_file: serde_json::Value = serde_json::from_str(KEY_FILE).unwrap();
+        encrypt(&mut key_file, &passphrase).unwrap();
+
+        for (field, value) in [("log_n", 40), ("r", 1 << 20), ("p", 1 << 20)] {
+            let mut key_file = key_file.clone();
+            key_file[ENCRYPTED_SECRET_KEY_FIELD]["kdf"][field] = value.into();
+            let err = decrypt(&mut key_file, &passphrase).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            assert!(err.to_string().contains("exceed the supported limits"), "{err}");
+        }
+
+        // Each parameter is within bounds, but together they need 2 GiB.
+        let mut key_file = key_file.clone();
+        key_file[ENCRYPTED_SECRET_KEY_FIELD]["kdf"]["log_n"] = MAX_SCRYPT_LOG_N.into();
+        key_file[ENCRYPTED_SECRET_KEY_FIELD]["kdf"]["r"] = MAX_SCRYPT_R.into();
+        let err = decrypt(&mut key_file, &passphrase).unwrap_err();
+        assert!(err.to_string().contains("exceed the supported limits"), "{err}");
+    }
+}
@@ -19,6 +19,7 @@ mod util;
 mod errors;
 pub mod key_conversion;
 mod key_file;
+pub mod key_file_encryption;
 mod signature;
 mod signer;
 mod test_utils;
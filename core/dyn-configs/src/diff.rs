@@ -0,0 +1,121 @@
+use crate::{UpdatableConfigs, UpdatableValidatorSigner};
+use near_primitives::validator_signer::ValidatorSigner;
+use near_primitives::views::ConfigFieldChangeView;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+/// Returns the fields changed between two versions of the updatable configs.
+/// Sections which could not be read in either version are skipped.
+pub fn diff_updatable_configs(
+    old: &UpdatableConfigs,
+    new: &UpdatableConfigs,
+) -> Vec<ConfigFieldChangeView> {
+    let mut changes = diff_config_section("log_config", &old.log_config, &new.log_config);
+    if let (Some(old), Some(new)) = (&old.client_config, &new.client_config) {
+        changes.extend(diff_config_section("client_config", old, new));
+    }
+    if let (Some(old), Some(new)) = (&old.network_config, &new.network_config) {
+        changes.extend(diff_config_section("network_config", old, new));
+    }
+    if let (Some(old), Some(new)) = (&old.rpc_config, &new.rpc_config) {
+        changes.extend(diff_config_section("rpc_config", old, new));
+    }
+    if let (UpdatableValidatorSigner::MaybeKey(old), UpdatableValidatorSigner::MaybeKey(new)) =
+        (&old.validator_signer, &new.validator_signer)
+    {
+        changes.extend(diff_config_section(
+            "validator_key",
+            &validator_key_id(old),
+            &validator_key_id(new),
+        ));
+    }
+    changes
+}
+
+/// Identifies the validator key by its account and public key.
+fn validator_key_id(signer: &Option<Arc<ValidatorSigner>>) -> Option<(String, String)> {
+    signer
+        .as_ref()
+        .map(|signer| (signer.validator_id().to_string(), signer.public_key().to_string()))
+}
+
+/// Compares two versions of a config section and returns the fields that differ.
+/// Fields of nested objects are compared one by one and named by their path,
+/// e.g. `client_config.resharding_config.batch_size`.
+pub fn diff_config_section<T: Serialize>(
+    section: &str,
+    old: &T,
+    new: &T,
+) -> Vec<ConfigFieldChangeView> {
+    // Updatable configs are plain data which always serializes successfully.
+    let old = serde_json::to_value(old).unwrap_or_default();
+    let new = serde_json::to_value(new).unwrap_or_default();
+    let mut changes = vec![];
+    diff_values(section.to_string(), Some(&old), Some(&new), &mut changes);
+    changes
+}
+
+fn diff_values(
+    path: String,
+    old: Option<&Value>,
+    new: Option<&Value>,
+    changes: &mut Vec<ConfigFieldChangeView>,
+) {
+    if old == new {
+        return;
+    }
+    if let (Some(Value::Object(old)), Some(Value::Object(new))) = (old, new) {
+        let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+        for key in keys {
+            diff_values(format!("{path}.{key}"), old.get(key), new.get(key), changes);
+        }
+        return;
+    }
+    changes.push(ConfigFieldChangeView {
+        field: path,
+        old_value: old.map(Value::to_string),
+        new_value: new.map(Value::to_string),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff_config_section;
+    use near_chain_configs::{SingleMessageConfig, UpdatableNetworkConfig};
+
+    #[test]
+    fn test_diff_config_section() {
+        let old = UpdatableNetworkConfig {
+            max_num_peers: 40,
+            minimum_outbound_peers: 5,
+            ideal_connections_lo: 30,
+            ideal_connections_hi: 35,
+            received_messages_rate_limits: [(
+                "Block".to_string(),
+                SingleMessageConfig::new(4, 1.0, None),
+            )]
+            .into(),
+        };
+        assert!(diff_config_section("network_config", &old, &old).is_empty());
+
+        let mut new = old.clone();
+        new.max_num_peers = 50;
+        new.received_messages_rate_limits
+            .insert("BlockHeaders".to_string(), SingleMessageConfig::new(1, 1.0, None));
+        let changes = diff_config_section("network_config", &old, &new);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].field, "network_config.max_num_peers");
+        assert_eq!(changes[0].old_value.as_deref(), Some("40"));
+        assert_eq!(changes[0].new_value.as_deref(), Some("50"));
+        assert_eq!(changes[1].field, "network_config.received_messages_rate_limits.BlockHeaders");
+        assert_eq!(changes[1].old_value, None);
+        let new_value: serde_json::Value =
+            serde_json::from_str(changes[1].new_value.as_ref().unwrap()).unwrap();
+        assert_eq!(
+            new_value,
+            serde_json::json!({"maximum_size": 1, "refill_rate": 1.0, "initial_size": null})
+        );
+    }
+}
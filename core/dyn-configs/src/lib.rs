@@ -1,6 +1,6 @@
 #![doc = include_str!("../README.md")]
 
-use near_chain_configs::UpdatableClientConfig;
+use near_chain_configs::{UpdatableClientConfig, UpdatableNetworkConfig, UpdatableRpcConfig};
 use near_o11y::log_config::LogConfig;
 use near_primitives::validator_signer::ValidatorSigner;
 use near_time::Clock;
@@ -8,8 +8,11 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::broadcast::Sender;
 
+mod diff;
 mod metrics;
 
+pub use diff::{diff_config_section, diff_updatable_configs};
+
 #[derive(Clone, Default)]
 pub enum UpdatableValidatorSigner {
     /// Validator key existence could not be determined.
@@ -26,6 +29,10 @@ pub struct UpdatableConfigs {
     pub log_config: Option<LogConfig>,
     /// Contents of the `config.json` corresponding to the mutable fields of `ClientConfig`.
     pub client_config: Option<UpdatableClientConfig>,
+    /// Contents of the `config.json` corresponding to the mutable fields of `NetworkConfig`.
+    pub network_config: Option<UpdatableNetworkConfig>,
+    /// Contents of the `config.json` corresponding to the mutable fields of `RpcConfig`.
+    pub rpc_config: Option<UpdatableRpcConfig>,
     /// Validator key hot loaded from file.
     pub validator_signer: UpdatableValidatorSigner,
 }
@@ -48,6 +55,8 @@ pub enum UpdatableConfigLoaderError {
     ConfigFileError { file: PathBuf, err: anyhow::Error },
     #[error("Can't open or read the validator key file {file:?}: {err:?}")]
     ValidatorKeyFileError { file: PathBuf, err: anyhow::Error },
+    #[error("Can't connect to the remote signer at {endpoint}: {err:?}")]
+    RemoteSignerError { endpoint: String, err: anyhow::Error },
     #[error("One or multiple dynamic config files reload errors {0:?}")]
     Errors(Vec<UpdatableConfigLoaderError>),
     #[error("No home dir set")]
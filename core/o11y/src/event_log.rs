@@ -0,0 +1,355 @@
+//! Structured log of consensus-relevant node events.
+//!
+//! Events are written as JSON lines to rotated files in the configured
+//! directory and, on unix, can additionally be streamed to any number of local
+//! readers connected to a Unix socket. Every line is an [`EventRecord`], whose
+//! format is versioned by [`SCHEMA_VERSION`]. Adding a new event or a new
+//! optional field is a backwards compatible change; renaming or removing
+//! anything requires bumping the version.
+//!
+//! The log is a process-wide singleton initialised once with [`init`]; until
+//! then [`emit`] is a no-op, so emitters don't need to check whether the log is
+//! enabled.
+
+use near_primitives_core::hash::CryptoHash;
+use near_primitives_core::types::{AccountId, BlockHeight, EpochHeight, ShardId};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Version of the event schema. Bump whenever an existing event or field is
+/// renamed, removed or changes meaning.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A single line of the event log.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EventRecord {
+    pub schema_version: u32,
+    /// Wall-clock time at which the event was emitted, in milliseconds since
+    /// the unix epoch.
+    pub timestamp_ms: u64,
+    #[serde(flatten)]
+    pub event: NodeEvent,
+}
+
+/// Consensus-relevant events emitted by the node.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum NodeEvent {
+    /// This node produced a block.
+    BlockProduced { height: BlockHeight, hash: CryptoHash, prev_hash: CryptoHash },
+    /// This node was the block producer for a height that was skipped.
+    BlockMissed { height: BlockHeight, block_producer: AccountId },
+    /// This node produced a chunk.
+    ChunkProduced { height: BlockHeight, shard_id: ShardId, chunk_hash: CryptoHash },
+    /// This node was the chunk producer for a shard that got no new chunk.
+    ChunkMissed { height: BlockHeight, shard_id: ShardId, chunk_producer: AccountId },
+    /// This node sent an endorsement of a chunk to the block producers.
+    EndorsementSent { height: BlockHeight, shard_id: ShardId, chunk_hash: CryptoHash },
+    /// This node sent an approval for the given target height.
+    ApprovalSent { target_height: BlockHeight, account_id: AccountId },
+    /// The chain moved into a new epoch.
+    EpochTransition {
+        height: BlockHeight,
+        epoch_id: CryptoHash,
+        epoch_height: EpochHeight,
+        prev_epoch_id: CryptoHash,
+    },
+    /// A peer was disconnected and banned. `reason` is the `ReasonForBan`.
+    PeerBanned { peer_id: String, reason: String },
+    /// State sync of a shard moved to a new phase.
+    StateSyncPhase { sync_hash: CryptoHash, shard_id: ShardId, phase: String },
+    /// A garbage collection run advanced the tail or the GC stop height.
+    GcRun { tail: Option<BlockHeight>, gc_stop_height: Option<BlockHeight>, duration_ms: u64 },
+    /// The dynamic config was reloaded. Lists the fields that changed.
+    ConfigReloaded { fields: Vec<String> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EventLogRotation {
+    Hourly,
+    #[default]
+    Daily,
+    Never,
+}
+
+/// Configures the event log.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct EventLogConfig {
+    /// Directory of the log files. Relative paths are resolved against the
+    /// node home directory.
+    pub dir: PathBuf,
+    pub rotation: EventLogRotation,
+    /// Maximum number of log files to keep. Older files are deleted on
+    /// rotation. Keeps all files if not set.
+    pub max_files: Option<usize>,
+    /// If set, events are also streamed to clients connected to a Unix socket
+    /// at this path. Relative paths are resolved against the node home
+    /// directory. Slow clients are disconnected rather than blocking the node.
+    pub socket_path: Option<PathBuf>,
+}
+
+impl Default for EventLogConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("event_log"),
+            rotation: EventLogRotation::default(),
+            max_files: None,
+            socket_path: None,
+        }
+    }
+}
+
+struct EventLog {
+    file: parking_lot::Mutex<tracing_appender::rolling::RollingFileAppender>,
+    #[cfg(unix)]
+    clients: std::sync::Arc<parking_lot::Mutex<Vec<std::os::unix::net::UnixStream>>>,
+}
+
+static EVENT_LOG: OnceLock<EventLog> = OnceLock::new();
+
+#[derive(thiserror::Error, Debug)]
+pub enum EventLogError {
+    #[error("event log is already initialized")]
+    AlreadyInitialized,
+    #[error("failed to open the event log files: {0}")]
+    Files(#[from] tracing_appender::rolling::InitError),
+    #[error("failed to listen on the event log socket: {0}")]
+    Socket(#[from] std::io::Error),
+}
+
+/// Initialises the process-wide event log. Paths in `config` are resolved
+/// against `home_dir`.
+pub fn init(config: &EventLogConfig, home_dir: &Path) -> Result<(), EventLogError> {
+    if EVENT_LOG.get().is_some() {
+        return Err(EventLogError::AlreadyInitialized);
+    }
+    let log = EventLog::open(config, home_dir)?;
+    EVENT_LOG.set(log).map_err(|_| EventLogError::AlreadyInitialized)
+}
+
+impl EventLog {
+    fn open(config: &EventLogConfig, home_dir: &Path) -> Result<Self, EventLogError> {
+        let rotation = match config.rotation {
+            EventLogRotation::Hourly => tracing_appender::rolling::Rotation::HOURLY,
+            EventLogRotation::Daily => tracing_appender::rolling::Rotation::DAILY,
+            EventLogRotation::Never => tracing_appender::rolling::Rotation::NEVER,
+        };
+        let mut builder = tracing_appender::rolling::RollingFileAppender::builder()
+            .rotation(rotation)
+            .filename_prefix("events")
+            .filename_suffix("jsonl");
+        if let Some(max_files) = config.max_files {
+            builder = builder.max_log_files(max_files);
+        }
+        let file = builder.build(home_dir.join(&config.dir))?;
+        let log = EventLog {
+            file: parking_lot::Mutex::new(file),
+            #[cfg(unix)]
+            clients: Default::default(),
+        };
+        #[cfg(unix)]
+        if let Some(socket_path) = &config.socket_path {
+            listen(&home_dir.join(socket_path), log.clients.clone())?;
+        }
+        #[cfg(not(unix))]
+        if config.socket_path.is_some() {
+            tracing::warn!(target: "event_log", "event log socket is only supported on unix");
+        }
+        Ok(log)
+    }
+
+    fn write(&self, event: NodeEvent) {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_millis() as u64);
+        let record = EventRecord { schema_version: SCHEMA_VERSION, timestamp_ms, event };
+        let mut line = match serde_json::to_vec(&record) {
+            Ok(line) => line,
+            Err(err) => {
+                tracing::warn!(target: "event_log", ?err, "failed to serialize event");
+                return;
+            }
+        };
+        line.push(b'\n');
+        if let Err(err) = self.file.lock().write_all(&line) {
+            tracing::warn!(target: "event_log", ?err, "failed to write event");
+        }
+        #[cfg(unix)]
+        {
+            // Clients which can't keep up with the node are dropped: writes are
+            // non-blocking and a partial write would corrupt the stream anyway.
+            let mut clients = self.clients.lock();
+            clients.retain_mut(|client| matches!(client.write(&line), Ok(n) if n == line.len()));
+        }
+    }
+}
+
+#[cfg(unix)]
+fn listen(
+    path: &Path,
+    clients: std::sync::Arc<parking_lot::Mutex<Vec<std::os::unix::net::UnixStream>>>,
+) -> std::io::Result<()> {
+    // A socket left behind by a previous run would make the bind fail.
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    let listener = std::os::unix::net::UnixListener::bind(path)?;
+    std::thread::Builder::new().name("event_log_socket".to_string()).spawn(move || {
+        for stream in listener.incoming() {
+            match stream.and_then(|stream| {
+                stream.set_nonblocking(true)?;
+                Ok(stream)
+            }) {
+                Ok(stream) => clients.lock().push(stream),
+                Err(err) => {
+                    tracing::warn!(target: "event_log", ?err, "failed to accept event log client")
+                }
+            }
+        }
+    })?;
+    Ok(())
+}
+
+/// Records `event` in the event log, if it is initialised.
+pub fn emit(event: NodeEvent) {
+    if let Some(log) = EVENT_LOG.get() {
+        log.write(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_record_format() {
+        let record = EventRecord {
+            schema_version: SCHEMA_VERSION,
+            timestamp_ms: 1700000000000,
+            event: NodeEvent::BlockMissed {
+                height: 42,
+                block_producer: "test.near".parse().unwrap(),
+            },
+        };
+        let value = serde_json::to_value(&record).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "schema_version": 1,
+                "timestamp_ms": 1700000000000u64,
+                "event": "block_missed",
+                "height": 42,
+                "block_producer": "test.near",
+            })
+        );
+        assert_eq!(serde_json::from_value::<EventRecord>(value).unwrap(), record);
+    }
+
+    fn block_missed(height: BlockHeight) -> NodeEvent {
+        NodeEvent::BlockMissed { height, block_producer: "test.near".parse().unwrap() }
+    }
+
+    fn read_records(path: &Path) -> Vec<EventRecord> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    /// Names of the log files, which encode the rotation period.
+    fn log_files(dir: &Path) -> Vec<String> {
+        let mut files: Vec<_> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_rotation() {
+        let home_dir = tempfile::tempdir().unwrap();
+        for (rotation, date_len) in [
+            (EventLogRotation::Never, None),
+            // YYYY-MM-DD
+            (EventLogRotation::Daily, Some(10)),
+            // YYYY-MM-DD-HH
+            (EventLogRotation::Hourly, Some(13)),
+        ] {
+            let dir = PathBuf::from(format!("{:?}", rotation));
+            let config = EventLogConfig { dir: dir.clone(), rotation, ..Default::default() };
+            let log = EventLog::open(&config, home_dir.path()).unwrap();
+            log.write(block_missed(1));
+            log.write(block_missed(2));
+
+            let files = log_files(&home_dir.path().join(&dir));
+            assert_eq!(files.len(), 1, "{files:?}");
+            let date = files[0].strip_prefix("events").unwrap().strip_suffix(".jsonl").unwrap();
+            match date_len {
+                None => assert_eq!(date, ""),
+                Some(date_len) => {
+                    let date = date.strip_prefix('.').unwrap();
+                    assert_eq!(date.len(), date_len, "{date}");
+                    assert!(date.chars().all(|c| c.is_ascii_digit() || c == '-'), "{date}");
+                }
+            }
+            let records = read_records(&home_dir.path().join(&dir).join(&files[0]));
+            let events: Vec<_> = records.into_iter().map(|record| record.event).collect();
+            assert_eq!(events, vec![block_missed(1), block_missed(2)]);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_socket() {
+        use std::io::BufRead;
+
+        let home_dir = tempfile::tempdir().unwrap();
+        let config =
+            EventLogConfig { socket_path: Some("events.sock".into()), ..Default::default() };
+        let log = EventLog::open(&config, home_dir.path()).unwrap();
+        let wait_for_clients = |count: usize| {
+            for _ in 0..1000 {
+                if log.clients.lock().len() == count {
+                    return;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(10));
+            }
+            panic!("expected {count} event log clients");
+        };
+
+        let reader =
+            std::os::unix::net::UnixStream::connect(home_dir.path().join("events.sock")).unwrap();
+        let slow_reader =
+            std::os::unix::net::UnixStream::connect(home_dir.path().join("events.sock")).unwrap();
+        wait_for_clients(2);
+
+        // Events are streamed to the clients as they are written to the files.
+        log.write(block_missed(1));
+        let mut reader = std::io::BufReader::new(reader);
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let record: EventRecord = serde_json::from_str(&line).unwrap();
+        assert_eq!(record.event, block_missed(1));
+
+        // A client which doesn't read is dropped once its socket buffer is full, while the
+        // node keeps going.
+        let mut height = 2;
+        while log.clients.lock().len() == 2 {
+            log.write(block_missed(height));
+            height += 1;
+            // Keep the other client drained.
+            line.clear();
+            reader.read_line(&mut line).unwrap();
+            assert!(height < 1_000_000, "slow client was never dropped");
+        }
+        assert_eq!(log.clients.lock().len(), 1);
+        drop(slow_reader);
+    }
+}
@@ -12,6 +12,7 @@ pub use {tracing, tracing_appender, tracing_subscriber};
 
 /// Custom tracing subscriber implementation that produces IO traces.
 pub mod env_filter;
+pub mod event_log;
 mod io_tracer;
 pub mod log_config;
 mod log_counter;
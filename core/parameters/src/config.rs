@@ -95,6 +95,14 @@ impl RuntimeConfig {
         }
     }
 
+    /// Returns a copy of this config that lets the VM capture wasm backtraces of failed
+    /// executions. Must not be used to apply chunks, see [`crate::vm::Config::wasm_backtraces`].
+    pub fn with_wasm_backtraces(&self) -> Self {
+        let mut wasm_config = crate::vm::Config::clone(&self.wasm_config);
+        wasm_config.wasm_backtraces = true;
+        Self { wasm_config: Arc::new(wasm_config), ..self.clone() }
+    }
+
     pub fn storage_amount_per_byte(&self) -> Balance {
         self.fees.storage_usage_config.storage_amount_per_byte
     }
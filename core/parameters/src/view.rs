@@ -301,6 +301,7 @@ impl From<VMConfigView> for crate::vm::Config {
             global_contract_host_fns: view.global_contract_host_fns,
             reftypes_bulk_memory: view.reftypes_bulk_memory,
             deterministic_account_ids: view.deterministic_account_ids,
+            wasm_backtraces: false,
         }
     }
 }
@@ -145,7 +145,7 @@ pub struct LimitConfig {
 /// protocol specific behavior of the contract runtime. The former contains
 /// configuration for the WASM runtime specifically, while the latter contains
 /// configuration for the transaction runtime and WASM runtime.
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Config {
     /// Costs for runtime externals
     pub ext_costs: ExtCostsConfig,
@@ -192,6 +192,52 @@ pub struct Config {
 
     /// Describes limits for VM and Runtime.
     pub limit_config: LimitConfig,
+
+    /// Whether the VM may capture wasm backtraces of failed executions.
+    ///
+    /// This is not a protocol parameter. It is only enabled for view calls and debugging tools,
+    /// never when applying chunks, and it is not part of the hash of the config.
+    pub wasm_backtraces: bool,
+}
+
+impl Hash for Config {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // The hash keys the compiled contract cache. `wasm_backtraces` doesn't affect the compiled
+        // code, so it is skipped, and the other fields are hashed like `#[derive(Hash)]` would.
+        let Self {
+            ext_costs,
+            grow_mem_cost,
+            regular_op_cost,
+            linear_op_base_cost,
+            linear_op_unit_cost,
+            vm_kind,
+            storage_get_mode,
+            fix_contract_loading_cost,
+            eth_implicit_accounts,
+            discard_custom_sections,
+            saturating_float_to_int,
+            global_contract_host_fns,
+            reftypes_bulk_memory,
+            deterministic_account_ids,
+            limit_config,
+            wasm_backtraces: _,
+        } = self;
+        ext_costs.hash(state);
+        grow_mem_cost.hash(state);
+        regular_op_cost.hash(state);
+        linear_op_base_cost.hash(state);
+        linear_op_unit_cost.hash(state);
+        vm_kind.hash(state);
+        storage_get_mode.hash(state);
+        fix_contract_loading_cost.hash(state);
+        eth_implicit_accounts.hash(state);
+        discard_custom_sections.hash(state);
+        saturating_float_to_int.hash(state);
+        global_contract_host_fns.hash(state);
+        reftypes_bulk_memory.hash(state);
+        deterministic_account_ids.hash(state);
+        limit_config.hash(state);
+    }
 }
 
 impl Config {
@@ -127,7 +127,7 @@ impl Block {
         // for now to indicate whether spice feature is enabled to allow creating non-spice blocks
         // for tests.
         spice_info: Option<SpiceNewBlockProductionInfo>,
-    ) -> Self {
+    ) -> std::io::Result<Self> {
         // Collect aggregate of validators and gas usage/limits from chunks.
 
         let mut prev_validator_proposals = vec![];
@@ -177,9 +177,8 @@ impl Block {
             .unwrap();
 
         // Use the optimistic block data if available, otherwise compute it.
-        let (time, vrf_value, vrf_proof, random_value) = optimistic_block
-            .as_ref()
-            .map(|ob| {
+        let (time, vrf_value, vrf_proof, random_value) = match optimistic_block.as_ref() {
+            Some(ob) => {
                 tracing::debug!(target: "client", "taking metadata from optimistic block");
                 (
                     ob.inner.block_timestamp,
@@ -187,11 +186,12 @@ impl Block {
                     ob.inner.vrf_proof,
                     ob.inner.random_value,
                 )
-            })
-            .unwrap_or_else(|| {
+            }
+            None => {
                 let now = clock.now_utc().unix_timestamp_nanos() as u64;
-                get_block_metadata(prev, signer, now, sandbox_delta_time)
-            });
+                get_block_metadata(prev, signer, now, sandbox_delta_time)?
+            }
+        };
 
         let last_ds_final_block =
             if height == prev.height() + 1 { prev.hash() } else { prev.last_ds_final_block() };
@@ -277,9 +277,9 @@ impl Block {
             block_merkle_root,
             prev.height(),
             chunk_endorsements_bitmap,
-        );
+        )?;
 
-        Self::new_block(header, body)
+        Ok(Self::new_block(header, body))
     }
 
     pub fn verify_total_supply(
@@ -5,7 +5,7 @@ use crate::network::PeerId;
 use crate::stateless_validation::chunk_endorsements_bitmap::ChunkEndorsementsBitmap;
 use crate::types::validator_stake::{ValidatorStake, ValidatorStakeIter, ValidatorStakeV1};
 use crate::types::{AccountId, Balance, BlockHeight, EpochId, MerkleHash, NumBlocks};
-use crate::validator_signer::ValidatorSigner;
+use crate::validator_signer::{SignatureKind, ValidatorSigner};
 use crate::version::ProtocolVersion;
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_crypto::{KeyType, PublicKey, Signature};
@@ -355,10 +355,24 @@ impl Approval {
         target_height: BlockHeight,
         signer: &ValidatorSigner,
     ) -> Self {
+        Self::try_new(parent_hash, parent_height, target_height, signer)
+            .expect("failed to sign the approval")
+    }
+
+    /// Like [`Self::new`], but returns the error of a remote signer failing to sign.
+    pub fn try_new(
+        parent_hash: CryptoHash,
+        parent_height: BlockHeight,
+        target_height: BlockHeight,
+        signer: &ValidatorSigner,
+    ) -> std::io::Result<Self> {
         let inner = ApprovalInner::new(&parent_hash, parent_height, target_height);
 
-        let signature = signer.sign_bytes(&Approval::get_data_for_sig(&inner, target_height));
-        Approval { inner, target_height, signature, account_id: signer.validator_id().clone() }
+        let signature = signer.try_sign(
+            SignatureKind::Approval,
+            &Approval::get_data_for_sig(&inner, target_height),
+        )?;
+        Ok(Approval { inner, target_height, signature, account_id: signer.validator_id().clone() })
     }
 
     pub fn get_data_for_sig(inner: &ApprovalInner, target_height: BlockHeight) -> Vec<u8> {
@@ -580,7 +594,8 @@ impl BlockHeader {
         combine_hash(&hash_inner, &prev_hash)
     }
 
-    /// Creates BlockHeader for a newly produced block.
+    /// Creates BlockHeader for a newly produced block, failing only if a remote signer fails to
+    /// sign it.
     pub fn new(
         latest_protocol_version: ProtocolVersion,
         height: BlockHeight,
@@ -609,7 +624,7 @@ impl BlockHeader {
         block_merkle_root: CryptoHash,
         prev_height: BlockHeight,
         chunk_endorsements: Option<ChunkEndorsementsBitmap>,
-    ) -> Self {
+    ) -> std::io::Result<Self> {
         Self::new_impl(
             latest_protocol_version,
             height,
@@ -700,7 +715,8 @@ impl BlockHeader {
             block_merkle_root,
             prev_height,
             chunk_endorsements,
-        );
+        )
+        .expect("signing with a given signature never fails");
         // Note: We do not panic but only log if the hash of the created header does not match the expected hash (From the view)
         // because there are tests that check if we can downgrade a BlockHeader's view a previous version, in which case the hash
         // of the header changes.
@@ -739,7 +755,7 @@ impl BlockHeader {
         block_merkle_root: CryptoHash,
         prev_height: BlockHeight,
         chunk_endorsements: Option<ChunkEndorsementsBitmap>,
-    ) -> Self {
+    ) -> std::io::Result<Self> {
         let inner_lite = BlockHeaderInnerLite {
             height,
             epoch_id,
@@ -777,20 +793,27 @@ impl BlockHeader {
             chunk_endorsements,
         };
         let (hash, signature) =
-            Self::compute_hash_and_sign(signature_source, prev_hash, &inner_lite, &inner_rest);
-        Self::BlockHeaderV5(BlockHeaderV5 { prev_hash, inner_lite, inner_rest, signature, hash })
+            Self::compute_hash_and_sign(signature_source, prev_hash, &inner_lite, &inner_rest)?;
+        Ok(Self::BlockHeaderV5(BlockHeaderV5 {
+            prev_hash,
+            inner_lite,
+            inner_rest,
+            signature,
+            hash,
+        }))
     }
 
     /// Helper function for `new_impl` and `old_impl` to compute the hash and signature of the hash from the block header parts.
     /// Exactly one of the `signer` and `signature` must be provided.
     /// If `signer` is given signs the header with given `prev_hash`, `inner_lite`, and `inner_rest` and returns the hash and signature of the header.
     /// If `signature` is given, uses the signature as is and only computes the hash.
+    /// Fails only if a remote signer fails to sign.
     fn compute_hash_and_sign<T>(
         signature_source: SignatureSource,
         prev_hash: CryptoHash,
         inner_lite: &BlockHeaderInnerLite,
         inner_rest: &T,
-    ) -> (CryptoHash, Signature)
+    ) -> std::io::Result<(CryptoHash, Signature)>
     where
         T: BorshSerialize + ?Sized,
     {
@@ -800,8 +823,10 @@ impl BlockHeader {
             &borsh::to_vec(&inner_rest).expect("Failed to serialize"),
         );
         match signature_source {
-            SignatureSource::Signer(signer) => (hash, signer.sign_bytes(hash.as_ref())),
-            SignatureSource::Signature(signature) => (hash, signature),
+            SignatureSource::Signer(signer) => {
+                Ok((hash, signer.try_sign(SignatureKind::Block, hash.as_ref())?))
+            }
+            SignatureSource::Signature(signature) => Ok((hash, signature)),
         }
     }
 
@@ -849,6 +874,7 @@ impl BlockHeader {
             0,                     // prev_height
             Some(ChunkEndorsementsBitmap::genesis()),
         )
+        .expect("signing with a given signature never fails")
     }
 
     #[inline]
@@ -85,7 +85,8 @@ fn genesis_chunk(
         BandwidthRequests::empty(),
         &EmptyValidatorSigner::default().into(),
         rs,
-    );
+    )
+    .expect("the empty signer never fails");
     let encoded_chunk = chunk.into_parts().1;
     encoded_chunk.decode_chunk().expect("Failed to decode genesis chunk")
 }
@@ -68,18 +68,31 @@ pub struct AnnounceAccount {
 }
 
 impl AnnounceAccount {
-    pub fn new(signer: &ValidatorSigner, peer_id: PeerId, epoch_id: EpochId) -> Self {
-        let signature = Self::sign(signer, &peer_id, &epoch_id);
-        Self { account_id: signer.validator_id().clone(), peer_id: peer_id, epoch_id, signature }
+    pub fn new(
+        signer: &ValidatorSigner,
+        peer_id: PeerId,
+        epoch_id: EpochId,
+    ) -> std::io::Result<Self> {
+        let signature = Self::sign(signer, &peer_id, &epoch_id)?;
+        Ok(Self {
+            account_id: signer.validator_id().clone(),
+            peer_id: peer_id,
+            epoch_id,
+            signature,
+        })
     }
 
     pub fn hash(&self) -> CryptoHash {
         Self::build_header_hash(&self.account_id, &self.peer_id, &self.epoch_id)
     }
 
-    fn sign(signer: &ValidatorSigner, peer_id: &PeerId, epoch_id: &EpochId) -> Signature {
+    fn sign(
+        signer: &ValidatorSigner,
+        peer_id: &PeerId,
+        epoch_id: &EpochId,
+    ) -> std::io::Result<Signature> {
         let hash = Self::build_header_hash(signer.validator_id(), peer_id, epoch_id);
-        signer.sign_bytes(hash.as_ref())
+        signer.try_sign_bytes(hash.as_ref())
     }
 
     /// We hash only (account_id, peer_id, epoch_id). There is no need hash the signature
@@ -8,7 +8,7 @@ use crate::transaction::SignedTransaction;
 use crate::transaction::ValidatedTransaction;
 use crate::types::validator_stake::{ValidatorStake, ValidatorStakeIter, ValidatorStakeV1};
 use crate::types::{Balance, BlockHeight, Gas, MerkleHash, ShardId, StateRoot};
-use crate::validator_signer::{EmptyValidatorSigner, ValidatorSigner};
+use crate::validator_signer::{EmptyValidatorSigner, SignatureKind, ValidatorSigner};
 use crate::version::ProtocolVersion;
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_crypto::Signature;
@@ -226,7 +226,7 @@ impl ShardChunkHeaderV2 {
             prev_validator_proposals,
         };
         let hash = Self::compute_hash(&inner);
-        let signature = signer.sign_bytes(hash.as_ref());
+        let signature = signer.sign(SignatureKind::ChunkHeader, hash.as_ref());
         Self { inner, height_included: 0, signature, hash }
     }
 }
@@ -309,7 +309,7 @@ impl ShardChunkHeaderV3 {
         congestion_info: CongestionInfo,
         bandwidth_requests: BandwidthRequests,
         signer: &ValidatorSigner,
-    ) -> Self {
+    ) -> std::io::Result<Self> {
         let inner = ShardChunkHeaderInner::V4(ShardChunkHeaderInnerV4 {
             prev_block_hash,
             prev_state_root,
@@ -339,7 +339,7 @@ impl ShardChunkHeaderV3 {
         prev_outgoing_receipts_root: CryptoHash,
         tx_root: CryptoHash,
         signer: &ValidatorSigner,
-    ) -> Self {
+    ) -> std::io::Result<Self> {
         let inner = ShardChunkHeaderInner::V5(ShardChunkHeaderInnerV5SpiceTxOnly {
             prev_block_hash,
             encoded_merkle_root,
@@ -352,10 +352,14 @@ impl ShardChunkHeaderV3 {
         Self::from_inner(inner, signer)
     }
 
-    pub fn from_inner(inner: ShardChunkHeaderInner, signer: &ValidatorSigner) -> Self {
+    /// Signs the header, failing only if a remote signer fails to sign.
+    pub fn from_inner(
+        inner: ShardChunkHeaderInner,
+        signer: &ValidatorSigner,
+    ) -> std::io::Result<Self> {
         let hash = Self::compute_hash(&inner);
-        let signature = signer.sign_bytes(hash.as_ref());
-        Self { inner, height_included: 0, signature, hash }
+        let signature = signer.try_sign(SignatureKind::ChunkHeader, hash.as_ref())?;
+        Ok(Self { inner, height_included: 0, signature, hash })
     }
 }
 
@@ -734,7 +738,7 @@ impl ShardChunkHeaderV1 {
             prev_validator_proposals,
         };
         let hash = Self::compute_hash(&inner);
-        let signature = signer.sign_bytes(hash.as_ref());
+        let signature = signer.sign(SignatureKind::ChunkHeader, hash.as_ref());
         Self { inner, height_included: 0, signature, hash }
     }
 }
@@ -1407,7 +1411,7 @@ impl ShardChunkWithEncoding {
         bandwidth_requests: BandwidthRequests,
         signer: &ValidatorSigner,
         rs: &reed_solomon_erasure::galois_8::ReedSolomon,
-    ) -> (ShardChunkWithEncoding, Vec<MerklePath>) {
+    ) -> std::io::Result<(ShardChunkWithEncoding, Vec<MerklePath>)> {
         let signed_txs =
             validated_txs.into_iter().map(|validated_tx| validated_tx.into_signed_tx()).collect();
         let transaction_receipt = TransactionReceipt(signed_txs, prev_outgoing_receipts);
@@ -1434,14 +1438,14 @@ impl ShardChunkWithEncoding {
             congestion_info,
             bandwidth_requests,
             signer,
-        ));
+        )?);
         let encoded_shard_chunk = EncodedShardChunk::V2(EncodedShardChunkV2 { header, content });
         let shard_chunk = ShardChunk::new(
             encoded_shard_chunk.cloned_header(),
             signed_txs,
             prev_outgoing_receipts,
         );
-        (Self { shard_chunk, bytes: encoded_shard_chunk }, merkle_paths)
+        Ok((Self { shard_chunk, bytes: encoded_shard_chunk }, merkle_paths))
     }
 
     #[cfg(feature = "solomon")]
@@ -1455,7 +1459,7 @@ impl ShardChunkWithEncoding {
         tx_root: CryptoHash,
         signer: &ValidatorSigner,
         rs: &reed_solomon_erasure::galois_8::ReedSolomon,
-    ) -> (ShardChunkWithEncoding, Vec<MerklePath>) {
+    ) -> std::io::Result<(ShardChunkWithEncoding, Vec<MerklePath>)> {
         let signed_txs =
             validated_txs.into_iter().map(|validated_tx| validated_tx.into_signed_tx()).collect();
         let transaction_receipt = TransactionReceipt(signed_txs, prev_outgoing_receipts);
@@ -1474,14 +1478,14 @@ impl ShardChunkWithEncoding {
             prev_outgoing_receipts_root,
             tx_root,
             signer,
-        ));
+        )?);
         let encoded_shard_chunk = EncodedShardChunk::V2(EncodedShardChunkV2 { header, content });
         let shard_chunk = ShardChunk::new(
             encoded_shard_chunk.cloned_header(),
             signed_txs,
             prev_outgoing_receipts,
         );
-        (Self { shard_chunk, bytes: encoded_shard_chunk }, merkle_paths)
+        Ok((Self { shard_chunk, bytes: encoded_shard_chunk }, merkle_paths))
     }
 
     pub fn from_encoded_shard_chunk(bytes: EncodedShardChunk) -> Result<Self, std::io::Error> {
@@ -50,10 +50,10 @@ impl SpicePartialData {
         commitment: SpiceDataCommitment,
         parts: Vec<SpiceDataPart>,
         signer: &ValidatorSigner,
-    ) -> Self {
+    ) -> std::io::Result<Self> {
         let inner = SpicePartialDataInner { id, commitment, parts };
-        let signature = signer.sign_bytes(&inner.serialize_for_signing());
-        Self { inner, signature, sender: signer.validator_id().clone() }
+        let signature = signer.try_sign_bytes(&inner.serialize_for_signing())?;
+        Ok(Self { inner, signature, sender: signer.validator_id().clone() })
     }
 
     pub fn block_hash(&self) -> &CryptoHash {
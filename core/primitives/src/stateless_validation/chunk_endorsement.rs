@@ -2,7 +2,7 @@ use std::fmt::Debug;
 
 use crate::sharding::{ChunkHash, ShardChunkHeader};
 use crate::types::{EpochId, SignatureDifferentiator};
-use crate::validator_signer::ValidatorSigner;
+use crate::validator_signer::{SignatureKind, ValidatorSigner};
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_crypto::{PublicKey, Signature};
 use near_primitives_core::types::{AccountId, BlockHeight, ShardId};
@@ -27,17 +27,28 @@ impl ChunkEndorsement {
         chunk_header: &ShardChunkHeader,
         signer: &ValidatorSigner,
     ) -> ChunkEndorsement {
+        Self::try_new(epoch_id, chunk_header, signer).expect("failed to sign the chunk endorsement")
+    }
+
+    /// Like [`Self::new`], but returns the error of a remote signer failing to sign.
+    pub fn try_new(
+        epoch_id: EpochId,
+        chunk_header: &ShardChunkHeader,
+        signer: &ValidatorSigner,
+    ) -> std::io::Result<ChunkEndorsement> {
         let metadata = ChunkEndorsementMetadata {
             account_id: signer.validator_id().clone(),
             shard_id: chunk_header.shard_id(),
             epoch_id,
             height_created: chunk_header.height_created(),
         };
-        let metadata_signature = signer.sign_bytes(&borsh::to_vec(&metadata).unwrap());
+        let metadata_signature =
+            signer.try_sign(SignatureKind::ChunkEndorsement, &borsh::to_vec(&metadata).unwrap())?;
         let inner = ChunkEndorsementInnerV1::new(chunk_header.chunk_hash().clone());
-        let signature = signer.sign_bytes(&borsh::to_vec(&inner).unwrap());
+        let signature =
+            signer.try_sign(SignatureKind::ChunkEndorsement, &borsh::to_vec(&inner).unwrap())?;
         let endorsement = ChunkEndorsementV2 { inner, signature, metadata, metadata_signature };
-        ChunkEndorsement::V2(endorsement)
+        Ok(ChunkEndorsement::V2(endorsement))
     }
 
     pub fn chunk_production_key(&self) -> ChunkProductionKey {
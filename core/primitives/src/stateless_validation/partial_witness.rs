@@ -3,7 +3,7 @@ use std::fmt::{Debug, Formatter};
 use super::ChunkProductionKey;
 use crate::sharding::ShardChunkHeader;
 use crate::types::{EpochId, SignatureDifferentiator};
-use crate::validator_signer::ValidatorSigner;
+use crate::validator_signer::{SignatureKind, ValidatorSigner};
 use borsh::{BorshDeserialize, BorshSerialize};
 use bytesize::ByteSize;
 use near_crypto::{PublicKey, Signature};
@@ -48,6 +48,19 @@ impl PartialEncodedStateWitness {
         encoded_length: usize,
         signer: &ValidatorSigner,
     ) -> Self {
+        Self::try_new(epoch_id, chunk_header, part_ord, part, encoded_length, signer)
+            .expect("failed to sign the state witness part")
+    }
+
+    /// Like [`Self::new`], but returns the error of a remote signer failing to sign.
+    pub fn try_new(
+        epoch_id: EpochId,
+        chunk_header: ShardChunkHeader,
+        part_ord: usize,
+        part: Vec<u8>,
+        encoded_length: usize,
+        signer: &ValidatorSigner,
+    ) -> std::io::Result<Self> {
         let inner = PartialEncodedStateWitnessInner::new(
             epoch_id,
             chunk_header,
@@ -55,8 +68,9 @@ impl PartialEncodedStateWitness {
             part,
             encoded_length,
         );
-        let signature = signer.sign_bytes(&borsh::to_vec(&inner).unwrap());
-        Self { inner, signature }
+        let signature =
+            signer.try_sign(SignatureKind::StateWitness, &borsh::to_vec(&inner).unwrap())?;
+        Ok(Self { inner, signature })
     }
 
     pub fn chunk_production_key(&self) -> ChunkProductionKey {
@@ -4,7 +4,7 @@ use crate::block_body::SpiceCoreStatement;
 use crate::types::{
     ChunkExecutionResult, ChunkExecutionResultHash, SpiceChunkId, StaticSignatureDifferentiator,
 };
-use crate::validator_signer::ValidatorSigner;
+use crate::validator_signer::{SignatureKind, ValidatorSigner};
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_crypto::{PublicKey, Signature};
 use near_primitives_core::hash::CryptoHash;
@@ -29,17 +29,28 @@ impl SpiceChunkEndorsement {
         execution_result: ChunkExecutionResult,
         signer: &ValidatorSigner,
     ) -> SpiceChunkEndorsement {
+        Self::try_new(chunk_id, execution_result, signer)
+            .expect("failed to sign the chunk endorsement")
+    }
+
+    /// Like [`Self::new`], but returns the error of a remote signer failing to sign.
+    pub fn try_new(
+        chunk_id: SpiceChunkId,
+        execution_result: ChunkExecutionResult,
+        signer: &ValidatorSigner,
+    ) -> std::io::Result<SpiceChunkEndorsement> {
         let signed_data = SpiceEndorsementSignedData {
             execution_result_hash: execution_result.compute_hash(),
             chunk_id,
         };
-        let signature = signer.sign_bytes(&signed_data.serialize_data_for_signing());
-        Self::V1(SpiceChunkEndorsementV1 {
+        let signature = signer
+            .try_sign(SignatureKind::ChunkEndorsement, &signed_data.serialize_data_for_signing())?;
+        Ok(Self::V1(SpiceChunkEndorsementV1 {
             chunk_id: signed_data.chunk_id,
             account_id: signer.validator_id().clone(),
             signature,
             execution_result,
-        })
+        }))
     }
 
     pub fn account_id(&self) -> &AccountId {
@@ -939,34 +939,37 @@ impl TestBlockBuilder {
                 })
                 .collect(),
         );
-        Arc::new(Block::produce(
-            PROTOCOL_VERSION,
-            &self.prev_header,
-            self.height,
-            self.prev_header.block_ordinal() + 1,
-            self.chunks,
-            vec![vec![]; chunks_len],
-            self.epoch_id,
-            self.next_epoch_id,
-            None,
-            self.approvals,
-            num_rational::Ratio::new(0, 1),
-            Balance::ZERO,
-            self.max_gas_price,
-            Some(Balance::ZERO),
-            self.signer.as_ref(),
-            self.next_bp_hash,
-            self.block_merkle_root,
-            self.clock,
-            None,
-            None,
-            self.spice_core_statements.map(|core_statements| {
-                crate::block::SpiceNewBlockProductionInfo {
-                    core_statements,
-                    last_certified_block_execution_results,
-                }
-            }),
-        ))
+        Arc::new(
+            Block::produce(
+                PROTOCOL_VERSION,
+                &self.prev_header,
+                self.height,
+                self.prev_header.block_ordinal() + 1,
+                self.chunks,
+                vec![vec![]; chunks_len],
+                self.epoch_id,
+                self.next_epoch_id,
+                None,
+                self.approvals,
+                num_rational::Ratio::new(0, 1),
+                Balance::ZERO,
+                self.max_gas_price,
+                Some(Balance::ZERO),
+                self.signer.as_ref(),
+                self.next_bp_hash,
+                self.block_merkle_root,
+                self.clock,
+                None,
+                None,
+                self.spice_core_statements.map(|core_statements| {
+                    crate::block::SpiceNewBlockProductionInfo {
+                        core_statements,
+                        last_certified_block_execution_results,
+                    }
+                }),
+            )
+            .unwrap(),
+        )
     }
 }
 
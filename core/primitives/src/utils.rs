@@ -496,9 +496,10 @@ pub fn get_block_metadata(
     signer: &crate::validator_signer::ValidatorSigner,
     now: u64,
     sandbox_delta_time: Option<near_time::Duration>,
-) -> (u64, near_crypto::vrf::Value, near_crypto::vrf::Proof, CryptoHash) {
+) -> std::io::Result<(u64, near_crypto::vrf::Value, near_crypto::vrf::Proof, CryptoHash)> {
     #[cfg(feature = "sandbox")]
-    let now = now + sandbox_delta_time.unwrap().whole_nanoseconds() as u64;
+    // The delta is negative when the block timestamp was set to a time in the past.
+    let now = now.saturating_add_signed(sandbox_delta_time.unwrap().whole_nanoseconds() as i64);
     #[cfg(not(feature = "sandbox"))]
     debug_assert!(sandbox_delta_time.is_none());
     let time = if now <= prev_block_header.raw_timestamp() {
@@ -508,9 +509,9 @@ pub fn get_block_metadata(
     };
 
     let (vrf_value, vrf_proof) =
-        signer.compute_vrf_with_proof(prev_block_header.random_value().as_ref());
+        signer.try_compute_vrf_with_proof(prev_block_header.random_value().as_ref())?;
     let random_value = hash(vrf_value.0.as_ref());
-    (time, vrf_value, vrf_proof, random_value)
+    Ok((time, vrf_value, vrf_proof, random_value))
 }
 
 #[cfg(test)]
@@ -2,6 +2,7 @@ use std::fmt::Debug;
 use std::path::Path;
 use std::sync::Arc;
 
+use borsh::{BorshDeserialize, BorshSerialize};
 use near_crypto::{InMemorySigner, KeyType, PublicKey, Signature, Signer};
 
 use crate::types::AccountId;
@@ -13,6 +14,24 @@ pub enum ValidatorSigner {
     Empty(EmptyValidatorSigner),
     /// Default validator signer that holds data in memory.
     InMemory(InMemoryValidatorSigner),
+    /// Signer that delegates to a separate signing process holding the key.
+    Remote(RemoteValidatorSigner),
+}
+
+/// The kind of message a [`ValidatorSigner`] is asked to sign.
+///
+/// Signers holding the key in memory ignore it, but it allows a remote signer to apply its own
+/// policy (e.g. double signing protection) depending on what is being signed.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureKind {
+    Block,
+    OptimisticBlock,
+    Approval,
+    ChunkHeader,
+    ChunkEndorsement,
+    StateWitness,
+    /// Anything else, e.g. network messages.
+    Other,
 }
 
 /// Validator signer that is used to sign blocks and approvals.
@@ -22,6 +41,7 @@ impl ValidatorSigner {
         match self {
             ValidatorSigner::Empty(signer) => signer.validator_id(),
             ValidatorSigner::InMemory(signer) => signer.validator_id(),
+            ValidatorSigner::Remote(signer) => signer.validator_id(),
         }
     }
 
@@ -30,23 +50,54 @@ impl ValidatorSigner {
         match self {
             ValidatorSigner::Empty(signer) => signer.public_key(),
             ValidatorSigner::InMemory(signer) => signer.public_key(),
+            ValidatorSigner::Remote(signer) => signer.public_key(),
         }
     }
 
     pub fn sign_bytes(&self, data: &[u8]) -> Signature {
+        self.sign(SignatureKind::Other, data)
+    }
+
+    pub fn try_sign_bytes(&self, data: &[u8]) -> std::io::Result<Signature> {
+        self.try_sign(SignatureKind::Other, data)
+    }
+
+    /// Signs `data`, which is a message of the given `kind`.
+    ///
+    /// Panics if a remote signer fails to sign. Use [`Self::try_sign`] where the failure can be
+    /// handled, e.g. by skipping the production of a block or a chunk.
+    pub fn sign(&self, kind: SignatureKind, data: &[u8]) -> Signature {
+        self.try_sign(kind, data).unwrap_or_else(|err| panic!("failed to sign {kind:?}: {err}"))
+    }
+
+    /// Signs `data`, which is a message of the given `kind`.
+    ///
+    /// Only a remote signer can fail, e.g. when the signing process can't be reached.
+    pub fn try_sign(&self, kind: SignatureKind, data: &[u8]) -> std::io::Result<Signature> {
         match self {
-            ValidatorSigner::Empty(signer) => signer.noop_signature(),
-            ValidatorSigner::InMemory(signer) => signer.sign_bytes(data),
+            ValidatorSigner::Empty(signer) => Ok(signer.noop_signature()),
+            ValidatorSigner::InMemory(signer) => Ok(signer.sign_bytes(data)),
+            ValidatorSigner::Remote(signer) => signer.sign(kind, data),
         }
     }
 
+    /// Panics if a remote signer fails, see [`Self::try_compute_vrf_with_proof`].
     pub fn compute_vrf_with_proof(
         &self,
         data: &[u8],
     ) -> (near_crypto::vrf::Value, near_crypto::vrf::Proof) {
+        self.try_compute_vrf_with_proof(data)
+            .unwrap_or_else(|err| panic!("failed to compute vrf: {err}"))
+    }
+
+    pub fn try_compute_vrf_with_proof(
+        &self,
+        data: &[u8],
+    ) -> std::io::Result<(near_crypto::vrf::Value, near_crypto::vrf::Proof)> {
         match self {
             ValidatorSigner::Empty(_) => unimplemented!(),
-            ValidatorSigner::InMemory(signer) => signer.compute_vrf_with_proof(data),
+            ValidatorSigner::InMemory(signer) => Ok(signer.compute_vrf_with_proof(data)),
+            ValidatorSigner::Remote(signer) => signer.compute_vrf_with_proof(data),
         }
     }
 
@@ -55,6 +106,9 @@ impl ValidatorSigner {
         match self {
             ValidatorSigner::Empty(_) => unimplemented!(),
             ValidatorSigner::InMemory(signer) => signer.write_to_file(path),
+            ValidatorSigner::Remote(_) => {
+                Err(std::io::Error::other("the key of a remote signer is not available locally"))
+            }
         }
     }
 }
@@ -146,3 +200,65 @@ impl InMemoryValidatorSigner {
         self.signer.write_to_file(path)
     }
 }
+
+/// Client of a signing process that holds the validator key, see [`RemoteValidatorSigner`].
+///
+/// Implementations are expected to retry transient failures (e.g. reconnect to the signer)
+/// before returning an error.
+pub trait RemoteSigner: Send + Sync + Debug {
+    fn sign(&self, kind: SignatureKind, data: &[u8]) -> std::io::Result<Signature>;
+
+    fn compute_vrf_with_proof(
+        &self,
+        data: &[u8],
+    ) -> std::io::Result<(near_crypto::vrf::Value, near_crypto::vrf::Proof)>;
+}
+
+/// Signer that keeps the secret key in a separate process and asks it to sign over the
+/// [`RemoteSigner`] interface.
+///
+/// Signing happens synchronously. A failure to reach the signing process is returned by the
+/// fallible signing methods of [`ValidatorSigner`], and the node skips producing whatever needed
+/// the signature.
+#[derive(Clone, Debug)]
+pub struct RemoteValidatorSigner {
+    account_id: AccountId,
+    public_key: PublicKey,
+    signer: Arc<dyn RemoteSigner>,
+}
+
+impl PartialEq for RemoteValidatorSigner {
+    fn eq(&self, other: &Self) -> bool {
+        self.account_id == other.account_id && self.public_key == other.public_key
+    }
+}
+
+impl RemoteValidatorSigner {
+    /// `account_id` and `public_key` must be the ones the signing process is signing for.
+    pub fn new(
+        account_id: AccountId,
+        public_key: PublicKey,
+        signer: Arc<dyn RemoteSigner>,
+    ) -> ValidatorSigner {
+        ValidatorSigner::Remote(Self { account_id, public_key, signer })
+    }
+
+    pub fn validator_id(&self) -> &AccountId {
+        &self.account_id
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        self.public_key.clone()
+    }
+
+    fn sign(&self, kind: SignatureKind, data: &[u8]) -> std::io::Result<Signature> {
+        self.signer.sign(kind, data)
+    }
+
+    fn compute_vrf_with_proof(
+        &self,
+        data: &[u8],
+    ) -> std::io::Result<(near_crypto::vrf::Value, near_crypto::vrf::Proof)> {
+        self.signer.compute_vrf_with_proof(data)
+    }
+}
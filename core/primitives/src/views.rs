@@ -270,6 +270,9 @@ pub struct ViewStateResult {
 pub struct CallResult {
     pub result: Vec<u8>,
     pub logs: Vec<String>,
+    /// Host calls made by the contract, only set if requested in the query.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<FunctionCallTraceView>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
@@ -394,6 +397,10 @@ pub enum QueryRequest {
         method_name: String,
         #[serde(rename = "args_base64")]
         args: FunctionArgs,
+        /// Record the host calls made by the contract and return them in
+        /// [`CallResult::trace`].
+        #[serde(default, skip_serializing_if = "is_false")]
+        trace: bool,
     },
     ViewGlobalContractCode {
         code_hash: CryptoHash,
@@ -556,10 +563,22 @@ pub enum SyncStatusView {
 pub struct StateSyncStatusView {
     pub sync_hash: CryptoHash,
     pub shard_sync_status: HashMap<ShardId, String>,
+    #[serde(default)]
+    pub shard_progress: HashMap<ShardId, ShardSyncProgressView>,
     pub download_tasks: Vec<String>,
     pub computation_tasks: Vec<String>,
 }
 
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ShardSyncProgressView {
+    pub num_parts: u64,
+    pub parts_downloaded: u64,
+    pub parts_applied: u64,
+    pub parts_resumed: u64,
+    pub eta_secs: Option<u64>,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PeerStoreView {
@@ -660,6 +679,28 @@ pub struct RequestedStatePartsView {
     pub shard_requested_parts: HashMap<ShardId, Vec<PartElapsedTimeView>>,
 }
 
+/// A config field changed by a reload of the dynamically updatable configs.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ConfigFieldChangeView {
+    /// Path of the field, e.g. `network_config.max_num_peers`.
+    pub field: String,
+    /// JSON encoded value before the reload, if the field was present.
+    pub old_value: Option<String>,
+    /// JSON encoded value after the reload, if the field is present.
+    pub new_value: Option<String>,
+}
+
+/// Config fields changed by a single reload of the dynamically updatable configs.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ConfigReloadView {
+    #[serde(with = "near_time::serde_utc_as_iso")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    pub reload_time: Utc,
+    pub changes: Vec<ConfigFieldChangeView>,
+}
+
 /// Height and hash of a block
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
@@ -744,6 +785,72 @@ pub struct BlockProcessingInfo {
     pub chunks_info: Vec<Option<ChunkProcessingInfo>>,
 }
 
+/// Stage of the lifetime of a block, as observed by the node.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum BlockTimelineStage {
+    /// From the timestamp set by the block producer until the block was received.
+    Propagation,
+    /// The chunk was requested until all its parts needed by the node were received. Starts
+    /// when the chunk was completed if it was never requested.
+    ChunkPartsCollection,
+    /// From the chunk being completed until its first endorsement was received, i.e. the state
+    /// witness distribution and validation by the fastest chunk validator. Only observed by
+    /// block producers.
+    WitnessValidation,
+    /// From the first endorsement of the chunk until endorsements with enough stake were
+    /// received. Only observed by block producers.
+    EndorsementCollection,
+    /// The block waited in the orphan pool for its previous block.
+    Orphaned,
+    /// The block waited for its chunks to be completed.
+    WaitingForChunks,
+    /// The block waited for the execution of previous blocks.
+    PendingExecution,
+    /// The block was checked and prepared for applying its chunks.
+    Preprocessing,
+    /// The chunk of the shard was applied.
+    ApplyChunk,
+    /// The results of applying the chunks were saved and the block was accepted.
+    Postprocessing,
+    /// From the block being processed until approvals for it with enough stake were received.
+    /// Only observed by the block producer of the next height.
+    ApprovalQuorum,
+    /// Until a block making this block final was processed.
+    Finality,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct BlockTimelineStageView {
+    pub stage: BlockTimelineStage,
+    /// Shard of the stages which are tracked per shard.
+    pub shard_id: Option<ShardId>,
+    /// Start of the stage in milliseconds since the block was received, negative for the stages
+    /// which happened before.
+    pub start_ms: i64,
+    /// End of the stage in milliseconds since the block was received, None if the stage is
+    /// still in progress.
+    pub end_ms: Option<i64>,
+}
+
+/// Timestamps of the stages of processing a recent block, see `/debug/pages/block_timeline`.
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct BlockTimelineView {
+    pub hash: CryptoHash,
+    pub height: BlockHeight,
+    #[serde(with = "near_time::serde_utc_as_iso")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    pub received_timestamp: Utc,
+    pub block_status: BlockProcessingStatus,
+    /// All recorded stages, ordered by their start.
+    pub stages: Vec<BlockTimelineStageView>,
+    /// The chain of stages which determined the time of the last recorded stage, across all
+    /// shards. Ordered by time.
+    pub critical_path: Vec<BlockTimelineStageView>,
+}
+
 #[derive(
     BorshSerialize,
     BorshDeserialize,
@@ -2538,6 +2645,61 @@ pub struct CurrentEpochValidatorInfo {
     pub shards_endorsed: Vec<ShardId>,
 }
 
+/// Mid-epoch projection of validator performance, rewards and kickouts.
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ValidatorForecastView {
+    pub epoch_id: EpochId,
+    pub epoch_height: EpochHeight,
+    pub epoch_start_height: BlockHeight,
+    /// Height of the block the forecast was computed at.
+    pub block_height: BlockHeight,
+    /// Height of the last block of the epoch, assuming it is not extended.
+    pub expected_epoch_end_height: BlockHeight,
+    pub validators: Vec<ValidatorForecastItemView>,
+}
+
+/// Forecast for a single validator of the current epoch.
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ValidatorForecastItemView {
+    pub account_id: AccountId,
+    pub stake: Balance,
+    pub num_produced_blocks: NumBlocks,
+    pub num_expected_blocks: NumBlocks,
+    /// Blocks the validator is still expected to produce until the end of the epoch.
+    pub num_remaining_blocks: NumBlocks,
+    pub num_produced_chunks: NumBlocks,
+    pub num_expected_chunks: NumBlocks,
+    pub num_remaining_chunks: NumBlocks,
+    pub num_produced_endorsements: NumBlocks,
+    pub num_expected_endorsements: NumBlocks,
+    pub num_remaining_endorsements: NumBlocks,
+    /// Online ratio used for rewards, as of the forecast block.
+    pub online_ratio: f64,
+    /// Online ratio at the end of the epoch if all remaining duties are fulfilled.
+    pub best_case_online_ratio: f64,
+    /// Reward for the epoch if the validator keeps its current online ratio.
+    pub projected_reward: Balance,
+    /// Kickout the validator is on track for if it keeps its current online ratio.
+    pub projected_kickout: Option<ValidatorKickoutReason>,
+    /// Whether the validator is kicked out even if it fulfills all remaining duties.
+    pub kickout_unavoidable: bool,
+    /// Heights at which the validator did not produce its block.
+    pub missed_blocks: Vec<BlockHeight>,
+    /// Chunks the validator did not produce.
+    pub missed_chunks: Vec<MissedChunkView>,
+    /// Chunks the validator did not endorse.
+    pub missed_endorsements: Vec<MissedChunkView>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MissedChunkView {
+    pub height: BlockHeight,
+    pub shard_id: ShardId,
+}
+
 #[derive(
     BorshSerialize,
     BorshDeserialize,
@@ -2649,6 +2811,22 @@ pub enum StateChangesRequestView {
     },
 }
 
+impl StateChangesRequestView {
+    /// Accounts whose changes are requested.
+    pub fn account_ids(&self) -> Vec<AccountId> {
+        match self {
+            Self::AccountChanges { account_ids }
+            | Self::AllAccessKeyChanges { account_ids }
+            | Self::AllGasKeyChanges { account_ids }
+            | Self::ContractCodeChanges { account_ids }
+            | Self::DataChanges { account_ids, .. } => account_ids.clone(),
+            Self::SingleAccessKeyChanges { keys } | Self::SingleGasKeyChanges { keys } => {
+                keys.iter().map(|key| key.account_id.clone()).collect()
+            }
+        }
+    }
+}
+
 impl From<StateChangesRequestView> for StateChangesRequest {
     fn from(request: StateChangesRequestView) -> Self {
         match request {
@@ -2879,6 +3057,119 @@ impl From<StateChangeWithCause> for StateChangeWithCauseView {
 
 pub type StateChangesView = Vec<StateChangeWithCauseView>;
 
+/// Outcome of a transaction or a receipt executed during a dry-run simulation.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SimulatedOutcomeView {
+    /// Shard on which the transaction or the receipt was executed.
+    pub shard_id: ShardId,
+    /// Number of blocks after the transaction at which the receipt would be
+    /// executed. The transaction itself is executed in round 0.
+    pub round: u64,
+    pub id: CryptoHash,
+    pub outcome: ExecutionOutcomeView,
+}
+
+/// Result of a dry-run of a transaction on top of the latest final block.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SimulatedTransactionView {
+    /// Hash of the block on top of which the transaction was simulated.
+    pub block_hash: CryptoHash,
+    pub block_height: BlockHeight,
+    pub transaction_outcome: SimulatedOutcomeView,
+    /// Outcomes of all executed receipts, in execution order.
+    pub receipts_outcome: Vec<SimulatedOutcomeView>,
+    /// State changes the transaction would cause on all touched shards.
+    pub state_changes: StateChangesView,
+    /// Receipts that were produced but not simulated, either because their
+    /// receiver shard is not tracked by the node or because the simulation
+    /// reached one of its limits.
+    pub unprocessed_receipts: Vec<ReceiptView>,
+}
+
+/// Host calls made while re-executing a receipt.
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ReceiptTraceView {
+    pub receipt_id: CryptoHash,
+    /// Block in which the receipt was executed.
+    pub block_hash: CryptoHash,
+    pub shard_id: ShardId,
+    /// One entry per function call action of the receipt.
+    pub function_calls: Vec<FunctionCallTraceView>,
+}
+
+/// Host calls made by a single contract function call, in call order.
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct FunctionCallTraceView {
+    /// Receipt that made the call. Not set for view calls.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receipt_id: Option<CryptoHash>,
+    pub account_id: AccountId,
+    pub method_name: String,
+    pub host_calls: Vec<HostCallTraceView>,
+    /// Wasm frames that led to the failure of the call, innermost first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backtrace: Option<Vec<WasmFrameView>>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct HostCallTraceView {
+    /// Name of the host function.
+    pub name: String,
+    /// Raw arguments passed by the contract, by argument name.
+    pub args: Vec<HostCallArgView>,
+    /// Gas burnt by the host call itself.
+    pub burnt_gas: Gas,
+    /// Gas left after the host call returned.
+    pub gas_left: Gas,
+    pub events: Vec<HostCallEventView>,
+    /// Registers referenced by the arguments, as they were after the call.
+    pub registers: Vec<RegisterView>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct HostCallArgView {
+    pub name: String,
+    pub value: u64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RegisterView {
+    pub register_id: u64,
+    pub data: StoreValue,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HostCallEventView {
+    StorageRead { key: StoreKey, value: Option<StoreValue> },
+    StorageWrite { key: StoreKey, value: StoreValue, evicted: Option<StoreValue> },
+    StorageRemove { key: StoreKey, removed: Option<StoreValue> },
+    StorageHasKey { key: StoreKey, exists: bool },
+    Log { message: String },
+    PromiseCreated { promise_index: u64, receiver_id: AccountId },
+}
+
+/// A wasm frame of the backtrace of a failed contract call.
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct WasmFrameView {
+    /// Index of the function in the contract code.
+    pub function_index: u32,
+    /// Name of the function from the contract's `name` custom section.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function_name: Option<String>,
+}
+
 /// Maintenance windows view are a vector of maintenance window.
 pub type MaintenanceWindowsView = Vec<Range<BlockHeight>>;
 
@@ -0,0 +1,186 @@
+//! The node side of the protocol.
+
+use crate::config::RemoteSignerConfig;
+use crate::protocol::{
+    PROTOCOL_VERSION, Request, RequestBody, Response, read_message, write_message,
+};
+use crate::transport::{Connector, Stream};
+use near_crypto::{PublicKey, Signature};
+use near_primitives::validator_signer::{
+    RemoteSigner, RemoteValidatorSigner, SignatureKind, ValidatorSigner,
+};
+use parking_lot::Mutex;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Connects to the signing process described by `config` and returns a validator signer using it.
+///
+/// The account and the public key of the validator are provided by the signing process.
+pub fn connect(config: &RemoteSignerConfig, home_dir: &Path) -> io::Result<ValidatorSigner> {
+    let client = RemoteSignerClient::new(config, home_dir)?;
+    match client.request(RequestBody::GetValidatorInfo)? {
+        Response::ValidatorInfo { account_id, public_key } => {
+            tracing::info!(target: "remote_signer", %account_id, %public_key, "connected to remote signer");
+            Ok(RemoteValidatorSigner::new(
+                account_id,
+                public_key.clone(),
+                Arc::new(client.with_public_key(public_key)),
+            ))
+        }
+        response => Err(unexpected_response(&response)),
+    }
+}
+
+/// Client of the signing process, keeping a connection open and reconnecting as needed.
+///
+/// A request takes the idle connection for its duration, so concurrent requests (and the retry
+/// of a failing one) don't wait for each other but open connections of their own.
+pub struct RemoteSignerClient {
+    connector: Connector,
+    timeout: Duration,
+    /// Key signatures and VRF proofs are verified against, once known.
+    public_key: Option<PublicKey>,
+    idle_connection: Mutex<Option<Box<dyn Stream>>>,
+}
+
+impl std::fmt::Debug for RemoteSignerClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteSignerClient").field("public_key", &self.public_key).finish()
+    }
+}
+
+impl RemoteSignerClient {
+    pub fn new(config: &RemoteSignerConfig, home_dir: &Path) -> io::Result<Self> {
+        Ok(Self {
+            connector: Connector::new(&config.endpoint, home_dir)?,
+            timeout: config.request_timeout,
+            public_key: None,
+            idle_connection: Mutex::new(None),
+        })
+    }
+
+    /// Makes the client check the signatures and VRF proofs it receives against `public_key`.
+    fn with_public_key(mut self, public_key: PublicKey) -> Self {
+        self.public_key = Some(public_key);
+        self
+    }
+
+    /// Sends the request and waits for the response, blocking for at most the request timeout
+    /// in total.
+    ///
+    /// If the request fails on the connection kept from a previous request, which the signing
+    /// process may have closed in the meantime, it is retried once on a new connection within
+    /// the same time limit. Failures on a new connection, and [`Response::Error`]s, are returned
+    /// as errors right away.
+    pub fn request(&self, body: RequestBody) -> io::Result<Response> {
+        let request = Request { version: PROTOCOL_VERSION, body };
+        let deadline = Instant::now() + self.timeout;
+        // The lock is only held to take the connection, not while waiting for the response.
+        let mut connection = self.idle_connection.lock().take();
+        loop {
+            let reused = connection.is_some();
+            match self.try_request(connection.take(), &request, deadline) {
+                Ok((connection, response)) => {
+                    // Keep the connection for the next request, unless another one was kept
+                    // in the meantime.
+                    self.idle_connection.lock().get_or_insert(connection);
+                    return match response {
+                        Response::Error(message) => Err(io::Error::other(message)),
+                        response => Ok(response),
+                    };
+                }
+                Err(err) if reused => {
+                    tracing::debug!(target: "remote_signer", ?err, "idle connection to remote signer failed, reconnecting");
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Sends the request over `connection`, or a new connection if there is none, giving up
+    /// at `deadline`.
+    ///
+    /// The connection is dropped on failure, as it may be left in an inconsistent state.
+    fn try_request(
+        &self,
+        connection: Option<Box<dyn Stream>>,
+        request: &Request,
+        deadline: Instant,
+    ) -> io::Result<(Box<dyn Stream>, Response)> {
+        let mut stream = match connection {
+            Some(stream) => stream,
+            None => self.connector.connect(time_left(deadline)?)?,
+        };
+        stream.set_timeout(time_left(deadline)?)?;
+        write_message(&mut stream, request)?;
+        stream.set_timeout(time_left(deadline)?)?;
+        let response = read_message(&mut stream)?;
+        Ok((stream, response))
+    }
+}
+
+impl RemoteSigner for RemoteSignerClient {
+    fn sign(&self, kind: SignatureKind, data: &[u8]) -> io::Result<Signature> {
+        match self.request(RequestBody::Sign { kind, data: data.to_vec() })? {
+            Response::Signature(signature) => {
+                // A wrong signature would get the produced message rejected by everyone else, so
+                // better to catch a misconfigured signer here.
+                if let Some(public_key) = &self.public_key {
+                    if !signature.verify(data, public_key) {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "remote signer returned an invalid signature",
+                        ));
+                    }
+                }
+                Ok(signature)
+            }
+            response => Err(unexpected_response(&response)),
+        }
+    }
+
+    fn compute_vrf_with_proof(
+        &self,
+        data: &[u8],
+    ) -> io::Result<(near_crypto::vrf::Value, near_crypto::vrf::Proof)> {
+        match self.request(RequestBody::ComputeVrf { data: data.to_vec() })? {
+            Response::Vrf { value, proof } => {
+                // Like the signatures, an invalid VRF proof would get the produced block rejected.
+                if let Some(public_key) = &self.public_key {
+                    let vrf_key = match public_key {
+                        PublicKey::ED25519(key) => {
+                            near_crypto::key_conversion::convert_public_key(key)
+                        }
+                        PublicKey::SECP256K1(_) => None,
+                    };
+                    let valid =
+                        vrf_key.is_some_and(|vrf_key| vrf_key.is_vrf_valid(&data, &value, &proof));
+                    if !valid {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "remote signer returned an invalid VRF proof",
+                        ));
+                    }
+                }
+                Ok((value, proof))
+            }
+            response => Err(unexpected_response(&response)),
+        }
+    }
+}
+
+fn time_left(deadline: Instant) -> io::Result<Duration> {
+    deadline
+        .checked_duration_since(Instant::now())
+        .filter(|time_left| !time_left.is_zero())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "remote signer request timed out"))
+}
+
+fn unexpected_response(response: &Response) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("unexpected response from remote signer: {response:?}"),
+    )
+}
@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Where to reach the signing process and how long to wait for it.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct RemoteSignerConfig {
+    pub endpoint: RemoteSignerEndpoint,
+    /// How long a request to the signing process may take in total, including reconnecting.
+    ///
+    /// Requests block the calling thread, which for blocks and approvals is the thread producing
+    /// them, so this should stay well below the block production delay.
+    #[serde(default = "default_request_timeout")]
+    pub request_timeout: Duration,
+}
+
+fn default_request_timeout() -> Duration {
+    Duration::from_millis(500)
+}
+
+/// Transport used to communicate with the signing process.
+///
+/// Relative paths are interpreted relative to the home directory of the node (or the working
+/// directory of the signing daemon).
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "transport", rename_all = "snake_case")]
+pub enum RemoteSignerEndpoint {
+    /// Unix domain socket; access is controlled by the file system permissions of the socket.
+    Unix { path: PathBuf },
+    /// TCP connection with mutual TLS authentication.
+    Tls {
+        /// Address to connect to (or to listen on, for the signing daemon).
+        address: String,
+        /// Name the certificate of the signing daemon is expected to be issued for.
+        server_name: String,
+        /// PEM file with the certificate authorities trusted to issue the peer's certificate.
+        ca_cert_file: PathBuf,
+        /// PEM file with this side's certificate chain.
+        cert_file: PathBuf,
+        /// PEM file with this side's private key.
+        key_file: PathBuf,
+    },
+}
@@ -0,0 +1,19 @@
+//! Support for keeping the validator key in a separate signing process.
+//!
+//! The node connects to the signing process over a Unix socket or a mutually authenticated TLS
+//! connection and asks it to sign blocks, approvals, chunks, chunk endorsements and state
+//! witnesses on its behalf, see [`protocol`] for the wire format. The [`client`] implements the
+//! [`near_primitives::validator_signer::RemoteSigner`] interface for the node, while the
+//! [`server`] is the basis of the reference `near-remote-signer` daemon.
+
+pub mod client;
+pub mod config;
+pub mod protocol;
+pub mod server;
+mod transport;
+
+pub use client::connect;
+pub use config::{RemoteSignerConfig, RemoteSignerEndpoint};
+
+#[cfg(test)]
+mod tests;
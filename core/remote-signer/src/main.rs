@@ -0,0 +1,66 @@
+//! Reference signing daemon holding a validator key on behalf of a node.
+//!
+//! Configure the node with a matching `remote_signer` section in its `config.json` and remove the
+//! `validator_key.json` from its home directory.
+
+use near_crypto::InMemorySigner;
+use near_remote_signer::RemoteSignerEndpoint;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[derive(clap::Parser)]
+struct Cli {
+    /// Validator key file, in the same format as the `validator_key.json` of a node.
+    #[clap(long)]
+    key_file: PathBuf,
+    #[clap(subcommand)]
+    transport: Transport,
+}
+
+#[derive(clap::Subcommand)]
+enum Transport {
+    /// Listen on a Unix socket, accessible only by the user running the signer.
+    Unix {
+        #[clap(long)]
+        path: PathBuf,
+    },
+    /// Listen on a TCP address, requiring the clients to authenticate with a TLS certificate.
+    Tls {
+        #[clap(long)]
+        address: String,
+        /// Certificate authorities trusted to issue the client (node) certificates.
+        #[clap(long)]
+        ca_cert_file: PathBuf,
+        /// Certificate chain of the signer.
+        #[clap(long)]
+        cert_file: PathBuf,
+        /// Private key of the signer's certificate.
+        #[clap(long)]
+        key_file: PathBuf,
+    },
+}
+
+fn main() -> anyhow::Result<()> {
+    let env_filter = near_o11y::EnvFilterBuilder::from_env().finish()?;
+    let _subscriber = near_o11y::default_subscriber(env_filter, &Default::default()).global();
+    let cli: Cli = clap::Parser::parse();
+
+    let signer = InMemorySigner::from_file(&cli.key_file).map_err(|err| {
+        anyhow::anyhow!("failed to load the key from {}: {err}", cli.key_file.display())
+    })?;
+    let endpoint = match cli.transport {
+        Transport::Unix { path } => RemoteSignerEndpoint::Unix { path },
+        Transport::Tls { address, ca_cert_file, cert_file, key_file } => {
+            RemoteSignerEndpoint::Tls {
+                address,
+                // Only used when connecting.
+                server_name: String::new(),
+                ca_cert_file,
+                cert_file,
+                key_file,
+            }
+        }
+    };
+    near_remote_signer::server::serve(&endpoint, &std::env::current_dir()?, Arc::new(signer))?;
+    Ok(())
+}
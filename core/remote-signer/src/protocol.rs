@@ -0,0 +1,72 @@
+//! Wire format of the communication with the signing process.
+//!
+//! Each message is a little endian `u32` length followed by that many bytes of a borsh-encoded
+//! [`Request`] or [`Response`]. The node sends a request and waits for the response before sending
+//! the next one on the same connection.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_crypto::{PublicKey, Signature};
+use near_primitives::types::AccountId;
+use near_primitives::validator_signer::SignatureKind;
+use std::io::{self, Read, Write};
+
+/// Version of the protocol, included in every request.
+///
+/// The signing process rejects requests with a version it does not know.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Maximum size of a single encoded message.
+pub const MAX_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct Request {
+    pub version: u32,
+    pub body: RequestBody,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub enum RequestBody {
+    /// Asks for the account and the public key the signing process signs for.
+    GetValidatorInfo,
+    /// Asks to sign `data`, which is a message of the given `kind`.
+    Sign { kind: SignatureKind, data: Vec<u8> },
+    /// Asks to compute the VRF value and proof used to produce a block.
+    ComputeVrf { data: Vec<u8> },
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub enum Response {
+    ValidatorInfo {
+        account_id: AccountId,
+        public_key: PublicKey,
+    },
+    Signature(Signature),
+    Vrf {
+        value: near_crypto::vrf::Value,
+        proof: near_crypto::vrf::Proof,
+    },
+    /// The request was refused or could not be processed.
+    Error(String),
+}
+
+pub fn write_message<T: BorshSerialize>(writer: &mut impl Write, message: &T) -> io::Result<()> {
+    let bytes = borsh::to_vec(message)?;
+    if bytes.len() > MAX_MESSAGE_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "message is too large"));
+    }
+    writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    writer.write_all(&bytes)?;
+    writer.flush()
+}
+
+pub fn read_message<T: BorshDeserialize>(reader: &mut impl Read) -> io::Result<T> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_MESSAGE_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "message is too large"));
+    }
+    let mut bytes = vec![0u8; len];
+    reader.read_exact(&mut bytes)?;
+    borsh::from_slice(&bytes)
+}
@@ -0,0 +1,95 @@
+//! The signing process side of the protocol.
+
+use crate::config::RemoteSignerEndpoint;
+use crate::protocol::{
+    PROTOCOL_VERSION, Request, RequestBody, Response, read_message, write_message,
+};
+use crate::transport::{Listener, Stream};
+use near_crypto::Signer;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Maximum number of connections served at the same time. Further connections are closed
+/// right away.
+const MAX_CONNECTIONS: usize = 64;
+/// Connections are closed once a read or write blocks for this long, e.g. when the node keeps an
+/// idle connection open, or when a client never completes the TLS handshake.
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(30);
+/// Delay before accepting again after failing to accept a connection, so that a persistent
+/// error (e.g. running out of file descriptors) doesn't make us spin.
+const ACCEPT_ERROR_DELAY: Duration = Duration::from_millis(100);
+
+/// Answers a single request using the key held by `signer`.
+///
+/// This signs anything it is asked to; a production signer may want to refuse signing e.g.
+/// conflicting blocks or approvals, using the [`near_primitives::validator_signer::SignatureKind`]
+/// of the request.
+pub fn handle_request(signer: &Signer, request: Request) -> Response {
+    if request.version != PROTOCOL_VERSION {
+        return Response::Error(format!(
+            "unsupported protocol version {}, expected {PROTOCOL_VERSION}",
+            request.version
+        ));
+    }
+    match request.body {
+        RequestBody::GetValidatorInfo => Response::ValidatorInfo {
+            account_id: signer.get_account_id(),
+            public_key: signer.public_key(),
+        },
+        RequestBody::Sign { kind, data } => {
+            tracing::debug!(target: "remote_signer", ?kind, len = data.len(), "signing");
+            Response::Signature(signer.sign(&data))
+        }
+        RequestBody::ComputeVrf { data } => {
+            let (value, proof) = signer.compute_vrf_with_proof(&data);
+            Response::Vrf { value, proof }
+        }
+    }
+}
+
+/// Serves requests on `endpoint` forever, returning only if listening on it fails.
+///
+/// Each connection is handled on its own thread, up to [`MAX_CONNECTIONS`] at a time.
+pub fn serve(
+    endpoint: &RemoteSignerEndpoint,
+    base_dir: &Path,
+    signer: Arc<Signer>,
+) -> io::Result<()> {
+    let listener = Listener::bind(endpoint, base_dir)?;
+    tracing::info!(target: "remote_signer", ?endpoint, account_id = %signer.get_account_id(), "listening for signing requests");
+    let connections = Arc::new(AtomicUsize::new(0));
+    loop {
+        let stream = match listener.accept(CONNECTION_TIMEOUT) {
+            Ok(stream) => stream,
+            Err(err) => {
+                tracing::warn!(target: "remote_signer", ?err, "failed to accept a connection");
+                std::thread::sleep(ACCEPT_ERROR_DELAY);
+                continue;
+            }
+        };
+        if connections.fetch_add(1, Ordering::Relaxed) >= MAX_CONNECTIONS {
+            connections.fetch_sub(1, Ordering::Relaxed);
+            tracing::warn!(target: "remote_signer", "too many connections, closing the new one");
+            continue;
+        }
+        let signer = Arc::clone(&signer);
+        let connections = Arc::clone(&connections);
+        std::thread::spawn(move || {
+            if let Err(err) = handle_connection(stream, &signer) {
+                tracing::debug!(target: "remote_signer", ?err, "connection closed");
+            }
+            connections.fetch_sub(1, Ordering::Relaxed);
+        });
+    }
+}
+
+fn handle_connection(mut stream: Box<dyn Stream>, signer: &Signer) -> io::Result<()> {
+    loop {
+        let request: Request = read_message(&mut stream)?;
+        let response = handle_request(signer, request);
+        write_message(&mut stream, &response)?;
+    }
+}
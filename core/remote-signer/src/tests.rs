@@ -0,0 +1,121 @@
+use crate::client::RemoteSignerClient;
+use crate::config::{RemoteSignerConfig, RemoteSignerEndpoint};
+use crate::protocol::{Request, RequestBody, Response};
+use crate::server::handle_request;
+use crate::transport::Listener;
+use near_crypto::{InMemorySigner, KeyType, Signer};
+use near_primitives::validator_signer::SignatureKind;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+fn test_signer() -> Signer {
+    InMemorySigner::from_seed("test0".parse().unwrap(), KeyType::ED25519, "test0")
+}
+
+/// Answers the requests of a single connection accepted on `listener`.
+fn serve_one_connection(listener: Listener, signer: Arc<Signer>) {
+    std::thread::spawn(move || {
+        let mut stream = listener.accept(Duration::from_secs(10)).unwrap();
+        loop {
+            let Ok(request) = crate::protocol::read_message(&mut stream) else { break };
+            let response = handle_request(&signer, request);
+            crate::protocol::write_message(&mut stream, &response).unwrap();
+        }
+    });
+}
+
+/// Checks that the node gets correct signatures and VRF proofs over `endpoint`.
+fn check_round_trip(endpoint: RemoteSignerEndpoint, base_dir: &Path, signer: &Signer) {
+    let config = RemoteSignerConfig { endpoint, request_timeout: Duration::from_secs(10) };
+    let validator_signer = crate::connect(&config, base_dir).unwrap();
+    assert_eq!(validator_signer.validator_id(), &signer.get_account_id());
+    assert_eq!(validator_signer.public_key(), signer.public_key());
+
+    let data = b"block hash";
+    let signature = validator_signer.try_sign(SignatureKind::Block, data).unwrap();
+    assert!(signature.verify(data, &signer.public_key()));
+    assert_eq!(
+        validator_signer.try_compute_vrf_with_proof(data).unwrap().0,
+        signer.compute_vrf_with_proof(data).0
+    );
+}
+
+#[test]
+fn test_sign_over_unix_socket() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let endpoint = RemoteSignerEndpoint::Unix { path: "signer.sock".into() };
+    let signer = Arc::new(test_signer());
+    let listener = Listener::bind(&endpoint, dir.path()).unwrap();
+    let mode = std::fs::metadata(dir.path().join("signer.sock")).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    serve_one_connection(listener, Arc::clone(&signer));
+
+    check_round_trip(endpoint, dir.path(), &signer);
+}
+
+#[test]
+fn test_sign_over_tls() {
+    // The certificates are issued for `localhost` by a test authority, see `res/test`.
+    let base_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("res/test");
+    let endpoint = |address: String, cert: &str| RemoteSignerEndpoint::Tls {
+        address,
+        server_name: "localhost".to_string(),
+        ca_cert_file: "ca.pem".into(),
+        cert_file: format!("{cert}.pem").into(),
+        key_file: format!("{cert}.key").into(),
+    };
+    let signer = Arc::new(test_signer());
+    let listener =
+        Listener::bind(&endpoint("127.0.0.1:0".to_string(), "signer"), &base_dir).unwrap();
+    let Listener::Tls { listener: tcp_listener, .. } = &listener else { unreachable!() };
+    let address = tcp_listener.local_addr().unwrap().to_string();
+    serve_one_connection(listener, Arc::clone(&signer));
+
+    check_round_trip(endpoint(address, "node"), &base_dir, &signer);
+}
+
+#[test]
+fn test_sign_fails_without_signer() {
+    let dir = tempfile::tempdir().unwrap();
+    let endpoint = RemoteSignerEndpoint::Unix { path: "signer.sock".into() };
+    let signer = test_signer();
+    let listener = Listener::bind(&endpoint, dir.path()).unwrap();
+    // Only answer the request made while connecting, then go away.
+    let server = std::thread::spawn(move || {
+        let mut stream = listener.accept(Duration::from_secs(10)).unwrap();
+        let request = crate::protocol::read_message(&mut stream).unwrap();
+        crate::protocol::write_message(&mut stream, &handle_request(&signer, request)).unwrap();
+    });
+
+    let config = RemoteSignerConfig { endpoint, request_timeout: Duration::from_secs(10) };
+    let validator_signer = crate::connect(&config, dir.path()).unwrap();
+    server.join().unwrap();
+    assert!(validator_signer.try_sign(SignatureKind::Block, b"block hash").is_err());
+    assert!(validator_signer.try_compute_vrf_with_proof(b"block hash").is_err());
+}
+
+#[test]
+fn test_request_time_is_bounded() {
+    let dir = tempfile::tempdir().unwrap();
+    let endpoint = RemoteSignerEndpoint::Unix { path: "signer.sock".into() };
+    let listener = Listener::bind(&endpoint, dir.path()).unwrap();
+    // Accept the connection and keep it open, but never answer.
+    let server = std::thread::spawn(move || listener.accept(Duration::from_secs(10)).unwrap());
+
+    let request_timeout = Duration::from_millis(200);
+    let config = RemoteSignerConfig { endpoint, request_timeout };
+    let client = RemoteSignerClient::new(&config, dir.path()).unwrap();
+    let start = Instant::now();
+    assert!(client.request(RequestBody::GetValidatorInfo).is_err());
+    assert!(start.elapsed() < 5 * request_timeout);
+    drop(server.join().unwrap());
+}
+
+#[test]
+fn test_unsupported_protocol_version() {
+    let request = Request { version: 0, body: RequestBody::GetValidatorInfo };
+    assert!(matches!(handle_request(&test_signer(), request), Response::Error(_)));
+}
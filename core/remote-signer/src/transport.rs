@@ -0,0 +1,195 @@
+//! Connection establishment for the [`RemoteSignerEndpoint`]s, on both the node and the signing
+//! process side.
+
+use crate::config::RemoteSignerEndpoint;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A connection to the other side, whatever the transport.
+pub(crate) trait Stream: Read + Write + Send {
+    /// Sets how long each read from or write to the connection may block.
+    fn set_timeout(&self, timeout: Duration) -> io::Result<()>;
+}
+
+impl Stream for UnixStream {
+    fn set_timeout(&self, timeout: Duration) -> io::Result<()> {
+        self.set_read_timeout(Some(timeout))?;
+        self.set_write_timeout(Some(timeout))
+    }
+}
+
+impl Stream for TcpStream {
+    fn set_timeout(&self, timeout: Duration) -> io::Result<()> {
+        self.set_read_timeout(Some(timeout))?;
+        self.set_write_timeout(Some(timeout))
+    }
+}
+
+impl Stream for rustls::StreamOwned<rustls::ClientConnection, TcpStream> {
+    fn set_timeout(&self, timeout: Duration) -> io::Result<()> {
+        self.sock.set_timeout(timeout)
+    }
+}
+
+impl Stream for rustls::StreamOwned<rustls::ServerConnection, TcpStream> {
+    fn set_timeout(&self, timeout: Duration) -> io::Result<()> {
+        self.sock.set_timeout(timeout)
+    }
+}
+
+/// Everything needed to connect to the signing process.
+pub(crate) enum Connector {
+    Unix { path: std::path::PathBuf },
+    Tls { address: String, server_name: ServerName<'static>, config: Arc<rustls::ClientConfig> },
+}
+
+impl Connector {
+    /// Loads the certificates and keys of the endpoint, with relative paths resolved against
+    /// `base_dir`.
+    pub fn new(endpoint: &RemoteSignerEndpoint, base_dir: &Path) -> io::Result<Self> {
+        Ok(match endpoint {
+            RemoteSignerEndpoint::Unix { path } => Self::Unix { path: base_dir.join(path) },
+            RemoteSignerEndpoint::Tls {
+                address,
+                server_name,
+                ca_cert_file,
+                cert_file,
+                key_file,
+            } => {
+                let roots = load_root_store(&base_dir.join(ca_cert_file))?;
+                let config = rustls::ClientConfig::builder()
+                    .with_root_certificates(roots)
+                    .with_client_auth_cert(
+                        load_certs(&base_dir.join(cert_file))?,
+                        load_key(&base_dir.join(key_file))?,
+                    )
+                    .map_err(io::Error::other)?;
+                let server_name = ServerName::try_from(server_name.clone())
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+                Self::Tls { address: address.clone(), server_name, config: Arc::new(config) }
+            }
+        })
+    }
+
+    pub fn connect(&self, timeout: Duration) -> io::Result<Box<dyn Stream>> {
+        match self {
+            Self::Unix { path } => {
+                let stream = UnixStream::connect(path)?;
+                stream.set_timeout(timeout)?;
+                Ok(Box::new(stream))
+            }
+            Self::Tls { address, server_name, config } => {
+                let address = address.to_socket_addrs()?.next().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "address did not resolve")
+                })?;
+                let stream = TcpStream::connect_timeout(&address, timeout)?;
+                stream.set_timeout(timeout)?;
+                stream.set_nodelay(true)?;
+                let connection =
+                    rustls::ClientConnection::new(Arc::clone(config), server_name.clone())
+                        .map_err(io::Error::other)?;
+                Ok(Box::new(rustls::StreamOwned::new(connection, stream)))
+            }
+        }
+    }
+}
+
+/// Listening side of an endpoint, used by the signing process.
+pub(crate) enum Listener {
+    Unix(UnixListener),
+    Tls { listener: TcpListener, config: Arc<rustls::ServerConfig> },
+}
+
+impl Listener {
+    /// Starts listening on the endpoint, with relative paths resolved against `base_dir`.
+    ///
+    /// A stale Unix socket left behind by a previous run is replaced. The new socket is only
+    /// accessible by the owner: it is created in a private directory and only moved into place
+    /// once its permissions are restricted, so nobody else can connect to it in the meantime.
+    pub fn bind(endpoint: &RemoteSignerEndpoint, base_dir: &Path) -> io::Result<Self> {
+        match endpoint {
+            RemoteSignerEndpoint::Unix { path } => {
+                use std::os::unix::fs::PermissionsExt;
+                let path = base_dir.join(path);
+                let parent = match path.parent() {
+                    Some(parent) if !parent.as_os_str().is_empty() => parent,
+                    _ => Path::new("."),
+                };
+                // Temporary directories are only accessible by the owner.
+                let private_dir = tempfile::Builder::new().prefix(".signer").tempdir_in(parent)?;
+                let private_path = private_dir.path().join("socket");
+                let listener = UnixListener::bind(&private_path)?;
+                std::fs::set_permissions(&private_path, std::fs::Permissions::from_mode(0o600))?;
+                // Renaming replaces a stale socket left behind by a previous run.
+                std::fs::rename(&private_path, &path)?;
+                Ok(Self::Unix(listener))
+            }
+            RemoteSignerEndpoint::Tls { address, ca_cert_file, cert_file, key_file, .. } => {
+                let roots = load_root_store(&base_dir.join(ca_cert_file))?;
+                let verifier = rustls::server::WebPkiClientVerifier::builder(Arc::new(roots))
+                    .build()
+                    .map_err(io::Error::other)?;
+                let config = rustls::ServerConfig::builder()
+                    .with_client_cert_verifier(verifier)
+                    .with_single_cert(
+                        load_certs(&base_dir.join(cert_file))?,
+                        load_key(&base_dir.join(key_file))?,
+                    )
+                    .map_err(io::Error::other)?;
+                let listener = TcpListener::bind(address)?;
+                Ok(Self::Tls { listener, config: Arc::new(config) })
+            }
+        }
+    }
+
+    /// Accepts a connection, on which reads and writes time out after `timeout`.
+    ///
+    /// The TLS handshake only happens on the first read or write, so the timeout also applies
+    /// to connections that never authenticate.
+    pub fn accept(&self, timeout: Duration) -> io::Result<Box<dyn Stream>> {
+        match self {
+            Self::Unix(listener) => {
+                let (stream, _) = listener.accept()?;
+                stream.set_timeout(timeout)?;
+                Ok(Box::new(stream))
+            }
+            Self::Tls { listener, config } => {
+                let (stream, _) = listener.accept()?;
+                stream.set_timeout(timeout)?;
+                stream.set_nodelay(true)?;
+                let connection =
+                    rustls::ServerConnection::new(Arc::clone(config)).map_err(io::Error::other)?;
+                Ok(Box::new(rustls::StreamOwned::new(connection, stream)))
+            }
+        }
+    }
+}
+
+fn load_certs(path: &Path) -> io::Result<Vec<CertificateDer<'static>>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    rustls_pemfile::certs(&mut reader).collect()
+}
+
+fn load_key(path: &Path) -> io::Result<PrivateKeyDer<'static>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    rustls_pemfile::private_key(&mut reader)?.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("no private key found in {}", path.display()),
+        )
+    })
+}
+
+fn load_root_store(path: &Path) -> io::Result<rustls::RootCertStore> {
+    let mut roots = rustls::RootCertStore::empty();
+    for cert in load_certs(path)? {
+        roots.add(cert).map_err(io::Error::other)?;
+    }
+    Ok(roots)
+}
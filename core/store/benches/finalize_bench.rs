@@ -116,35 +116,41 @@ fn create_benchmark_receipts() -> Vec<Receipt> {
 
 fn create_chunk_header(height: u64, shard_id: ShardId) -> ShardChunkHeader {
     if ProtocolFeature::Spice.enabled(PROTOCOL_VERSION) {
-        ShardChunkHeader::V3(ShardChunkHeaderV3::new_for_spice(
-            CryptoHash::default(),
-            CryptoHash::default(),
-            1,
-            height,
-            shard_id,
-            CryptoHash::default(),
-            CryptoHash::default(),
-            &validator_signer(),
-        ))
+        ShardChunkHeader::V3(
+            ShardChunkHeaderV3::new_for_spice(
+                CryptoHash::default(),
+                CryptoHash::default(),
+                1,
+                height,
+                shard_id,
+                CryptoHash::default(),
+                CryptoHash::default(),
+                &validator_signer(),
+            )
+            .unwrap(),
+        )
     } else {
-        ShardChunkHeader::V3(ShardChunkHeaderV3::new(
-            CryptoHash::default(),
-            CryptoHash::default(),
-            CryptoHash::default(),
-            CryptoHash::default(),
-            1,
-            height,
-            shard_id,
-            Gas::ZERO,
-            Gas::ZERO,
-            Balance::ZERO,
-            CryptoHash::default(),
-            CryptoHash::default(),
-            vec![],
-            Default::default(),
-            BandwidthRequests::empty(),
-            &validator_signer(),
-        ))
+        ShardChunkHeader::V3(
+            ShardChunkHeaderV3::new(
+                CryptoHash::default(),
+                CryptoHash::default(),
+                CryptoHash::default(),
+                CryptoHash::default(),
+                1,
+                height,
+                shard_id,
+                Gas::ZERO,
+                Gas::ZERO,
+                Balance::ZERO,
+                CryptoHash::default(),
+                CryptoHash::default(),
+                vec![],
+                Default::default(),
+                BandwidthRequests::empty(),
+                &validator_signer(),
+            )
+            .unwrap(),
+        )
     }
 }
 
@@ -209,6 +215,7 @@ fn create_encoded_shard_chunk(
             &validator_signer(),
             &rs,
         )
+        .unwrap()
     } else {
         ShardChunkWithEncoding::new(
             Default::default(),
@@ -229,6 +236,7 @@ fn create_encoded_shard_chunk(
             &validator_signer(),
             &rs,
         )
+        .unwrap()
     }
 }
 
@@ -1,3 +1,5 @@
+use near_primitives::block::Block;
+use near_primitives::sharding::ShardChunk;
 use near_primitives::types::{BlockHeight, ShardId};
 
 use borsh::BorshDeserialize;
@@ -35,6 +37,26 @@ impl CloudStorage {
         self.retrieve(&CloudStorageFileID::Head).await
     }
 
+    /// Returns the block at the given height from the cloud archive.
+    pub async fn retrieve_block(
+        &self,
+        block_height: BlockHeight,
+    ) -> Result<Block, CloudRetrievalError> {
+        let block_data = self.retrieve_block_data(block_height).await?;
+        Ok(block_data.get_block().clone())
+    }
+
+    /// Returns the chunk of the given shard included in the block at the given
+    /// height from the cloud archive.
+    pub async fn retrieve_chunk(
+        &self,
+        block_height: BlockHeight,
+        shard_id: ShardId,
+    ) -> Result<ShardChunk, CloudRetrievalError> {
+        let shard_data = self.retrieve_shard_data(block_height, shard_id).await?;
+        Ok(shard_data.get_chunk().clone())
+    }
+
     pub(super) async fn retrieve_block_data(
         &self,
         block_height: BlockHeight,
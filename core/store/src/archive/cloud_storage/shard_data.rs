@@ -149,7 +149,6 @@ fn get_state_changes(
 }
 
 impl ShardData {
-    #[allow(unused)]
     pub fn get_chunk(&self) -> &ShardChunk {
         match self {
             ShardData::V1(data) => &data.chunk,
@@ -333,6 +333,20 @@ pub enum DBCol {
     /// - *Rows*: BlockShardId (BlockHash || ShardId) - 40 bytes
     /// - *Column type*: `ChunkApplyStats`
     ChunkApplyStats,
+    /// Index of the data kept beyond the regular garbage collection because it
+    /// touches one of the accounts configured in `GCConfig::retained_accounts`.
+    /// Used to garbage collect that data once its extended retention expires.
+    /// The height is big-endian so that the rows are ordered by height.
+    /// - *Rows*: (BlockHeight || BlockHash)
+    /// - *Column type*: `RetainedBlockData`
+    RetainedAccountData,
+    /// Receipts caused by transactions or receipts touching the retained
+    /// accounts whose execution outcomes are not garbage collected yet. The
+    /// outcomes of these receipts are retained as well, so that the full result
+    /// of a retained transaction stays available.
+    /// - *Rows*: ReceiptHash (CryptoHash)
+    /// - *Column type*: empty
+    RetainedReceiptIds,
     /// Mapping from Block Hash + Target Shard Id + Source Shard Id to Receipt Proof.
     /// The receipts result from applying the chunk on the source shard of the corresponding block.
     /// The key includes the target shard first to enable prefix queries for retrieving all incoming
@@ -602,6 +616,9 @@ impl DBCol {
             | DBCol::EpochSyncProof
             | DBCol::StateSyncHashes
             | DBCol::StateSyncNewChunks => false,
+            // Data of the retained accounts is GC-ed separately and is only
+            // needed in the hot storage, cold storage keeps all of it anyway.
+            DBCol::RetainedAccountData | DBCol::RetainedReceiptIds => false,
         }
     }
 
@@ -682,6 +699,8 @@ impl DBCol {
             DBCol::StateSyncHashes => &[DBKeyType::EpochId],
             DBCol::StateSyncNewChunks => &[DBKeyType::BlockHash],
             DBCol::ChunkApplyStats => &[DBKeyType::BlockHash, DBKeyType::ShardId],
+            DBCol::RetainedAccountData => &[DBKeyType::BlockHeight, DBKeyType::BlockHash],
+            DBCol::RetainedReceiptIds => &[DBKeyType::ReceiptHash],
             #[cfg(feature = "protocol_feature_spice")]
             DBCol::ReceiptProofs => &[DBKeyType::BlockHash, DBKeyType::ShardId, DBKeyType::ShardId],
             #[cfg(feature = "protocol_feature_spice")]
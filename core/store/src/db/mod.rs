@@ -47,6 +47,7 @@ pub const CLOUD_HEAD_KEY: &[u8] = b"CLOUD_HEAD";
 pub const TRIE_STATE_RESHARDING_STATUS_KEY: &[u8] = b"TRIE_STATE_RESHARDING_STATUS";
 pub const LATEST_WITNESSES_INFO: &[u8] = b"LATEST_WITNESSES_INFO";
 pub const INVALID_WITNESSES_INFO: &[u8] = b"INVALID_WITNESSES_INFO";
+pub const STATE_SYNC_PROGRESS_KEY: &[u8] = b"STATE_SYNC_PROGRESS";
 
 #[derive(Default, Debug)]
 pub struct DBTransaction {
@@ -8,6 +8,7 @@ use near_async::time::{Clock, Duration, Utc};
 use near_chain::rayon_spawner::RayonAsyncComputationSpawner;
 use near_chain::resharding::resharding_actor::ReshardingActor;
 use near_chain::resharding::types::ReshardingSender;
+use near_chain::signing_history::SigningHistory;
 use near_chain::state_snapshot_actor::SnapshotCallbacks;
 use near_chain::types::{ChainConfig, RuntimeAdapter};
 use near_chain::{ApplyChunksIterationMode, Chain, ChainGenesis, DoomslugThresholdMode};
@@ -230,11 +231,12 @@ fn setup(
             spice_data_distributor_sender: noop().into_sender(),
             spice_core_writer_sender: noop().into_sender(),
         },
+        SigningHistory::disabled(),
     );
 
     let rpc_handler_config = RpcHandlerConfig {
         handler_threads: config.transaction_request_handler_threads,
-        tx_routing_height_horizon: config.tx_routing_height_horizon,
+        tx_routing_height_horizon: config.tx_routing_height_horizon.clone(),
         epoch_length: config.epoch_length,
         transaction_validity_period,
         disable_tx_routing: config.disable_tx_routing,
@@ -505,6 +507,7 @@ pub fn setup_client_with_runtime(
         noop().into_multi_sender(), // apply chunks ping not necessary for these tests
         chunk_validation_sender,
         protocol_upgrade_schedule,
+        SigningHistory::disabled(),
     )
     .unwrap();
     client.sync_handler.sync_status = SyncStatus::NoSync;
@@ -519,6 +522,7 @@ pub fn setup_client_with_runtime(
         runtime,
         network_adapter.into_sender(),
         validator_signer,
+        client.signing_history.clone(),
         false,
         false,
         Arc::new(RayonAsyncComputationSpawner),
@@ -609,7 +613,7 @@ pub fn setup_tx_request_handler(
     });
     let config = RpcHandlerConfig {
         handler_threads: 1,
-        tx_routing_height_horizon: client_config.tx_routing_height_horizon,
+        tx_routing_height_horizon: client_config.tx_routing_height_horizon.clone(),
         epoch_length: chain_genesis.epoch_length,
         transaction_validity_period: chain_genesis.transaction_validity_period,
         disable_tx_routing: client_config.disable_tx_routing,
@@ -80,6 +80,7 @@ fn test_block_sync() {
         block_fetch_horizon,
         false,
         true,
+        None,
     );
     let mut env = test_env_with_epoch_length(100);
     let mut blocks = vec![];
@@ -159,6 +160,7 @@ fn test_block_sync_archival() {
         block_fetch_horizon,
         true,
         true,
+        None,
     );
     let mut env = test_env_with_epoch_length(5);
     let mut blocks = vec![];
@@ -14,6 +14,7 @@ mod query_client;
 mod runtimes;
 #[cfg(feature = "sandbox")]
 mod sandbox;
+mod signing_history;
 mod state_dump;
 mod state_snapshot;
 mod sync_state_nodes;
@@ -1332,7 +1332,7 @@ fn test_tx_forwarding() {
     );
     assert_eq!(
         env.network_adapters[client_index].requests.read().len(),
-        env.clients[client_index].config.tx_routing_height_horizon as usize
+        env.clients[client_index].config.tx_routing_height_horizon.get() as usize
     );
 }
 
@@ -3416,6 +3416,7 @@ mod contract_precompilation_tests {
             block_timestamp: block.header().raw_timestamp(),
             current_protocol_version: PROTOCOL_VERSION,
             cache: Some(Box::new(caches.swap_remove(1))),
+            execution_trace_collector: None,
         };
         viewer
             .call_function(
@@ -146,6 +146,7 @@ fn test_bad_shard_id() {
             *chunk.tx_root(),
             &validator_signer,
         )
+        .unwrap()
     } else {
         ShardChunkHeaderV3::new(
             *chunk.prev_block_hash(),
@@ -165,6 +166,7 @@ fn test_bad_shard_id() {
             chunk.bandwidth_requests().cloned().unwrap_or_else(BandwidthRequests::empty),
             &validator_signer,
         )
+        .unwrap()
     };
     modified_chunk.height_included = 2;
     chunks[0] = ShardChunkHeader::V3(modified_chunk);
@@ -312,7 +314,8 @@ fn test_validate_chunk_with_chunk_extra_bad_congestion_info_impl(mode: BadConges
         congestion_info,
         chunk.bandwidth_requests().cloned().unwrap_or_else(BandwidthRequests::empty),
         &validator_signer,
-    );
+    )
+    .unwrap();
     modified_chunk_header.height_included = 2;
 
     let modified_chunk = ShardChunkHeader::V3(modified_chunk_header);
@@ -89,7 +89,8 @@ async fn query_status_not_crash() {
         None,
         None,
         None,
-    );
+    )
+    .unwrap();
     let timestamp = next_block.header().timestamp();
     next_block
         .mut_header()
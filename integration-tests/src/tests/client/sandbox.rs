@@ -106,3 +106,44 @@ fn test_patch_account() {
     let test1_after = env.query_account("test1".parse().unwrap());
     assert_eq!(test1_after.amount, Balance::from_yoctonear(10));
 }
+
+#[test]
+fn test_snapshot_revert() {
+    let (mut env, _signer) = test_setup();
+    let snapshot_head = env.clients[0].chain.head().unwrap();
+    let snapshot_id = env.clients[0].sandbox_snapshot().unwrap();
+    let amount_before = env.query_account("test1".parse().unwrap()).amount;
+
+    let mut test1: Account = env.query_account("test1".parse().unwrap()).into();
+    test1.set_amount(Balance::from_yoctonear(10));
+    env.clients[0].chain.patch_state(SandboxStatePatch::new(vec![StateRecord::Account {
+        account_id: "test1".parse().unwrap(),
+        account: test1,
+    }]));
+    do_blocks(&mut env, 9, 20);
+    assert_eq!(env.query_account("test1".parse().unwrap()).amount, Balance::from_yoctonear(10));
+    assert!(env.clients[0].chain.get_block_hash_by_height(19).is_ok());
+
+    env.clients[0].sandbox_revert(snapshot_id).unwrap();
+    let chain = &env.clients[0].chain;
+    assert_eq!(chain.head().unwrap().last_block_hash, snapshot_head.last_block_hash);
+    assert_eq!(chain.header_head().unwrap().last_block_hash, snapshot_head.last_block_hash);
+    // The abandoned blocks are no longer found by height.
+    for height in snapshot_head.height + 1..20 {
+        assert!(
+            chain.get_block_hash_by_height(height).is_err(),
+            "height {height} is still indexed"
+        );
+    }
+    assert_eq!(env.query_account("test1".parse().unwrap()).amount, amount_before);
+
+    // New blocks are built on top of the snapshot at heights above the abandoned blocks.
+    do_blocks(&mut env, 20, 25);
+    let block = env.clients[0].chain.get_block_by_height(20).unwrap();
+    assert_eq!(block.header().prev_hash(), &snapshot_head.last_block_hash);
+    assert_eq!(env.query_account("test1".parse().unwrap()).amount, amount_before);
+
+    // The snapshot can be reverted to again.
+    env.clients[0].sandbox_revert(snapshot_id).unwrap();
+    assert_eq!(env.clients[0].chain.head().unwrap().last_block_hash, snapshot_head.last_block_hash);
+}
@@ -0,0 +1,29 @@
+use near_chain::Provenance;
+use near_chain::signing_history::{SigningHistory, SigningKind};
+use near_chain_configs::Genesis;
+use near_primitives::hash::hash;
+
+use crate::env::nightshade_setup::TestEnvNightshadeSetupExt;
+use crate::env::test_env::TestEnv;
+
+/// A block signed before (e.g. on another machine, with the history imported) is not signed
+/// again with a different content, while the following heights are produced as usual.
+#[test]
+fn test_refuse_block_signed_in_history() {
+    let genesis = Genesis::test(vec!["test0".parse().unwrap()], 1);
+    let mut env = TestEnv::builder(&genesis.config).nightshade_runtimes(&genesis).build();
+    let dir = tempfile::tempdir().unwrap();
+    let history = SigningHistory::open(&dir.path().join("signing_history")).unwrap();
+    history.check_and_record(2, SigningKind::Block, None, hash(&[1])).unwrap();
+    env.clients[0].signing_history = history.clone();
+
+    let block = env.clients[0].produce_block(1).unwrap().unwrap();
+    env.process_block(0, block, Provenance::PRODUCED);
+    assert!(env.clients[0].produce_block(2).is_err());
+
+    let block = env.clients[0].produce_block(3).unwrap().unwrap();
+    env.process_block(0, block.clone(), Provenance::PRODUCED);
+    let signed = history.export().records;
+    assert!(signed.iter().any(|record| record.height == 3 && record.hash == *block.hash()));
+    assert!(!signed.iter().any(|record| record.height == 2 && record.hash != hash(&[1])));
+}
@@ -4,6 +4,7 @@ use near_async::messaging::{CanSendAsync, IntoMultiSender, IntoSender, LateBound
 use near_async::time::{self, Clock};
 use near_async::tokio::TokioRuntimeHandle;
 use near_chain::rayon_spawner::RayonAsyncComputationSpawner;
+use near_chain::signing_history::SigningHistory;
 use near_chain::types::RuntimeAdapter;
 use near_chain::{Chain, ChainGenesis, ChainStore};
 use near_chain_configs::test_utils::TestClientConfigParams;
@@ -135,6 +136,7 @@ fn setup_network_node(
             spice_data_distributor_sender: noop().into_sender(),
             spice_core_writer_sender: noop().into_sender(),
         },
+        SigningHistory::disabled(),
     );
     let view_client_addr = ViewClientActor::spawn_multithread_actor(
         Clock::real(),
@@ -158,7 +160,7 @@ fn setup_network_node(
     ));
     let rpc_handler_config = RpcHandlerConfig {
         handler_threads: client_config.transaction_request_handler_threads,
-        tx_routing_height_horizon: client_config.tx_routing_height_horizon,
+        tx_routing_height_horizon: client_config.tx_routing_height_horizon.clone(),
         epoch_length: client_config.epoch_length,
         transaction_validity_period: genesis.config.transaction_validity_period,
         disable_tx_routing: client_config.disable_tx_routing,
@@ -197,6 +199,7 @@ fn setup_network_node(
         runtime.clone(),
         network_adapter.as_sender(),
         validator_signer.clone(),
+        SigningHistory::disabled(),
         false,
         false,
         Arc::new(RayonAsyncComputationSpawner),
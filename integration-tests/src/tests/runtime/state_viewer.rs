@@ -115,6 +115,7 @@ fn test_view_call() {
         block_timestamp: 1,
         current_protocol_version: PROTOCOL_VERSION,
         cache: None,
+        execution_trace_collector: None,
     };
     let result = viewer.call_function(
         root,
@@ -143,6 +144,7 @@ fn test_view_call_try_changing_storage() {
         block_timestamp: 1,
         current_protocol_version: PROTOCOL_VERSION,
         cache: None,
+        execution_trace_collector: None,
     };
     let result = viewer.call_function(
         root,
@@ -175,6 +177,7 @@ fn test_view_call_with_args() {
         block_timestamp: 1,
         current_protocol_version: PROTOCOL_VERSION,
         cache: None,
+        execution_trace_collector: None,
     };
     let view_call_result = viewer.call_function(
         root,
@@ -407,6 +410,7 @@ fn test_log_when_panic() {
         block_timestamp: 1,
         current_protocol_version: PROTOCOL_VERSION,
         cache: None,
+        execution_trace_collector: None,
     };
     let mut logs = vec![];
     viewer
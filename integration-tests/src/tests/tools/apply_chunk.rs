@@ -121,6 +121,7 @@ fn test_apply_chunk() {
                     None,
                     Some(rng),
                     StorageSource::Trie,
+                    None,
                 )
                 .unwrap();
                 assert_eq!(apply_result.new_root, new_root);
@@ -228,6 +229,7 @@ fn test_apply_tx_apply_receipt() {
                         store.clone(),
                         receipt.get_hash(),
                         StorageSource::Trie,
+                        None,
                     )
                     .unwrap();
                     assert_eq!(results.len(), 1);
@@ -279,6 +281,7 @@ fn test_apply_tx_apply_receipt() {
                 store.clone(),
                 receipt.get_hash(),
                 StorageSource::Trie,
+                None,
             )
             .unwrap();
             for result in results {
@@ -0,0 +1,93 @@
+use mock_node::MockNetworkConfig;
+use mock_node::scenario::MockScenario;
+use mock_node::setup::setup_mock_node;
+use near_async::ActorSystem;
+use near_async::messaging::CanSendAsync;
+use near_chain_configs::Genesis;
+use near_client::GetBlock;
+use near_network::tcp;
+use near_network::test_utils::{convert_boot_nodes, wait_or_timeout};
+use near_o11y::testonly::init_integration_logger;
+use near_store::db::RocksDB;
+use nearcore::{load_test_config, start_with_config};
+use std::ops::ControlFlow;
+
+const SCENARIO: &str = r#"{
+    "seed": 1,
+    "faults": {
+        "Block": {
+            "drop_probability": 0.2
+        },
+        "BlockHeaders": {
+            "delay": { "secs": 0, "nanos": 200000000 }
+        },
+        "PartialEncodedChunkResponse": {
+            "reorder_jitter": { "secs": 0, "nanos": 500000000 }
+        }
+    }
+}"#;
+
+/// Produces a short chain with a single validator, then serves it from a mock node running
+/// the scenario above and checks that a fresh node still syncs the whole chain despite the
+/// dropped, delayed and reordered responses.
+// The mock node runs on the test runtime, so it needs other threads to keep serving while
+// we block waiting for the databases to be closed.
+#[tokio::test(flavor = "multi_thread")]
+async fn slow_test_mock_node_scenario() {
+    init_integration_logger();
+
+    let mut genesis = Genesis::test(vec!["test1".parse().unwrap()], 1);
+    // Keep the whole chain in the first epoch so that the node syncs it block by block.
+    genesis.config.epoch_length = 100;
+    let target_height = 20;
+
+    let mock_dir = tempfile::Builder::new().prefix("mock_node_scenario_mock").tempdir().unwrap();
+    let node_dir = tempfile::Builder::new().prefix("mock_node_scenario_node").tempdir().unwrap();
+
+    let port1 = tcp::ListenerAddr::reserve_for_test();
+    let mut near1 = load_test_config("test1", port1, genesis.clone());
+    near1.client_config.min_num_peers = 0;
+    // The mock node loads its config from the home dir and listens on the same address.
+    near1.save_to_dir(mock_dir.path());
+
+    let actor_system = ActorSystem::new();
+    let nearcore::NearNode { view_client: view_client1, .. } =
+        start_with_config(mock_dir.path(), near1, actor_system.clone())
+            .await
+            .expect("start_with_config");
+    wait_or_timeout(100, 30000, async || match view_client1.send_async(GetBlock::latest()).await {
+        Ok(Ok(block)) if block.header.height >= target_height => ControlFlow::Break(()),
+        _ => ControlFlow::Continue(()),
+    })
+    .await
+    .unwrap();
+    actor_system.stop();
+    RocksDB::block_until_all_instances_are_dropped();
+
+    let scenario_path = mock_dir.path().join("scenario.json");
+    std::fs::write(&scenario_path, SCENARIO).unwrap();
+    let mut network_config = MockNetworkConfig::with_delay(std::time::Duration::from_millis(10));
+    network_config.scenario = MockScenario::from_file(&scenario_path).unwrap();
+    let mock = setup_mock_node(mock_dir.path(), network_config, None, None, None, true).unwrap();
+
+    let mut near2 = load_test_config("", tcp::ListenerAddr::reserve_for_test(), genesis);
+    near2.network_config.peer_store.boot_nodes = convert_boot_nodes(vec![("test1", *port1)]);
+    near2.client_config.min_num_peers = 1;
+
+    let actor_system = ActorSystem::new();
+    let nearcore::NearNode { view_client: view_client2, .. } =
+        start_with_config(node_dir.path(), near2, actor_system.clone())
+            .await
+            .expect("start_with_config");
+    wait_or_timeout(100, 60000, async || match view_client2.send_async(GetBlock::latest()).await {
+        Ok(Ok(block)) if block.header.height >= target_height => ControlFlow::Break(()),
+        _ => ControlFlow::Continue(()),
+    })
+    .await
+    .unwrap();
+
+    mock.abort();
+    let _ = mock.await;
+    actor_system.stop();
+    RocksDB::block_until_all_instances_are_dropped();
+}
@@ -1,5 +1,6 @@
 mod apply_chain_range;
 mod apply_chunk;
 mod dependencies;
+mod mock_node;
 mod state_dump;
 mod state_viewer;
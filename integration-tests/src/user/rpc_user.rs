@@ -120,6 +120,7 @@ impl User for RpcUser {
             account_id: account_id.clone(),
             method_name: method_name.to_string(),
             args: args.to_vec().into(),
+            trace: false,
         };
         match self.query(query)?.kind {
             QueryResponseKind::CallResult(call_result) => Ok(call_result),
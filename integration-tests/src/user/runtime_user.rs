@@ -207,6 +207,7 @@ impl RuntimeUser {
             bandwidth_requests: BlockBandwidthRequests::empty(),
             trie_access_tracker_state: Default::default(),
             on_post_state_ready: None,
+            execution_trace_collector: None,
         }
     }
 
@@ -333,6 +334,7 @@ impl User for RuntimeUser {
             block_timestamp: apply_state.block_timestamp,
             current_protocol_version: PROTOCOL_VERSION,
             cache: apply_state.cache,
+            execution_trace_collector: None,
         };
         result.result = self
             .trie_viewer
@@ -80,6 +80,7 @@ fn create_block() -> Block {
         None,
         None,
     )
+    .unwrap()
 }
 
 fn create_account() -> Account {
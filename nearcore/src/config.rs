@@ -1,5 +1,6 @@
 use crate::download_file::{FileDownloadError, run_download_file};
 use crate::dyn_config::LOG_CONFIG_FILENAME;
+use crate::failover::FailoverConfig;
 use anyhow::{Context, anyhow, bail};
 use bytesize::ByteSize;
 use near_async::time::{Clock, Duration};
@@ -9,15 +10,15 @@ use near_chain_configs::test_utils::{
     random_chain_id,
 };
 use near_chain_configs::{
-    BLOCK_PRODUCER_KICKOUT_THRESHOLD, CHUNK_PRODUCER_KICKOUT_THRESHOLD,
-    CHUNK_VALIDATOR_ONLY_KICKOUT_THRESHOLD, ChunkDistributionNetworkConfig, ClientConfig,
-    CloudArchivalWriterConfig, EXPECTED_EPOCH_LENGTH, EpochSyncConfig, FAST_EPOCH_LENGTH,
-    FISHERMEN_THRESHOLD, GAS_PRICE_ADJUSTMENT_RATE, GCConfig, GENESIS_CONFIG_FILENAME, Genesis,
-    GenesisConfig, GenesisValidationMode, INITIAL_GAS_LIMIT, LogSummaryStyle, MAX_INFLATION_RATE,
-    MIN_BLOCK_PRODUCTION_DELAY, MIN_GAS_PRICE, MutableConfigValue, MutableValidatorSigner,
-    NUM_BLOCK_PRODUCER_SEATS, NUM_BLOCKS_PER_YEAR, PROTOCOL_REWARD_RATE,
-    PROTOCOL_UPGRADE_STAKE_THRESHOLD, ProtocolVersionCheckConfig, ReshardingConfig,
-    StateSyncConfig, TRANSACTION_VALIDITY_PERIOD, TrackedShardsConfig,
+    BLOCK_PRODUCER_KICKOUT_THRESHOLD, BlockSyncCloudArchiveConfig,
+    CHUNK_PRODUCER_KICKOUT_THRESHOLD, CHUNK_VALIDATOR_ONLY_KICKOUT_THRESHOLD,
+    ChunkDistributionNetworkConfig, ClientConfig, CloudArchivalWriterConfig, EXPECTED_EPOCH_LENGTH,
+    EpochSyncConfig, FAST_EPOCH_LENGTH, FISHERMEN_THRESHOLD, GAS_PRICE_ADJUSTMENT_RATE, GCConfig,
+    GENESIS_CONFIG_FILENAME, Genesis, GenesisConfig, GenesisValidationMode, INITIAL_GAS_LIMIT,
+    LogSummaryStyle, MAX_INFLATION_RATE, MIN_BLOCK_PRODUCTION_DELAY, MIN_GAS_PRICE,
+    MutableConfigValue, MutableValidatorSigner, NUM_BLOCK_PRODUCER_SEATS, NUM_BLOCKS_PER_YEAR,
+    PROTOCOL_REWARD_RATE, PROTOCOL_UPGRADE_STAKE_THRESHOLD, ProtocolVersionCheckConfig,
+    ReshardingConfig, StateSyncConfig, TRANSACTION_VALIDITY_PERIOD, TrackedShardsConfig,
     default_chunk_validation_threads, default_chunk_wait_mult, default_chunks_cache_height_horizon,
     default_enable_early_prepare_transactions, default_enable_multiline_logging,
     default_epoch_sync, default_header_sync_expected_height_per_second,
@@ -41,6 +42,7 @@ use near_jsonrpc::RpcConfig;
 use near_network::PeerAddr;
 use near_network::config::NetworkConfig;
 use near_network::tcp;
+use near_o11y::event_log::EventLogConfig;
 use near_o11y::log_config::LogConfig;
 use near_primitives::hash::CryptoHash;
 use near_primitives::network::PeerId;
@@ -52,6 +54,7 @@ use near_primitives::types::{
 use near_primitives::utils::{from_timestamp, get_num_seats_per_shard};
 use near_primitives::validator_signer::{InMemoryValidatorSigner, ValidatorSigner};
 use near_primitives::version::PROTOCOL_VERSION;
+use near_remote_signer::RemoteSignerConfig;
 #[cfg(feature = "rosetta_rpc")]
 use near_rosetta_rpc::RosettaRpcConfig;
 use near_store::archive::cloud_storage::config::CloudArchivalConfig;
@@ -62,7 +65,7 @@ use near_vm_runner::{ContractRuntimeCache, FilesystemContractRuntimeCache};
 use num_rational::Rational32;
 use std::fs;
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::Write;
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -111,6 +114,7 @@ pub const MINIMUM_STAKE_DIVISOR: u64 = 10;
 pub const CONFIG_FILENAME: &str = "config.json";
 pub const NODE_KEY_FILE: &str = "node_key.json";
 pub const VALIDATOR_KEY_FILE: &str = "validator_key.json";
+pub const SIGNING_HISTORY_FILE: &str = "signing_history.jsonl";
 
 pub const NETWORK_TELEMETRY_URL: &str = "https://telemetry.nearone.org/nodes";
 
@@ -236,6 +240,18 @@ pub struct Config {
     pub genesis_file: String,
     pub genesis_records_file: Option<String>,
     pub validator_key_file: String,
+    /// If set, the validator key is held by a separate signing process, reached as configured
+    /// here, and `validator_key_file` is not used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_signer: Option<RemoteSignerConfig>,
+    /// Record of blocks, approvals and chunk endorsements signed by this node, used to refuse
+    /// double signing. Keep it with the validator key when moving the validator to another
+    /// machine, or transfer it with `neard signing-history export` and `import`.
+    pub signing_history_file: String,
+    /// If set, this node is one of an active/standby pair sharing the validator key and only
+    /// signs while it holds the failover lease.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failover: Option<FailoverConfig>,
     pub node_key_file: String,
     #[cfg(feature = "json_rpc")]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -378,6 +394,10 @@ pub struct Config {
     /// latency due to the need of requesting chunks over the peer-to-peer network.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub chunk_distribution_network: Option<ChunkDistributionNetworkConfig>,
+    /// Cloud archive that block sync falls back to when peers can't provide
+    /// the blocks it needs. Header sync doesn't use it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_sync_cloud_archive: Option<BlockSyncCloudArchiveConfig>,
     /// OrphanStateWitnessPool keeps instances of ChunkStateWitness which can't be processed
     /// because the previous block isn't available. The witnesses wait in the pool until the
     /// required block appears. This variable controls how many witnesses can be stored in the pool.
@@ -417,6 +437,16 @@ pub struct Config {
     ///
     /// Use [`Self::contract_cache_path()`] to access this field.
     pub(crate) contract_cache_path: Option<PathBuf>,
+    /// Maximum total size of the compiled contract cache on disk.
+    ///
+    /// When exceeded, the least recently used entries are removed from the cache. The cache is
+    /// unbounded if this is not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract_cache_max_size: Option<ByteSize>,
+    /// The number of the most frequently executed contracts to compile in the background on
+    /// startup if they are missing from the compiled contract cache, e.g. after a change of the
+    /// VM configuration.
+    pub contract_cache_warmup_contracts: usize,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     /// If true, transactions for the next chunk will be prepared early, right after the previous chunk's
@@ -434,6 +464,10 @@ pub struct Config {
     /// The default is disabled.
     #[serde(skip_serializing_if = "std::ops::Not::not")]
     pub dynamic_resharding_dry_run: bool,
+    /// If set, consensus-relevant events such as produced and missed blocks are written as JSON
+    /// lines to rotated files and optionally streamed over a Unix socket.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_log: Option<EventLogConfig>,
 }
 
 fn is_false(value: &bool) -> bool {
@@ -446,6 +480,9 @@ impl Default for Config {
             genesis_file: GENESIS_CONFIG_FILENAME.to_string(),
             genesis_records_file: None,
             validator_key_file: VALIDATOR_KEY_FILE.to_string(),
+            remote_signer: None,
+            signing_history_file: SIGNING_HISTORY_FILE.to_string(),
+            failover: None,
             node_key_file: NODE_KEY_FILE.to_string(),
             #[cfg(feature = "json_rpc")]
             rpc: Some(RpcConfig::default()),
@@ -493,10 +530,13 @@ impl Default for Config {
             produce_chunk_add_transactions_time_limit:
                 default_produce_chunk_add_transactions_time_limit(),
             chunk_distribution_network: None,
+            block_sync_cloud_archive: None,
             orphan_state_witness_pool_size: default_orphan_state_witness_pool_size(),
             orphan_state_witness_max_size: default_orphan_state_witness_max_size(),
             max_loaded_contracts: 256,
             contract_cache_path: None,
+            contract_cache_max_size: None,
+            contract_cache_warmup_contracts: 100,
             save_latest_witnesses: false,
             save_invalid_witnesses: false,
             transaction_request_handler_threads: 4,
@@ -504,6 +544,7 @@ impl Default for Config {
             enable_early_prepare_transactions: None,
             chunks_cache_height_horizon: None,
             dynamic_resharding_dry_run: false,
+            event_log: None,
         }
     }
 }
@@ -752,13 +793,17 @@ impl NearConfig {
                     config.resharding_config,
                     "resharding_config",
                 ),
-                tx_routing_height_horizon: config.tx_routing_height_horizon,
+                tx_routing_height_horizon: MutableConfigValue::new(
+                    config.tx_routing_height_horizon,
+                    "tx_routing_height_horizon",
+                ),
                 disable_tx_routing: config.disable_tx_routing,
                 produce_chunk_add_transactions_time_limit: MutableConfigValue::new(
                     config.produce_chunk_add_transactions_time_limit,
                     "produce_chunk_add_transactions_time_limit",
                 ),
                 chunk_distribution_network: config.chunk_distribution_network,
+                block_sync_cloud_archive: config.block_sync_cloud_archive,
                 orphan_state_witness_pool_size: config.orphan_state_witness_pool_size,
                 orphan_state_witness_max_size: config.orphan_state_witness_max_size,
                 save_latest_witnesses: config.save_latest_witnesses,
@@ -847,11 +892,12 @@ impl NightshadeRuntime {
         // FIXME: this (and other contract runtime resources) should probably get constructed by
         // the caller and passed into this `NightshadeRuntime::from_config` here. But that's a big
         // refactor...
-        let contract_cache = FilesystemContractRuntimeCache::with_memory_cache(
+        let contract_cache = FilesystemContractRuntimeCache::with_limits(
             home_dir,
             config.config.store.path.as_ref(),
             &config.config.contract_cache_path(),
             config.config.max_loaded_contracts,
+            config.config.contract_cache_max_size.map(|size| size.as_u64()),
         )?;
         Ok(NightshadeRuntime::new(
             store,
@@ -1545,15 +1591,9 @@ struct NodeKeyFile {
 }
 
 impl NodeKeyFile {
-    // the file can be JSON with comments
+    // the file can be JSON with comments, and can be encrypted
     fn from_file(path: &Path) -> std::io::Result<Self> {
-        let mut file = File::open(path)?;
-        let mut json_str = String::new();
-        file.read_to_string(&mut json_str)?;
-
-        let json_str_without_comments = near_config_utils::strip_comments_from_json_str(&json_str)?;
-
-        Ok(serde_json::from_str(&json_str_without_comments)?)
+        Ok(serde_json::from_value(near_crypto::key_file_encryption::read_key_file(path)?)?)
     }
 }
 
@@ -1579,14 +1619,32 @@ pub fn load_validator_key(validator_file: &Path) -> anyhow::Result<Option<Arc<Va
     }
     match InMemoryValidatorSigner::from_file(&validator_file) {
         Ok(signer) => Ok(Some(Arc::new(signer))),
-        Err(_) => {
-            let error_message =
-                format!("Failed initializing validator signer from {}", validator_file.display());
+        Err(err) => {
+            let error_message = format!(
+                "Failed initializing validator signer from {}: {}",
+                validator_file.display(),
+                err
+            );
             Err(anyhow!(error_message))
         }
     }
 }
 
+/// Creates the validator signer of the node: one using the remote signer if configured, otherwise
+/// one with the key from the validator key file, if it exists.
+pub fn load_validator_signer(
+    dir: &Path,
+    config: &Config,
+) -> anyhow::Result<Option<Arc<ValidatorSigner>>> {
+    if let Some(remote_signer) = &config.remote_signer {
+        let signer = near_remote_signer::connect(remote_signer, dir).with_context(|| {
+            format!("Failed connecting to the remote signer at {:?}", remote_signer.endpoint)
+        })?;
+        return Ok(Some(Arc::new(signer)));
+    }
+    load_validator_key(&dir.join(&config.validator_key_file))
+}
+
 pub fn load_config(
     dir: &Path,
     genesis_validation: GenesisValidationMode,
@@ -1600,8 +1658,7 @@ pub fn load_config(
         validation_errors.push_errors(e)
     };
 
-    let validator_file: PathBuf = dir.join(&config.validator_key_file);
-    let validator_signer = match load_validator_key(&validator_file) {
+    let validator_signer = match load_validator_signer(dir, &config) {
         Ok(validator_signer) => validator_signer,
         Err(e) => {
             validation_errors.push_validator_key_file_error(e.to_string());
@@ -1613,9 +1670,9 @@ pub fn load_config(
     let network_signer_result = NodeKeyFile::from_file(&node_key_path);
     let network_signer = match network_signer_result {
         Ok(node_key_file) => Some(node_key_file),
-        Err(_) => {
+        Err(err) => {
             let error_message =
-                format!("Failed reading node key file from {}", node_key_path.display());
+                format!("Failed reading node key file from {}: {}", node_key_path.display(), err);
             validation_errors.push_node_key_file_error(error_message);
             None
         }
@@ -1873,6 +1930,7 @@ mod tests {
                     gc_fork_clean_step: 420,
                     gc_num_epochs_to_keep: 24,
                     gc_step_period: Duration::milliseconds(500),
+                    retained_accounts: None,
                 }
             } else {
                 GCConfig {
@@ -1880,6 +1938,7 @@ mod tests {
                     gc_fork_clean_step: 100,
                     gc_num_epochs_to_keep: 5,
                     gc_step_period: Duration::milliseconds(500),
+                    retained_accounts: None,
                 }
             };
             assert_eq!(want_gc, config.gc);
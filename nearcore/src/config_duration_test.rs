@@ -1,10 +1,12 @@
 use std::str::FromStr;
 
 use crate::config::Config;
+use crate::failover::{FailoverConfig, LeaseConfig};
 use near_jsonrpc::RpcConfig;
 use near_network::config_json::{ExperimentalConfig, NetworkConfigOverrides};
 use near_o11y::testonly::init_test_logger;
 use near_primitives::types::AccountId;
+use near_remote_signer::{RemoteSignerConfig, RemoteSignerEndpoint};
 use near_store::StoreConfig;
 use serde::ser::{
     SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
@@ -28,6 +30,18 @@ fn test_config_duration_all_std() {
     // because there are Vec's. So it's best-effort.
     let config = Config {
         contract_cache_path: Some(".".into()),
+        contract_cache_max_size: Some(Default::default()),
+        remote_signer: Some(RemoteSignerConfig {
+            endpoint: RemoteSignerEndpoint::Unix { path: Default::default() },
+            request_timeout: Default::default(),
+        }),
+        failover: Some(FailoverConfig {
+            node_name: "test".to_string(),
+            lease: LeaseConfig::File { path: Default::default() },
+            lease_duration: Default::default(),
+            renew_period: Default::default(),
+            promotion_height_margin: Default::default(),
+        }),
         chunk_distribution_network: Some(Default::default()),
         store: StoreConfig { path: Some(Default::default()), ..Default::default() },
         cold_store: Some(StoreConfig { path: Some(Default::default()), ..Default::default() }),
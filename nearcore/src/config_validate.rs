@@ -33,6 +33,7 @@ impl<'a> ConfigValidator<'a> {
     /// this function would check all conditions, and add all error messages to ConfigValidator.errors
     fn validate_all_conditions(&mut self) {
         self.validate_cloud_archival_config();
+        self.validate_block_sync_cloud_archive_config();
         self.validate_cold_store_config();
         self.validate_state_sync_config();
         self.validate_tracked_shards_config();
@@ -266,6 +267,19 @@ impl<'a> ConfigValidator<'a> {
         }
     }
 
+    fn validate_block_sync_cloud_archive_config(&mut self) {
+        let Some(cloud_archive_config) = &self.config.block_sync_cloud_archive else {
+            return;
+        };
+        if !CloudStorageOpener::is_storage_location_supported(&cloud_archive_config.location) {
+            let error_message = format!(
+                "{} is not supported block sync cloud archive location.",
+                cloud_archive_config.location.name()
+            );
+            self.validation_errors.push_config_semantics_error(error_message);
+        }
+    }
+
     fn validate_tracked_shards_config(&mut self) {
         if self.config.tracked_shards_config.is_none() {
             return;
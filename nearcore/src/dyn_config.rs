@@ -1,17 +1,34 @@
 use crate::config::Config;
-use near_chain_configs::UpdatableClientConfig;
+use near_chain_configs::{UpdatableClientConfig, UpdatableRpcConfig};
 use near_dyn_configs::{UpdatableConfigLoaderError, UpdatableConfigs, UpdatableValidatorSigner};
+use near_network::config::NetworkConfig;
 use near_o11y::log_config::LogConfig;
 use near_primitives::validator_signer::ValidatorSigner;
+use near_remote_signer::RemoteSignerConfig;
 use serde::Deserialize;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 pub const LOG_CONFIG_FILENAME: &str = "log_config.json";
 
+/// Remote signer connected by an earlier load of the configs.
+///
+/// Reloads reuse it as long as the `remote_signer` config doesn't change, instead of reconnecting
+/// to the signing process each time.
+#[derive(Default)]
+pub struct RemoteSignerCache(Option<(RemoteSignerConfig, Arc<ValidatorSigner>)>);
+
+impl RemoteSignerCache {
+    /// Starts with the signer the node was started with, if it is a remote one.
+    pub fn new(config: Option<RemoteSignerConfig>, signer: Option<Arc<ValidatorSigner>>) -> Self {
+        Self(config.zip(signer))
+    }
+}
+
 /// This function gets called at the startup and each time a config needs to be reloaded.
 pub fn read_updatable_configs(
     home_dir: &Path,
+    remote_signer: &mut RemoteSignerCache,
 ) -> Result<UpdatableConfigs, UpdatableConfigLoaderError> {
     let mut errs = vec![];
     let log_config = match read_log_config(home_dir) {
@@ -32,9 +49,22 @@ pub fn read_updatable_configs(
         }
     };
     let updatable_client_config = config.as_ref().map(get_updatable_client_config);
+    let updatable_network_config = config.as_ref().and_then(|config| {
+        match NetworkConfig::updatable_config_from_json(&config.network) {
+            Ok(network_config) => Some(network_config),
+            Err(err) => {
+                errs.push(UpdatableConfigLoaderError::ConfigFileError {
+                    file: PathBuf::from(crate::config::CONFIG_FILENAME),
+                    err,
+                });
+                None
+            }
+        }
+    });
+    let updatable_rpc_config = config.as_ref().and_then(get_updatable_rpc_config);
 
     let validator_signer = if let Some(config) = config {
-        match read_validator_key(home_dir, &config) {
+        match read_validator_key(home_dir, &config, remote_signer) {
             Ok(validator_key) => UpdatableValidatorSigner::MaybeKey(validator_key),
             Err(err) => {
                 errs.push(err);
@@ -50,6 +80,8 @@ pub fn read_updatable_configs(
         Ok(UpdatableConfigs {
             log_config,
             client_config: updatable_client_config,
+            network_config: updatable_network_config,
+            rpc_config: updatable_rpc_config,
             validator_signer,
         })
     } else {
@@ -66,9 +98,20 @@ pub fn get_updatable_client_config(config: &Config) -> UpdatableClientConfig {
         expected_shutdown: config.expected_shutdown,
         resharding_config: config.resharding_config,
         produce_chunk_add_transactions_time_limit: config.produce_chunk_add_transactions_time_limit,
+        tx_routing_height_horizon: config.tx_routing_height_horizon,
     }
 }
 
+#[cfg(feature = "json_rpc")]
+fn get_updatable_rpc_config(config: &Config) -> Option<UpdatableRpcConfig> {
+    config.rpc.as_ref().map(|rpc| rpc.updatable_config())
+}
+
+#[cfg(not(feature = "json_rpc"))]
+fn get_updatable_rpc_config(_config: &Config) -> Option<UpdatableRpcConfig> {
+    None
+}
+
 fn read_log_config(home_dir: &Path) -> Result<Option<LogConfig>, UpdatableConfigLoaderError> {
     read_json_config::<LogConfig>(&home_dir.join(LOG_CONFIG_FILENAME))
 }
@@ -108,9 +151,32 @@ where
 fn read_validator_key(
     home_dir: &Path,
     config: &Config,
+    remote_signer: &mut RemoteSignerCache,
 ) -> Result<Option<Arc<ValidatorSigner>>, UpdatableConfigLoaderError> {
+    if let Some(remote_signer_config) = &config.remote_signer {
+        let endpoint = &remote_signer_config.endpoint;
+        if let Some((cached_config, signer)) = &remote_signer.0 {
+            if cached_config == remote_signer_config {
+                tracing::debug!(target: "neard", ?endpoint, "remote signer config unchanged, keeping the connection");
+                return Ok(Some(signer.clone()));
+            }
+        }
+        return match near_remote_signer::connect(remote_signer_config, home_dir) {
+            Ok(signer) => {
+                tracing::info!(target: "neard", ?endpoint, "hot loading remote validator signer");
+                let signer = Arc::new(signer);
+                remote_signer.0 = Some((remote_signer_config.clone(), signer.clone()));
+                Ok(Some(signer))
+            }
+            Err(err) => Err(UpdatableConfigLoaderError::RemoteSignerError {
+                endpoint: format!("{endpoint:?}"),
+                err: err.into(),
+            }),
+        };
+    }
+    remote_signer.0 = None;
     let validator_file: PathBuf = home_dir.join(&config.validator_key_file);
-    match crate::config::load_validator_key(&validator_file) {
+    match crate::config::load_validator_signer(home_dir, config) {
         Ok(Some(validator_signer)) => {
             tracing::info!(target: "neard", validator_file = %validator_file.display(), "hot loading validator key");
             Ok(Some(validator_signer))
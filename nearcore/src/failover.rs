@@ -0,0 +1,609 @@
+//! Active/standby failover for validators.
+//!
+//! Two `neard` instances configured with the same validator key and the same lease compete for
+//! the lease, and only the holder gets the validator key. The holder renews the lease every
+//! `renew_period` and reports the highest height it has signed. The other instance keeps
+//! running as a regular node and takes the lease over once it expires, after raising its own
+//! signing history so that nothing at or below the last height signed by the previous holder
+//! can be signed again. The reported height can be up to a lease duration old, so the new
+//! holder also skips `promotion_height_margin` heights past its own head and the reported
+//! height.
+//!
+//! The key is handed over through the same path as a hot reload of `validator_key.json`: the
+//! validator signer in the updatable configs is withheld while the node is on standby.
+//!
+//! The lease is either a JSON file on storage shared by both nodes, guarded by `flock`, or the
+//! small TCP service started with `neard failover lease-service`. File leases compare
+//! expiration times against the local wall clock, so the clocks of both machines must be
+//! synchronized. The service uses only its own clock, and only accepts requests carrying the
+//! secret shared by the service and both nodes.
+
+use near_async::time::Duration;
+use near_chain::signing_history::SigningHistory;
+use near_dyn_configs::{UpdatableConfigs, UpdatableValidatorSigner};
+use near_primitives::types::{BlockHeight, BlockHeightDelta};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use subtle::ConstantTimeEq;
+
+/// Maximum size of a single lease service message.
+const MAX_MESSAGE_SIZE: u64 = 4096;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct FailoverConfig {
+    /// Name under which this node holds the lease. Must be different on the two instances.
+    pub node_name: String,
+    pub lease: LeaseConfig,
+    /// How long the lease stays valid after the last renewal. The standby takes over at the
+    /// earliest this long after the active node stopped renewing.
+    #[serde(default = "default_lease_duration")]
+    #[serde(with = "near_async::time::serde_duration_as_std")]
+    pub lease_duration: Duration,
+    /// How often the lease is renewed by the active node and polled by the standby.
+    #[serde(default = "default_renew_period")]
+    #[serde(with = "near_async::time::serde_duration_as_std")]
+    pub renew_period: Duration,
+    /// Number of heights skipped on promotion past the chain head and the last height reported
+    /// by the previous holder. Must exceed the number of heights the previous holder can sign
+    /// within `lease_duration` after its last renewal.
+    #[serde(default = "default_promotion_height_margin")]
+    pub promotion_height_margin: BlockHeightDelta,
+}
+
+fn default_lease_duration() -> Duration {
+    Duration::seconds(10)
+}
+
+fn default_renew_period() -> Duration {
+    Duration::seconds(1)
+}
+
+fn default_promotion_height_margin() -> BlockHeightDelta {
+    30
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(tag = "backend", rename_all = "snake_case")]
+pub enum LeaseConfig {
+    /// Lease file on storage shared by both nodes.
+    File { path: PathBuf },
+    /// Lease service started with `neard failover lease-service`. `secret_file` holds the
+    /// secret the service was started with.
+    Service { address: SocketAddr, secret_file: PathBuf },
+}
+
+/// Current state of the lease.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Lease {
+    /// Name of the node holding the lease, empty if it was never taken.
+    pub holder: String,
+    /// Unix timestamp in milliseconds after which the lease can be taken over.
+    pub expires_at_ms: u64,
+    /// Highest height signed by any holder of the lease, as reported on renewal.
+    pub last_signed_height: BlockHeight,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+struct LeaseRequest {
+    holder: String,
+    duration_ms: u64,
+    signed_height: Option<BlockHeight>,
+    /// Gives the lease up if it is held by `holder`.
+    release: bool,
+    /// Secret shared with the lease service. Not used by file leases.
+    #[serde(default)]
+    secret: String,
+}
+
+impl Lease {
+    fn is_held_by(&self, holder: &str, now_ms: u64) -> bool {
+        self.holder == holder && self.expires_at_ms > now_ms
+    }
+
+    /// Applies a request to acquire, renew or release the lease.
+    fn apply(&self, request: &LeaseRequest, now_ms: u64) -> Lease {
+        let last_signed_height =
+            self.last_signed_height.max(request.signed_height.unwrap_or_default());
+        let held_by_other =
+            !self.holder.is_empty() && self.holder != request.holder && self.expires_at_ms > now_ms;
+        if held_by_other || (request.release && self.holder != request.holder) {
+            return Lease { last_signed_height, ..self.clone() };
+        }
+        let expires_at_ms = if request.release { 0 } else { now_ms + request.duration_ms };
+        Lease { holder: request.holder.clone(), expires_at_ms, last_signed_height }
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+trait LeaseBackend: Send {
+    fn update(&mut self, request: &LeaseRequest) -> io::Result<Lease>;
+}
+
+struct FileLease {
+    path: PathBuf,
+    lock_path: PathBuf,
+}
+
+impl FileLease {
+    fn new(path: &Path) -> Self {
+        let mut lock_path = path.as_os_str().to_owned();
+        lock_path.push(".lock");
+        Self { path: path.to_path_buf(), lock_path: lock_path.into() }
+    }
+}
+
+impl LeaseBackend for FileLease {
+    fn update(&mut self, request: &LeaseRequest) -> io::Result<Lease> {
+        let lock_file = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&self.lock_path)?;
+        let _lock = FileLock::exclusive(&lock_file)?;
+        let current = match std::fs::read(&self.path) {
+            Ok(data) => serde_json::from_slice(&data)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Lease::default(),
+            Err(err) => return Err(err),
+        };
+        let lease = current.apply(request, now_ms());
+        if lease != current {
+            write_atomically(&self.path, &lease)?;
+        }
+        Ok(lease)
+    }
+}
+
+/// Exclusive `flock` held until dropped.
+struct FileLock<'a>(&'a std::fs::File);
+
+impl<'a> FileLock<'a> {
+    fn exclusive(file: &'a std::fs::File) -> io::Result<Self> {
+        use std::os::unix::io::AsRawFd;
+        // SAFETY: the file descriptor stays open for as long as the lock is held.
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self(file))
+    }
+}
+
+impl Drop for FileLock<'_> {
+    fn drop(&mut self) {
+        use std::os::unix::io::AsRawFd;
+        // SAFETY: see `FileLock::exclusive`.
+        unsafe { libc::flock(self.0.as_raw_fd(), libc::LOCK_UN) };
+    }
+}
+
+fn write_atomically(path: &Path, lease: &Lease) -> io::Result<()> {
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+    let mut tmp = tempfile::NamedTempFile::new_in(dir.unwrap_or(Path::new(".")))?;
+    serde_json::to_writer(&mut tmp, lease)?;
+    tmp.as_file().sync_all()?;
+    tmp.persist(path).map_err(|err| err.error)?;
+    Ok(())
+}
+
+struct ServiceLease {
+    address: SocketAddr,
+    secret: String,
+    timeout: std::time::Duration,
+}
+
+impl LeaseBackend for ServiceLease {
+    fn update(&mut self, request: &LeaseRequest) -> io::Result<Lease> {
+        let mut stream = TcpStream::connect_timeout(&self.address, self.timeout)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        let request = LeaseRequest { secret: self.secret.clone(), ..request.clone() };
+        write_message(&mut stream, &request)?;
+        read_message(&mut BufReader::new(stream))
+    }
+}
+
+/// Reads the lease service secret, ignoring surrounding whitespace.
+pub fn read_lease_secret(path: &Path) -> anyhow::Result<String> {
+    let secret = std::fs::read_to_string(path)
+        .map_err(|err| anyhow::anyhow!("failed to read {}: {err}", path.display()))?;
+    let secret = secret.trim();
+    anyhow::ensure!(!secret.is_empty(), "lease service secret in {} is empty", path.display());
+    Ok(secret.to_string())
+}
+
+fn write_message<T: serde::Serialize>(stream: &mut TcpStream, message: &T) -> io::Result<()> {
+    let mut data = serde_json::to_vec(message)?;
+    data.push(b'\n');
+    stream.write_all(&data)
+}
+
+fn read_message<T: serde::de::DeserializeOwned>(reader: &mut impl BufRead) -> io::Result<T> {
+    let mut line = String::new();
+    reader.take(MAX_MESSAGE_SIZE).read_line(&mut line)?;
+    Ok(serde_json::from_str(&line)?)
+}
+
+/// Runs the lease service until the process is stopped. The lease is persisted to `state_file`
+/// so that the last signed height survives restarts of the service. Requests that do not carry
+/// `secret` are rejected.
+pub fn run_lease_service(
+    address: SocketAddr,
+    state_file: &Path,
+    secret: String,
+) -> anyhow::Result<()> {
+    anyhow::ensure!(!secret.is_empty(), "lease service secret must not be empty");
+    let secret = Arc::new(secret);
+    let lease = match std::fs::read(state_file) {
+        Ok(data) => serde_json::from_slice(&data)?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Lease::default(),
+        Err(err) => return Err(err.into()),
+    };
+    let lease = Arc::new(parking_lot::Mutex::new(lease));
+    let listener = TcpListener::bind(address)?;
+    tracing::info!(target: "near", %address, "lease service listening");
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                tracing::warn!(target: "near", ?err, "failed to accept lease connection");
+                continue;
+            }
+        };
+        let lease = lease.clone();
+        let secret = secret.clone();
+        let state_file = state_file.to_path_buf();
+        std::thread::spawn(move || {
+            let result = (|| -> io::Result<()> {
+                stream.set_read_timeout(Some(std::time::Duration::from_secs(5)))?;
+                let request: LeaseRequest = read_message(&mut BufReader::new(stream.try_clone()?))?;
+                if !bool::from(request.secret.as_bytes().ct_eq(secret.as_bytes())) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::PermissionDenied,
+                        format!("wrong secret from {:?}", stream.peer_addr()),
+                    ));
+                }
+                let mut lease = lease.lock();
+                let updated = lease.apply(&request, now_ms());
+                if updated != *lease {
+                    write_atomically(&state_file, &updated)?;
+                    if updated.holder != lease.holder {
+                        tracing::info!(target: "near", holder = %updated.holder, "lease changed hands");
+                    }
+                    *lease = updated;
+                }
+                write_message(&mut stream, &*lease)
+            })();
+            if let Err(err) = result {
+                tracing::warn!(target: "near", ?err, "failed to handle lease request");
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Decides whether this node may use the validator key, based on the lease.
+pub struct Failover {
+    config: FailoverConfig,
+    active: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl Failover {
+    /// Creates the failover in the standby state. Nothing happens until `start` is called.
+    pub fn new(config: FailoverConfig) -> Self {
+        Self {
+            config,
+            active: Arc::new(AtomicBool::new(false)),
+            stop: Arc::new(AtomicBool::new(false)),
+            thread: None,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Acquire)
+    }
+
+    /// Withholds the validator key from freshly read updatable configs unless this node holds
+    /// the lease.
+    pub fn gate(&self, configs: &mut UpdatableConfigs) {
+        if !self.is_active()
+            && matches!(configs.validator_signer, UpdatableValidatorSigner::MaybeKey(_))
+        {
+            configs.validator_signer = UpdatableValidatorSigner::MaybeKey(None);
+        }
+    }
+
+    /// Starts competing for the lease. `head_height` returns the height of the local chain
+    /// head. `on_change` is called after every promotion or demotion, and is expected to reload
+    /// the updatable configs so that the change reaches the client.
+    pub fn start(
+        &mut self,
+        signing_history: SigningHistory,
+        head_height: impl Fn() -> Option<BlockHeight> + Send + 'static,
+        on_change: impl Fn() + Send + 'static,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.config.renew_period < self.config.lease_duration,
+            "failover renew_period must be shorter than lease_duration"
+        );
+        let backend: Box<dyn LeaseBackend> = match &self.config.lease {
+            LeaseConfig::File { path } => Box::new(FileLease::new(path)),
+            LeaseConfig::Service { address, secret_file } => Box::new(ServiceLease {
+                address: *address,
+                secret: read_lease_secret(secret_file)?,
+                timeout: self.config.renew_period.unsigned_abs(),
+            }),
+        };
+        let runner = FailoverRunner {
+            config: self.config.clone(),
+            backend,
+            signing_history,
+            head_height: Box::new(head_height),
+            active: self.active.clone(),
+            stop: self.stop.clone(),
+            on_change: Box::new(on_change),
+        };
+        self.thread =
+            Some(std::thread::Builder::new().name("failover".to_string()).spawn(|| runner.run())?);
+        Ok(())
+    }
+
+    /// Stops signing and releases the lease, so that the standby can take over right away.
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+struct FailoverRunner {
+    config: FailoverConfig,
+    backend: Box<dyn LeaseBackend>,
+    signing_history: SigningHistory,
+    head_height: Box<dyn Fn() -> Option<BlockHeight> + Send>,
+    active: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
+    on_change: Box<dyn Fn() + Send>,
+}
+
+impl FailoverRunner {
+    fn run(mut self) {
+        let renew_period = self.config.renew_period.unsigned_abs();
+        // Local deadline by which the lease must be renewed, measured from when the last
+        // successful renewal was sent.
+        let mut held_until = None;
+        while !self.stop.load(Ordering::Acquire) {
+            self.renew(&mut held_until);
+            std::thread::sleep(renew_period);
+        }
+
+        if self.is_active() {
+            self.demote();
+            let request = LeaseRequest {
+                holder: self.config.node_name.clone(),
+                duration_ms: 0,
+                signed_height: self.signing_history.highest_signed_height(),
+                release: true,
+                secret: String::new(),
+            };
+            if let Err(err) = self.backend.update(&request) {
+                tracing::warn!(target: "near", ?err, "failed to release the failover lease");
+            }
+        }
+    }
+
+    /// Acquires or renews the lease once, promoting or demoting this node as needed.
+    fn renew(&mut self, held_until: &mut Option<std::time::Instant>) {
+        let lease_duration = self.config.lease_duration.unsigned_abs();
+        let renew_period = self.config.renew_period.unsigned_abs();
+        let request = LeaseRequest {
+            holder: self.config.node_name.clone(),
+            duration_ms: lease_duration.as_millis() as u64,
+            signed_height: self.signing_history.highest_signed_height(),
+            release: false,
+            secret: String::new(),
+        };
+        let sent_at = std::time::Instant::now();
+        match self.backend.update(&request) {
+            Ok(lease) if lease.is_held_by(&self.config.node_name, now_ms()) => {
+                *held_until = Some(sent_at + lease_duration);
+                if !self.is_active() {
+                    self.promote(&lease);
+                }
+            }
+            Ok(lease) => {
+                *held_until = None;
+                if self.is_active() {
+                    tracing::warn!(target: "near", holder = %lease.holder, "lost the failover lease");
+                    self.demote();
+                }
+            }
+            Err(err) => {
+                tracing::warn!(target: "near", ?err, "failed to renew the failover lease");
+            }
+        }
+        // Stop signing before the lease can expire while we are unable to renew it.
+        let must_demote = held_until
+            .is_none_or(|held_until| std::time::Instant::now() + renew_period >= held_until);
+        if self.is_active() && must_demote {
+            tracing::warn!(target: "near", "failover lease is about to expire");
+            self.demote();
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.active.load(Ordering::Acquire)
+    }
+
+    fn promote(&mut self, lease: &Lease) {
+        // Whatever the previous holder signed must never be signed again by this node. The
+        // reported height may be stale by up to a lease duration, during which the previous
+        // holder kept signing past its last report, and possibly past our head.
+        let head_height = (self.head_height)().unwrap_or_default();
+        let forbid_below =
+            lease.last_signed_height.max(head_height) + self.config.promotion_height_margin + 1;
+        if let Err(err) = self.signing_history.forbid_below(forbid_below) {
+            tracing::error!(target: "near", ?err, "cannot promote to active validator");
+            return;
+        }
+        tracing::info!(
+            target: "near",
+            last_signed_height = lease.last_signed_height,
+            head_height,
+            forbid_below,
+            "acquired the failover lease, promoting to active validator"
+        );
+        self.active.store(true, Ordering::Release);
+        crate::metrics::FAILOVER_ACTIVE.set(1);
+        (self.on_change)();
+    }
+
+    fn demote(&mut self) {
+        tracing::info!(target: "near", "demoting to standby");
+        self.active.store(false, Ordering::Release);
+        crate::metrics::FAILOVER_ACTIVE.set(0);
+        (self.on_change)();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_chain::signing_history::{SigningHistoryError, SigningKind};
+    use near_primitives::hash::hash;
+    use std::sync::atomic::AtomicUsize;
+
+    fn request(holder: &str, signed_height: Option<BlockHeight>) -> LeaseRequest {
+        LeaseRequest {
+            holder: holder.to_string(),
+            duration_ms: 1000,
+            signed_height,
+            release: false,
+            secret: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_lease_handover() {
+        let lease = Lease::default().apply(&request("primary", Some(10)), 0);
+        assert!(lease.is_held_by("primary", 500));
+
+        // The standby cannot take over until the lease expires, but learns the signed height.
+        let lease = lease.apply(&request("primary", Some(12)), 500);
+        let lease = lease.apply(&request("standby", None), 1000);
+        assert_eq!(lease.holder, "primary");
+        assert_eq!(lease.last_signed_height, 12);
+
+        let lease = lease.apply(&request("standby", None), 1500);
+        assert!(lease.is_held_by("standby", 1500));
+        assert_eq!(lease.last_signed_height, 12);
+    }
+
+    #[test]
+    fn test_lease_release() {
+        let lease = Lease::default().apply(&request("primary", Some(10)), 0);
+        let release = LeaseRequest { release: true, ..request("standby", None) };
+        assert_eq!(lease.apply(&release, 1), lease);
+
+        let release = LeaseRequest { release: true, ..request("primary", Some(11)) };
+        let lease = lease.apply(&release, 1);
+        assert!(!lease.is_held_by("primary", 1));
+        let lease = lease.apply(&request("standby", None), 2);
+        assert!(lease.is_held_by("standby", 2));
+        assert_eq!(lease.last_signed_height, 11);
+    }
+
+    #[test]
+    fn test_file_lease() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lease.json");
+        let mut primary = FileLease::new(&path);
+        let mut standby = FileLease::new(&path);
+        assert!(
+            primary.update(&request("primary", Some(3))).unwrap().is_held_by("primary", now_ms())
+        );
+        let lease = standby.update(&request("standby", None)).unwrap();
+        assert_eq!(lease.holder, "primary");
+        assert_eq!(lease.last_signed_height, 3);
+    }
+
+    #[test]
+    fn test_runner_promote_and_demote() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lease.json");
+        let signing_history = SigningHistory::open(&dir.path().join("signing_history")).unwrap();
+        let changes = Arc::new(AtomicUsize::new(0));
+        let on_change = {
+            let changes = changes.clone();
+            move || {
+                changes.fetch_add(1, Ordering::Relaxed);
+            }
+        };
+        let mut runner = FailoverRunner {
+            config: FailoverConfig {
+                node_name: "standby".to_string(),
+                lease: LeaseConfig::File { path: path.clone() },
+                lease_duration: Duration::seconds(10),
+                renew_period: Duration::seconds(1),
+                promotion_height_margin: 5,
+            },
+            backend: Box::new(FileLease::new(&path)),
+            signing_history: signing_history.clone(),
+            head_height: Box::new(|| Some(20)),
+            active: Arc::new(AtomicBool::new(false)),
+            stop: Arc::new(AtomicBool::new(false)),
+            on_change: Box::new(on_change),
+        };
+        let mut held_until = None;
+
+        // The standby stays passive while the primary holds the lease.
+        let mut primary = FileLease::new(&path);
+        primary
+            .update(&LeaseRequest { duration_ms: 60_000, ..request("primary", Some(30)) })
+            .unwrap();
+        runner.renew(&mut held_until);
+        assert!(!runner.is_active());
+        assert_eq!(changes.load(Ordering::Relaxed), 0);
+
+        // Once the primary is gone, the standby takes over and skips the margin past the last
+        // height reported by the primary, which is above the standby's head.
+        let release = LeaseRequest { release: true, ..request("primary", Some(32)) };
+        primary.update(&release).unwrap();
+        runner.renew(&mut held_until);
+        assert!(runner.is_active());
+        assert_eq!(changes.load(Ordering::Relaxed), 1);
+        assert!(matches!(
+            signing_history.check_and_record(37, SigningKind::Block, None, hash(&[1])),
+            Err(SigningHistoryError::BelowPrunedHeight { pruned_below: 38, .. })
+        ));
+        signing_history.check_and_record(38, SigningKind::Block, None, hash(&[1])).unwrap();
+
+        // Renewing keeps the node active and reports what it signed.
+        runner.renew(&mut held_until);
+        assert!(runner.is_active());
+        let lease = primary.update(&request("primary", None)).unwrap();
+        assert_eq!(lease.holder, "standby");
+        assert_eq!(lease.last_signed_height, 38);
+
+        // A competing primary that took the lease over, e.g. because of a clock jump, makes
+        // the node step down on the next renewal.
+        let lease = Lease {
+            holder: "primary".to_string(),
+            expires_at_ms: now_ms() + 60_000,
+            last_signed_height: 38,
+        };
+        write_atomically(&path, &lease).unwrap();
+        runner.renew(&mut held_until);
+        assert!(!runner.is_active());
+        assert_eq!(changes.load(Ordering::Relaxed), 2);
+    }
+}
@@ -11,6 +11,7 @@ use near_async::time::{self, Clock};
 use near_chain::rayon_spawner::RayonAsyncComputationSpawner;
 use near_chain::resharding::resharding_actor::ReshardingActor;
 pub use near_chain::runtime::NightshadeRuntime;
+use near_chain::signing_history::SigningHistory;
 use near_chain::spice_core::SpiceCoreReader;
 use near_chain::spice_core_writer_actor::SpiceCoreWriterActor;
 use near_chain::state_snapshot_actor::{
@@ -46,6 +47,7 @@ use near_primitives::genesis::GenesisId;
 use near_primitives::types::EpochId;
 use near_primitives::version::PROTOCOL_VERSION;
 use near_store::adapter::StoreAdapter as _;
+use near_store::adapter::chain_store::ChainStoreAdapter;
 use near_store::db::metadata::DbKind;
 use near_store::genesis::initialize_sharded_genesis_state;
 use near_store::metrics::spawn_db_metrics_loop;
@@ -66,6 +68,7 @@ pub mod dyn_config;
 #[cfg(feature = "json_rpc")]
 pub mod entity_debug;
 mod entity_debug_serializer;
+pub mod failover;
 mod metrics;
 pub mod migrations;
 pub mod state_sync;
@@ -224,6 +227,28 @@ fn get_split_store(config: &NearConfig, storage: &NodeStorage) -> anyhow::Result
     Ok(storage.get_split_store())
 }
 
+/// Compiles the most frequently used contracts missing from the compiled contract cache on a
+/// separate thread, so that the first chunks applied after a restart do not pay for it.
+fn spawn_contract_cache_warmup(runtime: &Arc<NightshadeRuntime>, limit: usize) {
+    if limit == 0 {
+        return;
+    }
+    let runtime = runtime.clone();
+    let spawned = std::thread::Builder::new().name("contract_cache_warmup".to_string()).spawn(
+        move || match runtime.warmup_compiled_contract_cache(limit) {
+            Ok(num_contracts) => {
+                tracing::info!(target: "near", num_contracts, "compiled contract cache warmed up")
+            }
+            Err(err) => {
+                tracing::warn!(target: "near", ?err, "failed to warm up compiled contract cache")
+            }
+        },
+    );
+    if let Err(err) = spawned {
+        tracing::warn!(target: "near", ?err, "failed to spawn compiled contract cache warmup");
+    }
+}
+
 fn new_spice_client_config(
     chunk_executor_adapter: &Arc<LateBoundSender<TokioRuntimeHandle<ChunkExecutorActor>>>,
     spice_chunk_validator_adapter: &Arc<
@@ -351,6 +376,10 @@ pub struct NearNode {
     pub resharding_handle: ReshardingHandle,
     /// Shard tracker, allows querying of which shards are tracked by this node.
     pub shard_tracker: ShardTracker,
+    /// Record of what this node has signed with the validator key.
+    pub signing_history: SigningHistory,
+    /// Read access to the chain in the hot store.
+    pub chain_store: ChainStoreAdapter,
 }
 
 pub fn start_with_config(
@@ -387,6 +416,10 @@ pub async fn start_with_config_and_synchronization_impl(
     shutdown_signal: Option<broadcast::Sender<()>>,
     config_updater: Option<ConfigUpdater>,
 ) -> anyhow::Result<NearNode> {
+    if let Some(event_log_config) = &config.config.event_log {
+        near_o11y::event_log::init(event_log_config, home_dir)
+            .context("could not initialize the event log")?;
+    }
     let storage = open_storage(home_dir, &config)?;
     if config.client_config.enable_statistics_export {
         let period = config.client_config.log_summary_period;
@@ -432,6 +465,7 @@ pub async fn start_with_config_and_synchronization_impl(
         epoch_manager.clone(),
     )
     .context("could not create the transaction runtime")?;
+    spawn_contract_cache_warmup(&runtime, config.config.contract_cache_warmup_contracts);
 
     // Get the split store. If split store is some then create a new set of structures for
     // the view client. Otherwise just re-use the existing ones.
@@ -593,6 +627,9 @@ pub async fn start_with_config_and_synchronization_impl(
     let state_sync_spawner: Arc<dyn FutureSpawner> =
         actor_system.new_future_spawner("state sync").into();
 
+    let signing_history =
+        SigningHistory::open(&home_dir.join(&config.config.signing_history_file))?;
+
     let chunk_executor_adapter = LateBoundSender::new();
     let spice_chunk_validator_adapter = LateBoundSender::new();
     let spice_data_distributor_adapter = LateBoundSender::new();
@@ -604,6 +641,20 @@ pub async fn start_with_config_and_synchronization_impl(
         &spice_core_writer_adapter,
     );
 
+    // Shared by the JSON-RPC server and the config updater, which applies reloads of the config.
+    #[cfg(feature = "json_rpc")]
+    let updatable_rpc_config = config.rpc_config.as_ref().map(|rpc_config| {
+        near_chain_configs::MutableConfigValue::new(
+            rpc_config.updatable_config(),
+            "rpc_updatable_config",
+        )
+    });
+    #[cfg(feature = "json_rpc")]
+    let config_updater = config_updater.map(|config_updater| match &updatable_rpc_config {
+        Some(updatable_rpc_config) => config_updater.with_rpc_config(updatable_rpc_config.clone()),
+        None => config_updater,
+    });
+
     let StartClientResult {
         client_actor,
         tx_pool,
@@ -632,6 +683,7 @@ pub async fn start_with_config_and_synchronization_impl(
         None,
         resharding_sender.into_multi_sender(),
         spice_client_config,
+        signing_history.clone(),
     );
     client_adapter_for_shards_manager.bind(client_actor.clone());
     client_adapter_for_partial_witness_actor.bind(ChunkValidationSenderForPartialWitness {
@@ -670,7 +722,7 @@ pub async fn start_with_config_and_synchronization_impl(
 
     let rpc_handler_config = RpcHandlerConfig {
         handler_threads: config.client_config.transaction_request_handler_threads,
-        tx_routing_height_horizon: config.client_config.tx_routing_height_horizon,
+        tx_routing_height_horizon: config.client_config.tx_routing_height_horizon.clone(),
         disable_tx_routing: config.client_config.disable_tx_routing,
         epoch_length: config.client_config.epoch_length,
         transaction_validity_period: config.genesis.config.transaction_validity_period,
@@ -701,6 +753,7 @@ pub async fn start_with_config_and_synchronization_impl(
     state_sync_dumper.start()?;
 
     let hot_store = storage.get_hot_store();
+    let chain_store = hot_store.chain_store();
     let cold_store = storage.get_cold_store();
 
     let network_actor = PeerManagerActor::spawn(
@@ -733,7 +786,9 @@ pub async fn start_with_config_and_synchronization_impl(
     .context("PeerManager::spawn()")?;
     network_adapter.bind(network_actor.clone());
     #[cfg(feature = "json_rpc")]
-    if let Some(rpc_config) = config.rpc_config {
+    if let (Some(rpc_config), Some(updatable_rpc_config)) =
+        (config.rpc_config, updatable_rpc_config)
+    {
         let entity_debug_handler = EntityDebugHandlerImpl {
             epoch_manager: view_epoch_manager,
             runtime: view_runtime,
@@ -742,6 +797,7 @@ pub async fn start_with_config_and_synchronization_impl(
         };
         near_jsonrpc::start_http(
             rpc_config,
+            updatable_rpc_config,
             config.genesis.config.clone(),
             client_actor.clone().into_multi_sender(),
             view_client_addr.clone().into_multi_sender(),
@@ -797,5 +853,7 @@ pub async fn start_with_config_and_synchronization_impl(
         cloud_archival_writer_handle,
         resharding_handle,
         shard_tracker,
+        signing_history,
+        chain_store,
     })
 }
@@ -34,6 +34,14 @@ pub(crate) static CONFIG_CORRECT: LazyLock<IntGauge> = LazyLock::new(|| {
     .unwrap()
 });
 
+pub(crate) static FAILOVER_ACTIVE: LazyLock<IntGauge> = LazyLock::new(|| {
+    try_create_int_gauge(
+        "near_failover_active",
+        "Whether this node holds the failover lease and signs with the validator key",
+    )
+    .unwrap()
+});
+
 pub(crate) static STATE_SYNC_DUMP_ITERATION_ELAPSED: LazyLock<HistogramVec> = LazyLock::new(|| {
     try_create_histogram_vec(
         "near_state_sync_dump_iteration_elapsed_sec",
@@ -2,13 +2,21 @@
 use anyhow::Context;
 use near_amend_genesis::AmendGenesisCommand;
 use near_async::ActorSystem;
-use near_chain_configs::{GenesisValidationMode, TrackedShardsConfig};
+use near_async::time::Clock;
+use near_chain::signing_history::{SigningHistory, SigningHistoryExport};
+use near_chain::{ChainStore, ChainStoreAccess};
+use near_chain_configs::{GenesisValidationMode, StateSyncConfig, TrackedShardsConfig};
 use near_client::ConfigUpdater;
+use near_client::sync::epoch::{
+    EpochSync, read_epoch_sync_proof_file, write_epoch_sync_proof_file,
+};
 use near_cold_store_tool::ColdStoreCommand;
 use near_config_utils::DownloadConfigType;
+use near_crypto::key_file_encryption::{self, Passphrase, set_key_file_passphrase};
 use near_database_tool::commands::DatabaseCommand;
 use near_dump_test_contract::DumpTestContractCommand;
 use near_dyn_configs::{UpdatableConfigLoader, UpdatableConfigLoaderError, UpdatableConfigs};
+use near_epoch_manager::EpochManager;
 use near_flat_storage::commands::FlatStorageCommand;
 use near_fork_network::cli::ForkNetworkCommand;
 use near_jsonrpc_primitives::types::light_client::RpcLightClientExecutionProofResponse;
@@ -29,11 +37,12 @@ use near_state_parts::cli::StatePartsCommand;
 use near_state_parts_dump_check::cli::StatePartsDumpCheckCommand;
 use near_state_viewer::StateViewerSubCommand;
 use near_store::db::RocksDB;
-use near_store::{Mode, ShardUId};
+use near_store::{Mode, NodeStorage, ShardUId};
 use near_undo_block::cli::UndoBlockCommand;
+use nearcore::failover::Failover;
 use serde_json::Value;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, Write};
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
@@ -85,6 +94,11 @@ impl NeardCmd {
             }
         }
 
+        if let Some(passphrase) = neard_cmd.opts.key_passphrase.read()? {
+            set_key_file_passphrase(passphrase)
+                .map_err(|_| anyhow::anyhow!("key file passphrase is already set"))?;
+        }
+
         let home_dir = neard_cmd.opts.home.clone();
         let genesis_validation = if neard_cmd.opts.unsafe_fast_startup {
             GenesisValidationMode::UnsafeFast
@@ -157,6 +171,18 @@ impl NeardCmd {
             NeardSubCommand::DumpEpochConfigs(cmd) => {
                 cmd.run(&home_dir)?;
             }
+            NeardSubCommand::SigningHistory(cmd) => {
+                cmd.run(&home_dir)?;
+            }
+            NeardSubCommand::Failover(cmd) => {
+                cmd.run(&home_dir)?;
+            }
+            NeardSubCommand::EpochSync(cmd) => {
+                cmd.run(&home_dir, genesis_validation)?;
+            }
+            NeardSubCommand::KeyFile(cmd) => {
+                cmd.run()?;
+            }
         };
         Ok(())
     }
@@ -194,6 +220,9 @@ struct NeardOpts {
     /// Enables export of span data using opentelemetry protocol.
     #[clap(flatten)]
     o11y: near_o11y::Options,
+    /// Where to read the passphrase of encrypted node and validator key files from.
+    #[clap(flatten)]
+    key_passphrase: KeyPassphraseOpts,
 }
 
 impl NeardOpts {
@@ -269,6 +298,20 @@ pub(super) enum NeardSubCommand {
 
     /// Dump hard-coded epoch configs into JSON files
     DumpEpochConfigs(DumpEpochConfigsCommand),
+
+    /// Export or import the record of what this validator has signed, e.g. when moving the
+    /// validator to another machine. Fails while the node is running.
+    SigningHistory(SigningHistoryCommand),
+
+    /// Tools for running an active/standby validator pair.
+    Failover(FailoverCommand),
+
+    /// Export an epoch sync proof to a file, or set up a fresh node to bootstrap from such a
+    /// file and a directory of state parts instead of from peers.
+    EpochSync(EpochSyncCommand),
+
+    /// Encrypt, decrypt or change the passphrase of node and validator key files.
+    KeyFile(KeyFileCommand),
 }
 
 #[allow(unused)]
@@ -541,7 +584,26 @@ impl RunCmd {
             }
         }
 
+        let mut remote_signer = nearcore::dyn_config::RemoteSignerCache::new(
+            near_config.config.remote_signer.clone(),
+            near_config.validator_signer.get(),
+        );
+        // With failover, the validator key is only handed to the client once the lease is held.
+        let mut failover = near_config.config.failover.clone().map(Failover::new);
+        if failover.is_some() {
+            near_config.validator_signer.update(None);
+        }
+        let mut read_updatable_configs = move |failover: &Option<Failover>| {
+            let mut updatable_configs =
+                nearcore::dyn_config::read_updatable_configs(home_dir, &mut remote_signer);
+            if let (Ok(updatable_configs), Some(failover)) = (&mut updatable_configs, failover) {
+                failover.gate(updatable_configs);
+            }
+            updatable_configs
+        };
+
         let (tx_crash, mut rx_crash) = broadcast::channel::<()>(16);
+        let (tx_failover, mut rx_failover) = tokio::sync::mpsc::unbounded_channel::<()>();
         let (tx_config_update, rx_config_update) =
             broadcast::channel::<Result<UpdatableConfigs, Arc<UpdatableConfigLoaderError>>>(16);
         let tokio_runtime = tokio::runtime::Builder::new_multi_thread()
@@ -562,34 +624,56 @@ impl RunCmd {
             .await
             .global();
 
-            let updatable_configs = nearcore::dyn_config::read_updatable_configs(home_dir)
+            let updatable_configs = read_updatable_configs(&failover)
                 .unwrap_or_else(|e| panic!("Error reading dynamic configs: {:#}", e));
             let mut updatable_config_loader =
                 UpdatableConfigLoader::new(updatable_configs.clone(), tx_config_update);
-            let config_updater = ConfigUpdater::new(rx_config_update);
-
-            let nearcore::NearNode { cold_store_loop_handle, resharding_handle, .. } =
-                nearcore::start_with_config_and_synchronization(
-                    home_dir,
-                    near_config,
-                    ActorSystem::new(),
-                    Some(tx_crash),
-                    Some(config_updater),
-                )
-                .await
-                .expect("start_with_config");
+            let config_updater = ConfigUpdater::new(Clock::real(), rx_config_update);
+
+            let nearcore::NearNode {
+                cold_store_loop_handle,
+                resharding_handle,
+                signing_history,
+                chain_store,
+                ..
+            } = nearcore::start_with_config_and_synchronization(
+                home_dir,
+                near_config,
+                ActorSystem::new(),
+                Some(tx_crash),
+                Some(config_updater),
+            )
+            .await
+            .expect("start_with_config");
+
+            if let Some(failover) = &mut failover {
+                failover
+                    .start(
+                        signing_history,
+                        move || chain_store.head().ok().map(|tip| tip.height),
+                        move || {
+                            let _ = tx_failover.send(());
+                        },
+                    )
+                    .unwrap_or_else(|e| panic!("Error starting failover: {:#}", e));
+            }
 
             let sig = loop {
-                let sig = wait_for_interrupt_signal(home_dir, &mut rx_crash).await;
-                if sig == "SIGHUP" {
-                    let maybe_updatable_configs =
-                        nearcore::dyn_config::read_updatable_configs(home_dir);
-                    updatable_config_loader.reload(maybe_updatable_configs);
-                } else {
-                    break sig;
+                tokio::select! {
+                    sig = wait_for_interrupt_signal(home_dir, &mut rx_crash) => {
+                        if sig != "SIGHUP" {
+                            break sig;
+                        }
+                    }
+                    // Promotion or demotion: hand the key over the same way as a reload.
+                    Some(()) = rx_failover.recv() => {}
                 }
+                updatable_config_loader.reload(read_updatable_configs(&failover));
             };
             tracing::warn!(target: "neard", %sig, "stopping, this may take a few minutes");
+            if let Some(failover) = &mut failover {
+                failover.stop();
+            }
             if let Some(handle) = cold_store_loop_handle {
                 handle.store(false, std::sync::atomic::Ordering::Relaxed);
             }
@@ -859,6 +943,383 @@ impl DumpEpochConfigsCommand {
     }
 }
 
+#[derive(clap::Parser)]
+pub(super) struct SigningHistoryCommand {
+    #[clap(subcommand)]
+    subcmd: SigningHistorySubCommand,
+}
+
+#[derive(clap::Subcommand)]
+enum SigningHistorySubCommand {
+    /// Writes the signing history to a JSON file.
+    Export {
+        #[clap(long)]
+        output: PathBuf,
+    },
+    /// Merges a JSON file written by `export` into the local signing history. Fails without
+    /// changing anything if the two histories conflict.
+    Import {
+        #[clap(long)]
+        input: PathBuf,
+    },
+}
+
+impl SigningHistoryCommand {
+    pub(super) fn run(self, home_dir: &Path) -> anyhow::Result<()> {
+        let config = nearcore::config::Config::from_file_skip_validation(
+            &home_dir.join(nearcore::config::CONFIG_FILENAME),
+        )?;
+        let history = SigningHistory::open(&home_dir.join(&config.signing_history_file))?;
+        match self.subcmd {
+            SigningHistorySubCommand::Export { output } => {
+                let export = history.export();
+                let file = File::create(&output)
+                    .with_context(|| format!("Failed to create {}", output.display()))?;
+                serde_json::to_writer_pretty(file, &export)?;
+                println!("Exported {} records to {}", export.records.len(), output.display());
+            }
+            SigningHistorySubCommand::Import { input } => {
+                let file = File::open(&input)
+                    .with_context(|| format!("Failed to open {}", input.display()))?;
+                let export: SigningHistoryExport = serde_json::from_reader(BufReader::new(file))?;
+                let num_records = export.records.len();
+                history.import(export)?;
+                println!("Imported {} records from {}", num_records, input.display());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(clap::Parser)]
+pub(super) struct EpochSyncCommand {
+    #[clap(subcommand)]
+    subcmd: EpochSyncSubCommand,
+}
+
+#[derive(clap::Subcommand)]
+enum EpochSyncSubCommand {
+    /// Derives an epoch sync proof from the local database, verifies it and writes it to a
+    /// file. The node must be synced and must not be running.
+    Export {
+        #[clap(long)]
+        output: PathBuf,
+    },
+    /// Reads a proof written by `export` and prints the config that makes a fresh node
+    /// bootstrap from it instead of asking peers for one, and optionally read state parts from
+    /// a local directory, laid out as written by a state dumper with a filesystem location.
+    /// Headers and blocks after the proof are still fetched from peers.
+    Import {
+        #[clap(long)]
+        input: PathBuf,
+        #[clap(long)]
+        state_parts_dir: Option<PathBuf>,
+        /// Write the printed settings to config.json instead of only printing them. Note that
+        /// this rewrites the whole file.
+        #[clap(long)]
+        write_config: bool,
+    },
+}
+
+impl EpochSyncCommand {
+    pub(super) fn run(
+        self,
+        home_dir: &Path,
+        genesis_validation: GenesisValidationMode,
+    ) -> anyhow::Result<()> {
+        match self.subcmd {
+            EpochSyncSubCommand::Export { output } => {
+                let near_config = nearcore::config::load_config(home_dir, genesis_validation)?;
+                let storage = NodeStorage::opener(
+                    home_dir,
+                    &near_config.config.store,
+                    near_config.config.cold_store.as_ref(),
+                    near_config.config.cloud_storage_config(),
+                )
+                .open_in_mode(Mode::ReadOnly)?;
+                let store = storage.get_hot_store();
+                let epoch_manager = EpochManager::new_arc_handle(
+                    store.clone(),
+                    &near_config.genesis.config,
+                    Some(home_dir),
+                );
+                let transaction_validity_period =
+                    near_config.genesis.config.transaction_validity_period;
+                let chain_store = ChainStore::new(
+                    store.clone(),
+                    near_config.client_config.save_trie_changes,
+                    transaction_validity_period,
+                );
+                let genesis = chain_store
+                    .get_block_header_by_height(near_config.genesis.config.genesis_height)?;
+                let proof = EpochSync::export_proof(
+                    store,
+                    &genesis,
+                    transaction_validity_period,
+                    epoch_manager.as_ref(),
+                )?;
+                write_epoch_sync_proof_file(&output, &proof)
+                    .with_context(|| format!("Failed to write {}", output.display()))?;
+                println!("Exported epoch sync proof to {}", output.display());
+            }
+            EpochSyncSubCommand::Import { input, state_parts_dir, write_config } => {
+                let proof = read_epoch_sync_proof_file(&input)?.into_v1();
+                let config_path = home_dir.join(nearcore::config::CONFIG_FILENAME);
+                let mut config = nearcore::config::Config::from_file_skip_validation(&config_path)?;
+                let mut epoch_sync = config.epoch_sync.unwrap_or_default();
+                epoch_sync.proof_file = Some(input.canonicalize()?);
+                let mut settings = serde_json::json!({
+                    "epoch_sync": { "proof_file": epoch_sync.proof_file },
+                });
+                config.epoch_sync = Some(epoch_sync);
+                if let Some(state_parts_dir) = state_parts_dir {
+                    let state_sync =
+                        StateSyncConfig::filesystem(state_parts_dir.canonicalize().with_context(
+                            || format!("Failed to open {}", state_parts_dir.display()),
+                        )?);
+                    settings["state_sync_enabled"] = true.into();
+                    settings["state_sync"] = serde_json::to_value(&state_sync)?;
+                    config.state_sync_enabled = true;
+                    config.state_sync = Some(state_sync);
+                }
+                println!(
+                    "Proof bootstraps to the epoch starting at height {}",
+                    proof.current_epoch.first_block_header_in_epoch.height(),
+                );
+                if write_config {
+                    config.write_to_file(&config_path)?;
+                    println!(
+                        "Updated {} with:\n{}",
+                        config_path.display(),
+                        serde_json::to_string_pretty(&settings)?
+                    );
+                } else {
+                    println!(
+                        "Add these settings to {}, or rerun with --write-config:\n{}",
+                        config_path.display(),
+                        serde_json::to_string_pretty(&settings)?
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(clap::Parser)]
+pub(super) struct FailoverCommand {
+    #[clap(subcommand)]
+    subcmd: FailoverSubCommand,
+}
+
+#[derive(clap::Subcommand)]
+enum FailoverSubCommand {
+    /// Runs a lease service that an active/standby pair can use instead of a shared lease file.
+    /// Both nodes need `failover.lease` set to
+    /// `{"backend": "service", "address": ..., "secret_file": ...}`.
+    LeaseService {
+        #[clap(long, default_value = "127.0.0.1:3040")]
+        addr: SocketAddr,
+        /// File holding the secret that both nodes must present. Requests without it are
+        /// rejected.
+        #[clap(long)]
+        secret_file: PathBuf,
+        /// Where the lease is persisted. Defaults to `<neard_home>/failover_lease.json`.
+        #[clap(long)]
+        state_file: Option<PathBuf>,
+    },
+}
+
+impl FailoverCommand {
+    pub(super) fn run(self, home_dir: &Path) -> anyhow::Result<()> {
+        match self.subcmd {
+            FailoverSubCommand::LeaseService { addr, secret_file, state_file } => {
+                let state_file = state_file.unwrap_or_else(|| home_dir.join("failover_lease.json"));
+                let secret = nearcore::failover::read_lease_secret(&secret_file)?;
+                nearcore::failover::run_lease_service(addr, &state_file, secret)
+            }
+        }
+    }
+}
+
+/// Reads a passphrase from one of the given sources, if any is given. Only the trailing newline
+/// is stripped, so passphrases can start or end with other whitespace.
+fn read_passphrase(
+    fd: Option<i32>,
+    env: Option<&str>,
+    credential: Option<&str>,
+) -> anyhow::Result<Option<Passphrase>> {
+    let mut passphrase = match (fd, env, credential) {
+        (None, None, None) => return Ok(None),
+        (Some(fd), None, None) => read_passphrase_fd(fd)?,
+        (None, Some(name), None) => std::env::var(name)
+            .with_context(|| format!("Failed to read environment variable {name}"))?,
+        (None, None, Some(name)) => {
+            // See https://systemd.io/CREDENTIALS/.
+            let dir = std::env::var_os("CREDENTIALS_DIRECTORY")
+                .context("CREDENTIALS_DIRECTORY is not set, is neard running under systemd?")?;
+            let path = Path::new(&dir).join(name);
+            std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read credential {}", path.display()))?
+        }
+        _ => anyhow::bail!("Please give only one source for the passphrase."),
+    };
+    if passphrase.ends_with('\n') {
+        passphrase.pop();
+        if passphrase.ends_with('\r') {
+            passphrase.pop();
+        }
+    }
+    if passphrase.is_empty() {
+        anyhow::bail!("The key file passphrase is empty.");
+    }
+    Ok(Some(Passphrase::new(passphrase)))
+}
+
+#[cfg(unix)]
+fn read_passphrase_fd(fd: i32) -> anyhow::Result<String> {
+    use std::io::Read;
+    use std::os::fd::FromRawFd;
+    // SAFETY: the descriptor is handed to neard for the sole purpose of reading the passphrase,
+    // so nothing else in the process owns it. It is closed when the file is dropped.
+    let mut file = unsafe { File::from_raw_fd(fd) };
+    let mut passphrase = String::new();
+    file.read_to_string(&mut passphrase)
+        .with_context(|| format!("Failed to read the passphrase from file descriptor {fd}"))?;
+    Ok(passphrase)
+}
+
+#[cfg(not(unix))]
+fn read_passphrase_fd(_fd: i32) -> anyhow::Result<String> {
+    anyhow::bail!("Reading the passphrase from a file descriptor is only supported on unix")
+}
+
+#[derive(clap::Args)]
+struct KeyPassphraseOpts {
+    /// Reads the key file passphrase from this file descriptor, e.g. `3` when running
+    /// `neard --key-passphrase-fd 3 run 3<passphrase_file`.
+    #[clap(long)]
+    key_passphrase_fd: Option<i32>,
+    /// Reads the key file passphrase from this environment variable. The variable stays set
+    /// and is inherited by child processes, so prefer the other sources where possible.
+    #[clap(long)]
+    key_passphrase_env: Option<String>,
+    /// Reads the key file passphrase from this systemd credential, as set up with
+    /// `LoadCredential=` or `LoadCredentialEncrypted=`.
+    #[clap(long)]
+    key_passphrase_credential: Option<String>,
+}
+
+impl KeyPassphraseOpts {
+    fn read(&self) -> anyhow::Result<Option<Passphrase>> {
+        read_passphrase(
+            self.key_passphrase_fd,
+            self.key_passphrase_env.as_deref(),
+            self.key_passphrase_credential.as_deref(),
+        )
+    }
+}
+
+#[derive(clap::Parser)]
+pub(super) struct KeyFileCommand {
+    #[clap(subcommand)]
+    subcmd: KeyFileSubCommand,
+}
+
+#[derive(clap::Subcommand)]
+enum KeyFileSubCommand {
+    /// Encrypts key files in place with the key file passphrase.
+    Encrypt { paths: Vec<PathBuf> },
+    /// Decrypts key files in place with the key file passphrase.
+    Decrypt { paths: Vec<PathBuf> },
+    /// Re-encrypts key files in place with a new passphrase.
+    Rotate {
+        paths: Vec<PathBuf>,
+        #[clap(long)]
+        new_passphrase_fd: Option<i32>,
+        #[clap(long)]
+        new_passphrase_env: Option<String>,
+        #[clap(long)]
+        new_passphrase_credential: Option<String>,
+    },
+}
+
+impl KeyFileCommand {
+    pub(super) fn run(self) -> anyhow::Result<()> {
+        let passphrase = key_file_encryption::key_file_passphrase().context(
+            "Please give the key file passphrase with --key-passphrase-fd, \
+             --key-passphrase-env or --key-passphrase-credential.",
+        )?;
+        match self.subcmd {
+            KeyFileSubCommand::Encrypt { paths } => {
+                for path in paths {
+                    let mut key_file = key_file_encryption::read_key_file_raw(&path)
+                        .with_context(|| format!("Failed to read {}", path.display()))?;
+                    if key_file_encryption::is_encrypted(&key_file) {
+                        println!("{} is already encrypted, skipping", path.display());
+                        continue;
+                    }
+                    key_file_encryption::encrypt(&mut key_file, passphrase)?;
+                    replace_key_file(&path, &key_file)?;
+                    println!("Encrypted {}", path.display());
+                }
+            }
+            KeyFileSubCommand::Decrypt { paths } => {
+                for path in paths {
+                    let key_file = key_file_encryption::read_key_file(&path)
+                        .with_context(|| format!("Failed to decrypt {}", path.display()))?;
+                    replace_key_file(&path, &key_file)?;
+                    println!("Decrypted {}", path.display());
+                }
+            }
+            KeyFileSubCommand::Rotate {
+                paths,
+                new_passphrase_fd,
+                new_passphrase_env,
+                new_passphrase_credential,
+            } => {
+                let new_passphrase = read_passphrase(
+                    new_passphrase_fd,
+                    new_passphrase_env.as_deref(),
+                    new_passphrase_credential.as_deref(),
+                )?
+                .context(
+                    "Please give the new passphrase with --new-passphrase-fd, \
+                     --new-passphrase-env or --new-passphrase-credential.",
+                )?;
+                for path in paths {
+                    let mut key_file = key_file_encryption::read_key_file(&path)
+                        .with_context(|| format!("Failed to decrypt {}", path.display()))?;
+                    key_file_encryption::encrypt(&mut key_file, &new_passphrase)?;
+                    replace_key_file(&path, &key_file)?;
+                    println!("Rotated {}", path.display());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Atomically replaces a key file, so that a crash can't leave a half-written key behind.
+fn replace_key_file(path: &Path, key_file: &Value) -> anyhow::Result<()> {
+    let tmp_path = path.with_extension("json.tmp");
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&tmp_path)
+        .with_context(|| format!("Failed to create {}", tmp_path.display()))?;
+    file.write_all(serde_json::to_string_pretty(key_file)?.as_bytes())?;
+    file.sync_all()?;
+    std::fs::rename(&tmp_path, path)
+        .with_context(|| format!("Failed to replace {}", path.display()))?;
+    Ok(())
+}
+
 #[derive(clap::Parser)]
 pub(super) struct ValidateConfigCommand {}
 
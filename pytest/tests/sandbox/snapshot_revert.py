@@ -0,0 +1,78 @@
+#!/usr/bin/env python3
+# Take a snapshot of a sandbox node, change the state and revert the node back to
+# the snapshot. Also checks that the block timestamp can be set.
+
+import sys, time
+import base64
+import pathlib
+
+sys.path.append(str(pathlib.Path(__file__).resolve().parents[2] / 'lib'))
+
+import utils
+from cluster import start_cluster
+from transaction import sign_deploy_contract_tx, sign_function_call_tx
+
+CONFIG = utils.figure_out_sandbox_binary()
+
+# start node
+nodes = start_cluster(1, 0, 1, CONFIG, [["epoch_length", 10]], {})
+
+# deploy contract
+hash_ = nodes[0].get_latest_block().hash_bytes
+tx = sign_deploy_contract_tx(nodes[0].signer_key, utils.load_test_contract(),
+                             10, hash_)
+nodes[0].send_tx(tx)
+time.sleep(3)
+
+k = (10).to_bytes(8, byteorder="little")
+
+
+def write_value(value, nonce):
+    hash_ = nodes[0].get_latest_block().hash_bytes
+    tx = sign_function_call_tx(nodes[0].signer_key,
+                               nodes[0].signer_key.account_id,
+                               'write_key_value',
+                               k + value.to_bytes(8, byteorder="little"),
+                               1000000000000, 0, nonce, hash_)
+    res = nodes[0].send_tx_and_wait(tx, 20)
+    assert ('SuccessValue' in res['result']['status']), res
+
+
+def read_value():
+    res = nodes[0].call_function("test0", "read_value",
+                                 base64.b64encode(k).decode('ascii'))
+    return int.from_bytes(res['result']['result'], byteorder="little")
+
+
+write_value(20, 20)
+res = nodes[0].json_rpc('sandbox_snapshot', {})
+snapshot_id = res['result']['snapshot_id']
+
+write_value(30, 30)
+assert read_value() == 30
+
+res = nodes[0].json_rpc('sandbox_revert', {"snapshot_id": snapshot_id})
+assert 'error' not in res, res
+assert read_value() == 20
+
+# The access key nonce is reverted too, so the same nonce can be used again.
+write_value(40, 30)
+assert read_value() == 40
+
+res = nodes[0].json_rpc('sandbox_revert', {"snapshot_id": snapshot_id + 1})
+assert res['error']['cause']['name'] == 'REVERT_FAILED', res
+
+# Set the timestamp of the next block one day ahead.
+latest = nodes[0].json_rpc('block', {"finality": "optimistic"})
+timestamp = int(latest['result']['header']['timestamp_nanosec'])
+target = timestamp + 24 * 60 * 60 * 10**9
+res = nodes[0].json_rpc('sandbox_set_block_timestamp',
+                        {"timestamp_nanosec": str(target)})
+assert 'error' not in res, res
+utils.wait_for_blocks(nodes[0], count=2)
+latest = nodes[0].json_rpc('block', {"finality": "optimistic"})
+assert int(latest['result']['header']['timestamp_nanosec']) >= target
+
+res = nodes[0].json_rpc('sandbox_set_block_timestamp',
+                        {"timestamp_nanosec": str(timestamp)})
+assert res['error']['cause']['name'] == 'INVALID_TIMESTAMP', res
@@ -92,6 +92,17 @@ pub trait ContractRuntimeCache: Send + Sync {
     fn has(&self, key: &CryptoHash) -> std::io::Result<bool> {
         self.get(key).map(|entry| entry.is_some())
     }
+    /// Notes that the contract with the given code hash is about to be executed, using the
+    /// compiled contract stored under `key`.
+    ///
+    /// This is called on every lookup, including the ones served by the [`Self::memory_cache`].
+    /// Caches may use this to keep the entries that are in use, and to track which contracts are
+    /// the most valuable to have compiled ahead of time, see [`Self::hottest_contracts`].
+    fn record_contract_use(&self, _code_hash: &CryptoHash, _key: &CryptoHash) {}
+    /// Code hashes of the up to `limit` most frequently executed contracts, most used first.
+    fn hottest_contracts(&self, _limit: usize) -> Vec<CryptoHash> {
+        Vec::new()
+    }
     /// TESTING ONLY: Clears the cache including in-memory and persistent data (if any).
     ///
     /// This should be used only for testing, since the implementations may not provide
@@ -127,6 +138,14 @@ impl ContractRuntimeCache for Box<dyn ContractRuntimeCache> {
     fn has(&self, key: &CryptoHash) -> std::io::Result<bool> {
         <dyn ContractRuntimeCache>::has(&**self, key)
     }
+
+    fn record_contract_use(&self, code_hash: &CryptoHash, key: &CryptoHash) {
+        <dyn ContractRuntimeCache>::record_contract_use(&**self, code_hash, key)
+    }
+
+    fn hottest_contracts(&self, limit: usize) -> Vec<CryptoHash> {
+        <dyn ContractRuntimeCache>::hottest_contracts(&**self, limit)
+    }
 }
 
 impl<C: ContractRuntimeCache> ContractRuntimeCache for &C {
@@ -145,6 +164,14 @@ impl<C: ContractRuntimeCache> ContractRuntimeCache for &C {
     fn has(&self, key: &CryptoHash) -> std::io::Result<bool> {
         <C as ContractRuntimeCache>::has(self, key)
     }
+
+    fn record_contract_use(&self, code_hash: &CryptoHash, key: &CryptoHash) {
+        <C as ContractRuntimeCache>::record_contract_use(self, code_hash, key)
+    }
+
+    fn hottest_contracts(&self, limit: usize) -> Vec<CryptoHash> {
+        <C as ContractRuntimeCache>::hottest_contracts(self, limit)
+    }
 }
 
 #[derive(Default, Clone)]
@@ -206,9 +233,15 @@ impl fmt::Debug for MockContractRuntimeCache {
 /// Clones of this type share the same underlying state and information. The cache is thread safe
 /// and atomic.
 ///
-/// This cache however does not implement any clean-up policies. While it is possible to truncate
-/// a file that has been written to the cache before (`put` an empty buffer), the file will remain
-/// in place until an operator (or somebody else) removes files at their own discretion.
+/// By default the cache does not implement any clean-up policies and files remain in place until
+/// an operator (or somebody else) removes them at their own discretion. When constructed with a
+/// disk size limit (see [`Self::with_limits`]) the least recently accessed entries are removed
+/// whenever the total size of the cached files exceeds the limit.
+///
+/// The cache also keeps track of how often each contract is executed, persisting these counts in
+/// the cache directory, so that the most frequently used contracts can be compiled ahead of time
+/// after the cache is invalidated (e.g. due to a change in the VM configuration). The uses are
+/// handed over to a background thread, so that recording them doesn't slow down the execution.
 #[cfg(not(windows))]
 #[derive(Clone)]
 pub struct FilesystemContractRuntimeCache {
@@ -219,9 +252,269 @@ pub struct FilesystemContractRuntimeCache {
 struct FilesystemContractRuntimeCacheState {
     dir: rustix::fd::OwnedFd,
     any_cache: AnyCache,
+    disk_usage: Option<Arc<Mutex<DiskUsage>>>,
+    /// Sends the uses to the `usage_tracker` thread. Only `None` while the cache is dropped.
+    usage_sender: Option<std::sync::mpsc::SyncSender<UsageMessage>>,
+    usage_tracker: Option<std::thread::JoinHandle<()>>,
     test_temp_dir: Option<tempfile::TempDir>,
 }
 
+/// Name of the file in the cache directory storing the [`ContractUsage`] counts.
+///
+/// This is not a valid base58 string, so it never collides with a cache entry.
+#[cfg(not(windows))]
+const CONTRACT_USAGE_FILENAME: &str = "contract_usage";
+/// The [`ContractUsage`] counts are persisted after this many recorded uses.
+#[cfg(not(windows))]
+const CONTRACT_USAGE_SAVE_INTERVAL: u64 = 10_000;
+/// Maximum number of contracts for which [`ContractUsage`] keeps the counts.
+#[cfg(not(windows))]
+const MAX_TRACKED_CONTRACTS: usize = 4096;
+/// Maximum number of uses waiting for the usage tracker thread. More uses are not recorded.
+#[cfg(not(windows))]
+const MAX_PENDING_USES: usize = 16 * 1024;
+
+/// Bookkeeping of the files in a size-limited cache directory.
+#[cfg(not(windows))]
+struct DiskUsage {
+    max_size: u64,
+    total_size: u64,
+    /// Sizes of the cached files, ordered by their last access.
+    entries: lru::LruCache<CryptoHash, u64>,
+}
+
+#[cfg(not(windows))]
+impl DiskUsage {
+    /// Builds the index from the files currently present in the cache directory.
+    ///
+    /// The initial access order is approximated using the file access and modification times.
+    fn scan(dir: &rustix::fd::OwnedFd, max_size: u64) -> std::io::Result<Self> {
+        use std::str::FromStr;
+        let mut files = Vec::new();
+        for entry in rustix::fs::Dir::read_from(dir)? {
+            let entry = entry?;
+            // Temporary files and other non-entry files do not parse as a hash.
+            let Some(key) =
+                entry.file_name().to_str().ok().and_then(|name| CryptoHash::from_str(name).ok())
+            else {
+                continue;
+            };
+            let stat =
+                match rustix::fs::statat(dir, entry.file_name(), rustix::fs::AtFlags::empty()) {
+                    Ok(stat) => stat,
+                    Err(rustix::io::Errno::NOENT) => continue,
+                    Err(e) => return Err(e.into()),
+                };
+            let last_access = std::cmp::max(stat.st_atime, stat.st_mtime);
+            files.push((last_access, key, u64::try_from(stat.st_size).unwrap_or(0)));
+        }
+        files.sort_unstable_by_key(|(last_access, _, _)| *last_access);
+        let mut usage = Self { max_size, total_size: 0, entries: lru::LruCache::unbounded() };
+        for (_, key, size) in files {
+            usage.total_size += size;
+            usage.entries.put(key, size);
+        }
+        usage.evict(dir);
+        Ok(usage)
+    }
+
+    fn insert(&mut self, dir: &rustix::fd::OwnedFd, key: CryptoHash, size: u64) {
+        if let Some(old_size) = self.entries.put(key, size) {
+            self.total_size = self.total_size.saturating_sub(old_size);
+        }
+        self.total_size += size;
+        self.evict(dir);
+    }
+
+    /// Removes the least recently used files until the cache fits within its size limit.
+    ///
+    /// The most recently used entry is never removed, even if it alone exceeds the limit.
+    fn evict(&mut self, dir: &rustix::fd::OwnedFd) {
+        while self.total_size > self.max_size && self.entries.len() > 1 {
+            let Some((key, size)) = self.entries.pop_lru() else { break };
+            self.total_size = self.total_size.saturating_sub(size);
+            match rustix::fs::unlinkat(dir, key.to_string(), rustix::fs::AtFlags::empty()) {
+                Ok(()) | Err(rustix::io::Errno::NOENT) => {}
+                Err(err) => tracing::warn!(
+                    target: "vm",
+                    key = %key,
+                    err = &err as &dyn std::error::Error,
+                    "failed to evict a compiled contract cache entry",
+                ),
+            }
+            #[cfg(feature = "metrics")]
+            crate::metrics::record_compiled_contract_cache_eviction();
+        }
+        #[cfg(feature = "metrics")]
+        crate::metrics::set_compiled_contract_cache_disk_usage(self.total_size, self.entries.len());
+    }
+}
+
+/// Number of times each contract, identified by its code hash, has been prepared for execution.
+#[cfg(not(windows))]
+#[derive(Default)]
+struct ContractUsage {
+    counts: HashMap<CryptoHash, u64>,
+    /// Uses recorded since the counts were last persisted.
+    unsaved: u64,
+}
+
+#[cfg(not(windows))]
+impl ContractUsage {
+    fn load(dir: &rustix::fd::OwnedFd) -> Self {
+        use rustix::fs::{Mode, OFlags};
+        let file =
+            match rustix::fs::openat(dir, CONTRACT_USAGE_FILENAME, OFlags::RDONLY, Mode::empty()) {
+                Ok(file) => file,
+                Err(rustix::io::Errno::NOENT) => return Self::default(),
+                Err(err) => {
+                    tracing::debug!(
+                        target: "vm",
+                        err = &err as &dyn std::error::Error,
+                        "could not open the contract usage file"
+                    );
+                    return Self::default();
+                }
+            };
+        let mut buffer = Vec::new();
+        let counts = std::fs::File::from(file)
+            .read_to_end(&mut buffer)
+            .and_then(|_| borsh::from_slice::<Vec<(CryptoHash, u64)>>(&buffer));
+        match counts {
+            Ok(counts) => Self { counts: counts.into_iter().collect(), unsaved: 0 },
+            // Like the cache entries, the counts are only an optimization, so start from scratch
+            // if the file is unreadable.
+            Err(err) => {
+                tracing::debug!(
+                    target: "vm",
+                    err = &err as &dyn std::error::Error,
+                    "contract usage file was found to be malformed"
+                );
+                Self::default()
+            }
+        }
+    }
+
+    fn record(&mut self, code_hash: &CryptoHash) {
+        *self.counts.entry(*code_hash).or_default() += 1;
+        if self.counts.len() > MAX_TRACKED_CONTRACTS {
+            self.prune();
+        }
+        self.unsaved += 1;
+    }
+
+    /// Keeps only the more frequently used half of the tracked contracts and halves their counts,
+    /// so that contracts which are no longer used eventually make way for new ones.
+    fn prune(&mut self) {
+        let mut counts: Vec<_> = self.counts.drain().collect();
+        counts.sort_unstable_by(|(_, a), (_, b)| b.cmp(a));
+        counts.truncate(MAX_TRACKED_CONTRACTS / 2);
+        self.counts = counts.into_iter().map(|(hash, count)| (hash, count.div_ceil(2))).collect();
+    }
+
+    fn hottest(&self, limit: usize) -> Vec<CryptoHash> {
+        let mut counts: Vec<_> = self.counts.iter().collect();
+        counts.sort_unstable_by(|(_, a), (_, b)| b.cmp(a));
+        counts.into_iter().take(limit).map(|(hash, _)| *hash).collect()
+    }
+
+    fn save(&mut self, dir: &rustix::fd::OwnedFd) -> std::io::Result<()> {
+        use rustix::fs::{Mode, OFlags};
+        let temp_filename = format!("{CONTRACT_USAGE_FILENAME}.temp");
+        let mode = Mode::RUSR | Mode::WUSR | Mode::RGRP | Mode::WGRP;
+        let flags = OFlags::CREATE | OFlags::TRUNC | OFlags::WRONLY;
+        let file = rustix::fs::openat(dir, &temp_filename, flags, mode)?;
+        let counts: Vec<_> = self.counts.iter().map(|(hash, count)| (*hash, *count)).collect();
+        borsh::to_writer(std::fs::File::from(file), &counts)?;
+        rustix::fs::renameat(dir, temp_filename, dir, CONTRACT_USAGE_FILENAME)?;
+        self.unsaved = 0;
+        Ok(())
+    }
+
+    fn save_or_warn(&mut self, dir: &rustix::fd::OwnedFd) {
+        if let Err(err) = self.save(dir) {
+            tracing::warn!(
+                target: "vm",
+                err = &err as &dyn std::error::Error,
+                "failed to save the contract usage counts"
+            );
+        }
+    }
+}
+
+/// Requests handled by the usage tracker thread of a [`FilesystemContractRuntimeCache`].
+#[cfg(not(windows))]
+enum UsageMessage {
+    Use {
+        code_hash: CryptoHash,
+        key: CryptoHash,
+    },
+    Hottest {
+        limit: usize,
+        reply: std::sync::mpsc::Sender<Vec<CryptoHash>>,
+    },
+    #[cfg(feature = "test_features")]
+    Clear,
+}
+
+/// Keeps the [`ContractUsage`] counts and the access order of the [`DiskUsage`] entries up to
+/// date with the uses received from the cache, persisting the counts every
+/// [`CONTRACT_USAGE_SAVE_INTERVAL`] uses and once the cache is dropped.
+#[cfg(not(windows))]
+fn track_usage(
+    dir: rustix::fd::OwnedFd,
+    disk_usage: Option<Arc<Mutex<DiskUsage>>>,
+    mut usage: ContractUsage,
+    receiver: std::sync::mpsc::Receiver<UsageMessage>,
+) {
+    while let Ok(message) = receiver.recv() {
+        // Handle all the messages queued up in the meantime at once, so that the disk usage lock
+        // is taken once per batch.
+        let mut used_keys = Vec::new();
+        let mut replies = Vec::new();
+        for message in std::iter::once(message).chain(receiver.try_iter()) {
+            match message {
+                UsageMessage::Use { code_hash, key } => {
+                    usage.record(&code_hash);
+                    used_keys.push(key);
+                }
+                UsageMessage::Hottest { limit, reply } => replies.push((limit, reply)),
+                #[cfg(feature = "test_features")]
+                UsageMessage::Clear => usage = ContractUsage::default(),
+            }
+        }
+        if let Some(disk_usage) = &disk_usage {
+            let mut disk_usage = disk_usage.lock();
+            for key in &used_keys {
+                disk_usage.entries.promote(key);
+            }
+        }
+        // Reply once the whole batch is applied, so that the caller observes all the earlier uses.
+        for (limit, reply) in replies {
+            let _ = reply.send(usage.hottest(limit));
+        }
+        if usage.unsaved >= CONTRACT_USAGE_SAVE_INTERVAL {
+            usage.save_or_warn(&dir);
+        }
+    }
+    if usage.unsaved > 0 {
+        usage.save_or_warn(&dir);
+    }
+}
+
+#[cfg(not(windows))]
+impl Drop for FilesystemContractRuntimeCacheState {
+    fn drop(&mut self) {
+        // Closing the channel makes the tracker persist the counts and exit.
+        drop(self.usage_sender.take());
+        if let Some(usage_tracker) = self.usage_tracker.take() {
+            if usage_tracker.join().is_err() {
+                tracing::warn!(target: "vm", "contract usage tracker thread panicked");
+            }
+        }
+    }
+}
+
 #[cfg(not(windows))]
 impl FilesystemContractRuntimeCache {
     pub fn new<StorePath, ContractCachePath>(
@@ -250,6 +543,25 @@ impl FilesystemContractRuntimeCache {
         contract_cache_path: &ContractCachePath,
         memory_cache_size: usize,
     ) -> std::io::Result<Self>
+    where
+        StorePath: AsRef<std::path::Path> + ?Sized,
+        ContractCachePath: AsRef<std::path::Path> + ?Sized,
+    {
+        Self::with_limits(home_dir, store_path, contract_cache_path, memory_cache_size, None)
+    }
+
+    /// Like [`Self::with_memory_cache`], but additionally limits the total size of the files in
+    /// the cache directory to `max_disk_size` bytes, if specified.
+    ///
+    /// Entries are evicted in the least recently used order. Files already present in the
+    /// directory count towards the limit as well.
+    pub fn with_limits<StorePath, ContractCachePath>(
+        home_dir: &std::path::Path,
+        store_path: Option<&StorePath>,
+        contract_cache_path: &ContractCachePath,
+        memory_cache_size: usize,
+        max_disk_size: Option<u64>,
+    ) -> std::io::Result<Self>
     where
         StorePath: AsRef<std::path::Path> + ?Sized,
         ContractCachePath: AsRef<std::path::Path> + ?Sized,
@@ -279,10 +591,25 @@ impl FilesystemContractRuntimeCache {
             path = %path.display(),
             message = "opened a contract executable cache directory"
         );
+        let disk_usage = match max_disk_size {
+            Some(max_size) => Some(Arc::new(Mutex::new(DiskUsage::scan(&dir, max_size)?))),
+            None => None,
+        };
+        let contract_usage = ContractUsage::load(&dir);
+        let (usage_sender, receiver) = std::sync::mpsc::sync_channel(MAX_PENDING_USES);
+        let usage_tracker =
+            std::thread::Builder::new().name("contract_usage".to_string()).spawn({
+                let dir = dir.try_clone()?;
+                let disk_usage = disk_usage.clone();
+                move || track_usage(dir, disk_usage, contract_usage, receiver)
+            })?;
         Ok(Self {
             state: Arc::new(FilesystemContractRuntimeCacheState {
                 dir,
                 any_cache: AnyCache::new(memory_cache_size),
+                disk_usage,
+                usage_sender: Some(usage_sender),
+                usage_tracker: Some(usage_tracker),
                 test_temp_dir: None,
             }),
         })
@@ -366,9 +693,13 @@ impl ContractRuntimeCache for FilesystemContractRuntimeCache {
         }
         file.write_all(&value.wasm_bytes.to_le_bytes())?;
         file.sync_data()?;
+        let size = file.metadata()?.len();
         drop(file);
         // This is atomic, so there wouldn't be instances where getters see an intermediate state.
         rustix::fs::renameat(&self.state.dir, temp_filename, &self.state.dir, final_filename)?;
+        if let Some(disk_usage) = &self.state.disk_usage {
+            disk_usage.lock().insert(&self.state.dir, *key, size);
+        }
 
         // NOTE: we do not remove the temporary file in case of failure in many of the
         // intermediate steps above. This is not considered to be a significant risk: any failure
@@ -393,10 +724,19 @@ impl ContractRuntimeCache for FilesystemContractRuntimeCache {
         let flags = OFlags::RDONLY;
         let file = rustix::fs::openat(&self.state.dir, &filename, flags, mode);
         let file = match file {
-            Err(rustix::io::Errno::NOENT) => return Ok(None),
+            Err(rustix::io::Errno::NOENT) => {
+                #[cfg(feature = "metrics")]
+                crate::metrics::record_compiled_contract_cache_disk_lookup(false);
+                return Ok(None);
+            }
             Err(e) => return Err(e.into()),
             Ok(file) => file,
         };
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_compiled_contract_cache_disk_lookup(true);
+        if let Some(disk_usage) = &self.state.disk_usage {
+            disk_usage.lock().entries.promote(key);
+        }
         let stat = rustix::fs::fstat(&file)?;
         // TODO: explore mmap-ing the file and lending the map to the caller via a closure callback.
         // This would require some additional refactor work, but would likely help us to reduce the
@@ -434,6 +774,23 @@ impl ContractRuntimeCache for FilesystemContractRuntimeCache {
         })
     }
 
+    fn record_contract_use(&self, code_hash: &CryptoHash, key: &CryptoHash) {
+        let Some(sender) = &self.state.usage_sender else { return };
+        // The uses are only used for the eviction order and the cache warmup, so rather than
+        // blocking the execution, they are dropped if the tracker thread falls behind.
+        let _ = sender.try_send(UsageMessage::Use { code_hash: *code_hash, key: *key });
+    }
+
+    fn hottest_contracts(&self, limit: usize) -> Vec<CryptoHash> {
+        let Some(sender) = &self.state.usage_sender else { return Vec::new() };
+        let (reply, receiver) = std::sync::mpsc::channel();
+        if sender.send(UsageMessage::Hottest { limit, reply }).is_err() {
+            return Vec::new();
+        }
+        // The uses sent before are handled by the time the tracker replies.
+        receiver.recv().unwrap_or_default()
+    }
+
     /// Clears the in-memory cache and files in the cache directory.
     ///
     /// The cache must be created using `test` method, otherwise this method will panic.
@@ -444,6 +801,14 @@ impl ContractRuntimeCache for FilesystemContractRuntimeCache {
             panic!("must be called for testing only");
         };
         self.memory_cache().clear();
+        if let Some(disk_usage) = &self.state.disk_usage {
+            let mut disk_usage = disk_usage.lock();
+            disk_usage.entries.clear();
+            disk_usage.total_size = 0;
+        }
+        if let Some(sender) = &self.state.usage_sender {
+            let _ = sender.send(UsageMessage::Clear);
+        }
         for entry in rustix::fs::Dir::read_from(&self.state.dir).unwrap() {
             if let Ok(entry) = entry {
                 let filename_bytes = entry.file_name().to_bytes();
@@ -722,4 +1087,92 @@ mod tests {
         // Insert the keys again and assert that the cache can be updated after clear.
         insert_and_assert_keys_exist();
     }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_disk_size_limit() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let open = |max_disk_size| {
+            FilesystemContractRuntimeCache::with_limits(
+                tempdir.path(),
+                None::<&str>,
+                "contract.cache",
+                0,
+                Some(max_disk_size),
+            )
+            .unwrap()
+        };
+        // Each entry takes 100 bytes on disk, including the tag and the wasm size.
+        let entry =
+            CompiledContractInfo { wasm_bytes: 0, compiled: CompiledContract::Code(vec![0; 91]) };
+        let keys = [b"1", b"2", b"3"].map(|k| CryptoHash::hash_bytes(k));
+
+        let cache = open(250);
+        cache.put(&keys[0], entry.clone()).unwrap();
+        cache.put(&keys[1], entry.clone()).unwrap();
+        // Reading the first entry makes the second one the least recently used.
+        assert!(cache.has(&keys[0]).unwrap());
+        cache.put(&keys[2], entry.clone()).unwrap();
+        assert!(cache.has(&keys[0]).unwrap());
+        assert!(!cache.has(&keys[1]).unwrap());
+        assert!(cache.has(&keys[2]).unwrap());
+        drop(cache);
+
+        // Reopening the cache with a lower limit evicts the existing entries right away.
+        let cache = open(150);
+        let present = keys.iter().filter(|key| cache.has(key).unwrap()).count();
+        assert_eq!(present, 1);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_disk_size_limit_counts_memory_hits() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let cache = FilesystemContractRuntimeCache::with_limits(
+            tempdir.path(),
+            None::<&str>,
+            "contract.cache",
+            0,
+            Some(250),
+        )
+        .unwrap();
+        let entry =
+            CompiledContractInfo { wasm_bytes: 0, compiled: CompiledContract::Code(vec![0; 91]) };
+        let keys = [b"1", b"2", b"3"].map(|k| CryptoHash::hash_bytes(k));
+
+        cache.put(&keys[0], entry.clone()).unwrap();
+        cache.put(&keys[1], entry.clone()).unwrap();
+        // A use served from the memory cache never reads the file, but still keeps it around.
+        cache.record_contract_use(&keys[0], &keys[0]);
+        // The uses recorded before are applied by the time the hottest contracts are returned.
+        assert_eq!(cache.hottest_contracts(1), vec![keys[0]]);
+        cache.put(&keys[2], entry.clone()).unwrap();
+        assert!(cache.has(&keys[0]).unwrap());
+        assert!(!cache.has(&keys[1]).unwrap());
+        assert!(cache.has(&keys[2]).unwrap());
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_hottest_contracts() {
+        let tempdir = tempfile::TempDir::new().unwrap();
+        let open = || {
+            FilesystemContractRuntimeCache::new(tempdir.path(), None::<&str>, "contract.cache")
+                .unwrap()
+        };
+        let keys = [b"1", b"2", b"3"].map(|k| CryptoHash::hash_bytes(k));
+
+        let cache = open();
+        cache.record_contract_use(&keys[0], &keys[0]);
+        for _ in 0..3 {
+            cache.record_contract_use(&keys[1], &keys[1]);
+        }
+        cache.record_contract_use(&keys[0], &keys[0]);
+        assert_eq!(cache.hottest_contracts(1), vec![keys[1]]);
+        drop(cache);
+
+        // The counts are persisted when the cache is dropped.
+        let cache = open();
+        assert_eq!(cache.hottest_contracts(10), vec![keys[1], keys[0]]);
+    }
 }
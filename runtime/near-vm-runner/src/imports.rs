@@ -328,6 +328,13 @@ pub(crate) const fn should_trace_host_function(host_function: &str) -> bool {
     }
 }
 
+/// Whether calls to the host function are recorded into the execution trace
+/// when host call tracing is enabled. Only host functions the contract calls
+/// explicitly are recorded, the instrumentation internals are not.
+pub(crate) const fn should_record_host_call(module: &str, host_function: &str) -> bool {
+    str_eq(module, "env") && should_trace_host_function(host_function)
+}
+
 /// Constant-time string equality, work-around for `"foo" == "bar"` not working
 /// in const context yet.
 const fn str_eq(s1: &str, s2: &str) -> bool {
@@ -62,6 +62,13 @@ pub struct VMContext {
     /// How many `DataReceipt`'s should receive this execution result. This should be empty if
     /// this function call is a part of a batch and it is not the last action.
     pub output_data_receivers: Vec<AccountId>,
+    /// If true, host function calls are recorded and returned in
+    /// [`super::VMOutcome::trace`]. Tracing does not affect the execution.
+    pub trace_host_calls: bool,
+    /// If true, the wasm frames that led to a trap or a failed host call are
+    /// returned in [`super::VMOutcome::backtrace`]. Capturing does not affect
+    /// the execution, but it requires [`super::Config::wasm_backtraces`].
+    pub capture_backtrace: bool,
 }
 
 impl VMContext {
@@ -3,6 +3,7 @@ use super::dependencies::{External, MemSlice, MemoryLike};
 use super::errors::{FunctionCallError, InconsistentStateError};
 use super::gas_counter::GasCounter;
 use super::recorded_storage_counter::RecordedStorageCounter;
+use super::trace::{ExecutionTrace, TraceEvent, WasmFrame};
 use super::types::{
     GlobalContractDeployMode, GlobalContractIdentifier, PromiseIndex, PromiseResult, ReceiptIndex,
     ReturnData,
@@ -49,6 +50,10 @@ pub struct ExecutionResultState {
     pub(crate) current_account_balance: Balance,
     /// Storage usage of the current account at the moment
     pub(crate) current_storage_usage: StorageUsage,
+    /// Host calls recorded so far, if tracing is enabled in the context.
+    pub(crate) trace: Option<ExecutionTrace>,
+    /// Wasm frames that led to the failure, if capturing is enabled in the context.
+    pub(crate) backtrace: Option<Vec<WasmFrame>>,
 }
 
 impl ExecutionResultState {
@@ -72,6 +77,8 @@ impl ExecutionResultState {
             return_data: ReturnData::None,
             current_account_balance,
             current_storage_usage,
+            trace: context.trace_host_calls.then(ExecutionTrace::default),
+            backtrace: None,
         }
     }
 
@@ -105,10 +112,51 @@ impl ExecutionResultState {
         if self.total_log_length > self.config.limit_config.max_total_log_length {
             return self.total_log_length_exceeded(len);
         }
+        self.trace_event(|| TraceEvent::Log { message: message.clone() });
         self.logs.push(message);
         Ok(())
     }
 
+    /// Records an effect of the current host call if tracing is enabled.
+    ///
+    /// The event is constructed lazily so that executions without tracing do
+    /// not pay for copying keys and values.
+    pub(crate) fn trace_event(&mut self, event: impl FnOnce() -> TraceEvent) {
+        if let Some(trace) = &mut self.trace {
+            trace.record_event(event());
+        }
+    }
+
+    /// Whether host calls are being traced. Checked before building the
+    /// arguments of [`Self::trace_host_call_begin`] so that executions
+    /// without tracing skip it altogether.
+    #[inline]
+    pub(crate) fn tracing_enabled(&self) -> bool {
+        self.trace.is_some()
+    }
+
+    pub(crate) fn trace_host_call_begin(
+        &mut self,
+        name: &'static str,
+        args: &[(&'static str, u64)],
+    ) {
+        if let Some(trace) = &mut self.trace {
+            trace.begin_host_call(name, args, self.gas_counter.burnt_gas());
+        }
+    }
+
+    pub(crate) fn trace_host_call_end(
+        &mut self,
+        registers: &super::vmstate::Registers,
+        error: Option<&VMLogicError>,
+    ) {
+        if let Some(trace) = &mut self.trace {
+            let burnt_gas = self.gas_counter.burnt_gas();
+            let gas_left = self.gas_counter.remaining_gas();
+            trace.end_host_call(registers, burnt_gas, gas_left, error);
+        }
+    }
+
     pub(crate) fn total_log_length_exceeded<T>(&self, add_len: u64) -> Result<T> {
         Err(HostError::TotalLogLengthExceeded {
             length: self.total_log_length.saturating_add(add_len),
@@ -140,6 +188,8 @@ impl ExecutionResultState {
             logs: self.logs,
             profile,
             aborted: None,
+            trace: self.trace,
+            backtrace: self.backtrace,
         }
     }
 }
@@ -267,6 +317,27 @@ impl<'a> VMLogic<'a> {
         &self.result_state.logs
     }
 
+    #[inline]
+    pub(crate) fn tracing_enabled(&self) -> bool {
+        self.result_state.tracing_enabled()
+    }
+
+    pub(crate) fn trace_host_call_begin(
+        &mut self,
+        name: &'static str,
+        args: &[(&'static str, u64)],
+    ) {
+        self.result_state.trace_host_call_begin(name, args);
+    }
+
+    pub(crate) fn trace_host_call_end(&mut self, error: Option<&VMLogicError>) {
+        self.result_state.trace_host_call_end(&self.registers, error);
+    }
+
+    pub(crate) fn capture_backtrace(&self) -> bool {
+        self.context.capture_backtrace && self.config.wasm_backtraces
+    }
+
     #[cfg(test)]
     pub(super) fn config(&self) -> &Config {
         &self.config
@@ -612,6 +683,14 @@ impl<'a> VMLogic<'a> {
         }
     }
 
+    fn trace_promise_created(&mut self, promise_index: PromiseIndex, receipt_index: ReceiptIndex) {
+        let ext = &*self.ext;
+        self.result_state.trace_event(|| TraceEvent::PromiseCreated {
+            promise_index,
+            receiver_id: ext.get_receipt_receiver(receipt_index).clone(),
+        });
+    }
+
     fn get_public_key(&mut self, ptr: u64, len: u64) -> Result<PublicKeyBuffer> {
         Ok(PublicKeyBuffer::new(&get_memory_or_register!(self, ptr, len)?))
     }
@@ -2088,7 +2167,9 @@ bls12381_p2_decompress_base + bls12381_p2_decompress_element * num_elements`
         self.pay_gas_for_new_receipt(sir, &[])?;
         let new_receipt_idx = self.ext.create_action_receipt(vec![], account_id)?;
 
-        self.checked_push_promise(Promise::Receipt(new_receipt_idx))
+        let promise_index = self.checked_push_promise(Promise::Receipt(new_receipt_idx))?;
+        self.trace_promise_created(promise_index, new_receipt_idx);
+        Ok(promise_index)
     }
 
     /// Creates a new promise towards given `account_id` without any actions attached, that is
@@ -2145,7 +2226,9 @@ bls12381_p2_decompress_base + bls12381_p2_decompress_element * num_elements`
 
         let new_receipt_idx = self.ext.create_action_receipt(receipt_dependencies, account_id)?;
 
-        self.checked_push_promise(Promise::Receipt(new_receipt_idx))
+        let promise_index = self.checked_push_promise(Promise::Receipt(new_receipt_idx))?;
+        self.trace_promise_created(promise_index, new_receipt_idx);
+        Ok(promise_index)
     }
 
     /// Sets the `refund_to` field on the promise
@@ -3620,6 +3703,11 @@ bls12381_p2_decompress_base + bls12381_p2_decompress_element * num_elements`
         self.result_state.gas_counter.pay_per(storage_write_key_byte, key.len() as u64)?;
         self.result_state.gas_counter.pay_per(storage_write_value_byte, value.len() as u64)?;
         let evicted = self.ext.storage_set(&mut self.result_state.gas_counter, &key, &value)?;
+        self.result_state.trace_event(|| TraceEvent::StorageWrite {
+            key: key.to_vec(),
+            value: value.to_vec(),
+            evicted: evicted.clone(),
+        });
         let storage_config = &self.fees_config.storage_usage_config;
         self.recorded_storage_counter.observe_size(self.ext.get_recorded_storage_size())?;
         match evicted {
@@ -3705,6 +3793,8 @@ bls12381_p2_decompress_base + bls12381_p2_decompress_element * num_elements`
             }
             None => None,
         };
+        self.result_state
+            .trace_event(|| TraceEvent::StorageRead { key: key.to_vec(), value: read.clone() });
 
         self.recorded_storage_counter.observe_size(self.ext.get_recorded_storage_size())?;
         match read {
@@ -3758,6 +3848,10 @@ bls12381_p2_decompress_base + bls12381_p2_decompress_element * num_elements`
         }
         self.result_state.gas_counter.pay_per(storage_remove_key_byte, key.len() as u64)?;
         let removed = self.ext.storage_remove(&mut self.result_state.gas_counter, &key)?;
+        self.result_state.trace_event(|| TraceEvent::StorageRemove {
+            key: key.to_vec(),
+            removed: removed.clone(),
+        });
         let storage_config = &self.fees_config.storage_usage_config;
         self.recorded_storage_counter.observe_size(self.ext.get_recorded_storage_size())?;
         match removed {
@@ -3811,7 +3905,10 @@ bls12381_p2_decompress_base + bls12381_p2_decompress_element * num_elements`
         let res = self.ext.storage_has_key(&mut self.result_state.gas_counter, &key);
 
         self.recorded_storage_counter.observe_size(self.ext.get_recorded_storage_size())?;
-        Ok(res? as u64)
+        let res = res?;
+        self.result_state
+            .trace_event(|| TraceEvent::StorageHasKey { key: key.to_vec(), exists: res });
+        Ok(res as u64)
     }
 
     /// Debug print given utf-8 string to node log. It's only available in Sandbox node
@@ -3978,6 +4075,12 @@ pub struct VMOutcome {
     /// Data collected from making a contract call
     pub profile: ProfileDataV3,
     pub aborted: Option<FunctionCallError>,
+    /// Host calls made by the contract, if requested with
+    /// [`VMContext::trace_host_calls`].
+    pub trace: Option<ExecutionTrace>,
+    /// Wasm frames that led to the failure, innermost first, if requested
+    /// with [`VMContext::capture_backtrace`]. Not part of the consensus.
+    pub backtrace: Option<Vec<WasmFrame>>,
 }
 
 impl VMOutcome {
@@ -4010,6 +4113,8 @@ impl VMOutcome {
             logs: Vec::new(),
             profile: ProfileDataV3::default(),
             aborted: Some(error),
+            trace: None,
+            backtrace: None,
         }
     }
 
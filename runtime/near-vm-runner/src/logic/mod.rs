@@ -10,6 +10,7 @@ pub mod recorded_storage_counter;
 pub mod test_utils;
 #[cfg(test)]
 mod tests;
+pub mod trace;
 pub mod types;
 pub(crate) mod utils;
 pub(crate) mod vmstate;
@@ -21,4 +22,5 @@ pub use gas_counter::{GasCounter, with_ext_cost_counter};
 pub use logic::{ExecutionResultState, VMLogic, VMOutcome};
 pub use near_parameters::vm::{Config, ContractPrepareVersion, LimitConfig};
 pub use near_primitives_core::types::ProtocolVersion;
+pub use trace::{ExecutionTrace, HostCallTrace, TraceEvent, WasmFrame};
 pub use types::ReturnData;
@@ -86,6 +86,8 @@ fn get_context() -> VMContext {
         random_seed: vec![0, 1, 2],
         view_config: None,
         output_data_receivers: vec![],
+        trace_host_calls: false,
+        capture_backtrace: false,
     }
 }
 
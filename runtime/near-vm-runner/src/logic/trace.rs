@@ -0,0 +1,126 @@
+//! Opt-in recording of the host functions called by a contract.
+//!
+//! Tracing is enabled per execution with [`VMContext::trace_host_calls`] and the
+//! recorded trace is returned in [`VMOutcome::trace`]. Recording never charges
+//! gas and never changes the outcome of the execution, so it can be enabled
+//! when re-executing receipts for debugging purposes.
+//!
+//! Similarly, [`VMContext::capture_backtrace`] makes the VM capture the wasm
+//! frames that led to a trap or a failed host call, returned in
+//! [`VMOutcome::backtrace`].
+//!
+//! [`VMContext::trace_host_calls`]: super::VMContext::trace_host_calls
+//! [`VMOutcome::trace`]: super::VMOutcome::trace
+//! [`VMContext::capture_backtrace`]: super::VMContext::capture_backtrace
+//! [`VMOutcome::backtrace`]: super::VMOutcome::backtrace
+
+use super::VMLogicError;
+use super::vmstate::Registers;
+use near_primitives_core::types::{AccountId, Gas};
+
+/// Host calls made during a single function call execution, in call order.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ExecutionTrace {
+    pub host_calls: Vec<HostCallTrace>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostCallTrace {
+    /// Name of the host function as imported by the contract.
+    pub name: &'static str,
+    /// Raw arguments the contract passed to the host function.
+    pub args: Vec<(&'static str, u64)>,
+    /// Gas burnt by the host call itself.
+    pub burnt_gas: Gas,
+    /// Gas left after the host call returned.
+    pub gas_left: Gas,
+    /// Effects observed while executing the host call.
+    pub events: Vec<TraceEvent>,
+    /// Contents of the registers referenced by the call arguments after the
+    /// call returned.
+    pub registers: Vec<(u64, Vec<u8>)>,
+    /// Error the host call failed with, if any.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceEvent {
+    StorageRead { key: Vec<u8>, value: Option<Vec<u8>> },
+    StorageWrite { key: Vec<u8>, value: Vec<u8>, evicted: Option<Vec<u8>> },
+    StorageRemove { key: Vec<u8>, removed: Option<Vec<u8>> },
+    StorageHasKey { key: Vec<u8>, exists: bool },
+    Log { message: String },
+    PromiseCreated { promise_index: u64, receiver_id: AccountId },
+}
+
+impl ExecutionTrace {
+    pub(crate) fn begin_host_call(
+        &mut self,
+        name: &'static str,
+        args: &[(&'static str, u64)],
+        burnt_gas: Gas,
+    ) {
+        self.host_calls.push(HostCallTrace {
+            name,
+            args: args.to_vec(),
+            // Holds the gas burnt before the call until `end_host_call`.
+            burnt_gas,
+            gas_left: Gas::ZERO,
+            events: vec![],
+            registers: vec![],
+            error: None,
+        });
+    }
+
+    pub(crate) fn record_event(&mut self, event: TraceEvent) {
+        if let Some(call) = self.host_calls.last_mut() {
+            call.events.push(event);
+        }
+    }
+
+    pub(crate) fn end_host_call(
+        &mut self,
+        registers: &Registers,
+        burnt_gas: Gas,
+        gas_left: Gas,
+        error: Option<&VMLogicError>,
+    ) {
+        let Some(call) = self.host_calls.last_mut() else {
+            return;
+        };
+        call.burnt_gas = burnt_gas.saturating_sub(call.burnt_gas);
+        call.gas_left = gas_left;
+        call.error = error.map(|err| err.to_string());
+        call.registers = call
+            .args
+            .iter()
+            .filter(|(name, _)| name.ends_with("register_id"))
+            .filter_map(|&(_, register_id)| {
+                registers.get_for_trace(register_id).map(|data| (register_id, data.to_vec()))
+            })
+            .collect();
+    }
+}
+
+/// A wasm frame of the backtrace captured when a contract execution failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmFrame {
+    /// Index of the function in the contract code as deployed, before the
+    /// instrumentation functions were injected.
+    pub func_index: u32,
+    /// Name of the function from the contract's `name` custom section, if the
+    /// VM was able to read it.
+    pub func_name: Option<String>,
+}
+
+impl WasmFrame {
+    /// Creates a frame from the index of the function in the prepared module.
+    ///
+    /// Returns `None` for the functions injected by the instrumentation, which
+    /// aren't part of the contract code.
+    pub(crate) fn new(prepared_func_index: u32) -> Option<Self> {
+        let func_index =
+            prepared_func_index.checked_sub(crate::prepare::INSTRUMENTATION_FUNCTIONS)?;
+        Some(Self { func_index, func_name: None })
+    }
+}
@@ -152,6 +152,13 @@ impl Registers {
         self.registers.get(&register_id).map(|data| &data[..])
     }
 
+    /// Returns register with given index without charging gas.
+    ///
+    /// Only meant for recording execution traces, see [`super::trace`].
+    pub(crate) fn get_for_trace(&self, register_id: u64) -> Option<&[u8]> {
+        self.registers.get(&register_id).map(|data| &data[..])
+    }
+
     /// Returns length of register with given index or None if no such register.
     pub(crate) fn get_len(&self, register_id: u64) -> Option<u64> {
         self.registers.get(&register_id).map(|data| data.len() as u64)
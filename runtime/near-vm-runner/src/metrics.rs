@@ -1,5 +1,6 @@
 use near_o11y::metrics::{
-    HistogramVec, IntCounterVec, try_create_histogram_vec, try_create_int_counter_vec,
+    HistogramVec, IntCounter, IntCounterVec, IntGauge, try_create_histogram_vec,
+    try_create_int_counter, try_create_int_counter_vec, try_create_int_gauge,
 };
 use std::sync::LazyLock;
 use std::{cell::RefCell, time::Duration};
@@ -41,6 +42,39 @@ static COMPILED_CONTRACT_CACHE_HITS_TOTAL: LazyLock<IntCounterVec> = LazyLock::n
     .unwrap()
 });
 
+static COMPILED_CONTRACT_CACHE_DISK_LOOKUPS_TOTAL: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    try_create_int_counter_vec(
+        "near_vm_compiled_contract_cache_disk_lookups_total",
+        "The number of lookups in the on-disk compiled-contract cache, by result (hit or miss)",
+        &["result"],
+    )
+    .unwrap()
+});
+
+static COMPILED_CONTRACT_CACHE_EVICTIONS_TOTAL: LazyLock<IntCounter> = LazyLock::new(|| {
+    try_create_int_counter(
+        "near_vm_compiled_contract_cache_evictions_total",
+        "The number of entries removed from the on-disk compiled-contract cache to stay within its size limit",
+    )
+    .unwrap()
+});
+
+static COMPILED_CONTRACT_CACHE_DISK_SIZE_BYTES: LazyLock<IntGauge> = LazyLock::new(|| {
+    try_create_int_gauge(
+        "near_vm_compiled_contract_cache_disk_size_bytes",
+        "Total size of the entries in the size-limited on-disk compiled-contract cache",
+    )
+    .unwrap()
+});
+
+static COMPILED_CONTRACT_CACHE_DISK_ENTRIES: LazyLock<IntGauge> = LazyLock::new(|| {
+    try_create_int_gauge(
+        "near_vm_compiled_contract_cache_disk_entries",
+        "The number of entries in the size-limited on-disk compiled-contract cache",
+    )
+    .unwrap()
+});
+
 #[derive(Default, Copy, Clone)]
 struct Metrics {
     near_vm_compilation_time: Duration,
@@ -74,6 +108,21 @@ pub(crate) fn record_compiled_contract_cache_lookup(is_hit: bool) {
     });
 }
 
+/// Records a lookup in the on-disk compiled-contract cache.
+pub(crate) fn record_compiled_contract_cache_disk_lookup(is_hit: bool) {
+    let result = if is_hit { "hit" } else { "miss" };
+    COMPILED_CONTRACT_CACHE_DISK_LOOKUPS_TOTAL.with_label_values(&[result]).inc();
+}
+
+pub(crate) fn record_compiled_contract_cache_eviction() {
+    COMPILED_CONTRACT_CACHE_EVICTIONS_TOTAL.inc();
+}
+
+pub(crate) fn set_compiled_contract_cache_disk_usage(size: u64, entries: usize) {
+    COMPILED_CONTRACT_CACHE_DISK_SIZE_BYTES.set(size.try_into().unwrap_or(i64::MAX));
+    COMPILED_CONTRACT_CACHE_DISK_ENTRIES.set(entries.try_into().unwrap_or(i64::MAX));
+}
+
 pub fn reset_metrics() {
     METRICS.with_borrow_mut(|m| *m = Metrics::default());
 }
@@ -6,7 +6,7 @@ use crate::logic::errors::{
 };
 use crate::logic::gas_counter::FastGasCounter;
 use crate::logic::{
-    Config, ExecutionResultState, External, GasCounter, VMContext, VMLogic, VMOutcome,
+    Config, ExecutionResultState, External, GasCounter, VMContext, VMLogic, VMOutcome, WasmFrame,
 };
 use crate::near_vm_runner::{NearVmCompiler, NearVmEngine};
 use crate::runner::VMResult;
@@ -55,7 +55,11 @@ fn get_entrypoint_index(
 fn translate_runtime_error(
     error: near_vm_engine::RuntimeError,
     logic: &mut VMLogic,
+    artifact: &UniversalArtifact,
 ) -> Result<FunctionCallError, VMRunnerError> {
+    if logic.capture_backtrace() {
+        logic.result_state.backtrace = Some(wasm_backtrace(&error, artifact));
+    }
     // Errors produced by host function calls also become `RuntimeError`s that wrap a dynamic
     // instance of `VMLogicError` internally. See the implementation of `NearVmImports`.
     let error = match error.downcast::<crate::logic::VMLogicError>() {
@@ -91,6 +95,23 @@ fn translate_runtime_error(
     })
 }
 
+/// Symbolicates the wasm frames of the native backtrace captured with the `error`.
+///
+/// The code compiled by near_vm has no unwind information, so the frames past the innermost wasm
+/// frame may be missing from the native backtrace.
+fn wasm_backtrace(
+    error: &near_vm_engine::RuntimeError,
+    artifact: &UniversalArtifact,
+) -> Vec<WasmFrame> {
+    error
+        .native_trace_pcs()
+        .filter_map(|pc| {
+            let index = artifact.function_index_at(pc)?;
+            WasmFrame::new(index.as_u32())
+        })
+        .collect()
+}
+
 pub(crate) struct NearVM {
     pub(crate) config: Arc<Config>,
     pub(crate) engine: UniversalEngine,
@@ -221,6 +242,7 @@ impl NearVM {
         // and then we set it to false when we fail to find any entry and decide to compile (by calling compile_and_cache below).
         let mut is_cache_hit = true;
         let key = get_contract_cache_key(contract.hash(), &self.config, near_vm_vm_hash());
+        cache.record_contract_use(&contract.hash(), &key);
         let (wasm_bytes, artifact_result) = cache.memory_cache().try_lookup(
             key,
             || {
@@ -368,7 +390,7 @@ impl NearVM {
                     Err(err) => {
                         use near_vm_engine::InstantiationError::*;
                         let abort = match err {
-                            Start(err) => translate_runtime_error(err, import.vmlogic)?,
+                            Start(err) => translate_runtime_error(err, import.vmlogic, artifact)?,
                             Link(e) => FunctionCallError::LinkError { msg: e.to_string() },
                             CpuFeature(e) => panic!(
                                 "host doesn't support the CPU features needed to run contracts: {}",
@@ -385,6 +407,7 @@ impl NearVM {
                         let abort = translate_runtime_error(
                             near_vm_engine::RuntimeError::from_trap(trap),
                             import.vmlogic,
+                            artifact,
                         )?;
                         return Ok(Err(abort));
                     }
@@ -416,6 +439,7 @@ impl NearVM {
                         let abort = translate_runtime_error(
                             near_vm_engine::RuntimeError::from_trap(trap),
                             import.vmlogic,
+                            artifact,
                         )?;
                         return Ok(Err(abort));
                     }
@@ -610,6 +634,7 @@ impl crate::runner::VM for NearVM {
                     entrypoint,
                     artifact: Arc::clone(artifact),
                     vm,
+                    original_code: config.wasm_backtraces.then(|| contract.get_code()).flatten(),
                 });
                 Ok(PreparedContract { config, gas_counter, result })
             },
@@ -639,6 +664,8 @@ struct ReadyContract {
     entrypoint: FunctionIndex,
     artifact: VMArtifact,
     vm: Box<NearVM>,
+    /// The code as deployed, to read the function names of backtraces from.
+    original_code: Option<Arc<ContractCode>>,
 }
 
 struct PreparedContract {
@@ -663,7 +690,7 @@ impl crate::PreparedContract for VMResult<PreparedContract> {
     ) -> VMResult {
         let PreparedContract { config, gas_counter, result } = (*self)?;
         let result_state = ExecutionResultState::new(&context, gas_counter, config);
-        let ReadyContract { mut memory, entrypoint, artifact, vm } = match result {
+        let ReadyContract { mut memory, entrypoint, artifact, vm, original_code } = match result {
             PreparationResult::Ready(r) => r,
             PreparationResult::OutcomeAbortButNopInOldProtocol(e) => {
                 return Ok(VMOutcome::abort_but_nop_outcome_in_old_protocol(result_state, e));
@@ -678,7 +705,14 @@ impl crate::PreparedContract for VMResult<PreparedContract> {
         let import = build_imports(vmmemory, &mut logic, config, artifact.engine());
         let result = match vm.run_method(&artifact, import, entrypoint)? {
             Ok(()) => Ok(VMOutcome::ok(logic.result_state)),
-            Err(err) => Ok(VMOutcome::abort(logic.result_state, err)),
+            Err(err) => {
+                if let (Some(frames), Some(code)) =
+                    (&mut logic.result_state.backtrace, &original_code)
+                {
+                    prepare::symbolicate_backtrace(frames, code.code());
+                }
+                Ok(VMOutcome::abort(logic.result_state, err))
+            }
         };
         lazy_drop(Box::new(memory));
         result
@@ -752,11 +786,21 @@ impl<'e, 'l, 'lr> Resolver for NearVmImports<'e, 'l, 'lr> {
                                 tracing::trace_span!(target: "vm::host_function", stringify!($name)).entered()
                             });
 
+                            const RECORD: bool = $crate::imports::should_record_host_call(stringify!($mod), stringify!($name));
                             // SAFETY: This code should only be executable within `'vmlogic`
                             // lifetime and so it is safe to dereference the `env` pointer which is
                             // known to be derived from a valid `&'vmlogic mut VMLogic<'_>` in the
                             // first place.
-                            unsafe { (*env).$func( $( $arg_name, )* ) }
+                            let logic = unsafe { &mut *env };
+                            let record = RECORD && logic.tracing_enabled();
+                            if record {
+                                logic.trace_host_call_begin(stringify!($name), &[$( (stringify!($arg_name), u64::from($arg_name)) ),*]);
+                            }
+                            let result = logic.$func( $( $arg_name, )* );
+                            if record {
+                                logic.trace_host_call_end(result.as_ref().err());
+                            }
+                            result
                         }));
                         // We want to ensure that the only kind of error that host function calls
                         // return are VMLogicError. This is important because we later attempt to
@@ -766,9 +810,22 @@ impl<'e, 'l, 'lr> Resolver for NearVmImports<'e, 'l, 'lr> {
                         match result {
                             Ok(Ok(($($returns),*))) => make_ret($($returns),*),
                             Ok(Err(trap)) => unsafe {
+                                // SAFETY: see above, `env` is still valid here.
+                                let capture_backtrace = (&*env).capture_backtrace();
+                                let trap: Box<dyn std::error::Error + Send + Sync> =
+                                    if capture_backtrace {
+                                        // The backtrace has to be captured while the contract
+                                        // frames are still on the stack.
+                                        let trap = near_vm_engine::RuntimeError::user_with_backtrace(
+                                            Box::new(trap),
+                                        );
+                                        Box::new(trap)
+                                    } else {
+                                        Box::new(trap)
+                                    };
                                 // SAFETY: this can only be called by a WASM contract, so all the
                                 // necessary hooks are known to be in place.
-                                near_vm_vm::raise_user_trap(Box::new(trap))
+                                near_vm_vm::raise_user_trap(trap)
                             },
                             Err(e) => unsafe {
                                 // SAFETY: this can only be called by a WASM contract, so all the
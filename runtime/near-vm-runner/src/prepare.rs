@@ -8,6 +8,49 @@ mod instrument_v3;
 mod prepare_v2;
 mod prepare_v3;
 
+/// Number of functions the instrumentation prepends to the functions of the contract, shifting
+/// their indices in the prepared module.
+pub(crate) const INSTRUMENTATION_FUNCTIONS: u32 = instrument_v3::F;
+
+/// Fills in the names of the backtrace `frames` from the `name` section of `original_code`.
+///
+/// The `name` section is discarded when preparing the contract, so this reads it from the code
+/// as deployed. This is only used to make backtraces readable outside of the consensus, so a
+/// malformed `name` section is not an error: whatever was read before the malformed part is used.
+pub(crate) fn symbolicate_backtrace(frames: &mut [crate::logic::WasmFrame], original_code: &[u8]) {
+    use wasmparser_236 as wp;
+
+    let mut names = std::collections::HashMap::new();
+    'payloads: for payload in wp::Parser::new(0).parse_all(original_code) {
+        let Ok(payload) = payload else {
+            break;
+        };
+        let wp::Payload::CustomSection(reader) = payload else {
+            continue;
+        };
+        let wp::KnownCustom::Name(reader) = reader.as_known() else {
+            continue;
+        };
+        for name in reader {
+            let Ok(name) = name else {
+                break 'payloads;
+            };
+            let wp::Name::Function(map) = name else {
+                continue;
+            };
+            for naming in map {
+                let Ok(naming) = naming else {
+                    break 'payloads;
+                };
+                names.insert(naming.index, naming.name);
+            }
+        }
+    }
+    for frame in frames {
+        frame.func_name = names.get(&frame.func_index).map(|name| name.to_string());
+    }
+}
+
 /// Loads the given module given in `original_code`, performs some checks on it and
 /// does some preprocessing.
 ///
@@ -158,4 +201,24 @@ mod tests {
             */
         })
     }
+
+    #[test]
+    fn symbolicate_backtrace_ignores_malformed_names() {
+        use crate::logic::WasmFrame;
+
+        let mut wasm = wat::parse_str(r#"(module (func $f) (func $g))"#).unwrap();
+        let mut frames = vec![WasmFrame { func_index: 1, func_name: None }];
+        symbolicate_backtrace(&mut frames, &wasm);
+        assert_eq!(frames[0].func_name.as_deref(), Some("g"));
+
+        // A malformed `name` section only stops the lookup, the names read before it are kept.
+        wasm.extend_from_slice(&[0, 6, 4, b'n', b'a', b'm', b'e', 0xff]);
+        symbolicate_backtrace(&mut frames, &wasm);
+        assert_eq!(frames[0].func_name.as_deref(), Some("g"));
+
+        let mut wasm = wat::parse_str(r#"(module (func) (func))"#).unwrap();
+        wasm.extend_from_slice(&[0, 6, 4, b'n', b'a', b'm', b'e', 0xff]);
+        symbolicate_backtrace(&mut frames, &wasm);
+        assert_eq!(frames[0].func_name, None);
+    }
 }
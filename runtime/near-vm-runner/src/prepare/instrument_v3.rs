@@ -29,7 +29,7 @@ const STACK_EXHAUSTED_FN: u32 = GAS_EXHAUSTED_FN + 1;
 const GAS_INSTRUMENTATION_FN: u32 = STACK_EXHAUSTED_FN + 1;
 
 /// Total number of injected functions in the instrumented module.
-const F: u32 = GAS_INSTRUMENTATION_FN + 1;
+pub(super) const F: u32 = GAS_INSTRUMENTATION_FN + 1;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
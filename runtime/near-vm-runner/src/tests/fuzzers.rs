@@ -60,6 +60,8 @@ pub fn create_context(input: Vec<u8>) -> VMContext {
         random_seed: vec![0, 1, 2],
         view_config: None,
         output_data_receivers: vec![],
+        trace_host_calls: false,
+        capture_backtrace: false,
     }
 }
 
@@ -169,3 +171,219 @@ fn slow_test_wasmtime_vm_is_reproducible_fuzzer() {
         }
     })
 }
+
+/// Host functions imported by [`HostCallContract`].
+#[cfg(feature = "prepare")]
+const HOST_CALL_CONTRACT_IMPORTS: &str = r#"
+  (import "env" "storage_write" (func $storage_write (param i64 i64 i64 i64 i64) (result i64)))
+  (import "env" "storage_read" (func $storage_read (param i64 i64 i64) (result i64)))
+  (import "env" "storage_remove" (func $storage_remove (param i64 i64 i64) (result i64)))
+  (import "env" "storage_has_key" (func $storage_has_key (param i64 i64) (result i64)))
+  (import "env" "input" (func $input (param i64)))
+  (import "env" "read_register" (func $read_register (param i64 i64)))
+  (import "env" "register_len" (func $register_len (param i64) (result i64)))
+  (import "env" "write_register" (func $write_register (param i64 i64 i64)))
+  (import "env" "log_utf8" (func $log_utf8 (param i64 i64)))
+  (import "env" "panic_utf8" (func $panic_utf8 (param i64 i64)))
+  (import "env" "value_return" (func $value_return (param i64 i64)))
+  (import "env" "sha256" (func $sha256 (param i64 i64 i64)))
+  (import "env" "keccak256" (func $keccak256 (param i64 i64 i64)))
+  (import "env" "block_index" (func $block_index (result i64)))
+  (import "env" "used_gas" (func $used_gas (result i64)))
+  (import "env" "account_balance" (func $account_balance (param i64)))
+  (import "env" "attached_deposit" (func $attached_deposit (param i64)))
+  (import "env" "promise_batch_create" (func $promise_batch_create (param i64 i64) (result i64)))
+  (import "env" "promise_batch_action_transfer" (func $promise_batch_action_transfer (param i64 i64)))
+"#;
+
+/// Offset of the receiver account id used by [`HostCall::PromiseTransfer`].
+#[cfg(feature = "prepare")]
+const HOST_CALL_CONTRACT_RECEIVER_PTR: u16 = 0xf000;
+#[cfg(feature = "prepare")]
+const HOST_CALL_CONTRACT_RECEIVER: &str = "bob.near";
+
+/// A single host function call (or a bit of plain wasm) in a [`HostCallContract`].
+///
+/// Pointers are limited to the single page of memory the contract has and lengths are kept short,
+/// so that most of the calls get past the argument validation.
+#[cfg(feature = "prepare")]
+#[derive(arbitrary::Arbitrary, Debug)]
+enum HostCall {
+    StorageWrite { key_ptr: u16, key_len: u8, value_ptr: u16, value_len: u8, register: u8 },
+    StorageRead { key_ptr: u16, key_len: u8, register: u8 },
+    StorageRemove { key_ptr: u16, key_len: u8, register: u8 },
+    StorageHasKey { key_ptr: u16, key_len: u8 },
+    Input { register: u8 },
+    ReadRegister { register: u8, ptr: u16 },
+    RegisterLen { register: u8 },
+    WriteRegister { register: u8, ptr: u16, len: u8 },
+    Log { ptr: u16, len: u8 },
+    Panic { ptr: u16, len: u8 },
+    ValueReturn { ptr: u16, len: u8 },
+    Sha256 { ptr: u16, len: u8, register: u8 },
+    Keccak256 { ptr: u16, len: u8, register: u8 },
+    BlockIndex,
+    UsedGas,
+    AccountBalance { ptr: u16 },
+    AttachedDeposit { ptr: u16 },
+    PromiseTransfer { amount_ptr: u16 },
+    Store { ptr: u16, value: u64 },
+    Loop { iterations: u16 },
+    Unreachable,
+}
+
+#[cfg(feature = "prepare")]
+impl HostCall {
+    fn to_wat(&self) -> String {
+        match *self {
+            HostCall::StorageWrite { key_ptr, key_len, value_ptr, value_len, register } => format!(
+                "(drop (call $storage_write (i64.const {key_len}) (i64.const {key_ptr}) (i64.const {value_len}) (i64.const {value_ptr}) (i64.const {register})))"
+            ),
+            HostCall::StorageRead { key_ptr, key_len, register } => format!(
+                "(drop (call $storage_read (i64.const {key_len}) (i64.const {key_ptr}) (i64.const {register})))"
+            ),
+            HostCall::StorageRemove { key_ptr, key_len, register } => format!(
+                "(drop (call $storage_remove (i64.const {key_len}) (i64.const {key_ptr}) (i64.const {register})))"
+            ),
+            HostCall::StorageHasKey { key_ptr, key_len } => format!(
+                "(drop (call $storage_has_key (i64.const {key_len}) (i64.const {key_ptr})))"
+            ),
+            HostCall::Input { register } => format!("(call $input (i64.const {register}))"),
+            HostCall::ReadRegister { register, ptr } => {
+                format!("(call $read_register (i64.const {register}) (i64.const {ptr}))")
+            }
+            HostCall::RegisterLen { register } => {
+                format!("(drop (call $register_len (i64.const {register})))")
+            }
+            HostCall::WriteRegister { register, ptr, len } => format!(
+                "(call $write_register (i64.const {register}) (i64.const {len}) (i64.const {ptr}))"
+            ),
+            HostCall::Log { ptr, len } => {
+                format!("(call $log_utf8 (i64.const {len}) (i64.const {ptr}))")
+            }
+            HostCall::Panic { ptr, len } => {
+                format!("(call $panic_utf8 (i64.const {len}) (i64.const {ptr}))")
+            }
+            HostCall::ValueReturn { ptr, len } => {
+                format!("(call $value_return (i64.const {len}) (i64.const {ptr}))")
+            }
+            HostCall::Sha256 { ptr, len, register } => {
+                format!("(call $sha256 (i64.const {len}) (i64.const {ptr}) (i64.const {register}))")
+            }
+            HostCall::Keccak256 { ptr, len, register } => format!(
+                "(call $keccak256 (i64.const {len}) (i64.const {ptr}) (i64.const {register}))"
+            ),
+            HostCall::BlockIndex => "(drop (call $block_index))".to_string(),
+            HostCall::UsedGas => "(drop (call $used_gas))".to_string(),
+            HostCall::AccountBalance { ptr } => {
+                format!("(call $account_balance (i64.const {ptr}))")
+            }
+            HostCall::AttachedDeposit { ptr } => {
+                format!("(call $attached_deposit (i64.const {ptr}))")
+            }
+            HostCall::PromiseTransfer { amount_ptr } => format!(
+                "(call $promise_batch_action_transfer (call $promise_batch_create (i64.const {}) (i64.const {HOST_CALL_CONTRACT_RECEIVER_PTR})) (i64.const {amount_ptr}))",
+                HOST_CALL_CONTRACT_RECEIVER.len()
+            ),
+            HostCall::Store { ptr, value } => {
+                format!("(i64.store (i32.const {ptr}) (i64.const {value}))")
+            }
+            HostCall::Loop { iterations } => format!(
+                "(local.set $i (i64.const {iterations}))
+                 (block (loop
+                   (br_if 1 (i64.eqz (local.get $i)))
+                   (local.set $i (i64.sub (local.get $i) (i64.const 1)))
+                   (br 0)))"
+            ),
+            HostCall::Unreachable => "unreachable".to_string(),
+        }
+    }
+}
+
+/// A contract exporting a `main` method that performs a sequence of [`HostCall`]s.
+///
+/// Unlike [`ArbitraryModule`], whose host function calls rarely get past the argument validation,
+/// these contracts exercise the host function implementations and their gas accounting.
+#[cfg(feature = "prepare")]
+#[derive(arbitrary::Arbitrary)]
+struct HostCallContract {
+    /// Initial contents of the memory, starting at offset 0.
+    data: Vec<u8>,
+    /// Gas attached to the call, in gigagas, so that running out of gas is reasonably likely.
+    prepaid_gas: u16,
+    calls: Vec<HostCall>,
+}
+
+#[cfg(feature = "prepare")]
+impl HostCallContract {
+    fn to_wat(&self) -> String {
+        let data: String = self.data.iter().map(|b| format!("\\{b:02x}")).collect();
+        let body: Vec<String> = self.calls.iter().map(HostCall::to_wat).collect();
+        format!(
+            "(module {HOST_CALL_CONTRACT_IMPORTS}
+               (memory 1)
+               (data (i32.const 0) \"{data}\")
+               (data (i32.const {HOST_CALL_CONTRACT_RECEIVER_PTR}) \"{HOST_CALL_CONTRACT_RECEIVER}\")
+               (func (export \"main\") (local $i i64)
+                 {}))",
+            body.join("\n")
+        )
+    }
+}
+
+/// Prints the contract as text, so that a failure report can be turned into a regression test.
+#[cfg(feature = "prepare")]
+impl std::fmt::Debug for HostCallContract {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "prepaid gas: {} Ggas", self.prepaid_gas)?;
+        f.write_str(&self.to_wat())
+    }
+}
+
+/// Everything observable about a single execution of a [`HostCallContract`].
+#[cfg(feature = "prepare")]
+#[derive(Debug, PartialEq)]
+struct HostCallOutcome {
+    outcome: crate::logic::VMOutcome,
+    storage: std::collections::BTreeMap<Vec<u8>, Vec<u8>>,
+    actions: String,
+}
+
+#[cfg(feature = "prepare")]
+fn run_host_call_contract(contract: &HostCallContract, vm_kind: VMKind) -> HostCallOutcome {
+    let code = ContractCode::new(wat::parse_str(contract.to_wat()).unwrap(), None);
+    let mut fake_external = MockedExternal::with_code(code);
+    let mut context = create_context(b"fuzzer input".to_vec());
+    context.prepaid_gas =
+        near_primitives_core::types::Gas::from_gigagas(u64::from(contract.prepaid_gas) + 1);
+    context.trace_host_calls = true;
+    let config = test_vm_config(Some(vm_kind));
+    let fees = Arc::new(RuntimeFeesConfig::test());
+    let gas_counter = context.make_gas_counter(&config);
+    let outcome = vm_kind
+        .runtime(config.into())
+        .unwrap()
+        .prepare(&fake_external, None, gas_counter, "main")
+        .run(&mut fake_external, &context, fees)
+        .unwrap_or_else(|err| panic!("fatal error: {err:?}"));
+    HostCallOutcome {
+        outcome,
+        storage: fake_external.fake_trie.into_iter().collect(),
+        actions: format!("{:?}", fake_external.action_log),
+    }
+}
+
+/// Unlike [`slow_test_near_vm_and_wasmtime_agree_fuzzer`] this compares the outcomes in full,
+/// including the error messages, the host call trace and the effects on the storage and actions.
+#[test]
+#[cfg_attr(not(all(feature = "wasmtime_vm", feature = "near_vm", target_arch = "x86_64")), ignore)]
+#[cfg(feature = "prepare")]
+fn slow_test_near_vm_and_wasmtime_agree_on_host_calls_fuzzer() {
+    bolero::check!().with_arbitrary::<HostCallContract>().for_each(
+        |contract: &HostCallContract| {
+            let near_vm = run_host_call_contract(contract, VMKind::NearVm);
+            let wasmtime = run_host_call_contract(contract, VMKind::Wasmtime);
+            assert_eq!(near_vm, wasmtime);
+        },
+    );
+}
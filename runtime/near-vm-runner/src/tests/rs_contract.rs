@@ -2,6 +2,7 @@ use crate::ContractCode;
 use crate::logic::Config;
 use crate::logic::errors::{FunctionCallError, HostError, WasmTrap};
 use crate::logic::mocks::mock_external::{MockAction, MockedExternal};
+use crate::logic::trace::TraceEvent;
 use crate::logic::types::ReturnData;
 use crate::runner::VMKindExt;
 use near_parameters::RuntimeFeesConfig;
@@ -77,6 +78,106 @@ pub fn test_read_write() {
     });
 }
 
+#[test]
+pub fn test_trace_host_calls() {
+    with_vm_variants(|vm_kind: VMKind| {
+        let config = Arc::new(test_vm_config(Some(vm_kind)));
+        let fees = Arc::new(RuntimeFeesConfig::test());
+        let code = test_contract(vm_kind);
+        let mut fake_external = MockedExternal::with_code(code);
+        let mut context = create_context(encode(&[10u64, 20u64]));
+        context.trace_host_calls = true;
+
+        let runtime = vm_kind.runtime(config.clone()).expect("runtime has not been compiled");
+        let gas_counter = context.make_gas_counter(&config);
+        let outcome = runtime
+            .prepare(&fake_external, None, gas_counter, "write_key_value")
+            .run(&mut fake_external, &context, Arc::clone(&fees))
+            .expect("Failed execution");
+        let trace = outcome.trace.expect("trace was not recorded");
+        assert_eq!(trace.host_calls[0].name, "input");
+        let storage_write = trace
+            .host_calls
+            .iter()
+            .find(|call| call.name == "storage_write")
+            .expect("storage_write was not traced");
+        assert_eq!(
+            storage_write.events,
+            [TraceEvent::StorageWrite {
+                key: 10u64.to_le_bytes().to_vec(),
+                value: 20u64.to_le_bytes().to_vec(),
+                evicted: None,
+            }]
+        );
+        assert!(storage_write.burnt_gas > Gas::ZERO);
+        assert_eq!(trace.host_calls[0].registers, [(0, encode(&[10u64, 20u64]))]);
+
+        // Tracing is off by default.
+        let context = create_context(encode(&[10u64]));
+        let runtime = vm_kind.runtime(config.clone()).expect("runtime has not been compiled");
+        let gas_counter = context.make_gas_counter(&config);
+        let outcome = runtime
+            .prepare(&fake_external, None, gas_counter, "read_value")
+            .run(&mut fake_external, &context, Arc::clone(&fees))
+            .expect("Failed execution");
+        assert_eq!(outcome.trace, None);
+    });
+}
+
+#[test]
+pub fn test_capture_backtrace() {
+    let code = wat::parse_str(
+        r#"
+(module
+  (import "env" "panic" (func $panic))
+  (func $inner (param i32)
+    (if (local.get 0) (then (call $panic)) (else unreachable)))
+  (func $outer (param i32) (call $inner (local.get 0)))
+  (func (export "trap") (call $outer (i32.const 0)))
+  (func (export "host_error") (call $outer (i32.const 1))))"#,
+    )
+    .unwrap();
+    with_vm_variants(|vm_kind: VMKind| {
+        let mut config = test_vm_config(Some(vm_kind));
+        config.wasm_backtraces = true;
+        let config = Arc::new(config);
+        let fees = Arc::new(RuntimeFeesConfig::test());
+        let mut fake_external = MockedExternal::with_code(ContractCode::new(code.clone(), None));
+        let mut context = create_context(vec![]);
+        context.capture_backtrace = true;
+
+        for method in ["trap", "host_error"] {
+            let runtime = vm_kind.runtime(config.clone()).expect("runtime has not been compiled");
+            let gas_counter = context.make_gas_counter(&config);
+            let outcome = runtime
+                .prepare(&fake_external, None, gas_counter, method)
+                .run(&mut fake_external, &context, Arc::clone(&fees))
+                .expect("Failed execution");
+            assert!(outcome.aborted.is_some(), "{method} did not fail");
+            let backtrace = outcome.backtrace.expect("backtrace was not captured");
+            let innermost = backtrace.first().expect("backtrace is empty");
+            assert_eq!(innermost.func_index, 1, "{method}");
+            assert_eq!(innermost.func_name.as_deref(), Some("inner"), "{method}");
+        }
+
+        // Backtraces are only captured on request, and only if the config allows them.
+        let consensus_config = Arc::new(test_vm_config(Some(vm_kind)));
+        for (config, context) in [
+            (Arc::clone(&config), create_context(vec![])),
+            (Arc::clone(&consensus_config), context.clone()),
+        ] {
+            let runtime = vm_kind.runtime(config.clone()).expect("runtime has not been compiled");
+            let gas_counter = context.make_gas_counter(&config);
+            let outcome = runtime
+                .prepare(&fake_external, None, gas_counter, "trap")
+                .run(&mut fake_external, &context, Arc::clone(&fees))
+                .expect("Failed execution");
+            assert!(outcome.aborted.is_some());
+            assert_eq!(outcome.backtrace, None);
+        }
+    });
+}
+
 macro_rules! def_test_ext {
     ($name:ident, $method:expr, $expected:expr, $input:expr, $validator:expr) => {
         #[test]
@@ -30,6 +30,8 @@ pub(crate) fn test_builder() -> TestBuilder {
         random_seed: vec![0, 1, 2],
         view_config: None,
         output_data_receivers: vec![],
+        trace_host_calls: false,
+        capture_backtrace: false,
     };
     let mut skip = HashSet::new();
     for kind in [VMKind::NearVm, VMKind::Wasmtime] {
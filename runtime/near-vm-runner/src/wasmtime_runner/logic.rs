@@ -4,6 +4,7 @@ use crate::logic::bls12381;
 use crate::logic::errors::InconsistentStateError;
 use crate::logic::gas_counter::{FreeGasCounter, GasCounter};
 use crate::logic::logic::*;
+use crate::logic::trace::TraceEvent;
 use crate::logic::types::{
     GlobalContractDeployMode, GlobalContractIdentifier, PromiseIndex, PromiseResult, ReceiptIndex,
     ReturnData,
@@ -535,6 +536,14 @@ fn checked_push_promise(ctx: &mut Ctx, promise: Promise) -> Result<PromiseIndex>
     }
 }
 
+fn trace_promise_created(ctx: &mut Ctx, promise_index: PromiseIndex, receipt_index: ReceiptIndex) {
+    let ext = &*ctx.ext;
+    ctx.result_state.trace_event(|| TraceEvent::PromiseCreated {
+        promise_index,
+        receiver_id: ext.get_receipt_receiver(receipt_index).clone(),
+    });
+}
+
 fn get_public_key(
     gas_counter: &mut GasCounter,
     memory: &[u8],
@@ -2161,7 +2170,9 @@ pub fn promise_batch_create(
     pay_gas_for_new_receipt(&mut ctx.result_state.gas_counter, &ctx.fees_config, sir, &[])?;
     let new_receipt_idx = ctx.ext.create_action_receipt(vec![], account_id)?;
 
-    checked_push_promise(ctx, Promise::Receipt(new_receipt_idx))
+    let promise_index = checked_push_promise(ctx, Promise::Receipt(new_receipt_idx))?;
+    trace_promise_created(ctx, promise_index, new_receipt_idx);
+    Ok(promise_index)
 }
 
 /// Creates a new promise towards given `account_id` without any actions attached, that is
@@ -2226,7 +2237,9 @@ pub fn promise_batch_then(
 
     let new_receipt_idx = ctx.ext.create_action_receipt(receipt_dependencies, account_id)?;
 
-    checked_push_promise(ctx, Promise::Receipt(new_receipt_idx))
+    let promise_index = checked_push_promise(ctx, Promise::Receipt(new_receipt_idx))?;
+    trace_promise_created(ctx, promise_index, new_receipt_idx);
+    Ok(promise_index)
 }
 
 /// Sets the `refund_to` field on the promise
@@ -4019,6 +4032,11 @@ pub fn storage_write(
     ctx.result_state.gas_counter.pay_per(storage_write_key_byte, key.len() as u64)?;
     ctx.result_state.gas_counter.pay_per(storage_write_value_byte, value.len() as u64)?;
     let evicted = ctx.ext.storage_set(&mut ctx.result_state.gas_counter, &key, &value)?;
+    ctx.result_state.trace_event(|| TraceEvent::StorageWrite {
+        key: key.to_vec(),
+        value: value.to_vec(),
+        evicted: evicted.clone(),
+    });
     let storage_config = &ctx.fees_config.storage_usage_config;
     ctx.recorded_storage_counter.observe_size(ctx.ext.get_recorded_storage_size())?;
     match evicted {
@@ -4115,6 +4133,8 @@ pub fn storage_read(
         }
         None => None,
     };
+    ctx.result_state
+        .trace_event(|| TraceEvent::StorageRead { key: key.to_vec(), value: read.clone() });
 
     ctx.recorded_storage_counter.observe_size(ctx.ext.get_recorded_storage_size())?;
     match read {
@@ -4181,6 +4201,8 @@ pub fn storage_remove(
     }
     ctx.result_state.gas_counter.pay_per(storage_remove_key_byte, key.len() as u64)?;
     let removed = ctx.ext.storage_remove(&mut ctx.result_state.gas_counter, &key)?;
+    ctx.result_state
+        .trace_event(|| TraceEvent::StorageRemove { key: key.to_vec(), removed: removed.clone() });
     let storage_config = &ctx.fees_config.storage_usage_config;
     ctx.recorded_storage_counter.observe_size(ctx.ext.get_recorded_storage_size())?;
     match removed {
@@ -4240,7 +4262,9 @@ pub fn storage_has_key(caller: &mut Caller<'_, Ctx>, key_len: u64, key_ptr: u64)
     let res = ctx.ext.storage_has_key(&mut ctx.result_state.gas_counter, &key);
 
     ctx.recorded_storage_counter.observe_size(ctx.ext.get_recorded_storage_size())?;
-    Ok(res? as u64)
+    let res = res?;
+    ctx.result_state.trace_event(|| TraceEvent::StorageHasKey { key: key.to_vec(), exists: res });
+    Ok(res as u64)
 }
 
 /// Debug print given utf-8 string to node log. It's only available in Sandbox node
@@ -7,7 +7,9 @@ use crate::logic::errors::{
 use crate::logic::logic::Promise;
 use crate::logic::recorded_storage_counter::RecordedStorageCounter;
 use crate::logic::vmstate::Registers;
-use crate::logic::{Config, ExecutionResultState, External, GasCounter, VMContext, VMOutcome};
+use crate::logic::{
+    Config, ExecutionResultState, External, GasCounter, VMContext, VMOutcome, WasmFrame,
+};
 use crate::runner::VMResult;
 use crate::{
     CompiledContract, CompiledContractInfo, Contract, ContractCode, ContractRuntimeCache,
@@ -27,7 +29,7 @@ use std::sync::{Arc, LazyLock};
 use wasmtime::{
     CallHook, Engine, Extern, ExternType, Instance, InstanceAllocationStrategy, InstancePre,
     Linker, Memory, Module, ModuleExport, PoolingAllocationConfig, ResourcesRequired, Store,
-    StoreLimits, StoreLimitsBuilder, Strategy, Val, WasmBacktraceDetails,
+    StoreLimits, StoreLimitsBuilder, Strategy, Val, WasmBacktrace, WasmBacktraceDetails,
 };
 
 mod logic;
@@ -417,7 +419,9 @@ impl WasmtimeVM {
                 // > unwinding information which can greatly slow down the module loading/unloading process.
                 // https://docs.rs/wasmtime/latest/wasmtime/struct.Config.html#method.native_unwind_info
                 .native_unwind_info(false)
-                .wasm_backtrace(false)
+                // Capturing backtraces slows down traps, so it is only enabled for the engines
+                // that run view calls and debugging tools.
+                .wasm_backtrace(config.wasm_backtraces)
                 .wasm_backtrace_details(WasmBacktraceDetails::Disable)
                 // Enable copy-on-write heap images.
                 .memory_init_cow(true)
@@ -507,6 +511,7 @@ impl WasmtimeVM {
             (u64, Result<Result<PreparedModule, FunctionCallError>, CompilationError>);
         let to_any = |v: MemoryCacheType| -> Box<dyn std::any::Any + Send> { Box::new(v) };
         let key = get_contract_cache_key(contract.hash(), &self.config, self.vm_hash());
+        cache.record_contract_use(&contract.hash(), &key);
         let (wasm_bytes, pre_result) = cache.memory_cache().try_lookup(
             key,
             || {
@@ -671,6 +676,10 @@ impl crate::runner::VM for WasmtimeVM {
                             num_tables,
                             method: method.into(),
                             concurrency: self.concurrency.clone(),
+                            original_code: config
+                                .wasm_backtraces
+                                .then(|| code.get_code())
+                                .flatten(),
                         });
                         Ok(PreparedContract { config, gas_counter, result })
                     }
@@ -692,6 +701,8 @@ struct ReadyContract {
     method: Box<str>,
     num_tables: u32,
     concurrency: ConcurrencySemaphore,
+    /// The code as deployed, to read the function names of backtraces from.
+    original_code: Option<Arc<ContractCode>>,
 }
 
 struct PreparedContract {
@@ -722,10 +733,30 @@ enum RunOutcome {
     Abort(FunctionCallError),
 }
 
+/// Records the wasm frames wasmtime attached to the `err`, if requested in the context.
+fn record_backtrace(
+    store: &mut Store<Ctx>,
+    err: &anyhow::Error,
+    original_code: Option<&ContractCode>,
+) {
+    let ctx = store.data_mut();
+    if !ctx.context.capture_backtrace || !ctx.result_state.config.wasm_backtraces {
+        return;
+    }
+    let mut frames = err.downcast_ref::<WasmBacktrace>().map_or_else(Vec::new, |backtrace| {
+        backtrace.frames().iter().filter_map(|frame| WasmFrame::new(frame.func_index())).collect()
+    });
+    if let Some(code) = original_code {
+        crate::prepare::symbolicate_backtrace(&mut frames, code.code());
+    }
+    ctx.result_state.backtrace = Some(frames);
+}
+
 fn call(
     mut store: &mut Store<Ctx>,
     instance: Instance,
     method: &str,
+    original_code: Option<&ContractCode>,
 ) -> Result<RunOutcome, VMRunnerError> {
     let Some(func) = instance.get_func(&mut store, method) else {
         return Ok(RunOutcome::AbortNop(FunctionCallError::MethodResolveError(
@@ -733,9 +764,12 @@ fn call(
         )));
     };
     match func.typed(&mut store) {
-        Ok(run) => match run.call(store, ()) {
+        Ok(run) => match run.call(&mut *store, ()) {
             Ok(()) => Ok(RunOutcome::Ok),
-            Err(err) => err.into_vm_error().map(RunOutcome::Abort),
+            Err(err) => {
+                record_backtrace(store, &err, original_code);
+                err.into_vm_error().map(RunOutcome::Abort)
+            }
         },
         Err(err) => err.into_vm_error().map(RunOutcome::Abort),
     }
@@ -750,16 +784,24 @@ impl crate::PreparedContract for VMResult<PreparedContract> {
     ) -> VMResult {
         let PreparedContract { config, gas_counter, result } = (*self)?;
         let result_state = ExecutionResultState::new(&context, gas_counter, config);
-        let ReadyContract { pre, memory, remaining_gas, start, method, num_tables, concurrency } =
-            match result {
-                PreparationResult::Ready(r) => r,
-                PreparationResult::OutcomeAbortButNopInOldProtocol(e) => {
-                    return Ok(VMOutcome::abort_but_nop_outcome_in_old_protocol(result_state, e));
-                }
-                PreparationResult::OutcomeAbort(e) => {
-                    return Ok(VMOutcome::abort(result_state, e));
-                }
-            };
+        let ReadyContract {
+            pre,
+            memory,
+            remaining_gas,
+            start,
+            method,
+            num_tables,
+            concurrency,
+            original_code,
+        } = match result {
+            PreparationResult::Ready(r) => r,
+            PreparationResult::OutcomeAbortButNopInOldProtocol(e) => {
+                return Ok(VMOutcome::abort_but_nop_outcome_in_old_protocol(result_state, e));
+            }
+            PreparationResult::OutcomeAbort(e) => {
+                return Ok(VMOutcome::abort(result_state, e));
+            }
+        };
 
         // SAFETY:
         // Although the 'static here is a lie, we are pretty confident that the `External` and
@@ -781,6 +823,7 @@ impl crate::PreparedContract for VMResult<PreparedContract> {
         let instance = match pre.instantiate(&mut store) {
             Ok(instance) => instance,
             Err(err) => {
+                record_backtrace(&mut store, &err, original_code.as_deref());
                 let err = err.into_vm_error()?;
                 let Ctx { result_state, .. } = store.into_data();
                 return Ok(VMOutcome::abort(result_state, err));
@@ -822,13 +865,14 @@ impl crate::PreparedContract for VMResult<PreparedContract> {
                 panic!("start function export was present on the module, but not on the instance");
             };
             if let Err(err) = start.call(&mut store, &[], &mut []) {
+                record_backtrace(&mut store, &err, original_code.as_deref());
                 let err = err.into_vm_error()?;
                 let Ctx { result_state, .. } = store.into_data();
                 return Ok(VMOutcome::abort(result_state, err));
             }
         }
 
-        let res = call(&mut store, instance, &method);
+        let res = call(&mut store, instance, &method, original_code.as_deref());
         let Ctx { result_state, .. } = store.into_data();
         match res? {
             RunOutcome::Ok => Ok(VMOutcome::ok(result_state)),
@@ -868,7 +912,17 @@ fn link(linker: &mut wasmtime::Linker<Ctx>, config: &Config) {
                 let _span = TRACE.then(|| {
                     tracing::trace_span!(target: "vm::host_function", stringify!($name)).entered()
                 });
-                match logic::$func(&mut caller, $( $arg_name as $arg_type, )*) {
+                const RECORD: bool = imports::should_record_host_call(stringify!($mod), stringify!($name));
+                let record = RECORD && caller.data().result_state.tracing_enabled();
+                if record {
+                    caller.data_mut().result_state.trace_host_call_begin(stringify!($name), &[$( (stringify!($arg_name), u64::from($arg_name)) ),*]);
+                }
+                let result = logic::$func(&mut caller, $( $arg_name as $arg_type, )*);
+                if record {
+                    let ctx = caller.data_mut();
+                    ctx.result_state.trace_host_call_end(&ctx.registers, result.as_ref().err());
+                }
+                match result {
                     Ok(result) => Ok(result as ($( $returns ),* ) ),
                     Err(err) => {
                         Err(ErrorContainer(parking_lot::Mutex::new(Some(err))).into())
@@ -39,6 +39,8 @@ struct RuntimeErrorInner {
     wasm_trace: Vec<FrameInfo>,
     /// The native backtrace
     native_trace: Backtrace,
+    /// The program counter of the trapping instruction, if the error is a trap in wasm code.
+    trap_pc: Option<usize>,
 }
 
 fn _assert_trap_is_sync_and_send(t: &Trap) -> (&dyn Sync, &dyn Send) {
@@ -99,6 +101,21 @@ impl RuntimeError {
         }
     }
 
+    /// Creates a new user `RuntimeError` that captures the current native backtrace.
+    ///
+    /// Errors raised with [`RuntimeError::raise`] only get their backtrace captured once they
+    /// have unwound out of the wasm code. Host functions can raise the error returned by this
+    /// function instead to retain the wasm frames that called into them.
+    pub fn user_with_backtrace(error: Box<dyn Error + Send + Sync>) -> Self {
+        let info = FRAME_INFO.read();
+        Self::new_with_trace(
+            &info,
+            None,
+            RuntimeErrorSource::User(error),
+            Backtrace::new_unresolved(),
+        )
+    }
+
     /// Raises a custom user Error
     pub fn raise(error: Box<dyn Error + Send + Sync>) -> ! {
         unsafe { raise_user_trap(error) }
@@ -110,31 +127,10 @@ impl RuntimeError {
         source: RuntimeErrorSource,
         native_trace: Backtrace,
     ) -> Self {
-        let wasm_trace = native_trace
-            .frames()
-            .iter()
-            .filter_map(|frame| {
-                let pc = frame.ip() as usize;
-                if pc == 0 {
-                    None
-                } else {
-                    // Note that we need to be careful about the pc we pass in here to
-                    // lookup frame information. This program counter is used to
-                    // translate back to an original source location in the origin wasm
-                    // module. If this pc is the exact pc that the trap happened at,
-                    // then we look up that pc precisely. Otherwise backtrace
-                    // information typically points at the pc *after* the call
-                    // instruction (because otherwise it's likely a call instruction on
-                    // the stack). In that case we want to lookup information for the
-                    // previous instruction (the call instruction) so we subtract one as
-                    // the lookup.
-                    let pc = if Some(pc) == trap_pc { pc } else { pc - 1 };
-                    info.lookup_frame_info(pc)
-                }
-            })
-            .collect();
+        let wasm_trace =
+            frame_pcs(&native_trace, trap_pc).filter_map(|pc| info.lookup_frame_info(pc)).collect();
 
-        Self { inner: Arc::new(RuntimeErrorInner { source, wasm_trace, native_trace }) }
+        Self { inner: Arc::new(RuntimeErrorInner { source, wasm_trace, native_trace, trap_pc }) }
     }
 
     /// Returns a reference the `message` stored in `Trap`.
@@ -148,6 +144,14 @@ impl RuntimeError {
         &self.inner.wasm_trace
     }
 
+    /// Returns the program counters of the native frames that led to this error.
+    ///
+    /// Unlike [`RuntimeError::trace`], this does not rely on the `GlobalFrameInfo`, so the
+    /// caller can symbolicate the frames with the artifact it executed.
+    pub fn native_trace_pcs(&self) -> impl Iterator<Item = usize> + '_ {
+        frame_pcs(&self.inner.native_trace, self.inner.trap_pc)
+    }
+
     /// Attempts to downcast the `RuntimeError` to a concrete type.
     pub fn downcast<T: Error + 'static>(self) -> Result<T, Self> {
         match Arc::try_unwrap(self.inner) {
@@ -180,6 +184,28 @@ impl RuntimeError {
     }
 }
 
+/// Program counters of the frames in `native_trace` to use for the lookup of wasm frames.
+fn frame_pcs(native_trace: &Backtrace, trap_pc: Option<usize>) -> impl Iterator<Item = usize> + '_ {
+    native_trace.frames().iter().filter_map(move |frame| {
+        let pc = frame.ip() as usize;
+        if pc == 0 {
+            None
+        } else {
+            // Note that we need to be careful about the pc we pass in here to
+            // lookup frame information. This program counter is used to
+            // translate back to an original source location in the origin wasm
+            // module. If this pc is the exact pc that the trap happened at,
+            // then we look up that pc precisely. Otherwise backtrace
+            // information typically points at the pc *after* the call
+            // instruction (because otherwise it's likely a call instruction on
+            // the stack). In that case we want to lookup information for the
+            // previous instruction (the call instruction) so we subtract one as
+            // the lookup.
+            Some(if Some(pc) == trap_pc { pc } else { pc - 1 })
+        }
+    })
+}
+
 impl fmt::Debug for RuntimeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("RuntimeError")
@@ -57,6 +57,15 @@ impl UniversalArtifact {
         Some(FunctionExtent { address: func.body, length: usize::try_from(func.length).unwrap() })
     }
 
+    /// Return the index of the local function whose code contains the program counter `pc`.
+    pub fn function_index_at(&self, pc: usize) -> Option<FunctionIndex> {
+        self.functions.iter().find_map(|(index, func)| {
+            let start = *func.body as usize;
+            let end = start.checked_add(usize::try_from(func.length).ok()?)?;
+            (start..end).contains(&pc).then(|| self.import_counts.function_index(index))
+        })
+    }
+
     /// Return the engine instance this artifact is loaded into.
     pub fn engine(&self) -> &super::UniversalEngine {
         &self.engine
@@ -193,6 +193,7 @@ impl<'c> EstimatorContext<'c> {
             bandwidth_requests: BlockBandwidthRequests::empty(),
             trie_access_tracker_state: Default::default(),
             on_post_state_ready: None,
+            execution_trace_collector: None,
         }
     }
 
@@ -68,6 +68,9 @@ pub(crate) fn execute_function_call(
     };
     let random_seed =
         near_primitives::utils::create_random_seed(*action_hash, apply_state.random_seed);
+    let collector = apply_state.execution_trace_collector.as_deref();
+    // View calls aren't part of the consensus, so they can always report backtraces.
+    let capture_backtrace = collector.is_some() || view_config.is_some();
     let context = VMContext {
         current_account_id: runtime_ext.account_id().clone(),
         signer_account_id: action_receipt.signer_id().clone(),
@@ -89,6 +92,8 @@ pub(crate) fn execute_function_call(
         random_seed,
         view_config,
         output_data_receivers,
+        trace_host_calls: collector.is_some_and(|collector| collector.traces_host_calls()),
+        capture_backtrace,
     };
 
     near_vm_runner::reset_metrics();
@@ -196,7 +201,7 @@ pub(crate) fn action_function_call(
         config.wasm_config.storage_get_mode,
         Arc::clone(&apply_state.trie_access_tracker_state),
     );
-    let outcome = execute_function_call(
+    let mut outcome = execute_function_call(
         contract,
         apply_state,
         &mut runtime_ext,
@@ -209,6 +214,15 @@ pub(crate) fn action_function_call(
         is_last_action,
         None,
     )?;
+    if let Some(collector) = &apply_state.execution_trace_collector {
+        collector.record(
+            Some(*receipt.receipt_id()),
+            account_id.clone(),
+            function_call.method_name.clone(),
+            outcome.trace.take(),
+            outcome.backtrace.take(),
+        );
+    }
 
     match &outcome.aborted {
         None => {
@@ -1407,6 +1421,7 @@ mod tests {
             bandwidth_requests: BlockBandwidthRequests::empty(),
             trie_access_tracker_state: Default::default(),
             on_post_state_ready: None,
+            execution_trace_collector: None,
         }
     }
 
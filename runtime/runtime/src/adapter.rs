@@ -1,3 +1,4 @@
+use crate::execution_trace::ExecutionTraceCollector;
 use crate::near_primitives::shard_layout::ShardUId;
 use near_crypto::PublicKey;
 use near_primitives::account::{AccessKey, Account};
@@ -9,6 +10,7 @@ use near_primitives::types::{
 use near_primitives::version::ProtocolVersion;
 use near_primitives::views::{GasKeyInfoView, GasKeyView, ViewStateResult};
 use near_vm_runner::ContractCode;
+use std::sync::Arc;
 
 /// Adapter for querying runtime.
 pub trait ViewRuntimeAdapter {
@@ -41,6 +43,7 @@ pub trait ViewRuntimeAdapter {
         logs: &mut Vec<String>,
         epoch_info_provider: &dyn EpochInfoProvider,
         current_protocol_version: ProtocolVersion,
+        execution_trace_collector: Option<Arc<ExecutionTraceCollector>>,
     ) -> Result<Vec<u8>, crate::state_viewer::errors::CallFunctionError>;
 
     fn view_access_key(
@@ -23,6 +23,7 @@ use near_store::trie::receipts_column_helper::{
 use near_store::{StorageError, TrieAccess, TrieUpdate};
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::num::NonZeroU64;
 
 pub(crate) enum ReceiptSink {
     V2(ReceiptSinkV2WithInfo),
@@ -140,6 +141,42 @@ impl ReceiptSink {
         Ok(ReceiptSink::V2(ReceiptSinkV2WithInfo { sink, info }))
     }
 
+    /// Creates a sink that forwards every receipt without any congestion or
+    /// bandwidth limits. Used for dry-run simulations, where receipts must
+    /// never be parked in the outgoing buffers.
+    pub(crate) fn new_unlimited(
+        trie: &dyn TrieAccess,
+        apply_state: &ApplyState,
+        epoch_info_provider: &dyn EpochInfoProvider,
+    ) -> Result<Self, RuntimeError> {
+        let info = ReceiptSinkV2Info::new(apply_state.epoch_id, epoch_info_provider)?;
+        let outgoing_buffers = ShardsOutgoingReceiptBuffer::load(trie)?;
+        let outgoing_limit: HashMap<ShardId, OutgoingLimit> = info
+            .shard_layout
+            .shard_ids()
+            .map(|shard_id| (shard_id, OutgoingLimit { gas: Gas::MAX, size: u64::MAX }))
+            .collect();
+        let outgoing_metadatas = OutgoingMetadatas::load(
+            trie,
+            outgoing_buffers.shards(),
+            ReceiptGroupsConfig::default_config(),
+        )?;
+        let params = BandwidthSchedulerParams::new(
+            NonZeroU64::new(info.shard_layout.num_shards()).expect("ShardLayout has zero shards!"),
+            &apply_state.config,
+        );
+        let sink = ReceiptSinkV2 {
+            own_congestion_info: CongestionInfo::default(),
+            outgoing_receipts: Vec::new(),
+            outgoing_limit,
+            outgoing_buffers,
+            outgoing_metadatas,
+            bandwidth_scheduler_output: BandwidthSchedulerOutput::no_granted_bandwidth(params),
+            stats: ReceiptSinkStats::default(),
+        };
+        Ok(ReceiptSink::V2(ReceiptSinkV2WithInfo { sink, info }))
+    }
+
     /// Forward receipts already in the buffer to the outgoing receipts vector, as
     /// much as the gas limits allow.
     pub(crate) fn forward_from_buffer(
@@ -0,0 +1,118 @@
+//! Collection of host function traces recorded while applying receipts or
+//! executing view calls.
+//!
+//! Tracing is enabled by setting [`ApplyState::execution_trace_collector`].
+//! Every function call executed with such apply state records its host calls
+//! into the collector, along with the wasm backtrace if the call failed. A
+//! collector created with [`ExecutionTraceCollector::backtraces_only`] records
+//! only the backtraces of the failed calls.
+//!
+//! [`ApplyState::execution_trace_collector`]: crate::ApplyState::execution_trace_collector
+
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::AccountId;
+use near_primitives::views::{
+    FunctionCallTraceView, HostCallArgView, HostCallEventView, HostCallTraceView, RegisterView,
+    WasmFrameView,
+};
+use near_vm_runner::logic::{ExecutionTrace, HostCallTrace, TraceEvent, WasmFrame};
+use parking_lot::Mutex;
+
+#[derive(Debug, Default)]
+pub struct ExecutionTraceCollector {
+    backtraces_only: bool,
+    traces: Mutex<Vec<FunctionCallTraceView>>,
+}
+
+impl ExecutionTraceCollector {
+    /// Creates a collector that doesn't trace host calls, only the backtraces
+    /// of the function calls that failed.
+    pub fn backtraces_only() -> Self {
+        Self { backtraces_only: true, traces: Default::default() }
+    }
+
+    pub(crate) fn traces_host_calls(&self) -> bool {
+        !self.backtraces_only
+    }
+
+    pub(crate) fn record(
+        &self,
+        receipt_id: Option<CryptoHash>,
+        account_id: AccountId,
+        method_name: String,
+        trace: Option<ExecutionTrace>,
+        backtrace: Option<Vec<WasmFrame>>,
+    ) {
+        if trace.is_none() && backtrace.is_none() {
+            return;
+        }
+        let host_calls = trace
+            .map(|trace| trace.host_calls.into_iter().map(host_call_view).collect())
+            .unwrap_or_default();
+        self.traces.lock().push(FunctionCallTraceView {
+            receipt_id,
+            account_id,
+            method_name,
+            host_calls,
+            backtrace: backtrace.map(backtrace_view),
+        });
+    }
+
+    /// Returns the traces recorded so far, in execution order.
+    pub fn take(&self) -> Vec<FunctionCallTraceView> {
+        std::mem::take(&mut *self.traces.lock())
+    }
+}
+
+pub(crate) fn backtrace_view(frames: Vec<WasmFrame>) -> Vec<WasmFrameView> {
+    frames
+        .into_iter()
+        .map(|frame| WasmFrameView {
+            function_index: frame.func_index,
+            function_name: frame.func_name,
+        })
+        .collect()
+}
+
+fn host_call_view(call: HostCallTrace) -> HostCallTraceView {
+    HostCallTraceView {
+        name: call.name.to_string(),
+        args: call
+            .args
+            .into_iter()
+            .map(|(name, value)| HostCallArgView { name: name.to_string(), value })
+            .collect(),
+        burnt_gas: call.burnt_gas,
+        gas_left: call.gas_left,
+        events: call.events.into_iter().map(event_view).collect(),
+        registers: call
+            .registers
+            .into_iter()
+            .map(|(register_id, data)| RegisterView { register_id, data: data.into() })
+            .collect(),
+        error: call.error,
+    }
+}
+
+fn event_view(event: TraceEvent) -> HostCallEventView {
+    match event {
+        TraceEvent::StorageRead { key, value } => {
+            HostCallEventView::StorageRead { key: key.into(), value: value.map(Into::into) }
+        }
+        TraceEvent::StorageWrite { key, value, evicted } => HostCallEventView::StorageWrite {
+            key: key.into(),
+            value: value.into(),
+            evicted: evicted.map(Into::into),
+        },
+        TraceEvent::StorageRemove { key, removed } => {
+            HostCallEventView::StorageRemove { key: key.into(), removed: removed.map(Into::into) }
+        }
+        TraceEvent::StorageHasKey { key, exists } => {
+            HostCallEventView::StorageHasKey { key: key.into(), exists }
+        }
+        TraceEvent::Log { message } => HostCallEventView::Log { message },
+        TraceEvent::PromiseCreated { promise_index, receiver_id } => {
+            HostCallEventView::PromiseCreated { promise_index, receiver_id }
+        }
+    }
+}
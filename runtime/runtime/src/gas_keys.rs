@@ -181,6 +181,7 @@ mod tests {
             bandwidth_requests: BlockBandwidthRequests::empty(),
             trie_access_tracker_state: Default::default(),
             on_post_state_ready: None,
+            execution_trace_collector: None,
         }
     }
 
@@ -6,6 +6,7 @@ use crate::config::{
     total_prepaid_exec_fees, total_prepaid_gas,
 };
 use crate::congestion_control::DelayedReceiptQueueWrapper;
+use crate::execution_trace::ExecutionTraceCollector;
 use crate::gas_keys::{action_add_gas_key, action_delete_gas_key, action_transfer_to_gas_key};
 use crate::metrics::{
     TRANSACTION_BATCH_SIGNATURE_VERIFY_FAILURE_TOTAL,
@@ -104,6 +105,7 @@ pub mod config;
 mod congestion_control;
 mod conversions;
 mod deterministic_account_id;
+pub mod execution_trace;
 pub mod ext;
 mod gas_keys;
 mod global_contracts;
@@ -111,6 +113,7 @@ pub mod metrics;
 mod pipelining;
 mod prefetch;
 pub mod receipt_manager;
+pub mod simulation;
 pub mod state_viewer;
 #[cfg(test)]
 mod tests;
@@ -200,6 +203,9 @@ pub struct ApplyState {
     pub bandwidth_requests: BlockBandwidthRequests,
     /// Callback to be called when the post-state is ready.
     pub on_post_state_ready: Option<PostStateReadyCallback>,
+    /// If set, host calls made by every executed function call are recorded
+    /// into the collector.
+    pub execution_trace_collector: Option<Arc<ExecutionTraceCollector>>,
 }
 
 impl ApplyState {
@@ -1507,6 +1513,44 @@ impl Runtime {
         }
     }
 
+    /// Creates the receipt a verified transaction is converted into, along with the
+    /// transaction's execution outcome.
+    fn convert_transaction_to_receipt(
+        apply_state: &ApplyState,
+        tx: &SignedTransaction,
+        verification_result: &VerificationResult,
+    ) -> (Receipt, ExecutionOutcomeWithId) {
+        let signer_id = tx.transaction.signer_id();
+        let receipt_id = create_receipt_id_from_transaction(tx.hash(), apply_state.block_height);
+        let receipt = Receipt::from_tx(
+            receipt_id,
+            signer_id.clone(),
+            tx.transaction.receiver_id().clone(),
+            tx.transaction.public_key().clone(),
+            verification_result.receipt_gas_price,
+            tx.transaction.actions().to_vec(),
+        );
+        let gas_burnt = verification_result.gas_burnt;
+        let compute_usage = gas_burnt.as_gas();
+        let outcome = ExecutionOutcomeWithId {
+            id: tx.get_hash(),
+            outcome: ExecutionOutcome {
+                status: ExecutionStatus::SuccessReceiptId(*receipt.receipt_id()),
+                logs: vec![],
+                receipt_ids: vec![*receipt.receipt_id()],
+                gas_burnt,
+                // TODO(#8806): Support compute costs for actions. For now they match burnt gas.
+                compute_usage: Some(compute_usage),
+                tokens_burnt: verification_result.burnt_amount,
+                executor_id: signer_id.clone(),
+                // TODO: profile data is only counted in apply_action, which only happened at process_receipt
+                // VerificationResult needs updates to incorporate profile data to support profile data of txns
+                metadata: ExecutionMetadata::V1,
+            },
+        };
+        (receipt, outcome)
+    }
+
     /// Processes a collection of transactions.
     ///
     /// Fills the `processing_state` with local receipts generated during processing of the
@@ -1786,39 +1830,11 @@ impl Runtime {
                 }
             };
 
-            let (receipt, outcome) = {
-                let receipt_id = create_receipt_id_from_transaction(
-                    tx_hash,
-                    processing_state.apply_state.block_height,
-                );
-                let receipt = Receipt::from_tx(
-                    receipt_id,
-                    signer_id.clone(),
-                    tx.transaction.receiver_id().clone(),
-                    pubkey.clone(),
-                    verification_result.receipt_gas_price,
-                    tx.transaction.actions().to_vec(),
-                );
-                let gas_burnt = verification_result.gas_burnt;
-                let compute_usage = gas_burnt.as_gas();
-                let outcome = ExecutionOutcomeWithId {
-                    id: tx.get_hash(),
-                    outcome: ExecutionOutcome {
-                        status: ExecutionStatus::SuccessReceiptId(*receipt.receipt_id()),
-                        logs: vec![],
-                        receipt_ids: vec![*receipt.receipt_id()],
-                        gas_burnt,
-                        // TODO(#8806): Support compute costs for actions. For now they match burnt gas.
-                        compute_usage: Some(compute_usage),
-                        tokens_burnt: verification_result.burnt_amount,
-                        executor_id: signer_id.clone(),
-                        // TODO: profile data is only counted in apply_action, which only happened at process_receipt
-                        // VerificationResult needs updates to incorporate profile data to support profile data of txns
-                        metadata: ExecutionMetadata::V1,
-                    },
-                };
-                (receipt, outcome)
-            };
+            let (receipt, outcome) = Self::convert_transaction_to_receipt(
+                processing_state.apply_state,
+                tx,
+                &verification_result,
+            );
 
             match safe_add_balance(
                 processing_state.stats.balance.tx_burnt_amount,
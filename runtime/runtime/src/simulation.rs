@@ -0,0 +1,318 @@
+//! Dry-run execution of a single transaction.
+//!
+//! The transaction is converted into a receipt on the signer's shard and the
+//! resulting receipts are then followed shard by shard, round by round, until
+//! no more receipts are produced. All changes are kept in the provided
+//! [`TrieUpdate`]s and are never committed to the store.
+
+use crate::config::tx_cost;
+use crate::congestion_control::{DelayedReceiptQueueWrapper, ReceiptSink};
+use crate::verifier::validate_transaction_well_formed;
+use crate::{
+    ApplyProcessingState, ApplyState, Runtime, TotalResourceGuard, validate_transaction,
+    verify_and_charge_tx_ephemeral,
+};
+use near_primitives::chunk_apply_stats::ChunkApplyStatsV0;
+use near_primitives::errors::{
+    IntegerOverflowError, InvalidAccessKeyError, InvalidTxError, RuntimeError,
+};
+use near_primitives::receipt::Receipt;
+use near_primitives::transaction::{ExecutionOutcomeWithId, SignedTransaction};
+use near_primitives::types::{
+    EpochId, EpochInfoProvider, Gas, RawStateChangesWithTrieKey, ShardId, StateChangeCause,
+};
+use near_store::trie::receipts_column_helper::DelayedReceiptQueue;
+use near_store::{TrieUpdate, get_access_key, get_account, set_access_key, set_account};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Forked state of one shard used by [`Runtime::simulate_transaction`].
+pub struct SimulationShard {
+    /// Apply state of the shard. Its `shard_id` must match the key under which
+    /// the shard is passed to the simulation.
+    pub apply_state: ApplyState,
+    /// Throwaway state update on top of the shard's state root.
+    pub state_update: TrieUpdate,
+}
+
+/// Bounds on the work done by [`Runtime::simulate_transaction`].
+#[derive(Clone, Copy, Debug)]
+pub struct SimulationLimits {
+    /// Maximum number of receipt rounds followed after the transaction.
+    pub max_rounds: usize,
+    /// Once the transaction and the receipts executed so far have burnt this
+    /// much gas, no more receipts are executed.
+    pub max_gas_burnt: Gas,
+}
+
+#[derive(Debug)]
+pub struct SimulatedOutcome {
+    /// Shard on which the transaction or receipt was executed.
+    pub shard_id: ShardId,
+    /// Index of the round in which the outcome was produced. The transaction
+    /// itself, and its receipt if it is local, are executed in round 0.
+    pub round: usize,
+    pub outcome: ExecutionOutcomeWithId,
+}
+
+#[derive(Debug)]
+pub struct SimulatedStateChanges {
+    pub shard_id: ShardId,
+    pub changes: RawStateChangesWithTrieKey,
+}
+
+#[derive(Debug, Default)]
+pub struct SimulationResult {
+    /// Outcomes in execution order, starting with the transaction outcome.
+    pub outcomes: Vec<SimulatedOutcome>,
+    /// State changes made on every shard touched by the simulation.
+    pub state_changes: Vec<SimulatedStateChanges>,
+    /// Receipts that were produced but not executed, either because the
+    /// receiving shard is not available or because one of the limits was hit.
+    pub unprocessed_receipts: Vec<Receipt>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SimulationError {
+    #[error("state of the signer shard {0} is not available")]
+    SignerShardUnavailable(ShardId),
+    #[error(transparent)]
+    Runtime(#[from] RuntimeError),
+}
+
+impl Runtime {
+    /// Executes `signed_tx` and all receipts it produces on top of `shards`
+    /// without persisting anything.
+    ///
+    /// Receipts produced in one round are executed in the next one, which
+    /// mimics delivery in the following chunk. Like in a real chunk, a local
+    /// receipt (sent by the signer to itself) is executed in the same round
+    /// as the transaction. Congestion control and
+    /// bandwidth limits are ignored, so all receipts are always forwarded.
+    /// If `verify_signature` is false, the signature of `signed_tx` is not
+    /// checked, which allows simulating unsigned transactions.
+    pub fn simulate_transaction(
+        &self,
+        epoch_id: &EpochId,
+        mut shards: BTreeMap<ShardId, SimulationShard>,
+        signed_tx: &SignedTransaction,
+        verify_signature: bool,
+        limits: SimulationLimits,
+        epoch_info_provider: &dyn EpochInfoProvider,
+    ) -> Result<SimulationResult, SimulationError> {
+        let mut result = SimulationResult::default();
+        let signer_id = signed_tx.transaction.signer_id();
+        let shard_layout = epoch_info_provider.shard_layout(epoch_id)?;
+        let signer_shard_id = shard_layout.account_id_to_shard_id(signer_id);
+        let signer_shard = shards
+            .get_mut(&signer_shard_id)
+            .ok_or(SimulationError::SignerShardUnavailable(signer_shard_id))?;
+
+        let (receipt, mut gas_burnt) = match self.simulate_transaction_conversion(
+            signer_shard,
+            signed_tx,
+            verify_signature,
+        )? {
+            Ok((receipt, outcome)) => {
+                let gas_burnt = outcome.outcome.gas_burnt;
+                result.outcomes.push(SimulatedOutcome {
+                    shard_id: signer_shard_id,
+                    round: 0,
+                    outcome,
+                });
+                (receipt, gas_burnt)
+            }
+            Err(err) => {
+                let outcome = ExecutionOutcomeWithId::failed(signed_tx, err);
+                result.outcomes.push(SimulatedOutcome {
+                    shard_id: signer_shard_id,
+                    round: 0,
+                    outcome,
+                });
+                return Ok(result);
+            }
+        };
+
+        // A local receipt is executed in the same chunk as its transaction,
+        // anything else is delivered to the next one.
+        let first_round = if receipt.receiver_id() == signer_id { 0 } else { 1 };
+        let mut touched_shards = BTreeSet::from([signer_shard_id]);
+        let mut pending: BTreeMap<ShardId, Vec<Receipt>> = BTreeMap::new();
+        pending.entry(receipt.receiver_shard_id(&shard_layout)?).or_default().push(receipt);
+
+        for round in first_round..=limits.max_rounds {
+            if pending.is_empty() {
+                break;
+            }
+            let mut next_pending: BTreeMap<ShardId, Vec<Receipt>> = BTreeMap::new();
+            for (shard_id, receipts) in std::mem::take(&mut pending) {
+                let Some(shard) = shards.remove(&shard_id) else {
+                    result.unprocessed_receipts.extend(receipts);
+                    continue;
+                };
+                touched_shards.insert(shard_id);
+                let (shard, outcomes, outgoing_receipts, skipped_receipts) = self
+                    .simulate_receipts(
+                        shard,
+                        receipts,
+                        &mut gas_burnt,
+                        limits.max_gas_burnt,
+                        epoch_info_provider,
+                    )?;
+                shards.insert(shard_id, shard);
+                result.unprocessed_receipts.extend(skipped_receipts);
+                result.outcomes.extend(outcomes.into_iter().map(|outcome| SimulatedOutcome {
+                    shard_id,
+                    round,
+                    outcome,
+                }));
+                for receipt in outgoing_receipts {
+                    let receiver_shard_id = receipt.receiver_shard_id(&shard_layout)?;
+                    next_pending.entry(receiver_shard_id).or_default().push(receipt);
+                }
+            }
+            pending = next_pending;
+        }
+        result.unprocessed_receipts.extend(pending.into_values().flatten());
+
+        for shard_id in touched_shards {
+            let Some(shard) = shards.remove(&shard_id) else {
+                continue;
+            };
+            let update_result = shard.state_update.finalize().map_err(RuntimeError::from)?;
+            result.state_changes.extend(
+                update_result
+                    .state_changes
+                    .into_iter()
+                    .map(|changes| SimulatedStateChanges { shard_id, changes }),
+            );
+        }
+        Ok(result)
+    }
+
+    /// Verifies the transaction, charges the signer and converts the
+    /// transaction into a receipt. The outer error is returned on storage
+    /// failures, the inner one if the transaction is invalid.
+    fn simulate_transaction_conversion(
+        &self,
+        shard: &mut SimulationShard,
+        signed_tx: &SignedTransaction,
+        verify_signature: bool,
+    ) -> Result<Result<(Receipt, ExecutionOutcomeWithId), InvalidTxError>, RuntimeError> {
+        let SimulationShard { apply_state, state_update } = shard;
+        let config = &apply_state.config;
+        let protocol_version = apply_state.current_protocol_version;
+        let validation = if verify_signature {
+            validate_transaction(config, signed_tx.clone(), protocol_version)
+                .map(|_| ())
+                .map_err(|(err, _)| err)
+        } else {
+            validate_transaction_well_formed(config, signed_tx, protocol_version)
+        };
+        if let Err(err) = validation {
+            return Ok(Err(err));
+        }
+
+        let tx = &signed_tx.transaction;
+        let signer_id = tx.signer_id();
+        let public_key = tx.public_key();
+        let cost = match tx_cost(config, tx, apply_state.gas_price) {
+            Ok(cost) => cost,
+            Err(IntegerOverflowError) => return Ok(Err(InvalidTxError::CostOverflow)),
+        };
+        let Some(mut signer) = get_account(state_update, signer_id)? else {
+            return Ok(Err(InvalidTxError::InvalidSignerId { signer_id: signer_id.to_string() }));
+        };
+        let Some(mut access_key) = get_access_key(state_update, signer_id, public_key)? else {
+            return Ok(Err(InvalidTxError::InvalidAccessKeyError(
+                InvalidAccessKeyError::AccessKeyNotFound {
+                    account_id: signer_id.clone(),
+                    public_key: Box::new(public_key.clone()),
+                },
+            )));
+        };
+        let verification_result = match verify_and_charge_tx_ephemeral(
+            config,
+            &mut signer,
+            &mut access_key,
+            tx,
+            &cost,
+            Some(apply_state.block_height),
+        ) {
+            Ok(verification_result) => verification_result,
+            Err(err) => return Ok(Err(err)),
+        };
+        set_account(state_update, signer_id.clone(), &signer);
+        set_access_key(state_update, signer_id.clone(), public_key.clone(), &access_key);
+        state_update
+            .commit(StateChangeCause::TransactionProcessing { tx_hash: signed_tx.get_hash() });
+        Ok(Ok(Self::convert_transaction_to_receipt(apply_state, signed_tx, &verification_result)))
+    }
+
+    /// Executes `receipts` on the shard and returns the shard back together
+    /// with the produced outcomes and outgoing receipts. Receipts that come
+    /// after `gas_burnt` reached `max_gas_burnt` are not executed and are
+    /// returned last.
+    fn simulate_receipts(
+        &self,
+        shard: SimulationShard,
+        receipts: Vec<Receipt>,
+        gas_burnt: &mut Gas,
+        max_gas_burnt: Gas,
+        epoch_info_provider: &dyn EpochInfoProvider,
+    ) -> Result<
+        (SimulationShard, Vec<ExecutionOutcomeWithId>, Vec<Receipt>, Vec<Receipt>),
+        RuntimeError,
+    > {
+        let SimulationShard { apply_state, state_update } = shard;
+        let delayed_receipts = DelayedReceiptQueueWrapper::new(
+            DelayedReceiptQueue::load(&state_update)?,
+            epoch_info_provider,
+            apply_state.shard_id,
+            apply_state.epoch_id,
+        );
+        let mut receipt_sink =
+            ReceiptSink::new_unlimited(&state_update.trie, &apply_state, epoch_info_provider)?;
+        // The processing state borrows `apply_state`, so keep it in its own
+        // scope to be able to hand `apply_state` back to the caller.
+        let (state_update, outcomes, outgoing_receipts, skipped_receipts) = {
+            let processing_state = ApplyProcessingState {
+                protocol_version: apply_state.current_protocol_version,
+                apply_state: &apply_state,
+                prefetcher: None,
+                state_update,
+                epoch_info_provider,
+                total: TotalResourceGuard { span: tracing::Span::current(), gas: 0, compute: 0 },
+                stats: ChunkApplyStatsV0::new(apply_state.block_height, apply_state.shard_id),
+            };
+            let mut processing_state =
+                processing_state.into_processing_receipt_state(&[], delayed_receipts);
+            let mut validator_proposals = vec![];
+            let mut outcomes = vec![];
+            let mut skipped_receipts = vec![];
+            for receipt in receipts {
+                if *gas_burnt >= max_gas_burnt {
+                    skipped_receipts.push(receipt);
+                    continue;
+                }
+                if let Some(outcome) = self.process_receipt(
+                    &mut processing_state,
+                    &receipt,
+                    &mut receipt_sink,
+                    &mut validator_proposals,
+                )? {
+                    *gas_burnt = gas_burnt.saturating_add(outcome.outcome.gas_burnt);
+                    outcomes.push(outcome);
+                }
+            }
+            let outgoing_receipts = receipt_sink
+                .finalize_stats_get_outgoing_receipts(&mut processing_state.stats.receipt_sink);
+            (processing_state.state_update, outcomes, outgoing_receipts, skipped_receipts)
+        };
+        Ok((
+            SimulationShard { apply_state, state_update },
+            outcomes,
+            outgoing_receipts,
+            skipped_receipts,
+        ))
+    }
+}
@@ -63,7 +63,11 @@ pub enum CallFunctionError {
     #[error("Internal error: #{error_message}")]
     InternalError { error_message: String },
     #[error("VM error occurred: #{error_message}")]
-    VMError { error_message: String },
+    VMError {
+        error_message: String,
+        /// Wasm frames that led to the error, innermost first.
+        backtrace: Option<Vec<near_primitives::views::WasmFrameView>>,
+    },
 }
 
 impl From<ViewAccountError> for ViewContractCodeError {
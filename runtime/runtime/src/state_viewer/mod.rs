@@ -1,5 +1,6 @@
 use crate::ApplyState;
 use crate::actions::execute_function_call;
+use crate::execution_trace::{self, ExecutionTraceCollector};
 use crate::ext::RuntimeExt;
 use crate::global_contracts::{AccountContractAccessExt, GlobalContractAccessExt};
 use crate::pipelining::ReceiptPreparationPipeline;
@@ -49,6 +50,8 @@ pub struct ViewApplyState {
     pub current_protocol_version: ProtocolVersion,
     /// Cache for compiled contracts.
     pub cache: Option<Box<dyn ContractRuntimeCache>>,
+    /// If set, host calls made by the view call are recorded into the collector.
+    pub execution_trace_collector: Option<Arc<ExecutionTraceCollector>>,
 }
 
 pub struct TrieViewer {
@@ -322,7 +325,8 @@ impl TrieViewer {
         let empty_hash = CryptoHash::default();
         let mut receipt_manager = ReceiptManager::default();
         let config_store = RuntimeConfigStore::new(None);
-        let config = config_store.get_config(PROTOCOL_VERSION);
+        // View calls aren't part of the consensus, so they can capture wasm backtraces.
+        let config = &Arc::new(config_store.get_config(PROTOCOL_VERSION).with_wasm_backtraces());
         let apply_state = ApplyState {
             apply_reason: ApplyChunkReason::ViewTrackedShard,
             block_height: view_state.block_height,
@@ -343,6 +347,7 @@ impl TrieViewer {
             bandwidth_requests: BlockBandwidthRequests::empty(),
             trie_access_tracker_state: Default::default(),
             on_post_state_ready: None,
+            execution_trace_collector: view_state.execution_trace_collector,
         };
         let function_call = FunctionCallAction {
             method_name: method_name.to_string(),
@@ -387,7 +392,7 @@ impl TrieViewer {
             config.wasm_config.storage_get_mode,
             Arc::clone(&apply_state.trie_access_tracker_state),
         );
-        let outcome = execute_function_call(
+        let mut outcome = execute_function_call(
             contract,
             &apply_state,
             &mut runtime_ext,
@@ -401,6 +406,16 @@ impl TrieViewer {
             view_config,
         )
         .map_err(|e| errors::CallFunctionError::InternalError { error_message: e.to_string() })?;
+        let backtrace = outcome.backtrace.take();
+        if let Some(collector) = &apply_state.execution_trace_collector {
+            collector.record(
+                None,
+                contract_id.clone(),
+                method_name.to_string(),
+                outcome.trace.take(),
+                backtrace.clone(),
+            );
+        }
         let elapsed = now.elapsed();
         let time_ms =
             (elapsed.as_secs() as f64 / 1_000.0) + f64::from(elapsed.subsec_nanos()) / 1_000_000.0;
@@ -410,7 +425,10 @@ impl TrieViewer {
             logs.extend(outcome.logs);
             let message = format!("wasm execution failed with error: {:?}", err);
             tracing::debug!(target: "runtime", %time_str, %message, "exec time and error message");
-            Err(errors::CallFunctionError::VMError { error_message: message })
+            Err(errors::CallFunctionError::VMError {
+                error_message: message,
+                backtrace: backtrace.map(execution_trace::backtrace_view),
+            })
         } else {
             tracing::debug!(target: "runtime", %time_str, ?outcome, "exec time and result of execution");
             logs.extend(outcome.logs);
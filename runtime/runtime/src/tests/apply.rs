@@ -55,7 +55,7 @@ use testlib::runtime_utils::{alice_account, bob_account};
 
 const DEFAULT_MINIMAL_GAS_ATTACHMENT: Gas = Gas::from_gas(1);
 
-fn setup_runtime(
+pub(super) fn setup_runtime(
     initial_accounts: Vec<AccountId>,
     initial_balance: Balance,
     initial_locked: Balance,
@@ -176,6 +176,7 @@ fn setup_runtime_for_shard(
         bandwidth_requests: BlockBandwidthRequests::empty(),
         trie_access_tracker_state: Default::default(),
         on_post_state_ready: None,
+        execution_trace_collector: None,
     };
 
     (runtime, tries, root, apply_state, signers)
@@ -2889,7 +2890,7 @@ fn test_congestion_buffering() {
 
 // Apply trie changes in `ApplyResult` and update `ApplyState` with new
 // congestion info for the next call to apply().
-fn commit_apply_result(
+pub(super) fn commit_apply_result(
     apply_result: &ApplyResult,
     apply_state: &mut ApplyState,
     tries: &ShardTries,
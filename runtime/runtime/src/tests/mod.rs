@@ -14,6 +14,7 @@ use testlib::runtime_utils::bob_account;
 use crate::ApplyState;
 
 mod apply;
+mod simulation;
 
 const GAS_PRICE: Balance = Balance::from_yoctonear(5000);
 const MAX_ATTACHED_GAS: Gas = Gas::from_teragas(300);
@@ -0,0 +1,229 @@
+use super::apply::{commit_apply_result, setup_runtime};
+use crate::SignedValidPeriodTransactions;
+use crate::simulation::{SimulationLimits, SimulationResult, SimulationShard};
+use crate::tests::create_receipt_with_actions;
+use crate::{ApplyState, Runtime};
+use near_crypto::Signer;
+use near_primitives::action::{Action, DeployContractAction, FunctionCallAction};
+use near_primitives::hash::CryptoHash;
+use near_primitives::receipt::ReceiptEnum;
+use near_primitives::shard_layout::ShardUId;
+use near_primitives::transaction::{ExecutionStatus, SignedTransaction};
+use near_primitives::trie_key::TrieKey;
+use near_primitives::types::{AccountId, Balance, EpochInfoProvider, Gas, MerkleHash};
+use near_store::{ShardTries, get_account};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use testlib::runtime_utils::{alice_account, bob_account};
+
+const LIMITS: SimulationLimits =
+    SimulationLimits { max_rounds: 64, max_gas_burnt: Gas::from_teragas(1000) };
+
+struct TestEnv {
+    runtime: Runtime,
+    tries: ShardTries,
+    root: MerkleHash,
+    apply_state: ApplyState,
+    signers: Vec<Arc<Signer>>,
+    epoch_info_provider: Box<dyn EpochInfoProvider>,
+}
+
+impl TestEnv {
+    fn new() -> Self {
+        let (runtime, tries, root, apply_state, signers, epoch_info_provider) = setup_runtime(
+            vec![alice_account(), bob_account()],
+            Balance::from_near(1_000_000),
+            Balance::ZERO,
+            Gas::from_teragas(1000),
+        );
+        Self {
+            runtime,
+            tries,
+            root,
+            apply_state,
+            signers,
+            epoch_info_provider: Box::new(epoch_info_provider),
+        }
+    }
+
+    fn deploy_contract(&mut self, account_id: AccountId, signer: Arc<Signer>) {
+        let receipt = create_receipt_with_actions(
+            account_id,
+            signer,
+            vec![Action::DeployContract(DeployContractAction {
+                code: near_test_contracts::rs_contract().to_vec(),
+            })],
+        );
+        let shard_uid = ShardUId::single_shard();
+        let apply_result = self
+            .runtime
+            .apply(
+                self.tries.get_trie_for_shard(shard_uid, self.root),
+                &None,
+                &self.apply_state,
+                &[receipt],
+                SignedValidPeriodTransactions::empty(),
+                self.epoch_info_provider.as_ref(),
+                Default::default(),
+            )
+            .unwrap();
+        self.root =
+            commit_apply_result(&apply_result, &mut self.apply_state, &self.tries, shard_uid);
+    }
+
+    fn simulate(self, tx: &SignedTransaction, limits: SimulationLimits) -> SimulationResult {
+        let shard_id = self.apply_state.shard_id;
+        let epoch_id = self.apply_state.epoch_id;
+        let balances = balances(&self.tries, self.root);
+        let state_update = self.tries.new_trie_update(ShardUId::single_shard(), self.root);
+        let shards = BTreeMap::from([(
+            shard_id,
+            SimulationShard { apply_state: self.apply_state, state_update },
+        )]);
+        let result = self
+            .runtime
+            .simulate_transaction(
+                &epoch_id,
+                shards,
+                tx,
+                true,
+                limits,
+                self.epoch_info_provider.as_ref(),
+            )
+            .unwrap();
+
+        // Nothing the simulation did may end up in the store.
+        assert_eq!(balances(&self.tries, self.root), balances);
+        result
+    }
+}
+
+fn balances(tries: &ShardTries, root: MerkleHash) -> Vec<Balance> {
+    let trie = tries.new_trie_update(ShardUId::single_shard(), root);
+    [alice_account(), bob_account()]
+        .iter()
+        .map(|account_id| get_account(&trie, account_id).unwrap().unwrap().amount())
+        .collect()
+}
+
+fn executors(result: &SimulationResult) -> Vec<(usize, AccountId)> {
+    result
+        .outcomes
+        .iter()
+        .map(|simulated| (simulated.round, simulated.outcome.outcome.executor_id.clone()))
+        .collect()
+}
+
+fn assert_all_succeeded(result: &SimulationResult) {
+    for simulated in &result.outcomes {
+        assert!(
+            matches!(
+                simulated.outcome.outcome.status,
+                ExecutionStatus::SuccessValue(_) | ExecutionStatus::SuccessReceiptId(_)
+            ),
+            "{:?}",
+            simulated
+        );
+    }
+}
+
+#[test]
+fn test_simulate_transfer() {
+    let env = TestEnv::new();
+    let tx = SignedTransaction::send_money(
+        1,
+        alice_account(),
+        bob_account(),
+        &*env.signers[0],
+        Balance::from_yoctonear(100),
+        CryptoHash::default(),
+    );
+    let result = env.simulate(&tx, LIMITS);
+
+    assert_all_succeeded(&result);
+    assert_eq!(&executors(&result)[..2], &[(0, alice_account()), (1, bob_account())]);
+    assert!(result.unprocessed_receipts.is_empty());
+    assert!(result.state_changes.iter().any(|simulated| matches!(
+        &simulated.changes.trie_key,
+        TrieKey::Account { account_id } if *account_id == bob_account()
+    )));
+}
+
+#[test]
+fn test_simulate_local_receipt() {
+    let env = TestEnv::new();
+    let tx = SignedTransaction::send_money(
+        1,
+        alice_account(),
+        alice_account(),
+        &*env.signers[0],
+        Balance::from_yoctonear(100),
+        CryptoHash::default(),
+    );
+    let result = env.simulate(&tx, LIMITS);
+
+    assert_all_succeeded(&result);
+    // Like in a real chunk, the local receipt is executed together with its transaction.
+    assert_eq!(executors(&result), vec![(0, alice_account()), (0, alice_account())]);
+}
+
+#[test]
+fn test_simulate_cross_contract_call() {
+    let mut env = TestEnv::new();
+    env.deploy_contract(bob_account(), env.signers[1].clone());
+    let promise = serde_json::json!([{"create": {
+        "account_id": bob_account(),
+        "method_name": "ext_sha256",
+        "arguments": [],
+        "amount": "0",
+        "gas": Gas::from_teragas(20).as_gas(),
+    }, "id": 0 }]);
+    let tx = SignedTransaction::from_actions(
+        1,
+        alice_account(),
+        bob_account(),
+        &*env.signers[0],
+        vec![Action::FunctionCall(Box::new(FunctionCallAction {
+            method_name: "call_promise".to_string(),
+            args: serde_json::to_vec(&promise).unwrap(),
+            gas: Gas::from_teragas(100),
+            deposit: Balance::ZERO,
+        }))],
+        CryptoHash::default(),
+        0,
+    );
+    let result = env.simulate(&tx, LIMITS);
+
+    assert_all_succeeded(&result);
+    let executors = executors(&result);
+    assert_eq!(&executors[..3], &[(0, alice_account()), (1, bob_account()), (2, bob_account())]);
+    // Unused gas of both function calls is refunded to the signer in later rounds.
+    assert!(
+        executors[3..]
+            .iter()
+            .all(|(round, account_id)| *round > 1 && *account_id == alice_account())
+    );
+    assert!(executors.len() > 3, "expected gas refunds: {:?}", executors);
+    assert!(result.unprocessed_receipts.is_empty());
+}
+
+#[test]
+fn test_simulate_gas_limit() {
+    let env = TestEnv::new();
+    let tx = SignedTransaction::send_money(
+        1,
+        alice_account(),
+        bob_account(),
+        &*env.signers[0],
+        Balance::from_yoctonear(100),
+        CryptoHash::default(),
+    );
+    let limits = SimulationLimits { max_gas_burnt: Gas::from_gas(1), ..LIMITS };
+    let result = env.simulate(&tx, limits);
+
+    // The transaction is always converted, but its receipt is left unprocessed.
+    assert_eq!(executors(&result), vec![(0, alice_account())]);
+    assert_eq!(result.unprocessed_receipts.len(), 1);
+    assert_eq!(*result.unprocessed_receipts[0].receiver_id(), bob_account());
+    assert!(matches!(result.unprocessed_receipts[0].receipt(), ReceiptEnum::Action(_)));
+}
@@ -117,6 +117,7 @@ impl StandaloneRuntime {
             bandwidth_requests: BlockBandwidthRequests::empty(),
             trie_access_tracker_state: Default::default(),
             on_post_state_ready: None,
+            execution_trace_collector: None,
         };
 
         Self {
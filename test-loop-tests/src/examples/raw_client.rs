@@ -3,6 +3,7 @@ use near_async::test_loop::TestLoopV2;
 use near_async::time::Duration;
 use near_chain::ChainGenesis;
 use near_chain::chain::ApplyChunksIterationMode;
+use near_chain::signing_history::SigningHistory;
 use near_chain_configs::test_genesis::TestGenesisBuilder;
 use near_chain_configs::test_utils::TestClientConfigParams;
 use near_chain_configs::{ClientConfig, MutableConfigValue, TrackedShardsConfig};
@@ -111,6 +112,7 @@ fn test_raw_client_test_loop_setup() {
         client_adapter.as_multi_sender(),
         noop().into_multi_sender(),
         protocol_upgrade_schedule,
+        SigningHistory::disabled(),
     )
     .unwrap();
 
@@ -5,6 +5,7 @@ use near_async::test_loop::TestLoopV2;
 use near_async::time::Duration;
 use near_chain::resharding::resharding_actor::ReshardingActor;
 use near_chain::runtime::NightshadeRuntime;
+use near_chain::signing_history::SigningHistory;
 use near_chain::spice_core::SpiceCoreReader;
 use near_chain::spice_core_writer_actor::SpiceCoreWriterActor;
 use near_chain::state_snapshot_actor::{
@@ -172,6 +173,7 @@ pub fn setup_client(
         client_adapter.as_multi_sender(),
         chunk_validation_client_sender.as_multi_sender(),
         upgrade_schedule.clone(),
+        SigningHistory::disabled(),
     )
     .unwrap();
 
@@ -251,6 +253,7 @@ pub fn setup_client(
         runtime_adapter.clone(),
         network_adapter.as_sender(),
         validator_signer.clone(),
+        client.signing_history.clone(),
         client_config.save_latest_witnesses,
         client_config.save_invalid_witnesses,
         Arc::new(test_loop.async_computation_spawner(identifier, |_| Duration::milliseconds(80))),
@@ -296,7 +299,7 @@ pub fn setup_client(
 
     let rpc_handler_config = RpcHandlerConfig {
         handler_threads: client_config.transaction_request_handler_threads,
-        tx_routing_height_horizon: client_config.tx_routing_height_horizon,
+        tx_routing_height_horizon: client_config.tx_routing_height_horizon.clone(),
         epoch_length: client_config.epoch_length,
         transaction_validity_period: genesis.config.transaction_validity_period,
         disable_tx_routing: client_config.disable_tx_routing,
@@ -1,16 +1,22 @@
 use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use near_async::time::Duration;
 use near_chain_configs::test_genesis::{TestEpochConfigBuilder, ValidatorsSpec};
+use near_chain_configs::{BlockSyncCloudArchiveConfig, TrackedShardsConfig};
+use near_network::types::NetworkRequests;
 use near_o11y::testonly::init_test_logger;
 use near_primitives::shard_layout::ShardLayout;
 use near_primitives::types::{AccountId, BlockHeight, BlockHeightDelta};
+use near_store::archive::cloud_storage::config::test_cloud_archival_config;
 
-use crate::setup::builder::TestLoopBuilder;
+use crate::setup::builder::{NodeStateBuilder, TestLoopBuilder};
 use crate::utils::cloud_archival::{
-    gc_and_heads_sanity_checks, pause_and_resume_writer_with_sanity_checks, run_node_until,
-    test_view_client,
+    gc_and_heads_sanity_checks, get_cloud_head, pause_and_resume_writer_with_sanity_checks,
+    run_node_until, test_view_client,
 };
+use crate::utils::node::TestLoopNode;
 
 const MIN_GC_NUM_EPOCHS_TO_KEEP: u64 = 3;
 /// Minimum epoch length assumed in tests.
@@ -175,3 +181,94 @@ fn test_cloud_archival_read_block() {
             .build(),
     );
 }
+
+/// Verifies that a node whose block requests peers never answer catches up
+/// with the blocks and chunks block sync downloads from a filesystem cloud
+/// archive.
+#[test]
+// TODO(spice): Assess if this test is relevant for spice and if yes fix it.
+#[cfg_attr(feature = "protocol_feature_spice", ignore)]
+fn test_block_sync_from_cloud_archive() {
+    init_test_logger();
+
+    let validator_id: AccountId = "cp0".parse().unwrap();
+    let validators_spec = ValidatorsSpec::desired_roles(&[validator_id.as_str()], &[]);
+    let genesis = TestLoopBuilder::new_genesis_builder()
+        .epoch_length(MIN_EPOCH_LENGTH)
+        .validators_spec(validators_spec)
+        .shard_layout(ShardLayout::multi_shard(3, 3))
+        .build();
+    let epoch_config_store = TestEpochConfigBuilder::build_store_from_genesis(&genesis);
+
+    let archival_id: AccountId = "archival".parse().unwrap();
+    let all_clients = vec![archival_id.clone(), validator_id];
+    let archival_index = all_clients.iter().position(|id| id == &archival_id).unwrap();
+    let mut env = TestLoopBuilder::new()
+        .genesis(genesis)
+        .epoch_config_store(epoch_config_store)
+        .clients(all_clients)
+        .cloud_storage_archival_clients([archival_id.clone()].into_iter().collect())
+        .config_modifier(move |config, client_index| {
+            if client_index == archival_index {
+                config.cloud_archival_writer = Some(Default::default());
+            }
+        })
+        .build()
+        .warmup();
+
+    run_node_until(&mut env, &archival_id, MIN_NUM_EPOCHS_TO_WAIT * MIN_EPOCH_LENGTH);
+    let cloud_head = get_cloud_head(&env, &archival_id);
+    assert!(cloud_head > MIN_EPOCH_LENGTH);
+
+    // The archival writer uploads to the cloud archive under the shared test directory.
+    let tempdir_path = env.shared_state.tempdir.path().to_path_buf();
+    let location = test_cloud_archival_config(tempdir_path.clone()).cloud_storage.location;
+    let syncer_id: AccountId = "syncer".parse().unwrap();
+    let node_state = NodeStateBuilder::new(env.shared_state.genesis.clone(), tempdir_path)
+        .account_id(syncer_id.clone())
+        .config_modifier(move |config| {
+            config.tracked_shards_config = TrackedShardsConfig::AllShards;
+            // Catch up by block sync only.
+            config.state_sync_enabled = false;
+            config.epoch_sync.disable_epoch_sync_for_bootstrapping = true;
+            config.block_sync_cloud_archive = Some(BlockSyncCloudArchiveConfig {
+                location: location.clone(),
+                credentials_file: None,
+            });
+        })
+        .build();
+    env.add_node(syncer_id.as_str(), node_state);
+
+    // Peers still serve headers, but never answer block requests.
+    let dropped_block_requests = Arc::new(AtomicUsize::new(0));
+    {
+        let dropped_block_requests = dropped_block_requests.clone();
+        let peer_manager_handle = env.node_datas.last().unwrap().peer_manager_sender.actor_handle();
+        let peer_manager = env.test_loop.data.get_mut(&peer_manager_handle);
+        peer_manager.register_override_handler(Box::new(move |request| match request {
+            NetworkRequests::BlockRequest { .. } => {
+                dropped_block_requests.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+            other => Some(other),
+        }));
+    }
+
+    let syncer = TestLoopNode::for_account(&env.node_datas, &syncer_id);
+    syncer.run_until_head_height_with_timeout(
+        &mut env.test_loop,
+        cloud_head,
+        Duration::seconds(cloud_head as i64 * 5),
+    );
+    assert!(dropped_block_requests.load(Ordering::Relaxed) > 0);
+
+    // The blocks below the cloud head were produced before the node joined, so
+    // they and their chunks could only come from the cloud archive.
+    let client = syncer.client(env.test_loop_data());
+    let block = client.chain.get_block_by_height(cloud_head - 1).unwrap();
+    for chunk_header in block.chunks().iter_new() {
+        client.chain.get_chunk(chunk_header.chunk_hash()).unwrap();
+    }
+
+    env.shutdown_and_drain_remaining_events(Duration::seconds(10));
+}
@@ -1,12 +1,15 @@
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use itertools::Itertools;
 use near_async::time::Duration;
 use near_chain::ChainStoreAccess;
 use near_chain_configs::GenesisConfig;
 use near_chain_configs::test_genesis::{TestEpochConfigBuilder, ValidatorsSpec};
-use near_client::sync::epoch::EpochSync;
+use near_client::sync::epoch::{EpochSync, write_epoch_sync_proof_file};
+use near_network::types::NetworkRequests;
 use near_o11y::testonly::init_test_logger;
 use near_primitives::epoch_sync::EpochSyncProof;
 use near_primitives::shard_layout::ShardLayout;
@@ -233,6 +236,76 @@ fn slow_test_epoch_sync_with_expired_transactions() {
     env.shutdown_and_drain_remaining_events(Duration::seconds(5));
 }
 
+// Tests that a new node bootstraps from an epoch sync proof exported to a file,
+// without requesting a proof from its peers.
+#[test]
+// TODO(spice): Assess if this test is relevant for spice and if yes fix it.
+#[cfg_attr(feature = "protocol_feature_spice", ignore)]
+fn slow_test_epoch_sync_from_proof_file() {
+    init_test_logger();
+    let mut env = setup_initial_blockchain(20);
+
+    let proof_file = env.shared_state.tempdir.path().join("epoch_sync_proof");
+    {
+        let client_handle = env.node_datas[0].client_sender.actor_handle();
+        let client = &env.test_loop.data.get(&client_handle).client;
+        let proof = EpochSync::export_proof(
+            client.chain.chain_store.store(),
+            client.chain.genesis(),
+            env.shared_state.genesis.config.transaction_validity_period,
+            client.epoch_manager.as_ref(),
+        )
+        .unwrap();
+        write_epoch_sync_proof_file(&proof_file, &proof).unwrap();
+    }
+
+    let genesis = env.shared_state.genesis.clone();
+    let tempdir_path = env.shared_state.tempdir.path().to_path_buf();
+    let identifier = format!("account{}", env.node_datas.len());
+    let account_id = identifier.parse().unwrap();
+    let node_state = NodeStateBuilder::new(genesis, tempdir_path)
+        .account_id(account_id)
+        .config_modifier(move |config| {
+            config.epoch_sync.epoch_sync_horizon = 30;
+            config.epoch_sync.proof_file = Some(proof_file.clone());
+            config.block_header_fetch_horizon = 8;
+            config.block_fetch_horizon = 3;
+        })
+        .build();
+    env.add_node(&identifier, node_state);
+
+    let epoch_sync_requests = Arc::new(AtomicUsize::new(0));
+    {
+        let epoch_sync_requests = epoch_sync_requests.clone();
+        let peer_manager_handle = env.node_datas.last().unwrap().peer_manager_sender.actor_handle();
+        let peer_manager = env.test_loop.data.get_mut(&peer_manager_handle);
+        peer_manager.register_override_handler(Box::new(move |request| match request {
+            NetworkRequests::EpochSyncRequest { .. } => {
+                epoch_sync_requests.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+            other => Some(other),
+        }));
+    }
+
+    let new_node = env.node_datas.last().unwrap().client_sender.actor_handle();
+    let node0 = env.node_datas[0].client_sender.actor_handle();
+    env.test_loop.run_until(
+        |test_loop_data| {
+            let new_node_height = test_loop_data.get(&new_node).client.chain.head().unwrap().height;
+            let node0_height = test_loop_data.get(&node0).client.chain.head().unwrap().height;
+            new_node_height == node0_height
+        },
+        Duration::seconds(20),
+    );
+    assert_eq!(epoch_sync_requests.load(Ordering::Relaxed), 0);
+    env.assert_epoch_sync_proof_existence_on_disk(env.node_datas.len() - 1, true);
+    // The new node skipped the blocks before the proof's epoch.
+    let genesis_height = env.shared_state.genesis.config.genesis_height;
+    env.assert_header_existence(env.node_datas.len() - 1, genesis_height + 1, false);
+    env.shutdown_and_drain_remaining_events(Duration::seconds(5));
+}
+
 impl TestLoopEnv {
     fn derive_epoch_sync_proof(&self, node_index: usize) -> EpochSyncProof {
         let client_handle = self.node_datas[node_index].client_sender.actor_handle();
@@ -403,6 +403,7 @@ impl GlobalContractsTestEnv {
             account_id: account.clone(),
             method_name: "log_something".to_owned(),
             args: Vec::new().into(),
+            trace: false,
         };
         let response = self.runtime_query(account, query);
         let QueryResponseKind::CallResult(call_result) = response.kind else { unreachable!() };
@@ -0,0 +1,161 @@
+use assert_matches::assert_matches;
+use near_async::messaging::Handler;
+use near_async::time::Duration;
+use near_chain::get_epoch_block_producers_view;
+use near_client::{GetBlockProof, GetExecutionOutcome, GetNextLightClientBlock};
+use near_light_client::{LightClient, LightClientError, LightClientState};
+use near_o11y::testonly::init_test_logger;
+use near_primitives::types::{AccountId, Balance, EpochId, TransactionOrReceiptId};
+use near_primitives::views::LightClientBlockLiteView;
+
+use crate::utils::get_node_data;
+use crate::utils::setups::standard_setup_1;
+use crate::utils::transactions::{prepare_transfer_tx, run_tx};
+
+const EPOCH_LENGTH: u64 = 10;
+const NUM_EPOCHS: u64 = 5;
+
+/// Follows the chain from genesis over several epochs with the light client, checking that
+/// tampered light client blocks are rejected, that the head survives a restart, and that a
+/// transaction outcome can be proven against the light client head.
+#[test]
+// TODO(spice): Assess if this test is relevant for spice and if yes fix it.
+#[cfg_attr(feature = "protocol_feature_spice", ignore)]
+fn slow_test_light_client_follows_chain() {
+    init_test_logger();
+    let mut env = standard_setup_1();
+    let rpc_id: AccountId = "account4".parse().unwrap();
+    let sender: AccountId = "account0".parse().unwrap();
+    let receiver: AccountId = "account1".parse().unwrap();
+
+    let tx = prepare_transfer_tx(&env, &sender, &receiver, Balance::from_near(1));
+    let tx_hash = tx.get_hash();
+    run_tx(&mut env.test_loop, &rpc_id, tx, &env.node_datas, Duration::seconds(5));
+
+    let rpc_data = get_node_data(&env.node_datas, &rpc_id);
+    let client_handle = rpc_data.client_sender.actor_handle();
+    let view_client_handle = rpc_data.view_client_sender.actor_handle();
+    let genesis_height = env.test_loop.data.get(&client_handle).client.chain.genesis().height();
+    let target_height = genesis_height + EPOCH_LENGTH * NUM_EPOCHS;
+    env.test_loop.run_until(
+        |test_loop_data| {
+            let client = &test_loop_data.get(&client_handle).client;
+            client.chain.head().unwrap().height >= target_height
+        },
+        Duration::seconds((EPOCH_LENGTH * NUM_EPOCHS) as i64),
+    );
+
+    // Trust genesis and the block producers of its epoch and the next one.
+    let initial_state = {
+        let client = &env.test_loop.data.get(&client_handle).client;
+        let genesis_header = client.chain.genesis().clone();
+        let epoch_block_producers = [*genesis_header.epoch_id(), *genesis_header.next_epoch_id()]
+            .into_iter()
+            .map(|epoch_id: EpochId| {
+                let block_producers =
+                    get_epoch_block_producers_view(&epoch_id, client.epoch_manager.as_ref())
+                        .unwrap();
+                (epoch_id.0, block_producers)
+            })
+            .collect();
+        LightClientState {
+            head: LightClientBlockLiteView::from(genesis_header),
+            epoch_block_producers,
+        }
+    };
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let state_path = tmp_dir.path().join("light_client.json");
+    let mut light_client = LightClient::open(&state_path, initial_state.clone()).unwrap();
+
+    let view_client = env.test_loop.data.get_mut(&view_client_handle);
+    let mut num_updates = 0;
+    let mut checked_tampered_blocks = false;
+    loop {
+        let block = view_client
+            .handle(GetNextLightClientBlock { last_block_hash: light_client.head_hash() })
+            .unwrap();
+        let Some(block) = block else {
+            break;
+        };
+
+        if !checked_tampered_blocks
+            && block.inner_lite.epoch_id == light_client.head().inner_lite.next_epoch_id
+        {
+            let mut no_approvals = (*block).clone();
+            no_approvals.approvals_after_next.iter_mut().for_each(|approval| *approval = None);
+            assert_matches!(
+                light_client.validate_and_update_head(&no_approvals),
+                Err(LightClientError::NotEnoughApprovals { .. })
+            );
+
+            let mut wrong_next_bps = (*block).clone();
+            wrong_next_bps.next_bps.as_mut().unwrap().pop();
+            assert_matches!(
+                light_client.validate_and_update_head(&wrong_next_bps),
+                Err(LightClientError::InvalidNextBlockProducers)
+            );
+
+            let mut wrong_block = (*block).clone();
+            wrong_block.inner_lite.prev_state_root = Default::default();
+            assert_matches!(
+                light_client.validate_and_update_head(&wrong_block),
+                Err(LightClientError::InvalidSignature { .. })
+            );
+            checked_tampered_blocks = true;
+        }
+
+        light_client.validate_and_update_head(&block).unwrap();
+        assert_matches!(
+            light_client.validate_and_update_head(&block),
+            Err(LightClientError::NotAfterHead { .. })
+        );
+        num_updates += 1;
+    }
+    assert!(checked_tampered_blocks);
+    assert!(num_updates >= NUM_EPOCHS - 1);
+    assert!(light_client.head().inner_lite.height > target_height - EPOCH_LENGTH);
+
+    // The head is persisted.
+    let head_hash = light_client.head_hash();
+    drop(light_client);
+    let light_client = LightClient::open(&state_path, initial_state).unwrap();
+    assert_eq!(light_client.head_hash(), head_hash);
+
+    // Prove the transfer against the light client head.
+    let outcome = view_client
+        .handle(GetExecutionOutcome {
+            id: TransactionOrReceiptId::Transaction {
+                transaction_hash: tx_hash,
+                sender_id: sender,
+            },
+        })
+        .unwrap();
+    let block_proof = view_client
+        .handle(GetBlockProof {
+            block_hash: outcome.outcome_proof.block_hash,
+            head_block_hash: light_client.head_hash(),
+        })
+        .unwrap();
+    light_client
+        .verify_execution_outcome_proof(
+            &outcome.outcome_proof,
+            &outcome.outcome_root_proof,
+            &block_proof.block_header_lite,
+            &block_proof.proof,
+        )
+        .unwrap();
+
+    let mut wrong_outcome = outcome.outcome_proof.clone();
+    wrong_outcome.outcome.logs.push("forged".to_string());
+    assert_matches!(
+        light_client.verify_execution_outcome_proof(
+            &wrong_outcome,
+            &outcome.outcome_root_proof,
+            &block_proof.block_header_lite,
+            &block_proof.proof,
+        ),
+        Err(LightClientError::InvalidOutcomeProof)
+    );
+
+    env.shutdown_and_drain_remaining_events(Duration::seconds(20));
+}
@@ -25,6 +25,7 @@ mod in_memory_tries;
 mod increase_max_congestion_missing_chunks;
 #[cfg(feature = "test_features")]
 mod indexer;
+mod light_client;
 mod malicious_chunk_producer;
 mod max_receipt_size;
 mod multinode_stateless_validators;
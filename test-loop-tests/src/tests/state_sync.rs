@@ -1,22 +1,27 @@
 use near_async::messaging::{CanSend, Handler};
 use near_async::test_loop::TestLoopV2;
+use near_async::test_loop::data::{TestLoopData, TestLoopDataHandle};
 use near_async::time::Duration;
 use near_chain::ChainStoreAccess;
 use near_chain_configs::TrackedShardsConfig;
 use near_chain_configs::test_genesis::{
     TestEpochConfigBuilder, TestGenesisBuilder, ValidatorsSpec,
 };
+use near_client::SyncStatus;
+use near_client::client_actor::ClientActor;
 use near_network::client::{ProcessTxRequest, StateRequestHeader};
 use near_o11y::testonly::init_test_logger;
 use near_primitives::epoch_manager::EpochConfigStore;
 use near_primitives::hash::CryptoHash;
 use near_primitives::shard_layout::ShardLayout;
+use near_primitives::state_sync::StatePartKey;
 use near_primitives::test_utils::create_user_test_signer;
 use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::{
     AccountId, AccountInfo, Balance, BlockHeight, BlockHeightDelta, Nonce, NumSeats, ShardId,
 };
 use near_primitives::version::{PROTOCOL_VERSION, ProtocolVersion};
+use near_store::DBCol;
 
 use crate::setup::builder::{NodeStateBuilder, TestLoopBuilder};
 use crate::setup::drop_condition::DropCondition;
@@ -24,6 +29,7 @@ use crate::setup::env::TestLoopEnv;
 use crate::setup::state::NodeExecutionData;
 use crate::utils::transactions::{get_anchor_hash, get_smallest_height_head};
 
+use borsh::BorshDeserialize;
 use itertools::Itertools;
 use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
@@ -918,6 +924,101 @@ fn slow_test_state_sync_fork_before_sync() {
     run_state_sync_test_case(params);
 }
 
+/// Returns the state parts for `sync_hash` stored by a node, sorted by key.
+fn stored_state_parts(
+    data: &TestLoopData,
+    client_handle: &TestLoopDataHandle<ClientActor>,
+    sync_hash: CryptoHash,
+) -> Vec<(StatePartKey, Vec<u8>)> {
+    let store = data.get(client_handle).client.chain.chain_store.store();
+    store
+        .iter(DBCol::StateParts)
+        .map(|item| {
+            let (key, value) = item.unwrap();
+            (StatePartKey::try_from_slice(&key).unwrap(), value.to_vec())
+        })
+        .filter(|(key, _)| key.0 == sync_hash)
+        .sorted_by_key(|(key, _)| (key.1, key.2))
+        .collect()
+}
+
+// Restarts a node in the middle of state sync, after it stored some state parts, one of
+// which gets corrupted while the node is down. The restarted node should reuse the valid
+// stored part and download the corrupted one again.
+#[test]
+// TODO(spice): Assess if this test is relevant for spice and if yes fix it.
+#[cfg_attr(feature = "protocol_feature_spice", ignore)]
+fn slow_test_state_sync_resume_after_restart() {
+    init_test_logger();
+    let TestState { mut env, .. } = setup_initial_blockchain(
+        2,
+        2,
+        2,
+        2,
+        true,
+        HashMap::default(),
+        None,
+        &None,
+        PROTOCOL_VERSION,
+    );
+    let sync_hash = await_sync_hash(&mut env);
+
+    let genesis = env.shared_state.genesis.clone();
+    let tempdir_path = env.shared_state.tempdir.path().to_path_buf();
+    let account_id: AccountId = "sync-from-scratch".parse().unwrap();
+    let new_node_state = NodeStateBuilder::new(genesis, tempdir_path)
+        .account_id(account_id.clone())
+        .config_modifier(move |config| {
+            config.block_fetch_horizon = 5;
+            config.tracked_shards_config = TrackedShardsConfig::AllShards;
+        })
+        .build();
+    env.add_node(account_id.as_str(), new_node_state);
+    let new_node = env.node_datas.last().unwrap().client_sender.actor_handle();
+
+    // Stop the node once it stored the parts of both shards, before it finished applying them.
+    env.test_loop.run_until(
+        |data| stored_state_parts(data, &new_node, sync_hash).len() >= 2,
+        Duration::seconds(10),
+    );
+    let parts = stored_state_parts(&env.test_loop.data, &new_node, sync_hash);
+    let node_state = env.kill_node(account_id.as_str());
+    let (corrupted_key, valid_bytes) = parts[0].clone();
+    let corrupted_key_bytes = borsh::to_vec(&corrupted_key).unwrap();
+    let mut store_update = node_state.storage.hot_store.store_update();
+    store_update.set(DBCol::StateParts, &corrupted_key_bytes, b"corrupted");
+    store_update.commit().unwrap();
+
+    env.restart_node(&format!("{}-restart", account_id), node_state);
+    let restarted_node = env.node_datas.last().unwrap().client_sender.actor_handle();
+    let mut parts_resumed = 0;
+    let mut corrupted_part_downloaded = false;
+    env.test_loop.run_until(
+        |data| {
+            let client = &data.get(&restarted_node).client;
+            if let SyncStatus::StateSync(status) = &client.sync_handler.sync_status {
+                let resumed: u64 = status.shard_progress.values().map(|p| p.parts_resumed).sum();
+                parts_resumed = parts_resumed.max(resumed);
+            }
+            let stored = client
+                .chain
+                .chain_store
+                .store()
+                .get(DBCol::StateParts, &corrupted_key_bytes)
+                .unwrap();
+            corrupted_part_downloaded |=
+                stored.is_some_and(|bytes| bytes.as_slice() == valid_bytes.as_slice());
+            client.chain.head().unwrap().height > GENESIS_HEIGHT
+        },
+        Duration::seconds(10),
+    );
+    // The valid stored part was reused and the corrupted one was downloaded again.
+    assert!(parts_resumed > 0);
+    assert!(corrupted_part_downloaded);
+
+    env.shutdown_and_drain_remaining_events(Duration::seconds(3));
+}
+
 fn await_sync_hash(env: &mut TestLoopEnv) -> CryptoHash {
     env.test_loop.run_until(
         |data| {
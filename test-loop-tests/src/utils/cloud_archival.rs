@@ -94,7 +94,7 @@ fn get_writer_handle<'a>(
     env.test_loop.data.get(writer_handle).as_ref().unwrap()
 }
 
-fn get_cloud_head(env: &TestLoopEnv, writer_id: &AccountId) -> BlockHeight {
+pub fn get_cloud_head(env: &TestLoopEnv, writer_id: &AccountId) -> BlockHeight {
     let archival_node = TestLoopNode::for_account(&env.node_datas, writer_id);
     let hot_store = archival_node.client(env.test_loop_data()).chain.chain_store().store();
     hot_store.get_ser::<Tip>(DBCol::BlockMisc, CLOUD_HEAD_KEY).unwrap().unwrap().height
@@ -13,6 +13,7 @@ use crate::memtrie::{
 };
 use crate::run_migrations::RunMigrationsCommand;
 use crate::set_version::SetVersionCommand;
+use crate::simulate_resharding::SimulateReshardingCommand;
 use crate::state_perf::StatePerfCommand;
 use crate::write_to_db::WriteCryptoHashCommand;
 use clap::Parser;
@@ -58,6 +59,9 @@ enum SubCommand {
     /// Splits the given shard on the given boundary account and prints approximate
     /// RAM usage of the child shards.
     SplitShardTrie(SplitShardTrieCommand),
+    /// Runs the split of a shard into the given shard layout against a snapshot of the
+    /// database and reports timing, memory peak, child shard sizes and congestion info.
+    SimulateResharding(SimulateReshardingCommand),
     /// Find a split account for the given shard based on `memory_usage` stored in trie nodes.
     FindBoundaryAccount(FindBoundaryAccountCommand),
     /// Recover the archival data that was lost during resharding.
@@ -97,6 +101,7 @@ impl DatabaseCommand {
             SubCommand::StatePerf(cmd) => cmd.run(home),
             SubCommand::LoadMemTrie(cmd) => cmd.run(home, genesis_validation),
             SubCommand::SplitShardTrie(cmd) => cmd.run(home, genesis_validation),
+            SubCommand::SimulateResharding(cmd) => cmd.run(home, genesis_validation),
             SubCommand::FindBoundaryAccount(cmd) => cmd.run(home, genesis_validation),
             SubCommand::ArchivalDataLossRecovery(cmd) => cmd.run(home, genesis_validation),
             SubCommand::WriteCryptoHash(cmd) => cmd.run(home, genesis_validation),
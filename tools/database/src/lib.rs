@@ -12,6 +12,7 @@ mod make_snapshot;
 mod memtrie;
 mod run_migrations;
 mod set_version;
+mod simulate_resharding;
 mod state_perf;
 mod utils;
 mod write_to_db;
@@ -236,20 +236,24 @@ impl SplitShardTrieCommand {
     }
 }
 
-struct MemtrieSizeCalculator<'a, 'b> {
+pub(crate) struct MemtrieSizeCalculator<'a, 'b> {
     chain_store: ChainStoreAdapter,
     shard_tries: &'a ShardTries,
     block: &'b Block,
 }
 
 impl<'a, 'b> MemtrieSizeCalculator<'a, 'b> {
-    fn new(chain_store: ChainStoreAdapter, shard_tries: &'a ShardTries, block: &'b Block) -> Self {
+    pub(crate) fn new(
+        chain_store: ChainStoreAdapter,
+        shard_tries: &'a ShardTries,
+        block: &'b Block,
+    ) -> Self {
         Self { chain_store, shard_tries, block }
     }
 
     /// Get RAM usage of a shard trie
     /// Does a BFS of the whole memtrie
-    fn get_shard_trie_size(&self, shard_uid: ShardUId) -> anyhow::Result<ByteSize> {
+    pub(crate) fn get_shard_trie_size(&self, shard_uid: ShardUId) -> anyhow::Result<ByteSize> {
         let chunk_extra =
             self.chain_store.chunk_store().get_chunk_extra(self.block.hash(), &shard_uid)?;
         let state_root = chunk_extra.state_root();
@@ -0,0 +1,315 @@
+use crate::memtrie::MemtrieSizeCalculator;
+use anyhow::Context;
+use bytesize::ByteSize;
+use near_async::messaging::{IntoMultiSender, noop};
+use near_async::time::Instant;
+use near_chain::resharding::event_type::{ReshardingEventType, ReshardingSplitShardParams};
+use near_chain::resharding::flat_storage_resharder::FlatStorageResharder;
+use near_chain::resharding::manager::ReshardingManager;
+use near_chain::resharding::trie_state_resharder::{ResumeAllowed, TrieStateResharder};
+use near_chain::types::RuntimeAdapter;
+use near_chain::{ChainStore, ChainStoreAccess};
+use near_chain_configs::{GenesisValidationMode, ReshardingHandle, TrackedShardsConfig};
+use near_epoch_manager::shard_tracker::ShardTracker;
+use near_epoch_manager::{EpochManager, EpochManagerAdapter};
+use near_o11y::default_subscriber;
+use near_o11y::env_filter::EnvFilterBuilder;
+use near_primitives::epoch_manager::EpochConfigStore;
+use near_primitives::shard_layout::ShardLayout;
+use near_primitives::types::AccountId;
+use near_store::adapter::StoreAdapter;
+use near_store::db::RocksDB;
+use near_store::db::rocksdb::snapshot::Snapshot;
+use near_store::flat::BlockInfo;
+use near_store::trie::ops::resharding::RetainMode;
+use near_store::{Mode, ShardUId, Store, Temperature};
+use nearcore::{NightshadeRuntime, NightshadeRuntimeExt};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Runs the whole split of a shard, memtrie and flat storage included, against a
+/// snapshot of the database and reports how long it took, the memory peak, the sizes
+/// of the child shards and their congestion info. The live database is not modified.
+///
+/// Example usage: neard database simulate-resharding --boundary-account foo.near
+#[derive(clap::Parser)]
+pub struct SimulateReshardingCommand {
+    /// JSON file with the proposed shard layout. It must split exactly one shard of the
+    /// current shard layout.
+    #[clap(
+        long,
+        conflicts_with = "boundary_account",
+        required_unless_present = "boundary_account"
+    )]
+    shard_layout_file: Option<PathBuf>,
+    /// Derive the proposed shard layout from the current one by adding this boundary account.
+    #[clap(long)]
+    boundary_account: Option<AccountId>,
+}
+
+impl SimulateReshardingCommand {
+    pub fn run(
+        &self,
+        home: &Path,
+        genesis_validation: GenesisValidationMode,
+    ) -> anyhow::Result<()> {
+        let env_filter = EnvFilterBuilder::from_env().verbose(Some("resharding")).finish()?;
+        let _subscriber = default_subscriber(env_filter, &Default::default()).global();
+        let mut near_config = nearcore::config::load_config(&home, genesis_validation)
+            .unwrap_or_else(|e| panic!("Error loading config: {:#}", e));
+        near_config.config.store.load_memtries_for_tracked_shards = true;
+        // Both children have to be resharded, regardless of what the node tracks.
+        near_config.client_config.tracked_shards_config = TrackedShardsConfig::AllShards;
+        let genesis_config = &near_config.genesis.config;
+
+        println!("Creating database snapshot...");
+        let db_path =
+            near_config.config.store.path.as_ref().cloned().unwrap_or_else(|| home.join("data"));
+        let snapshot = SnapshotGuard(Snapshot::new(
+            db_path.as_path(),
+            &near_config.config.store,
+            Temperature::Hot,
+        )?);
+        let snapshot_path =
+            snapshot.0.0.clone().ok_or_else(|| anyhow::anyhow!("Snapshot not created"))?;
+        println!("Database snapshot created at {}", snapshot_path.display());
+
+        let rocksdb = Arc::new(RocksDB::open(
+            &snapshot_path,
+            &near_config.config.store,
+            Mode::ReadWrite,
+            Temperature::Hot,
+        )?);
+        let store = near_store::NodeStorage::new(rocksdb).get_hot_store();
+        let mut chain_store =
+            ChainStore::new(store.clone(), true, genesis_config.transaction_validity_period);
+        // The last block having chunk extras for all shards is used as the resharding block,
+        // so that flat storage catchup of the children stops right after it.
+        let final_head = chain_store.final_head()?;
+        let block = chain_store.get_block(&final_head.prev_block_hash)?;
+        let resharding_block = BlockInfo {
+            hash: *block.hash(),
+            height: block.header().height(),
+            prev_hash: *block.header().prev_hash(),
+        };
+        println!(
+            "Resharding block: {} at height {}",
+            resharding_block.hash, resharding_block.height
+        );
+
+        let epoch_manager =
+            EpochManager::new_arc_handle(store.clone(), &genesis_config, Some(home));
+        let epoch_id = block.header().epoch_id();
+        let protocol_version = epoch_manager.get_epoch_protocol_version(epoch_id)?;
+        let old_shard_layout = epoch_manager.get_shard_layout(epoch_id)?;
+        let shard_layout = self.new_shard_layout(&old_shard_layout)?;
+        println!("Old shard layout: {old_shard_layout:?}");
+        println!("New shard layout: {shard_layout:?}");
+
+        let Some(ReshardingEventType::SplitShard(split_params)) =
+            ReshardingEventType::from_shard_layout(&shard_layout, resharding_block)?
+        else {
+            anyhow::bail!("the new shard layout does not split any shard");
+        };
+        let parent_shard = split_params.parent_shard;
+        if !old_shard_layout.shard_uids().any(|shard_uid| shard_uid == parent_shard) {
+            anyhow::bail!("shard {parent_shard} being split is not in the current shard layout");
+        }
+        println!(
+            "Splitting shard {parent_shard} into {} and {} at account {}",
+            split_params.left_child_shard,
+            split_params.right_child_shard,
+            split_params.boundary_account
+        );
+
+        let runtime = NightshadeRuntime::from_config(
+            home,
+            store.clone(),
+            &near_config,
+            epoch_manager.clone(),
+        )
+        .context("could not create the transaction runtime")?;
+        let shard_tries = runtime.get_tries();
+        runtime.get_flat_storage_manager().create_flat_storage_for_shard(parent_shard)?;
+
+        let start_time = Instant::now();
+        shard_tries.load_memtrie(&parent_shard, None, false)?;
+        report_phase("Loading parent memtrie", start_time);
+
+        let size_calculator = MemtrieSizeCalculator::new(store.chain_store(), &shard_tries, &block);
+        let parent_size = size_calculator.get_shard_trie_size(parent_shard)?;
+        println!("Parent memtrie size: {parent_size}");
+
+        // The epoch manager used for resharding must return the new shard layout for the
+        // epoch after the resharding block.
+        let epoch_config_store = EpochConfigStore::for_chain_id(&genesis_config.chain_id, None)
+            .ok_or_else(|| anyhow::anyhow!("Cannot load epoch config store"))?;
+        let epoch_config = (**epoch_config_store.get_config(protocol_version))
+            .clone()
+            .with_shard_layout(shard_layout.clone());
+        let epoch_config_store =
+            EpochConfigStore::test_single_version(protocol_version, epoch_config);
+        let resharding_epoch_manager = EpochManager::new_arc_handle_from_epoch_config_store(
+            store.clone(),
+            &genesis_config,
+            epoch_config_store,
+        );
+
+        print_child_congestion_info(
+            &store,
+            &runtime,
+            &split_params,
+            &old_shard_layout,
+            &shard_layout,
+        )?;
+
+        let shard_tracker = ShardTracker::new(
+            near_config.client_config.tracked_shards_config.clone(),
+            resharding_epoch_manager.clone(),
+            near_config.validator_signer.clone(),
+        );
+        let resharding_manager = ReshardingManager::new(
+            store.clone(),
+            resharding_epoch_manager.clone(),
+            shard_tracker,
+            noop().into_multi_sender(),
+        );
+        let start_time = Instant::now();
+        resharding_manager.split_shard(
+            chain_store.store_update(),
+            &block,
+            parent_shard,
+            shard_tries.clone(),
+            split_params.clone(),
+            false,
+        )?;
+        report_phase("Memtrie split", start_time);
+        for child_shard in split_params.children_shards() {
+            let child_size = size_calculator.get_shard_trie_size(child_shard)?;
+            println!("Child {child_shard} memtrie size: {child_size}");
+        }
+        println!(
+            "WARNING: Calculated memory usages are only approximations and may differ from \
+             actual RAM allocation when the shards are loaded into memory."
+        );
+
+        let resharding_config = near_config.client_config.resharding_config.clone();
+        let flat_storage_resharder = FlatStorageResharder::new(
+            resharding_epoch_manager,
+            runtime.clone(),
+            ReshardingHandle::new(),
+            resharding_config.clone(),
+        );
+        let start_time = Instant::now();
+        flat_storage_resharder.start_resharding_blocking(&split_params)?;
+        report_phase("Flat storage split", start_time);
+        for child_shard in split_params.children_shards() {
+            let (num_entries, total_size) = flat_state_size(&store, child_shard)?;
+            println!(
+                "Child {child_shard} flat state: {num_entries} entries, {}",
+                ByteSize::b(total_size)
+            );
+        }
+
+        let trie_state_resharder = TrieStateResharder::new(
+            runtime,
+            ReshardingHandle::new(),
+            resharding_config,
+            ResumeAllowed::No,
+        );
+        let start_time = Instant::now();
+        trie_state_resharder.initialize_trie_state_resharding_status(&split_params)?;
+        trie_state_resharder.start_resharding_blocking(&split_params)?;
+        report_phase("Trie state split", start_time);
+
+        Ok(())
+    }
+
+    fn new_shard_layout(&self, old_shard_layout: &ShardLayout) -> anyhow::Result<ShardLayout> {
+        if let Some(path) = &self.shard_layout_file {
+            let contents = std::fs::read_to_string(path)
+                .with_context(|| format!("could not read {}", path.display()))?;
+            return serde_json::from_str(&contents)
+                .with_context(|| format!("could not parse shard layout from {}", path.display()));
+        }
+        let boundary_account = self.boundary_account.clone().expect("enforced by clap");
+        Ok(ShardLayout::derive_shard_layout(old_shard_layout, boundary_account))
+    }
+}
+
+/// Removes the database snapshot the simulation runs on when dropped, so that the snapshot
+/// doesn't outlive a failed simulation. It must be declared before anything that keeps the
+/// snapshot database open, so that it's dropped after it.
+struct SnapshotGuard(Snapshot);
+
+impl Drop for SnapshotGuard {
+    fn drop(&mut self) {
+        if self.0.0.is_none() {
+            return;
+        }
+        println!("Removing database snapshot...");
+        match std::mem::replace(&mut self.0, Snapshot::none()).remove() {
+            Ok(()) => println!("Database snapshot removed"),
+            Err(err) => eprintln!("{err}"),
+        }
+    }
+}
+
+/// Computes the congestion info of the children from the parent state at the resharding block.
+fn print_child_congestion_info(
+    store: &Store,
+    runtime: &NightshadeRuntime,
+    split_params: &ReshardingSplitShardParams,
+    parent_shard_layout: &ShardLayout,
+    child_shard_layout: &ShardLayout,
+) -> anyhow::Result<()> {
+    let parent_shard = split_params.parent_shard;
+    let parent_chunk_extra =
+        store.chunk_store().get_chunk_extra(&split_params.resharding_block.hash, &parent_shard)?;
+    let parent_congestion_info = parent_chunk_extra.congestion_info();
+    println!("Parent {parent_shard} congestion info: {parent_congestion_info:?}");
+    let parent_trie =
+        runtime.get_tries().get_trie_for_shard(parent_shard, *parent_chunk_extra.state_root());
+    for (child_shard, retain_mode) in [
+        (split_params.left_child_shard, RetainMode::Left),
+        (split_params.right_child_shard, RetainMode::Right),
+    ] {
+        let child_congestion_info = ReshardingManager::get_child_congestion_info(
+            &parent_trie,
+            parent_shard_layout,
+            parent_congestion_info,
+            child_shard_layout,
+            &child_shard,
+            retain_mode,
+        )?;
+        println!("Child {child_shard} congestion info: {child_congestion_info:?}");
+    }
+    Ok(())
+}
+
+/// Returns the number of entries and the total size of keys and values in the flat state
+/// of the given shard.
+fn flat_state_size(store: &Store, shard_uid: ShardUId) -> anyhow::Result<(u64, u64)> {
+    let mut num_entries = 0;
+    let mut total_size = 0;
+    for item in store.flat_store().iter(shard_uid) {
+        let (key, value) = item?;
+        num_entries += 1;
+        total_size += (key.len() + value.value_len()) as u64;
+    }
+    Ok((num_entries, total_size))
+}
+
+fn report_phase(name: &str, start_time: Instant) {
+    let peak_memory = peak_memory_usage()
+        .map_or_else(|| "unknown".to_string(), |peak_memory| peak_memory.to_string());
+    println!("{name} took {:?}, memory peak so far: {peak_memory}", start_time.elapsed());
+}
+
+/// Peak resident set size of the process, as reported by the kernel.
+fn peak_memory_usage() -> Option<ByteSize> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kib = line.trim_start_matches("VmHWM:").trim().trim_end_matches("kB").trim();
+    Some(ByteSize::kib(kib.parse().ok()?))
+}
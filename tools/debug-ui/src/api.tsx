@@ -125,6 +125,7 @@ export type SyncStatusView =
         StateSync: {
             sync_hash: string;
             sync_status: { [shard_id: number]: string };
+            shard_progress: { [shard_id: number]: ShardSyncProgressView };
             download_tasks: string[];
             computation_tasks: string[];
         };
@@ -138,6 +139,14 @@ export type SyncStatusView =
         };
     };
 
+export interface ShardSyncProgressView {
+    num_parts: number;
+    parts_downloaded: number;
+    parts_applied: number;
+    parts_resumed: number;
+    eta_secs: number | null;
+}
+
 export interface ShardSyncDownloadView {
     downloads: { error: boolean; done: boolean }[];
     status: string;
@@ -126,3 +126,55 @@ pub fn map_account(
         AccountType::NearDeterministicAccount => account_id.clone(),
     }
 }
+
+// Derives the key of the `clone_idx`-th clone of the account using `key`. The clone
+// account is the NEAR-implicit account of the returned key, so it can only be created
+// by someone knowing the mapped key, just like the account it's cloned from.
+pub fn map_clone_key(key: &SecretKey, clone_idx: u32) -> SecretKey {
+    let secret_bytes = match key {
+        SecretKey::ED25519(k) => k.0[..ed25519_dalek::SECRET_KEY_LENGTH].to_vec(),
+        SecretKey::SECP256K1(k) => k.secret_bytes().to_vec(),
+    };
+    let mut buf = [0; ed25519_dalek::SECRET_KEY_LENGTH];
+    let hk = Hkdf::<Sha256>::new(Some(&clone_idx.to_le_bytes()), &secret_bytes);
+    hk.expand(b"mirror clone", &mut buf).unwrap();
+
+    let secret_key = ed25519_dalek::SigningKey::from_bytes(&buf);
+    let mut keypair = [0; ed25519_dalek::KEYPAIR_LENGTH];
+    keypair[..ed25519_dalek::SECRET_KEY_LENGTH].copy_from_slice(&buf);
+    keypair[ed25519_dalek::SECRET_KEY_LENGTH..]
+        .copy_from_slice(ed25519_dalek::VerifyingKey::from(&secret_key).as_bytes());
+    SecretKey::ED25519(ED25519SecretKey(keypair))
+}
+
+// Returns the NEAR-implicit account of a clone with the given key.
+pub fn clone_account(clone_key: &SecretKey) -> AccountId {
+    derive_near_implicit_account_id(&clone_key.public_key().unwrap_as_ed25519())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{clone_account, default_extra_key, map_clone_key, map_key};
+    use near_crypto::KeyType;
+    use near_primitives_core::account::id::AccountType;
+
+    #[test]
+    fn test_map_clone_key() {
+        let key = default_extra_key(None);
+        let clone0 = map_clone_key(&key, 0);
+        let clone1 = map_clone_key(&key, 1);
+        // The clone keys are deterministic, and differ between clones and from the key.
+        assert_eq!(clone0, map_clone_key(&key, 0));
+        assert_ne!(clone0, clone1);
+        assert_ne!(clone0.public_key(), key.public_key());
+        assert_eq!(clone0.key_type(), KeyType::ED25519);
+
+        // Clones of a different key are different.
+        let other_key = map_key(&key.public_key(), Some(&[1; crate::secret::SECRET_LEN]));
+        assert_ne!(clone0, map_clone_key(&other_key, 0));
+
+        let clone_id = clone_account(&clone0);
+        assert_eq!(clone_id.get_account_type(), AccountType::NearImplicitAccount);
+        assert_ne!(clone_id, clone_account(&clone1));
+    }
+}
@@ -15,7 +15,7 @@ use near_primitives::hash::CryptoHash;
 use near_primitives::receipt::{Receipt, ReceiptEnum};
 use near_primitives::transaction::{
     Action, AddKeyAction, CreateAccountAction, DeleteAccountAction, DeleteKeyAction,
-    SignedTransaction, StakeAction, Transaction,
+    SignedTransaction, StakeAction, Transaction, TransferAction,
 };
 use near_primitives::types::{
     AccountId, Balance, BlockHeight, BlockReference, Finality, TransactionOrReceiptId,
@@ -246,6 +246,8 @@ struct SourceChunk {
 
 struct SourceBlock {
     hash: CryptoHash,
+    /// Timestamp of the block in nanoseconds.
+    timestamp: u64,
     chunks: Vec<SourceChunk>,
 }
 
@@ -395,6 +397,16 @@ trait ChainAccess {
     ) -> Result<Vec<PublicKey>, ChainError>;
 }
 
+// Only transfers and function calls to another account are duplicated when amplifying
+// traffic, since copies of account and key management actions would either fail or
+// interfere with the mirrored transactions.
+fn is_amplifiable(tx: &SignedTransaction, actions: &[Action]) -> bool {
+    tx.transaction.signer_id() != tx.transaction.receiver_id()
+        && actions
+            .iter()
+            .all(|action| matches!(action, Action::Transfer(_) | Action::FunctionCall(_)))
+}
+
 fn execution_status_good(status: &ExecutionStatusView) -> bool {
     matches!(
         status,
@@ -408,8 +420,86 @@ struct MirrorConfig {
     /// given, then instead of trying to get the transactions in consecutive
     /// mainnet blocks to appear in consecutive target chain blocks, we will
     /// wait this long before sending each mainnet block's worth of transactions.
-    /// TODO: add an option to target a specific number of transactions per second
     tx_batch_interval: Option<Duration>,
+    /// If given, each source block's worth of transactions is sent after the
+    /// time that passed between the source block and the previous one, divided
+    /// by this factor. So 2.0 replays the source chain at twice its speed.
+    /// Can't be combined with `tx_batch_interval`.
+    time_compression: Option<f64>,
+    /// Upper bound on the number of transactions sent per second. If sending a
+    /// batch would exceed it, the next batch is delayed accordingly.
+    max_tps: Option<f64>,
+    /// Restricts which source chain transactions are mirrored.
+    #[serde(default)]
+    tx_filter: TxFilter,
+    /// If given, mirrored transactions are duplicated to generate extra traffic.
+    amplification: Option<AmplificationConfig>,
+}
+
+impl MirrorConfig {
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.time_compression.is_some() && self.tx_batch_interval.is_some() {
+            anyhow::bail!("time_compression and tx_batch_interval can't both be set");
+        }
+        if let Some(time_compression) = self.time_compression {
+            if !(time_compression > 0.0) {
+                anyhow::bail!("time_compression must be positive, got {}", time_compression);
+            }
+        }
+        if let Some(max_tps) = self.max_tps {
+            if !(max_tps > 0.0) {
+                anyhow::bail!("max_tps must be positive, got {}", max_tps);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Source chain transactions are only mirrored if their signer and receiver
+/// pass these filters. Account IDs here are the ones in the source chain.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct TxFilter {
+    /// If given, only transactions sent to one of these accounts are mirrored.
+    include_receivers: Option<HashSet<AccountId>>,
+    /// Transactions sent to one of these accounts are not mirrored.
+    exclude_receivers: HashSet<AccountId>,
+    /// If given, only transactions signed by one of these accounts are mirrored.
+    include_signers: Option<HashSet<AccountId>>,
+    /// Transactions signed by one of these accounts are not mirrored.
+    exclude_signers: HashSet<AccountId>,
+}
+
+impl TxFilter {
+    fn allows(&self, tx: &SignedTransaction) -> bool {
+        let allowed = |account_id: &AccountId,
+                       include: &Option<HashSet<AccountId>>,
+                       exclude: &HashSet<AccountId>| {
+            include.as_ref().is_none_or(|include| include.contains(account_id))
+                && !exclude.contains(account_id)
+        };
+        allowed(tx.transaction.signer_id(), &self.include_signers, &self.exclude_signers)
+            && allowed(
+                tx.transaction.receiver_id(),
+                &self.include_receivers,
+                &self.exclude_receivers,
+            )
+    }
+}
+
+/// Each mirrored transaction consisting only of transfers and function calls
+/// to another account is also sent by `clones` extra accounts. The clones of an
+/// account are NEAR-implicit accounts with keys derived from its mapped key, and
+/// are created on first use by a transfer of `clone_balance` from it.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+struct AmplificationConfig {
+    clones: u32,
+    #[serde(default = "default_clone_balance")]
+    clone_balance: Balance,
+}
+
+fn default_clone_balance() -> Balance {
+    Balance::from_near(10)
 }
 
 const CREATE_ACCOUNT_DELTA: usize = 5;
@@ -427,6 +517,8 @@ struct TxMirror<T: ChainAccess> {
     secret: Option<[u8; crate::secret::SECRET_LEN]>,
     default_extra_key: SecretKey,
     config: MirrorConfig,
+    /// Clone accounts known to exist or to be created by an already prepared transaction.
+    funded_clones: Mutex<HashSet<AccountId>>,
 }
 
 fn open_db<P: AsRef<Path>>(home: P) -> anyhow::Result<DB> {
@@ -442,6 +534,8 @@ fn open_db<P: AsRef<Path>>(home: P) -> anyhow::Result<DB> {
 #[derive(Clone, Copy, Debug)]
 enum MappedTxProvenance {
     MappedSourceTx(BlockHeight, ShardId, usize),
+    AmplifiedSourceTx(BlockHeight, ShardId, usize, u32),
+    CloneFunding(BlockHeight, ShardId, usize, u32),
     TxAddKey(BlockHeight, ShardId, usize),
     ReceiptAddKey(BlockHeight, ShardId, usize),
     TxCreateAccount(BlockHeight, ShardId, usize),
@@ -476,6 +570,16 @@ impl std::fmt::Display for MappedTxProvenance {
             Self::MappedSourceTx(height, shard_id, idx) => {
                 write!(f, "source #{} shard {} tx #{}", height, shard_id, idx)
             }
+            Self::AmplifiedSourceTx(height, shard_id, idx, clone_idx) => write!(
+                f,
+                "clone #{} of source #{} shard {} tx #{}",
+                clone_idx, height, shard_id, idx
+            ),
+            Self::CloneFunding(height, shard_id, idx, clone_idx) => write!(
+                f,
+                "extra Transfer creating clone #{} for source #{} shard {} tx #{}",
+                clone_idx, height, shard_id, idx
+            ),
             Self::TxAddKey(height, shard_id, idx) => {
                 write!(f, "extra AddKey for source #{} shard {} tx #{}", height, shard_id, idx)
             }
@@ -737,6 +841,7 @@ struct MappedChunk {
 struct MappedBlock {
     source_height: BlockHeight,
     source_hash: CryptoHash,
+    source_timestamp: u64,
     chunks: Vec<MappedChunk>,
 }
 
@@ -744,6 +849,7 @@ struct MappedBlock {
 struct TxBatch {
     source_height: BlockHeight,
     source_hash: CryptoHash,
+    source_timestamp: u64,
     txs: Vec<(TxRef, TargetChainTx)>,
 }
 
@@ -752,6 +858,7 @@ impl From<&MappedBlock> for TxBatch {
         Self {
             source_height: block.source_height,
             source_hash: block.source_hash,
+            source_timestamp: block.source_timestamp,
             txs: block
                 .chunks
                 .iter()
@@ -866,6 +973,7 @@ impl<T: ChainAccess> TxMirror<T> {
             secret,
             default_extra_key,
             config,
+            funded_clones: Mutex::new(HashSet::new()),
         })
     }
 
@@ -1492,6 +1600,98 @@ impl<T: ChainAccess> TxMirror<T> {
         Ok(())
     }
 
+    // Returns whether the clone account exists or is created by a transaction
+    // prepared earlier. If not, the caller is expected to prepare one and then
+    // record it with `funded_clones`.
+    async fn clone_funded(
+        &self,
+        target_view_client: &MultithreadRuntimeHandle<ViewClientActor>,
+        clone_id: &AccountId,
+    ) -> anyhow::Result<bool> {
+        if self.funded_clones.lock().contains(clone_id) {
+            return Ok(true);
+        }
+        let exists = account_exists(target_view_client, clone_id)
+            .await
+            .with_context(|| format!("failed checking existence for account {}", clone_id))?;
+        if exists {
+            self.funded_clones.lock().insert(clone_id.clone());
+        }
+        Ok(exists)
+    }
+
+    // Sends copies of a mapped source chain transaction from the clones of its signer,
+    // preceded by transfers creating the clones that don't exist yet.
+    async fn add_amplified_txs(
+        &self,
+        amplification: &AmplificationConfig,
+        source_tx: &SignedTransaction,
+        target_signer_id: &AccountId,
+        target_receiver_id: &AccountId,
+        target_secret_key: &SecretKey,
+        actions: &[Action],
+        source_height: BlockHeight,
+        shard_id: ShardId,
+        idx: usize,
+        ref_hash: &CryptoHash,
+        tracker: &Mutex<crate::chain_tracker::TxTracker>,
+        tx_block_queue: &Mutex<VecDeque<MappedBlock>>,
+        target_view_client: &MultithreadRuntimeHandle<ViewClientActor>,
+        txs: &mut Vec<TargetChainTx>,
+    ) -> anyhow::Result<()> {
+        for clone_idx in 0..amplification.clones {
+            let clone_secret_key = crate::key_mapping::map_clone_key(target_secret_key, clone_idx);
+            let clone_id = crate::key_mapping::clone_account(&clone_secret_key);
+
+            if !self.clone_funded(target_view_client, &clone_id).await? {
+                let nonce_updates =
+                    HashSet::from([(clone_id.clone(), clone_secret_key.public_key())]);
+                let funding_tx = self
+                    .prepare_tx(
+                        tracker,
+                        tx_block_queue,
+                        target_view_client,
+                        source_tx.transaction.signer_id().clone(),
+                        clone_id.clone(),
+                        target_signer_id.clone(),
+                        clone_id.clone(),
+                        target_secret_key,
+                        vec![Action::Transfer(TransferAction {
+                            deposit: amplification.clone_balance,
+                        })],
+                        ref_hash,
+                        Some(source_height),
+                        MappedTxProvenance::CloneFunding(source_height, shard_id, idx, clone_idx),
+                        nonce_updates,
+                    )
+                    .await?;
+                txs.push(funding_tx);
+                self.funded_clones.lock().insert(clone_id.clone());
+            }
+
+            let clone_tx = self
+                .prepare_tx(
+                    tracker,
+                    tx_block_queue,
+                    target_view_client,
+                    source_tx.transaction.signer_id().clone(),
+                    source_tx.transaction.receiver_id().clone(),
+                    clone_id,
+                    target_receiver_id.clone(),
+                    &clone_secret_key,
+                    actions.to_vec(),
+                    ref_hash,
+                    Some(source_height),
+                    MappedTxProvenance::AmplifiedSourceTx(source_height, shard_id, idx, clone_idx),
+                    HashSet::new(),
+                )
+                .await?;
+            txs.push(clone_tx);
+            crate::metrics::TRANSACTIONS_AMPLIFIED.inc();
+        }
+        Ok(())
+    }
+
     // fetch the source chain block at `source_height`, and prepare a
     // set of transactions that should be valid in the target chain
     // from it.
@@ -1514,6 +1714,10 @@ impl<T: ChainAccess> TxMirror<T> {
             let mut txs = Vec::new();
 
             for (idx, source_tx) in ch.transactions.into_iter().enumerate() {
+                if !self.config.tx_filter.allows(&source_tx) {
+                    crate::metrics::TRANSACTIONS_FILTERED.inc();
+                    continue;
+                }
                 let (actions, nonce_updates) =
                     self.map_actions(target_view_client, &source_tx).await?;
                 if actions.is_empty() {
@@ -1534,6 +1738,12 @@ impl<T: ChainAccess> TxMirror<T> {
                     self.secret.as_ref(),
                 );
 
+                let amplified_actions = match &self.config.amplification {
+                    Some(amplification) if is_amplifiable(&source_tx, &actions) => {
+                        Some((amplification, actions.clone()))
+                    }
+                    _ => None,
+                };
                 let target_tx = self
                     .prepare_tx(
                         tracker,
@@ -1541,8 +1751,8 @@ impl<T: ChainAccess> TxMirror<T> {
                         target_view_client,
                         source_tx.transaction.signer_id().clone(),
                         source_tx.transaction.receiver_id().clone(),
-                        target_signer_id,
-                        target_receiver_id,
+                        target_signer_id.clone(),
+                        target_receiver_id.clone(),
                         &target_private_key,
                         actions,
                         &ref_hash,
@@ -1552,6 +1762,25 @@ impl<T: ChainAccess> TxMirror<T> {
                     )
                     .await?;
                 txs.push(target_tx);
+                if let Some((amplification, actions)) = amplified_actions {
+                    self.add_amplified_txs(
+                        amplification,
+                        &source_tx,
+                        &target_signer_id,
+                        &target_receiver_id,
+                        &target_private_key,
+                        &actions,
+                        source_height,
+                        ch.shard_id,
+                        idx,
+                        &ref_hash,
+                        tracker,
+                        tx_block_queue,
+                        target_view_client,
+                        &mut txs,
+                    )
+                    .await?;
+                }
                 self.add_tx_function_call_keys(
                     &source_tx,
                     MappedTxProvenance::TxAddKey(source_height, ch.shard_id, idx),
@@ -1607,7 +1836,12 @@ impl<T: ChainAccess> TxMirror<T> {
                 );
             }
         }
-        Ok(MappedBlock { source_height, source_hash: source_block.hash, chunks })
+        Ok(MappedBlock {
+            source_height,
+            source_hash: source_block.hash,
+            source_timestamp: source_block.timestamp,
+            chunks,
+        })
     }
 
     // Up to a certain capacity, prepare and queue up batches of
@@ -1724,8 +1958,12 @@ impl<T: ChainAccess> TxMirror<T> {
         mut send_time: Pin<Box<tokio::time::Sleep>>,
         send_delay: Arc<Mutex<Duration>>,
         target_client: MultithreadRuntimeHandle<RpcHandlerActor>,
+        config: MirrorConfig,
     ) -> anyhow::Result<()> {
         let mut sent_source_height = None;
+        // When we started sending the last batch, the timestamp of its source block,
+        // the number of transactions sent and how long we meant to wait after it.
+        let mut last_sent: Option<(tokio::time::Instant, u64, usize, Duration)> = None;
 
         loop {
             (&mut send_time).await;
@@ -1759,6 +1997,27 @@ impl<T: ChainAccess> TxMirror<T> {
                 }
             };
 
+            if let Some((last_start_time, last_timestamp, last_num_sent, intended_delay)) =
+                &mut last_sent
+            {
+                if let Some(time_compression) = config.time_compression {
+                    let source_delay = Duration::from_nanos(
+                        tx_batch.source_timestamp.saturating_sub(*last_timestamp),
+                    );
+                    let compressed_delay = source_delay.div_f64(time_compression);
+                    *intended_delay = (*intended_delay).max(compressed_delay);
+                    tokio::time::sleep_until(*last_start_time + compressed_delay).await;
+                }
+                let num_sent = *last_num_sent as f64;
+                if !intended_delay.is_zero() {
+                    crate::metrics::INTENDED_TPS.set(num_sent / intended_delay.as_secs_f64());
+                }
+                let achieved_delay = last_start_time.elapsed();
+                if !achieved_delay.is_zero() {
+                    crate::metrics::ACHIEVED_TPS.set(num_sent / achieved_delay.as_secs_f64());
+                }
+            }
+
             let start_time = tokio::time::Instant::now();
 
             tracing::trace!(target: "mirror", source_height = tx_batch.source_height, "send tx batch");
@@ -1769,10 +2028,26 @@ impl<T: ChainAccess> TxMirror<T> {
             .await?;
             set_last_source_height(&db, tx_batch.source_height)?;
             sent_source_height = Some(tx_batch.source_height);
+            let num_sent = tx_batch
+                .txs
+                .iter()
+                .filter(
+                    |(_tx_ref, tx)| matches!(tx, TargetChainTx::Ready(t) if t.sent_successfully),
+                )
+                .count();
+            let source_timestamp = tx_batch.source_timestamp;
 
             blocks_sent.send(tx_batch).await.context("failed to send block")?;
 
-            let send_delay = *send_delay.lock();
+            let mut send_delay = match config.time_compression {
+                // We wait for the time between the source blocks once we know the next one.
+                Some(_) => Duration::ZERO,
+                None => *send_delay.lock(),
+            };
+            if let Some(max_tps) = config.max_tps {
+                send_delay = send_delay.max(Duration::from_secs_f64(num_sent as f64 / max_tps));
+            }
+            last_sent = Some((start_time, source_timestamp, num_sent, send_delay));
             tracing::trace!(target: "mirror", ?send_delay, "sleep before sending more txs");
             let next_send_time = start_time + send_delay;
             send_time.as_mut().reset(next_send_time);
@@ -2067,6 +2342,7 @@ impl<T: ChainAccess> TxMirror<T> {
             let mut block = MappedBlock {
                 source_hash: CryptoHash::default(),
                 source_height: last_height,
+                source_timestamp: 0,
                 chunks: vec![MappedChunk { shard_id: ShardId::new(0), txs: Vec::new() }],
             };
 
@@ -2122,6 +2398,7 @@ impl<T: ChainAccess> TxMirror<T> {
         let tx_block_queue2 = tx_block_queue.clone();
         let rpc_handler2 = rpc_handler.clone();
         let db = self.db.clone();
+        let config = self.config.clone();
         let (send_txs_done_tx, send_txs_done_rx) =
             tokio::sync::oneshot::channel::<anyhow::Result<()>>();
         let _send_txs_task = tokio::task::spawn(async move {
@@ -2132,6 +2409,7 @@ impl<T: ChainAccess> TxMirror<T> {
                 send_time,
                 send_delay2,
                 rpc_handler2,
+                config,
             )
             .await;
             if let Err(err) = send_txs_done_tx.send(res) {
@@ -2179,6 +2457,7 @@ async fn run<P: AsRef<Path>>(
         }
         None => Default::default(),
     };
+    config.validate()?;
     if !online_source {
         let source_chain_access = crate::offline::ChainAccess::new(source_home)?;
         let stop_height = stop_height.unwrap_or(
@@ -2205,3 +2484,80 @@ async fn run<P: AsRef<Path>>(
         .await
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{MirrorConfig, TxFilter};
+    use near_crypto::{KeyType, Signature};
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::transaction::{SignedTransaction, Transaction};
+    use near_primitives::types::AccountId;
+    use std::collections::HashSet;
+
+    fn tx(signer_id: &str, receiver_id: &str) -> SignedTransaction {
+        let tx = Transaction::new_v0(
+            signer_id.parse().unwrap(),
+            crate::key_mapping::default_extra_key(None).public_key(),
+            receiver_id.parse().unwrap(),
+            1,
+            CryptoHash::default(),
+        );
+        SignedTransaction::new(Signature::empty(KeyType::ED25519), tx)
+    }
+
+    fn accounts(account_ids: &[&str]) -> HashSet<AccountId> {
+        account_ids.iter().map(|account_id| account_id.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_tx_filter_allows() {
+        let filter = TxFilter::default();
+        assert!(filter.allows(&tx("alice.near", "bob.near")));
+
+        let filter = TxFilter {
+            include_receivers: Some(accounts(&["bob.near", "carol.near"])),
+            exclude_receivers: accounts(&["carol.near"]),
+            include_signers: None,
+            exclude_signers: accounts(&["mallory.near"]),
+        };
+        assert!(filter.allows(&tx("alice.near", "bob.near")));
+        // Not among the included receivers.
+        assert!(!filter.allows(&tx("alice.near", "dave.near")));
+        // Excluded receivers take precedence over included ones.
+        assert!(!filter.allows(&tx("alice.near", "carol.near")));
+        // Excluded signer.
+        assert!(!filter.allows(&tx("mallory.near", "bob.near")));
+
+        let filter =
+            TxFilter { include_signers: Some(accounts(&["alice.near"])), ..Default::default() };
+        assert!(filter.allows(&tx("alice.near", "bob.near")));
+        assert!(!filter.allows(&tx("bob.near", "alice.near")));
+    }
+
+    #[test]
+    fn test_mirror_config_validate() {
+        assert!(MirrorConfig::default().validate().is_ok());
+
+        let config = MirrorConfig {
+            tx_batch_interval: Some(std::time::Duration::from_secs(1)),
+            time_compression: Some(2.0),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        for time_compression in [0.0, -1.0, f64::NAN] {
+            let config =
+                MirrorConfig { time_compression: Some(time_compression), ..Default::default() };
+            assert!(config.validate().is_err(), "{time_compression}");
+        }
+        let config = MirrorConfig { time_compression: Some(0.5), ..Default::default() };
+        assert!(config.validate().is_ok());
+
+        for max_tps in [0.0, -1.0, f64::NAN] {
+            let config = MirrorConfig { max_tps: Some(max_tps), ..Default::default() };
+            assert!(config.validate().is_err(), "{max_tps}");
+        }
+        let config = MirrorConfig { max_tps: Some(100.0), ..Default::default() };
+        assert!(config.validate().is_ok());
+    }
+}
@@ -1,5 +1,6 @@
 use near_o11y::metrics::{
-    IntCounter, IntCounterVec, try_create_int_counter, try_create_int_counter_vec,
+    Gauge, IntCounter, IntCounterVec, try_create_gauge, try_create_int_counter,
+    try_create_int_counter_vec,
 };
 use std::sync::LazyLock;
 
@@ -19,3 +20,35 @@ pub static TRANSACTIONS_INCLUDED: LazyLock<IntCounter> = LazyLock::new(|| {
     )
     .unwrap()
 });
+
+pub static TRANSACTIONS_FILTERED: LazyLock<IntCounter> = LazyLock::new(|| {
+    try_create_int_counter(
+        "near_mirror_transactions_filtered",
+        "Total number of source chain transactions skipped by the transaction filter",
+    )
+    .unwrap()
+});
+
+pub static TRANSACTIONS_AMPLIFIED: LazyLock<IntCounter> = LazyLock::new(|| {
+    try_create_int_counter(
+        "near_mirror_transactions_amplified",
+        "Total number of extra transactions prepared by duplicating mirrored ones",
+    )
+    .unwrap()
+});
+
+pub static INTENDED_TPS: LazyLock<Gauge> = LazyLock::new(|| {
+    try_create_gauge(
+        "near_mirror_intended_tps",
+        "Transactions per second the last batch was meant to be sent at",
+    )
+    .unwrap()
+});
+
+pub static ACHIEVED_TPS: LazyLock<Gauge> = LazyLock::new(|| {
+    try_create_gauge(
+        "near_mirror_achieved_tps",
+        "Transactions per second the last batch was actually sent at",
+    )
+    .unwrap()
+});
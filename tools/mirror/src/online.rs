@@ -151,7 +151,11 @@ impl crate::ChainAccess for ChainAccess {
             }
         }
 
-        Ok(SourceBlock { hash: block.header.hash, chunks })
+        Ok(SourceBlock {
+            hash: block.header.hash,
+            timestamp: block.header.timestamp_nanosec,
+            chunks,
+        })
     }
 
     async fn get_next_block_height(
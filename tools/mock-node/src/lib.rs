@@ -2,6 +2,8 @@
 //! components of the mock network.
 
 use anyhow::{Context as AnyhowContext, anyhow};
+use near_chain::state_sync::ChainStateSyncAdapter;
+use near_chain::types::RuntimeAdapter;
 use near_chain::{Block, Error, retrieve_headers};
 use near_client::sync::header::MAX_BLOCK_HEADERS;
 use near_crypto::SecretKey;
@@ -10,14 +12,20 @@ use near_network::raw::{
     ConnectError, Connection, DirectMessage, Listener, Message, RoutedMessage,
 };
 use near_network::tcp;
-use near_network::types::{PartialEncodedChunkRequestMsg, PartialEncodedChunkResponseMsg};
+use near_network::types::{
+    PartialEncodedChunkRequestMsg, PartialEncodedChunkResponseMsg, StateResponseInfo,
+    StateResponseInfoV2,
+};
 use near_primitives::hash::CryptoHash;
 use near_primitives::shard_layout::ShardLayout;
 use near_primitives::sharding::ChunkHash;
+use near_primitives::state_sync::{ShardStateSyncResponse, ShardStateSyncResponseHeader};
 use near_primitives::types::{BlockHeight, ShardId};
 use near_primitives::version::ProtocolVersion;
 use near_store::adapter::StoreAdapter;
 use near_store::adapter::chain_store::ChainStoreAdapter;
+use near_time::Clock;
+use scenario::{FaultAction, FaultInjector, MockScenario, corrupt_message};
 
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::future::Future;
@@ -27,6 +35,7 @@ use std::sync::Arc;
 use std::task::Poll;
 use std::time::Duration;
 
+pub mod scenario;
 pub mod setup;
 
 // For now this is a simple struct with one field just to leave the door
@@ -51,6 +60,9 @@ pub struct MockNetworkConfig {
     // How long we'll wait until sending replies to the client
     pub response_delay: Duration,
     pub incoming_requests: Option<MockIncomingRequestsConfig>,
+    #[serde(default)]
+    // Faults to inject and other adversarial behavior
+    pub scenario: MockScenario,
 }
 
 impl MockNetworkConfig {
@@ -62,7 +74,9 @@ impl MockNetworkConfig {
 
     pub fn from_file<P: AsRef<Path>>(path: &P) -> anyhow::Result<Self> {
         let s = std::fs::read_to_string(path)?;
-        Ok(serde_json::from_str(&s)?)
+        let config: Self = serde_json::from_str(&s)?;
+        config.scenario.validate()?;
+        Ok(config)
     }
 }
 
@@ -74,7 +88,11 @@ fn default_delay() -> Duration {
 
 impl Default for MockNetworkConfig {
     fn default() -> Self {
-        Self { response_delay: default_delay(), incoming_requests: None }
+        Self {
+            response_delay: default_delay(),
+            incoming_requests: None,
+            scenario: MockScenario::default(),
+        }
     }
 }
 
@@ -223,11 +241,12 @@ impl IncomingRequests {
 
 struct InFlightMessage {
     message: Message,
-    sent_at: tokio::time::Instant,
+    deliver_at: tokio::time::Instant,
 }
 
-// type that simulates network latency by waiting for `response_delay`
-// before delivering queued up messages
+// type that simulates network latency by waiting for `response_delay` (plus any
+// per-message extra delay) before delivering queued up messages. Messages are kept
+// sorted by delivery time, so different extra delays can reorder them
 #[pin_project::pin_project]
 struct InFlightMessages {
     #[pin]
@@ -245,18 +264,21 @@ impl InFlightMessages {
         }
     }
 
-    fn queue_message(self: Pin<&mut Self>, message: Message) {
+    fn queue_message(self: Pin<&mut Self>, message: Message, extra_delay: Duration) {
         let me = self.project();
-        let now = tokio::time::Instant::now();
-        if me.messages.is_empty() {
-            me.next_delivery.reset(now + *me.response_delay);
+        let deliver_at = tokio::time::Instant::now() + *me.response_delay + extra_delay;
+        // messages with the same delivery time are delivered in the order they were queued
+        let idx = me.messages.partition_point(|m| m.deliver_at <= deliver_at);
+        if idx == 0 {
+            me.next_delivery.reset(deliver_at);
         }
         tracing::debug!(
             %message,
             response_delay = ?me.response_delay,
+            ?extra_delay,
             "mock peer queueing up message to be delivered"
         );
-        me.messages.push_back(InFlightMessage { message, sent_at: now });
+        me.messages.insert(idx, InFlightMessage { message, deliver_at });
     }
 }
 
@@ -274,7 +296,7 @@ impl Future for InFlightMessages {
                     if let Some(m) = me.messages.front() {
                         // if there's another message after the one we're returning here, reset
                         // the time til the next message gets delivered accordingly.
-                        me.next_delivery.as_mut().reset(m.sent_at + *me.response_delay);
+                        me.next_delivery.as_mut().reset(m.deliver_at);
                     }
                     Poll::Ready(msg.message)
                 }
@@ -286,17 +308,27 @@ impl Future for InFlightMessages {
 
 struct MockPeer {
     chain: ChainStoreAdapter,
+    // If the scenario forks the history, the height above which we serve blocks from
+    // this other chain history instead of `chain`
+    fork: Option<(BlockHeight, ChainStoreAdapter)>,
     epoch_manager: Arc<dyn EpochManagerAdapter>,
     current_height: BlockHeight,
     network_config: MockNetworkConfig,
     block_production: tokio::time::Interval,
     incoming_requests: IncomingRequests,
+    faults: FaultInjector,
+    // Serves state headers and parts from `chain`
+    state_sync: ChainStateSyncAdapter,
+    // Number of state sync requests received so far
+    state_requests: u32,
 }
 
 impl MockPeer {
     fn new(
         chain: ChainStoreAdapter,
+        fork_chain: Option<ChainStoreAdapter>,
         epoch_manager: Arc<dyn EpochManagerAdapter>,
+        runtime: Arc<dyn RuntimeAdapter>,
         network_config: MockNetworkConfig,
         block_production_delay: Duration,
         head_block: Arc<Block>,
@@ -304,19 +336,173 @@ impl MockPeer {
         let current_height = head_block.header().height();
         let incoming_requests =
             IncomingRequests::new(&network_config.incoming_requests, &chain, head_block);
+        let fork = network_config
+            .scenario
+            .fork
+            .as_ref()
+            .zip(fork_chain)
+            .map(|(fork, fork_chain)| (fork.height, fork_chain));
+        let faults = FaultInjector::new(&network_config.scenario);
+        let state_sync = ChainStateSyncAdapter::new(
+            Clock::real(),
+            chain.clone(),
+            epoch_manager.clone(),
+            runtime,
+        );
         Self {
             chain,
+            fork,
             epoch_manager,
             current_height,
             network_config,
             block_production: tokio::time::interval(block_production_delay),
             incoming_requests,
+            faults,
+            state_sync,
+            state_requests: 0,
+        }
+    }
+
+    // The chain we serve headers from. The forked history shares blocks with ours up to the
+    // fork height, so if there is one, it can serve headers on both sides of the fork.
+    fn headers_chain(&self) -> &ChainStoreAdapter {
+        self.fork.as_ref().map_or(&self.chain, |(_, fork_chain)| fork_chain)
+    }
+
+    fn get_block(&self, hash: &CryptoHash) -> Result<Arc<Block>, Error> {
+        if let Some((fork_height, fork_chain)) = &self.fork {
+            if let Ok(block) = fork_chain.get_block(hash) {
+                if block.header().height() > *fork_height {
+                    return Ok(block);
+                }
+            }
+        }
+        self.chain.get_block(hash)
+    }
+
+    fn get_partial_encoded_chunk(
+        &self,
+        request: &PartialEncodedChunkRequestMsg,
+    ) -> Result<PartialEncodedChunkResponseMsg, Error> {
+        retrieve_partial_encoded_chunk(&self.chain, self.epoch_manager.as_ref(), request).or_else(
+            |e| match &self.fork {
+                Some((_, fork_chain)) => {
+                    retrieve_partial_encoded_chunk(fork_chain, self.epoch_manager.as_ref(), request)
+                }
+                None => Err(e),
+            },
+        )
+    }
+
+    // Queue up a message to be sent to the peer, after applying whatever faults
+    // the scenario says should be injected into it
+    fn queue_outgoing(&mut self, outbound: Pin<&mut InFlightMessages>, message: Message) {
+        match self.faults.decide(&message) {
+            FaultAction::Drop => {
+                tracing::debug!(%message, "mock peer dropping message");
+            }
+            FaultAction::Deliver { extra_delay, corrupt } => {
+                let message = if corrupt {
+                    tracing::debug!(%message, "mock peer corrupting message");
+                    corrupt_message(message, |hash| self.get_block(hash).ok())
+                } else {
+                    message
+                };
+                outbound.queue_message(message, extra_delay);
+            }
+        }
+    }
+
+    // The scenario might tell us to disconnect once the peer has sent us enough state
+    // sync requests. Returns whether we should continue
+    fn state_request_received(&mut self) -> bool {
+        self.state_requests += 1;
+        match self.network_config.scenario.disconnect_after_state_requests {
+            Some(max) if self.state_requests >= max => {
+                tracing::info!(
+                    state_requests = self.state_requests,
+                    "disconnecting in the middle of state sync"
+                );
+                false
+            }
+            _ => true,
+        }
+    }
+
+    // Like the StateRequestActor of a real node, respond with an empty header or part if
+    // we can't produce it, and don't respond at all if we don't know the sync block.
+    // `part_id` is None for header requests
+    fn get_state_response(
+        &mut self,
+        shard_id: ShardId,
+        sync_hash: CryptoHash,
+        part_id: Option<u64>,
+    ) -> Option<Box<StateResponseInfo>> {
+        let protocol_version = match self
+            .epoch_manager
+            .get_epoch_id(&sync_hash)
+            .and_then(|epoch_id| self.epoch_manager.get_epoch_protocol_version(&epoch_id))
+        {
+            Ok(v) => v,
+            Err(e) => {
+                tracing::debug!(?e, %sync_hash, "can't respond to state request");
+                return None;
+            }
+        };
+        let state_response = match part_id {
+            None => {
+                let header = self
+                    .state_sync
+                    .get_state_response_header(shard_id, sync_hash)
+                    .inspect_err(|e| tracing::debug!(?e, %shard_id, %sync_hash, "no state header"))
+                    .ok()
+                    .and_then(|header| match header {
+                        ShardStateSyncResponseHeader::V2(header) => Some(header),
+                        ShardStateSyncResponseHeader::V1(_) => None,
+                    });
+                ShardStateSyncResponse::new_from_header(header, protocol_version)
+            }
+            Some(part_id) => {
+                let part = self
+                    .state_sync
+                    .get_state_response_part(shard_id, part_id, sync_hash)
+                    .inspect_err(
+                        |e| tracing::debug!(?e, %shard_id, %sync_hash, part_id, "no state part"),
+                    )
+                    .ok()
+                    .map(|part| (part_id, part));
+                ShardStateSyncResponse::new_from_part(part, protocol_version)
+            }
+        };
+        Some(Box::new(StateResponseInfo::V2(Box::new(StateResponseInfoV2 {
+            shard_id,
+            sync_hash,
+            state_response,
+        }))))
+    }
+
+    fn handle_state_request(
+        &mut self,
+        outbound: Pin<&mut InFlightMessages>,
+        shard_id: ShardId,
+        sync_hash: CryptoHash,
+        part_id: Option<u64>,
+    ) -> bool {
+        if !self.state_request_received() {
+            return false;
         }
+        if let Some(response) = self.get_state_response(shard_id, sync_hash, part_id) {
+            self.queue_outgoing(
+                outbound,
+                Message::Direct(DirectMessage::VersionedStateResponse(response)),
+            );
+        }
+        true
     }
 
     // Handle the message and return a bool that tells whether we should continue
     fn handle_message(
-        &self,
+        &mut self,
         conn: &Connection,
         message: std::io::Result<Message>,
         outbound: Pin<&mut InFlightMessages>,
@@ -337,49 +523,69 @@ impl MockPeer {
             Message::Direct(msg) => {
                 match msg {
                     DirectMessage::BlockHeadersRequest(hashes) => {
-                        let headers = retrieve_headers(&self.chain, hashes, MAX_BLOCK_HEADERS)
-                            .with_context(|| {
-                                format!(
-                                    "failed retrieving block headers up to {}",
-                                    self.current_height
-                                )
-                            })?;
-                        outbound
-                            .queue_message(Message::Direct(DirectMessage::BlockHeaders(headers)));
+                        let headers =
+                            retrieve_headers(self.headers_chain(), hashes, MAX_BLOCK_HEADERS)
+                                .with_context(|| {
+                                    format!(
+                                        "failed retrieving block headers up to {}",
+                                        self.current_height
+                                    )
+                                })?;
+                        self.queue_outgoing(
+                            outbound,
+                            Message::Direct(DirectMessage::BlockHeaders(headers)),
+                        );
                     }
                     DirectMessage::BlockRequest(hash) => {
                         let block = self
-                            .chain
                             .get_block(&hash)
                             .with_context(|| format!("failed getting block {}", &hash))?;
-                        outbound.queue_message(Message::Direct(DirectMessage::Block(block)));
+                        self.queue_outgoing(outbound, Message::Direct(DirectMessage::Block(block)));
                     }
-                    _ => {}
-                };
-            }
-            Message::Routed(r) => {
-                match r {
-                    RoutedMessage::PartialEncodedChunkRequest(request) => {
-                        let response = retrieve_partial_encoded_chunk(
-                            &self.chain,
-                            self.epoch_manager.as_ref(),
-                            &request,
-                        )
-                        .with_context(|| {
-                            format!(
-                                "failed getting partial encoded chunk response for {:?}",
-                                &request
-                            )
-                        })?;
-                        outbound.queue_message(Message::Routed(
-                            RoutedMessage::PartialEncodedChunkResponse(response),
+                    DirectMessage::StateRequestHeader(shard_id, sync_hash) => {
+                        return Ok(self.handle_state_request(outbound, shard_id, sync_hash, None));
+                    }
+                    DirectMessage::StateRequestPart(shard_id, sync_hash, part_id) => {
+                        return Ok(self.handle_state_request(
+                            outbound,
+                            shard_id,
+                            sync_hash,
+                            Some(part_id),
                         ));
                     }
-                    // TODO: add state sync requests to possible request types so we can either
-                    // respond or just exit, saying we don't know how to do that
                     _ => {}
-                }
+                };
             }
+            Message::Routed(r) => match r {
+                RoutedMessage::PartialEncodedChunkRequest(request) => {
+                    let response = self.get_partial_encoded_chunk(&request).with_context(|| {
+                        format!("failed getting partial encoded chunk response for {:?}", &request)
+                    })?;
+                    self.queue_outgoing(
+                        outbound,
+                        Message::Routed(RoutedMessage::PartialEncodedChunkResponse(response)),
+                    );
+                }
+                // A real node would send the response over a separate connection to the
+                // address in the request, but we just send it back on this one.
+                RoutedMessage::StateHeaderRequest(request) => {
+                    return Ok(self.handle_state_request(
+                        outbound,
+                        request.shard_id,
+                        request.sync_hash,
+                        None,
+                    ));
+                }
+                RoutedMessage::StatePartRequest(request) => {
+                    return Ok(self.handle_state_request(
+                        outbound,
+                        request.shard_id,
+                        request.sync_hash,
+                        Some(request.part_id),
+                    ));
+                }
+                _ => {}
+            },
         };
         Ok(true)
     }
@@ -389,7 +595,11 @@ impl MockPeer {
     fn produce_block(&mut self) -> anyhow::Result<Option<Arc<Block>>> {
         let height = self.current_height;
         self.current_height += 1;
-        let hash = match self.chain.get_block_hash_by_height(height) {
+        let chain = match &self.fork {
+            Some((fork_height, fork_chain)) if height > *fork_height => fork_chain,
+            _ => &self.chain,
+        };
+        let hash = match chain.get_block_hash_by_height(height) {
             Ok(h) => h,
             Err(Error::DBNotFoundErr(_)) => return Ok(None),
             Err(e) => {
@@ -397,7 +607,7 @@ impl MockPeer {
                     .with_context(|| format!("get_block_hash_by_height #{} failed", height));
             }
         };
-        self.chain.get_block(&hash).with_context(|| format!("get_block {} failed", &hash)).map(Some)
+        chain.get_block(&hash).with_context(|| format!("get_block {} failed", &hash)).map(Some)
     }
 
     // returns a message produced by this mock peer. Right now this includes a new block
@@ -442,7 +652,7 @@ impl MockPeer {
                 }
                 msg = self.incoming_message(target_height) => {
                     let msg = msg?;
-                    messages.as_mut().queue_message(msg);
+                    self.queue_outgoing(messages.as_mut(), msg);
                 }
             }
         }
@@ -454,7 +664,9 @@ impl MockPeer {
 struct MockNode {
     listener: Listener,
     chain: ChainStoreAdapter,
+    fork_chain: Option<ChainStoreAdapter>,
     epoch_manager: Arc<dyn EpochManagerAdapter>,
+    runtime: Arc<dyn RuntimeAdapter>,
     network_start_height: BlockHeight,
     network_config: MockNetworkConfig,
     block_production_delay: Duration,
@@ -463,7 +675,9 @@ struct MockNode {
 impl MockNode {
     fn new(
         chain: ChainStoreAdapter,
+        fork_chain: Option<ChainStoreAdapter>,
         epoch_manager: Arc<dyn EpochManagerAdapter>,
+        runtime: Arc<dyn RuntimeAdapter>,
         genesis_hash: CryptoHash,
         secret_key: SecretKey,
         listen_addr: tcp::ListenerAddr,
@@ -480,7 +694,7 @@ impl MockNode {
             secret_key,
             &chain_id,
             genesis_hash,
-            network_start_height,
+            network_config.scenario.advertised_height.unwrap_or(network_start_height),
             shard_layout.shard_ids().collect(),
             archival,
             None,
@@ -490,7 +704,9 @@ impl MockNode {
         Ok(Self {
             listener,
             chain,
+            fork_chain,
             epoch_manager,
+            runtime,
             network_start_height,
             network_config,
             block_production_delay,
@@ -516,7 +732,9 @@ impl MockNode {
 
             let peer = MockPeer::new(
                 self.chain.clone(),
+                self.fork_chain.clone(),
                 self.epoch_manager.clone(),
+                self.runtime.clone(),
                 self.network_config.clone(),
                 self.block_production_delay,
                 head_block.clone(),
@@ -5,6 +5,7 @@
 
 use anyhow::Context;
 use mock_node::MockNetworkConfig;
+use mock_node::scenario::MockScenario;
 use mock_node::setup::setup_mock_node;
 use near_o11y::testonly::init_integration_logger;
 use near_primitives::types::BlockHeight;
@@ -15,7 +16,7 @@ use std::time::Duration;
 /// Program to start a mock node, which starts a TCP server and accepts incoming
 /// connections from NEAR nodes. Once connected, it will respond to block and chunk
 /// requests, but not do anything else unless periodic outgoing messages are
-/// are specified in $home/mock.json.
+/// are specified in $home/mock.json, or faults are injected with --scenario.
 ///
 #[derive(clap::Parser)]
 struct Cli {
@@ -42,6 +43,11 @@ struct Cli {
     /// If set, advertise that the node is archival in the handshake
     #[clap(long)]
     archival: bool,
+    /// Path to a JSON scenario file telling the mock node to delay, drop, reorder or
+    /// corrupt messages, fork its history, advertise a fake head or disconnect
+    /// during state sync. Overrides any scenario given in $home/mock.json.
+    #[clap(long)]
+    scenario: Option<String>,
 }
 
 fn main() -> anyhow::Result<()> {
@@ -60,6 +66,10 @@ fn main() -> anyhow::Result<()> {
     if let Some(delay) = args.network_delay {
         network_config.response_delay = Duration::from_millis(delay);
     }
+    if let Some(path) = &args.scenario {
+        network_config.scenario = MockScenario::from_file(path)
+            .with_context(|| format!("Error loading scenario from {}", path))?;
+    }
 
     let runtime = tokio::runtime::Runtime::new().unwrap();
     let res = runtime.block_on(async move {
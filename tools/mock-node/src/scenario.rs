@@ -0,0 +1,351 @@
+//! Scenarios that make the mock node behave like an adversarial peer. A scenario can
+//! delay, drop, reorder or corrupt the messages it sends per message type, serve a
+//! forked history above some height, advertise a fake head in the handshake, or
+//! disconnect in the middle of state sync.
+
+use anyhow::Context;
+use near_chain::Block;
+use near_network::raw::{DirectMessage, Message, RoutedMessage};
+use near_network::types::StateResponseInfo;
+use near_primitives::hash::CryptoHash;
+use near_primitives::state::PartialState;
+use near_primitives::state_part::StatePart;
+use near_primitives::state_sync::ShardStateSyncResponse;
+use near_primitives::types::BlockHeight;
+use near_primitives::version::ProtocolFeature;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Names of the outgoing message types faults can be configured for. These are the
+/// variant names of `near_network::raw::DirectMessage` and `RoutedMessage`.
+pub const FAULTABLE_MESSAGES: [&str; 5] = [
+    "Block",
+    "BlockHeaders",
+    "PartialEncodedChunkRequest",
+    "PartialEncodedChunkResponse",
+    "VersionedStateResponse",
+];
+
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct MockScenario {
+    // Seed for the random decisions made when injecting faults, so that a run can be reproduced
+    pub seed: u64,
+    // Faults to inject into outgoing messages, keyed by message type (see FAULTABLE_MESSAGES)
+    pub faults: HashMap<String, MessageFaults>,
+    // If set, blocks, headers and chunks above the fork height are served from another chain history
+    pub fork: Option<ForkConfig>,
+    // If set, this height is advertised as our head in handshakes instead of the real one
+    pub advertised_height: Option<BlockHeight>,
+    // If set, drop the connection after receiving this many state sync requests from the peer
+    pub disconnect_after_state_requests: Option<u32>,
+}
+
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct MessageFaults {
+    // Extra delay on top of the response delay before sending messages of this type
+    pub delay: Duration,
+    // Each message gets an additional random delay up to this value, which reorders messages
+    pub reorder_jitter: Duration,
+    // Probability that a message of this type is not sent at all
+    pub drop_probability: f64,
+    // Probability that a message of this type is corrupted before being sent
+    pub corrupt_probability: f64,
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+pub struct ForkConfig {
+    // Height above which the forked history is served
+    pub height: BlockHeight,
+    // Home dir of the node holding the forked chain history
+    pub home: PathBuf,
+}
+
+impl MockScenario {
+    pub fn from_file<P: AsRef<Path>>(path: &P) -> anyhow::Result<Self> {
+        let s = std::fs::read_to_string(path)?;
+        let scenario: Self = serde_json::from_str(&s)?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (message_type, faults) in &self.faults {
+            anyhow::ensure!(
+                FAULTABLE_MESSAGES.contains(&message_type.as_str()),
+                "unknown message type {:?} in scenario faults, expected one of {:?}",
+                message_type,
+                FAULTABLE_MESSAGES
+            );
+            faults.validate().with_context(|| format!("invalid faults for {}", message_type))?;
+        }
+        Ok(())
+    }
+}
+
+impl MessageFaults {
+    fn validate(&self) -> anyhow::Result<()> {
+        for (name, p) in [
+            ("drop_probability", self.drop_probability),
+            ("corrupt_probability", self.corrupt_probability),
+        ] {
+            anyhow::ensure!((0.0..=1.0).contains(&p), "{} must be in [0, 1], got {}", name, p);
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn message_type(message: &Message) -> &'static str {
+    match message {
+        Message::Direct(msg) => msg.into(),
+        Message::Routed(msg) => msg.into(),
+    }
+}
+
+/// What to do with an outgoing message.
+#[derive(Debug, PartialEq)]
+pub(crate) enum FaultAction {
+    Drop,
+    Deliver { extra_delay: Duration, corrupt: bool },
+}
+
+/// Decides which faults to inject into each outgoing message of a single connection.
+pub(crate) struct FaultInjector {
+    faults: HashMap<String, MessageFaults>,
+    rng: StdRng,
+}
+
+impl FaultInjector {
+    pub(crate) fn new(scenario: &MockScenario) -> Self {
+        Self { faults: scenario.faults.clone(), rng: StdRng::seed_from_u64(scenario.seed) }
+    }
+
+    pub(crate) fn decide(&mut self, message: &Message) -> FaultAction {
+        let Some(faults) = self.faults.get(message_type(message)) else {
+            return FaultAction::Deliver { extra_delay: Duration::ZERO, corrupt: false };
+        };
+        if self.rng.gen_bool(faults.drop_probability) {
+            return FaultAction::Drop;
+        }
+        let corrupt = self.rng.gen_bool(faults.corrupt_probability);
+        let jitter = faults.reorder_jitter.mul_f64(self.rng.gen_range(0.0..1.0));
+        FaultAction::Deliver { extra_delay: faults.delay + jitter, corrupt }
+    }
+}
+
+/// Returns a corrupted version of `message`. Blocks are replaced by their parent
+/// (looked up with `get_block`), a header is removed from the middle of header
+/// responses so they are no longer contiguous, and chunk parts and state parts get
+/// their contents flipped so that they no longer match their merkle proofs or the
+/// state root. State headers are sent unchanged.
+pub(crate) fn corrupt_message(
+    message: Message,
+    get_block: impl FnOnce(&CryptoHash) -> Option<Arc<Block>>,
+) -> Message {
+    match message {
+        Message::Direct(DirectMessage::Block(block)) => {
+            match get_block(block.header().prev_hash()) {
+                Some(prev) => Message::Direct(DirectMessage::Block(prev)),
+                None => Message::Direct(DirectMessage::Block(block)),
+            }
+        }
+        Message::Direct(DirectMessage::BlockHeaders(mut headers)) => {
+            if headers.len() > 2 {
+                headers.remove(headers.len() / 2);
+            } else {
+                headers.reverse();
+            }
+            Message::Direct(DirectMessage::BlockHeaders(headers))
+        }
+        Message::Routed(RoutedMessage::PartialEncodedChunkResponse(mut response)) => {
+            for part in &mut response.parts {
+                for byte in part.part.iter_mut() {
+                    *byte = !*byte;
+                }
+            }
+            Message::Routed(RoutedMessage::PartialEncodedChunkResponse(response))
+        }
+        Message::Direct(DirectMessage::VersionedStateResponse(mut response)) => {
+            if let StateResponseInfo::V2(response) = response.as_mut() {
+                corrupt_state_part(&mut response.state_response);
+            }
+            Message::Direct(DirectMessage::VersionedStateResponse(response))
+        }
+        message => message,
+    }
+}
+
+fn corrupt_state_part(response: &mut ShardStateSyncResponse) {
+    match response {
+        ShardStateSyncResponse::V3(response) => {
+            if let Some((_, part)) = &mut response.part {
+                for byte in part.iter_mut() {
+                    *byte = !*byte;
+                }
+            }
+        }
+        ShardStateSyncResponse::V4(response) => {
+            let Some((_, part)) = &mut response.part else {
+                return;
+            };
+            let Ok(PartialState::TrieValues(values)) = part.to_partial_state() else {
+                return;
+            };
+            let values = values.iter().map(|value| value.iter().map(|b| !b).collect()).collect();
+            // V4 responses are only sent once state parts are compressed
+            *part = StatePart::from_partial_state(
+                PartialState::TrieValues(values),
+                ProtocolFeature::StatePartsCompression.protocol_version(),
+                1,
+            );
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_network::types::{PartialEncodedChunkResponseMsg, StateResponseInfoV2};
+    use near_primitives::sharding::{ChunkHash, PartialEncodedChunkPart};
+    use near_primitives::types::ShardId;
+
+    fn chunk_response() -> Message {
+        Message::Routed(RoutedMessage::PartialEncodedChunkResponse(
+            PartialEncodedChunkResponseMsg {
+                chunk_hash: ChunkHash::default(),
+                parts: vec![PartialEncodedChunkPart {
+                    part_ord: 0,
+                    part: vec![1, 2, 3].into_boxed_slice(),
+                    merkle_proof: vec![],
+                }],
+                receipts: vec![],
+            },
+        ))
+    }
+
+    #[test]
+    fn test_parse_scenario() {
+        let scenario: MockScenario = serde_json::from_str(
+            r#"{
+                "seed": 7,
+                "faults": {
+                    "BlockHeaders": { "drop_probability": 0.5 },
+                    "PartialEncodedChunkResponse": {
+                        "reorder_jitter": { "secs": 1, "nanos": 0 },
+                        "corrupt_probability": 1.0
+                    }
+                },
+                "advertised_height": 1000,
+                "disconnect_after_state_requests": 3
+            }"#,
+        )
+        .unwrap();
+        scenario.validate().unwrap();
+        assert_eq!(scenario.seed, 7);
+        assert_eq!(scenario.faults["BlockHeaders"].drop_probability, 0.5);
+        assert_eq!(scenario.faults["PartialEncodedChunkResponse"].delay, Duration::ZERO);
+        assert_eq!(scenario.advertised_height, Some(1000));
+        assert!(scenario.fork.is_none());
+
+        let mut bad = scenario.clone();
+        bad.faults.insert("Pong".to_string(), MessageFaults::default());
+        assert!(bad.validate().is_err());
+        let mut bad = scenario;
+        bad.faults.get_mut("BlockHeaders").unwrap().drop_probability = 1.5;
+        assert!(bad.validate().is_err());
+    }
+
+    #[test]
+    fn test_fault_injection() {
+        let mut scenario = MockScenario::default();
+        scenario.faults.insert(
+            "PartialEncodedChunkResponse".to_string(),
+            MessageFaults {
+                delay: Duration::from_millis(10),
+                reorder_jitter: Duration::from_millis(5),
+                corrupt_probability: 1.0,
+                ..Default::default()
+            },
+        );
+        scenario.faults.insert(
+            "BlockHeaders".to_string(),
+            MessageFaults { drop_probability: 1.0, ..Default::default() },
+        );
+        let mut injector = FaultInjector::new(&scenario);
+
+        let headers = Message::Direct(DirectMessage::BlockHeaders(vec![]));
+        assert_eq!(injector.decide(&headers), FaultAction::Drop);
+        let request = Message::Direct(DirectMessage::BlockRequest(CryptoHash::default()));
+        assert_eq!(
+            injector.decide(&request),
+            FaultAction::Deliver { extra_delay: Duration::ZERO, corrupt: false }
+        );
+        let FaultAction::Deliver { extra_delay, corrupt } = injector.decide(&chunk_response())
+        else {
+            panic!("chunk response should not be dropped");
+        };
+        assert!(corrupt);
+        assert!(
+            extra_delay >= Duration::from_millis(10) && extra_delay < Duration::from_millis(15)
+        );
+
+        let Message::Routed(RoutedMessage::PartialEncodedChunkResponse(response)) =
+            corrupt_message(chunk_response(), |_| None)
+        else {
+            panic!("corrupting should keep the message type");
+        };
+        assert_eq!(&*response.parts[0].part, &[!1u8, !2, !3]);
+    }
+
+    #[test]
+    fn test_corrupt_state_part() {
+        let mut scenario = MockScenario::default();
+        scenario.faults.insert(
+            "VersionedStateResponse".to_string(),
+            MessageFaults { corrupt_probability: 1.0, ..Default::default() },
+        );
+        scenario.validate().unwrap();
+
+        let protocol_version = ProtocolFeature::StatePartsCompression.protocol_version();
+        let part = StatePart::from_partial_state(
+            PartialState::TrieValues(vec![Arc::from([1u8, 2, 3])]),
+            protocol_version,
+            1,
+        );
+        let response = Message::Direct(DirectMessage::VersionedStateResponse(Box::new(
+            StateResponseInfo::V2(Box::new(StateResponseInfoV2 {
+                shard_id: ShardId::new(0),
+                sync_hash: CryptoHash::default(),
+                state_response: ShardStateSyncResponse::new_from_part(
+                    Some((0, part)),
+                    protocol_version,
+                ),
+            })),
+        )));
+        let mut injector = FaultInjector::new(&scenario);
+        assert_eq!(
+            injector.decide(&response),
+            FaultAction::Deliver { extra_delay: Duration::ZERO, corrupt: true }
+        );
+
+        let Message::Direct(DirectMessage::VersionedStateResponse(response)) =
+            corrupt_message(response, |_| None)
+        else {
+            panic!("corrupting should keep the message type");
+        };
+        let StateResponseInfo::V2(response) = *response else {
+            panic!("corrupting should keep the response version");
+        };
+        let (part_id, part) = response.state_response.take_part().unwrap();
+        assert_eq!(part_id, 0);
+        let PartialState::TrieValues(values) = part.to_partial_state().unwrap();
+        assert_eq!(values, vec![Arc::<[u8]>::from([!1u8, !2, !3])]);
+    }
+}
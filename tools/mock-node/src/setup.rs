@@ -12,6 +12,7 @@ use near_primitives::types::BlockHeight;
 use near_primitives::version::ProtocolVersion;
 use near_store::adapter::StoreAdapter;
 use near_store::adapter::chain_store::ChainStoreAdapter;
+use near_store::{Mode, NodeStorage};
 
 use near_time::Clock;
 
@@ -30,6 +31,7 @@ use std::sync::Arc;
 /// it wont send us any chunk part requests for old chunks.
 pub(crate) fn setup_mock_peer(
     chain: Chain,
+    fork_chain: Option<ChainStoreAdapter>,
     epoch_manager: Arc<dyn EpochManagerAdapter>,
     config: NearConfig,
     network_start_height: Option<BlockHeight>,
@@ -54,7 +56,9 @@ pub(crate) fn setup_mock_peer(
     let mock_peer = tokio::spawn(async move {
         let mock = MockNode::new(
             ChainStoreAdapter::new(chain.chain_store().store()),
+            fork_chain,
             epoch_manager,
+            chain.runtime_adapter.clone(),
             *chain.genesis().hash(),
             secret_key,
             listen_addr,
@@ -76,6 +80,8 @@ pub(crate) fn setup_mock_peer(
 /// Some(), it will first send a block at that height, so that the connecting node sees
 /// that its head is at that height, and it will then send higher heights periodically.
 /// If target_height is Some(), it will not send any blocks or chunks of higher height.
+/// Adversarial behavior such as dropped or corrupted responses is configured with
+/// `network_config.scenario`.
 pub fn setup_mock_node(
     home_dir: &Path,
     network_config: MockNetworkConfig,
@@ -87,7 +93,7 @@ pub fn setup_mock_node(
     let near_config = nearcore::config::load_config(home_dir, GenesisValidationMode::Full)
         .context("Error loading config")?;
 
-    let store = near_store::NodeStorage::opener(
+    let store = NodeStorage::opener(
         home_dir,
         &near_config.config.store,
         near_config.config.cold_store.as_ref(),
@@ -107,6 +113,13 @@ pub fn setup_mock_node(
         NightshadeRuntime::from_config(home_dir, store, &near_config, epoch_manager.clone())
             .context("could not create transaction runtime")?;
 
+    let fork_chain = match &network_config.scenario.fork {
+        Some(fork) => Some(open_fork_chain(&fork.home).with_context(|| {
+            format!("failed opening forked chain history at {}", fork.home.display())
+        })?),
+        None => None,
+    };
+
     let chain_genesis = ChainGenesis::new(&near_config.genesis.config);
     let chain = Chain::new_for_view_client(
         Clock::real(),
@@ -128,6 +141,7 @@ pub fn setup_mock_node(
 
     Ok(setup_mock_peer(
         chain,
+        fork_chain,
         epoch_manager,
         near_config,
         network_start_height,
@@ -138,3 +152,20 @@ pub fn setup_mock_node(
         archival,
     ))
 }
+
+/// Opens the chain history of another node in read-only mode, to serve blocks from
+/// when the scenario forks the history.
+fn open_fork_chain(home_dir: &Path) -> anyhow::Result<ChainStoreAdapter> {
+    let near_config = nearcore::config::load_config(home_dir, GenesisValidationMode::Full)
+        .context("Error loading config")?;
+    let store = NodeStorage::opener(
+        home_dir,
+        &near_config.config.store,
+        near_config.config.cold_store.as_ref(),
+        near_config.config.cloud_storage_config(),
+    )
+    .open_in_mode(Mode::ReadOnly)
+    .context("failed opening storage")?
+    .get_hot_store();
+    Ok(store.chain_store())
+}
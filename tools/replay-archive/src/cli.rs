@@ -1,3 +1,4 @@
+use crate::divergence::{DivergenceBundle, find_divergence};
 use crate::replaydb::{ReplayDB, open_storage_for_replay};
 use anyhow::{Context, Result, anyhow, bail};
 use clap;
@@ -9,7 +10,7 @@ use near_chain::chain::{
 use near_chain::sharding::{get_receipts_shuffle_salt, shuffle_receipt_proofs};
 use near_chain::stateless_validation::chunk_endorsement::validate_chunk_endorsements_in_block;
 use near_chain::stateless_validation::chunk_validation::apply_result_to_chunk_extra;
-use near_chain::types::StorageDataSource;
+use near_chain::types::{ApplyChunkResult, StorageDataSource};
 use near_chain::update_shard::{ShardUpdateReason, ShardUpdateResult, process_shard_update};
 use near_chain::validate::{validate_chunk_proofs, validate_chunk_with_chunk_extra};
 use near_chain::{
@@ -24,18 +25,18 @@ use near_epoch_manager::{EpochManager, EpochManagerHandle};
 use near_primitives::epoch_block_info::BlockInfo;
 use near_primitives::hash::CryptoHash;
 use near_primitives::receipt::Receipt;
+use near_primitives::shard_layout::get_block_shard_uid;
 use near_primitives::sharding::{ReceiptProof, ShardChunk, ShardChunkHeader, ShardProof};
 use near_primitives::types::chunk_extra::ChunkExtra;
 use near_primitives::types::{BlockHeight, Gas, ShardId};
 use near_state_viewer::progress_reporter::ProgressReporter;
-use near_store::{ShardUId, Store, get_genesis_state_roots};
+use near_store::{DBCol, ShardUId, Store, get_genesis_state_roots};
 use nearcore::{NearConfig, NightshadeRuntime, NightshadeRuntimeExt, load_config};
 use node_runtime::SignedValidPeriodTransactions;
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::AtomicU64;
-
 /// This command assumes that it is run from an archival node
 /// and not all the operations data that is available for a
 /// regular validator might not be available in the archival database.
@@ -45,6 +46,14 @@ pub struct ReplayArchiveCommand {
     start_height: Option<BlockHeight>,
     #[clap(long)]
     end_height: Option<BlockHeight>,
+    /// Maximum number of heights replayed together. The chunks of each shard
+    /// are replayed in order, but different shards are replayed in parallel.
+    #[clap(long, default_value_t = 16)]
+    max_heights_in_parallel: usize,
+    /// Directory to dump the inputs reproducing a divergence from the archive.
+    /// Defaults to `replay-archive-divergences` in the home directory.
+    #[clap(long)]
+    divergence_dir: Option<PathBuf>,
 }
 
 impl ReplayArchiveCommand {
@@ -58,12 +67,23 @@ impl ReplayArchiveCommand {
         if near_config.config.cold_store.is_none() {
             bail!("Cold storage is not configured for the archival node.".to_string());
         }
+        if self.max_heights_in_parallel == 0 {
+            bail!("--max-heights-in-parallel must be positive");
+        }
 
-        let mut controller =
-            ReplayController::new(home_dir, near_config, self.start_height, self.end_height)?;
+        let divergence_dir =
+            self.divergence_dir.unwrap_or_else(|| home_dir.join("replay-archive-divergences"));
+        let mut controller = ReplayController::new(
+            home_dir,
+            near_config,
+            self.start_height,
+            self.end_height,
+            self.max_heights_in_parallel,
+            divergence_dir,
+        )?;
 
         // Replay all the blocks until we reach the end block height.
-        while controller.replay_next_block()? {}
+        while controller.replay_next_blocks()? {}
 
         println!(
             "Columns read during replay: {}",
@@ -78,12 +98,15 @@ impl ReplayArchiveCommand {
     }
 }
 
-/// Result of replaying a block. It is used to decide on
-/// the right post-processing steps after replaying the block.
-enum ReplayBlockOutput {
+/// Block prepared for replaying its chunks.
+enum PreparedBlock {
     Genesis(Arc<Block>),
     Missing(BlockHeight),
-    Replayed(Arc<Block>, Gas),
+    ToReplay {
+        block: Arc<Block>,
+        prev_block: Arc<Block>,
+        prev_chunk_headers: Vec<ShardChunkHeader>,
+    },
 }
 
 /// Result of replaying a chunk.
@@ -92,15 +115,24 @@ struct ReplayChunkOutput {
     outgoing_receipts: Vec<Receipt>,
 }
 
+/// Chunk to replay, referring to a block in the batch being replayed.
+struct ChunkTask<'a> {
+    block: &'a Block,
+    prev_block: &'a Block,
+    chunk_header: ShardChunkHeader,
+    prev_chunk_header: &'a ShardChunkHeader,
+}
+
 struct ReplayController {
     storage: Arc<ReplayDB>,
     chain_store: ChainStore,
-    runtime: Arc<NightshadeRuntime>,
     epoch_manager: Arc<EpochManagerHandle>,
+    chunk_replayer: ChunkReplayer,
     progress_reporter: ProgressReporter,
     start_height: BlockHeight,
     next_height: BlockHeight,
     end_height: BlockHeight,
+    max_heights_in_parallel: usize,
 }
 
 impl ReplayController {
@@ -109,16 +141,15 @@ impl ReplayController {
         near_config: NearConfig,
         start_height: Option<BlockHeight>,
         end_height: Option<BlockHeight>,
+        max_heights_in_parallel: usize,
+        divergence_dir: PathBuf,
     ) -> Result<Self> {
         let storage = open_storage_for_replay(home_dir, &near_config)?;
         let store = Store::new(storage.clone());
 
         let genesis_height = near_config.genesis.config.genesis_height;
-        let chain_store = ChainStore::new(
-            store.clone(),
-            false,
-            near_config.genesis.config.transaction_validity_period,
-        );
+        let transaction_validity_period = near_config.genesis.config.transaction_validity_period;
+        let chain_store = ChainStore::new(store.clone(), false, transaction_validity_period);
 
         let head_height = chain_store.head().context("Failed to get head of the chain")?.height;
         let start_height = start_height.unwrap_or(genesis_height);
@@ -130,9 +161,22 @@ impl ReplayController {
             Some(home_dir),
         );
 
-        let runtime =
-            NightshadeRuntime::from_config(home_dir, store, &near_config, epoch_manager.clone())
-                .context("Failed to create runtime")?;
+        let runtime = NightshadeRuntime::from_config(
+            home_dir,
+            store.clone(),
+            &near_config,
+            epoch_manager.clone(),
+        )
+        .context("Failed to create runtime")?;
+
+        let chunk_replayer = ChunkReplayer {
+            store,
+            archive: storage.archive_store(),
+            runtime,
+            epoch_manager: epoch_manager.clone(),
+            transaction_validity_period,
+            divergence_dir,
+        };
 
         let progress_reporter = ProgressReporter {
             cnt: AtomicU64::new(0),
@@ -148,12 +192,13 @@ impl ReplayController {
         Ok(Self {
             storage,
             chain_store,
-            runtime,
             epoch_manager,
+            chunk_replayer,
             progress_reporter,
             start_height,
             next_height: start_height,
             end_height,
+            max_heights_in_parallel,
         })
     }
 
@@ -173,9 +218,9 @@ impl ReplayController {
         Ok(())
     }
 
-    /// Replays the next block if any. Returns true if there are still blocks to replay
+    /// Replays the next batch of blocks if any. Returns true if there are still blocks to replay
     /// and false if it reached end block height.
-    fn replay_next_block(&mut self) -> Result<bool> {
+    fn replay_next_blocks(&mut self) -> Result<bool> {
         if self.next_height > self.end_height {
             bail!("End height is reached");
         }
@@ -183,118 +228,316 @@ impl ReplayController {
             // Initialize the DB columns that are not archival but need to be there for the replay.
             self.init_start_block().context("Failed to initialize store")?;
         }
-        let mut total_gas_burnt: Option<Gas> = None;
-        match self.replay_block(self.next_height)? {
-            ReplayBlockOutput::Genesis(block) => {
-                tracing::debug!(target: "replay-archive", height = %block.header().height(), "skipping genesis block at height");
-            }
-            ReplayBlockOutput::Missing(height) => {
-                tracing::debug!(target: "replay-archive", %height, "skipping missing block at height");
-            }
-            ReplayBlockOutput::Replayed(block, gas_burnt) => {
-                tracing::debug!(target: "replay-archive", height = %block.header().height(), "replayed block at height");
-                total_gas_burnt = Some(gas_burnt);
-            }
+        let blocks = self.prepare_next_blocks()?;
+        let gas_burnt_by_height = self.chunk_replayer.replay_blocks(&blocks)?;
+        for prepared_block in &blocks {
+            let height = match prepared_block {
+                PreparedBlock::Genesis(block) => {
+                    tracing::debug!(target: "replay-archive", height = %block.header().height(), "skipping genesis block at height");
+                    block.header().height()
+                }
+                PreparedBlock::Missing(height) => {
+                    tracing::debug!(target: "replay-archive", %height, "skipping missing block at height");
+                    *height
+                }
+                PreparedBlock::ToReplay { block, .. } => {
+                    tracing::debug!(target: "replay-archive", height = %block.header().height(), "replayed block at height");
+                    block.header().height()
+                }
+            };
+            let gas_burnt = gas_burnt_by_height.get(&height).copied().unwrap_or(Gas::ZERO);
+            self.progress_reporter.inc_and_report_progress(height, gas_burnt);
         }
-        self.progress_reporter
-            .inc_and_report_progress(self.next_height, total_gas_burnt.unwrap_or(Gas::ZERO));
-        self.next_height += 1;
         Ok(self.next_height <= self.end_height)
     }
 
-    fn replay_block(&mut self, height: BlockHeight) -> Result<ReplayBlockOutput> {
-        tracing::info!(target: "replay-archive", height = %self.next_height, "replaying block at height");
-
-        let Ok(block_hash) = self.chain_store.get_block_hash_by_height(height) else {
-            return Ok(ReplayBlockOutput::Missing(height));
-        };
+    /// Prepares the blocks at the next heights for replaying their chunks. Applying a chunk only
+    /// depends on the previous chunk of the same shard, because its incoming receipts are taken
+    /// from the archived chunks, so the chunks of different shards can be replayed in parallel.
+    /// The batch ends at a change of the shard layout, because the child shards depend on the
+    /// parent shard.
+    fn prepare_next_blocks(&mut self) -> Result<Vec<PreparedBlock>> {
+        let mut blocks = vec![];
+        let mut batch_shard_layout = None;
+        while self.next_height <= self.end_height && blocks.len() < self.max_heights_in_parallel {
+            let height = self.next_height;
+            let Ok(block_hash) = self.chain_store.get_block_hash_by_height(height) else {
+                blocks.push(PreparedBlock::Missing(height));
+                self.next_height += 1;
+                continue;
+            };
+            let block = self.chain_store.get_block(&block_hash)?;
+
+            let shard_layout = self.epoch_manager.get_shard_layout(block.header().epoch_id())?;
+            if batch_shard_layout.get_or_insert_with(|| shard_layout.clone()) != &shard_layout {
+                break;
+            }
+            blocks.push(self.prepare_block(block)?);
+            self.next_height += 1;
+        }
+        Ok(blocks)
+    }
 
-        let block = self.chain_store.get_block(&block_hash)?;
+    fn prepare_block(&mut self, block: Arc<Block>) -> Result<PreparedBlock> {
+        tracing::info!(target: "replay-archive", height = %block.header().height(), "preparing block at height");
 
         self.validate_block(&block)?;
 
         self.update_epoch_manager(&block)?;
-        if !block.header().is_genesis() {
-            self.update_incoming_receipts(&block)?;
-        }
-
         if block.header().is_genesis() {
-            return Ok(ReplayBlockOutput::Genesis(block));
+            return Ok(PreparedBlock::Genesis(block));
         }
+        self.update_incoming_receipts(&block)?;
 
-        let prev_block_hash = block.header().prev_hash();
         let prev_block = self
             .chain_store
-            .get_block(prev_block_hash)
+            .get_block(block.header().prev_hash())
             .context("Failed to get previous block to determine gas price")?;
-
         let prev_chunk_headers = self.epoch_manager.get_prev_chunk_headers(&prev_block)?;
+        Ok(PreparedBlock::ToReplay { block, prev_block, prev_chunk_headers })
+    }
+
+    /// Validates a given block. The current set of checks may be extended later.
+    fn validate_block(&self, block: &Block) -> Result<()> {
+        // Chunk endorsements will only exist for a non-genesis block generated with stateless validation.
+        if !block.header().is_genesis() {
+            validate_chunk_endorsements_in_block(self.epoch_manager.as_ref(), block)?;
+        }
+        Ok(())
+    }
+
+    fn update_epoch_manager(&self, block: &Block) -> Result<()> {
+        let last_finalized_height =
+            self.chain_store.get_block_height(block.header().last_final_block())?;
+        let store_update = self.epoch_manager.add_validator_proposals(
+            BlockInfo::from_header(block.header(), last_finalized_height),
+            *block.header().random_value(),
+        )?;
+        let _ = store_update.commit()?;
+        Ok(())
+    }
+
+    pub fn update_incoming_receipts(&mut self, block: &Block) -> Result<()> {
+        let block_hash = block.header().hash();
+        let mut receipt_proofs_by_shard_id: HashMap<ShardId, Vec<ReceiptProof>> = HashMap::new();
+        for chunk_header in block.chunks().iter_new() {
+            let chunk_hash = chunk_header.chunk_hash();
+            let chunk = self
+                .chain_store
+                .get_chunk(&chunk_hash)
+                .context("Failed to get chunk from chunk hash")?;
+            for receipt in make_outgoing_receipts_proofs(
+                chunk_header,
+                chunk.prev_outgoing_receipts().to_vec(),
+                self.epoch_manager.as_ref(),
+            )? {
+                let ReceiptProof(_, ref shard_proof) = receipt;
+                let ShardProof { to_shard_id, .. } = shard_proof;
+                receipt_proofs_by_shard_id
+                    .entry(*to_shard_id)
+                    .or_insert_with(Vec::new)
+                    .push(receipt.clone());
+            }
+        }
+
+        let mut store_update = self.chain_store.store_update();
+        for (shard_id, mut receipts) in receipt_proofs_by_shard_id {
+            shuffle_receipt_proofs(&mut receipts, get_receipts_shuffle_salt(block));
+            store_update.save_incoming_receipt(&block_hash, shard_id, Arc::new(receipts));
+        }
+        store_update.commit().unwrap();
+        Ok(())
+    }
 
-        let chunks = block.chunks();
-        let mut total_gas_burnt = Gas::ZERO;
-        // TODO: Parallelize this loop.
-        for shard_id in 0..chunks.len() {
-            let chunk_header = &chunks[shard_id];
-            let prev_chunk_header = &prev_chunk_headers[shard_id];
-            let epoch_id = block.header().epoch_id();
-            let shard_id: ShardId = shard_id.try_into()?;
-            let shard_uid = shard_id_to_uid(self.epoch_manager.as_ref(), shard_id, epoch_id)
-                .context("Failed to get shard UID from shard id")?;
+    /// Saves the ChunkExtras for the shards in the genesis block.
+    /// Note that there are no chunks in the genesis block, so we directly generate the ChunkExtras
+    /// from the information in the genesis block without applying any transactions or receipts.
+    fn save_genesis_chunk_extras(&mut self, genesis_block: &Block) -> Result<()> {
+        let state_roots = get_genesis_state_roots(&self.chain_store.store())?
+            .ok_or_else(|| anyhow!("genesis state roots do not exist in the db".to_owned()))?;
+        let mut store_update = self.chain_store.store_update();
+        Chain::save_genesis_chunk_extras(
+            genesis_block,
+            &state_roots,
+            self.epoch_manager.as_ref(),
+            &mut store_update,
+        )?;
+        let _ = store_update.commit()?;
+        Ok(())
+    }
+}
+
+/// Replays the chunks of the prepared blocks. It is shared by the threads replaying
+/// different shards, each of them using its own `ChainStore`.
+struct ChunkReplayer {
+    store: Store,
+    /// Archival data, used to compare the replayed chunks with.
+    archive: Store,
+    runtime: Arc<NightshadeRuntime>,
+    epoch_manager: Arc<EpochManagerHandle>,
+    transaction_validity_period: BlockHeight,
+    divergence_dir: PathBuf,
+}
+
+impl ChunkReplayer {
+    /// Replays the chunks of the given blocks, one thread per shard. Returns the gas
+    /// burnt at each height.
+    fn replay_blocks(&self, blocks: &[PreparedBlock]) -> Result<HashMap<BlockHeight, Gas>> {
+        let mut tasks_by_shard: BTreeMap<ShardUId, Vec<ChunkTask>> = BTreeMap::new();
+        for prepared_block in blocks {
+            let PreparedBlock::ToReplay { block, prev_block, prev_chunk_headers } = prepared_block
+            else {
+                continue;
+            };
+            let chunks = block.chunks();
+            for shard_id in 0..chunks.len() {
+                let epoch_id = block.header().epoch_id();
+                let shard_uid =
+                    shard_id_to_uid(self.epoch_manager.as_ref(), shard_id.try_into()?, epoch_id)
+                        .context("Failed to get shard UID from shard id")?;
+                tasks_by_shard.entry(shard_uid).or_default().push(ChunkTask {
+                    block,
+                    prev_block,
+                    chunk_header: chunks[shard_id].clone(),
+                    prev_chunk_header: &prev_chunk_headers[shard_id],
+                });
+            }
+        }
+
+        let results = std::thread::scope(|scope| {
+            let handles = tasks_by_shard
+                .into_iter()
+                .map(|(shard_uid, tasks)| scope.spawn(move || self.replay_shard(shard_uid, tasks)))
+                .collect_vec();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("shard replay thread panicked"))
+                .collect_vec()
+        });
+
+        let mut gas_burnt_by_height: HashMap<BlockHeight, Gas> = HashMap::new();
+        for result in results {
+            for (height, gas_burnt) in result? {
+                let total_gas_burnt = gas_burnt_by_height.entry(height).or_insert(Gas::ZERO);
+                *total_gas_burnt = total_gas_burnt.checked_add(gas_burnt).unwrap();
+            }
+        }
+        Ok(gas_burnt_by_height)
+    }
+
+    /// Replays the chunks of a shard in order. Returns the gas burnt by each chunk.
+    fn replay_shard(
+        &self,
+        shard_uid: ShardUId,
+        tasks: Vec<ChunkTask>,
+    ) -> Result<Vec<(BlockHeight, Gas)>> {
+        let mut chain_store =
+            ChainStore::new(self.store.clone(), false, self.transaction_validity_period);
+        let mut gas_burnt = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            let block_hash = task.block.hash();
             let replay_output = self
-                .replay_chunk(&block, &prev_block, shard_uid, chunk_header, prev_chunk_header)
+                .replay_chunk(&chain_store, shard_uid, &task)
                 .context("Failed to replay the chunk")?;
-            total_gas_burnt =
-                total_gas_burnt.checked_add(replay_output.chunk_extra.gas_used()).unwrap();
+            gas_burnt.push((task.block.header().height(), replay_output.chunk_extra.gas_used()));
 
             // Save chunk extra and outgoing receipts for future reads.
-            let mut store_update = self.chain_store.store_update();
-            store_update.save_chunk_extra(&block_hash, &shard_uid, replay_output.chunk_extra);
+            let mut store_update = chain_store.store_update();
+            store_update.save_chunk_extra(block_hash, &shard_uid, replay_output.chunk_extra);
             store_update.save_outgoing_receipt(
-                &block_hash,
-                shard_id,
+                block_hash,
+                shard_uid.shard_id(),
                 replay_output.outgoing_receipts,
             );
             let _ = store_update.commit()?;
         }
-
-        Ok(ReplayBlockOutput::Replayed(block, total_gas_burnt))
+        Ok(gas_burnt)
     }
 
     fn replay_chunk(
         &self,
-        block: &Block,
-        prev_block: &Block,
+        chain_store: &ChainStore,
         shard_uid: ShardUId,
-        chunk_header: &ShardChunkHeader,
-        prev_chunk_header: &ShardChunkHeader,
+        task: &ChunkTask,
     ) -> Result<ReplayChunkOutput> {
+        let (shard_update_result, prev_chunk_extra) =
+            self.apply_chunk(chain_store, shard_uid, task, true)?;
+
+        let output = match shard_update_result {
+            ShardUpdateResult::NewChunk(NewChunkResult {
+                gas_limit: _,
+                shard_uid: _,
+                apply_result,
+            }) => {
+                let outgoing_receipts = apply_result.outgoing_receipts.clone();
+                let chunk_extra =
+                    apply_result_to_chunk_extra(apply_result, task.chunk_header.gas_limit()).into();
+                ReplayChunkOutput { chunk_extra, outgoing_receipts }
+            }
+            ShardUpdateResult::OldChunk(OldChunkResult { shard_uid: _, apply_result }) => {
+                let mut chunk_extra = ChunkExtra::clone(&prev_chunk_extra.as_ref());
+                *chunk_extra.state_root_mut() = apply_result.new_root;
+                let outgoing_receipts = apply_result.outgoing_receipts;
+                ReplayChunkOutput { chunk_extra: chunk_extra.into(), outgoing_receipts }
+            }
+        };
+
+        let archived_chunk_extra: Option<ChunkExtra> = self
+            .archive
+            .get_ser(DBCol::ChunkExtra, &get_block_shard_uid(task.block.hash(), &shard_uid))?;
+        if let Some(archived_chunk_extra) = archived_chunk_extra {
+            if archived_chunk_extra != *output.chunk_extra {
+                return Err(self.report_divergence(
+                    chain_store,
+                    shard_uid,
+                    task,
+                    archived_chunk_extra,
+                    ChunkExtra::clone(&output.chunk_extra),
+                ));
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Applies the chunk, returning the result together with the chunk extra of the
+    /// previous chunk of the shard.
+    fn apply_chunk(
+        &self,
+        chain_store: &ChainStore,
+        shard_uid: ShardUId,
+        task: &ChunkTask,
+        validate: bool,
+    ) -> Result<(ShardUpdateResult, Arc<ChunkExtra>)> {
         let span = tracing::debug_span!(target: "replay-archive", "replay_chunk").entered();
+        let ChunkTask { block, prev_block, chunk_header, prev_chunk_header } = task;
 
         // Collect receipts and transactions.
         let chunk_hash = chunk_header.chunk_hash();
-        let chunk = self
-            .chain_store
-            .get_chunk(&chunk_hash)
-            .context("Failed to get chunk from chunk hash")?;
+        let chunk =
+            chain_store.get_chunk(&chunk_hash).context("Failed to get chunk from chunk hash")?;
 
         let block_header = block.header();
 
         let prev_block_header = prev_block.header();
         let prev_block_hash = prev_block_header.hash();
 
-        let prev_chunk_extra = self.chain_store.get_chunk_extra(prev_block_hash, &shard_uid)?;
+        let prev_chunk_extra = chain_store.get_chunk_extra(prev_block_hash, &shard_uid)?;
 
         let height = block_header.height();
         let is_new_chunk: bool = chunk_header.is_new_chunk(height);
 
-        self.validate_chunk(
-            is_new_chunk,
-            &chunk,
-            chunk_header,
-            prev_block_hash,
-            prev_chunk_header,
-            prev_chunk_extra.as_ref(),
-        )?;
+        if validate {
+            self.validate_chunk(
+                chain_store,
+                is_new_chunk,
+                &chunk,
+                chunk_header,
+                prev_block_hash,
+                prev_chunk_header,
+                prev_chunk_extra.as_ref(),
+            )?;
+        }
 
         let shard_id = shard_uid.shard_id();
         let shard_context = self.get_shard_context(shard_uid)?;
@@ -309,6 +552,7 @@ impl ReplayController {
 
         let update_reason = if is_new_chunk {
             let receipts = self.collect_incoming_receipts(
+                chain_store,
                 block_header,
                 shard_id,
                 prev_chunk_header.height_included(),
@@ -339,32 +583,95 @@ impl ReplayController {
 
         let shard_update_result =
             process_shard_update(&span, self.runtime.as_ref(), update_reason, shard_context, None)?;
+        Ok((shard_update_result, prev_chunk_extra))
+    }
 
-        let output = match shard_update_result {
-            ShardUpdateResult::NewChunk(NewChunkResult {
-                gas_limit: _,
-                shard_uid: _,
-                apply_result,
-            }) => {
-                let outgoing_receipts = apply_result.outgoing_receipts.clone();
-                let chunk_extra =
-                    apply_result_to_chunk_extra(apply_result, chunk_header.gas_limit()).into();
-                ReplayChunkOutput { chunk_extra, outgoing_receipts }
-            }
-            ShardUpdateResult::OldChunk(OldChunkResult { shard_uid: _, apply_result }) => {
-                let mut chunk_extra = ChunkExtra::clone(&prev_chunk_extra.as_ref());
-                *chunk_extra.state_root_mut() = apply_result.new_root;
-                let outgoing_receipts = apply_result.outgoing_receipts;
-                ReplayChunkOutput { chunk_extra: chunk_extra.into(), outgoing_receipts }
-            }
+    /// Narrows down the divergence of a replayed chunk from the archive and dumps the
+    /// inputs reproducing it. Returns the error to stop the replay with.
+    fn report_divergence(
+        &self,
+        chain_store: &ChainStore,
+        shard_uid: ShardUId,
+        task: &ChunkTask,
+        expected_chunk_extra: ChunkExtra,
+        replayed_chunk_extra: ChunkExtra,
+    ) -> anyhow::Error {
+        let height = task.block.header().height();
+        tracing::error!(target: "replay-archive", %height, %shard_uid, ?expected_chunk_extra, ?replayed_chunk_extra, "replayed chunk extra differs from the archive");
+        match self.dump_divergence(
+            chain_store,
+            shard_uid,
+            task,
+            expected_chunk_extra,
+            replayed_chunk_extra,
+        ) {
+            Ok(path) => anyhow!(
+                "Chunk of shard {shard_uid} at height {height} diverged from the archive, inputs reproducing it are dumped to {}",
+                path.display()
+            ),
+            Err(err) => anyhow!(
+                "Chunk of shard {shard_uid} at height {height} diverged from the archive, failed to narrow it down: {err:#}"
+            ),
+        }
+    }
+
+    fn dump_divergence(
+        &self,
+        chain_store: &ChainStore,
+        shard_uid: ShardUId,
+        task: &ChunkTask,
+        expected_chunk_extra: ChunkExtra,
+        replayed_chunk_extra: ChunkExtra,
+    ) -> Result<PathBuf> {
+        // The replay is deterministic, so applying the chunk again gives the same result.
+        let apply_result: ApplyChunkResult =
+            match self.apply_chunk(chain_store, shard_uid, task, false)?.0 {
+                ShardUpdateResult::NewChunk(NewChunkResult { apply_result, .. })
+                | ShardUpdateResult::OldChunk(OldChunkResult { apply_result, .. }) => apply_result,
+            };
+
+        let block_header = task.block.header();
+        let (transactions, receipts) = if task.chunk_header.is_new_chunk(block_header.height()) {
+            let chunk = chain_store.get_chunk(&task.chunk_header.chunk_hash())?;
+            let receipts = self.collect_incoming_receipts(
+                chain_store,
+                block_header,
+                shard_uid.shard_id(),
+                task.prev_chunk_header.height_included(),
+            )?;
+            (chunk.to_transactions().to_vec(), receipts)
+        } else {
+            (vec![], vec![])
         };
 
-        Ok(output)
+        let shard_layout = self.epoch_manager.get_shard_layout(block_header.epoch_id())?;
+        let divergence = find_divergence(
+            &self.archive,
+            block_header.hash(),
+            shard_uid,
+            &shard_layout,
+            &transactions,
+            &apply_result,
+        )?;
+        let bundle = DivergenceBundle::new(
+            block_header.height(),
+            *block_header.hash(),
+            shard_uid,
+            task.chunk_header.chunk_hash().clone(),
+            task.chunk_header.prev_state_root(),
+            expected_chunk_extra,
+            replayed_chunk_extra,
+            divergence,
+            transactions,
+            receipts,
+        );
+        bundle.write(&self.divergence_dir)
     }
 
     /// Returns the incoming receipts to the given shard.
     fn collect_incoming_receipts(
         &self,
+        chain_store: &ChainStore,
         block_header: &BlockHeader,
         shard_id: ShardId,
         prev_chunk_height_included: BlockHeight,
@@ -372,7 +679,7 @@ impl ReplayController {
         let shard_layout =
             self.epoch_manager.get_shard_layout_from_prev_block(block_header.prev_hash())?;
         let receipt_response = get_incoming_receipts_for_shard(
-            &self.chain_store,
+            chain_store,
             self.epoch_manager.as_ref(),
             shard_id,
             &shard_layout,
@@ -384,18 +691,10 @@ impl ReplayController {
         Ok(receipts)
     }
 
-    /// Validates a given block. The current set of checks may be extended later.
-    fn validate_block(&self, block: &Block) -> Result<()> {
-        // Chunk endorsements will only exist for a non-genesis block generated with stateless validation.
-        if !block.header().is_genesis() {
-            validate_chunk_endorsements_in_block(self.epoch_manager.as_ref(), block)?;
-        }
-        Ok(())
-    }
-
     /// Validates a given chunk. The current set of checks may be extended later.
     fn validate_chunk(
         &self,
+        chain_store: &ChainStore,
         is_new_chunk: bool,
         chunk: &ShardChunk,
         chunk_header: &ShardChunkHeader,
@@ -406,7 +705,7 @@ impl ReplayController {
         // Check if the information in the ChunkExtra recorded after applying the previous chunk matches the information in the new chunk.
         if is_new_chunk {
             validate_chunk_with_chunk_extra(
-                &self.chain_store,
+                chain_store,
                 self.epoch_manager.as_ref(),
                 prev_block_hash,
                 prev_chunk_extra,
@@ -425,70 +724,10 @@ impl ReplayController {
         Ok(())
     }
 
-    fn update_epoch_manager(&self, block: &Block) -> Result<()> {
-        let last_finalized_height =
-            self.chain_store.get_block_height(block.header().last_final_block())?;
-        let store_update = self.epoch_manager.add_validator_proposals(
-            BlockInfo::from_header(block.header(), last_finalized_height),
-            *block.header().random_value(),
-        )?;
-        let _ = store_update.commit()?;
-        Ok(())
-    }
-
-    pub fn update_incoming_receipts(&mut self, block: &Block) -> Result<()> {
-        let block_hash = block.header().hash();
-        let mut receipt_proofs_by_shard_id: HashMap<ShardId, Vec<ReceiptProof>> = HashMap::new();
-        for chunk_header in block.chunks().iter_new() {
-            let chunk_hash = chunk_header.chunk_hash();
-            let chunk = self
-                .chain_store
-                .get_chunk(&chunk_hash)
-                .context("Failed to get chunk from chunk hash")?;
-            for receipt in make_outgoing_receipts_proofs(
-                chunk_header,
-                chunk.prev_outgoing_receipts().to_vec(),
-                self.epoch_manager.as_ref(),
-            )? {
-                let ReceiptProof(_, ref shard_proof) = receipt;
-                let ShardProof { to_shard_id, .. } = shard_proof;
-                receipt_proofs_by_shard_id
-                    .entry(*to_shard_id)
-                    .or_insert_with(Vec::new)
-                    .push(receipt.clone());
-            }
-        }
-
-        let mut store_update = self.chain_store.store_update();
-        for (shard_id, mut receipts) in receipt_proofs_by_shard_id {
-            shuffle_receipt_proofs(&mut receipts, get_receipts_shuffle_salt(block));
-            store_update.save_incoming_receipt(&block_hash, shard_id, Arc::new(receipts));
-        }
-        store_update.commit().unwrap();
-        Ok(())
-    }
-
     /// Generates a ShardContext specific to replaying the blocks, which indicates that
     /// we care about all the shards and should always apply chunk.
     fn get_shard_context(&self, shard_uid: ShardUId) -> Result<ShardContext> {
         let shard_context = ShardContext { shard_uid, should_apply_chunk: true };
         Ok(shard_context)
     }
-
-    /// Saves the ChunkExtras for the shards in the genesis block.
-    /// Note that there are no chunks in the genesis block, so we directly generate the ChunkExtras
-    /// from the information in the genesis block without applying any transactions or receipts.
-    fn save_genesis_chunk_extras(&mut self, genesis_block: &Block) -> Result<()> {
-        let state_roots = get_genesis_state_roots(&self.chain_store.store())?
-            .ok_or_else(|| anyhow!("genesis state roots do not exist in the db".to_owned()))?;
-        let mut store_update = self.chain_store.store_update();
-        Chain::save_genesis_chunk_extras(
-            genesis_block,
-            &state_roots,
-            self.epoch_manager.as_ref(),
-            &mut store_update,
-        )?;
-        let _ = store_update.commit()?;
-        Ok(())
-    }
 }
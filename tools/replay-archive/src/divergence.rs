@@ -0,0 +1,396 @@
+//! Narrowing down the difference between a replayed chunk and the archive.
+//!
+//! When the `ChunkExtra` produced by the replay differs from the archived one,
+//! the execution outcomes and state changes of the chunk are compared with the
+//! archived ones to find the first transaction or receipt whose processing
+//! diverged and the state key it wrote differently. The result is dumped
+//! together with the inputs of the chunk, so that the divergence can be
+//! reproduced with `neard view-state apply-receipt` or `apply-tx`.
+
+use anyhow::{Context, Result};
+use itertools::{EitherOrBoth, Itertools};
+use near_chain::types::ApplyChunkResult;
+use near_primitives::hash::CryptoHash;
+use near_primitives::receipt::Receipt;
+use near_primitives::shard_layout::ShardLayout;
+use near_primitives::sharding::ChunkHash;
+use near_primitives::transaction::{ExecutionOutcomeWithId, SignedTransaction};
+use near_primitives::trie_key::TrieKey;
+use near_primitives::types::chunk_extra::ChunkExtra;
+use near_primitives::types::{
+    BlockHeight, RawStateChange, RawStateChangesWithTrieKey, StateChangeCause, StateRoot,
+};
+use near_primitives::views::{
+    ExecutionOutcomeView, ReceiptView, SignedTransactionView, StateChangeCauseView,
+};
+use near_primitives_core::serialize::to_base64;
+use near_store::adapter::StoreAdapter;
+use near_store::{KeyForStateChanges, ShardUId, Store};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum DivergentItemKind {
+    Transaction,
+    Receipt,
+}
+
+/// The first point at which the replayed chunk differs from the archive.
+#[derive(Serialize)]
+pub(crate) struct DivergencePoint {
+    /// Transaction or receipt whose processing diverged first. It is unknown if
+    /// the outcomes match and the differing state changes have no such cause.
+    pub id: Option<CryptoHash>,
+    pub kind: Option<DivergentItemKind>,
+    /// Position of the first differing outcome among the outcomes of the chunk.
+    pub outcome_index: Option<usize>,
+    pub expected_outcome: Option<ExecutionOutcomeView>,
+    pub replayed_outcome: Option<ExecutionOutcomeView>,
+    /// State key written differently by the divergent transaction or receipt.
+    pub state_key: Option<StateKeyDivergence>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct StateKeyDivergence {
+    pub trie_key: String,
+    pub trie_key_base64: String,
+    pub expected_changes: Vec<StateChangeEntry>,
+    pub replayed_changes: Vec<StateChangeEntry>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct StateChangeEntry {
+    pub cause: StateChangeCauseView,
+    pub data_base64: Option<String>,
+}
+
+impl From<&RawStateChange> for StateChangeEntry {
+    fn from(change: &RawStateChange) -> Self {
+        Self {
+            cause: change.cause.clone().into(),
+            data_base64: change.data.as_deref().map(to_base64),
+        }
+    }
+}
+
+/// Everything needed to reproduce a divergence of a replayed chunk.
+#[derive(Serialize)]
+pub(crate) struct DivergenceBundle {
+    pub height: BlockHeight,
+    pub block_hash: CryptoHash,
+    pub shard_uid: String,
+    pub chunk_hash: ChunkHash,
+    pub prev_state_root: StateRoot,
+    pub expected_chunk_extra: ChunkExtra,
+    pub replayed_chunk_extra: ChunkExtra,
+    pub divergence: DivergencePoint,
+    /// Transactions of the chunk, in the order they were applied.
+    pub transactions: Vec<SignedTransactionView>,
+    /// Incoming receipts of the chunk, in the order they were applied.
+    pub receipts: Vec<ReceiptView>,
+    /// Commands reproducing the divergence against the archival database.
+    pub commands: Vec<String>,
+}
+
+impl DivergenceBundle {
+    pub fn new(
+        height: BlockHeight,
+        block_hash: CryptoHash,
+        shard_uid: ShardUId,
+        chunk_hash: ChunkHash,
+        prev_state_root: StateRoot,
+        expected_chunk_extra: ChunkExtra,
+        replayed_chunk_extra: ChunkExtra,
+        divergence: DivergencePoint,
+        transactions: Vec<SignedTransaction>,
+        receipts: Vec<Receipt>,
+    ) -> Self {
+        let mut commands = vec![];
+        match (divergence.kind, divergence.id) {
+            (Some(DivergentItemKind::Transaction), Some(id)) => {
+                commands.push(format!("neard view-state apply-tx --hash {id}"))
+            }
+            (Some(DivergentItemKind::Receipt), Some(id)) => {
+                commands.push(format!("neard view-state apply-receipt --hash {id}"))
+            }
+            _ => {}
+        }
+        commands.push(format!("neard view-state apply-chunk --chunk-hash {}", chunk_hash.0));
+        Self {
+            height,
+            block_hash,
+            shard_uid: shard_uid.to_string(),
+            chunk_hash,
+            prev_state_root,
+            expected_chunk_extra,
+            replayed_chunk_extra,
+            divergence,
+            transactions: transactions.into_iter().map(Into::into).collect(),
+            receipts: receipts.into_iter().map(Into::into).collect(),
+            commands,
+        }
+    }
+
+    /// Writes the bundle as JSON into the given directory and returns its path.
+    pub fn write(&self, dir: &Path) -> Result<PathBuf> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join(format!("{}-{}.json", self.height, self.shard_uid));
+        let file = std::fs::File::create(&path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(path)
+    }
+}
+
+/// Compares the result of applying a chunk with the outcomes and state changes
+/// recorded in the archive for the same chunk.
+pub(crate) fn find_divergence(
+    archive: &Store,
+    block_hash: &CryptoHash,
+    shard_uid: ShardUId,
+    shard_layout: &ShardLayout,
+    transactions: &[SignedTransaction],
+    apply_result: &ApplyChunkResult,
+) -> Result<DivergencePoint> {
+    let chain_store = archive.chain_store();
+    let expected_ids =
+        chain_store.get_outcomes_by_block_hash_and_shard_id(block_hash, shard_uid.shard_id())?;
+    let mut expected_outcomes = Vec::with_capacity(expected_ids.len());
+    for id in expected_ids {
+        let outcome = chain_store
+            .get_outcome_by_id_and_block_hash(&id, block_hash)?
+            .map(|outcome| ExecutionOutcomeWithId { id, outcome: outcome.outcome });
+        expected_outcomes.push(outcome);
+    }
+
+    // Outcomes are stored in the order of execution, so the first differing one
+    // belongs to the first transaction or receipt whose processing diverged.
+    let num_outcomes = expected_outcomes.len().max(apply_result.outcomes.len());
+    let first_outcome = (0..num_outcomes).find_map(|index| {
+        let expected = expected_outcomes.get(index).and_then(Option::as_ref);
+        let replayed = apply_result.outcomes.get(index);
+        (expected != replayed).then(|| (index, expected.cloned(), replayed.cloned()))
+    });
+
+    let execution_order = |id: &CryptoHash| {
+        apply_result.outcomes.iter().position(|outcome| &outcome.id == id).unwrap_or(usize::MAX)
+    };
+    let expected_changes = expected_state_changes(archive, shard_layout, block_hash, shard_uid)?;
+    let replayed_changes = apply_result
+        .trie_changes
+        .state_changes()
+        .iter()
+        .map(|replayed| {
+            let row_key = match replayed.trie_key.get_account_id() {
+                None => KeyForStateChanges::delayed_receipt_key_from_trie_key(
+                    block_hash,
+                    &replayed.trie_key,
+                    &shard_uid,
+                ),
+                _ => KeyForStateChanges::from_trie_key(block_hash, &replayed.trie_key),
+            };
+            (row_key.into(), replayed.clone())
+        })
+        .collect();
+    let mut state_keys = diff_state_changes(expected_changes, replayed_changes);
+
+    let id = first_outcome
+        .as_ref()
+        .and_then(|(_, expected, replayed)| replayed.as_ref().or(expected.as_ref()))
+        .map(|outcome| outcome.id);
+    // Prefer the state key written differently by the divergent transaction or
+    // receipt, otherwise the one written by the earliest executed one.
+    let state_key = id
+        .and_then(|id| state_keys.iter().position(|diff| diff.cause_id == Some(id)))
+        .or_else(|| {
+            state_keys
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, diff)| diff.cause_id.as_ref().map_or(usize::MAX, execution_order))
+                .map(|(index, _)| index)
+        })
+        .map(|index| state_keys.swap_remove(index));
+    let id = id.or_else(|| state_key.as_ref().and_then(|diff| diff.cause_id));
+
+    let kind = id.map(|id| {
+        if transactions.iter().any(|transaction| transaction.get_hash() == id) {
+            DivergentItemKind::Transaction
+        } else {
+            DivergentItemKind::Receipt
+        }
+    });
+    let (outcome_index, expected_outcome, replayed_outcome) = match first_outcome {
+        Some((index, expected, replayed)) => (
+            Some(index),
+            expected.map(|outcome| outcome.outcome.into()),
+            replayed.map(|outcome| outcome.outcome.into()),
+        ),
+        None => (None, None, None),
+    };
+    let state_key = state_key.map(|diff| StateKeyDivergence {
+        trie_key: format!("{:?}", diff.trie_key),
+        trie_key_base64: to_base64(&diff.trie_key.to_vec()),
+        expected_changes: diff.expected.iter().map(Into::into).collect(),
+        replayed_changes: diff.replayed.iter().map(Into::into).collect(),
+    });
+    Ok(DivergencePoint { id, kind, outcome_index, expected_outcome, replayed_outcome, state_key })
+}
+
+/// A state key written differently by the replay and the archive.
+struct StateKeyDiff {
+    /// Transaction or receipt that caused the first differing change, if any.
+    cause_id: Option<CryptoHash>,
+    trie_key: TrieKey,
+    expected: Vec<RawStateChange>,
+    replayed: Vec<RawStateChange>,
+}
+
+/// Reads the state changes of the given shard recorded in the archive for a block, keyed by
+/// their row key in `DBCol::StateChanges`.
+fn expected_state_changes(
+    archive: &Store,
+    shard_layout: &ShardLayout,
+    block_hash: &CryptoHash,
+    shard_uid: ShardUId,
+) -> Result<BTreeMap<Vec<u8>, RawStateChangesWithTrieKey>> {
+    let mut state_changes = BTreeMap::new();
+    for item in KeyForStateChanges::for_block(block_hash).find_rows_iter(archive) {
+        let (row_key, changes) = item?;
+        let changes_shard_uid = match changes.trie_key.get_account_id() {
+            Some(account_id) => shard_layout.account_id_to_shard_uid(&account_id),
+            None => KeyForStateChanges::delayed_receipt_key_decode_shard_uid(
+                &row_key,
+                block_hash,
+                &changes.trie_key,
+            )?,
+        };
+        if changes_shard_uid == shard_uid {
+            state_changes.insert(row_key.into_vec(), changes);
+        }
+    }
+    Ok(state_changes)
+}
+
+/// Compares the state changes of the archive and the replay over the row keys of both, so
+/// that a key written by only one of them is reported as well.
+fn diff_state_changes(
+    expected: BTreeMap<Vec<u8>, RawStateChangesWithTrieKey>,
+    replayed: BTreeMap<Vec<u8>, RawStateChangesWithTrieKey>,
+) -> Vec<StateKeyDiff> {
+    expected
+        .into_iter()
+        .merge_join_by(replayed, |(expected_key, _), (replayed_key, _)| {
+            expected_key.cmp(replayed_key)
+        })
+        .filter_map(|item| {
+            let (expected, replayed) = match item {
+                EitherOrBoth::Both((_, expected), (_, replayed)) => {
+                    (Some(expected), Some(replayed))
+                }
+                EitherOrBoth::Left((_, expected)) => (Some(expected), None),
+                EitherOrBoth::Right((_, replayed)) => (None, Some(replayed)),
+            };
+            let trie_key = replayed.as_ref().or(expected.as_ref())?.trie_key.clone();
+            let expected = expected.map_or_else(Vec::new, |expected| expected.changes);
+            let replayed = replayed.map_or_else(Vec::new, |replayed| replayed.changes);
+            let id = cause_id(first_differing_cause(&expected, &replayed)?);
+            Some(StateKeyDiff { cause_id: id, trie_key, expected, replayed })
+        })
+        .collect()
+}
+
+/// Returns the cause of the first change that differs between the two lists.
+fn first_differing_cause<'a>(
+    expected: &'a [RawStateChange],
+    replayed: &'a [RawStateChange],
+) -> Option<&'a StateChangeCause> {
+    let len = expected.len().max(replayed.len());
+    (0..len).find_map(|index| match (expected.get(index), replayed.get(index)) {
+        (Some(expected), Some(replayed))
+            if expected.cause == replayed.cause && expected.data == replayed.data =>
+        {
+            None
+        }
+        (_, Some(replayed)) => Some(&replayed.cause),
+        (Some(expected), None) => Some(&expected.cause),
+        (None, None) => unreachable!(),
+    })
+}
+
+/// Returns the hash of the transaction or receipt that caused a state change.
+fn cause_id(cause: &StateChangeCause) -> Option<CryptoHash> {
+    match cause {
+        StateChangeCause::TransactionProcessing { tx_hash } => Some(*tx_hash),
+        StateChangeCause::ActionReceiptProcessingStarted { receipt_hash }
+        | StateChangeCause::ActionReceiptGasReward { receipt_hash }
+        | StateChangeCause::ReceiptProcessing { receipt_hash }
+        | StateChangeCause::PostponedReceipt { receipt_hash } => Some(*receipt_hash),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account_key(account: &str) -> TrieKey {
+        TrieKey::Account { account_id: account.parse().unwrap() }
+    }
+
+    fn changes(
+        trie_key: TrieKey,
+        tx_hash: CryptoHash,
+        data: &[u8],
+    ) -> (Vec<u8>, RawStateChangesWithTrieKey) {
+        let row_key = KeyForStateChanges::from_trie_key(&CryptoHash::default(), &trie_key);
+        let changes = vec![RawStateChange {
+            cause: StateChangeCause::TransactionProcessing { tx_hash },
+            data: Some(data.to_vec()),
+        }];
+        (row_key.into(), RawStateChangesWithTrieKey { trie_key, changes })
+    }
+
+    #[test]
+    fn test_diff_state_changes_reports_keys_missing_on_either_side() {
+        let expected_tx = CryptoHash::hash_bytes(b"expected");
+        let replayed_tx = CryptoHash::hash_bytes(b"replayed");
+        let expected = BTreeMap::from([
+            changes(account_key("alice"), expected_tx, b"same"),
+            changes(account_key("bob"), expected_tx, b"bob"),
+        ]);
+        let replayed = BTreeMap::from([
+            changes(account_key("alice"), expected_tx, b"same"),
+            changes(account_key("carol"), replayed_tx, b"carol"),
+        ]);
+
+        let diffs = diff_state_changes(expected, replayed);
+        assert_eq!(diffs.len(), 2);
+
+        // Written only in the archive.
+        assert_eq!(diffs[0].trie_key, account_key("bob"));
+        assert_eq!(diffs[0].cause_id, Some(expected_tx));
+        assert_eq!(diffs[0].expected.len(), 1);
+        assert!(diffs[0].replayed.is_empty());
+
+        // Written only by the replay.
+        assert_eq!(diffs[1].trie_key, account_key("carol"));
+        assert_eq!(diffs[1].cause_id, Some(replayed_tx));
+        assert!(diffs[1].expected.is_empty());
+        assert_eq!(diffs[1].replayed.len(), 1);
+    }
+
+    #[test]
+    fn test_diff_state_changes_reports_differing_data() {
+        let tx = CryptoHash::hash_bytes(b"tx");
+        let expected = BTreeMap::from([changes(account_key("alice"), tx, b"expected")]);
+        let replayed = BTreeMap::from([changes(account_key("alice"), tx, b"replayed")]);
+
+        let diffs = diff_state_changes(expected, replayed);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].trie_key, account_key("alice"));
+        assert_eq!(diffs[0].cause_id, Some(tx));
+    }
+}
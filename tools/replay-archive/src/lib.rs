@@ -1,3 +1,4 @@
 pub mod cli;
+mod divergence;
 mod replaydb;
 pub use cli::ReplayArchiveCommand;
@@ -8,7 +8,7 @@ use itertools::Itertools;
 use near_store::db::{
     DBIterator, DBSlice, DBTransaction, Database, SplitDB, StoreStatistics, TestDB,
 };
-use near_store::{DBCol, Mode, NodeStorage};
+use near_store::{DBCol, Mode, NodeStorage, Store};
 use nearcore::NearConfig;
 use parking_lot::Mutex;
 
@@ -53,6 +53,11 @@ impl ReplayDB {
         }
     }
 
+    /// Returns a store reading only the archival data, regardless of the column.
+    pub fn archive_store(&self) -> Store {
+        Store::new(self.split_db.clone())
+    }
+
     /// Returns the set of columns read from the store since its creation.
     pub fn get_columns_read(&self) -> HashSet<DBCol> {
         self.columns_read.lock().clone()
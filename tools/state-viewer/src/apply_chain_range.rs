@@ -103,6 +103,7 @@ fn apply_chunk_from_input(
         gas_limit: chunk_header.gas_limit(),
         is_new_chunk: chunk_header.is_new_chunk(block.header().height()),
         on_post_state_ready: None,
+        execution_trace_collector: None,
     };
 
     runtime_adapter
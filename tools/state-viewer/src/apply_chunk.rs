@@ -21,6 +21,7 @@ use near_store::DBCol;
 use near_store::Store;
 use near_store::adapter::StoreAdapter;
 use node_runtime::SignedValidPeriodTransactions;
+use node_runtime::execution_trace::ExecutionTraceCollector;
 use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
 use std::collections::{BTreeMap, HashMap, HashSet};
@@ -95,6 +96,7 @@ pub fn apply_chunk(
     target_height: Option<u64>,
     rng: Option<StdRng>,
     storage: StorageSource,
+    execution_trace_collector: Option<Arc<ExecutionTraceCollector>>,
 ) -> anyhow::Result<(ApplyChunkResult, Gas)> {
     let chunk = chain_store.get_chunk(chunk_hash)?;
     let chunk_header = chunk.cloned_header();
@@ -178,6 +180,7 @@ pub fn apply_chunk(
                 gas_limit: chunk_header.gas_limit(),
                 is_new_chunk: true,
                 on_post_state_ready: None,
+                execution_trace_collector,
             },
             ApplyChunkBlockContext {
                 block_type: BlockType::Normal,
@@ -260,6 +263,7 @@ fn apply_tx_in_block(
                     runtime,
                     chain_store,
                     storage,
+                    None,
                 );
                 check_apply_block_result(
                     &block,
@@ -339,8 +343,16 @@ fn apply_tx_in_chunk(
             "found tx in chunk {}. Equivalent command (which will run faster than apply_tx):\nview_state apply_chunk --chunk_hash {}\n",
             &chunk_hash.0, &chunk_hash.0
         );
-        let (apply_result, gas_limit) =
-            apply_chunk(epoch_manager, runtime, chain_store, &chunk_hash, None, None, storage)?;
+        let (apply_result, gas_limit) = apply_chunk(
+            epoch_manager,
+            runtime,
+            chain_store,
+            &chunk_hash,
+            None,
+            None,
+            storage,
+            None,
+        )?;
         println!("resulting chunk extra:\n{:?}", resulting_chunk_extra(&apply_result, gas_limit));
         results.push(apply_result);
     }
@@ -380,6 +392,7 @@ fn apply_receipt_in_block(
     id: &CryptoHash,
     block_hash: CryptoHash,
     storage: StorageSource,
+    execution_trace_collector: Option<Arc<ExecutionTraceCollector>>,
 ) -> anyhow::Result<ApplyChunkResult> {
     match find_tx_or_receipt(id, &block_hash, epoch_manager, chain_store)? {
         Some((hash_type, shard_id)) => match hash_type {
@@ -403,6 +416,7 @@ fn apply_receipt_in_block(
                     runtime,
                     chain_store,
                     storage,
+                    execution_trace_collector,
                 );
                 check_apply_block_result(
                     &block,
@@ -432,6 +446,7 @@ fn apply_receipt_in_chunk(
     chain_store: &mut ChainStore,
     id: &CryptoHash,
     storage: StorageSource,
+    execution_trace_collector: Option<Arc<ExecutionTraceCollector>>,
 ) -> anyhow::Result<Vec<ApplyChunkResult>> {
     println!("Receipt is not indexed; searching in chunks that haven't been applied...");
 
@@ -494,8 +509,16 @@ fn apply_receipt_in_chunk(
             "Applying chunk at height {} in shard {}. Equivalent command (which will run faster than apply_receipt):\nview_state apply_chunk --chunk_hash {}\n",
             height, shard_id, chunk_hash.0
         );
-        let (apply_result, gas_limit) =
-            apply_chunk(epoch_manager, runtime, chain_store, chunk_hash, None, None, storage)?;
+        let (apply_result, gas_limit) = apply_chunk(
+            epoch_manager,
+            runtime,
+            chain_store,
+            chunk_hash,
+            None,
+            None,
+            storage,
+            execution_trace_collector.clone(),
+        )?;
         let chunk_extra = resulting_chunk_extra(&apply_result, gas_limit);
         println!("resulting chunk extra:\n{:?}", chunk_extra);
         results.push(apply_result);
@@ -510,6 +533,7 @@ pub fn apply_receipt(
     store: Store,
     id: CryptoHash,
     storage: StorageSource,
+    execution_trace_collector: Option<Arc<ExecutionTraceCollector>>,
 ) -> anyhow::Result<Vec<ApplyChunkResult>> {
     let mut chain_store =
         ChainStore::new(store.clone(), false, genesis_config.transaction_validity_period);
@@ -522,8 +546,17 @@ pub fn apply_receipt(
             &id,
             outcome.block_hash,
             storage,
+            execution_trace_collector,
         )?])
     } else {
-        apply_receipt_in_chunk(epoch_manager, runtime, store, &mut chain_store, &id, storage)
+        apply_receipt_in_chunk(
+            epoch_manager,
+            runtime,
+            store,
+            &mut chain_store,
+            &id,
+            storage,
+            execution_trace_collector,
+        )
     }
 }
@@ -3,6 +3,7 @@ use crate::congestion_control::CongestionControlCmd;
 use crate::contract_accounts::ContractAccountFilter;
 use crate::replay_headers::replay_headers;
 use crate::rocksdb_stats::get_rocksdb_stats;
+use crate::state_diff::StateDiffCmd;
 use crate::trie_iteration_benchmark::TrieIterationBenchmarkCmd;
 
 use crate::latest_witnesses::StateWitnessCmd;
@@ -102,6 +103,9 @@ pub enum StateViewerSubCommand {
     /// Dumps or applies StateChanges.
     /// Experimental tool for shard shadowing development.
     StateChanges(StateChangesCmd),
+    /// Compares two state roots of a shard, possibly from two databases, and
+    /// prints the differing state records as JSON.
+    StateDiff(StateDiffCmd),
     /// Dump or apply state parts.
     StateParts(StatePartsCmd),
     /// Iterates over the Flat State and prints some statistics.
@@ -118,6 +122,9 @@ pub enum StateViewerSubCommand {
     /// View trie structure.
     #[clap(alias = "view_trie")]
     ViewTrie(ViewTrieCmd),
+    /// Projects validator online ratios, rewards and kickouts at the end of the current epoch.
+    #[clap(alias = "validator_forecast")]
+    ValidatorForecast(ValidatorForecastCmd),
     /// Tools for manually validating state witnesses.
     ///
     /// First, dump some of the stored state witnesses to a directory
@@ -194,11 +201,13 @@ impl StateViewerSubCommand {
             StateViewerSubCommand::ScanDbColumn(cmd) => cmd.run(store),
             StateViewerSubCommand::State => state(home_dir, near_config, store),
             StateViewerSubCommand::StateChanges(cmd) => cmd.run(home_dir, near_config, store),
+            StateViewerSubCommand::StateDiff(cmd) => cmd.run(store),
             StateViewerSubCommand::StateParts(cmd) => cmd.run(home_dir, near_config, store),
             StateViewerSubCommand::StateStats(cmd) => cmd.run(home_dir, near_config, store),
             StateViewerSubCommand::ViewChain(cmd) => cmd.run(home_dir, near_config, store),
             StateViewerSubCommand::ViewGenesis(cmd) => cmd.run(home_dir, near_config, store),
             StateViewerSubCommand::ViewTrie(cmd) => cmd.run(store),
+            StateViewerSubCommand::ValidatorForecast(cmd) => cmd.run(near_config, store),
             StateViewerSubCommand::TrieIterationBenchmark(cmd) => cmd.run(near_config, store),
             StateViewerSubCommand::StateWitness(cmd) => cmd.run(home_dir, near_config, store),
             StateViewerSubCommand::CongestionControl(cmd) => cmd.run(home_dir, near_config, store),
@@ -374,12 +383,16 @@ pub struct ApplyReceiptCmd {
     hash: String,
     #[clap(long, default_value = "trie")]
     storage: StorageSource,
+    /// Print the host function calls made while executing the receipt as JSON.
+    /// Otherwise only the wasm backtraces of the failed function calls are printed.
+    #[clap(long)]
+    trace: bool,
 }
 
 impl ApplyReceiptCmd {
     pub fn run(self, home_dir: &Path, near_config: NearConfig, store: Store) {
         let hash = CryptoHash::from_str(&self.hash).unwrap();
-        apply_receipt(home_dir, near_config, store, hash, self.storage).unwrap();
+        apply_receipt(home_dir, near_config, store, hash, self.storage, self.trace).unwrap();
     }
 }
 
@@ -659,6 +672,32 @@ impl EpochInfoCmd {
     }
 }
 
+#[derive(clap::Args)]
+pub struct ValidatorForecastCmd {
+    /// Height of the block to compute the forecast at. Defaults to the head.
+    #[clap(long)]
+    height: Option<BlockHeight>,
+    /// Only show the given validator, including the heights and shards it missed.
+    #[clap(long)]
+    validator_account_id: Option<AccountId>,
+    /// Print the full forecast as JSON.
+    #[clap(long)]
+    json: bool,
+}
+
+impl ValidatorForecastCmd {
+    pub fn run(self, near_config: NearConfig, store: Store) {
+        print_validator_forecast(
+            self.height,
+            self.validator_account_id,
+            self.json,
+            near_config,
+            store,
+        )
+        .unwrap();
+    }
+}
+
 #[derive(clap::Args)]
 pub struct EpochAnalysisCmd {
     /// Start height of the epochs to analyze.
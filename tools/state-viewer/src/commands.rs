@@ -44,6 +44,7 @@ use near_primitives::trie_key::TrieKey;
 use near_primitives::trie_key::col::COLUMNS_WITH_ACCOUNT_ID_IN_KEY;
 use near_primitives::types::{BlockHeight, EpochId, ShardId};
 use near_primitives::version::PROTOCOL_VERSION;
+use near_primitives::views::FunctionCallTraceView;
 use near_primitives_core::types::{Balance, EpochHeight};
 use near_primitives_core::version::ProtocolFeature;
 use near_store::TrieStorage;
@@ -57,6 +58,7 @@ use nearcore::NightshadeRuntimeExt;
 use nearcore::{NearConfig, NightshadeRuntime};
 use node_runtime::SignedValidPeriodTransactions;
 use node_runtime::adapter::ViewRuntimeAdapter;
+use node_runtime::execution_trace::ExecutionTraceCollector;
 use serde_json::json;
 use std::collections::HashMap;
 use std::collections::{BTreeMap, BinaryHeap};
@@ -73,6 +75,7 @@ pub(crate) fn apply_block(
     runtime: &dyn RuntimeAdapter,
     chain_store: &ChainStore,
     storage: StorageSource,
+    execution_trace_collector: Option<Arc<ExecutionTraceCollector>>,
 ) -> (Arc<Block>, ApplyChunkResult) {
     let block = chain_store.get_block(&block_hash).unwrap();
     let height = block.header().height();
@@ -113,6 +116,7 @@ pub(crate) fn apply_block(
                     gas_limit: chunk_inner.gas_limit(),
                     is_new_chunk: true,
                     on_post_state_ready: None,
+                    execution_trace_collector: execution_trace_collector.clone(),
                 },
                 ApplyChunkBlockContext::from_header(
                     block.header(),
@@ -139,6 +143,7 @@ pub(crate) fn apply_block(
                     gas_limit: chunk_extra.gas_limit(),
                     is_new_chunk: false,
                     on_post_state_ready: None,
+                    execution_trace_collector,
                 },
                 ApplyChunkBlockContext::from_header(
                     block.header(),
@@ -184,6 +189,7 @@ pub(crate) fn apply_block_at_height(
         runtime.as_ref(),
         &mut read_chain_store,
         storage,
+        None,
     );
     check_apply_block_result(
         &block,
@@ -226,6 +232,7 @@ pub(crate) fn apply_chunk(
         near_config.client_config.save_trie_changes,
         near_config.genesis.config.transaction_validity_period,
     );
+    let execution_trace_collector = Arc::new(ExecutionTraceCollector::backtraces_only());
     let (apply_result, gas_limit) = apply_chunk::apply_chunk(
         epoch_manager.as_ref(),
         runtime.as_ref(),
@@ -234,8 +241,10 @@ pub(crate) fn apply_chunk(
         target_height,
         None,
         storage,
+        Some(execution_trace_collector.clone()),
     )?;
     println!("resulting chunk extra:\n{:?}", resulting_chunk_extra(&apply_result, gas_limit));
+    print_backtraces(&execution_trace_collector.take());
     Ok(())
 }
 
@@ -337,6 +346,7 @@ pub(crate) fn apply_receipt(
     store: Store,
     hash: CryptoHash,
     storage: StorageSource,
+    trace: bool,
 ) -> anyhow::Result<()> {
     let epoch_manager =
         EpochManager::new_arc_handle(store.clone(), &near_config.genesis.config, Some(home_dir));
@@ -347,6 +357,11 @@ pub(crate) fn apply_receipt(
         epoch_manager.clone(),
     )
     .context("could not create the transaction runtime")?;
+    let execution_trace_collector = Arc::new(if trace {
+        ExecutionTraceCollector::default()
+    } else {
+        ExecutionTraceCollector::backtraces_only()
+    });
     apply_chunk::apply_receipt(
         &near_config.genesis.config,
         epoch_manager.as_ref(),
@@ -354,8 +369,39 @@ pub(crate) fn apply_receipt(
         store,
         hash,
         storage,
-    )
-    .map(|_| ())
+        Some(execution_trace_collector.clone()),
+    )?;
+    let traces: Vec<_> = execution_trace_collector
+        .take()
+        .into_iter()
+        .filter(|trace| trace.receipt_id == Some(hash))
+        .collect();
+    if trace {
+        println!("{}", serde_json::to_string_pretty(&traces)?);
+    } else {
+        print_backtraces(&traces);
+    }
+    Ok(())
+}
+
+/// Prints the wasm backtraces of the function calls that failed.
+fn print_backtraces(traces: &[FunctionCallTraceView]) {
+    for trace in traces {
+        let Some(backtrace) = &trace.backtrace else {
+            continue;
+        };
+        match trace.receipt_id {
+            Some(receipt_id) => println!(
+                "call of {}.{} in receipt {} failed at:",
+                trace.account_id, trace.method_name, receipt_id
+            ),
+            None => println!("call of {}.{} failed at:", trace.account_id, trace.method_name),
+        }
+        for (i, frame) in backtrace.iter().enumerate() {
+            let name = frame.function_name.as_deref().unwrap_or("<unnamed>");
+            println!("  {i}: {name} (function #{})", frame.function_index);
+        }
+    }
 }
 
 pub(crate) fn apply_tx(
@@ -984,6 +1030,82 @@ pub(crate) fn print_epoch_info(
     );
 }
 
+pub(crate) fn print_validator_forecast(
+    height: Option<BlockHeight>,
+    validator_account_id: Option<AccountId>,
+    json: bool,
+    near_config: NearConfig,
+    store: Store,
+) -> anyhow::Result<()> {
+    let chain_store = ChainStore::new(
+        store.clone(),
+        near_config.client_config.save_trie_changes,
+        near_config.genesis.config.transaction_validity_period,
+    );
+    let epoch_manager = EpochManager::new_arc_handle(store, &near_config.genesis.config, None);
+    let block_hash = match height {
+        Some(height) => chain_store.get_block_hash_by_height(height)?,
+        None => chain_store.head()?.last_block_hash,
+    };
+    let mut forecast = epoch_manager.get_validator_forecast(&block_hash)?;
+    if let Some(account_id) = &validator_account_id {
+        forecast.validators.retain(|validator| &validator.account_id == account_id);
+        anyhow::ensure!(!forecast.validators.is_empty(), "{account_id} is not a validator");
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&forecast)?);
+        return Ok(());
+    }
+
+    println!(
+        "Epoch {} (height {}) started at #{} and is expected to end at #{}, forecast at #{}",
+        forecast.epoch_id.0,
+        forecast.epoch_height,
+        forecast.epoch_start_height,
+        forecast.expected_epoch_end_height,
+        forecast.block_height,
+    );
+    for validator in &forecast.validators {
+        let kickout = match (&validator.projected_kickout, validator.kickout_unavoidable) {
+            (None, _) => "-".to_string(),
+            (Some(reason), true) => format!("{reason:?} (unavoidable)"),
+            (Some(reason), false) => format!("{reason:?}"),
+        };
+        println!(
+            "{}: stake {}, blocks {}/{} (+{}), chunks {}/{} (+{}), endorsements {}/{} (+{}), \
+             online {:.2}% (best case {:.2}%), reward {}, kickout {}",
+            validator.account_id,
+            validator.stake,
+            validator.num_produced_blocks,
+            validator.num_expected_blocks,
+            validator.num_remaining_blocks,
+            validator.num_produced_chunks,
+            validator.num_expected_chunks,
+            validator.num_remaining_chunks,
+            validator.num_produced_endorsements,
+            validator.num_expected_endorsements,
+            validator.num_remaining_endorsements,
+            validator.online_ratio * 100.0,
+            validator.best_case_online_ratio * 100.0,
+            validator.projected_reward,
+            kickout,
+        );
+        if validator_account_id.is_some() {
+            println!("  missed blocks: {:?}", validator.missed_blocks);
+            for (name, missed) in [
+                ("chunks", &validator.missed_chunks),
+                ("endorsements", &validator.missed_endorsements),
+            ] {
+                println!("  missed {name}:");
+                for missed in missed {
+                    println!("    #{} shard {}", missed.height, missed.shard_id);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 pub(crate) fn print_epoch_analysis(
     epoch_height: EpochHeight,
     mode: EpochAnalysisMode,
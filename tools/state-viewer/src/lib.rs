@@ -13,6 +13,7 @@ mod replay_headers;
 mod rocksdb_stats;
 mod scan_db;
 mod state_changes;
+mod state_diff;
 mod state_dump;
 mod state_parts;
 mod trie_iteration_benchmark;
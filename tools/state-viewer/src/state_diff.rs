@@ -0,0 +1,415 @@
+//! Comparison of two state roots of a shard, stored in the same database or in
+//! the databases of two nodes.
+//!
+//! Both tries are walked together from their roots, and subtrees with the same
+//! hash on both sides are skipped, so the cost is proportional to the size of
+//! the difference rather than to the size of the state.
+
+use anyhow::Context;
+use borsh::BorshDeserialize;
+use near_chain_configs::GenesisValidationMode;
+use near_primitives::hash::CryptoHash;
+use near_primitives::serialize::to_base64;
+use near_primitives::shard_layout::ShardUId;
+use near_primitives::state::ValueRef;
+use near_primitives::state_record::StateRecord;
+use near_primitives::trie_key::col;
+use near_primitives::trie_key::trie_key_parsers::parse_account_id_from_raw_key;
+use near_primitives::types::{AccountId, StateRoot};
+use near_store::adapter::StoreAdapter;
+use near_store::trie::Children;
+use near_store::{
+    Mode, NibbleSlice, NodeStorage, RawTrieNode, RawTrieNodeWithSize, Store, Trie, TrieDBStorage,
+    TrieStorage,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Prints the differences between two state roots of a shard as JSON.
+///
+/// Example usage: neard view-state state-diff --shard-id 0 --shard-version 3
+///   --left-state-root <hash> --right-state-root <hash> --right-home ~/.near-fork
+#[derive(clap::Parser)]
+pub struct StateDiffCmd {
+    /// State root of the first trie.
+    #[clap(long)]
+    left_state_root: StateRoot,
+    /// State root of the second trie.
+    #[clap(long)]
+    right_state_root: StateRoot,
+    /// The id of the shard both tries belong to.
+    #[clap(long)]
+    shard_id: u32,
+    /// The version of the shard layout the shard belongs to.
+    #[clap(long)]
+    shard_version: u32,
+    /// Home directory of the node to read the second trie from. By default both
+    /// tries are read from the database of this node.
+    #[clap(long)]
+    right_home: Option<PathBuf>,
+    /// Write the differences to this file instead of stdout.
+    #[clap(long)]
+    output: Option<PathBuf>,
+}
+
+impl StateDiffCmd {
+    pub fn run(self, store: Store) {
+        self.run_impl(store).unwrap()
+    }
+
+    fn run_impl(self, store: Store) -> anyhow::Result<()> {
+        let shard_uid = ShardUId { version: self.shard_version, shard_id: self.shard_id };
+        let right_store = match &self.right_home {
+            Some(home) => open_store(home)?,
+            None => store.clone(),
+        };
+        let left = TrieDBStorage::new(store.trie_store(), shard_uid);
+        let right = TrieDBStorage::new(right_store.trie_store(), shard_uid);
+        let differences = diff_tries(&left, self.left_state_root, &right, self.right_state_root)?;
+        let diff =
+            StateDiff::new(shard_uid, self.left_state_root, self.right_state_root, differences);
+
+        match &self.output {
+            Some(path) => {
+                let file = std::fs::File::create(path)
+                    .with_context(|| format!("Failed to create {}", path.display()))?;
+                serde_json::to_writer_pretty(std::io::BufWriter::new(file), &diff)?;
+            }
+            None => serde_json::to_writer_pretty(std::io::stdout().lock(), &diff)?,
+        }
+        eprintln!(
+            "Found {} differing keys in {} accounts",
+            diff.differences.len(),
+            diff.accounts.len()
+        );
+        Ok(())
+    }
+}
+
+fn open_store(home: &Path) -> anyhow::Result<Store> {
+    let near_config = nearcore::config::load_config(home, GenesisValidationMode::UnsafeFast)
+        .with_context(|| format!("Error loading config from {}", home.display()))?;
+    let storage = NodeStorage::opener(
+        home,
+        &near_config.config.store,
+        near_config.config.cold_store.as_ref(),
+        near_config.config.cloud_storage_config(),
+    )
+    .open_in_mode(Mode::ReadOnly)?;
+    Ok(storage.get_hot_store())
+}
+
+#[derive(Serialize)]
+struct StateDiff {
+    shard_uid: String,
+    left_state_root: StateRoot,
+    right_state_root: StateRoot,
+    /// Number of differing keys per account.
+    accounts: BTreeMap<AccountId, DiffCounts>,
+    /// Number of differing keys which don't belong to any account, e.g. the
+    /// delayed receipts queue.
+    shard_global: DiffCounts,
+    differences: Vec<KeyDiff>,
+}
+
+#[derive(Serialize, Default, Debug, PartialEq, Eq)]
+struct DiffCounts {
+    /// Keys only present in the second trie.
+    added: u64,
+    /// Keys only present in the first trie.
+    removed: u64,
+    /// Keys present in both tries with different values.
+    changed: u64,
+}
+
+#[derive(Serialize)]
+struct KeyDiff {
+    column: &'static str,
+    account_id: Option<AccountId>,
+    key_base64: String,
+    left: Option<DiffValue>,
+    right: Option<DiffValue>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum DiffValue {
+    Record(StateRecord),
+    /// Values of the keys which don't map to a `StateRecord`.
+    Raw {
+        value_base64: String,
+    },
+}
+
+impl DiffValue {
+    fn new(key: &[u8], value: Vec<u8>) -> Self {
+        let value_base64 = to_base64(&value);
+        match StateRecord::from_raw_key_value(key, value) {
+            Some(record) => Self::Record(record),
+            None => Self::Raw { value_base64 },
+        }
+    }
+}
+
+impl StateDiff {
+    fn new(
+        shard_uid: ShardUId,
+        left_state_root: StateRoot,
+        right_state_root: StateRoot,
+        differences: Vec<(Vec<u8>, Option<Vec<u8>>, Option<Vec<u8>>)>,
+    ) -> Self {
+        let mut accounts = BTreeMap::<AccountId, DiffCounts>::new();
+        let mut shard_global = DiffCounts::default();
+        let differences = differences
+            .into_iter()
+            .map(|(key, left, right)| {
+                let account_id = parse_account_id_from_raw_key(&key).ok().flatten();
+                let counts = match &account_id {
+                    Some(account_id) => accounts.entry(account_id.clone()).or_default(),
+                    None => &mut shard_global,
+                };
+                match (&left, &right) {
+                    (None, _) => counts.added += 1,
+                    (_, None) => counts.removed += 1,
+                    _ => counts.changed += 1,
+                }
+                let column = col::ALL_COLUMNS_WITH_NAMES
+                    .iter()
+                    .find(|(col, _)| Some(col) == key.first())
+                    .map_or("Unknown", |(_, name)| *name);
+                KeyDiff {
+                    column,
+                    account_id,
+                    key_base64: to_base64(&key),
+                    left: left.map(|value| DiffValue::new(&key, value)),
+                    right: right.map(|value| DiffValue::new(&key, value)),
+                }
+            })
+            .collect();
+        Self {
+            shard_uid: shard_uid.to_string(),
+            left_state_root,
+            right_state_root,
+            accounts,
+            shard_global,
+            differences,
+        }
+    }
+}
+
+/// A subtree of a trie, either not read yet or decoded from a node whose key
+/// may have been partially consumed while walking down the trie.
+#[derive(Clone, PartialEq, Eq)]
+enum Subtree {
+    Hash(CryptoHash),
+    Leaf(Vec<u8>, ValueRef),
+    Extension(Vec<u8>, CryptoHash),
+    Branch(Option<ValueRef>, Children),
+}
+
+impl Subtree {
+    fn read(storage: &dyn TrieStorage, hash: CryptoHash) -> anyhow::Result<Option<Self>> {
+        if hash == Trie::EMPTY_ROOT {
+            return Ok(None);
+        }
+        let bytes = storage.retrieve_raw_bytes(&hash)?;
+        let node = RawTrieNodeWithSize::try_from_slice(&bytes)
+            .with_context(|| format!("Failed to decode trie node {hash}"))?;
+        Ok(Some(match node.node {
+            RawTrieNode::Leaf(key, value) => Self::Leaf(nibbles(&key), value),
+            RawTrieNode::Extension(key, child) => Self::Extension(nibbles(&key), child),
+            RawTrieNode::BranchNoValue(children) => Self::Branch(None, children),
+            RawTrieNode::BranchWithValue(value, children) => Self::Branch(Some(value), children),
+        }))
+    }
+
+    /// Views the subtree as a branch, i.e. as the value stored at its root and
+    /// its subtrees one nibble further down.
+    fn expand(
+        self,
+        storage: &dyn TrieStorage,
+    ) -> anyhow::Result<(Option<ValueRef>, [Option<Subtree>; 16])> {
+        let mut children: [Option<Subtree>; 16] = Default::default();
+        match self {
+            Self::Hash(hash) => {
+                return match Self::read(storage, hash)? {
+                    Some(subtree) => subtree.expand(storage),
+                    None => Ok((None, children)),
+                };
+            }
+            Self::Leaf(key, value) => match key.split_first() {
+                None => return Ok((Some(value), children)),
+                Some((first, rest)) => {
+                    children[*first as usize] = Some(Self::Leaf(rest.to_vec(), value))
+                }
+            },
+            Self::Extension(key, child) => match key.split_first() {
+                None => return Self::Hash(child).expand(storage),
+                Some((first, rest)) => {
+                    children[*first as usize] = Some(Self::Extension(rest.to_vec(), child))
+                }
+            },
+            Self::Branch(value, branch_children) => {
+                for (index, child) in branch_children.iter() {
+                    children[index as usize] = Some(Self::Hash(*child));
+                }
+                return Ok((value, children));
+            }
+        }
+        Ok((None, children))
+    }
+}
+
+fn nibbles(encoded_key: &[u8]) -> Vec<u8> {
+    let (key, _is_leaf) = NibbleSlice::from_encoded(encoded_key);
+    key.iter().collect()
+}
+
+/// Returns the keys whose values differ between the two tries, in key order,
+/// together with their values in the first and the second trie.
+///
+/// The walk is iterative, since keys can be thousands of nibbles long and the
+/// walk goes one nibble down at a time.
+fn diff_tries(
+    left: &dyn TrieStorage,
+    left_root: StateRoot,
+    right: &dyn TrieStorage,
+    right_root: StateRoot,
+) -> anyhow::Result<Vec<(Vec<u8>, Option<Vec<u8>>, Option<Vec<u8>>)>> {
+    let mut differences = vec![];
+    // Subtrees left to compare, with the nibbles of the key leading to them.
+    // Children are pushed in reverse order, so that they're compared in key order.
+    let mut stack = vec![(Some(Subtree::Hash(left_root)), Some(Subtree::Hash(right_root)), vec![])];
+    while let Some((left_subtree, right_subtree, prefix)) = stack.pop() {
+        // Identical hashes, or identical remainders of the same node, mean identical subtrees.
+        if left_subtree == right_subtree {
+            continue;
+        }
+        let (left_value, left_children) = match left_subtree {
+            Some(subtree) => subtree.expand(left)?,
+            None => Default::default(),
+        };
+        let (right_value, right_children) = match right_subtree {
+            Some(subtree) => subtree.expand(right)?,
+            None => Default::default(),
+        };
+
+        if left_value != right_value {
+            let read_value = |storage: &dyn TrieStorage, value: Option<ValueRef>| {
+                value.map(|value| {
+                    storage.retrieve_raw_bytes(&value.hash).map(|bytes| bytes.to_vec())
+                })
+            };
+            let key = NibbleSlice::nibbles_to_bytes(&prefix);
+            let left_value = read_value(left, left_value).transpose()?;
+            let right_value = read_value(right, right_value).transpose()?;
+            differences.push((key, left_value, right_value));
+        }
+
+        for (index, (left_child, right_child)) in
+            left_children.into_iter().zip(right_children).enumerate().rev()
+        {
+            if left_child.is_none() && right_child.is_none() {
+                continue;
+            }
+            let mut child_prefix = prefix.clone();
+            child_prefix.push(index as u8);
+            stack.push((left_child, right_child, child_prefix));
+        }
+    }
+    Ok(differences)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DiffCounts, StateDiff, diff_tries};
+    use near_primitives::shard_layout::ShardUId;
+    use near_primitives::trie_key::TrieKey;
+    use near_primitives::types::AccountId;
+    use near_store::adapter::StoreAdapter;
+    use near_store::test_utils::{TestTriesBuilder, create_test_store, test_populate_trie};
+    use near_store::{Trie, TrieDBStorage};
+
+    fn contract_data(account_id: &str, key: &[u8]) -> Vec<u8> {
+        TrieKey::ContractData { account_id: account_id.parse().unwrap(), key: key.to_vec() }
+            .to_vec()
+    }
+
+    #[test]
+    fn test_diff_tries() {
+        let store = create_test_store();
+        let tries = TestTriesBuilder::new().with_store(store.clone()).build();
+        let shard_uid = ShardUId::single_shard();
+        let mut changes = vec![];
+        for account in ["alice.near", "bob.near", "carol.near"] {
+            for key in 0..50u8 {
+                changes.push((contract_data(account, &[key]), Some(vec![key; 3])));
+            }
+        }
+        let left_root = test_populate_trie(&tries, &Trie::EMPTY_ROOT, shard_uid, changes);
+        let right_root = test_populate_trie(
+            &tries,
+            &left_root,
+            shard_uid,
+            vec![
+                (contract_data("alice.near", &[7]), Some(vec![100])),
+                (contract_data("bob.near", &[8]), None),
+                (contract_data("bob.near", &[8, 1]), Some(vec![1])),
+                (contract_data("dave.near", &[1]), Some(vec![1])),
+            ],
+        );
+
+        let storage = TrieDBStorage::new(store.trie_store(), shard_uid);
+        assert!(diff_tries(&storage, left_root, &storage, left_root).unwrap().is_empty());
+        let differences = diff_tries(&storage, left_root, &storage, right_root).unwrap();
+        assert_eq!(
+            differences,
+            vec![
+                (contract_data("alice.near", &[7]), Some(vec![7; 3]), Some(vec![100])),
+                (contract_data("bob.near", &[8]), Some(vec![8; 3]), None),
+                (contract_data("bob.near", &[8, 1]), None, Some(vec![1])),
+                (contract_data("dave.near", &[1]), None, Some(vec![1])),
+            ]
+        );
+
+        let diff = StateDiff::new(shard_uid, left_root, right_root, differences);
+        let bob: AccountId = "bob.near".parse().unwrap();
+        assert_eq!(diff.accounts[&bob], DiffCounts { added: 1, removed: 1, changed: 0 });
+        assert_eq!(diff.accounts.len(), 3);
+        assert_eq!(diff.shard_global, DiffCounts::default());
+        assert!(diff.differences.iter().all(|d| d.column == "ContractData"));
+    }
+
+    #[test]
+    fn test_diff_tries_long_keys() {
+        let store = create_test_store();
+        let tries = TestTriesBuilder::new().with_store(store.clone()).build();
+        let shard_uid = ShardUId::single_shard();
+        // Keys sharing a prefix of thousands of nibbles.
+        let long_key = |last: u8| {
+            let mut key = vec![7; 2047];
+            key.push(last);
+            contract_data("alice.near", &key)
+        };
+        let left_root = test_populate_trie(
+            &tries,
+            &Trie::EMPTY_ROOT,
+            shard_uid,
+            vec![(long_key(0), Some(vec![0])), (long_key(1), Some(vec![1]))],
+        );
+        let right_root = test_populate_trie(
+            &tries,
+            &left_root,
+            shard_uid,
+            vec![(long_key(1), Some(vec![2])), (long_key(2), Some(vec![2]))],
+        );
+
+        let storage = TrieDBStorage::new(store.trie_store(), shard_uid);
+        let differences = diff_tries(&storage, left_root, &storage, right_root).unwrap();
+        assert_eq!(
+            differences,
+            vec![(long_key(1), Some(vec![1]), Some(vec![2])), (long_key(2), None, Some(vec![2])),]
+        );
+    }
+}
@@ -1,23 +1,22 @@
-use crate::db::Database;
-use crate::primitives::RawTrace;
-use bson::spec::BinarySubtype;
-use bson::Binary;
+use crate::db::TraceStore;
+use crate::primitives::TraceChunk;
 use opentelemetry_proto::tonic::collector::trace::v1::trace_service_server::TraceService;
 use opentelemetry_proto::tonic::collector::trace::v1::{
     ExportTraceServiceRequest, ExportTraceServiceResponse,
 };
+use std::sync::Arc;
 use tonic::{Request, Response, Status};
 
 /// Runs an OpenTelemetry-compatible collector service. This service can accept
 /// trace data from any OpenTelemetry client (such as neard), and will store the
-/// data verbatim into a MongoDB database for later querying and processing.
+/// data verbatim into the trace store for later querying and processing.
 pub struct Collector {
-    db: Database,
+    store: Arc<dyn TraceStore>,
 }
 
 impl Collector {
-    pub fn new(db: Database) -> Self {
-        Self { db }
+    pub fn new(store: Arc<dyn TraceStore>) -> Self {
+        Self { store }
     }
 }
 
@@ -56,16 +55,8 @@ impl TraceService for Collector {
         };
         println!("Persisting trace of size {} bytes", serialized.len());
 
-        // Insert into MongoDB.
-        let span_chunk = RawTrace {
-            _id: None,
-            min_time,
-            max_time,
-            data: Binary { subtype: BinarySubtype::Generic, bytes: serialized },
-        };
-        self.db
-            .raw_traces()
-            .insert_one(span_chunk, None)
+        self.store
+            .insert(TraceChunk { min_time, max_time, data: serialized })
             .await
             .map_err(|err| Status::internal(err.to_string()))?;
 
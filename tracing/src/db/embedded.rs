@@ -0,0 +1,211 @@
+use crate::db::{TraceChunkStream, TraceStore};
+use crate::primitives::TraceChunk;
+use rusqlite::{Connection, OpenFlags, params};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+use tonic::codegen::tokio_stream::wrappers::ReceiverStream;
+
+/// Name of the SQLite database within the data directory.
+const DB_FILE: &str = "traces.sqlite";
+/// How often the traces past the retention period are deleted.
+const GC_INTERVAL: Duration = Duration::from_secs(60);
+/// How long to wait for a lock held by another connection.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Number of chunks read ahead of the consumer of a query.
+const FIND_BATCH_SIZE: usize = 100;
+
+/// SQLite integers are signed, times past `i64::MAX` are clamped to it.
+fn to_sql_time(time: u64) -> i64 {
+    i64::try_from(time).unwrap_or(i64::MAX)
+}
+
+/// Trace store kept in an SQLite database on the local disk, so that the
+/// collector and the querier can run without any external service.
+///
+/// The database is in WAL mode and every query opens its own read-only
+/// connection, so long queries don't block the collector from inserting.
+#[derive(Clone)]
+pub struct EmbeddedStore {
+    path: PathBuf,
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl EmbeddedStore {
+    pub fn open(data_dir: &Path) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(data_dir)?;
+        let path = data_dir.join(DB_FILE);
+        let conn = Connection::open(&path)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        // Setting the journal mode returns the resulting mode as a row.
+        conn.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))?;
+        conn.execute_batch(include_str!("init.sql"))?;
+        println!("Embedded store initialized at {}", path.display());
+        Ok(Self { path, conn: Arc::new(Mutex::new(conn)) })
+    }
+
+    /// Spawns a task periodically deleting the traces which ended more than
+    /// `retention` ago.
+    pub fn start_gc(&self, retention: Duration) {
+        let store = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(GC_INTERVAL);
+            loop {
+                interval.tick().await;
+                let store = store.clone();
+                match tokio::task::spawn_blocking(move || store.delete_older_than(retention)).await
+                {
+                    Ok(Ok(0)) => {}
+                    Ok(Ok(deleted)) => println!("Deleted {} expired trace chunks", deleted),
+                    Ok(Err(err)) => eprintln!("Failed to delete expired traces: {:?}", err),
+                    Err(err) => eprintln!("Failed to delete expired traces: {:?}", err),
+                }
+            }
+        });
+    }
+
+    fn delete_older_than(&self, retention: Duration) -> anyhow::Result<usize> {
+        let cutoff = SystemTime::now()
+            .checked_sub(retention)
+            .unwrap_or(UNIX_EPOCH)
+            .duration_since(UNIX_EPOCH)?
+            .as_nanos();
+        let cutoff = i64::try_from(cutoff).unwrap_or(i64::MAX);
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.execute("DELETE FROM raw_trace WHERE max_time < ?1", params![cutoff])?)
+    }
+
+    fn find_blocking(
+        path: &Path,
+        start_time: u64,
+        end_time: u64,
+        sender: &mpsc::Sender<anyhow::Result<TraceChunk>>,
+    ) -> anyhow::Result<()> {
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        let mut stmt = conn.prepare(
+            "SELECT min_time, max_time, data FROM raw_trace WHERE max_time > ?1 AND min_time < ?2",
+        )?;
+        let chunks =
+            stmt.query_map(params![to_sql_time(start_time), to_sql_time(end_time)], |row| {
+                Ok(TraceChunk {
+                    min_time: row.get::<_, i64>(0)? as u64,
+                    max_time: row.get::<_, i64>(1)? as u64,
+                    data: row.get(2)?,
+                })
+            })?;
+        for chunk in chunks {
+            if sender.blocking_send(chunk.map_err(Into::into)).is_err() {
+                // The receiver is gone, e.g. the client closed the connection.
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[tonic::async_trait]
+impl TraceStore for EmbeddedStore {
+    async fn insert(&self, chunk: TraceChunk) -> anyhow::Result<()> {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            conn.lock().unwrap().execute(
+                "INSERT INTO raw_trace (min_time, max_time, data) VALUES (?1, ?2, ?3)",
+                params![to_sql_time(chunk.min_time), to_sql_time(chunk.max_time), chunk.data],
+            )
+        })
+        .await??;
+        Ok(())
+    }
+
+    async fn find(&self, start_time: u64, end_time: u64) -> anyhow::Result<TraceChunkStream> {
+        let (sender, receiver) = mpsc::channel(FIND_BATCH_SIZE);
+        let path = self.path.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(err) = Self::find_blocking(&path, start_time, end_time, &sender) {
+                let _ = sender.blocking_send(Err(err));
+            }
+        });
+        Ok(Box::pin(ReceiverStream::new(receiver)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tonic::codegen::tokio_stream::StreamExt;
+
+    /// Data directory removed when dropped.
+    struct TestDir(PathBuf);
+
+    impl TestDir {
+        fn new(name: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("near-tracing-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+            Self(path)
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn chunk(min_time: u64, max_time: u64) -> TraceChunk {
+        TraceChunk { min_time, max_time, data: min_time.to_le_bytes().to_vec() }
+    }
+
+    async fn find(store: &EmbeddedStore, start_time: u64, end_time: u64) -> Vec<(u64, u64)> {
+        let mut chunks: Vec<_> = store
+            .find(start_time, end_time)
+            .await
+            .unwrap()
+            .map(|chunk| {
+                let chunk = chunk.unwrap();
+                assert_eq!(chunk.data, chunk.min_time.to_le_bytes());
+                (chunk.min_time, chunk.max_time)
+            })
+            .collect()
+            .await;
+        chunks.sort();
+        chunks
+    }
+
+    #[tokio::test]
+    async fn test_insert_and_find() {
+        let dir = TestDir::new("insert");
+        let store = EmbeddedStore::open(&dir.0).unwrap();
+        for (min_time, max_time) in [(10, 20), (30, 40), (50, 60)] {
+            store.insert(chunk(min_time, max_time)).await.unwrap();
+        }
+
+        assert_eq!(find(&store, 25, 45).await, vec![(30, 40)]);
+        assert_eq!(find(&store, 15, 35).await, vec![(10, 20), (30, 40)]);
+        assert_eq!(find(&store, 60, 70).await, vec![]);
+        assert_eq!(find(&store, 0, u64::MAX).await, vec![(10, 20), (30, 40), (50, 60)]);
+
+        // The data survives reopening the store.
+        drop(store);
+        let store = EmbeddedStore::open(&dir.0).unwrap();
+        assert_eq!(find(&store, 0, u64::MAX).await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retention() {
+        let dir = TestDir::new("retention");
+        let store = EmbeddedStore::open(&dir.0).unwrap();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos() as u64;
+        let hour = Duration::from_secs(3600).as_nanos() as u64;
+        let old = chunk(now - 3 * hour, now - 2 * hour);
+        let recent = chunk(now - 2 * hour, now);
+        store.insert(old).await.unwrap();
+        store.insert(recent).await.unwrap();
+
+        assert_eq!(store.delete_older_than(Duration::from_secs(3 * 3600)).unwrap(), 0);
+        assert_eq!(store.delete_older_than(Duration::from_secs(3600)).unwrap(), 1);
+        assert_eq!(find(&store, 0, u64::MAX).await, vec![(now - 2 * hour, now)]);
+    }
+}
@@ -0,0 +1,10 @@
+-- Every request received by the collector is stored verbatim, indexed by the
+-- time interval covered by its spans.
+CREATE TABLE IF NOT EXISTS raw_trace (
+    id INTEGER PRIMARY KEY,
+    min_time INTEGER NOT NULL,
+    max_time INTEGER NOT NULL,
+    data BLOB NOT NULL
+);
+CREATE INDEX IF NOT EXISTS raw_trace_min_time ON raw_trace(min_time);
+CREATE INDEX IF NOT EXISTS raw_trace_max_time ON raw_trace(max_time);
@@ -0,0 +1,57 @@
+use crate::primitives::TraceChunk;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tonic::codegen::tokio_stream::Stream;
+
+mod embedded;
+mod mongo;
+
+pub use embedded::EmbeddedStore;
+pub use mongo::MongoStore;
+
+pub type TraceChunkStream = Pin<Box<dyn Stream<Item = anyhow::Result<TraceChunk>> + Send>>;
+
+/// Storage backend of the collector and the querier.
+#[tonic::async_trait]
+pub trait TraceStore: Send + Sync {
+    /// Persists a chunk of traces received by the collector.
+    async fn insert(&self, chunk: TraceChunk) -> anyhow::Result<()>;
+
+    /// Returns all chunks with spans overlapping the interval between
+    /// `start_time` and `end_time`, both in unix nanoseconds.
+    async fn find(&self, start_time: u64, end_time: u64) -> anyhow::Result<TraceChunkStream>;
+}
+
+/// Selects where the traces are stored.
+#[derive(clap::Args, Debug)]
+pub struct StoreArgs {
+    /// Store the traces in MongoDB instead of the embedded store.
+    #[clap(long)]
+    mongodb_uri: Option<String>,
+
+    /// Directory of the embedded store.
+    #[clap(long, default_value = "tracing-data", conflicts_with = "mongodb_uri")]
+    data_dir: PathBuf,
+
+    /// Traces older than this are deleted from the embedded store. Zero
+    /// keeps the traces forever.
+    #[clap(long, default_value = "24", conflicts_with = "mongodb_uri")]
+    retention_hours: u64,
+}
+
+impl StoreArgs {
+    /// Opens the store. `initialize` is set by the collector, which is the
+    /// only writer and thus the one maintaining indexes and retention.
+    pub async fn open(&self, initialize: bool) -> anyhow::Result<Arc<dyn TraceStore>> {
+        if let Some(mongodb_uri) = &self.mongodb_uri {
+            return Ok(Arc::new(MongoStore::new(mongodb_uri, initialize).await));
+        }
+        let store = EmbeddedStore::open(&self.data_dir)?;
+        if initialize && self.retention_hours > 0 {
+            store.start_gc(Duration::from_secs(self.retention_hours * 3600));
+        }
+        Ok(Arc::new(store))
+    }
+}
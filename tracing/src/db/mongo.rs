@@ -1,14 +1,17 @@
-use crate::primitives::RawTrace;
+use crate::db::{TraceChunkStream, TraceStore};
+use crate::primitives::{RawTrace, TraceChunk};
 use bson::doc;
+use mongodb::options::FindOptions;
 use mongodb::{Collection, IndexModel};
+use tonic::codegen::tokio_stream::StreamExt;
 
 /// Simple wrapper on top of mongodb.
 #[derive(Clone)]
-pub struct Database {
+pub struct MongoStore {
     pub db: mongodb::Database,
 }
 
-impl Database {
+impl MongoStore {
     /// The collection where we dump all the traces received by the collector.
     pub fn raw_traces(&self) -> Collection<RawTrace> {
         self.db.collection("RawTrace")
@@ -38,3 +41,25 @@ impl Database {
             .unwrap();
     }
 }
+
+#[tonic::async_trait]
+impl TraceStore for MongoStore {
+    async fn insert(&self, chunk: TraceChunk) -> anyhow::Result<()> {
+        self.raw_traces().insert_one(RawTrace::from(chunk), None).await?;
+        Ok(())
+    }
+
+    async fn find(&self, start_time: u64, end_time: u64) -> anyhow::Result<TraceChunkStream> {
+        let chunks = self
+            .raw_traces()
+            .find(
+                doc! {
+                    "max_time": {"$gt": start_time as i64},
+                    "min_time": {"$lt": end_time as i64},
+                },
+                Some(FindOptions::builder().batch_size(100).build()),
+            )
+            .await?;
+        Ok(Box::pin(chunks.map(|chunk| anyhow::Ok(TraceChunk::from(chunk?)))))
+    }
+}
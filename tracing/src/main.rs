@@ -1,8 +1,9 @@
 use crate::collector::Collector;
-use crate::db::Database;
+use crate::db::{StoreArgs, TraceStore};
 use clap::Parser;
 use queries::run_query_server;
 use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::sync::Arc;
 
 mod collector;
 mod db;
@@ -12,8 +13,8 @@ mod queries;
 
 #[derive(clap::Parser, Debug)]
 struct CollectorCmd {
-    #[clap(long)]
-    mongodb_uri: String,
+    #[clap(flatten)]
+    store: StoreArgs,
 
     #[clap(long, default_value = "4317")]
     otlp_port: u16,
@@ -21,8 +22,21 @@ struct CollectorCmd {
 
 #[derive(clap::Parser, Debug)]
 struct QuerierCmd {
-    #[clap(long)]
-    mongodb_uri: String,
+    #[clap(flatten)]
+    store: StoreArgs,
+
+    #[clap(long, default_value = "8080")]
+    query_port: u16,
+}
+
+/// Runs the collector and the querier in a single process sharing the store.
+#[derive(clap::Parser, Debug)]
+struct StandaloneCmd {
+    #[clap(flatten)]
+    store: StoreArgs,
+
+    #[clap(long, default_value = "4317")]
+    otlp_port: u16,
 
     #[clap(long, default_value = "8080")]
     query_port: u16,
@@ -32,23 +46,39 @@ struct QuerierCmd {
 enum Cmd {
     Collector(CollectorCmd),
     Querier(QuerierCmd),
+    Standalone(StandaloneCmd),
+}
+
+async fn run_collector(store: Arc<dyn TraceStore>, otlp_port: u16) -> anyhow::Result<()> {
+    let collector = Collector::new(store);
+    tonic::transport::Server::builder()
+        .add_service(opentelemetry_proto::tonic::collector::trace::v1::trace_service_server::TraceServiceServer::new(collector))
+        .serve(SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, otlp_port))).await?;
+    Ok(())
 }
 
 impl CollectorCmd {
     async fn run(&self) -> anyhow::Result<()> {
-        let db = Database::new(&self.mongodb_uri, true).await;
-        let collector = Collector::new(db);
-        tonic::transport::Server::builder()
-        .add_service(opentelemetry_proto::tonic::collector::trace::v1::trace_service_server::TraceServiceServer::new(collector))
-        .serve(SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, self.otlp_port))).await?;
-        Ok(())
+        let store = self.store.open(true).await?;
+        run_collector(store, self.otlp_port).await
     }
 }
 
 impl QuerierCmd {
     async fn run(&self) -> anyhow::Result<()> {
-        let db = Database::new(&self.mongodb_uri, false).await;
-        run_query_server(db, self.query_port).await?;
+        let store = self.store.open(false).await?;
+        run_query_server(store, self.query_port).await?;
+        Ok(())
+    }
+}
+
+impl StandaloneCmd {
+    async fn run(&self) -> anyhow::Result<()> {
+        let store = self.store.open(true).await?;
+        tokio::try_join!(run_collector(store.clone(), self.otlp_port), async {
+            run_query_server(store, self.query_port).await?;
+            anyhow::Ok(())
+        })?;
         Ok(())
     }
 }
@@ -59,6 +89,7 @@ async fn main() -> anyhow::Result<()> {
     match cmd {
         Cmd::Collector(collector) => collector.run().await?,
         Cmd::Querier(querier) => querier.run().await?,
+        Cmd::Standalone(standalone) => standalone.run().await?,
     }
     Ok(())
 }
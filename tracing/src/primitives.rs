@@ -1,7 +1,19 @@
-use bson::oid::ObjectId;
 use bson::Binary;
+use bson::oid::ObjectId;
+use bson::spec::BinarySubtype;
 use serde::{Deserialize, Serialize};
 
+/// A request received by the collector, as handed to and returned by the
+/// storage backend.
+pub struct TraceChunk {
+    /// Earliest start time of the spans in the request, in unix nanoseconds.
+    pub min_time: u64,
+    /// Latest end time of the spans in the request, in unix nanoseconds.
+    pub max_time: u64,
+    /// Serialized `opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest`.
+    pub data: Vec<u8>,
+}
+
 // The storage format in MongoDB.
 #[derive(Serialize, Deserialize)]
 pub struct RawTrace {
@@ -12,3 +24,24 @@ pub struct RawTrace {
     /// Serialized `opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest`.
     pub data: Binary,
 }
+
+impl From<TraceChunk> for RawTrace {
+    fn from(chunk: TraceChunk) -> Self {
+        Self {
+            _id: None,
+            min_time: chunk.min_time,
+            max_time: chunk.max_time,
+            data: Binary { subtype: BinarySubtype::Generic, bytes: chunk.data },
+        }
+    }
+}
+
+impl From<RawTrace> for TraceChunk {
+    fn from(raw_trace: RawTrace) -> Self {
+        Self {
+            min_time: raw_trace.min_time,
+            max_time: raw_trace.max_time,
+            data: raw_trace.data.bytes,
+        }
+    }
+}
@@ -1,12 +1,10 @@
-use crate::db::Database;
-use crate::primitives::RawTrace;
+use crate::db::TraceStore;
+use crate::primitives::TraceChunk;
 use crate::profile::{Category, Profile, ProfileMeta, StringTableBuilder, Thread};
 use axum::body::Body;
 use axum::http::header;
 use axum::response::{IntoResponse, Response};
 use axum::{Json, Router, extract::State, http::StatusCode, routing::post};
-use bson::doc;
-use mongodb::options::FindOptions;
 use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
 use opentelemetry_proto::tonic::common::v1::AnyValue;
 use opentelemetry_proto::tonic::common::v1::any_value::Value;
@@ -52,8 +50,8 @@ impl From<QueryError> for Box<(dyn StdError + Send + Sync + 'static)> {
 
 /// Runs a server that allows trace data to be queried and returned as a
 /// Firefox profile.
-pub async fn run_query_server(db: Database, port: u16) -> std::io::Result<()> {
-    let state = Arc::new(QueryState { db });
+pub async fn run_query_server(store: Arc<dyn TraceStore>, port: u16) -> std::io::Result<()> {
+    let state = Arc::new(QueryState { store });
 
     let app = Router::new()
         .route("/raw_trace", post(raw_trace))
@@ -67,7 +65,7 @@ pub async fn run_query_server(db: Database, port: u16) -> std::io::Result<()> {
 }
 
 pub struct QueryState {
-    db: Database,
+    store: Arc<dyn TraceStore>,
 }
 
 #[derive(Deserialize)]
@@ -77,6 +75,14 @@ pub struct Query {
     filter: QueryFilter,
 }
 
+impl Query {
+    /// The queried interval in unix nanoseconds.
+    fn interval_unix_nano(&self) -> (u64, u64) {
+        let to_nanos = |unix_ms: i64| (unix_ms.max(0) as u64).saturating_mul(1000000);
+        (to_nanos(self.start_timestamp_unix_ms), to_nanos(self.end_timestamp_unix_ms))
+    }
+}
+
 #[derive(Deserialize)]
 pub struct QueryFilter {
     nodes: HashSet<String>,
@@ -129,15 +135,10 @@ async fn raw_trace(
 ) -> Result<impl IntoResponse, QueryError> {
     // TODO: Set a limit on the duration of the request interval.
 
-    let col = data.db.raw_traces();
-    let chunks = col
-        .find(
-            doc! {
-                "max_time": {"$gt": req.start_timestamp_unix_ms * 1000000},
-                "min_time": {"$lt": req.end_timestamp_unix_ms * 1000000},
-            },
-            Some(FindOptions::builder().batch_size(100).build()),
-        )
+    let (start_time, end_time) = req.interval_unix_nano();
+    let chunks = data
+        .store
+        .find(start_time, end_time)
         .await
         .map_err(|err| QueryError::new(err.to_string()))?;
 
@@ -169,10 +170,10 @@ async fn raw_trace(
 }
 
 fn raw_trace_to_json_str(
-    res: mongodb::error::Result<RawTrace>,
+    res: anyhow::Result<TraceChunk>,
     is_first_request: &mut bool,
 ) -> anyhow::Result<String> {
-    let chunk_bytes = res?.data.bytes;
+    let chunk_bytes = res?.data;
     let request = ExportTraceServiceRequest::decode(chunk_bytes.as_slice())?;
     let result = serde_json::to_string(&request)?;
 
@@ -189,21 +190,16 @@ async fn profile(
     Json(req): Json<Query>,
 ) -> Result<Json<Profile>, QueryError> {
     // TODO: Set a limit on the duration of the request interval.
-    let col = data.db.raw_traces();
-    let mut chunks = col
-        .find(
-            doc! {
-                "max_time": {"$gt": req.start_timestamp_unix_ms * 1000000},
-                "min_time": {"$lt": req.end_timestamp_unix_ms * 1000000},
-            },
-            Some(FindOptions::builder().batch_size(100).build()),
-        )
+    let (start_time, end_time) = req.interval_unix_nano();
+    let mut chunks = data
+        .store
+        .find(start_time, end_time)
         .await
         .map_err(|err| QueryError::new(err.to_string()))?;
 
     let mut result = QueryResult::default();
     while let Some(chunk) = chunks.next().await {
-        let chunk_bytes = chunk.map_err(|err| QueryError::new(err.to_string()))?.data.bytes;
+        let chunk_bytes = chunk.map_err(|err| QueryError::new(err.to_string()))?.data;
         let request = ExportTraceServiceRequest::decode(chunk_bytes.as_slice())
             .map_err(|err| QueryError::new(err.to_string()))?;
         for resource_span in request.resource_spans {
@@ -350,3 +346,23 @@ struct OneThreadResult {
 }
 
 // cspell:ignore Kvlist, stackwalk
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(start_timestamp_unix_ms: i64, end_timestamp_unix_ms: i64) -> Query {
+        Query {
+            start_timestamp_unix_ms,
+            end_timestamp_unix_ms,
+            filter: QueryFilter { nodes: HashSet::new(), threads: HashSet::new() },
+        }
+    }
+
+    #[test]
+    fn test_interval_unix_nano() {
+        assert_eq!(query(1, 2).interval_unix_nano(), (1000000, 2000000));
+        assert_eq!(query(-5, 0).interval_unix_nano(), (0, 0));
+        assert_eq!(query(0, i64::MAX).interval_unix_nano(), (0, u64::MAX));
+    }
+}